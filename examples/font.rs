@@ -1,7 +1,7 @@
 //! An example of drawing text. Writes to the user-provided target file.
 
 use image::{Rgb, RgbImage};
-use imageproc::drawing::{draw_text_mut, text_size};
+use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut, text_bounds, text_size};
 use rusttype::{Font, Scale};
 use std::env;
 use std::path::Path;
@@ -31,5 +31,20 @@ fn main() {
     let (w, h) = text_size(scale, &font, text);
     println!("Text size: {}x{}", w, h);
 
+    // Draw a label with a solid background
+    let label = "Label";
+    if let Some(bounds) = text_bounds(10, 100, scale, &font, label) {
+        draw_filled_rect_mut(&mut image, bounds, Rgb([255u8, 255u8, 255u8]));
+    }
+    draw_text_mut(
+        &mut image,
+        Rgb([255u8, 0u8, 0u8]),
+        10,
+        100,
+        scale,
+        &font,
+        label,
+    );
+
     let _ = image.save(path).unwrap();
 }
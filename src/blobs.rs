@@ -0,0 +1,431 @@
+//! A blob detector which finds connected regions that persist across a range of
+//! thresholds, equivalent to OpenCV's `SimpleBlobDetector`.
+
+use crate::contours::{find_contours, BorderType};
+use crate::geometry::{arc_length, convex_hull};
+use crate::point::Point;
+use image::GrayImage;
+use std::f64::consts::PI;
+
+/// Whether to detect blobs darker or lighter than their surroundings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlobPolarity {
+    /// Detect blobs darker than their surroundings.
+    Dark,
+    /// Detect blobs lighter than their surroundings.
+    Light,
+}
+
+/// Parameters for [`detect_blobs`](fn.detect_blobs.html).
+///
+/// Each of the optional filters is an inclusive range `(min, max)` of allowed values.
+/// Filters which are `None` are not applied.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BlobDetectorOptions {
+    /// The first threshold used to binarize the image.
+    pub min_threshold: u8,
+    /// Thresholds greater than this are not used.
+    pub max_threshold: u8,
+    /// The difference between consecutive thresholds. Must be non-zero.
+    pub threshold_step: u8,
+    /// The minimum number of thresholds at which a blob must be found to be returned.
+    pub min_repeatability: usize,
+    /// Blobs found at different thresholds are merged if their centres are closer than this.
+    pub min_distance_between_blobs: f32,
+    /// Whether to detect dark or light blobs.
+    pub polarity: BlobPolarity,
+    /// Allowed range of blob areas, in pixels.
+    pub area: Option<(f32, f32)>,
+    /// Allowed range of `4 * pi * area / perimeter^2`. This is 1 for a circle, and smaller
+    /// for less circular shapes.
+    pub circularity: Option<(f32, f32)>,
+    /// Allowed range of the ratio of the area of a blob to the area of its convex hull.
+    pub convexity: Option<(f32, f32)>,
+    /// Allowed range of the ratio of the minimum and maximum second moments of a blob.
+    /// This is 1 for a circle, and 0 for a line.
+    pub inertia_ratio: Option<(f32, f32)>,
+}
+
+impl Default for BlobDetectorOptions {
+    /// Parameters similar to the defaults used by OpenCV, with a looser convexity filter
+    /// to allow for the staircase contours of small digital discs.
+    fn default() -> BlobDetectorOptions {
+        BlobDetectorOptions {
+            min_threshold: 50,
+            max_threshold: 220,
+            threshold_step: 10,
+            min_repeatability: 2,
+            min_distance_between_blobs: 10.0,
+            polarity: BlobPolarity::Dark,
+            area: Some((25.0, 5000.0)),
+            circularity: None,
+            convexity: Some((0.9, f32::INFINITY)),
+            inertia_ratio: Some((0.1, f32::INFINITY)),
+        }
+    }
+}
+
+/// A blob found by [`detect_blobs`](fn.detect_blobs.html).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Blob {
+    /// x-coordinate of the centre of the blob.
+    pub x: f32,
+    /// y-coordinate of the centre of the blob.
+    pub y: f32,
+    /// The radius of the blob.
+    pub radius: f32,
+}
+
+/// Detects blobs which persist across a range of thresholds.
+///
+/// The image is binarized at each threshold from `options.min_threshold` to
+/// `options.max_threshold` in steps of `options.threshold_step`, keeping pixels less than
+/// the threshold when detecting dark blobs and pixels at least the threshold when detecting
+/// light blobs. The outer [contours](../contours/fn.find_contours.html) of the connected
+/// regions of each binary image are filtered by area, circularity, convexity and inertia
+/// ratio, and discarded if the pixel at their centre is not part of the region.
+///
+/// Regions from different thresholds whose centres lie within
+/// `options.min_distance_between_blobs` of each other, and within each other's radius, are
+/// grouped into a single blob. Groups found at fewer than `options.min_repeatability`
+/// thresholds are discarded. The centre of each blob is the mean of the centres of its
+/// regions, and its radius is the median of their radii, where the radius of a region is the
+/// median distance from its centre to its contour.
+///
+/// # Panics
+///
+/// If `options.threshold_step` is zero.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::{GrayImage, Luma};
+/// use imageproc::blobs::{detect_blobs, BlobDetectorOptions};
+///
+/// // A dark disc of radius 8 centred at (20, 15)
+/// let image = GrayImage::from_fn(40, 30, |x, y| {
+///     let (dx, dy) = (x as f32 - 20.0, y as f32 - 15.0);
+///     Luma([if dx * dx + dy * dy <= 64.0 { 20 } else { 240 }])
+/// });
+///
+/// let blobs = detect_blobs(&image, &BlobDetectorOptions::default());
+/// assert_eq!(blobs.len(), 1);
+/// assert!((blobs[0].x - 20.0).abs() < 0.5 && (blobs[0].y - 15.0).abs() < 0.5);
+/// assert!((blobs[0].radius - 8.0).abs() < 1.0);
+/// # }
+/// ```
+pub fn detect_blobs(image: &GrayImage, options: &BlobDetectorOptions) -> Vec<Blob> {
+    assert!(
+        options.threshold_step > 0,
+        "threshold_step must be non-zero"
+    );
+
+    // Each group contains the regions found at different thresholds for a single blob,
+    // sorted by radius
+    let mut groups: Vec<Vec<Blob>> = vec![];
+
+    let mut threshold = options.min_threshold as u32;
+    while threshold <= options.max_threshold as u32 {
+        let binary = GrayImage::from_fn(image.width(), image.height(), |x, y| {
+            let p = image.get_pixel(x, y)[0] as u32;
+            let foreground = match options.polarity {
+                BlobPolarity::Dark => p < threshold,
+                BlobPolarity::Light => p >= threshold,
+            };
+            image::Luma([foreground as u8 * 255])
+        });
+
+        for region in find_regions(&binary, options) {
+            let existing = groups.iter_mut().find(|group| {
+                let median = group[group.len() / 2];
+                let distance =
+                    ((median.x - region.x).powi(2) + (median.y - region.y).powi(2)).sqrt();
+                distance < options.min_distance_between_blobs
+                    && distance < median.radius
+                    && distance < region.radius
+            });
+            match existing {
+                Some(group) => {
+                    let position = group
+                        .iter()
+                        .position(|b| b.radius > region.radius)
+                        .unwrap_or(group.len());
+                    group.insert(position, region);
+                }
+                None => groups.push(vec![region]),
+            }
+        }
+
+        threshold += options.threshold_step as u32;
+    }
+
+    groups
+        .iter()
+        .filter(|group| group.len() >= options.min_repeatability)
+        .map(|group| {
+            let n = group.len() as f32;
+            Blob {
+                x: group.iter().map(|b| b.x).sum::<f32>() / n,
+                y: group.iter().map(|b| b.y).sum::<f32>() / n,
+                radius: group[group.len() / 2].radius,
+            }
+        })
+        .collect()
+}
+
+// Finds the outer contours of the foreground regions of a binary image which pass the
+// filters in options.
+fn find_regions(binary: &GrayImage, options: &BlobDetectorOptions) -> Vec<Blob> {
+    let in_range = |value: f64, range: Option<(f32, f32)>| match range {
+        Some((min, max)) => value >= min as f64 && value <= max as f64,
+        None => true,
+    };
+
+    let mut regions = vec![];
+    for contour in find_contours::<i32>(binary) {
+        if contour.border_type != BorderType::Outer {
+            continue;
+        }
+        let points = &contour.points;
+        let moments = PolygonMoments::new(points);
+        if moments.m00 <= 0.0 {
+            continue;
+        }
+        let area = moments.m00;
+
+        if !in_range(area, options.area) {
+            continue;
+        }
+        if options.circularity.is_some() {
+            let perimeter = arc_length(points, true);
+            if !in_range(
+                4.0 * PI * area / (perimeter * perimeter),
+                options.circularity,
+            ) {
+                continue;
+            }
+        }
+        if !in_range(moments.inertia_ratio(), options.inertia_ratio) {
+            continue;
+        }
+        if options.convexity.is_some() {
+            let hull_area = PolygonMoments::new(&convex_hull(points)).m00;
+            if hull_area <= 0.0 || !in_range(area / hull_area, options.convexity) {
+                continue;
+            }
+        }
+
+        let (cx, cy) = (moments.m10 / moments.m00, moments.m01 / moments.m00);
+        let (px, py) = (cx.round() as u32, cy.round() as u32);
+        if px >= binary.width() || py >= binary.height() || binary.get_pixel(px, py)[0] == 0 {
+            continue;
+        }
+
+        let mut distances: Vec<f64> = points
+            .iter()
+            .map(|p| ((p.x as f64 - cx).powi(2) + (p.y as f64 - cy).powi(2)).sqrt())
+            .collect();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let radius = (distances[(distances.len() - 1) / 2] + distances[distances.len() / 2]) / 2.0;
+
+        regions.push(Blob {
+            x: cx as f32,
+            y: cy as f32,
+            radius: radius as f32,
+        });
+    }
+    regions
+}
+
+// Spatial moments of the region enclosed by a polygon, up to second order.
+struct PolygonMoments {
+    m00: f64,
+    m10: f64,
+    m01: f64,
+    m20: f64,
+    m11: f64,
+    m02: f64,
+}
+
+impl PolygonMoments {
+    // Computes moments using Green's theorem. The moments are normalized to be
+    // non-negative regardless of the orientation of the polygon.
+    fn new(points: &[Point<i32>]) -> PolygonMoments {
+        let mut m = PolygonMoments {
+            m00: 0.0,
+            m10: 0.0,
+            m01: 0.0,
+            m20: 0.0,
+            m11: 0.0,
+            m02: 0.0,
+        };
+        for i in 0..points.len() {
+            let (xi, yi) = (points[i].x as f64, points[i].y as f64);
+            let next = points[(i + 1) % points.len()];
+            let (xj, yj) = (next.x as f64, next.y as f64);
+            let a = xi * yj - xj * yi;
+            m.m00 += a / 2.0;
+            m.m10 += a * (xi + xj) / 6.0;
+            m.m01 += a * (yi + yj) / 6.0;
+            m.m20 += a * (xi * xi + xi * xj + xj * xj) / 12.0;
+            m.m11 += a * (xi * yj + 2.0 * xi * yi + 2.0 * xj * yj + xj * yi) / 24.0;
+            m.m02 += a * (yi * yi + yi * yj + yj * yj) / 12.0;
+        }
+        if m.m00 < 0.0 {
+            for v in [
+                &mut m.m00, &mut m.m10, &mut m.m01, &mut m.m20, &mut m.m11, &mut m.m02,
+            ]
+            .iter_mut()
+            {
+                **v = -**v;
+            }
+        }
+        m
+    }
+
+    // The ratio of the smallest and largest eigenvalues of the covariance matrix of
+    // the region.
+    fn inertia_ratio(&self) -> f64 {
+        let (cx, cy) = (self.m10 / self.m00, self.m01 / self.m00);
+        let mu20 = self.m20 - cx * self.m10;
+        let mu02 = self.m02 - cy * self.m01;
+        let mu11 = self.m11 - cx * self.m01;
+
+        let denominator = ((mu20 - mu02).powi(2) + 4.0 * mu11 * mu11).sqrt();
+        if denominator <= 1e-2 {
+            return 1.0;
+        }
+        let cos = (mu20 - mu02) / denominator;
+        let sin = 2.0 * mu11 / denominator;
+        let min = 0.5 * (mu20 + mu02) - 0.5 * (mu20 - mu02) * cos - mu11 * sin;
+        let max = 0.5 * (mu20 + mu02) + 0.5 * (mu20 - mu02) * cos + mu11 * sin;
+        if max <= 0.0 {
+            1.0
+        } else {
+            min / max
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+    use test::{black_box, Bencher};
+
+    fn disc(image: &mut GrayImage, cx: i32, cy: i32, radius: i32, value: u8) {
+        for (x, y, p) in image.enumerate_pixels_mut() {
+            let (dx, dy) = (x as i32 - cx, y as i32 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                *p = Luma([value]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_polygon_moments() {
+        // A 4x2 axis-aligned rectangle, listed clockwise in image coordinates
+        let points = [
+            Point::new(1, 1),
+            Point::new(5, 1),
+            Point::new(5, 3),
+            Point::new(1, 3),
+        ];
+        let m = PolygonMoments::new(&points);
+        assert!((m.m00 - 8.0).abs() < 1e-9);
+        assert!((m.m10 / m.m00 - 3.0).abs() < 1e-9);
+        assert!((m.m01 / m.m00 - 2.0).abs() < 1e-9);
+        assert!((m.inertia_ratio() - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_detect_blobs_finds_dark_discs() {
+        let mut image = GrayImage::from_pixel(80, 50, Luma([230u8]));
+        disc(&mut image, 20, 25, 10, 30);
+        disc(&mut image, 55, 20, 6, 100);
+
+        let mut blobs = detect_blobs(&image, &BlobDetectorOptions::default());
+        blobs.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+
+        assert_eq!(blobs.len(), 2);
+        assert!((blobs[0].x - 20.0).abs() < 0.5 && (blobs[0].y - 25.0).abs() < 0.5);
+        assert!((blobs[0].radius - 10.0).abs() < 1.0);
+        assert!((blobs[1].x - 55.0).abs() < 0.5 && (blobs[1].y - 20.0).abs() < 0.5);
+        assert!((blobs[1].radius - 6.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_detect_blobs_polarity() {
+        let mut image = GrayImage::from_pixel(40, 40, Luma([20u8]));
+        disc(&mut image, 20, 20, 8, 200);
+
+        assert!(detect_blobs(&image, &BlobDetectorOptions::default()).is_empty());
+
+        let options = BlobDetectorOptions {
+            polarity: BlobPolarity::Light,
+            ..BlobDetectorOptions::default()
+        };
+        assert_eq!(detect_blobs(&image, &options).len(), 1);
+    }
+
+    #[test]
+    fn test_detect_blobs_filters_by_inertia_and_area() {
+        // A thin dark bar, a tiny dark spot and a dark disc
+        let mut image = GrayImage::from_pixel(90, 40, Luma([230u8]));
+        for y in 18..22 {
+            for x in 5..35 {
+                image.put_pixel(x, y, Luma([30]));
+            }
+        }
+        disc(&mut image, 45, 20, 1, 30);
+        disc(&mut image, 70, 20, 9, 30);
+
+        let blobs = detect_blobs(&image, &BlobDetectorOptions::default());
+        assert_eq!(blobs.len(), 1);
+        assert!((blobs[0].x - 70.0).abs() < 0.5);
+
+        let options = BlobDetectorOptions {
+            area: None,
+            inertia_ratio: None,
+            ..BlobDetectorOptions::default()
+        };
+        assert_eq!(detect_blobs(&image, &options).len(), 3);
+    }
+
+    #[test]
+    fn test_detect_blobs_min_repeatability() {
+        // A disc which is only below the first threshold
+        let mut image = GrayImage::from_pixel(40, 40, Luma([230u8]));
+        disc(&mut image, 20, 20, 8, 55);
+
+        let options = BlobDetectorOptions {
+            min_threshold: 50,
+            max_threshold: 60,
+            threshold_step: 10,
+            ..BlobDetectorOptions::default()
+        };
+        assert_eq!(detect_blobs(&image, &options).len(), 0);
+
+        let options = BlobDetectorOptions {
+            min_repeatability: 1,
+            ..options
+        };
+        assert_eq!(detect_blobs(&image, &options).len(), 1);
+    }
+
+    #[bench]
+    fn bench_detect_blobs(b: &mut Bencher) {
+        let mut image = GrayImage::from_pixel(200, 200, Luma([230u8]));
+        for i in 0..5 {
+            for j in 0..5 {
+                disc(&mut image, 20 + 40 * i, 20 + 40 * j, 5 + i + j, 30);
+            }
+        }
+        b.iter(|| {
+            let blobs = detect_blobs(&image, &BlobDetectorOptions::default());
+            black_box(blobs);
+        });
+    }
+}
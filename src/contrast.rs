@@ -1,12 +1,16 @@
 //! Functions for manipulating the contrast of images.
 
-use crate::definitions::{HasBlack, HasWhite};
-use crate::integral_image::{integral_image, sum_image_pixels};
-use crate::stats::{cumulative_histogram, histogram};
-use image::{GrayImage, ImageBuffer, Luma};
+use crate::definitions::{HasBlack, HasWhite, Image, MaybeSync};
+use crate::filter::{gaussian_blur_f32, separable_filter_equal};
+use crate::integral_image::{integral_image, local_mean_variance, sum_image_pixels};
+use crate::map::{map_subpixels, map_subpixels_parallel, ChannelMap, WithChannel};
+use crate::rect::Rect;
+use crate::stats::{cumulative_histogram, histogram, Histogram};
+use image::{GrayImage, ImageBuffer, Luma, Pixel, Primitive, RgbImage};
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 use std::cmp::{max, min};
+use std::convert::TryInto;
 
 /// Applies an adaptive threshold to an image.
 ///
@@ -15,7 +19,7 @@ use std::cmp::{max, min};
 /// as the threshold then it will have a value of 255 in the output image, otherwise 0.
 pub fn adaptive_threshold(image: &GrayImage, block_radius: u32) -> GrayImage {
     assert!(block_radius > 0);
-    let integral = integral_image::<_, u32>(image);
+    let integral = integral_image::<_, u64>(image);
     let mut out = ImageBuffer::from_pixel(image.width(), image.height(), Luma::black());
 
     for y in 0..image.height() {
@@ -33,7 +37,7 @@ pub fn adaptive_threshold(image: &GrayImage, block_radius: u32) -> GrayImage {
 
             // Number of pixels in the block, adjusted for edge cases.
             let w = (y_high - y_low + 1) * (x_high - x_low + 1);
-            let mean = sum_image_pixels(&integral, x_low, y_low, x_high, y_high)[0] / w;
+            let mean = sum_image_pixels(&integral, x_low, y_low, x_high, y_high)[0] / w as u64;
 
             if current_pixel[0] as u32 >= mean as u32 {
                 out.put_pixel(x, y, Luma::white());
@@ -43,6 +47,187 @@ pub fn adaptive_threshold(image: &GrayImage, block_radius: u32) -> GrayImage {
     out
 }
 
+/// How the local threshold is computed in
+/// [`adaptive_threshold_with_method`](fn.adaptive_threshold_with_method.html).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AdaptiveThresholdMethod {
+    /// The unweighted mean of the intensities in the block around each pixel.
+    /// Blocks are truncated at the image boundaries.
+    Mean,
+    /// A Gaussian-weighted mean of the intensities in the block around each pixel,
+    /// with the image padded by continuity. The standard deviation of the Gaussian is
+    /// chosen based on the block size in the same way as OpenCV's `adaptiveThreshold`.
+    Gaussian,
+}
+
+/// Applies an adaptive threshold to an image, using a local mean of the type given by `method`.
+///
+/// Each pixel is compared with the local mean of the pixels in the (2 * `block_radius` + 1)
+/// square block centered on it, minus the constant `c`. If the pixel is at least as bright as
+/// this threshold then it will have a value of 255 in the output image, otherwise 0.
+///
+/// Positive values of `c` are useful for binarizing dark text on a light but unevenly lit
+/// background, as they prevent noise in flat background regions from being sent to 0.
+/// Using `AdaptiveThresholdMethod::Mean` and a `c` of 0 gives similar results to
+/// [`adaptive_threshold`](fn.adaptive_threshold.html), but without rounding the local means.
+///
+/// # Panics
+/// If `block_radius` is 0.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::{adaptive_threshold_with_method, AdaptiveThresholdMethod};
+///
+/// // A dark stroke on a background whose brightness varies from left to right
+/// let image = gray_image!(
+///     100, 110, 120, 130, 140, 150;
+///     100, 110,  40, 130, 140, 150;
+///     100, 110, 120, 130, 140,  70;
+///     100, 110, 120, 130, 140, 150);
+///
+/// let expected = gray_image!(
+///     255, 255, 255, 255, 255, 255;
+///     255, 255,   0, 255, 255, 255;
+///     255, 255, 255, 255, 255,   0;
+///     255, 255, 255, 255, 255, 255);
+///
+/// let binary = adaptive_threshold_with_method(&image, 1, 10.0, AdaptiveThresholdMethod::Mean);
+/// assert_pixels_eq!(binary, expected);
+/// # }
+/// ```
+pub fn adaptive_threshold_with_method(
+    image: &GrayImage,
+    block_radius: u32,
+    c: f32,
+    method: AdaptiveThresholdMethod,
+) -> GrayImage {
+    assert!(block_radius > 0);
+    let means = match method {
+        AdaptiveThresholdMethod::Mean => local_mean_variance(image, block_radius).0,
+        AdaptiveThresholdMethod::Gaussian => {
+            let sigma = 0.3 * (block_radius as f32 - 1.0) + 0.8;
+            let mut kernel: Vec<f32> = (0..2 * block_radius + 1)
+                .map(|i| {
+                    let d = i as f32 - block_radius as f32;
+                    (-d * d / (2.0 * sigma * sigma)).exp()
+                })
+                .collect();
+            let total: f32 = kernel.iter().sum();
+            for k in kernel.iter_mut() {
+                *k /= total;
+            }
+            let image = map_subpixels(image, |p| p as f32);
+            separable_filter_equal(&image, &kernel)
+        }
+    };
+
+    let mut out = ImageBuffer::from_pixel(image.width(), image.height(), Luma::black());
+    for ((o, p), m) in out.iter_mut().zip(image.iter()).zip(means.iter()) {
+        // Allow for rounding errors when computing the means of flat regions
+        if *p as f32 + 1e-3 >= m - c {
+            *o = 255;
+        }
+    }
+    out
+}
+
+/// Binarizes an image using [Niblack's method].
+///
+/// Each pixel is compared with the threshold `m + k * s`, where `m` and `s` are the mean
+/// and standard deviation of the intensities in the (2 * `block_radius` + 1) square block
+/// centered on it. Blocks are truncated at the image boundaries. Pixels brighter than the
+/// threshold have a value of 255 in the output image, and all others 0.
+///
+/// Dark text on a light background is typically binarized using a negative `k`, e.g. -0.2.
+/// Note that as the threshold in flat regions is equal to the mean, Niblack's method
+/// produces noisy output in background regions. [`sauvola_threshold`](fn.sauvola_threshold.html)
+/// avoids this.
+///
+/// Local means and variances are computed from integral images, so the running time
+/// does not depend on `block_radius`.
+///
+/// [Niblack's method]: https://en.wikipedia.org/wiki/Thresholding_(image_processing)
+///
+/// # Panics
+/// If `block_radius` is 0.
+pub fn niblack_threshold(image: &GrayImage, block_radius: u32, k: f32) -> GrayImage {
+    local_statistics_threshold(image, block_radius, |mean, std_dev| mean + k * std_dev)
+}
+
+/// Binarizes an image using [Sauvola's method].
+///
+/// Each pixel is compared with the threshold `m * (1 + k * (s / r - 1))`, where `m` and `s`
+/// are the mean and standard deviation of the intensities in the (2 * `block_radius` + 1)
+/// square block centered on it. Blocks are truncated at the image boundaries. Pixels brighter
+/// than the threshold have a value of 255 in the output image, and all others 0.
+///
+/// `r` is the dynamic range of the standard deviation, and is usually 128 for 8bpp images.
+/// `k` is typically between 0.2 and 0.5. Sauvola's method adapts the threshold to the local
+/// contrast, so that flat background regions are sent to 255, which makes it well suited to
+/// binarizing documents.
+///
+/// Local means and variances are computed from integral images, so the running time
+/// does not depend on `block_radius`.
+///
+/// [Sauvola's method]: https://doi.org/10.1016/S0031-3203(99)00055-2
+///
+/// # Panics
+/// If `block_radius` is 0 or `r` is not positive.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::sauvola_threshold;
+///
+/// let image = gray_image!(
+///     200, 200, 200, 200;
+///     200,  50, 200, 200;
+///     200, 200, 200, 200;
+///     200, 200,  50, 200);
+///
+/// let expected = gray_image!(
+///     255, 255, 255, 255;
+///     255,   0, 255, 255;
+///     255, 255, 255, 255;
+///     255, 255,   0, 255);
+///
+/// assert_pixels_eq!(sauvola_threshold(&image, 1, 0.3, 128.0), expected);
+/// # }
+/// ```
+pub fn sauvola_threshold(image: &GrayImage, block_radius: u32, k: f32, r: f32) -> GrayImage {
+    assert!(r > 0.0, "r must be positive");
+    local_statistics_threshold(image, block_radius, |mean, std_dev| {
+        mean * (1.0 + k * (std_dev / r - 1.0))
+    })
+}
+
+// Binarizes an image by comparing each pixel with a threshold computed from the mean
+// and standard deviation of the block around it.
+fn local_statistics_threshold<F>(image: &GrayImage, block_radius: u32, threshold: F) -> GrayImage
+where
+    F: Fn(f32, f32) -> f32,
+{
+    assert!(block_radius > 0);
+    let (means, variances) = local_mean_variance(image, block_radius);
+
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let mean = means.get_pixel(x, y)[0];
+        let std_dev = variances.get_pixel(x, y)[0].sqrt();
+        if image.get_pixel(x, y)[0] as f32 > threshold(mean, std_dev) {
+            Luma::white()
+        } else {
+            Luma::black()
+        }
+    })
+}
+
 /// Returns the [Otsu threshold level] of an 8bpp image.
 ///
 /// [Otsu threshold level]: https://en.wikipedia.org/wiki/Otsu%27s_method
@@ -99,10 +284,13 @@ pub fn otsu_level(image: &GrayImage) -> u8 {
     best_threshold
 }
 
-/// Returns a binarized image from an input 8bpp grayscale image
+/// Returns a binarized image from an input grayscale image
 /// obtained by applying the given threshold. Pixels with intensity
 /// equal to the threshold are assigned to the background.
 ///
+/// Foreground pixels are set to white and background pixels to black. For `f32`
+/// images white is `1.0`.
+///
 /// # Examples
 /// ```
 /// # extern crate image;
@@ -122,7 +310,11 @@ pub fn otsu_level(image: &GrayImage) -> u8 {
 /// assert_pixels_eq!(threshold(&image, 50), thresholded);
 /// # }
 /// ```
-pub fn threshold(image: &GrayImage, thresh: u8) -> GrayImage {
+pub fn threshold<T>(image: &Image<Luma<T>>, thresh: T) -> Image<Luma<T>>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
     let mut out = image.clone();
     threshold_mut(&mut out, thresh);
     out
@@ -153,10 +345,282 @@ pub fn threshold(image: &GrayImage, thresh: u8) -> GrayImage {
 /// assert_pixels_eq!(image, thresholded);
 /// # }
 /// ```
-pub fn threshold_mut(image: &mut GrayImage, thresh: u8) {
+pub fn threshold_mut<T>(image: &mut Image<Luma<T>>, thresh: T)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    let (black, white) = (Luma::<T>::black()[0], Luma::<T>::white()[0]);
     for p in image.iter_mut() {
-        *p = if *p <= thresh { 0 } else { 255 };
+        *p = if *p <= thresh { black } else { white };
+    }
+}
+
+/// Returns the threshold level of an 8bpp image chosen using the [triangle method].
+///
+/// A line is drawn from the peak of the histogram to the end of its longer tail, and the
+/// threshold is the intensity whose histogram count is furthest below this line. This works
+/// well for images with a single dominant background peak and a small, spread out foreground,
+/// such as fluorescence microscopy images.
+///
+/// As with [`otsu_level`](fn.otsu_level.html), the returned level can be passed directly
+/// to [`threshold`](fn.threshold.html).
+///
+/// [triangle method]: https://doi.org/10.1177/25.7.70454
+pub fn triangle_level(image: &GrayImage) -> u8 {
+    let hist = Histogram::from_channel(image, 0, 256);
+    let counts = hist.counts();
+
+    let first = match counts.iter().position(|&h| h > 0) {
+        Some(i) => i,
+        None => return 0,
+    };
+    let last = counts.iter().rposition(|&h| h > 0).unwrap();
+    // The first bin with the maximum count
+    let peak = counts
+        .iter()
+        .enumerate()
+        .fold(0, |best, (i, &h)| if h > counts[best] { i } else { best });
+
+    // Walk from the peak towards the end of the longer tail, measuring the
+    // (unnormalised) distance of each bin below the line from the peak to
+    // the tail end, which is treated as having a count of zero.
+    let (tail, flipped) = if last - peak > peak - first {
+        (last, true)
+    } else {
+        (first, false)
+    };
+    let width = if flipped { tail - peak } else { peak - tail };
+    if width == 0 {
+        return peak as u8;
+    }
+    let peak_height = counts[peak] as f64;
+
+    let mut best_distance = f64::NEG_INFINITY;
+    let mut level = tail;
+    for offset in 0..width {
+        let bin = if flipped {
+            tail - offset
+        } else {
+            tail + offset
+        };
+        let distance = peak_height * offset as f64 - width as f64 * counts[bin] as f64;
+        if distance > best_distance {
+            best_distance = distance;
+            level = bin;
+        }
+    }
+
+    // Pixels with intensity less than or equal to the threshold are assigned
+    // to the background, so step back by one when the tail is above the peak.
+    if flipped {
+        (level - 1) as u8
+    } else {
+        level as u8
+    }
+}
+
+/// Returns the threshold level of an 8bpp image chosen using the [isodata method], also
+/// known as the iterative intermeans or Ridler-Calvard method.
+///
+/// Starting from the mean intensity, the threshold is repeatedly replaced by the average of
+/// the mean intensities of the pixels at or below it and of the pixels above it, until this
+/// no longer changes. Pixels with intensity equal to the returned level should be assigned
+/// to the background, as in [`threshold`](fn.threshold.html).
+///
+/// [isodata method]: https://doi.org/10.1109/TSMC.1978.4310039
+pub fn isodata_level(image: &GrayImage) -> u8 {
+    let hist = Histogram::from_channel(image, 0, 256);
+    let mut counts = [0f64; 257];
+    let mut sums = [0f64; 257];
+    for (i, &h) in hist.counts().iter().enumerate() {
+        counts[i + 1] = counts[i] + h as f64;
+        sums[i + 1] = sums[i] + i as f64 * h as f64;
+    }
+    if counts[256] == 0.0 {
+        return 0;
+    }
+
+    let mut level = (sums[256] / counts[256]) as usize;
+    // The sequence of levels usually converges in a handful of iterations, but can
+    // cycle between two values. In this case we return the lower one.
+    let mut previous = None;
+    for _ in 0..256 {
+        let (below, above) = (counts[level + 1], counts[256] - counts[level + 1]);
+        if below == 0.0 || above == 0.0 {
+            break;
+        }
+        let below_mean = sums[level + 1] / below;
+        let above_mean = (sums[256] - sums[level + 1]) / above;
+        let next = ((below_mean + above_mean) / 2.0) as usize;
+        if next == level {
+            break;
+        }
+        if previous == Some(next) {
+            level = min(level, next);
+            break;
+        }
+        previous = Some(level);
+        level = next;
+    }
+    level as u8
+}
+
+/// Binarizes an 8bpp grayscale image using its [Otsu threshold level],
+/// returning both the threshold and the binarized image.
+///
+/// This is equivalent to calling [`threshold`](fn.threshold.html) with the
+/// output of [`otsu_level`](fn.otsu_level.html).
+///
+/// [Otsu threshold level]: https://en.wikipedia.org/wiki/Otsu%27s_method
+pub fn otsu_threshold(image: &GrayImage) -> (u8, GrayImage) {
+    let level = otsu_level(image);
+    (level, threshold(image, level))
+}
+
+/// Returns the `num_classes - 1` thresholds which split the histogram of an 8bpp image
+/// into `num_classes` classes with maximal between-class variance, in increasing order.
+///
+/// This is the multi-level generalisation of [`otsu_level`](fn.otsu_level.html).
+/// Class `k` contains the intensities greater than `thresholds[k - 1]` and less than or equal
+/// to `thresholds[k]`. The optimal thresholds are found exactly by dynamic programming,
+/// in time proportional to `num_classes * 256^2`.
+///
+/// # Panics
+/// If `num_classes` is less than 2 or greater than 256.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::multi_otsu_levels;
+///
+/// let image = gray_image!(
+///     10, 12, 100, 102, 200, 202;
+///     11, 13, 101, 103, 201, 203);
+///
+/// assert_eq!(multi_otsu_levels(&image, 3), vec![13, 103]);
+/// # }
+/// ```
+pub fn multi_otsu_levels(image: &GrayImage, num_classes: usize) -> Vec<u8> {
+    assert!(
+        (2..=256).contains(&num_classes),
+        "num_classes must be between 2 and 256"
+    );
+    let hist = Histogram::from_channel(image, 0, 256);
+
+    // Prefix sums of the counts and intensity sums, so that the bins in the
+    // range [a, b] have count weights[b + 1] - weights[a].
+    let mut weights = [0f64; 257];
+    let mut sums = [0f64; 257];
+    for (i, &h) in hist.counts().iter().enumerate() {
+        weights[i + 1] = weights[i] + h as f64;
+        sums[i + 1] = sums[i] + i as f64 * h as f64;
+    }
+
+    // Maximising the between-class variance is equivalent to maximising
+    // the sum over all classes of (class sum)^2 / (class weight).
+    let score = |a: usize, b: usize| {
+        let w = weights[b + 1] - weights[a];
+        let s = sums[b + 1] - sums[a];
+        if w == 0.0 {
+            0.0
+        } else {
+            s * s / w
+        }
+    };
+
+    // best[k][b] is the maximum score when splitting the bins [0, b] into
+    // k + 1 non-empty ranges, and starts[k][b] is the first bin of the last range.
+    let mut best = vec![[0f64; 256]; num_classes];
+    let mut starts = vec![[0usize; 256]; num_classes];
+    for b in 0..256 {
+        best[0][b] = score(0, b);
+    }
+    for k in 1..num_classes {
+        for b in k..256 {
+            let mut best_score = f64::NEG_INFINITY;
+            for a in k..=b {
+                let candidate = best[k - 1][a - 1] + score(a, b);
+                if candidate > best_score {
+                    best_score = candidate;
+                    starts[k][b] = a;
+                }
+            }
+            best[k][b] = best_score;
+        }
+    }
+
+    let mut levels = vec![0u8; num_classes - 1];
+    let mut end = 255;
+    for k in (1..num_classes).rev() {
+        let start = starts[k][end];
+        levels[k - 1] = (start - 1) as u8;
+        end = start - 1;
+    }
+    levels
+}
+
+/// Quantizes an 8bpp grayscale image using the given increasing thresholds.
+///
+/// A pixel in class `k` (i.e. with intensity greater than `thresholds[k - 1]` and less than
+/// or equal to `thresholds[k]`) is assigned the intensity `255 * k / thresholds.len()`,
+/// rounded to the nearest integer. In particular, using a single threshold is equivalent
+/// to [`threshold`](fn.threshold.html).
+///
+/// # Panics
+/// If `thresholds` is empty or not strictly increasing.
+pub fn threshold_multi(image: &GrayImage, thresholds: &[u8]) -> GrayImage {
+    assert!(!thresholds.is_empty(), "thresholds must be non-empty");
+    assert!(
+        thresholds.windows(2).all(|w| w[0] < w[1]),
+        "thresholds must be strictly increasing"
+    );
+    let num_levels = thresholds.len() as u32;
+    let mut lut = [0u8; 256];
+    for (i, l) in lut.iter_mut().enumerate() {
+        let class = thresholds.iter().filter(|&&t| i > t as usize).count() as u32;
+        *l = ((255 * class + num_levels / 2) / num_levels) as u8;
     }
+    apply_lut(image, &lut)
+}
+
+/// Quantizes an 8bpp grayscale image into `num_classes` intensity levels using
+/// [`multi_otsu_levels`](fn.multi_otsu_levels.html), returning both the thresholds
+/// and the quantized image.
+///
+/// See [`threshold_multi`](fn.threshold_multi.html) for the output intensity of each class.
+///
+/// # Panics
+/// If `num_classes` is less than 2 or greater than 256.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::multi_otsu_threshold;
+///
+/// let image = gray_image!(
+///     10, 12, 100, 102, 200, 202;
+///     11, 13, 101, 103, 201, 203);
+///
+/// let expected = gray_image!(
+///     0, 0, 128, 128, 255, 255;
+///     0, 0, 128, 128, 255, 255);
+///
+/// let (levels, quantized) = multi_otsu_threshold(&image, 3);
+/// assert_eq!(levels, vec![13, 103]);
+/// assert_pixels_eq!(quantized, expected);
+/// # }
+/// ```
+pub fn multi_otsu_threshold(image: &GrayImage, num_classes: usize) -> (Vec<u8>, GrayImage) {
+    let levels = multi_otsu_levels(image, num_classes);
+    let quantized = threshold_multi(image, &levels);
+    (levels, quantized)
 }
 
 /// Equalises the histogram of an 8bpp grayscale image in place. See also
@@ -190,6 +654,297 @@ pub fn equalize_histogram(image: &GrayImage) -> GrayImage {
     out
 }
 
+/// Equalises the histogram of the luminance of an RGB image in place.
+///
+/// Equalising the red, green and blue channels independently changes the hue of most
+/// pixels. Instead, this function converts each pixel to YCbCr, equalises the histogram
+/// of the luma channel Y as in [`equalize_histogram_mut`](fn.equalize_histogram_mut.html),
+/// and converts back leaving the chroma channels Cb and Cr unchanged. Output channels are
+/// clamped to `[0, 255]`, so very saturated pixels may still see small hue shifts.
+///
+/// Luma is computed using the ITU-R BT.601 weights, as in JPEG.
+pub fn equalize_histogram_rgb_mut(image: &mut RgbImage) {
+    let luma = |p: &[u8]| 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32;
+
+    let mut hist = [0u32; 256];
+    for p in image.pixels() {
+        hist[(luma(&p.0) + 0.5) as usize] += 1;
+    }
+    let total = image.width() * image.height();
+    let lut = equalization_lut(&hist, total);
+
+    for p in image.pixels_mut() {
+        let y = luma(&p.0);
+        // Converting from RGB to YCbCr and back after changing Y by delta is equivalent
+        // to adding delta to each of R, G and B.
+        let delta = lut[(y + 0.5) as usize] as f32 - y;
+        for c in p.0.iter_mut() {
+            *c = (*c as f32 + delta + 0.5).clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Equalises the histogram of the luminance of an RGB image.
+///
+/// See the [`equalize_histogram_rgb_mut`](fn.equalize_histogram_rgb_mut.html) documentation
+/// for more.
+pub fn equalize_histogram_rgb(image: &RgbImage) -> RgbImage {
+    let mut out = image.clone();
+    equalize_histogram_rgb_mut(&mut out);
+    out
+}
+
+/// Corrects the white balance of an RGB image using the gray world assumption.
+///
+/// Each channel is scaled so that its mean is equal to the mean over all channels, i.e.
+/// so that the average color of the image is a neutral gray. Channel values are clamped
+/// to `[0, 255]` after scaling. Channels whose mean is zero are left unchanged.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::white_balance_gray_world;
+///
+/// // An image with a blue cast
+/// let image = rgb_image!(
+///     [40, 60, 100], [80, 120, 200]);
+///
+/// let expected = rgb_image!(
+///     [67, 67, 67], [133, 133, 133]);
+///
+/// assert_pixels_eq!(white_balance_gray_world(&image), expected);
+/// # }
+/// ```
+pub fn white_balance_gray_world(image: &RgbImage) -> RgbImage {
+    let means = channel_means(image, 0, 0, image.width(), image.height());
+    apply_channel_gains(image, neutral_gains(means))
+}
+
+/// Corrects the white balance of an RGB image using the white patch (or max-RGB) assumption.
+///
+/// The `percentile`th percentile value of each channel is assumed to be the response to a
+/// white surface, and each channel is scaled so that this value is sent to 255. Using the
+/// maximum (a `percentile` of 100) is sensitive to noise and specular highlights, so values
+/// such as 99 are more robust. Channel values are clamped to `[0, 255]` after scaling.
+/// Channels whose percentile value is zero are left unchanged.
+///
+/// # Panics
+/// If `percentile` is not in the range `[0, 100]`.
+pub fn white_balance_white_patch(image: &RgbImage, percentile: f32) -> RgbImage {
+    let hists = Histogram::per_channel(image, 256);
+    let mut gains = [1.0; 3];
+    for (gain, hist) in gains.iter_mut().zip(&hists) {
+        if let Some(white) = hist.percentile(percentile) {
+            if white > 0 {
+                *gain = 255.0 / white as f32;
+            }
+        }
+    }
+    apply_channel_gains(image, gains)
+}
+
+/// Corrects the white balance of an RGB image using a region known to be a neutral gray
+/// or white, e.g. a gray card.
+///
+/// Each channel is scaled so that the mean color of the pixels in `region` becomes a
+/// neutral gray with the same mean intensity. Channel values are clamped to `[0, 255]`
+/// after scaling. Channels whose mean in the region is zero are left unchanged.
+///
+/// # Panics
+/// If `region` does not intersect the image.
+pub fn white_balance_reference(image: &RgbImage, region: Rect) -> RgbImage {
+    let bounds = Rect::at(0, 0).of_size(image.width().max(1), image.height().max(1));
+    let region = region
+        .intersect(bounds)
+        .filter(|_| image.width() > 0 && image.height() > 0)
+        .expect("region must intersect the image");
+    let means = channel_means(
+        image,
+        region.left() as u32,
+        region.top() as u32,
+        region.width(),
+        region.height(),
+    );
+    apply_channel_gains(image, neutral_gains(means))
+}
+
+// The mean of each channel over the given region.
+fn channel_means(image: &RgbImage, left: u32, top: u32, width: u32, height: u32) -> [f64; 3] {
+    let mut sums = [0f64; 3];
+    for y in top..top + height {
+        for x in left..left + width {
+            for (s, c) in sums.iter_mut().zip(image.get_pixel(x, y).0.iter()) {
+                *s += *c as f64;
+            }
+        }
+    }
+    let count = (width as f64 * height as f64).max(1.0);
+    sums.map(|s| s / count)
+}
+
+// The gains which map the given channel means to their average.
+fn neutral_gains(means: [f64; 3]) -> [f32; 3] {
+    let gray = means.iter().sum::<f64>() / 3.0;
+    means.map(|m| if m > 0.0 { (gray / m) as f32 } else { 1.0 })
+}
+
+fn apply_channel_gains(image: &RgbImage, gains: [f32; 3]) -> RgbImage {
+    let mut out = image.clone();
+    for p in out.pixels_mut() {
+        for (c, g) in p.0.iter_mut().zip(gains.iter()) {
+            *c = (*c as f32 * g + 0.5).min(255.0) as u8;
+        }
+    }
+    out
+}
+
+/// Applies [contrast limited adaptive histogram equalization] (CLAHE) to an 8bpp
+/// grayscale image in place.
+///
+/// The image is divided into a grid of `tiles_x` by `tiles_y` tiles and a histogram
+/// equalization mapping is computed for each tile. Each pixel is then mapped using a bilinear
+/// interpolation of the mappings of the four tiles whose centres are closest to it. This
+/// enhances local contrast, and avoids the blown out regions that global
+/// [`equalize_histogram`](fn.equalize_histogram.html) produces on unevenly lit images.
+///
+/// To avoid amplifying noise in near-uniform regions, each tile histogram bin is clipped to
+/// `clip_limit` times the mean bin count before equalization, and the clipped counts are
+/// redistributed uniformly across all bins. A `clip_limit` of 0 disables clipping. This
+/// matches the `clipLimit` parameter of OpenCV's `createCLAHE`, for which typical values
+/// are between 2 and 4.
+///
+/// [contrast limited adaptive histogram equalization]: https://en.wikipedia.org/wiki/Adaptive_histogram_equalization#Contrast_Limited_AHE
+///
+/// # Panics
+/// If `tiles_x` or `tiles_y` is 0 or larger than the corresponding image dimension,
+/// or if `clip_limit` is negative.
+pub fn clahe_mut(image: &mut GrayImage, clip_limit: f32, tiles_x: u32, tiles_y: u32) {
+    let (width, height) = image.dimensions();
+    assert!(
+        tiles_x > 0 && tiles_x <= width,
+        "tiles_x must be > 0 and <= image width"
+    );
+    assert!(
+        tiles_y > 0 && tiles_y <= height,
+        "tiles_y must be > 0 and <= image height"
+    );
+    assert!(clip_limit >= 0.0, "clip_limit must be >= 0");
+
+    // Tile i along an axis of length len covers [i * len / n, (i + 1) * len / n).
+    let bounds = |i: u32, len: u32, n: u32| {
+        let start = (i as u64 * len as u64 / n as u64) as u32;
+        let end = ((i + 1) as u64 * len as u64 / n as u64) as u32;
+        (start, end)
+    };
+
+    let mut luts = vec![[0u8; 256]; (tiles_x * tiles_y) as usize];
+    for ty in 0..tiles_y {
+        let (y0, y1) = bounds(ty, height, tiles_y);
+        for tx in 0..tiles_x {
+            let (x0, x1) = bounds(tx, width, tiles_x);
+            let mut hist = [0u32; 256];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    hist[image.get_pixel(x, y)[0] as usize] += 1;
+                }
+            }
+            let area = (x1 - x0) * (y1 - y0);
+            if clip_limit > 0.0 {
+                let limit = ((clip_limit * area as f32 / 256.0) as u32).max(1);
+                clip_histogram(&mut hist, limit);
+            }
+            luts[(ty * tiles_x + tx) as usize] = equalization_lut(&hist, area);
+        }
+    }
+
+    // For each coordinate along an axis, the indices of the tiles whose centres
+    // bracket it and the weight of the second of these.
+    let neighbours = |len: u32, n: u32| -> Vec<(u32, u32, f32)> {
+        let centre = |i: u32| {
+            let (start, end) = bounds(i, len, n);
+            (start + end - 1) as f32 / 2.0
+        };
+        (0..len)
+            .map(|p| {
+                let p = p as f32;
+                if p <= centre(0) {
+                    return (0, 0, 0.0);
+                }
+                if p >= centre(n - 1) {
+                    return (n - 1, n - 1, 0.0);
+                }
+                let mut i = 0;
+                while centre(i + 1) < p {
+                    i += 1;
+                }
+                let (c0, c1) = (centre(i), centre(i + 1));
+                (i, i + 1, (p - c0) / (c1 - c0))
+            })
+            .collect()
+    };
+    let x_neighbours = neighbours(width, tiles_x);
+    let y_neighbours = neighbours(height, tiles_y);
+
+    for y in 0..height {
+        let (ty0, ty1, wy) = y_neighbours[y as usize];
+        for x in 0..width {
+            let (tx0, tx1, wx) = x_neighbours[x as usize];
+            let v = image.get_pixel(x, y)[0] as usize;
+            let lut = |tx: u32, ty: u32| luts[(ty * tiles_x + tx) as usize][v] as f32;
+            let top = (1.0 - wx) * lut(tx0, ty0) + wx * lut(tx1, ty0);
+            let bottom = (1.0 - wx) * lut(tx0, ty1) + wx * lut(tx1, ty1);
+            let mapped = (1.0 - wy) * top + wy * bottom;
+            image.put_pixel(x, y, Luma([(mapped + 0.5).min(255.0) as u8]));
+        }
+    }
+}
+
+/// Applies [contrast limited adaptive histogram equalization] (CLAHE) to an 8bpp
+/// grayscale image.
+///
+/// See [`clahe_mut`](fn.clahe_mut.html) for details.
+///
+/// [contrast limited adaptive histogram equalization]: https://en.wikipedia.org/wiki/Adaptive_histogram_equalization#Contrast_Limited_AHE
+pub fn clahe(image: &GrayImage, clip_limit: f32, tiles_x: u32, tiles_y: u32) -> GrayImage {
+    let mut out = image.clone();
+    clahe_mut(&mut out, clip_limit, tiles_x, tiles_y);
+    out
+}
+
+// Clips each bin of a histogram to limit, and redistributes the excess as
+// evenly as possible over all bins.
+fn clip_histogram(hist: &mut [u32; 256], limit: u32) {
+    let mut excess = 0;
+    for h in hist.iter_mut() {
+        if *h > limit {
+            excess += *h - limit;
+            *h = limit;
+        }
+    }
+    let increment = excess / 256;
+    let remainder = (excess % 256) as usize;
+    for (i, h) in hist.iter_mut().enumerate() {
+        *h += increment;
+        if i < remainder {
+            *h += 1;
+        }
+    }
+}
+
+// The histogram equalization mapping for a histogram of the given total count.
+fn equalization_lut(hist: &[u32; 256], total: u32) -> [u8; 256] {
+    let mut lut = [0u8; 256];
+    let mut sum = 0;
+    for (l, h) in lut.iter_mut().zip(hist.iter()) {
+        sum += h;
+        *l = (f32::min(255f32, 255f32 * sum as f32 / total as f32)) as u8;
+    }
+    lut
+}
+
 /// Adjusts contrast of an 8bpp grayscale image in place so that its
 /// histogram is as close as possible to that of the target image.
 pub fn match_histogram_mut(image: &mut GrayImage, target: &GrayImage) {
@@ -302,28 +1057,595 @@ pub fn stretch_contrast_mut(image: &mut GrayImage, lower: u8, upper: u8) {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::definitions::{HasBlack, HasWhite};
-    use crate::utils::gray_bench_image;
-    use image::{GrayImage, Luma};
-    use test::{black_box, Bencher};
-
-    #[test]
-    fn adaptive_threshold_constant() {
-        let image = GrayImage::from_pixel(3, 3, Luma([100u8]));
-        let binary = adaptive_threshold(&image, 1);
-        let expected = GrayImage::from_pixel(3, 3, Luma::white());
-        assert_pixels_eq!(expected, binary);
-    }
-
-    #[test]
-    fn adaptive_threshold_one_darker_pixel() {
-        for y in 0..3 {
-            for x in 0..3 {
-                let mut image = GrayImage::from_pixel(3, 3, Luma([200u8]));
-                image.put_pixel(x, y, Luma([100u8]));
+/// Linearly stretches the contrast in an image, choosing the cutoffs from percentiles of its
+/// intensities.
+///
+/// The `low_pct`th and `high_pct`th percentile intensities are sent to 0 and 255 respectively,
+/// as in [`stretch_contrast`](fn.stretch_contrast.html). Percentiles are defined as in
+/// [`stats::percentile`](../stats/fn.percentile.html), except that the 0th percentile is the
+/// minimum intensity rather than 0. Choosing e.g. 1% and 99% rather than
+/// the minimum and maximum intensities prevents a few outlying pixels from limiting the stretch.
+///
+/// If `mask` is provided then only the pixels at which the mask is non-zero contribute
+/// to the percentile computations, but all pixels are stretched. If the cutoffs are equal,
+/// e.g. for a constant image, then the image is returned unchanged.
+///
+/// # Panics
+/// If `low_pct > high_pct`, `high_pct > 100`, `low_pct < 0`, or if `mask` does
+/// not have the same dimensions as `image`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::stretch_contrast_percentile;
+///
+/// let image = gray_image!(
+///     0,  100, 110, 120, 130;
+///     140, 150, 160, 170, 255);
+///
+/// let stretched = stretch_contrast_percentile(&image, 20.0, 80.0, None);
+///
+/// // The 20th percentile is 100 and the 80th is 160
+/// let expected = gray_image!(
+///       0,   0,  42,  85, 127;
+///     170, 212, 255, 255, 255);
+///
+/// assert_pixels_eq!(stretched, expected);
+/// # }
+/// ```
+pub fn stretch_contrast_percentile(
+    image: &GrayImage,
+    low_pct: f32,
+    high_pct: f32,
+    mask: Option<&GrayImage>,
+) -> GrayImage {
+    let mut out = image.clone();
+    stretch_contrast_percentile_mut(&mut out, low_pct, high_pct, mask);
+    out
+}
+
+/// Linearly stretches the contrast in an image in place, choosing the cutoffs from percentiles
+/// of its intensities.
+///
+/// See the [`stretch_contrast_percentile`](fn.stretch_contrast_percentile.html) documentation
+/// for more.
+pub fn stretch_contrast_percentile_mut(
+    image: &mut GrayImage,
+    low_pct: f32,
+    high_pct: f32,
+    mask: Option<&GrayImage>,
+) {
+    assert!(
+        0.0 <= low_pct && low_pct <= high_pct && high_pct <= 100.0,
+        "percentiles must satisfy 0 <= low_pct <= high_pct <= 100"
+    );
+    if let Some(mask) = mask {
+        assert_eq!(
+            mask.dimensions(),
+            image.dimensions(),
+            "mask must have the same dimensions as image"
+        );
+    }
+
+    let hist = match mask {
+        Some(mask) => {
+            let mut counts = vec![0u32; 256];
+            for (p, m) in image.iter().zip(mask.iter()) {
+                if *m != 0 {
+                    counts[*p as usize] += 1;
+                }
+            }
+            Histogram::from_counts(counts)
+        }
+        None => Histogram::from_channel(image, 0, 256),
+    };
+
+    let (lower, upper) = match (hist.percentile(low_pct), hist.percentile(high_pct)) {
+        (Some(lower), Some(upper)) => (lower, upper),
+        _ => return,
+    };
+    if upper > lower {
+        stretch_contrast_mut(image, lower, upper);
+    }
+}
+
+/// Enhances local contrast in a grayscale image using [single-scale retinex].
+///
+/// Each pixel is replaced by the log of the ratio of its intensity to a Gaussian-weighted
+/// mean of the intensities around it, with standard deviation `sigma`. This removes slowly
+/// varying illumination, recovering detail in shadows. The result is then linearly rescaled
+/// to the range `[0, 255]`, saturating the darkest and brightest 1% of pixels.
+///
+/// Small values of `sigma` (e.g. 15) give strong local contrast enhancement,
+/// and large values (e.g. 250) preserve more of the tonal balance of the input.
+/// See [`multi_scale_retinex`](fn.multi_scale_retinex.html) for a combination of these.
+///
+/// [single-scale retinex]: https://doi.org/10.1109/83.557356
+///
+/// # Panics
+/// If `sigma <= 0.0`.
+pub fn single_scale_retinex(image: &GrayImage, sigma: f32) -> GrayImage {
+    multi_scale_retinex(image, &[sigma])
+}
+
+/// Enhances local contrast in a grayscale image using [multi-scale retinex].
+///
+/// This is the average of the unscaled outputs of
+/// [`single_scale_retinex`](fn.single_scale_retinex.html) at each of the standard deviations
+/// in `sigmas`, linearly rescaled to the range `[0, 255]` so that the darkest and brightest 1%
+/// of pixels are saturated. A common choice of `sigmas` is `[15.0, 80.0, 250.0]`.
+///
+/// The running time is proportional to the sum of `sigmas`.
+///
+/// [multi-scale retinex]: https://doi.org/10.1109/83.597272
+///
+/// # Panics
+/// If `sigmas` is empty or any sigma is not positive.
+pub fn multi_scale_retinex(image: &GrayImage, sigmas: &[f32]) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let retinex = retinex_log_ratios(image, sigmas);
+    ImageBuffer::from_raw(width, height, rescale_to_u8(&retinex)).unwrap()
+}
+
+/// Enhances local contrast in an RGB image using [multi-scale retinex with color restoration].
+///
+/// The multi-scale retinex of each channel is computed as in
+/// [`multi_scale_retinex`](fn.multi_scale_retinex.html), and then multiplied by the
+/// color restoration factor `46 * (ln(125 * c) - ln(r + g + b))` for the channel value `c`.
+/// This undoes the desaturation caused by treating each channel independently. The result is
+/// linearly rescaled to the range `[0, 255]`, using the same scaling for all channels and
+/// saturating the darkest and brightest 1% of channel values.
+///
+/// [multi-scale retinex with color restoration]: https://doi.org/10.1109/83.597272
+///
+/// # Panics
+/// If `sigmas` is empty or any sigma is not positive.
+pub fn multi_scale_retinex_color(image: &RgbImage, sigmas: &[f32]) -> RgbImage {
+    const ALPHA: f32 = 125.0;
+    const BETA: f32 = 46.0;
+
+    let (width, height) = image.dimensions();
+    let mut retinex = retinex_log_ratios(image, sigmas);
+    for (r, p) in retinex.pixels_mut().zip(image.pixels()) {
+        let sum = p.0.iter().map(|&c| c as f32 + 1.0).sum::<f32>().ln();
+        for (r, &c) in r.0.iter_mut().zip(p.0.iter()) {
+            *r *= BETA * ((ALPHA * (c as f32 + 1.0)).ln() - sum);
+        }
+    }
+    ImageBuffer::from_raw(width, height, rescale_to_u8(&retinex)).unwrap()
+}
+
+// The per-channel average over all sigmas of ln(I + 1) - ln(G_sigma * (I + 1)).
+fn retinex_log_ratios<P>(image: &Image<P>, sigmas: &[f32]) -> Image<ChannelMap<P, f32>>
+where
+    P: WithChannel<f32> + 'static,
+    P: Pixel<Subpixel = u8> + MaybeSync,
+    ChannelMap<P, f32>: MaybeSync,
+{
+    assert!(!sigmas.is_empty(), "sigmas must be non-empty");
+    // Offset by one to avoid taking the log of zero
+    let shifted = map_subpixels_parallel(image, |c| c as f32 + 1.0);
+    let mut retinex = map_subpixels_parallel(image, |c| (c as f32 + 1.0).ln());
+    for &sigma in sigmas {
+        let blurred = gaussian_blur_f32(&shifted, sigma);
+        for (r, b) in retinex.iter_mut().zip(blurred.iter()) {
+            *r -= b.max(f32::MIN_POSITIVE).ln() / sigmas.len() as f32;
+        }
+    }
+    retinex
+}
+
+// Linearly rescales values to [0, 255], saturating the top and bottom 1%.
+fn rescale_to_u8(values: &[f32]) -> Vec<u8> {
+    if values.is_empty() {
+        return vec![];
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let clip = sorted.len() / 100;
+    let (lower, upper) = (sorted[clip], sorted[sorted.len() - 1 - clip]);
+    if upper <= lower {
+        return vec![128; values.len()];
+    }
+    values
+        .iter()
+        .map(|v| (255.0 * (v - lower) / (upper - lower) + 0.5).clamp(0.0, 255.0) as u8)
+        .collect()
+}
+
+/// Maps every channel of every pixel in an image through a lookup table.
+///
+/// The same table is used for all channels, including any alpha channel.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::apply_lut;
+///
+/// // Invert an image
+/// let mut lut = [0u8; 256];
+/// for (i, l) in lut.iter_mut().enumerate() {
+///     *l = 255 - i as u8;
+/// }
+///
+/// let image = gray_image!(
+///     0, 10;
+///     100, 255);
+///
+/// let expected = gray_image!(
+///     255, 245;
+///     155, 0);
+///
+/// assert_pixels_eq!(apply_lut(&image, &lut), expected);
+/// # }
+/// ```
+pub fn apply_lut<P>(image: &Image<P>, lut: &[u8; 256]) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    let mut out = image.clone();
+    apply_lut_mut(&mut out, lut);
+    out
+}
+
+/// Maps every channel of every pixel in an image through a lookup table, in place.
+///
+/// See the [`apply_lut`](fn.apply_lut.html) documentation for more.
+pub fn apply_lut_mut<P>(image: &mut Image<P>, lut: &[u8; 256])
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    for c in image.iter_mut() {
+        *c = lut[*c as usize];
+    }
+}
+
+/// Builds a lookup table for use with [`apply_lut`](fn.apply_lut.html) from a
+/// transfer function on normalized intensities.
+///
+/// Each input intensity `i` is mapped to `255 * f(i / 255)`, rounded to the nearest
+/// integer and clamped to `[0, 255]`.
+///
+/// # Examples
+/// ```
+/// use imageproc::contrast::lut_from_fn;
+///
+/// // A smoothstep tone curve, which increases contrast in the midtones
+/// let lut = lut_from_fn(|x| x * x * (3.0 - 2.0 * x));
+///
+/// assert_eq!(lut[0], 0);
+/// assert_eq!(lut[64], 40);
+/// assert_eq!(lut[128], 128);
+/// assert_eq!(lut[255], 255);
+/// ```
+pub fn lut_from_fn<F>(f: F) -> [u8; 256]
+where
+    F: Fn(f32) -> f32,
+{
+    let mut lut = [0u8; 256];
+    for (i, l) in lut.iter_mut().enumerate() {
+        let mapped = 255.0 * f(i as f32 / 255.0) + 0.5;
+        *l = if mapped.is_nan() {
+            0
+        } else {
+            mapped.clamp(0.0, 255.0) as u8
+        };
+    }
+    lut
+}
+
+/// Maps each channel of every pixel in an image through its own lookup table.
+///
+/// `luts[c]` is used for channel `c`, so e.g. an RGB image requires three tables. Use
+/// [`apply_lut`](fn.apply_lut.html) to apply the same table to all channels.
+///
+/// # Panics
+/// If the number of tables is not equal to the number of channels of `P`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::{apply_channel_luts, lut_from_fn};
+///
+/// // Warm an image by boosting red and reducing blue
+/// let luts = [
+///     lut_from_fn(|x| x.powf(0.5)),
+///     lut_from_fn(|x| x),
+///     lut_from_fn(|x| x * x),
+/// ];
+///
+/// let image = rgb_image!(
+///     [64, 64, 64], [255, 0, 255]);
+///
+/// let expected = rgb_image!(
+///     [128, 64, 16], [255, 0, 255]);
+///
+/// assert_pixels_eq!(apply_channel_luts(&image, &luts), expected);
+/// # }
+/// ```
+pub fn apply_channel_luts<P>(image: &Image<P>, luts: &[[u8; 256]]) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    let mut out = image.clone();
+    apply_channel_luts_mut(&mut out, luts);
+    out
+}
+
+/// Maps each channel of every pixel in an image through its own lookup table, in place.
+///
+/// See the [`apply_channel_luts`](fn.apply_channel_luts.html) documentation for more.
+pub fn apply_channel_luts_mut<P>(image: &mut Image<P>, luts: &[[u8; 256]])
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    let channels = P::CHANNEL_COUNT as usize;
+    assert_eq!(
+        luts.len(),
+        channels,
+        "expected one lookup table per channel"
+    );
+    for pixel in image.chunks_exact_mut(channels) {
+        for (c, lut) in pixel.iter_mut().zip(luts) {
+            *c = lut[*c as usize];
+        }
+    }
+}
+
+/// Maps every channel of every pixel in an image with 16 bits per channel through a
+/// lookup table with 65536 entries.
+///
+/// The same table is used for all channels, including any alpha channel.
+///
+/// # Panics
+/// If `lut` does not have exactly 65536 entries.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::apply_lut_u16;
+///
+/// // Keep only the top 12 bits of each value
+/// let lut: Vec<u16> = (0..=65535u16).map(|v| v & 0xfff0).collect();
+///
+/// let image = gray_image!(type: u16,
+///     0x1234, 0xffff);
+///
+/// let expected = gray_image!(type: u16,
+///     0x1230, 0xfff0);
+///
+/// assert_pixels_eq!(apply_lut_u16(&image, &lut), expected);
+/// # }
+/// ```
+pub fn apply_lut_u16<P>(image: &Image<P>, lut: &[u16]) -> Image<P>
+where
+    P: Pixel<Subpixel = u16> + 'static,
+{
+    let mut out = image.clone();
+    apply_lut_u16_mut(&mut out, lut);
+    out
+}
+
+/// Maps every channel of every pixel in an image with 16 bits per channel through a
+/// lookup table with 65536 entries, in place.
+///
+/// See the [`apply_lut_u16`](fn.apply_lut_u16.html) documentation for more.
+pub fn apply_lut_u16_mut<P>(image: &mut Image<P>, lut: &[u16])
+where
+    P: Pixel<Subpixel = u16> + 'static,
+{
+    assert_eq!(lut.len(), 65536, "lookup table must have 65536 entries");
+    // Converting to a fixed size array removes the bounds checks from the loop
+    let lut: &[u16; 65536] = lut.try_into().unwrap();
+    for c in image.iter_mut() {
+        *c = lut[*c as usize];
+    }
+}
+
+/// Solarizes an image by inverting all channel values greater than `threshold`.
+///
+/// Any alpha channel is left unchanged.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::solarize;
+///
+/// let image = gray_image!(
+///     0, 100;
+///     150, 255);
+///
+/// let expected = gray_image!(
+///     0, 100;
+///     105, 0);
+///
+/// assert_pixels_eq!(solarize(&image, 128), expected);
+/// # }
+/// ```
+pub fn solarize<P>(image: &Image<P>, threshold: u8) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    let mut out = image.clone();
+    solarize_mut(&mut out, threshold);
+    out
+}
+
+/// Solarizes an image in place by inverting all channel values greater than `threshold`.
+///
+/// See the [`solarize`](fn.solarize.html) documentation for more.
+pub fn solarize_mut<P>(image: &mut Image<P>, threshold: u8)
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    let mut lut = [0u8; 256];
+    for (i, l) in lut.iter_mut().enumerate() {
+        *l = if i > threshold as usize {
+            255 - i as u8
+        } else {
+            i as u8
+        };
+    }
+    apply_color_lut_mut(image, &lut);
+}
+
+/// Posterizes an image by reducing each channel to `levels` evenly spaced values.
+///
+/// The range `[0, 255]` is split into `levels` equal-sized intervals, and each
+/// channel value in the `k`th interval is replaced by `255 * k / (levels - 1)`, rounded
+/// to the nearest integer. Any alpha channel is left unchanged.
+///
+/// # Panics
+/// If `levels < 2`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::posterize;
+///
+/// let image = gray_image!(
+///     0, 80, 90;
+///     170, 200, 255);
+///
+/// let expected = gray_image!(
+///     0, 0, 128;
+///     128, 255, 255);
+///
+/// assert_pixels_eq!(posterize(&image, 3), expected);
+/// # }
+/// ```
+pub fn posterize<P>(image: &Image<P>, levels: u16) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    let mut out = image.clone();
+    posterize_mut(&mut out, levels);
+    out
+}
+
+/// Posterizes an image in place by reducing each channel to `levels` evenly spaced values.
+///
+/// See the [`posterize`](fn.posterize.html) documentation for more.
+pub fn posterize_mut<P>(image: &mut Image<P>, levels: u16)
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    assert!(levels >= 2, "levels must be at least 2");
+    let levels = min(levels, 256) as usize;
+    let mut lut = [0u8; 256];
+    for (i, l) in lut.iter_mut().enumerate() {
+        let k = i * levels / 256;
+        *l = ((255 * k + (levels - 1) / 2) / (levels - 1)) as u8;
+    }
+    apply_color_lut_mut(image, &lut);
+}
+
+// Applies lut to every channel other than alpha.
+fn apply_color_lut_mut<P>(image: &mut Image<P>, lut: &[u8; 256])
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    if !P::COLOR_TYPE.has_alpha() {
+        apply_lut_mut(image, lut);
+        return;
+    }
+    // The alpha channel is the last channel of every pixel type with one
+    let mut luts = vec![*lut; P::CHANNEL_COUNT as usize];
+    let alpha = luts.last_mut().unwrap();
+    for (i, l) in alpha.iter_mut().enumerate() {
+        *l = i as u8;
+    }
+    apply_channel_luts_mut(image, &luts);
+}
+
+/// Applies gamma correction to an image, mapping each normalized intensity `i` to `i^gamma`.
+///
+/// Values of `gamma` less than 1 brighten an image, and values greater than 1 darken it.
+/// Any alpha channel is left unchanged.
+///
+/// # Panics
+/// If `gamma` is not positive.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::contrast::adjust_gamma;
+///
+/// let image = gray_image!(
+///     0, 64;
+///     128, 255);
+///
+/// let expected = gray_image!(
+///     0, 128;
+///     181, 255);
+///
+/// assert_pixels_eq!(adjust_gamma(&image, 0.5), expected);
+/// # }
+/// ```
+pub fn adjust_gamma<P>(image: &Image<P>, gamma: f32) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    let mut out = image.clone();
+    adjust_gamma_mut(&mut out, gamma);
+    out
+}
+
+/// Applies gamma correction to an image in place.
+///
+/// See the [`adjust_gamma`](fn.adjust_gamma.html) documentation for more.
+pub fn adjust_gamma_mut<P>(image: &mut Image<P>, gamma: f32)
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    assert!(gamma > 0.0, "gamma must be positive");
+    apply_color_lut_mut(image, &lut_from_fn(|x| x.powf(gamma)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::{HasBlack, HasWhite};
+    use crate::rect::Rect;
+    use crate::utils::gray_bench_image;
+    use image::{GrayImage, Luma, Rgb, RgbImage, Rgba, RgbaImage};
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn adaptive_threshold_constant() {
+        let image = GrayImage::from_pixel(3, 3, Luma([100u8]));
+        let binary = adaptive_threshold(&image, 1);
+        let expected = GrayImage::from_pixel(3, 3, Luma::white());
+        assert_pixels_eq!(expected, binary);
+    }
+
+    #[test]
+    fn adaptive_threshold_one_darker_pixel() {
+        for y in 0..3 {
+            for x in 0..3 {
+                let mut image = GrayImage::from_pixel(3, 3, Luma([200u8]));
+                image.put_pixel(x, y, Luma([100u8]));
                 let binary = adaptive_threshold(&image, 1);
                 // All except the dark pixel have brightness >= their local mean
                 let mut expected = GrayImage::from_pixel(3, 3, Luma::white());
@@ -364,6 +1686,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn adaptive_threshold_with_method_constant() {
+        let image = GrayImage::from_pixel(5, 5, Luma([100u8]));
+        for method in [
+            AdaptiveThresholdMethod::Mean,
+            AdaptiveThresholdMethod::Gaussian,
+        ] {
+            let white = GrayImage::from_pixel(5, 5, Luma::white());
+            let black = GrayImage::from_pixel(5, 5, Luma::black());
+            assert_pixels_eq!(
+                adaptive_threshold_with_method(&image, 2, 0.0, method),
+                white
+            );
+            assert_pixels_eq!(
+                adaptive_threshold_with_method(&image, 2, -1.0, method),
+                black
+            );
+        }
+    }
+
+    #[test]
+    fn adaptive_threshold_with_method_shaded_document() {
+        // Dark text pixels on a background which darkens steadily from left to right.
+        // No global threshold can separate the text from the background.
+        let mut image = GrayImage::from_fn(40, 9, |x, _| Luma([230 - 4 * x as u8]));
+        let text = [(3, 4), (4, 4), (20, 3), (20, 4), (36, 5), (37, 5)];
+        for &(x, y) in &text {
+            let background = image.get_pixel(x, y)[0];
+            image.put_pixel(x, y, Luma([background - 60]));
+        }
+
+        for method in [
+            AdaptiveThresholdMethod::Mean,
+            AdaptiveThresholdMethod::Gaussian,
+        ] {
+            let binary = adaptive_threshold_with_method(&image, 3, 10.0, method);
+            for (x, y, p) in binary.enumerate_pixels() {
+                let expected = if text.contains(&(x, y)) { 0 } else { 255 };
+                assert_eq!(p[0], expected, "{:?} at ({}, {})", method, x, y);
+            }
+        }
+    }
+
+    #[bench]
+    fn bench_adaptive_threshold_gaussian(b: &mut Bencher) {
+        let image = gray_bench_image(200, 200);
+        b.iter(|| {
+            let thresholded =
+                adaptive_threshold_with_method(&image, 5, 2.0, AdaptiveThresholdMethod::Gaussian);
+            black_box(thresholded);
+        });
+    }
+
+    #[test]
+    fn test_niblack_threshold() {
+        let image = gray_image!(
+            10, 10, 10, 10, 10;
+            10, 90, 90, 90, 10;
+            10, 90, 90, 90, 10;
+            10, 90, 90, 90, 10;
+            10, 10, 10, 10, 10);
+        // With k = 0 each pixel is compared with its local mean
+        let expected = gray_image!(
+              0,   0,   0,   0,   0;
+              0, 255, 255, 255,   0;
+              0, 255,   0, 255,   0;
+              0, 255, 255, 255,   0;
+              0,   0,   0,   0,   0);
+        assert_pixels_eq!(niblack_threshold(&image, 1, 0.0), expected);
+
+        // Large negative k sends every pixel in a non-flat block to white
+        let mut expected = GrayImage::from_pixel(5, 5, Luma::white());
+        expected.put_pixel(2, 2, Luma::black());
+        assert_pixels_eq!(niblack_threshold(&image, 1, -10.0), expected);
+
+        // Large positive k sends everything to black
+        let black = GrayImage::from_pixel(5, 5, Luma::black());
+        assert_pixels_eq!(niblack_threshold(&image, 1, 10.0), black);
+    }
+
+    #[test]
+    fn test_sauvola_threshold_flat_background_is_white() {
+        let image = GrayImage::from_pixel(10, 10, Luma([180u8]));
+        let white = GrayImage::from_pixel(10, 10, Luma::white());
+        assert_pixels_eq!(sauvola_threshold(&image, 3, 0.3, 128.0), white);
+    }
+
+    #[test]
+    fn test_sauvola_threshold_shaded_document() {
+        let mut image = GrayImage::from_fn(40, 9, |x, _| Luma([230 - 4 * x as u8]));
+        let text = [(3, 4), (4, 4), (20, 3), (20, 4), (36, 5), (37, 5)];
+        for &(x, y) in &text {
+            let background = image.get_pixel(x, y)[0];
+            image.put_pixel(x, y, Luma([background - 60]));
+        }
+
+        let binary = sauvola_threshold(&image, 3, 0.2, 128.0);
+        for (x, y, p) in binary.enumerate_pixels() {
+            let expected = if text.contains(&(x, y)) { 0 } else { 255 };
+            assert_eq!(p[0], expected, "at ({}, {})", x, y);
+        }
+    }
+
+    #[bench]
+    fn bench_sauvola_threshold(b: &mut Bencher) {
+        let image = gray_bench_image(200, 200);
+        b.iter(|| {
+            let thresholded = sauvola_threshold(&image, 7, 0.3, 128.0);
+            black_box(thresholded);
+        });
+    }
+
     #[bench]
     fn bench_adaptive_threshold(b: &mut Bencher) {
         let image = gray_bench_image(200, 200);
@@ -374,6 +1808,371 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_equalize_histogram_rgb_of_gray_image() {
+        let gray = gray_bench_image(20, 20);
+        let rgb = RgbImage::from_fn(20, 20, |x, y| {
+            let v = gray.get_pixel(x, y)[0];
+            Rgb([v, v, v])
+        });
+        let equalized = equalize_histogram(&gray);
+        let expected = RgbImage::from_fn(20, 20, |x, y| {
+            let v = equalized.get_pixel(x, y)[0];
+            Rgb([v, v, v])
+        });
+        assert_pixels_eq!(equalize_histogram_rgb(&rgb), expected);
+    }
+
+    #[test]
+    fn test_equalize_histogram_rgb_preserves_chroma() {
+        // Low contrast reddish image
+        let image = RgbImage::from_fn(16, 16, |x, y| {
+            let v = (100 + (x + y) / 2) as u8;
+            Rgb([v + 20, v, v - 10])
+        });
+        let equalized = equalize_histogram_rgb(&image);
+        for (p, q) in image.pixels().zip(equalized.pixels()) {
+            let (r, g, b) = (q[0] as i32, q[1] as i32, q[2] as i32);
+            // Unclamped pixels keep their channel differences, up to rounding
+            if r < 255 && b > 0 {
+                assert!((r - g - (p[0] as i32 - p[1] as i32)).abs() <= 1);
+                assert!((g - b - (p[1] as i32 - p[2] as i32)).abs() <= 1);
+            }
+        }
+        // Contrast has been increased
+        let luma_range = |image: &RgbImage| {
+            let greens: Vec<u8> = image.pixels().map(|p| p[1]).collect();
+            greens.iter().max().unwrap() - greens.iter().min().unwrap()
+        };
+        assert!(luma_range(&equalized) > 2 * luma_range(&image));
+    }
+
+    #[test]
+    fn test_white_balance_gray_image_is_unchanged() {
+        let image = RgbImage::from_fn(10, 10, |x, y| {
+            let v = (x * 20 + y) as u8;
+            Rgb([v, v, v])
+        });
+        assert_pixels_eq!(white_balance_gray_world(&image), image);
+        assert_pixels_eq!(
+            white_balance_reference(&image, Rect::at(2, 2).of_size(3, 3)),
+            image
+        );
+    }
+
+    #[test]
+    fn test_white_balance_white_patch() {
+        let image = rgb_image!(
+            [100, 50, 200], [200, 100, 250];
+            [50, 25, 100], [0, 0, 0]);
+        let expected = rgb_image!(
+            [128, 128, 204], [255, 255, 255];
+            [64, 64, 102], [0, 0, 0]);
+        assert_pixels_eq!(white_balance_white_patch(&image, 100.0), expected);
+    }
+
+    #[test]
+    fn test_white_balance_reference() {
+        // The top left pixel is a gray card lit by yellowish light
+        let image = rgb_image!(
+            [120, 100, 80], [60, 50, 40];
+            [240, 200, 160], [30, 100, 10]);
+        let expected = rgb_image!(
+            [100, 100, 100], [50, 50, 50];
+            [200, 200, 200], [25, 100, 13]);
+        let balanced = white_balance_reference(&image, Rect::at(-5, -5).of_size(6, 6));
+        assert_pixels_eq!(balanced, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_white_balance_reference_rejects_region_outside_image() {
+        let image = RgbImage::new(4, 4);
+        white_balance_reference(&image, Rect::at(10, 10).of_size(2, 2));
+    }
+
+    #[test]
+    fn test_clahe_single_tile_without_clipping_equalizes() {
+        let image = gray_bench_image(30, 20);
+        assert_pixels_eq!(clahe(&image, 0.0, 1, 1), equalize_histogram(&image));
+    }
+
+    #[test]
+    fn test_clahe_uneven_lighting() {
+        // A dark gradient on the left and a bright gradient on the right
+        let image = GrayImage::from_fn(64, 32, |x, y| {
+            let base = if x < 32 { 20 } else { 200 };
+            Luma([base + ((x % 32 + y) / 4) as u8])
+        });
+
+        let range = |image: &GrayImage, x0: u32, x1: u32| {
+            let values: Vec<u8> = (x0..x1)
+                .flat_map(|x| (0..32).map(move |y| (x, y)))
+                .map(|(x, y)| image.get_pixel(x, y)[0])
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+
+        let equalized = clahe(&image, 0.0, 2, 1);
+        // Contrast is enhanced in the interior of both halves
+        assert!(range(&equalized, 4, 12) > 2 * range(&image, 4, 12));
+        assert!(range(&equalized, 52, 60) > 2 * range(&image, 52, 60));
+
+        // Clipping limits the enhancement
+        let clipped = clahe(&image, 2.0, 2, 1);
+        assert!(range(&clipped, 4, 12) < range(&equalized, 4, 12));
+    }
+
+    #[test]
+    fn test_clip_histogram() {
+        let mut hist = [0u32; 256];
+        hist[10] = 600;
+        hist[20] = 3;
+        clip_histogram(&mut hist, 50);
+        // 550 excess counts are spread over 256 bins, with the
+        // remaining 38 going to the first 38 bins
+        assert_eq!(hist[0], 3);
+        assert_eq!(hist[10], 53);
+        assert_eq!(hist[20], 6);
+        assert_eq!(hist[100], 2);
+        assert_eq!(hist.iter().sum::<u32>(), 603);
+    }
+
+    #[bench]
+    fn bench_clahe(b: &mut Bencher) {
+        let image = gray_bench_image(500, 500);
+        b.iter(|| {
+            let equalized = clahe(&image, 2.0, 8, 8);
+            black_box(equalized);
+        });
+    }
+
+    #[test]
+    fn test_stretch_contrast_percentile_full_range() {
+        let image = gray_image!(
+            50, 60, 70;
+            80, 90, 100);
+        assert_pixels_eq!(
+            stretch_contrast_percentile(&image, 0.0, 100.0, None),
+            stretch_contrast(&image, 50, 100)
+        );
+    }
+
+    #[test]
+    fn test_stretch_contrast_percentile_ignores_outliers() {
+        let mut image = GrayImage::from_fn(10, 10, |x, _| Luma([100 + x as u8]));
+        image.put_pixel(0, 0, Luma([0]));
+        image.put_pixel(9, 9, Luma([255]));
+        assert_pixels_eq!(
+            stretch_contrast_percentile(&image, 2.0, 98.0, None),
+            stretch_contrast(&image, 100, 109)
+        );
+    }
+
+    #[test]
+    fn test_stretch_contrast_percentile_mask() {
+        let image = gray_image!(
+            10, 20, 200;
+            30, 40, 250);
+        let mask = gray_image!(
+            1, 1, 0;
+            1, 1, 0);
+        // Only the left two columns contribute, but all pixels are stretched
+        let expected = gray_image!(
+              0,  85, 255;
+            170, 255, 255);
+        assert_pixels_eq!(
+            stretch_contrast_percentile(&image, 0.0, 100.0, Some(&mask)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_stretch_contrast_percentile_constant() {
+        let image = GrayImage::from_pixel(4, 4, Luma([77u8]));
+        assert_pixels_eq!(stretch_contrast_percentile(&image, 1.0, 99.0, None), image);
+    }
+
+    #[test]
+    fn test_single_scale_retinex_constant() {
+        let image = GrayImage::from_pixel(10, 10, Luma([40u8]));
+        let expected = GrayImage::from_pixel(10, 10, Luma([128u8]));
+        assert_pixels_eq!(single_scale_retinex(&image, 5.0), expected);
+    }
+
+    #[test]
+    fn test_multi_scale_retinex_recovers_shadow_detail() {
+        // A textured image whose left half is in deep shadow
+        let image = GrayImage::from_fn(60, 20, |x, y| {
+            let texture = if (x / 2 + y / 2) % 2 == 0 { 1.0 } else { 1.5 };
+            let illumination = if x < 30 { 10.0 } else { 120.0 };
+            Luma([(illumination * texture) as u8])
+        });
+        let enhanced = multi_scale_retinex(&image, &[3.0, 10.0]);
+
+        let contrast = |image: &GrayImage, x0: u32| {
+            let values: Vec<u8> = (x0..x0 + 10)
+                .flat_map(|x| (5..15).map(move |y| (x, y)))
+                .map(|(x, y)| image.get_pixel(x, y)[0])
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        assert!(contrast(&image, 10) < 10);
+        assert!(contrast(&enhanced, 10) > 50);
+    }
+
+    #[test]
+    fn test_multi_scale_retinex_color_gray_input_stays_gray() {
+        let image = RgbImage::from_fn(20, 20, |x, y| {
+            let v = ((x * 7 + y * 3) % 50 + 20) as u8;
+            Rgb([v, v, v])
+        });
+        let enhanced = multi_scale_retinex_color(&image, &[2.0, 8.0]);
+        for p in enhanced.pixels() {
+            assert_eq!(p[0], p[1]);
+            assert_eq!(p[1], p[2]);
+        }
+    }
+
+    #[bench]
+    fn bench_multi_scale_retinex(b: &mut Bencher) {
+        let image = gray_bench_image(200, 200);
+        b.iter(|| {
+            let enhanced = multi_scale_retinex(&image, &[5.0, 15.0, 50.0]);
+            black_box(enhanced);
+        });
+    }
+
+    #[test]
+    fn test_apply_lut_rgb() {
+        let image = rgb_image!([0, 1, 2], [3, 4, 5]);
+        let mut lut = [0u8; 256];
+        for (i, l) in lut.iter_mut().enumerate() {
+            *l = (2 * i).min(255) as u8;
+        }
+        let expected = rgb_image!([0, 2, 4], [6, 8, 10]);
+        assert_pixels_eq!(apply_lut(&image, &lut), expected);
+    }
+
+    #[test]
+    fn test_lut_from_fn_identity_and_clamping() {
+        let identity = lut_from_fn(|x| x);
+        for (i, l) in identity.iter().enumerate() {
+            assert_eq!(*l as usize, i);
+        }
+        assert_eq!(lut_from_fn(|x| 2.0 * x)[200], 255);
+        assert_eq!(lut_from_fn(|x| x - 1.0)[200], 0);
+        assert_eq!(lut_from_fn(|_| f32::NAN)[200], 0);
+    }
+
+    #[test]
+    fn test_apply_channel_luts_rgba() {
+        let invert = lut_from_fn(|x| 1.0 - x);
+        let identity = lut_from_fn(|x| x);
+        let luts = [invert, identity, invert, identity];
+        let image = RgbaImage::from_pixel(2, 1, Rgba([10, 20, 30, 40]));
+        let expected = RgbaImage::from_pixel(2, 1, Rgba([245, 20, 225, 40]));
+        assert_pixels_eq!(apply_channel_luts(&image, &luts), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_channel_luts_rejects_wrong_number_of_tables() {
+        let image = RgbImage::new(2, 2);
+        let _ = apply_channel_luts(&image, &[[0u8; 256]; 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_lut_u16_rejects_short_table() {
+        let image: Image<Luma<u16>> = Image::new(2, 2);
+        let _ = apply_lut_u16(&image, &[0u16; 256]);
+    }
+
+    #[test]
+    fn test_solarize_rgb() {
+        let image = rgb_image!([10, 128, 129], [200, 0, 255]);
+        let expected = rgb_image!([10, 128, 126], [55, 0, 0]);
+        assert_pixels_eq!(solarize(&image, 128), expected);
+    }
+
+    #[test]
+    fn test_solarize_and_posterize_preserve_alpha() {
+        let image = rgba_image!([10, 200, 255, 200], [255, 0, 130, 30]);
+        assert_pixels_eq!(
+            solarize(&image, 128),
+            rgba_image!([10, 55, 0, 200], [0, 0, 125, 30])
+        );
+        assert_pixels_eq!(
+            posterize(&image, 2),
+            rgba_image!([0, 255, 255, 200], [255, 0, 255, 30])
+        );
+    }
+
+    #[test]
+    fn test_solarize_max_threshold_is_identity() {
+        let image = gray_bench_image(10, 10);
+        assert_pixels_eq!(solarize(&image, 255), image);
+    }
+
+    #[test]
+    fn test_posterize_256_levels_is_identity() {
+        let image = gray_bench_image(10, 10);
+        assert_pixels_eq!(posterize(&image, 256), image);
+    }
+
+    #[test]
+    fn test_posterize_two_levels() {
+        let image = rgb_image!([0, 127, 128], [255, 64, 200]);
+        let expected = rgb_image!([0, 0, 255], [255, 0, 255]);
+        assert_pixels_eq!(posterize(&image, 2), expected);
+    }
+
+    #[test]
+    fn test_posterize_output_values() {
+        let image = GrayImage::from_fn(256, 1, |x, _| Luma([x as u8]));
+        let posterized = posterize(&image, 4);
+        let mut values: Vec<u8> = posterized.iter().cloned().collect();
+        values.dedup();
+        assert_eq!(values, vec![0, 85, 170, 255]);
+    }
+
+    #[test]
+    fn test_adjust_gamma_one_is_identity() {
+        let image = gray_bench_image(10, 10);
+        assert_pixels_eq!(adjust_gamma(&image, 1.0), image);
+    }
+
+    #[test]
+    fn test_adjust_gamma_preserves_alpha() {
+        let image = rgba_image!([0, 64, 128, 64], [255, 128, 64, 128]);
+        assert_pixels_eq!(
+            adjust_gamma(&image, 0.5),
+            rgba_image!([0, 128, 181, 64], [255, 181, 128, 128])
+        );
+    }
+
+    #[test]
+    fn test_adjust_gamma_is_monotonic() {
+        let image = GrayImage::from_fn(256, 1, |x, _| Luma([x as u8]));
+        let darker = adjust_gamma(&image, 2.2);
+        let brighter = adjust_gamma(&image, 1.0 / 2.2);
+        for x in 1..256 {
+            assert!(darker.get_pixel(x, 0)[0] >= darker.get_pixel(x - 1, 0)[0]);
+            assert!(darker.get_pixel(x, 0)[0] <= image.get_pixel(x, 0)[0]);
+            assert!(brighter.get_pixel(x, 0)[0] >= image.get_pixel(x, 0)[0]);
+        }
+    }
+
+    #[bench]
+    fn bench_adjust_gamma(b: &mut Bencher) {
+        let image = gray_bench_image(500, 500);
+        b.iter(|| {
+            let adjusted = adjust_gamma(&image, 2.2);
+            black_box(adjusted);
+        });
+    }
+
     #[bench]
     fn bench_match_histogram(b: &mut Bencher) {
         let target = GrayImage::from_pixel(200, 200, Luma([150]));
@@ -465,6 +2264,116 @@ mod tests {
         assert_eq!(level, 120);
     }
 
+    #[test]
+    fn test_triangle_level() {
+        // A large dark background peak with a long, low tail of bright pixels
+        let mut pixels = vec![20u8; 200];
+        pixels.extend(vec![21u8; 100]);
+        pixels.extend(vec![19u8; 100]);
+        for i in 0..100 {
+            pixels.push(30 + (i % 100) as u8 * 2);
+        }
+        let image = GrayImage::from_raw(500, 1, pixels).unwrap();
+        let level = triangle_level(&image);
+        // The level separates the background from the tail
+        assert!((21..30).contains(&level), "level {}", level);
+    }
+
+    #[test]
+    fn test_triangle_level_tail_below_peak() {
+        // As above, but inverted so that the tail is below the peak
+        let mut pixels = vec![235u8; 200];
+        pixels.extend(vec![234u8; 100]);
+        pixels.extend(vec![236u8; 100]);
+        for i in 0..100 {
+            pixels.push(225 - (i % 100) as u8 * 2);
+        }
+        let image = GrayImage::from_raw(500, 1, pixels).unwrap();
+        let level = triangle_level(&image);
+        assert!((225..234).contains(&level), "level {}", level);
+    }
+
+    #[test]
+    fn test_triangle_and_isodata_constant() {
+        for &intensity in &[0, 128, 255] {
+            let image = constant_image(10, 10, intensity);
+            assert_eq!(triangle_level(&image), intensity);
+            assert_eq!(isodata_level(&image), intensity);
+        }
+    }
+
+    #[test]
+    fn test_isodata_level_two_clusters() {
+        let image = gray_image!(
+            10, 12, 14, 200;
+            11, 13, 210, 220);
+        // The class means at threshold 14 are 12 and 210, whose mean is 111
+        assert_eq!(isodata_level(&image), 111);
+    }
+
+    #[test]
+    fn test_isodata_level_gradient() {
+        let contents = (0u8..26u8).map(|x| x * 10u8).collect();
+        let image = GrayImage::from_raw(26, 1, contents).unwrap();
+        assert_eq!(isodata_level(&image), 125);
+    }
+
+    #[test]
+    fn test_otsu_threshold() {
+        let image = gray_bench_image(20, 20);
+        let (level, binarized) = otsu_threshold(&image);
+        assert_eq!(level, otsu_level(&image));
+        assert_pixels_eq!(binarized, threshold(&image, level));
+    }
+
+    #[test]
+    fn test_multi_otsu_two_classes_matches_otsu_level() {
+        let image = gray_bench_image(30, 30);
+        assert_eq!(multi_otsu_levels(&image, 2), vec![otsu_level(&image)]);
+    }
+
+    #[test]
+    fn test_multi_otsu_levels_separates_clusters() {
+        let image = GrayImage::from_fn(40, 10, |x, y| {
+            let centre = [30, 90, 160, 230][(x / 10) as usize];
+            Luma([centre + (x % 10 + y % 3) as u8 - 5])
+        });
+        let levels = multi_otsu_levels(&image, 4);
+        assert_eq!(levels.len(), 3);
+        assert!(levels[0] >= 36 && levels[0] < 85);
+        assert!(levels[1] >= 96 && levels[1] < 155);
+        assert!(levels[2] >= 166 && levels[2] < 225);
+    }
+
+    #[test]
+    fn test_multi_otsu_constant_image() {
+        let image = GrayImage::from_pixel(5, 5, Luma([100u8]));
+        let levels = multi_otsu_levels(&image, 3);
+        assert_eq!(levels.len(), 2);
+        assert!(levels[0] < levels[1]);
+    }
+
+    #[test]
+    fn test_threshold_multi_single_level_matches_threshold() {
+        let image = gray_bench_image(10, 10);
+        assert_pixels_eq!(threshold_multi(&image, &[77]), threshold(&image, 77));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_threshold_multi_rejects_unsorted_thresholds() {
+        threshold_multi(&GrayImage::new(2, 2), &[20, 10]);
+    }
+
+    #[bench]
+    fn bench_multi_otsu_levels(b: &mut Bencher) {
+        let image = gray_bench_image(200, 200);
+        b.iter(|| {
+            let levels = multi_otsu_levels(&image, 3);
+            black_box(levels);
+        });
+    }
+
     #[bench]
     fn bench_otsu_level(b: &mut Bencher) {
         let image = gray_bench_image(200, 200);
@@ -508,6 +2417,39 @@ mod tests {
         assert_pixels_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_threshold_u16() {
+        let image = gray_image!(type: u16,
+            0, 1000, 30000;
+            30001, 65535, 12);
+
+        let expected = gray_image!(type: u16,
+            0, 0, 0;
+            65535, 65535, 0);
+
+        assert_pixels_eq!(threshold(&image, 30000), expected);
+    }
+
+    #[test]
+    fn test_threshold_f32() {
+        let image = gray_image!(type: f32,
+            0.0, 0.25, 0.5;
+            0.75, 1.0, 4.0);
+
+        let expected = gray_image!(type: f32,
+            0.0, 0.0, 0.0;
+            1.0, 1.0, 1.0);
+
+        assert_pixels_eq!(threshold(&image, 0.5), expected);
+    }
+
+    #[test]
+    fn test_threshold_mut_u16() {
+        let mut image = gray_image!(type: u16, 100, 300; 200, 400);
+        threshold_mut(&mut image, 200);
+        assert_pixels_eq!(image, gray_image!(type: u16, 0, 65535; 0, 65535));
+    }
+
     #[bench]
     fn bench_equalize_histogram(b: &mut Bencher) {
         let image = gray_bench_image(500, 500);
@@ -1,7 +1,7 @@
 //! Trait definitions and type aliases.
 
 use image::{Bgr, Bgra, ImageBuffer, Luma, LumaA, Pixel, Rgb, Rgba};
-use std::{i16, u16, u8};
+use std::{f32, i16, u16, u8};
 
 /// An `ImageBuffer` containing Pixels of type P with storage `Vec<P::Subpixel>`.
 /// Most operations in this library only support inputs of type `Image`, rather
@@ -77,6 +77,12 @@ impl_black_white!(
     Bgra([u16::MAX, u16::MAX, u16::MAX, u16::MAX])
 );
 
+// Floating point images are taken to have intensities in the range [0, 1].
+impl_black_white!(Luma<f32>, Luma([0.0]), Luma([1.0]));
+impl_black_white!(LumaA<f32>, LumaA([0.0, 1.0]), LumaA([1.0, 1.0]));
+impl_black_white!(Rgb<f32>, Rgb([0.0; 3]), Rgb([1.0; 3]));
+impl_black_white!(Rgba<f32>, Rgba([0.0, 0.0, 0.0, 1.0]), Rgba([1.0; 4]));
+
 /// Something with a 2d position.
 pub trait Position {
     /// x-coordinate.
@@ -91,6 +97,44 @@ pub trait Score {
     fn score(&self) -> f32;
 }
 
+/// Equivalent to `Sync` when the `rayon` feature is enabled, and implemented by all types
+/// otherwise.
+///
+/// Functions which process images in parallel use this bound rather than `Sync`, so that
+/// they place no extra requirements on their inputs when `rayon` is disabled.
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "rayon")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// Equivalent to `Sync` when the `rayon` feature is enabled, and implemented by all types
+/// otherwise.
+///
+/// Functions which process images in parallel use this bound rather than `Sync`, so that
+/// they place no extra requirements on their inputs when `rayon` is disabled.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "rayon"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// Equivalent to `Send` when the `rayon` feature is enabled, and implemented by all types
+/// otherwise.
+///
+/// See [`MaybeSync`](trait.MaybeSync.html).
+#[cfg(feature = "rayon")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "rayon")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Equivalent to `Send` when the `rayon` feature is enabled, and implemented by all types
+/// otherwise.
+///
+/// See [`MaybeSync`](trait.MaybeSync.html).
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "rayon"))]
+impl<T: ?Sized> MaybeSend for T {}
+
 /// A type to which we can clamp a value of type T.
 /// Implementations are not required to handle `NaN`s gracefully.
 pub trait Clamp<T> {
@@ -160,6 +204,15 @@ implement_clamp!(
     u16::MAX as f64
 );
 
+implement_clamp!(
+    f64,
+    f32,
+    f32::MIN,
+    f32::MAX,
+    f32::MIN as f64,
+    f32::MAX as f64
+);
+
 implement_clamp!(
     i32,
     i16,
@@ -171,9 +224,165 @@ implement_clamp!(
 
 implement_identity_clamp!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
 
+/// Arithmetic on subpixel values which saturates at the bounds of the type
+/// rather than overflowing.
+///
+/// This allows the arithmetic functions in [`pixelops`](../pixelops/index.html) to work
+/// uniformly for any pixel type with integer or floating point channels. For floating point
+/// types the operations are the usual unbounded ones.
+pub trait SaturatingOps: Copy {
+    /// Adds `other` to `self`, saturating at the bounds of the type.
+    fn saturating_add(self, other: Self) -> Self;
+    /// Subtracts `other` from `self`, saturating at the bounds of the type.
+    fn saturating_sub(self, other: Self) -> Self;
+    /// Multiplies `self` by `other`, saturating at the bounds of the type.
+    fn saturating_mul(self, other: Self) -> Self;
+    /// Divides `self` by `other`, saturating at the bounds of the type. Returns
+    /// zero if `other` is zero.
+    fn saturating_div(self, other: Self) -> Self;
+    /// Multiplies `self` by `factor`, rounding to the nearest representable value and
+    /// saturating at the bounds of the type.
+    fn saturating_scale(self, factor: f32) -> Self;
+}
+
+macro_rules! implement_saturating_ops_int {
+    ( $($t:ty),* ) => {
+        $(
+            impl SaturatingOps for $t {
+                fn saturating_add(self, other: $t) -> $t { <$t>::saturating_add(self, other) }
+                fn saturating_sub(self, other: $t) -> $t { <$t>::saturating_sub(self, other) }
+                fn saturating_mul(self, other: $t) -> $t { <$t>::saturating_mul(self, other) }
+                fn saturating_div(self, other: $t) -> $t {
+                    if other == 0 { 0 } else { <$t>::saturating_div(self, other) }
+                }
+                fn saturating_scale(self, factor: f32) -> $t {
+                    // Casts from floats to integers saturate, and map NaN to 0
+                    (self as f64 * factor as f64).round() as $t
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! implement_saturating_ops_float {
+    ( $($t:ty),* ) => {
+        $(
+            impl SaturatingOps for $t {
+                fn saturating_add(self, other: $t) -> $t { self + other }
+                fn saturating_sub(self, other: $t) -> $t { self - other }
+                fn saturating_mul(self, other: $t) -> $t { self * other }
+                fn saturating_div(self, other: $t) -> $t {
+                    if other == 0.0 { 0.0 } else { self / other }
+                }
+                fn saturating_scale(self, factor: f32) -> $t { self * factor as $t }
+            }
+        )*
+    };
+}
+
+implement_saturating_ops_int!(u8, i8, u16, i16, u32, i32, u64, i64);
+implement_saturating_ops_float!(f32, f64);
+
+/// How to treat locations outside the bounds of an image.
+///
+/// The examples show how the pixels `abcd` of a row are extended beyond its ends.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BorderMode<P> {
+    /// Locations outside the image have the given value: `ppp|abcd|ppp`.
+    Constant(P),
+    /// Locations outside the image take the value of the nearest pixel inside it: `aaa|abcd|ddd`.
+    Replicate,
+    /// The image is mirrored at its edges: `cba|abcd|dcb`.
+    Reflect,
+    /// The image is tiled: `bcd|abcd|abc`.
+    Wrap,
+    /// Locations outside the image have no value, and any output pixel that depends
+    /// on them is left unchanged.
+    Transparent,
+}
+
+impl<P> BorderMode<P> {
+    /// Maps a possibly out of bounds coordinate along an axis of length `len` to a
+    /// coordinate in `0..len`.
+    ///
+    /// Returns `None` for coordinates outside `0..len` in the `Constant` and
+    /// `Transparent` modes, and for all coordinates if `len` is 0.
+    pub fn map_coordinate(&self, i: i64, len: u32) -> Option<u32> {
+        let len = len as i64;
+        if i >= 0 && i < len {
+            return Some(i as u32);
+        }
+        if len == 0 {
+            return None;
+        }
+        match self {
+            BorderMode::Constant(_) | BorderMode::Transparent => None,
+            BorderMode::Replicate => Some(i.max(0).min(len - 1) as u32),
+            BorderMode::Reflect => {
+                let m = i.rem_euclid(2 * len);
+                Some(if m < len { m } else { 2 * len - 1 - m } as u32)
+            }
+            BorderMode::Wrap => Some(i.rem_euclid(len) as u32),
+        }
+    }
+}
+
+impl<P> From<P> for BorderMode<P> {
+    /// A pixel value converts to `BorderMode::Constant`, so functions accepting an
+    /// `Into<BorderMode<P>>` can be passed a default pixel value directly.
+    fn from(p: P) -> Self {
+        BorderMode::Constant(p)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Clamp;
+    use super::{BorderMode, Clamp, SaturatingOps};
+
+    #[test]
+    fn test_border_mode_map_coordinate() {
+        let map = |border: BorderMode<u8>| -> Vec<Option<u32>> {
+            (-3..7).map(|i| border.map_coordinate(i, 4)).collect()
+        };
+        let none = None;
+        assert_eq!(
+            map(BorderMode::Constant(0)),
+            vec![
+                none,
+                none,
+                none,
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(3),
+                none,
+                none,
+                none
+            ]
+        );
+        assert_eq!(
+            map(BorderMode::Replicate),
+            [0, 0, 0, 0, 1, 2, 3, 3, 3, 3]
+                .iter()
+                .map(|&i| Some(i))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            map(BorderMode::Reflect),
+            [2, 1, 0, 0, 1, 2, 3, 3, 2, 1]
+                .iter()
+                .map(|&i| Some(i))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            map(BorderMode::Wrap),
+            [1, 2, 3, 0, 1, 2, 3, 0, 1, 2]
+                .iter()
+                .map(|&i| Some(i))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(BorderMode::<u8>::Wrap.map_coordinate(0, 0), None);
+    }
 
     #[test]
     fn test_clamp_f32_u8() {
@@ -198,4 +407,27 @@ mod tests {
         let w: u16 = Clamp::clamp(-5f32);
         assert_eq!(w, 0u16);
     }
+
+    #[test]
+    fn test_saturating_ops_int() {
+        assert_eq!(SaturatingOps::saturating_add(200u8, 100u8), 255);
+        assert_eq!(SaturatingOps::saturating_sub(-100i8, 100i8), -128);
+        assert_eq!(SaturatingOps::saturating_mul(300u16, 300u16), 65535);
+        assert_eq!(SaturatingOps::saturating_div(7u32, 2u32), 3);
+        assert_eq!(SaturatingOps::saturating_div(7u32, 0u32), 0);
+        assert_eq!(SaturatingOps::saturating_div(i16::MIN, -1i16), i16::MAX);
+        assert_eq!(200u8.saturating_scale(0.5), 100);
+        assert_eq!(3u8.saturating_scale(0.5), 2);
+        assert_eq!(200u8.saturating_scale(2.0), 255);
+        assert_eq!(200u8.saturating_scale(-1.0), 0);
+        assert_eq!(200u8.saturating_scale(f32::NAN), 0);
+    }
+
+    #[test]
+    fn test_saturating_ops_float() {
+        assert_eq!(SaturatingOps::saturating_add(0.5f32, 0.75f32), 1.25);
+        assert_eq!(SaturatingOps::saturating_sub(0.5f64, 0.75f64), -0.25);
+        assert_eq!(SaturatingOps::saturating_div(1.0f32, 0.0f32), 0.0);
+        assert_eq!(1.5f32.saturating_scale(-2.0), -3.0);
+    }
 }
@@ -0,0 +1,396 @@
+//! Sliding-window object detection and non-maximum suppression of bounding boxes.
+//!
+//! These functions handle the plumbing common to sliding-window detectors: enumerating
+//! windows over an image or an image pyramid, scoring each window using a user-provided
+//! classifier (for example a linear SVM applied to [HOG](../hog/index.html) features) and
+//! discarding overlapping detections.
+
+use crate::definitions::{Clamp, Image};
+use crate::geometric_transformations::resize_area;
+use crate::rect::Rect;
+use conv::ValueInto;
+use image::{GenericImageView, Pixel, SubImage};
+use std::cmp::Ordering;
+
+/// A bounding box together with a detection score. Higher scores are better.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Detection {
+    /// The bounding box of the detected object.
+    pub rect: Rect,
+    /// The score of the detection.
+    pub score: f32,
+}
+
+/// Returns the area of the intersection of two rectangles divided by the area of their union.
+///
+/// # Examples
+/// ```
+/// use imageproc::detection::intersection_over_union;
+/// use imageproc::rect::Rect;
+///
+/// let a = Rect::at(0, 0).of_size(4, 4);
+/// let b = Rect::at(2, 0).of_size(4, 4);
+/// assert_eq!(intersection_over_union(a, b), 8.0 / 24.0);
+/// assert_eq!(intersection_over_union(a, a), 1.0);
+/// ```
+pub fn intersection_over_union(a: Rect, b: Rect) -> f32 {
+    let area = |r: Rect| r.width() as f32 * r.height() as f32;
+    match a.intersect(b) {
+        Some(i) => {
+            let intersection = area(i);
+            intersection / (area(a) + area(b) - intersection)
+        }
+        None => 0.0,
+    }
+}
+
+/// Greedy non-maximum suppression of overlapping detections.
+///
+/// Detections are considered in order of decreasing score, and each is kept unless its
+/// [intersection over union](fn.intersection_over_union.html) with a previously kept
+/// detection is greater than `iou_threshold`. The kept detections are returned in order of
+/// decreasing score.
+pub fn suppress_overlapping(detections: &[Detection], iou_threshold: f32) -> Vec<Detection> {
+    let mut sorted = detections.to_vec();
+    sorted.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+
+    let mut kept: Vec<Detection> = Vec::new();
+    for d in sorted {
+        if kept
+            .iter()
+            .all(|k| intersection_over_union(k.rect, d.rect) <= iou_threshold)
+        {
+            kept.push(d);
+        }
+    }
+    kept
+}
+
+/// Returns the positions of all windows of the given size that lie entirely within an image
+/// of the given size, with the top left corners of the windows on a grid with spacing `stride`.
+///
+/// Windows are returned in row-major order.
+///
+/// # Panics
+///
+/// If `stride` is zero or either window dimension is zero.
+pub fn sliding_windows(
+    image_width: u32,
+    image_height: u32,
+    window_width: u32,
+    window_height: u32,
+    stride: u32,
+) -> impl ExactSizeIterator<Item = Rect> + Clone {
+    assert!(stride > 0, "stride must be non-zero");
+    assert!(
+        window_width > 0 && window_height > 0,
+        "window dimensions must be non-zero"
+    );
+
+    let count = |image: u32, window: u32| {
+        if image < window {
+            0
+        } else {
+            (image - window) / stride + 1
+        }
+    };
+    let columns = count(image_width, window_width);
+    let rows = count(image_height, window_height);
+
+    (0..rows as usize * columns as usize).map(move |i| {
+        let column = (i % columns as usize) as u32;
+        let row = (i / columns as usize) as u32;
+        Rect::at((column * stride) as i32, (row * stride) as i32)
+            .of_size(window_width, window_height)
+    })
+}
+
+/// Scores every window of each of the given sizes using `score` and returns the windows
+/// with score at least `threshold`, after [suppressing overlapping detections](fn.suppress_overlapping.html)
+/// with intersection over union greater than `iou_threshold`.
+///
+/// Windows are placed as by [`sliding_windows`](fn.sliding_windows.html). Detections are
+/// returned in order of decreasing score.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::{GenericImage, GenericImageView, GrayImage, Luma};
+/// use imageproc::detection::detect;
+/// use imageproc::rect::Rect;
+///
+/// // A bright 4x4 square on a dark background
+/// let mut image = GrayImage::new(20, 20);
+/// image.sub_image(8, 4, 4, 4).copy_from(&GrayImage::from_pixel(4, 4, Luma([255])), 0, 0).unwrap();
+///
+/// // Score each window by its mean intensity
+/// let detections = detect(&image, &[(4, 4)], 2, 200.0, 0.0, |window| {
+///     window.pixels().map(|(_, _, p)| p[0] as f32).sum::<f32>() / 16.0
+/// });
+///
+/// assert_eq!(detections.len(), 1);
+/// assert_eq!(detections[0].rect, Rect::at(8, 4).of_size(4, 4));
+/// # }
+/// ```
+pub fn detect<I, F>(
+    image: &I,
+    window_sizes: &[(u32, u32)],
+    stride: u32,
+    threshold: f32,
+    iou_threshold: f32,
+    mut score: F,
+) -> Vec<Detection>
+where
+    I: GenericImageView,
+    F: FnMut(&SubImage<&I>) -> f32,
+{
+    let mut detections = Vec::new();
+    collect_detections(
+        image,
+        window_sizes,
+        stride,
+        threshold,
+        1.0,
+        &mut score,
+        &mut detections,
+    );
+    suppress_overlapping(&detections, iou_threshold)
+}
+
+/// Scores windows of a fixed size over an image pyramid using `score` and returns the
+/// windows with score at least `threshold`, after [suppressing overlapping detections](fn.suppress_overlapping.html)
+/// with intersection over union greater than `iou_threshold`.
+///
+/// The first level of the pyramid is the input image, and each subsequent level is smaller than
+/// the previous one by a factor of `scale_factor`. Windows are placed on every level which is
+/// at least as large as the window, as by [`sliding_windows`](fn.sliding_windows.html). This
+/// allows detecting objects which appear larger than the window in the input image.
+///
+/// Returned detections are in the coordinates of the input image, so detections from
+/// smaller pyramid levels have larger bounding boxes. Detections are returned in order of
+/// decreasing score.
+///
+/// # Panics
+///
+/// If `scale_factor` is not greater than 1.
+pub fn detect_multiscale<P, F>(
+    image: &Image<P>,
+    window_size: (u32, u32),
+    stride: u32,
+    scale_factor: f32,
+    threshold: f32,
+    iou_threshold: f32,
+    mut score: F,
+) -> Vec<Detection>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+    F: FnMut(&SubImage<&Image<P>>) -> f32,
+{
+    assert!(scale_factor > 1.0, "scale_factor must be greater than 1");
+
+    let (window_width, window_height) = window_size;
+    let mut detections = Vec::new();
+    let mut scale = 1.0f32;
+
+    loop {
+        let width = (image.width() as f32 / scale).round() as u32;
+        let height = (image.height() as f32 / scale).round() as u32;
+        if width < window_width || height < window_height {
+            break;
+        }
+
+        if scale == 1.0 {
+            collect_detections(
+                image,
+                &[window_size],
+                stride,
+                threshold,
+                scale,
+                &mut score,
+                &mut detections,
+            );
+        } else {
+            let level = resize_area(image, width, height);
+            collect_detections(
+                &level,
+                &[window_size],
+                stride,
+                threshold,
+                scale,
+                &mut score,
+                &mut detections,
+            );
+        }
+
+        scale *= scale_factor;
+    }
+
+    suppress_overlapping(&detections, iou_threshold)
+}
+
+// Scores all windows of the given sizes, and adds those with score at least threshold to
+// detections, after scaling their positions and sizes by scale.
+fn collect_detections<I, F>(
+    image: &I,
+    window_sizes: &[(u32, u32)],
+    stride: u32,
+    threshold: f32,
+    scale: f32,
+    score: &mut F,
+    detections: &mut Vec<Detection>,
+) where
+    I: GenericImageView,
+    F: FnMut(&SubImage<&I>) -> f32,
+{
+    let (width, height) = image.dimensions();
+    for &(window_width, window_height) in window_sizes {
+        for window in sliding_windows(width, height, window_width, window_height, stride) {
+            let view = SubImage::new(
+                image,
+                window.left() as u32,
+                window.top() as u32,
+                window.width(),
+                window.height(),
+            );
+            let s = score(&view);
+            if s >= threshold {
+                let scaled = |v: f32| (v * scale).round();
+                let rect = Rect::at(
+                    scaled(window.left() as f32) as i32,
+                    scaled(window.top() as f32) as i32,
+                )
+                .of_size(
+                    scaled(window.width() as f32).max(1.0) as u32,
+                    scaled(window.height() as f32).max(1.0) as u32,
+                );
+                detections.push(Detection { rect, score: s });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImage, GrayImage, Luma};
+
+    fn detection(x: i32, y: i32, size: u32, score: f32) -> Detection {
+        Detection {
+            rect: Rect::at(x, y).of_size(size, size),
+            score,
+        }
+    }
+
+    fn mean(window: &SubImage<&GrayImage>) -> f32 {
+        let (width, height) = window.dimensions();
+        let sum: f32 = window.pixels().map(|(_, _, p)| p[0] as f32).sum();
+        sum / (width * height) as f32
+    }
+
+    fn square_image(width: u32, height: u32, x: u32, y: u32, size: u32) -> GrayImage {
+        let mut image = GrayImage::new(width, height);
+        image
+            .sub_image(x, y, size, size)
+            .copy_from(&GrayImage::from_pixel(size, size, Luma([255])), 0, 0)
+            .unwrap();
+        image
+    }
+
+    #[test]
+    fn test_intersection_over_union_disjoint() {
+        let a = Rect::at(0, 0).of_size(2, 2);
+        let b = Rect::at(2, 2).of_size(2, 2);
+        assert_eq!(intersection_over_union(a, b), 0.0);
+    }
+
+    #[test]
+    fn test_suppress_overlapping() {
+        let detections = vec![
+            detection(0, 0, 10, 0.5),
+            detection(1, 1, 10, 0.9),
+            detection(20, 20, 10, 0.2),
+            detection(5, 0, 10, 0.7),
+        ];
+
+        let kept = suppress_overlapping(&detections, 0.3);
+        assert_eq!(
+            kept,
+            vec![detection(1, 1, 10, 0.9), detection(20, 20, 10, 0.2)]
+        );
+
+        // (5, 0) overlaps (1, 1) with IoU 54 / 146
+        let kept = suppress_overlapping(&detections, 0.4);
+        assert_eq!(
+            kept,
+            vec![
+                detection(1, 1, 10, 0.9),
+                detection(5, 0, 10, 0.7),
+                detection(20, 20, 10, 0.2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sliding_windows() {
+        let windows: Vec<Rect> = sliding_windows(7, 5, 3, 2, 2).collect();
+        let expected: Vec<Rect> = [(0, 0), (2, 0), (4, 0), (0, 2), (2, 2), (4, 2)]
+            .iter()
+            .map(|&(x, y)| Rect::at(x, y).of_size(3, 2))
+            .collect();
+        assert_eq!(windows, expected);
+    }
+
+    #[test]
+    fn test_sliding_windows_larger_than_image() {
+        assert_eq!(sliding_windows(3, 3, 4, 2, 1).count(), 0);
+    }
+
+    #[test]
+    fn test_detect_multiple_window_sizes() {
+        let image = square_image(30, 30, 4, 10, 6);
+        let detections = detect(&image, &[(4, 4), (6, 6)], 1, 250.0, 0.1, mean);
+
+        // All windows inside the square score 255, but they overlap the full-size window
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].score, 255.0);
+    }
+
+    #[test]
+    fn test_detect_multiscale() {
+        // Scores how closely a window matches a bright square centred on a dark background
+        // with the same size as the square
+        let centre_surround = |window: &SubImage<&GrayImage>| {
+            let (mut inner, mut outer) = (0.0, 0.0);
+            for (x, y, p) in window.pixels() {
+                if (2..6).contains(&x) && (2..6).contains(&y) {
+                    inner += p[0] as f32 / 16.0;
+                } else {
+                    outer += p[0] as f32 / 48.0;
+                }
+            }
+            inner - outer
+        };
+
+        // The square only fits the window at half resolution
+        let image = square_image(64, 64, 20, 28, 8);
+        let detections = detect_multiscale(&image, (8, 8), 1, 2.0, 250.0, 0.3, centre_surround);
+
+        assert_eq!(
+            detections,
+            vec![Detection {
+                rect: Rect::at(16, 24).of_size(16, 16),
+                score: 255.0
+            }]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_detect_multiscale_rejects_small_scale_factor() {
+        let image = GrayImage::new(10, 10);
+        let _ = detect_multiscale(&image, (4, 4), 1, 1.0, 0.0, 0.5, mean);
+    }
+}
@@ -2,6 +2,7 @@
 //! image from the nearest pixel of interest.
 
 use crate::definitions::Image;
+use crate::parallel::for_each_row;
 use image::{GenericImage, GenericImageView, GrayImage, ImageBuffer, Luma};
 use std::cmp::min;
 use std::{f64, u8};
@@ -26,6 +27,53 @@ pub enum Norm {
     /// Defines d((x1, y1), (x2, y2)) to be max(abs(x1 - x2), abs(y1 - y2)).
     /// Also known as the chessboard norm.
     LInf,
+    /// The 3-4 chamfer distance, an approximation to the `L2` norm in which horizontal
+    /// and vertical steps have weight 3 and diagonal steps have weight 4.
+    ///
+    /// [`distance_transform`](fn.distance_transform.html) divides distances by 3, rounding
+    /// to the nearest integer. Use
+    /// [`chamfer_distance_transform`](fn.chamfer_distance_transform.html) to get the
+    /// weighted distances.
+    Chamfer34,
+    /// The 5-7-11 chamfer distance, an approximation to the `L2` norm in which horizontal
+    /// and vertical steps have weight 5, diagonal steps have weight 7 and knight's move steps
+    /// (such as (2, 1)) have weight 11.
+    ///
+    /// [`distance_transform`](fn.distance_transform.html) divides distances by 5, rounding
+    /// to the nearest integer. Use
+    /// [`chamfer_distance_transform`](fn.chamfer_distance_transform.html) to get the
+    /// weighted distances.
+    Chamfer5711,
+}
+
+impl Norm {
+    // The weight of a horizontal or vertical step.
+    fn unit_weight(self) -> u32 {
+        match self {
+            Norm::L1 | Norm::LInf => 1,
+            Norm::Chamfer34 => 3,
+            Norm::Chamfer5711 => 5,
+        }
+    }
+
+    // The offsets and weights of the neighbours which precede a pixel in raster order.
+    fn forward_mask(self) -> &'static [(i32, i32, u32)] {
+        match self {
+            Norm::L1 => &[(-1, 0, 1), (0, -1, 1)],
+            Norm::LInf => &[(-1, 0, 1), (-1, -1, 1), (0, -1, 1), (1, -1, 1)],
+            Norm::Chamfer34 => &[(-1, 0, 3), (-1, -1, 4), (0, -1, 3), (1, -1, 4)],
+            Norm::Chamfer5711 => &[
+                (-1, 0, 5),
+                (-2, -1, 11),
+                (-1, -1, 7),
+                (0, -1, 5),
+                (1, -1, 7),
+                (2, -1, 11),
+                (-1, -2, 11),
+                (1, -2, 11),
+            ],
+        }
+    }
 }
 
 /// Returns an image showing the distance of each pixel from a foreground pixel in the original image.
@@ -96,6 +144,113 @@ pub(crate) enum DistanceFrom {
 }
 
 pub(crate) fn distance_transform_impl(image: &mut GrayImage, norm: Norm, from: DistanceFrom) {
+    match norm {
+        Norm::L1 => {
+            // The L1 norm is separable, so we find the distance to the nearest source pixel in
+            // each column and then combine these distances along each row. The row transforms
+            // are independent, so are computed in parallel.
+            let width = image.width() as usize;
+            column_distance_transform(image, from);
+            for_each_row(image, width, |_, row| l1_row_transform(row));
+        }
+        // LInf is also separable, but its row transform requires computing a lower envelope
+        // and is slower than a raster scan unless many threads are available.
+        Norm::LInf => raster_distance_transform(image, norm, from),
+        Norm::Chamfer34 | Norm::Chamfer5711 => {
+            let distances = chamfer_distance_transform_impl(image, norm, from);
+            let unit = norm.unit_weight();
+            for (p, d) in image.iter_mut().zip(distances.iter()) {
+                let rounded = d.saturating_add(unit / 2) / unit;
+                *p = min(rounded, 255) as u8;
+            }
+        }
+    }
+}
+
+// Sets each pixel to within if its distance from the nearest source pixel is at most k, and to
+// beyond otherwise. Distances are as computed by distance_transform_impl.
+pub(crate) fn within_distance_impl(
+    image: &mut GrayImage,
+    norm: Norm,
+    from: DistanceFrom,
+    k: u8,
+    within: u8,
+    beyond: u8,
+) {
+    let threshold = |p: &mut u8| *p = if *p <= k { within } else { beyond };
+    if norm != Norm::L1 && norm != Norm::LInf {
+        distance_transform_impl(image, norm, from);
+        image.iter_mut().for_each(threshold);
+        return;
+    }
+    let width = image.width() as usize;
+    column_distance_transform(image, from);
+    for_each_row(image, width, |_, row| {
+        if norm == Norm::LInf {
+            // A pixel is within LInf distance k of a source pixel if and only if some pixel
+            // in the same row and within distance k of it has column distance at most k.
+            for p in row.iter_mut() {
+                *p = if *p <= k { 0 } else { u8::MAX };
+            }
+        }
+        l1_row_transform(row);
+        row.iter_mut().for_each(threshold);
+    });
+}
+
+// Sets each pixel to its distance from the nearest source pixel in the same column, or to
+// min(width + height, 255) if there is no such pixel.
+fn column_distance_transform(image: &mut GrayImage, from: DistanceFrom) {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    if width == 0 || height == 0 {
+        return;
+    }
+    let max_distance = min(width + height, 255) as u16;
+    let is_source = |p: u8| match from {
+        DistanceFrom::Foreground => p > 0,
+        DistanceFrom::Background => p == 0,
+    };
+
+    // Arithmetic is performed at type u16 to avoid overflow, as u8::saturating_add is slower.
+    let data: &mut [u8] = image;
+    for p in &mut data[..width] {
+        *p = if is_source(*p) { 0 } else { max_distance as u8 };
+    }
+    for y in 1..height {
+        let (above, rest) = data.split_at_mut(y * width);
+        for (p, &a) in rest[..width].iter_mut().zip(&above[(y - 1) * width..]) {
+            *p = if is_source(*p) {
+                0
+            } else {
+                min(a as u16 + 1, max_distance) as u8
+            };
+        }
+    }
+    for y in (0..height - 1).rev() {
+        let (upto, below) = data.split_at_mut((y + 1) * width);
+        for (p, &b) in upto[y * width..].iter_mut().zip(&below[..width]) {
+            *p = min(*p as u16, b as u16 + 1) as u8;
+        }
+    }
+}
+
+// Replaces each value row[x] with the minimum over x' of row[x'] + |x - x'|.
+fn l1_row_transform(row: &mut [u8]) {
+    let mut d = row[0] as u16;
+    for p in row.iter_mut().skip(1) {
+        d = min(*p as u16, d + 1);
+        *p = d as u8;
+    }
+    let mut d = row[row.len() - 1] as u16;
+    for p in row.iter_mut().rev().skip(1) {
+        d = min(*p as u16, d + 1);
+        *p = d as u8;
+    }
+}
+
+// Computes distances using two raster scans, each of which propagates distances from the
+// neighbours of a pixel which precede it in that scan.
+fn raster_distance_transform(image: &mut GrayImage, norm: Norm, from: DistanceFrom) {
     let max_distance = Luma([min(image.width() + image.height(), 255u32) as u8]);
 
     unsafe {
@@ -174,6 +329,100 @@ unsafe fn check(
     }
 }
 
+/// Returns an image showing the weighted distance of each pixel from a foreground pixel in
+/// the original image.
+///
+/// A pixel belongs to the foreground if it has non-zero intensity. Distances are the
+/// minimum total weight of a path of steps to a foreground pixel, where the weight of each
+/// step is given by `norm`. Steps have weight 1 for `Norm::L1` and `Norm::LInf`, so this
+/// is equal to [`distance_transform`](fn.distance_transform.html) for those norms except
+/// that distances do not saturate at 255. Pixels are `u32::MAX` if `image` has no
+/// foreground pixels.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::distance_transform::{chamfer_distance_transform, Norm};
+///
+/// let image = gray_image!(
+///     0,   0,   0,   0,   0;
+///     0,   0,   0,   0,   0;
+///     0,   0,   1,   0,   0;
+///     0,   0,   0,   0,   0;
+///     0,   0,   0,   0,   0
+/// );
+///
+/// let chamfer_34 = gray_image!(type: u32,
+///     8,   7,   6,   7,   8;
+///     7,   4,   3,   4,   7;
+///     6,   3,   0,   3,   6;
+///     7,   4,   3,   4,   7;
+///     8,   7,   6,   7,   8
+/// );
+///
+/// assert_pixels_eq!(chamfer_distance_transform(&image, Norm::Chamfer34), chamfer_34);
+///
+/// let chamfer_5711 = gray_image!(type: u32,
+///     14, 11,  10,  11,  14;
+///     11,  7,   5,   7,  11;
+///     10,  5,   0,   5,  10;
+///     11,  7,   5,   7,  11;
+///     14, 11,  10,  11,  14
+/// );
+///
+/// assert_pixels_eq!(chamfer_distance_transform(&image, Norm::Chamfer5711), chamfer_5711);
+/// # }
+/// ```
+pub fn chamfer_distance_transform(image: &GrayImage, norm: Norm) -> Image<Luma<u32>> {
+    chamfer_distance_transform_impl(image, norm, DistanceFrom::Foreground)
+}
+
+// Two pass weighted distance transform using the mask for the given norm.
+fn chamfer_distance_transform_impl(
+    image: &GrayImage,
+    norm: Norm,
+    from: DistanceFrom,
+) -> Image<Luma<u32>> {
+    let (width, height) = (image.width() as i32, image.height() as i32);
+    let mut distances = ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let foreground = image.get_pixel(x, y)[0] > 0;
+        if foreground == (from == DistanceFrom::Foreground) {
+            Luma([0u32])
+        } else {
+            Luma([u32::MAX])
+        }
+    });
+
+    let mask = norm.forward_mask();
+    let mut relax = |x: i32, y: i32, sign: i32| {
+        let mut best = distances.get_pixel(x as u32, y as u32)[0];
+        for &(dx, dy, weight) in mask {
+            let (nx, ny) = (x + sign * dx, y + sign * dy);
+            if nx >= 0 && ny >= 0 && nx < width && ny < height {
+                let candidate = distances.get_pixel(nx as u32, ny as u32)[0].saturating_add(weight);
+                best = min(best, candidate);
+            }
+        }
+        distances.put_pixel(x as u32, y as u32, Luma([best]));
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            relax(x, y, 1);
+        }
+    }
+    for y in (0..height).rev() {
+        for x in (0..width).rev() {
+            relax(x, y, -1);
+        }
+    }
+
+    distances
+}
+
 /// Computes the square of the `L2` (Euclidean) distance transform of `image`. Distances are to the
 /// nearest foreground pixel, where a pixel is counted as foreground if it has non-zero value.
 ///
@@ -214,6 +463,262 @@ pub fn euclidean_squared_distance_transform(image: &Image<Luma<u8>>) -> Image<Lu
     result
 }
 
+/// Computes the exact `L2` (Euclidean) distance of each pixel in `image` from the nearest
+/// foreground pixel, where a pixel is counted as foreground if it has non-zero value.
+///
+/// This is the square root of the
+/// [`euclidean_squared_distance_transform`](fn.euclidean_squared_distance_transform.html),
+/// stored at single precision. Unlike the `L1` and `LInf` distance transforms computed by
+/// [`distance_transform`](fn.distance_transform.html), distances do not saturate. If `image`
+/// has no foreground pixels then all distances are infinite.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::distance_transform::euclidean_distance_transform;
+///
+/// let image = gray_image!(
+///     1, 0, 0;
+///     0, 0, 0;
+///     0, 0, 0
+/// );
+///
+/// let distances = gray_image!(type: f32,
+///     0.0, 1.0,           2.0;
+///     1.0, 2f32.sqrt(),   5f32.sqrt();
+///     2.0, 5f32.sqrt(),   8f32.sqrt()
+/// );
+///
+/// assert_pixels_eq!(euclidean_distance_transform(&image), distances);
+/// # }
+/// ```
+pub fn euclidean_distance_transform(image: &Image<Luma<u8>>) -> Image<Luma<f32>> {
+    let squared = euclidean_squared_distance_transform(image);
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        Luma([squared.get_pixel(x, y)[0].sqrt() as f32])
+    })
+}
+
+/// Computes the signed Euclidean distance of each pixel in `image` from the boundary between
+/// the foreground and background, where a pixel is counted as foreground if it has non-zero
+/// value.
+///
+/// Background pixels have a positive value equal to their distance from the nearest
+/// foreground pixel, and foreground pixels have a negative value whose magnitude is their
+/// distance from the nearest background pixel. Every pixel therefore has magnitude at least 1,
+/// and the boundary between foreground and background lies between pixels of opposite sign.
+/// If `image` has no foreground pixels then all values are positive infinity, and if it has
+/// no background pixels then all values are negative infinity.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::distance_transform::signed_distance_transform;
+///
+/// let image = gray_image!(
+///     0, 0, 0, 0, 0;
+///     0, 1, 1, 1, 0;
+///     0, 1, 1, 1, 0;
+///     0, 1, 1, 1, 0;
+///     0, 0, 0, 0, 0
+/// );
+///
+/// let r2 = 2f32.sqrt();
+/// let distances = gray_image!(type: f32,
+///     r2,   1.0,  1.0,  1.0,  r2;
+///     1.0, -1.0, -1.0, -1.0,  1.0;
+///     1.0, -1.0, -2.0, -1.0,  1.0;
+///     1.0, -1.0, -1.0, -1.0,  1.0;
+///     r2,   1.0,  1.0,  1.0,  r2
+/// );
+///
+/// assert_pixels_eq!(signed_distance_transform(&image), distances);
+/// # }
+/// ```
+pub fn signed_distance_transform(image: &Image<Luma<u8>>) -> Image<Luma<f32>> {
+    let background = ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        Luma([(image.get_pixel(x, y)[0] == 0) as u8])
+    });
+    let outside = euclidean_squared_distance_transform(image);
+    let inside = euclidean_squared_distance_transform(&background);
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let distance = if image.get_pixel(x, y)[0] > 0 {
+            -inside.get_pixel(x, y)[0].sqrt()
+        } else {
+            outside.get_pixel(x, y)[0].sqrt()
+        };
+        Luma([distance as f32])
+    })
+}
+
+/// The result of [`nearest_features`](fn.nearest_features.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearestFeatures {
+    /// The squared Euclidean distance of each pixel from its nearest feature pixel.
+    pub squared_distances: Image<Luma<f64>>,
+    nearest: Vec<Option<(u32, u32)>>,
+}
+
+impl NearestFeatures {
+    /// The coordinates of the feature pixel nearest to `(x, y)`, or `None` if there
+    /// are no feature pixels. Ties are broken arbitrarily.
+    ///
+    /// # Panics
+    ///
+    /// If `(x, y)` is outside the image.
+    pub fn nearest(&self, x: u32, y: u32) -> Option<(u32, u32)> {
+        let (width, height) = self.squared_distances.dimensions();
+        assert!(
+            x < width && y < height,
+            "({}, {}) is outside the image",
+            x,
+            y
+        );
+        self.nearest[(y * width + x) as usize]
+    }
+}
+
+/// Computes the [`euclidean_squared_distance_transform`] of `image`, and the location of the
+/// nearest foreground pixel to each pixel, i.e. a discrete Voronoi diagram of the foreground
+/// pixels. A pixel is counted as foreground if it has non-zero value.
+///
+/// [`euclidean_squared_distance_transform`]: fn.euclidean_squared_distance_transform.html
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::distance_transform::nearest_features;
+///
+/// let image = gray_image!(
+///     1, 0, 0, 0, 0;
+///     0, 0, 0, 0, 1
+/// );
+///
+/// let features = nearest_features(&image);
+/// assert_eq!(features.nearest(1, 1), Some((0, 0)));
+/// assert_eq!(features.nearest(3, 0), Some((4, 1)));
+/// assert_eq!(features.squared_distances.get_pixel(3, 0)[0], 2.0);
+/// # }
+/// ```
+pub fn nearest_features(image: &Image<Luma<u8>>) -> NearestFeatures {
+    nearest_features_impl(image.width(), image.height(), |x, y| {
+        image.get_pixel(x, y)[0] > 0
+    })
+}
+
+/// Assigns each pixel the label of its nearest labelled pixel, as measured by Euclidean
+/// distance. Pixels with label 0 are unlabelled. If there are no labelled pixels then the
+/// result is all zeros.
+///
+/// This is useful for assigning pixels to their nearest seed, and for computing skeletons by
+/// influence zones (the boundaries between the regions of the result).
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::distance_transform::voronoi_labels;
+///
+/// let seeds = gray_image!(type: u32,
+///     1, 0, 0, 0, 0, 0;
+///     0, 0, 0, 0, 0, 0;
+///     0, 0, 0, 0, 0, 2
+/// );
+///
+/// let expected = gray_image!(type: u32,
+///     1, 1, 1, 2, 2, 2;
+///     1, 1, 1, 2, 2, 2;
+///     1, 1, 1, 2, 2, 2
+/// );
+///
+/// assert_pixels_eq!(voronoi_labels(&seeds), expected);
+/// # }
+/// ```
+pub fn voronoi_labels(labels: &Image<Luma<u32>>) -> Image<Luma<u32>> {
+    let features = nearest_features_impl(labels.width(), labels.height(), |x, y| {
+        labels.get_pixel(x, y)[0] > 0
+    });
+    ImageBuffer::from_fn(labels.width(), labels.height(), |x, y| {
+        match features.nearest(x, y) {
+            Some((fx, fy)) => *labels.get_pixel(fx, fy),
+            None => Luma([0]),
+        }
+    })
+}
+
+fn nearest_features_impl<F>(width: u32, height: u32, is_feature: F) -> NearestFeatures
+where
+    F: Fn(u32, u32) -> bool,
+{
+    let (w, h) = (width as usize, height as usize);
+
+    // Squared vertical distance to, and row of, the nearest feature in each column,
+    // stored in column-major order
+    let mut column_distances = vec![0f64; w * h];
+    let mut column_locations = vec![0usize; w * h];
+    let mut column_buffer = vec![0f64; h];
+    let mut column_envelope = LowerEnvelope::new(h);
+
+    for x in 0..w {
+        for y in 0..h {
+            column_buffer[y] = if is_feature(x as u32, y as u32) {
+                0.0
+            } else {
+                f64::INFINITY
+            };
+        }
+        let mut sink = WithLocations {
+            distances: &mut column_distances[x * h..(x + 1) * h],
+            locations: &mut column_locations[x * h..(x + 1) * h],
+        };
+        distance_transform_1d_mut(&column_buffer, &mut sink, &mut column_envelope);
+    }
+
+    let mut squared_distances = ImageBuffer::new(width, height);
+    let mut nearest = vec![None; w * h];
+    let mut row_buffer = vec![0f64; w];
+    let mut row_distances = vec![0f64; w];
+    let mut row_locations = vec![0usize; w];
+    let mut row_envelope = LowerEnvelope::new(w);
+
+    for y in 0..h {
+        for x in 0..w {
+            row_buffer[x] = column_distances[x * h + y];
+        }
+        let mut sink = WithLocations {
+            distances: &mut row_distances,
+            locations: &mut row_locations,
+        };
+        distance_transform_1d_mut(&row_buffer, &mut sink, &mut row_envelope);
+
+        for x in 0..w {
+            let distance = row_distances[x];
+            squared_distances.put_pixel(x as u32, y as u32, Luma([distance]));
+            if distance < f64::INFINITY {
+                let fx = row_locations[x];
+                let fy = column_locations[fx * h + y];
+                nearest[y * w + x] = Some((fx as u32, fy as u32));
+            }
+        }
+    }
+
+    NearestFeatures {
+        squared_distances,
+        nearest,
+    }
+}
+
 struct LowerEnvelope {
     // Indices of the parabolas in the lower envelope.
     locations: Vec<usize>,
@@ -236,6 +741,8 @@ impl LowerEnvelope {
 trait Sink {
     fn put(&mut self, idx: usize, value: f64);
     fn len(&self) -> usize;
+    // Records the index of the input which gave the least value at idx.
+    fn put_location(&mut self, _idx: usize, _location: usize) {}
 }
 
 trait Source {
@@ -381,6 +888,25 @@ where
         }
         let dist = q as f64 - envelope.locations[k] as f64;
         result.put(q, dist * dist + f.get(envelope.locations[k]));
+        result.put_location(q, envelope.locations[k]);
+    }
+}
+
+// A sink which records both distances and the locations that they were computed from.
+struct WithLocations<'a> {
+    distances: &'a mut [f64],
+    locations: &'a mut [usize],
+}
+
+impl<'a> Sink for WithLocations<'a> {
+    fn put(&mut self, idx: usize, value: f64) {
+        self.distances[idx] = value;
+    }
+    fn len(&self) -> usize {
+        self.distances.len()
+    }
+    fn put_location(&mut self, idx: usize, location: usize) {
+        self.locations[idx] = location;
     }
 }
 
@@ -428,6 +954,56 @@ mod tests {
         assert_pixels_eq!(distances, expected);
     }
 
+    fn distance_transform_reference(image: &GrayImage, norm: Norm) -> GrayImage {
+        let sources: Vec<(i64, i64)> = image
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p[0] > 0)
+            .map(|(x, y, _)| (x as i64, y as i64))
+            .collect();
+        let max_distance = min(image.width() + image.height(), 255) as i64;
+        GrayImage::from_fn(image.width(), image.height(), |x, y| {
+            let (x, y) = (x as i64, y as i64);
+            let d = sources
+                .iter()
+                .map(|&(sx, sy)| {
+                    let (dx, dy) = ((x - sx).abs(), (y - sy).abs());
+                    match norm {
+                        Norm::L1 => dx + dy,
+                        _ => max(dx, dy),
+                    }
+                })
+                .min()
+                .unwrap_or(max_distance);
+            Luma([min(d, max_distance) as u8])
+        })
+    }
+
+    #[test]
+    fn test_distance_transform_matches_reference_implementation() {
+        fn prop(image: GrayTestImage) -> TestResult {
+            for &norm in &[Norm::L1, Norm::LInf] {
+                let expected = distance_transform_reference(&image.0, norm);
+                let actual = distance_transform(&image.0, norm);
+                if let Some(err) = pixel_diff_summary(&actual, &expected) {
+                    return TestResult::error(err);
+                }
+            }
+            TestResult::passed()
+        }
+        quickcheck(prop as fn(GrayTestImage) -> TestResult);
+    }
+
+    #[test]
+    fn test_distance_transform_large_sparse_image() {
+        let image = GrayImage::from_fn(300, 270, |x, y| {
+            Luma([((x * 7 + y * 13) % 997 == 0 || (x, y) == (299, 5)) as u8])
+        });
+        for &norm in &[Norm::L1, Norm::LInf] {
+            let expected = distance_transform_reference(&image, norm);
+            assert_pixels_eq!(distance_transform(&image, norm), expected);
+        }
+    }
+
     impl<'a> Sink for Vec<f64> {
         fn put(&mut self, idx: usize, value: f64) {
             self[idx] = value;
@@ -560,6 +1136,155 @@ mod tests {
         assert_pixels_eq_within!(dist, expected, 1e-6);
     }
 
+    #[test]
+    fn test_euclidean_distance_transform_matches_squared_transform() {
+        fn prop(image: GrayTestImage) -> TestResult {
+            let squared = euclidean_squared_distance_transform(&image.0);
+            let expected: Image<Luma<f32>> =
+                ImageBuffer::from_fn(squared.width(), squared.height(), |x, y| {
+                    Luma([squared.get_pixel(x, y)[0].sqrt() as f32])
+                });
+            let actual = euclidean_distance_transform(&image.0);
+            match pixel_diff_summary(&actual, &expected) {
+                None => TestResult::passed(),
+                Some(err) => TestResult::error(err),
+            }
+        }
+        quickcheck(prop as fn(GrayTestImage) -> TestResult);
+    }
+
+    #[test]
+    fn test_euclidean_distance_transform_no_foreground() {
+        let image = GrayImage::new(3, 2);
+        let distances = euclidean_distance_transform(&image);
+        assert!(distances.iter().all(|d| d.is_infinite()));
+    }
+
+    #[test]
+    fn test_signed_distance_transform_sign_matches_foreground() {
+        fn prop(image: GrayTestImage) -> bool {
+            let image = image.0;
+            let distances = signed_distance_transform(&image);
+            image
+                .pixels()
+                .zip(distances.pixels())
+                .all(|(p, d)| (p[0] > 0) == (d[0] < 0.0) && d[0].abs() >= 1.0)
+        }
+        quickcheck(prop as fn(GrayTestImage) -> bool);
+    }
+
+    #[test]
+    fn test_signed_distance_transform_uniform_images() {
+        let background = GrayImage::new(3, 2);
+        let distances = signed_distance_transform(&background);
+        assert!(distances.iter().all(|&d| d == f32::INFINITY));
+
+        let foreground = GrayImage::from_pixel(3, 2, Luma([1u8]));
+        let distances = signed_distance_transform(&foreground);
+        assert!(distances.iter().all(|&d| d == f32::NEG_INFINITY));
+    }
+
+    #[test]
+    fn test_chamfer_distance_transform_matches_distance_transform() {
+        fn prop(image: GrayTestImage) -> TestResult {
+            for &norm in &[Norm::L1, Norm::LInf] {
+                let expected = distance_transform(&image.0, norm);
+                let chamfer = chamfer_distance_transform(&image.0, norm);
+                // distance_transform fills images with no foreground with this value
+                let max_distance = min(image.0.width() + image.0.height(), 255);
+                let actual = GrayImage::from_fn(chamfer.width(), chamfer.height(), |x, y| {
+                    Luma([min(chamfer.get_pixel(x, y)[0], max_distance) as u8])
+                });
+                if let Some(err) = pixel_diff_summary(&actual, &expected) {
+                    return TestResult::error(err);
+                }
+            }
+            TestResult::passed()
+        }
+        quickcheck(prop as fn(GrayTestImage) -> TestResult);
+    }
+
+    #[test]
+    fn test_chamfer_distance_transform_approximates_euclidean() {
+        let image = GrayImage::from_fn(41, 41, |x, y| Luma([(x == 20 && y == 20) as u8]));
+        for &(norm, max_error) in &[(Norm::Chamfer34, 0.09), (Norm::Chamfer5711, 0.03)] {
+            let distances = chamfer_distance_transform(&image, norm);
+            let unit = norm.unit_weight() as f32;
+            for (x, y, d) in distances.enumerate_pixels() {
+                let (dx, dy) = (x as f32 - 20.0, y as f32 - 20.0);
+                let euclidean = (dx * dx + dy * dy).sqrt();
+                let error = (d[0] as f32 / unit - euclidean).abs();
+                assert!(
+                    error <= max_error * euclidean,
+                    "{:?} at ({}, {})",
+                    norm,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_transform_chamfer_34() {
+        let image = gray_image!(1, 0, 0, 0, 0, 0, 0);
+        let expected = gray_image!(0, 1, 2, 3, 4, 5, 6);
+        assert_pixels_eq!(distance_transform(&image, Norm::Chamfer34), expected);
+
+        // Diagonal steps have weight 4 / 3
+        let image = gray_image!(
+            1, 0, 0, 0;
+            0, 0, 0, 0;
+            0, 0, 0, 0;
+            0, 0, 0, 0
+        );
+        let expected = gray_image!(
+            0, 1, 2, 3;
+            1, 1, 2, 3;
+            2, 2, 3, 4;
+            3, 3, 4, 4
+        );
+        assert_pixels_eq!(distance_transform(&image, Norm::Chamfer34), expected);
+    }
+
+    #[test]
+    fn test_nearest_features_matches_reference_implementation() {
+        fn prop(image: GrayTestImage) -> TestResult {
+            let image = image.0;
+            let expected = euclidean_squared_distance_transform_reference(&image);
+            let features = nearest_features(&image);
+            if let Some(err) = pixel_diff_summary(&features.squared_distances, &expected) {
+                return TestResult::error(err);
+            }
+            for (x, y, d) in expected.enumerate_pixels() {
+                match features.nearest(x, y) {
+                    Some((fx, fy)) => {
+                        let (dx, dy) = (fx as f64 - x as f64, fy as f64 - y as f64);
+                        if image.get_pixel(fx, fy)[0] == 0 || dx * dx + dy * dy != d[0] {
+                            return TestResult::error(format!(
+                                "({}, {}) has nearest feature ({}, {})",
+                                x, y, fx, fy
+                            ));
+                        }
+                    }
+                    None => {
+                        if d[0] != f64::INFINITY {
+                            return TestResult::error(format!("({}, {}) has no feature", x, y));
+                        }
+                    }
+                }
+            }
+            TestResult::passed()
+        }
+        quickcheck(prop as fn(GrayTestImage) -> TestResult);
+    }
+
+    #[test]
+    fn test_voronoi_labels_no_seeds() {
+        let labels: Image<Luma<u32>> = Image::new(4, 3);
+        assert_pixels_eq!(voronoi_labels(&labels), labels);
+    }
+
     macro_rules! bench_euclidean_squared_distance_transform {
         ($name:ident, side: $s:expr) => {
             #[bench]
@@ -596,4 +1321,6 @@ mod tests {
     bench_distance_transform!(bench_distance_transform_linf_10, Norm::LInf, side: 10);
     bench_distance_transform!(bench_distance_transform_linf_100, Norm::LInf, side: 100);
     bench_distance_transform!(bench_distance_transform_linf_200, Norm::LInf, side: 200);
+    bench_distance_transform!(bench_distance_transform_chamfer_34_100, Norm::Chamfer34, side: 100);
+    bench_distance_transform!(bench_distance_transform_chamfer_5711_100, Norm::Chamfer5711, side: 100);
 }
@@ -0,0 +1,174 @@
+use crate::definitions::Image;
+use crate::drawing::line::draw_line_segment_mut;
+use crate::drawing::polygon::draw_polygon_mut;
+use crate::drawing::Canvas;
+use crate::point::Point;
+use image::{GenericImage, GenericImageView, ImageBuffer};
+use std::f32;
+
+// Half the angle between the two sides of an arrow head.
+const HEAD_HALF_ANGLE: f32 = f32::consts::PI / 6.0;
+
+// Returns the two outer vertices of the head of an arrow from start to end,
+// rounded to the nearest pixel so that both sides are drawn symmetrically,
+// or None if start and end coincide.
+fn arrow_head(start: (f32, f32), end: (f32, f32), head_size: f32) -> Option<[(f32, f32); 2]> {
+    let (dx, dy) = (start.0 - end.0, start.1 - end.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return None;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let (sin, cos) = HEAD_HALF_ANGLE.sin_cos();
+    let side = |sin: f32| {
+        (
+            (end.0 + head_size * (ux * cos - uy * sin)).round(),
+            (end.1 + head_size * (ux * sin + uy * cos)).round(),
+        )
+    };
+    Some([side(sin), side(-sin)])
+}
+
+/// Draws an arrow with an open head on a new copy of an image.
+///
+/// Draws as much of the arrow as lies inside the image bounds. The head
+/// points at `end` and its two sides have length `head_size`.
+pub fn draw_arrow<I>(
+    image: &I,
+    start: (f32, f32),
+    end: (f32, f32),
+    color: I::Pixel,
+    head_size: f32,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_arrow_mut(&mut out, start, end, color, head_size);
+    out
+}
+
+/// Draws an arrow with an open head on an image in place.
+///
+/// Draws as much of the arrow as lies inside the image bounds. The head
+/// points at `end` and its two sides have length `head_size`.
+pub fn draw_arrow_mut<C>(
+    canvas: &mut C,
+    start: (f32, f32),
+    end: (f32, f32),
+    color: C::Pixel,
+    head_size: f32,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    draw_line_segment_mut(canvas, start, end, color);
+    if let Some([left, right]) = arrow_head(start, end, head_size) {
+        draw_line_segment_mut(canvas, end, left, color);
+        draw_line_segment_mut(canvas, end, right, color);
+    }
+}
+
+/// Draws an arrow with a filled triangular head on a new copy of an image.
+///
+/// Draws as much of the arrow as lies inside the image bounds. The head
+/// points at `end` and its two sides have length `head_size`.
+pub fn draw_filled_arrow<I>(
+    image: &I,
+    start: (f32, f32),
+    end: (f32, f32),
+    color: I::Pixel,
+    head_size: f32,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_filled_arrow_mut(&mut out, start, end, color, head_size);
+    out
+}
+
+/// Draws an arrow with a filled triangular head on an image in place.
+///
+/// Draws as much of the arrow as lies inside the image bounds. The head
+/// points at `end` and its two sides have length `head_size`.
+pub fn draw_filled_arrow_mut<C>(
+    canvas: &mut C,
+    start: (f32, f32),
+    end: (f32, f32),
+    color: C::Pixel,
+    head_size: f32,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    draw_line_segment_mut(canvas, start, end, color);
+    if let Some([left, right]) = arrow_head(start, end, head_size) {
+        let round = |p: (f32, f32)| Point::new(p.0.round() as i32, p.1.round() as i32);
+        let head = [round(end), round(left), round(right)];
+        if head[0] == head[1] || head[0] == head[2] || head[1] == head[2] {
+            // The head is too small to fill, draw its outline instead.
+            draw_line_segment_mut(canvas, end, left, color);
+            draw_line_segment_mut(canvas, end, right, color);
+        } else {
+            draw_polygon_mut(canvas, &head, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn test_draw_arrow() {
+        let image = GrayImage::from_pixel(9, 7, Luma([1u8]));
+
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1, 1, 1;
+            1, 1, 1, 1, 4, 1, 1, 1, 1;
+            1, 1, 1, 1, 1, 4, 4, 1, 1;
+            4, 4, 4, 4, 4, 4, 4, 4, 1;
+            1, 1, 1, 1, 1, 4, 4, 1, 1;
+            1, 1, 1, 1, 4, 1, 1, 1, 1;
+            1, 1, 1, 1, 1, 1, 1, 1, 1);
+
+        let actual = draw_arrow(&image, (0.0, 3.0), (7.0, 3.0), Luma([4u8]), 4.0);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_filled_arrow() {
+        let image = GrayImage::from_pixel(9, 7, Luma([1u8]));
+
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1, 1, 1;
+            1, 1, 1, 1, 4, 1, 1, 1, 1;
+            1, 1, 1, 1, 4, 4, 4, 1, 1;
+            4, 4, 4, 4, 4, 4, 4, 4, 1;
+            1, 1, 1, 1, 4, 4, 4, 1, 1;
+            1, 1, 1, 1, 4, 1, 1, 1, 1;
+            1, 1, 1, 1, 1, 1, 1, 1, 1);
+
+        let actual = draw_filled_arrow(&image, (0.0, 3.0), (7.0, 3.0), Luma([4u8]), 4.0);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_arrow_zero_length() {
+        let image = GrayImage::from_pixel(3, 3, Luma([1u8]));
+
+        let expected = gray_image!(
+            1, 1, 1;
+            1, 4, 1;
+            1, 1, 1);
+
+        let actual = draw_filled_arrow(&image, (1.0, 1.0), (1.0, 1.0), Luma([4u8]), 3.0);
+        assert_pixels_eq!(actual, expected);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::definitions::Image;
 use crate::drawing::line::draw_line_segment_mut;
 use crate::drawing::Canvas;
-use image::{GenericImage, ImageBuffer};
+use image::{GenericImage, GenericImageView, ImageBuffer};
 use std::f32;
 use std::i32;
 
@@ -17,7 +17,7 @@ pub fn draw_cubic_bezier_curve<I>(
     color: I::Pixel,
 ) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -1,3 +1,6 @@
+use crate::definitions::Clamp;
+use crate::pixelops::weighted_sum;
+use conv::ValueInto;
 use image::{GenericImage, GenericImageView, Pixel};
 
 /// A surface for drawing on - many drawing functions in this
@@ -8,9 +11,14 @@ use image::{GenericImage, GenericImageView, Pixel};
 /// the behaviour of `draw_pixel` being equivalent to calling
 /// `set_pixel` with the same arguments.
 ///
+/// In particular this includes the mutable views returned by
+/// `GenericImage::sub_image`, which allows drawing into a region
+/// of interest using coordinates relative to that region.
+///
 /// See [`Blend`](struct.Blend.html) for another example implementation
 /// of this trait - its implementation of `draw_pixel` alpha-blends
-/// the input value with the pixel's current value.
+/// the input value with the pixel's current value. [`Translucent`](struct.Translucent.html)
+/// instead composites drawn pixels with a fixed opacity.
 ///
 /// # Examples
 /// ```
@@ -18,7 +26,7 @@ use image::{GenericImage, GenericImageView, Pixel};
 /// # #[macro_use]
 /// # extern crate imageproc;
 /// # fn main() {
-/// use image::{Pixel, Rgba, RgbaImage};
+/// use image::{GenericImage, Pixel, Rgba, RgbaImage};
 /// use imageproc::drawing::{Canvas, Blend};
 ///
 /// // A trivial function which draws on a Canvas
@@ -45,6 +53,16 @@ use image::{GenericImage, GenericImageView, Pixel};
 /// let mut image = Blend(RgbaImage::from_pixel(1, 1, solid_blue));
 /// write_a_pixel(&mut image, translucent_red);
 /// assert_eq!(*image.0.get_pixel(0, 0), alpha_blended);
+///
+/// // Sub-image views are also canvases, with coordinates relative to the view
+/// let mut image = RgbaImage::from_pixel(3, 3, solid_blue);
+/// write_a_pixel(&mut image.sub_image(1, 2, 2, 1), translucent_red);
+/// assert_eq!(*image.get_pixel(1, 2), translucent_red);
+///
+/// // ... which can be combined with blending
+/// let mut image = RgbaImage::from_pixel(3, 3, solid_blue);
+/// write_a_pixel(&mut Blend(image.sub_image(1, 2, 2, 1)), translucent_red);
+/// assert_eq!(*image.get_pixel(1, 2), alpha_blended);
 /// # }
 /// ```
 pub trait Canvas {
@@ -113,3 +131,113 @@ impl<I: GenericImage> Canvas for Blend<I> {
         self.0.get_pixel_mut(x, y).blend(&color)
     }
 }
+
+/// A canvas that composites pixels onto the underlying image with a fixed opacity
+/// when drawing.
+///
+/// Drawing a pixel with color `c` onto a pixel with current value `p` sets it to
+/// `opacity * c + (1 - opacity) * p`, computed per channel. This allows e.g. drawing
+/// semi-transparent overlays on images without an alpha channel.
+///
+/// When used with functions that compute per-pixel coverage, such as
+/// [`draw_antialiased_line_segment_mut`](fn.draw_antialiased_line_segment_mut.html),
+/// the coverage and opacity combine multiplicatively.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::{GrayImage, Luma};
+/// use imageproc::drawing::{draw_filled_rect_mut, Translucent};
+/// use imageproc::rect::Rect;
+///
+/// let mut canvas = Translucent::new(GrayImage::from_pixel(3, 3, Luma([100u8])), 0.25);
+/// draw_filled_rect_mut(&mut canvas, Rect::at(0, 0).of_size(2, 2), Luma([200u8]));
+///
+/// assert_eq!(*canvas.image.get_pixel(0, 0), Luma([125u8]));
+/// assert_eq!(*canvas.image.get_pixel(2, 2), Luma([100u8]));
+/// # }
+/// ```
+pub struct Translucent<I> {
+    /// The image being drawn on.
+    pub image: I,
+    /// The opacity of drawn pixels, between 0 (fully transparent) and 1 (fully opaque).
+    pub opacity: f32,
+}
+
+impl<I> Translucent<I> {
+    /// Creates a canvas drawing onto `image` with the given opacity.
+    ///
+    /// Panics if `opacity` is not between 0 and 1.
+    pub fn new(image: I, opacity: f32) -> Translucent<I> {
+        assert!(
+            (0.0..=1.0).contains(&opacity),
+            "opacity must be between 0 and 1, got {}",
+            opacity
+        );
+        Translucent { image, opacity }
+    }
+}
+
+impl<I> Canvas for Translucent<I>
+where
+    I: GenericImage,
+    <I::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    type Pixel = I::Pixel;
+
+    fn dimensions(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+
+    fn get_pixel(&self, x: u32, y: u32) -> Self::Pixel {
+        self.image.get_pixel(x, y)
+    }
+
+    fn draw_pixel(&mut self, x: u32, y: u32, color: Self::Pixel) {
+        let current = self.image.get_pixel(x, y);
+        let blended = weighted_sum(current, color, 1.0 - self.opacity, self.opacity);
+        self.image.put_pixel(x, y, blended);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing::{draw_antialiased_line_segment_mut, draw_filled_circle_mut};
+    use crate::pixelops::interpolate;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn test_translucent_opaque_matches_image() {
+        let image = GrayImage::from_pixel(7, 7, Luma([10u8]));
+
+        let mut expected = image.clone();
+        draw_filled_circle_mut(&mut expected, (3, 3), 2, Luma([200u8]));
+
+        let mut canvas = Translucent::new(image, 1.0);
+        draw_filled_circle_mut(&mut canvas, (3, 3), 2, Luma([200u8]));
+
+        assert_pixels_eq!(canvas.image, expected);
+    }
+
+    #[test]
+    fn test_translucent_antialiased_line() {
+        let mut canvas = Translucent::new(GrayImage::from_pixel(5, 3, Luma([0u8])), 0.5);
+        draw_antialiased_line_segment_mut(&mut canvas, (0, 1), (4, 1), Luma([200u8]), interpolate);
+
+        let expected = gray_image!(
+              0,   0,   0,   0,   0;
+            100, 100, 100, 100, 100;
+              0,   0,   0,   0,   0);
+
+        assert_pixels_eq!(canvas.image, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_translucent_invalid_opacity() {
+        let _ = Translucent::new(GrayImage::new(1, 1), 1.5);
+    }
+}
@@ -2,7 +2,7 @@ use crate::definitions::Image;
 use crate::drawing::draw_if_in_bounds;
 use crate::drawing::line::draw_line_segment_mut;
 use crate::drawing::Canvas;
-use image::{GenericImage, ImageBuffer};
+use image::{GenericImage, GenericImageView, ImageBuffer};
 use std::f32;
 use std::i32;
 
@@ -24,7 +24,7 @@ pub fn draw_hollow_ellipse<I>(
     color: I::Pixel,
 ) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -87,7 +87,7 @@ pub fn draw_filled_ellipse<I>(
     color: I::Pixel,
 ) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -200,7 +200,7 @@ pub fn draw_hollow_circle<I>(
     color: I::Pixel,
 ) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -303,7 +303,7 @@ pub fn draw_filled_circle<I>(
     color: I::Pixel,
 ) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -0,0 +1,249 @@
+use crate::contours::{BorderType, Contour};
+use crate::definitions::Image;
+use crate::drawing::line::draw_line_segment_mut;
+use crate::drawing::polygon::draw_polygon_mut;
+use crate::drawing::polyline::draw_closed_polyline_mut;
+use crate::drawing::Canvas;
+use crate::point::Point;
+use image::{GenericImage, GenericImageView, ImageBuffer};
+use num::{cast, NumCast};
+
+/// Draws the outlines of contours on a new copy of an image.
+///
+/// Draws as much of each contour as lies inside the image bounds. Consecutive points of each
+/// contour are connected by line segments, including an edge from the last point to the first.
+pub fn draw_contours<I, T>(image: &I, contours: &[Contour<T>], color: I::Pixel) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    T: NumCast + Copy,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_contours_mut(&mut out, contours, color);
+    out
+}
+
+/// Draws the outlines of contours on an image in place.
+///
+/// Draws as much of each contour as lies inside the image bounds. Consecutive points of each
+/// contour are connected by line segments, including an edge from the last point to the first.
+pub fn draw_contours_mut<C, T>(canvas: &mut C, contours: &[Contour<T>], color: C::Pixel)
+where
+    C: Canvas,
+    C::Pixel: 'static,
+    T: NumCast + Copy,
+{
+    for contour in contours {
+        draw_closed_polyline_mut(canvas, &contour.points, color, false);
+    }
+}
+
+/// Draws contours on a new copy of an image, choosing the color for each contour based on
+/// its depth in the contour hierarchy.
+///
+/// See [`draw_contours_by_depth_mut`](fn.draw_contours_by_depth_mut.html) for details.
+pub fn draw_contours_by_depth<I, T, F>(
+    image: &I,
+    contours: &[Contour<T>],
+    filled: bool,
+    color: F,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    T: NumCast + Copy,
+    F: Fn(usize) -> I::Pixel,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_contours_by_depth_mut(&mut out, contours, filled, color);
+    out
+}
+
+/// Draws contours on an image in place, choosing the color for each contour based on
+/// its depth in the contour hierarchy.
+///
+/// `contours` should be the complete output of a call to
+/// [`find_contours`](../contours/fn.find_contours.html), so that the `parent` of each contour
+/// is an index into `contours`. Contours without a parent have depth 0, and every other
+/// contour has depth one greater than its parent. For the output of `find_contours` this means
+/// that outer borders have even depth and hole borders have odd depth.
+///
+/// If `filled` is false then the outline of each contour is drawn with `color(depth)`.
+///
+/// If `filled` is true then contours are drawn in order of increasing depth, and the region
+/// enclosed by each contour is filled with `color(depth)`. The border pixels of a hole belong
+/// to the enclosing foreground region, so for hole borders only the pixels strictly inside the
+/// border are filled. For example, using `|depth| if depth % 2 == 0 { white } else { black }`
+/// redraws the foreground regions the contours were extracted from.
+pub fn draw_contours_by_depth_mut<C, T, F>(
+    canvas: &mut C,
+    contours: &[Contour<T>],
+    filled: bool,
+    color: F,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+    T: NumCast + Copy,
+    F: Fn(usize) -> C::Pixel,
+{
+    let depths = contour_depths(contours);
+
+    if !filled {
+        for (contour, depth) in contours.iter().zip(&depths) {
+            draw_closed_polyline_mut(canvas, &contour.points, color(*depth), false);
+        }
+        return;
+    }
+
+    let mut order: Vec<usize> = (0..contours.len()).collect();
+    order.sort_by_key(|&i| depths[i]);
+
+    for i in order {
+        let points = to_polygon(&contours[i].points);
+        if points.is_empty() {
+            continue;
+        }
+        let depth = depths[i];
+        fill_polygon(canvas, &points, color(depth));
+        if contours[i].border_type == BorderType::Hole && depth > 0 {
+            // Restore the hole border to the color of the enclosing region
+            draw_closed_polyline_mut(canvas, &points, color(depth - 1), false);
+        }
+    }
+}
+
+// Depth of each contour in the hierarchy described by the parent indices.
+fn contour_depths<T>(contours: &[Contour<T>]) -> Vec<usize> {
+    let mut depths = vec![None; contours.len()];
+    for i in 0..contours.len() {
+        // Walk up to the nearest ancestor of known depth
+        let mut chain = vec![i];
+        let mut base = 0;
+        while let Some(parent) = contours[*chain.last().unwrap()].parent {
+            if let Some(d) = depths[parent] {
+                base = d + 1;
+                break;
+            }
+            assert!(
+                chain.len() <= contours.len(),
+                "contour hierarchy contains a cycle"
+            );
+            chain.push(parent);
+        }
+        for (k, &j) in chain.iter().rev().enumerate() {
+            depths[j] = Some(base + k);
+        }
+    }
+    depths.into_iter().map(|d| d.unwrap()).collect()
+}
+
+// Converts contour points to a polygon suitable for draw_polygon_mut, i.e. with
+// integer coordinates and distinct first and last points.
+fn to_polygon<T: NumCast + Copy>(points: &[Point<T>]) -> Vec<Point<i32>> {
+    let mut polygon: Vec<Point<i32>> = points
+        .iter()
+        .map(|p| {
+            let x: f64 = cast(p.x).expect("point coordinate out of range");
+            let y: f64 = cast(p.y).expect("point coordinate out of range");
+            Point::new(x.round() as i32, y.round() as i32)
+        })
+        .collect();
+    while polygon.len() > 1 && polygon[0] == polygon[polygon.len() - 1] {
+        polygon.pop();
+    }
+    polygon
+}
+
+fn fill_polygon<C>(canvas: &mut C, points: &[Point<i32>], color: C::Pixel)
+where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    if points.len() == 1 {
+        let p = (points[0].x as f32, points[0].y as f32);
+        draw_line_segment_mut(canvas, p, p, color);
+    } else {
+        draw_polygon_mut(canvas, points, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contours::find_contours;
+    use image::{GrayImage, Luma};
+
+    fn ring() -> GrayImage {
+        gray_image!(
+            0, 0, 0, 0, 0, 0, 0;
+            0, 1, 1, 1, 1, 1, 0;
+            0, 1, 1, 1, 1, 1, 0;
+            0, 1, 1, 0, 1, 1, 0;
+            0, 1, 1, 1, 1, 1, 0;
+            0, 1, 1, 1, 1, 1, 0;
+            0, 0, 0, 0, 0, 0, 0)
+    }
+
+    #[test]
+    fn test_draw_contours() {
+        let image = ring();
+        let contours = find_contours::<i32>(&image);
+
+        let expected = gray_image!(
+            0, 0, 0, 0, 0, 0, 0;
+            0, 9, 9, 9, 9, 9, 0;
+            0, 9, 1, 9, 1, 9, 0;
+            0, 9, 9, 0, 9, 9, 0;
+            0, 9, 1, 9, 1, 9, 0;
+            0, 9, 9, 9, 9, 9, 0;
+            0, 0, 0, 0, 0, 0, 0);
+
+        assert_pixels_eq!(draw_contours(&image, &contours, Luma([9u8])), expected);
+    }
+
+    #[test]
+    fn test_draw_contours_by_depth_outline() {
+        let image = ring();
+        let contours = find_contours::<i32>(&image);
+
+        let expected = gray_image!(
+            0, 0, 0, 0, 0, 0, 0;
+            0, 7, 7, 7, 7, 7, 0;
+            0, 7, 1, 8, 1, 7, 0;
+            0, 7, 8, 0, 8, 7, 0;
+            0, 7, 1, 8, 1, 7, 0;
+            0, 7, 7, 7, 7, 7, 0;
+            0, 0, 0, 0, 0, 0, 0);
+
+        let actual = draw_contours_by_depth(&image, &contours, false, |d| Luma([7 + d as u8]));
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_contours_by_depth_filled_reproduces_input() {
+        let image = ring();
+        let contours = find_contours::<i32>(&image);
+
+        let actual = draw_contours_by_depth(
+            &GrayImage::new(image.width(), image.height()),
+            &contours,
+            true,
+            |d| if d % 2 == 0 { Luma([1u8]) } else { Luma([0u8]) },
+        );
+        assert_pixels_eq!(actual, image);
+    }
+
+    #[test]
+    fn test_contour_depths() {
+        let contour = |parent| Contour::<i32>::new(vec![], BorderType::Outer, parent);
+        let contours = vec![
+            contour(Some(2)),
+            contour(None),
+            contour(Some(1)),
+            contour(None),
+        ];
+        assert_eq!(contour_depths(&contours), vec![2, 0, 1, 0]);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::definitions::Image;
 use crate::drawing::Canvas;
-use image::{GenericImage, ImageBuffer};
+use image::{GenericImage, GenericImageView, ImageBuffer};
 use std::i32;
 
 /// Draws a colored cross on an image in place.
@@ -41,7 +41,7 @@ where
 /// Handles coordinates outside image bounds.
 pub fn draw_cross<I>(image: &I, color: I::Pixel, x: i32, y: i32) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -0,0 +1,347 @@
+use crate::definitions::{Clamp, Image};
+use crate::drawing::conics::draw_filled_circle_mut;
+use crate::drawing::polygon::draw_polygon_mut;
+use crate::drawing::rect::draw_filled_rect_mut;
+use crate::drawing::Canvas;
+use crate::pixelops::interpolate;
+use crate::point::Point;
+use crate::rect::Rect;
+use conv::ValueInto;
+use image::{GenericImage, GenericImageView, ImageBuffer, Pixel};
+
+/// A color gradient, used to fill shapes with smoothly varying colors.
+///
+/// A gradient maps each point in the plane to a position `t` between 0 and 1, and
+/// interpolates linearly between the colors of the two nearest color stops at `t`.
+/// Positions outside the range of the stops take the color of the nearest stop.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::Luma;
+/// use imageproc::drawing::Gradient;
+///
+/// let gradient = Gradient::linear((0.0, 0.0), (10.0, 0.0), vec![(0.0, Luma([0u8])), (1.0, Luma([100u8]))]);
+///
+/// assert_eq!(gradient.color_at(-3.0, 7.0), Luma([0u8]));
+/// assert_eq!(gradient.color_at(5.0, 7.0), Luma([50u8]));
+/// assert_eq!(gradient.color_at(12.0, 0.0), Luma([100u8]));
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient<P> {
+    shape: GradientShape,
+    stops: Vec<(f32, P)>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum GradientShape {
+    Linear { start: (f32, f32), end: (f32, f32) },
+    Radial { center: (f32, f32), radius: f32 },
+}
+
+impl<P: Pixel> Gradient<P>
+where
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    /// A gradient varying along the line from `start` (position 0) to `end` (position 1),
+    /// and constant along lines perpendicular to it.
+    ///
+    /// `stops` is a list of (position, color) pairs. Panics if there are fewer than two stops,
+    /// if the stop positions are not sorted in increasing order, or if `start == end`.
+    pub fn linear(start: (f32, f32), end: (f32, f32), stops: Vec<(f32, P)>) -> Gradient<P> {
+        assert!(
+            start != end,
+            "start and end of a linear gradient must differ"
+        );
+        Gradient::new(GradientShape::Linear { start, end }, stops)
+    }
+
+    /// A gradient varying with distance from `center`, from position 0 at the center
+    /// to position 1 at distance `radius`.
+    ///
+    /// `stops` is a list of (position, color) pairs. Panics if there are fewer than two stops,
+    /// if the stop positions are not sorted in increasing order, or if `radius` is not positive.
+    pub fn radial(center: (f32, f32), radius: f32, stops: Vec<(f32, P)>) -> Gradient<P> {
+        assert!(radius > 0.0, "radius must be strictly positive");
+        Gradient::new(GradientShape::Radial { center, radius }, stops)
+    }
+
+    fn new(shape: GradientShape, stops: Vec<(f32, P)>) -> Gradient<P> {
+        assert!(
+            stops.len() >= 2,
+            "a gradient requires at least two color stops"
+        );
+        assert!(
+            stops.windows(2).all(|w| w[0].0 <= w[1].0),
+            "color stop positions must be sorted in increasing order"
+        );
+        Gradient { shape, stops }
+    }
+
+    /// The color of this gradient at the point (x, y).
+    pub fn color_at(&self, x: f32, y: f32) -> P {
+        let t = match self.shape {
+            GradientShape::Linear { start, end } => {
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                ((x - start.0) * dx + (y - start.1) * dy) / (dx * dx + dy * dy)
+            }
+            GradientShape::Radial { center, radius } => {
+                ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt() / radius
+            }
+        };
+
+        let first = &self.stops[0];
+        let last = &self.stops[self.stops.len() - 1];
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        let upper = self.stops.iter().position(|s| s.0 > t).unwrap();
+        let (t0, c0) = self.stops[upper - 1];
+        let (t1, c1) = self.stops[upper];
+        interpolate(c0, c1, (t1 - t) / (t1 - t0))
+    }
+}
+
+// A canvas which ignores the color it is asked to draw and instead
+// draws the color of a gradient at each pixel.
+struct GradientCanvas<'a, C: Canvas> {
+    canvas: &'a mut C,
+    gradient: &'a Gradient<C::Pixel>,
+}
+
+impl<'a, C> Canvas for GradientCanvas<'a, C>
+where
+    C: Canvas,
+    <C::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    type Pixel = C::Pixel;
+
+    fn dimensions(&self) -> (u32, u32) {
+        self.canvas.dimensions()
+    }
+
+    fn get_pixel(&self, x: u32, y: u32) -> Self::Pixel {
+        self.canvas.get_pixel(x, y)
+    }
+
+    fn draw_pixel(&mut self, x: u32, y: u32, _: Self::Pixel) {
+        let color = self.gradient.color_at(x as f32, y as f32);
+        self.canvas.draw_pixel(x, y, color);
+    }
+}
+
+// Draws a shape onto a canvas, coloring it with a gradient.
+fn fill_with_gradient<C, F>(canvas: &mut C, gradient: &Gradient<C::Pixel>, draw: F)
+where
+    C: Canvas,
+    C::Pixel: 'static,
+    <C::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+    F: FnOnce(&mut GradientCanvas<'_, C>, C::Pixel),
+{
+    let placeholder = gradient.stops[0].1;
+    let mut gradient_canvas = GradientCanvas { canvas, gradient };
+    draw(&mut gradient_canvas, placeholder);
+}
+
+/// Draws a rectangle filled with a gradient on a new copy of an image.
+///
+/// Draws as much of the rectangle as lies inside the image bounds.
+pub fn draw_gradient_filled_rect<I>(
+    image: &I,
+    rect: Rect,
+    gradient: &Gradient<I::Pixel>,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_gradient_filled_rect_mut(&mut out, rect, gradient);
+    out
+}
+
+/// Draws a rectangle filled with a gradient on an image in place.
+///
+/// Draws as much of the rectangle as lies inside the image bounds.
+pub fn draw_gradient_filled_rect_mut<C>(canvas: &mut C, rect: Rect, gradient: &Gradient<C::Pixel>)
+where
+    C: Canvas,
+    C::Pixel: 'static,
+    <C::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    fill_with_gradient(canvas, gradient, |c, color| {
+        draw_filled_rect_mut(c, rect, color)
+    });
+}
+
+/// Draws a circle filled with a gradient on a new copy of an image.
+///
+/// Draws as much of the circle as lies inside the image bounds.
+pub fn draw_gradient_filled_circle<I>(
+    image: &I,
+    center: (i32, i32),
+    radius: i32,
+    gradient: &Gradient<I::Pixel>,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_gradient_filled_circle_mut(&mut out, center, radius, gradient);
+    out
+}
+
+/// Draws a circle filled with a gradient on an image in place.
+///
+/// Draws as much of the circle as lies inside the image bounds.
+pub fn draw_gradient_filled_circle_mut<C>(
+    canvas: &mut C,
+    center: (i32, i32),
+    radius: i32,
+    gradient: &Gradient<C::Pixel>,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+    <C::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    fill_with_gradient(canvas, gradient, |c, color| {
+        draw_filled_circle_mut(c, center, radius, color)
+    });
+}
+
+/// Draws a polygon filled with a gradient on a new copy of an image.
+///
+/// Draws as much of the polygon as lies inside the image bounds. See
+/// [`draw_polygon`](fn.draw_polygon.html) for the requirements on `poly`.
+pub fn draw_gradient_filled_polygon<I>(
+    image: &I,
+    poly: &[Point<i32>],
+    gradient: &Gradient<I::Pixel>,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_gradient_filled_polygon_mut(&mut out, poly, gradient);
+    out
+}
+
+/// Draws a polygon filled with a gradient on an image in place.
+///
+/// Draws as much of the polygon as lies inside the image bounds. See
+/// [`draw_polygon_mut`](fn.draw_polygon_mut.html) for the requirements on `poly`.
+pub fn draw_gradient_filled_polygon_mut<C>(
+    canvas: &mut C,
+    poly: &[Point<i32>],
+    gradient: &Gradient<C::Pixel>,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+    <C::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    fill_with_gradient(canvas, gradient, |c, color| {
+        draw_polygon_mut(c, poly, color)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma, Rgb};
+
+    #[test]
+    fn test_gradient_color_at_multiple_stops() {
+        let gradient = Gradient::linear(
+            (0.0, 0.0),
+            (0.0, 4.0),
+            vec![
+                (0.0, Rgb([0u8, 0u8, 0u8])),
+                (0.5, Rgb([200u8, 0u8, 100u8])),
+                (1.0, Rgb([200u8, 200u8, 200u8])),
+            ],
+        );
+        assert_eq!(gradient.color_at(3.0, 1.0), Rgb([100, 0, 50]));
+        assert_eq!(gradient.color_at(0.0, 2.0), Rgb([200, 0, 100]));
+        assert_eq!(gradient.color_at(0.0, 3.0), Rgb([200, 100, 150]));
+    }
+
+    #[test]
+    fn test_draw_gradient_filled_rect_linear() {
+        let image = GrayImage::from_pixel(7, 3, Luma([1u8]));
+        let gradient = Gradient::linear(
+            (1.0, 0.0),
+            (5.0, 0.0),
+            vec![(0.0, Luma([0u8])), (1.0, Luma([200u8]))],
+        );
+
+        let expected = gray_image!(
+            1, 1,  1,   1,   1,   1, 1;
+            1, 0, 50, 100, 150, 200, 1;
+            1, 1,  1,   1,   1,   1, 1);
+
+        let rect = Rect::at(1, 1).of_size(5, 1);
+        let actual = draw_gradient_filled_rect(&image, rect, &gradient);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_gradient_filled_circle_radial() {
+        let image = GrayImage::from_pixel(5, 5, Luma([1u8]));
+        let gradient = Gradient::radial(
+            (2.0, 2.0),
+            2.0,
+            vec![(0.0, Luma([200u8])), (1.0, Luma([100u8]))],
+        );
+
+        let expected = gray_image!(
+            1,   1,   1,   1, 1;
+            1,   1, 150,   1, 1;
+            1, 150, 200, 150, 1;
+            1,   1, 150,   1, 1;
+            1,   1,   1,   1, 1);
+
+        let actual = draw_gradient_filled_circle(&image, (2, 2), 1, &gradient);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_gradient_filled_polygon() {
+        let image = GrayImage::from_pixel(4, 4, Luma([1u8]));
+        let gradient = Gradient::linear(
+            (0.0, 0.0),
+            (0.0, 3.0),
+            vec![(0.0, Luma([30u8])), (1.0, Luma([60u8]))],
+        );
+
+        let expected = gray_image!(
+            30,  1,  1,  1;
+            40, 40,  1,  1;
+            50, 50, 50,  1;
+            60, 60, 60, 60);
+
+        let poly = [Point::new(0, 0), Point::new(3, 3), Point::new(0, 3)];
+        let actual = draw_gradient_filled_polygon(&image, &poly, &gradient);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_gradient_rejects_single_stop() {
+        let _ = Gradient::radial((0.0, 0.0), 1.0, vec![(0.0, Luma([0u8]))]);
+    }
+}
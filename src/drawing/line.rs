@@ -1,6 +1,7 @@
 use crate::definitions::Image;
 use crate::drawing::Canvas;
-use image::{GenericImage, ImageBuffer, Pixel};
+use crate::rect::{Rect, Region};
+use image::{GenericImage, GenericImageView, ImageBuffer, Pixel};
 use std::f32;
 use std::i32;
 use std::mem::{swap, transmute};
@@ -86,7 +87,7 @@ fn clamp(x: f32, upper_bound: u32) -> f32 {
     x
 }
 
-fn clamp_point<I: GenericImage>(p: (f32, f32), image: &I) -> (f32, f32) {
+fn clamp_point<I: GenericImageView>(p: (f32, f32), image: &I) -> (f32, f32) {
     (clamp(p.0, image.width()), clamp(p.1, image.height()))
 }
 
@@ -165,6 +166,68 @@ impl<'a, P: Pixel + 'static> Iterator for BresenhamLinePixelIterMut<'a, P> {
     }
 }
 
+/// Clips the line segment between `start` and `end` to the given rectangle, using the
+/// [Liang–Barsky algorithm](https://en.wikipedia.org/wiki/Liang%E2%80%93Barsky_algorithm).
+///
+/// The rectangle is treated as the continuous region `[left, right] x [top, bottom]`, i.e.
+/// its boundary passes through the centres of its outermost pixels. Returns the endpoints of
+/// the part of the segment lying inside this region, in the same direction as the input, or
+/// `None` if the segment does not intersect it.
+///
+/// # Examples
+/// ```
+/// use imageproc::drawing::clip_line_segment;
+/// use imageproc::rect::Rect;
+///
+/// let rect = Rect::at(0, 0).of_size(11, 11);
+///
+/// assert_eq!(
+///     clip_line_segment((-5.0, 5.0), (15.0, 5.0), rect),
+///     Some(((0.0, 5.0), (10.0, 5.0)))
+/// );
+/// assert_eq!(clip_line_segment((-5.0, -5.0), (-1.0, 20.0), rect), None);
+/// ```
+pub fn clip_line_segment(
+    start: (f32, f32),
+    end: (f32, f32),
+    rect: Rect,
+) -> Option<((f32, f32), (f32, f32))> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let (mut t0, mut t1) = (0f32, 1f32);
+
+    // Each boundary is expressed as p * t <= q
+    let boundaries = [
+        (-dx, start.0 - rect.left() as f32),
+        (dx, rect.right() as f32 - start.0),
+        (-dy, start.1 - rect.top() as f32),
+        (dy, rect.bottom() as f32 - start.1),
+    ];
+
+    for &(p, q) in &boundaries {
+        if p == 0.0 {
+            // Parallel to this boundary
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+            if t0 > t1 {
+                return None;
+            }
+        }
+    }
+
+    Some((
+        (start.0 + t0 * dx, start.1 + t0 * dy),
+        (start.0 + t1 * dx, start.1 + t1 * dy),
+    ))
+}
+
 /// Draws a line segment on a new copy of an image.
 ///
 /// Draws as much of the line segment between start and end as lies inside the image bounds.
@@ -177,7 +240,7 @@ pub fn draw_line_segment<I>(
     color: I::Pixel,
 ) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -197,7 +260,18 @@ where
     C::Pixel: 'static,
 {
     let (width, height) = canvas.dimensions();
-    let in_bounds = |x, y| x >= 0 && x < width as i32 && y >= 0 && y < height as i32;
+    if width == 0 || height == 0 {
+        return;
+    }
+    let bounds = Rect::at(0, 0).of_size(width, height);
+    // BresenhamLineIter truncates the start point and can then stray from the
+    // exact line by up to half a pixel, so only skip lines which pass no
+    // closer than two pixels to the image.
+    let cull_bounds = Rect::at(-2, -2).of_size(width + 4, height + 4);
+    if clip_line_segment(start, end, cull_bounds).is_none() {
+        return;
+    }
+    let in_bounds = |x, y| bounds.contains(x, y);
 
     let line_iterator = BresenhamLineIter::new(start, end);
 
@@ -227,7 +301,7 @@ pub fn draw_antialiased_line_segment<I, B>(
     blend: B,
 ) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
     B: Fn(I::Pixel, I::Pixel, f32) -> I::Pixel,
 {
@@ -245,16 +319,16 @@ where
 /// Consider using [`interpolate`](fn.interpolate.html) for blend.
 ///
 /// Uses [Xu's line drawing algorithm](https://en.wikipedia.org/wiki/Xiaolin_Wu%27s_line_algorithm).
-pub fn draw_antialiased_line_segment_mut<I, B>(
-    image: &mut I,
+pub fn draw_antialiased_line_segment_mut<C, B>(
+    canvas: &mut C,
     start: (i32, i32),
     end: (i32, i32),
-    color: I::Pixel,
+    color: C::Pixel,
     blend: B,
 ) where
-    I: GenericImage,
-    I::Pixel: 'static,
-    B: Fn(I::Pixel, I::Pixel, f32) -> I::Pixel,
+    C: Canvas,
+    C::Pixel: 'static,
+    B: Fn(C::Pixel, C::Pixel, f32) -> C::Pixel,
 {
     let (mut x0, mut y0) = (start.0, start.1);
     let (mut x1, mut y1) = (end.0, end.1);
@@ -267,7 +341,7 @@ pub fn draw_antialiased_line_segment_mut<I, B>(
             swap(&mut y0, &mut y1);
         }
         let plotter = Plotter {
-            image,
+            canvas,
             transform: |x, y| (y, x),
             blend,
         };
@@ -278,7 +352,7 @@ pub fn draw_antialiased_line_segment_mut<I, B>(
             swap(&mut y0, &mut y1);
         }
         let plotter = Plotter {
-            image,
+            canvas,
             transform: |x, y| (x, y),
             blend,
         };
@@ -286,16 +360,16 @@ pub fn draw_antialiased_line_segment_mut<I, B>(
     };
 }
 
-fn plot_wu_line<I, T, B>(
-    mut plotter: Plotter<'_, I, T, B>,
+fn plot_wu_line<C, T, B>(
+    mut plotter: Plotter<'_, C, T, B>,
     start: (i32, i32),
     end: (i32, i32),
-    color: I::Pixel,
+    color: C::Pixel,
 ) where
-    I: GenericImage,
-    I::Pixel: 'static,
+    C: Canvas,
+    C::Pixel: 'static,
     T: Fn(i32, i32) -> (i32, i32),
-    B: Fn(I::Pixel, I::Pixel, f32) -> I::Pixel,
+    B: Fn(C::Pixel, C::Pixel, f32) -> C::Pixel,
 {
     let dx = end.0 - start.0;
     let dy = end.1 - start.1;
@@ -309,36 +383,36 @@ fn plot_wu_line<I, T, B>(
     }
 }
 
-struct Plotter<'a, I, T, B>
+struct Plotter<'a, C, T, B>
 where
-    I: GenericImage,
-    I::Pixel: 'static,
+    C: Canvas,
+    C::Pixel: 'static,
     T: Fn(i32, i32) -> (i32, i32),
-    B: Fn(I::Pixel, I::Pixel, f32) -> I::Pixel,
+    B: Fn(C::Pixel, C::Pixel, f32) -> C::Pixel,
 {
-    image: &'a mut I,
+    canvas: &'a mut C,
     transform: T,
     blend: B,
 }
 
-impl<'a, I, T, B> Plotter<'a, I, T, B>
+impl<'a, C, T, B> Plotter<'a, C, T, B>
 where
-    I: GenericImage,
-    I::Pixel: 'static,
+    C: Canvas,
+    C::Pixel: 'static,
     T: Fn(i32, i32) -> (i32, i32),
-    B: Fn(I::Pixel, I::Pixel, f32) -> I::Pixel,
+    B: Fn(C::Pixel, C::Pixel, f32) -> C::Pixel,
 {
     fn in_bounds(&self, x: i32, y: i32) -> bool {
-        x >= 0 && x < self.image.width() as i32 && y >= 0 && y < self.image.height() as i32
+        x >= 0 && x < self.canvas.width() as i32 && y >= 0 && y < self.canvas.height() as i32
     }
 
-    pub fn plot(&mut self, x: i32, y: i32, line_color: I::Pixel, line_weight: f32) {
+    pub fn plot(&mut self, x: i32, y: i32, line_color: C::Pixel, line_weight: f32) {
         let (x_trans, y_trans) = (self.transform)(x, y);
         if self.in_bounds(x_trans, y_trans) {
-            let original = self.image.get_pixel(x_trans as u32, y_trans as u32);
+            let original = self.canvas.get_pixel(x_trans as u32, y_trans as u32);
             let blended = (self.blend)(line_color, original, line_weight);
-            self.image
-                .put_pixel(x_trans as u32, y_trans as u32, blended);
+            self.canvas
+                .draw_pixel(x_trans as u32, y_trans as u32, blended);
         }
     }
 }
@@ -359,6 +433,45 @@ mod tests {
     //   3 / | \ 0
     //   / 2 | 1 \
 
+    #[test]
+    fn test_clip_line_segment_inside() {
+        let rect = Rect::at(2, 3).of_size(5, 5);
+        assert_eq!(
+            clip_line_segment((3.0, 4.0), (5.5, 6.0), rect),
+            Some(((3.0, 4.0), (5.5, 6.0)))
+        );
+    }
+
+    #[test]
+    fn test_clip_line_segment_crossing() {
+        let rect = Rect::at(0, 0).of_size(5, 5);
+        // Enters through the left edge and leaves through the bottom edge
+        assert_eq!(
+            clip_line_segment((-2.0, 0.0), (6.0, 8.0), rect),
+            Some(((0.0, 2.0), (2.0, 4.0)))
+        );
+        // Direction is preserved
+        assert_eq!(
+            clip_line_segment((6.0, 8.0), (-2.0, 0.0), rect),
+            Some(((2.0, 4.0), (0.0, 2.0)))
+        );
+    }
+
+    #[test]
+    fn test_clip_line_segment_outside() {
+        let rect = Rect::at(0, 0).of_size(5, 5);
+        assert_eq!(clip_line_segment((-1.0, 6.0), (6.0, 6.0), rect), None);
+        assert_eq!(clip_line_segment((3.0, 7.0), (8.0, 2.0), rect), None);
+        assert_eq!(clip_line_segment((7.0, 7.0), (7.0, 7.0), rect), None);
+    }
+
+    #[test]
+    fn test_draw_line_segment_outside_image() {
+        let image = GrayImage::from_pixel(5, 5, Luma([1u8]));
+        let actual = draw_line_segment(&image, (-1e9, -3.0), (1e9, -2.0), Luma([4u8]));
+        assert_pixels_eq!(actual, image);
+    }
+
     #[test]
     fn test_draw_line_segment_horizontal() {
         let image = GrayImage::from_pixel(5, 5, Luma([1u8]));
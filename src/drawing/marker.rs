@@ -0,0 +1,217 @@
+use crate::definitions::Image;
+use crate::drawing::line::draw_line_segment_mut;
+use crate::drawing::Canvas;
+use image::{GenericImage, GenericImageView, ImageBuffer};
+
+/// The shape of a marker drawn by [`draw_marker`](fn.draw_marker.html).
+///
+/// These match the marker types supported by OpenCV's `drawMarker`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MarkerStyle {
+    /// A horizontal and a vertical line, i.e. a plus sign.
+    Cross,
+    /// Two diagonal lines, i.e. an x shape.
+    TiltedCross,
+    /// A combination of `Cross` and `TiltedCross`.
+    Star,
+    /// The outline of a square rotated by 45 degrees.
+    Diamond,
+    /// The outline of an axis-aligned square.
+    Square,
+    /// The outline of a triangle pointing upwards.
+    TriangleUp,
+    /// The outline of a triangle pointing downwards.
+    TriangleDown,
+}
+
+/// Draws a marker on a new copy of an image.
+///
+/// Draws as much of the marker as lies inside the image bounds. The marker is centred
+/// at `center` and fits inside a square with side length `size + 1`.
+pub fn draw_marker<I>(
+    image: &I,
+    center: (i32, i32),
+    style: MarkerStyle,
+    size: u32,
+    color: I::Pixel,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_marker_mut(&mut out, center, style, size, color);
+    out
+}
+
+/// Draws a marker on an image in place.
+///
+/// Draws as much of the marker as lies inside the image bounds. The marker is centred
+/// at `center` and fits inside a square with side length `size + 1`.
+pub fn draw_marker_mut<C>(
+    canvas: &mut C,
+    center: (i32, i32),
+    style: MarkerStyle,
+    size: u32,
+    color: C::Pixel,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    let (x, y) = (center.0 as f32, center.1 as f32);
+    let h = (size / 2) as f32;
+
+    match style {
+        MarkerStyle::Cross => {
+            draw_line_segment_mut(canvas, (x - h, y), (x + h, y), color);
+            draw_line_segment_mut(canvas, (x, y - h), (x, y + h), color);
+        }
+        MarkerStyle::TiltedCross => {
+            draw_line_segment_mut(canvas, (x - h, y - h), (x + h, y + h), color);
+            draw_line_segment_mut(canvas, (x + h, y - h), (x - h, y + h), color);
+        }
+        MarkerStyle::Star => {
+            draw_marker_mut(canvas, center, MarkerStyle::Cross, size, color);
+            draw_marker_mut(canvas, center, MarkerStyle::TiltedCross, size, color);
+        }
+        MarkerStyle::Diamond => {
+            let points = [(x, y - h), (x + h, y), (x, y + h), (x - h, y)];
+            draw_closed_path(canvas, &points, color);
+        }
+        MarkerStyle::Square => {
+            let points = [
+                (x - h, y - h),
+                (x + h, y - h),
+                (x + h, y + h),
+                (x - h, y + h),
+            ];
+            draw_closed_path(canvas, &points, color);
+        }
+        MarkerStyle::TriangleUp => {
+            let points = [(x - h, y + h), (x + h, y + h), (x, y - h)];
+            draw_closed_path(canvas, &points, color);
+        }
+        MarkerStyle::TriangleDown => {
+            let points = [(x - h, y - h), (x + h, y - h), (x, y + h)];
+            draw_closed_path(canvas, &points, color);
+        }
+    }
+}
+
+fn draw_closed_path<C>(canvas: &mut C, points: &[(f32, f32)], color: C::Pixel)
+where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    for i in 0..points.len() {
+        draw_line_segment_mut(canvas, points[i], points[(i + 1) % points.len()], color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    fn draw(style: MarkerStyle) -> GrayImage {
+        draw_marker(
+            &GrayImage::from_pixel(7, 7, Luma([1u8])),
+            (3, 3),
+            style,
+            4,
+            Luma([2u8]),
+        )
+    }
+
+    #[test]
+    fn test_draw_marker_cross() {
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 2, 2, 2, 2, 2, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 1, 1, 1, 1, 1);
+        assert_pixels_eq!(draw(MarkerStyle::Cross), expected);
+    }
+
+    #[test]
+    fn test_draw_marker_tilted_cross() {
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 2, 1, 1, 1, 2, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 2, 1, 1, 1, 2, 1;
+            1, 1, 1, 1, 1, 1, 1);
+        assert_pixels_eq!(draw(MarkerStyle::TiltedCross), expected);
+    }
+
+    #[test]
+    fn test_draw_marker_star() {
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 2, 1, 2, 1, 2, 1;
+            1, 1, 2, 2, 2, 1, 1;
+            1, 2, 2, 2, 2, 2, 1;
+            1, 1, 2, 2, 2, 1, 1;
+            1, 2, 1, 2, 1, 2, 1;
+            1, 1, 1, 1, 1, 1, 1);
+        assert_pixels_eq!(draw(MarkerStyle::Star), expected);
+    }
+
+    #[test]
+    fn test_draw_marker_diamond() {
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 2, 1, 1, 1, 2, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 1, 1, 1, 1, 1);
+        assert_pixels_eq!(draw(MarkerStyle::Diamond), expected);
+    }
+
+    #[test]
+    fn test_draw_marker_square() {
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 2, 2, 2, 2, 2, 1;
+            1, 2, 1, 1, 1, 2, 1;
+            1, 2, 1, 1, 1, 2, 1;
+            1, 2, 1, 1, 1, 2, 1;
+            1, 2, 2, 2, 2, 2, 1;
+            1, 1, 1, 1, 1, 1, 1);
+        assert_pixels_eq!(draw(MarkerStyle::Square), expected);
+    }
+
+    #[test]
+    fn test_draw_marker_triangle_up() {
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 2, 2, 2, 2, 2, 1;
+            1, 1, 1, 1, 1, 1, 1);
+        assert_pixels_eq!(draw(MarkerStyle::TriangleUp), expected);
+    }
+
+    #[test]
+    fn test_draw_marker_triangle_down() {
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 2, 2, 2, 2, 2, 1;
+            1, 2, 1, 1, 1, 2, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 1, 2, 1, 2, 1, 1;
+            1, 1, 1, 2, 1, 1, 1;
+            1, 1, 1, 1, 1, 1, 1);
+        assert_pixels_eq!(draw(MarkerStyle::TriangleDown), expected);
+    }
+}
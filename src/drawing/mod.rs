@@ -2,12 +2,19 @@
 //!
 //! Every `draw_` function comes in two variants: one creates a new copy of the input image, one modifies the image in place.
 //! The latter is more memory efficient, but you lose the original image.
+//!
+//! The in-place variants draw onto any [`Canvas`](trait.Canvas.html), so the same functions can be used
+//! to draw into a sub-image view, or with blending via [`Blend`](struct.Blend.html) or
+//! [`Translucent`](struct.Translucent.html).
+
+mod arrow;
+pub use self::arrow::{draw_arrow, draw_arrow_mut, draw_filled_arrow, draw_filled_arrow_mut};
 
 mod bezier;
 pub use self::bezier::{draw_cubic_bezier_curve, draw_cubic_bezier_curve_mut};
 
 mod canvas;
-pub use self::canvas::{Blend, Canvas};
+pub use self::canvas::{Blend, Canvas, Translucent};
 
 mod conics;
 pub use self::conics::{
@@ -15,25 +22,47 @@ pub use self::conics::{
     draw_hollow_circle, draw_hollow_circle_mut, draw_hollow_ellipse, draw_hollow_ellipse_mut,
 };
 
+mod contours;
+pub use self::contours::{
+    draw_contours, draw_contours_by_depth, draw_contours_by_depth_mut, draw_contours_mut,
+};
+
 mod cross;
 pub use self::cross::{draw_cross, draw_cross_mut};
 
+mod gradient;
+pub use self::gradient::{
+    draw_gradient_filled_circle, draw_gradient_filled_circle_mut, draw_gradient_filled_polygon,
+    draw_gradient_filled_polygon_mut, draw_gradient_filled_rect, draw_gradient_filled_rect_mut,
+    Gradient,
+};
+
 mod line;
 pub use self::line::{
-    draw_antialiased_line_segment, draw_antialiased_line_segment_mut, draw_line_segment,
-    draw_line_segment_mut, BresenhamLineIter, BresenhamLinePixelIter, BresenhamLinePixelIterMut,
+    clip_line_segment, draw_antialiased_line_segment, draw_antialiased_line_segment_mut,
+    draw_line_segment, draw_line_segment_mut, BresenhamLineIter, BresenhamLinePixelIter,
+    BresenhamLinePixelIterMut,
 };
 
+mod marker;
+pub use self::marker::{draw_marker, draw_marker_mut, MarkerStyle};
+
 mod polygon;
 pub use self::polygon::{draw_polygon, draw_polygon_mut};
 
+mod polyline;
+pub use self::polyline::{
+    draw_closed_polyline, draw_closed_polyline_mut, draw_polyline, draw_polyline_mut,
+};
+
 mod rect;
 pub use self::rect::{
-    draw_filled_rect, draw_filled_rect_mut, draw_hollow_rect, draw_hollow_rect_mut,
+    draw_filled_rect, draw_filled_rect_mut, draw_filled_rotated_rect, draw_filled_rotated_rect_mut,
+    draw_hollow_rect, draw_hollow_rect_mut, draw_hollow_rotated_rect, draw_hollow_rotated_rect_mut,
 };
 
 mod text;
-pub use self::text::{draw_text, draw_text_mut, text_size};
+pub use self::text::{draw_text, draw_text_mut, text_bounds, text_size};
 
 // Set pixel at (x, y) to color if this point lies within image bounds,
 // otherwise do nothing.
@@ -2,7 +2,7 @@ use crate::definitions::Image;
 use crate::drawing::line::draw_line_segment_mut;
 use crate::drawing::Canvas;
 use crate::point::Point;
-use image::{GenericImage, ImageBuffer};
+use image::{GenericImage, GenericImageView, ImageBuffer};
 use std::cmp::{max, min};
 use std::f32;
 use std::i32;
@@ -14,7 +14,7 @@ use std::i32;
 /// An implicit edge is added from the last to the first point in the slice.
 pub fn draw_polygon<I>(image: &I, poly: &[Point<i32>], color: I::Pixel) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
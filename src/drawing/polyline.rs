@@ -0,0 +1,202 @@
+use crate::definitions::Image;
+use crate::drawing::cross::draw_cross_mut;
+use crate::drawing::line::draw_line_segment_mut;
+use crate::drawing::Canvas;
+use crate::point::Point;
+use image::{GenericImage, GenericImageView, ImageBuffer};
+use num::{cast, NumCast};
+
+/// Draws a sequence of connected line segments on a new copy of an image.
+///
+/// Draws as much of the polyline as lies inside the image bounds. Consecutive points are
+/// connected by line segments, and the last point is not connected back to the first.
+/// See [`draw_closed_polyline`](fn.draw_closed_polyline.html) for a closed variant.
+///
+/// If `mark_vertices` is true then a cross is drawn at each point.
+pub fn draw_polyline<I, T>(
+    image: &I,
+    points: &[Point<T>],
+    color: I::Pixel,
+    mark_vertices: bool,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    T: NumCast + Copy,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_polyline_mut(&mut out, points, color, mark_vertices);
+    out
+}
+
+/// Draws a sequence of connected line segments on an image in place.
+///
+/// Draws as much of the polyline as lies inside the image bounds. Consecutive points are
+/// connected by line segments, and the last point is not connected back to the first.
+/// See [`draw_closed_polyline_mut`](fn.draw_closed_polyline_mut.html) for a closed variant.
+///
+/// If `mark_vertices` is true then a cross is drawn at each point.
+pub fn draw_polyline_mut<C, T>(
+    canvas: &mut C,
+    points: &[Point<T>],
+    color: C::Pixel,
+    mark_vertices: bool,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+    T: NumCast + Copy,
+{
+    let points: Vec<(f32, f32)> = points.iter().map(to_f32).collect();
+    draw_path(canvas, &points, color, mark_vertices, false);
+}
+
+/// Draws a closed sequence of connected line segments on a new copy of an image.
+///
+/// Draws as much of the polyline as lies inside the image bounds. Consecutive points are
+/// connected by line segments, and an edge is added from the last to the first point.
+/// This can be used to draw the output of e.g. [`find_contours`](../contours/fn.find_contours.html).
+///
+/// If `mark_vertices` is true then a cross is drawn at each point.
+pub fn draw_closed_polyline<I, T>(
+    image: &I,
+    points: &[Point<T>],
+    color: I::Pixel,
+    mark_vertices: bool,
+) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    T: NumCast + Copy,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_closed_polyline_mut(&mut out, points, color, mark_vertices);
+    out
+}
+
+/// Draws a closed sequence of connected line segments on an image in place.
+///
+/// Draws as much of the polyline as lies inside the image bounds. Consecutive points are
+/// connected by line segments, and an edge is added from the last to the first point.
+/// This can be used to draw the output of e.g. [`find_contours`](../contours/fn.find_contours.html).
+///
+/// If `mark_vertices` is true then a cross is drawn at each point.
+pub fn draw_closed_polyline_mut<C, T>(
+    canvas: &mut C,
+    points: &[Point<T>],
+    color: C::Pixel,
+    mark_vertices: bool,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+    T: NumCast + Copy,
+{
+    let points: Vec<(f32, f32)> = points.iter().map(to_f32).collect();
+    draw_path(canvas, &points, color, mark_vertices, true);
+}
+
+fn to_f32<T: NumCast + Copy>(p: &Point<T>) -> (f32, f32) {
+    (
+        cast(p.x).expect("point coordinate out of range for f32"),
+        cast(p.y).expect("point coordinate out of range for f32"),
+    )
+}
+
+fn draw_path<C>(
+    canvas: &mut C,
+    points: &[(f32, f32)],
+    color: C::Pixel,
+    mark_vertices: bool,
+    closed: bool,
+) where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    if points.is_empty() {
+        return;
+    }
+    if points.len() == 1 {
+        draw_line_segment_mut(canvas, points[0], points[0], color);
+    }
+    for edge in points.windows(2) {
+        draw_line_segment_mut(canvas, edge[0], edge[1], color);
+    }
+    if closed && points.len() > 2 {
+        draw_line_segment_mut(canvas, points[points.len() - 1], points[0], color);
+    }
+
+    if mark_vertices {
+        for p in points {
+            draw_cross_mut(canvas, color, p.0.round() as i32, p.1.round() as i32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn test_draw_polyline() {
+        let image = GrayImage::from_pixel(5, 5, Luma([1u8]));
+
+        let expected = gray_image!(
+            1, 1, 1, 1, 1;
+            1, 4, 4, 4, 1;
+            1, 1, 1, 4, 1;
+            1, 1, 1, 4, 1;
+            1, 1, 1, 1, 1);
+
+        let points = [Point::new(1, 1), Point::new(3, 1), Point::new(3, 3)];
+        let actual = draw_polyline(&image, &points, Luma([4u8]), false);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_closed_polyline() {
+        let image = GrayImage::from_pixel(5, 5, Luma([1u8]));
+
+        let expected = gray_image!(
+            1, 1, 1, 1, 1;
+            1, 4, 4, 4, 1;
+            1, 1, 4, 4, 1;
+            1, 1, 1, 4, 1;
+            1, 1, 1, 1, 1);
+
+        let points = [
+            Point::new(1.0, 1.0),
+            Point::new(3.0, 1.0),
+            Point::new(3.0, 3.0),
+        ];
+        let actual = draw_closed_polyline(&image, &points, Luma([4u8]), false);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_polyline_mark_vertices() {
+        let image = GrayImage::from_pixel(7, 5, Luma([1u8]));
+
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 4, 1, 1, 1, 4, 1;
+            4, 4, 4, 4, 4, 4, 4;
+            1, 4, 1, 1, 1, 4, 1;
+            1, 1, 1, 1, 1, 1, 1);
+
+        let points = [Point::new(1u32, 2u32), Point::new(5u32, 2u32)];
+        let actual = draw_polyline(&image, &points, Luma([4u8]), true);
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_draw_polyline_empty() {
+        let image = GrayImage::from_pixel(3, 3, Luma([1u8]));
+        let points: [Point<i32>; 0] = [];
+        assert_pixels_eq!(
+            draw_closed_polyline(&image, &points, Luma([4u8]), true),
+            image
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use crate::definitions::Image;
 use crate::drawing::line::draw_line_segment_mut;
 use crate::drawing::Canvas;
-use crate::rect::Rect;
-use image::{GenericImage, ImageBuffer};
+use crate::rect::{Rect, Region, RotatedRect};
+use image::{GenericImage, GenericImageView, ImageBuffer};
 use std::f32;
 
 /// Draws the outline of a rectangle on a new copy of an image.
@@ -10,7 +10,7 @@ use std::f32;
 /// Draws as much of the boundary of the rectangle as lies inside the image bounds.
 pub fn draw_hollow_rect<I>(image: &I, rect: Rect, color: I::Pixel) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -43,7 +43,7 @@ where
 /// Draws as much of the rectangle and its contents as lies inside the image bounds.
 pub fn draw_filled_rect<I>(image: &I, rect: Rect, color: I::Pixel) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -72,6 +72,83 @@ where
     }
 }
 
+/// Draws the outline of a rotated rectangle on a new copy of an image.
+///
+/// Draws as much of the boundary of the rectangle as lies inside the image bounds.
+pub fn draw_hollow_rotated_rect<I>(image: &I, rect: RotatedRect, color: I::Pixel) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_hollow_rotated_rect_mut(&mut out, rect, color);
+    out
+}
+
+/// Draws the outline of a rotated rectangle on an image in place.
+///
+/// Draws as much of the boundary of the rectangle as lies inside the image bounds.
+pub fn draw_hollow_rotated_rect_mut<C>(canvas: &mut C, rect: RotatedRect, color: C::Pixel)
+where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    // The outline passes through the centres of the outermost pixels covered by the
+    // rectangle, so that an unrotated rectangle matches draw_hollow_rect.
+    let half_width = ((rect.width() - 1.0) / 2.0).max(0.0);
+    let half_height = ((rect.height() - 1.0) / 2.0).max(0.0);
+    let corners = rect.corners_with_extents(half_width, half_height);
+
+    for i in 0..4 {
+        let start = corners[i];
+        let end = corners[(i + 1) % 4];
+        draw_line_segment_mut(
+            canvas,
+            (start.0.round(), start.1.round()),
+            (end.0.round(), end.1.round()),
+            color,
+        );
+    }
+}
+
+/// Draws a rotated rectangle and its contents on a new copy of an image.
+///
+/// Draws as much of the rectangle and its contents as lies inside the image bounds.
+pub fn draw_filled_rotated_rect<I>(image: &I, rect: RotatedRect, color: I::Pixel) -> Image<I::Pixel>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+{
+    let mut out = ImageBuffer::new(image.width(), image.height());
+    out.copy_from(image, 0, 0).unwrap();
+    draw_filled_rotated_rect_mut(&mut out, rect, color);
+    out
+}
+
+/// Draws a rotated rectangle and its contents on an image in place.
+///
+/// Draws as much of the rectangle and its contents as lies inside the image bounds.
+/// A pixel is drawn if its centre lies within the rectangle.
+pub fn draw_filled_rotated_rect_mut<C>(canvas: &mut C, rect: RotatedRect, color: C::Pixel)
+where
+    C: Canvas,
+    C::Pixel: 'static,
+{
+    let canvas_bounds = Rect::at(0, 0).of_size(canvas.width(), canvas.height());
+    if let Some(bounds) = canvas_bounds.intersect(rect.bounding_rect()) {
+        for y in bounds.top()..=bounds.bottom() {
+            for x in bounds.left()..=bounds.right() {
+                if rect.contains(x as f32, y as f32) {
+                    canvas.draw_pixel(x as u32, y as u32, color);
+                }
+            }
+        }
+    }
+    // Avoid gaps along the boundary due to rounding.
+    draw_hollow_rotated_rect_mut(canvas, rect, color);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,4 +236,55 @@ mod tests {
         draw_filled_rect_mut(&mut image, Rect::at(2, 2).of_size(1, 1), blue);
         assert_eq!(*image.0.get_pixel(2, 2), blue);
     }
+
+    #[test]
+    fn test_draw_unrotated_rect_matches_rect() {
+        let image = GrayImage::from_pixel(6, 6, Luma([1u8]));
+        let rect = Rect::at(1, 2).of_size(4, 3);
+        let rotated = RotatedRect::new((2.5, 3.0), 4.0, 3.0, 0.0);
+
+        assert_pixels_eq!(
+            draw_hollow_rotated_rect(&image, rotated, Luma([4u8])),
+            draw_hollow_rect(&image, rect, Luma([4u8]))
+        );
+        assert_pixels_eq!(
+            draw_filled_rotated_rect(&image, rotated, Luma([4u8])),
+            draw_filled_rect(&image, rect, Luma([4u8]))
+        );
+    }
+
+    #[test]
+    fn test_draw_rotated_rect_quarter_turn() {
+        let image = GrayImage::from_pixel(6, 6, Luma([1u8]));
+        let rect = Rect::at(2, 1).of_size(3, 4);
+        let rotated = RotatedRect::new((3.0, 2.5), 4.0, 3.0, std::f32::consts::FRAC_PI_2);
+
+        assert_pixels_eq!(
+            draw_hollow_rotated_rect(&image, rotated, Luma([4u8])),
+            draw_hollow_rect(&image, rect, Luma([4u8]))
+        );
+        assert_pixels_eq!(
+            draw_filled_rotated_rect(&image, rotated, Luma([4u8])),
+            draw_filled_rect(&image, rect, Luma([4u8]))
+        );
+    }
+
+    #[test]
+    fn test_draw_filled_rotated_rect_diamond() {
+        let image = GrayImage::from_pixel(7, 7, Luma([1u8]));
+
+        let expected = gray_image!(
+            1, 1, 1, 1, 1, 1, 1;
+            1, 1, 1, 4, 1, 1, 1;
+            1, 1, 4, 4, 4, 1, 1;
+            1, 4, 4, 4, 4, 4, 1;
+            1, 1, 4, 4, 4, 1, 1;
+            1, 1, 1, 4, 1, 1, 1;
+            1, 1, 1, 1, 1, 1, 1);
+
+        let side = 2.0 * std::f32::consts::SQRT_2 + 1.0;
+        let rect = RotatedRect::new((3.0, 3.0), side, side, std::f32::consts::FRAC_PI_4);
+        let actual = draw_filled_rotated_rect(&image, rect, Luma([4u8]));
+        assert_pixels_eq!(actual, expected);
+    }
 }
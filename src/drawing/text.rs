@@ -7,7 +7,7 @@ use std::i32;
 
 use crate::pixelops::weighted_sum;
 use rusttype::{point, Font, PositionedGlyph, Rect, Scale};
-use std::cmp::max;
+use std::cmp::{max, min};
 
 fn layout_glyphs(
     scale: Scale,
@@ -37,6 +37,37 @@ pub fn text_size(scale: Scale, font: &Font, text: &str) -> (i32, i32) {
     layout_glyphs(scale, font, text, |_, _| {})
 }
 
+/// Get the bounding box of the pixels covered by the given text when it is drawn
+/// at position (x, y), e.g. by [`draw_text_mut`](fn.draw_text_mut.html).
+///
+/// Returns `None` if the text contains no visible glyphs. This can be used to
+/// position labels or to draw a background behind them before drawing the text.
+///
+/// Note that this function *does not* support newlines, you must do this manually.
+pub fn text_bounds(
+    x: i32,
+    y: i32,
+    scale: Scale,
+    font: &Font,
+    text: &str,
+) -> Option<crate::rect::Rect> {
+    let mut bounds: Option<Rect<i32>> = None;
+    layout_glyphs(scale, font, text, |_, bb| {
+        bounds = Some(match bounds {
+            Some(b) => Rect {
+                min: point(min(b.min.x, bb.min.x), min(b.min.y, bb.min.y)),
+                max: point(max(b.max.x, bb.max.x), max(b.max.y, bb.max.y)),
+            },
+            None => bb,
+        });
+    });
+
+    bounds.map(|b| {
+        crate::rect::Rect::at(x + b.min.x, y + b.min.y)
+            .of_size((b.max.x - b.min.x) as u32, (b.max.y - b.min.y) as u32)
+    })
+}
+
 /// Draws colored text on an image in place.
 ///
 /// `scale` is augmented font scaling on both the x and y axis (in pixels).
@@ -80,7 +111,7 @@ pub fn draw_text_mut<'a, C>(
 ///
 /// Note that this function *does not* support newlines, you must do this manually.
 pub fn draw_text<'a, I>(
-    image: &'a mut I,
+    image: &'a I,
     color: I::Pixel,
     x: i32,
     y: i32,
@@ -98,3 +129,51 @@ where
     draw_text_mut(&mut out, color, x, y, scale, font, text);
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rect::Region;
+    use image::{GrayImage, Luma};
+
+    fn test_font() -> Font<'static> {
+        Font::try_from_bytes(include_bytes!("../../examples/DejaVuSans.ttf")).unwrap()
+    }
+
+    #[test]
+    fn test_text_bounds_contains_drawn_pixels() {
+        let font = test_font();
+        let scale = Scale::uniform(20.0);
+        let text = "Hello";
+
+        let image = draw_text(
+            &GrayImage::new(100, 50),
+            Luma([255u8]),
+            10,
+            12,
+            scale,
+            &font,
+            text,
+        );
+        let bounds = text_bounds(10, 12, scale, &font, text).unwrap();
+
+        let mut drawn = 0;
+        for (x, y, p) in image.enumerate_pixels() {
+            if p[0] > 0 {
+                drawn += 1;
+                assert!(bounds.contains(x as i32, y as i32));
+            }
+        }
+        assert!(drawn > 0);
+
+        let (w, h) = text_size(scale, &font, text);
+        assert_eq!(bounds.right() + 1, 10 + w);
+        assert_eq!(bounds.bottom() + 1, 12 + h);
+    }
+
+    #[test]
+    fn test_text_bounds_whitespace() {
+        let font = test_font();
+        assert_eq!(text_bounds(0, 0, Scale::uniform(20.0), &font, "   "), None);
+    }
+}
@@ -0,0 +1,108 @@
+//! The error type returned by the fallible `try_*` variants of functions in this crate.
+//!
+//! Many functions in this crate panic when given degenerate inputs, e.g. a template larger
+//! than the image it is matched against. Where a `try_*` variant exists it performs the same
+//! checks but returns an [`Error`](enum.Error.html) instead, so that applications can reject
+//! bad inputs without catching panics. The panicking functions panic with the error's
+//! `Display` message.
+
+use std::fmt;
+
+/// Errors returned by the `try_*` functions in this crate.
+///
+/// New variants may be added in future releases, so matches on this type must include a
+/// wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An image did not have the dimensions required by the operation.
+    DimensionMismatch {
+        /// The required dimensions, as `(width, height)`.
+        expected: (u32, u32),
+        /// The actual dimensions, as `(width, height)`.
+        actual: (u32, u32),
+    },
+    /// A kernel or template was larger than the image it was applied to.
+    KernelTooLarge {
+        /// The dimensions of the kernel or template, as `(width, height)`.
+        kernel: (u32, u32),
+        /// The dimensions of the image, as `(width, height)`.
+        image: (u32, u32),
+    },
+    /// An image with zero width or height was provided where a non-empty image is required.
+    EmptyImage,
+    /// A parameter had an invalid value.
+    InvalidParameter {
+        /// The name of the parameter.
+        name: &'static str,
+        /// Why the value is invalid.
+        reason: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DimensionMismatch { expected, actual } => write!(
+                f,
+                "expected an image of dimensions {:?}, found {:?}",
+                expected, actual
+            ),
+            Error::KernelTooLarge { kernel, image } => write!(
+                f,
+                "kernel dimensions {:?} exceed image dimensions {:?}",
+                kernel, image
+            ),
+            Error::EmptyImage => write!(f, "image must be non-empty"),
+            Error::InvalidParameter { name, reason } => write!(f, "invalid {}: {}", name, reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Returns an error if actual does not equal expected.
+pub(crate) fn check_dimensions(actual: (u32, u32), expected: (u32, u32)) -> Result<(), Error> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::DimensionMismatch { expected, actual })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::DimensionMismatch {
+                expected: (3, 4),
+                actual: (4, 3)
+            }
+            .to_string(),
+            "expected an image of dimensions (3, 4), found (4, 3)"
+        );
+        assert_eq!(
+            Error::InvalidParameter {
+                name: "sigma",
+                reason: "must be > 0.0".to_string()
+            }
+            .to_string(),
+            "invalid sigma: must be > 0.0"
+        );
+    }
+
+    #[test]
+    fn test_check_dimensions() {
+        assert_eq!(check_dimensions((2, 3), (2, 3)), Ok(()));
+        assert_eq!(
+            check_dimensions((2, 3), (3, 2)),
+            Err(Error::DimensionMismatch {
+                expected: (3, 2),
+                actual: (2, 3)
+            })
+        );
+    }
+}
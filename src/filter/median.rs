@@ -1,4 +1,5 @@
 use crate::definitions::Image;
+use crate::error::{check_dimensions, Error};
 use image::{GenericImageView, Pixel};
 use std::cmp::{max, min};
 
@@ -105,41 +106,75 @@ use std::cmp::{max, min};
 /// assert_pixels_eq!(median_filter(&image, 2, 1), filtered);
 /// # }
 /// ```
-pub fn median_filter<P>(image: &Image<P>, x_radius: u32, y_radius: u32) -> Image<P>
+pub fn median_filter<I, P>(image: &I, x_radius: u32, y_radius: u32) -> Image<P>
 where
+    I: GenericImageView<Pixel = P>,
     P: Pixel<Subpixel = u8> + 'static,
 {
     let (width, height) = image.dimensions();
+    let mut out = Image::<P>::new(width, height);
+    median_filter_into(image, x_radius, y_radius, &mut out);
+    out
+}
+
+/// Applies a median filter of given dimensions to an image, writing the result to `out`,
+/// which must have the same dimensions as `image`.
+///
+/// See the [`median_filter`](fn.median_filter.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn median_filter_into<I, P>(image: &I, x_radius: u32, y_radius: u32, out: &mut Image<P>)
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    try_median_filter_into(image, x_radius, y_radius, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Applies a median filter of given dimensions to an image, writing the result to `out`.
+/// Returns an error if `out` does not have the same dimensions as `image`.
+///
+/// See the [`median_filter`](fn.median_filter.html) documentation for more information.
+pub fn try_median_filter_into<I, P>(
+    image: &I,
+    x_radius: u32,
+    y_radius: u32,
+    out: &mut Image<P>,
+) -> Result<(), Error>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    let (width, height) = image.dimensions();
 
     if width == 0 || height == 0 {
-        return image.clone();
+        return Ok(());
     }
 
-    let mut out = Image::<P>::new(width, height);
     let rx = x_radius as i32;
     let ry = y_radius as i32;
 
-    let mut hist = initialise_histogram_for_top_left_pixel(&image, x_radius, y_radius);
-    slide_down_column(&mut hist, &image, &mut out, 0, rx, ry);
+    let mut hist = initialise_histogram_for_top_left_pixel(image, x_radius, y_radius);
+    slide_down_column(&mut hist, image, out, 0, rx, ry);
 
     for x in 1..width {
         if x % 2 == 0 {
-            slide_right(&mut hist, &image, x, 0, rx, ry);
-            slide_down_column(&mut hist, &image, &mut out, x, rx, ry);
+            slide_right(&mut hist, image, x, 0, rx, ry);
+            slide_down_column(&mut hist, image, out, x, rx, ry);
         } else {
-            slide_right(&mut hist, &image, x, height - 1, rx, ry);
-            slide_up_column(&mut hist, &image, &mut out, x, rx, ry);
+            slide_right(&mut hist, image, x, height - 1, rx, ry);
+            slide_up_column(&mut hist, image, out, x, rx, ry);
         }
     }
-    out
+    Ok(())
 }
 
-fn initialise_histogram_for_top_left_pixel<P>(
-    image: &Image<P>,
-    x_radius: u32,
-    y_radius: u32,
-) -> HistSet
+fn initialise_histogram_for_top_left_pixel<I, P>(image: &I, x_radius: u32, y_radius: u32) -> HistSet
 where
+    I: GenericImageView<Pixel = P>,
     P: Pixel<Subpixel = u8> + 'static,
 {
     let (width, height) = image.dimensions();
@@ -163,8 +198,9 @@ where
     hist
 }
 
-fn slide_right<P>(hist: &mut HistSet, image: &Image<P>, x: u32, y: u32, rx: i32, ry: i32)
+fn slide_right<I, P>(hist: &mut HistSet, image: &I, x: u32, y: u32, rx: i32, ry: i32)
 where
+    I: GenericImageView<Pixel = P>,
     P: Pixel<Subpixel = u8> + 'static,
 {
     let (width, height) = image.dimensions();
@@ -180,14 +216,15 @@ where
     }
 }
 
-fn slide_down_column<P>(
+fn slide_down_column<I, P>(
     hist: &mut HistSet,
-    image: &Image<P>,
+    image: &I,
     out: &mut Image<P>,
     x: u32,
     rx: i32,
     ry: i32,
 ) where
+    I: GenericImageView<Pixel = P>,
     P: Pixel<Subpixel = u8> + 'static,
 {
     let (width, height) = image.dimensions();
@@ -208,14 +245,15 @@ fn slide_down_column<P>(
     }
 }
 
-fn slide_up_column<P>(
+fn slide_up_column<I, P>(
     hist: &mut HistSet,
-    image: &Image<P>,
+    image: &I,
     out: &mut Image<P>,
     x: u32,
     rx: i32,
     ry: i32,
 ) where
+    I: GenericImageView<Pixel = P>,
     P: Pixel<Subpixel = u8> + 'static,
 {
     let (width, height) = image.dimensions();
@@ -262,8 +300,9 @@ impl HistSet {
         }
     }
 
-    fn incr<P>(&mut self, image: &Image<P>, x: u32, y: u32)
+    fn incr<I, P>(&mut self, image: &I, x: u32, y: u32)
     where
+        I: GenericImageView<Pixel = P>,
         P: Pixel<Subpixel = u8> + 'static,
     {
         unsafe {
@@ -277,8 +316,9 @@ impl HistSet {
         }
     }
 
-    fn decr<P>(&mut self, image: &Image<P>, x: u32, y: u32)
+    fn decr<I, P>(&mut self, image: &I, x: u32, y: u32)
     where
+        I: GenericImageView<Pixel = P>,
         P: Pixel<Subpixel = u8> + 'static,
     {
         unsafe {
@@ -1,22 +1,36 @@
 //! Functions for filtering images.
+//!
+//! The filters accept any `GenericImageView`, so can be applied to a region of an image
+//! created by [`rect::view`](../rect/fn.view.html) without copying it out first. The
+//! specialised `u8` implementations are used for `GrayImage`s and for `SubImage`s of them,
+//! such as those returned by `rect::view`. Other views use the generic implementations.
 
 mod median;
-pub use self::median::median_filter;
+pub use self::median::{median_filter, median_filter_into, try_median_filter_into};
 
 mod sharpen;
 pub use self::sharpen::*;
 
-use image::{GenericImage, GenericImageView, GrayImage, ImageBuffer, Luma, Pixel, Primitive};
+mod simd;
 
-use crate::definitions::{Clamp, Image};
-use crate::integral_image::{column_running_sum, row_running_sum};
+use image::{
+    GenericImage, GenericImageView, GrayImage, ImageBuffer, Luma, Pixel, Primitive, SubImage,
+};
+
+use crate::definitions::{Clamp, Image, MaybeSend, MaybeSync};
+use crate::error::{check_dimensions, Error};
+use crate::gpu;
+use crate::integral_image::column_running_sum;
 use crate::map::{ChannelMap, WithChannel};
+use crate::parallel::for_each_row_init;
 use num::{abs, pow, Num};
 
 use crate::math::cast;
 use conv::ValueInto;
+use std::any::{Any, TypeId};
 use std::cmp::{max, min};
 use std::f32;
+use std::marker::PhantomData;
 
 /// Denoise 8-bit grayscale image using bilateral filtering.
 ///
@@ -49,12 +63,15 @@ use std::f32;
 /// let image = gray_bench_image(500, 500);
 /// let filtered = bilateral_filter(&image, 10, 10., 3.);
 /// ```
-pub fn bilateral_filter(
-    image: &GrayImage,
+pub fn bilateral_filter<I>(
+    image: &I,
     window_size: u32,
     sigma_color: f32,
     sigma_spatial: f32,
-) -> Image<Luma<u8>> {
+) -> Image<Luma<u8>>
+where
+    I: GenericImageView<Pixel = Luma<u8>>,
+{
     /// Un-normalized Gaussian weights for look-up tables.
     fn gaussian_weight(x: f32, sigma_squared: f32) -> f32 {
         return (-0.5 * x.powi(2) / sigma_squared).exp();
@@ -109,7 +126,7 @@ pub fn bilateral_filter(
 
     let (width, height) = image.dimensions();
     let mut out = ImageBuffer::new(width, height);
-    let max_value = *image.iter().max().unwrap() as f32;
+    let max_value = image.pixels().map(|(_, _, p)| p[0]).max().unwrap() as f32;
     let n_bins: u32 = 255; // for color or > 8-bit, make n_bins a user input for tuning accuracy.
     let color_lut = compute_color_lut(n_bins, sigma_color, max_value);
     let color_dist_scale = n_bins as f32 / max_value;
@@ -152,36 +169,138 @@ pub fn bilateral_filter(
     out
 }
 
-/// Convolves an 8bpp grayscale image with a kernel of width (2 * `x_radius` + 1)
+/// Convolves a grayscale image with a kernel of width (2 * `x_radius` + 1)
 /// and height (2 * `y_radius` + 1) whose entries are equal and
 /// sum to one. i.e. each output pixel is the unweighted mean of
 /// a rectangular region surrounding its corresponding input pixel.
 /// We handle locations where the kernel would extend past the image's
 /// boundary by treating the image as if its boundary pixels were
 /// repeated indefinitely.
+///
+/// For integer subpixel types the means are rounded down.
 // TODO: for small kernels we probably want to do the convolution
 // TODO: directly instead of using an integral image.
 // TODO: more formats!
-pub fn box_filter(image: &GrayImage, x_radius: u32, y_radius: u32) -> Image<Luma<u8>> {
+pub fn box_filter<I, T>(image: &I, x_radius: u32, y_radius: u32) -> Image<Luma<T>>
+where
+    I: GenericImageView<Pixel = Luma<T>>,
+    T: Primitive + ValueInto<f64> + Clamp<f64> + 'static,
+{
     let (width, height) = image.dimensions();
     let mut out = ImageBuffer::new(width, height);
+    box_filter_into(image, x_radius, y_radius, &mut out);
+    out
+}
+
+/// Applies a box filter to an image, writing the result to `out`, which must have the
+/// same dimensions as `image`.
+///
+/// See the [`box_filter`](fn.box_filter.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn box_filter_into<I, T>(image: &I, x_radius: u32, y_radius: u32, out: &mut Image<Luma<T>>)
+where
+    I: GenericImageView<Pixel = Luma<T>>,
+    T: Primitive + ValueInto<f64> + Clamp<f64> + 'static,
+{
+    try_box_filter_into(image, x_radius, y_radius, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Applies a box filter to an image, writing the result to `out`. Returns an error if
+/// `out` does not have the same dimensions as `image`.
+///
+/// See the [`box_filter`](fn.box_filter.html) documentation for more information.
+pub fn try_box_filter_into<I, T>(
+    image: &I,
+    x_radius: u32,
+    y_radius: u32,
+    out: &mut Image<Luma<T>>,
+) -> Result<(), Error>
+where
+    I: GenericImageView<Pixel = Luma<T>>,
+    T: Primitive + ValueInto<f64> + Clamp<f64> + 'static,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    let (width, height) = image.dimensions();
     if width == 0 || height == 0 {
-        return out;
+        return Ok(());
+    }
+
+    let gray = GrayRegion::of(image);
+    let gray_out = (out as &mut dyn Any).downcast_mut::<GrayImage>();
+    if let (Some(gray), Some(gray_out)) = (gray, gray_out) {
+        box_filter_u8(gray, x_radius, y_radius, gray_out);
+        return Ok(());
+    }
+
+    let kernel_width = (2 * x_radius + 1) as f64;
+    let kernel_height = (2 * y_radius + 1) as f64;
+
+    let mut line = vec![0.0; width as usize];
+    let mut row_buffer = vec![0.0; (width + 2 * x_radius) as usize];
+    for y in 0..height {
+        for (x, l) in line.iter_mut().enumerate() {
+            *l = cast(unsafe { image.unsafe_get_pixel(x as u32, y) }[0]);
+        }
+        padded_running_sum(&line, &mut row_buffer, x_radius as usize);
+        let val = row_buffer[(2 * x_radius) as usize] / kernel_width;
+        unsafe {
+            out.unsafe_put_pixel(0, y, Luma([T::clamp(val)]));
+        }
+        for x in 1..width {
+            // TODO: This way we pay rounding errors for each of the
+            // TODO: x and y convolutions. Is there a better way?
+            let u = (x + 2 * x_radius) as usize;
+            let l = (x - 1) as usize;
+            let val = (row_buffer[u] - row_buffer[l]) / kernel_width;
+            unsafe {
+                out.unsafe_put_pixel(x, y, Luma([T::clamp(val)]));
+            }
+        }
+    }
+
+    let mut line = vec![0.0; height as usize];
+    let mut col_buffer = vec![0.0; (height + 2 * y_radius) as usize];
+    for x in 0..width {
+        let column = out.iter().skip(x as usize).step_by(width as usize);
+        for (l, p) in line.iter_mut().zip(column) {
+            *l = cast(*p);
+        }
+        padded_running_sum(&line, &mut col_buffer, y_radius as usize);
+        let val = col_buffer[(2 * y_radius) as usize] / kernel_height;
+        unsafe {
+            out.unsafe_put_pixel(x, 0, Luma([T::clamp(val)]));
+        }
+        for y in 1..height {
+            let u = (y + 2 * y_radius) as usize;
+            let l = (y - 1) as usize;
+            let val = (col_buffer[u] - col_buffer[l]) / kernel_height;
+            unsafe {
+                out.unsafe_put_pixel(x, y, Luma([T::clamp(val)]));
+            }
+        }
     }
 
+    Ok(())
+}
+
+// Equivalent to box_filter, but avoids the floating point arithmetic used by the
+// generic implementation, which makes the u8 case around 1.4x slower.
+fn box_filter_u8(image: GrayRegion<'_>, x_radius: u32, y_radius: u32, out: &mut GrayImage) {
+    let (width, height) = (image.width() as u32, image.height() as u32);
     let kernel_width = 2 * x_radius + 1;
     let kernel_height = 2 * y_radius + 1;
 
     let mut row_buffer = vec![0; (width + 2 * x_radius) as usize];
     for y in 0..height {
-        row_running_sum(image, y, &mut row_buffer, x_radius);
+        padded_running_sum(image.row(y as usize), &mut row_buffer, x_radius as usize);
         let val = row_buffer[(2 * x_radius) as usize] / kernel_width;
         unsafe {
             out.unsafe_put_pixel(0, y, Luma([val as u8]));
         }
         for x in 1..width {
-            // TODO: This way we pay rounding errors for each of the
-            // TODO: x and y convolutions. Is there a better way?
             let u = (x + 2 * x_radius) as usize;
             let l = (x - 1) as usize;
             let val = (row_buffer[u] - row_buffer[l]) / kernel_width;
@@ -193,7 +312,7 @@ pub fn box_filter(image: &GrayImage, x_radius: u32, y_radius: u32) -> Image<Luma
 
     let mut col_buffer = vec![0; (height + 2 * y_radius) as usize];
     for x in 0..width {
-        column_running_sum(&out, x, &mut col_buffer, y_radius);
+        column_running_sum(out, x, &mut col_buffer, y_radius);
         let val = col_buffer[(2 * y_radius) as usize] / kernel_height;
         unsafe {
             out.unsafe_put_pixel(x, 0, Luma([val as u8]));
@@ -207,8 +326,30 @@ pub fn box_filter(image: &GrayImage, x_radius: u32, y_radius: u32) -> Image<Luma
             }
         }
     }
+}
 
-    out
+// Writes the running sums of line to buffer, treating line as if it were padded
+// at each end by `padding` copies of its first and last values.
+fn padded_running_sum<T, S>(line: &[T], buffer: &mut [S], padding: usize)
+where
+    T: Copy + Into<S>,
+    S: Num + Copy,
+{
+    let (first, last) = (line[0].into(), line[line.len() - 1].into());
+    let mut sum = S::zero();
+
+    for b in &mut buffer[..padding] {
+        sum = sum + first;
+        *b = sum;
+    }
+    for (b, p) in buffer[padding..].iter_mut().zip(line) {
+        sum = sum + (*p).into();
+        *b = sum;
+    }
+    for b in &mut buffer[padding + line.len()..] {
+        sum = sum + last;
+        *b = sum;
+    }
 }
 
 /// A 2D kernel, used to filter images via convolution.
@@ -221,60 +362,182 @@ pub struct Kernel<'a, K> {
 impl<'a, K: Num + Copy + 'a> Kernel<'a, K> {
     /// Construct a kernel from a slice and its dimensions. The input slice is
     /// in row-major form.
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is zero, or if `data.len()` is not `width * height`.
     pub fn new(data: &'a [K], width: u32, height: u32) -> Kernel<'a, K> {
-        assert!(width > 0 && height > 0, "width and height must be non-zero");
-        assert!(
-            width * height == data.len() as u32,
-            "Invalid kernel len: expecting {}, found {}",
-            width * height,
-            data.len()
-        );
-        Kernel {
+        Kernel::try_new(data, width, height).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a kernel from a slice and its dimensions, returning an error if
+    /// [`new`](#method.new) would panic.
+    pub fn try_new(data: &'a [K], width: u32, height: u32) -> Result<Kernel<'a, K>, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidParameter {
+                name: "kernel dimensions",
+                reason: "width and height must be non-zero".to_string(),
+            });
+        }
+        if width as u64 * height as u64 != data.len() as u64 {
+            return Err(Error::InvalidParameter {
+                name: "kernel data",
+                reason: format!(
+                    "expected length {}, found {}",
+                    width as u64 * height as u64,
+                    data.len()
+                ),
+            });
+        }
+        Ok(Kernel {
             data,
             width,
             height,
-        }
+        })
     }
 
     /// Returns 2d correlation of an image. Intermediate calculations are performed
     /// at type K, and the results converted to pixel Q via f. Pads by continuity.
-    pub fn filter<P, F, Q>(&self, image: &Image<P>, mut f: F) -> Image<Q>
+    ///
+    /// If the `gpu` feature is enabled, `K` is `f32` and the kernel has at least 49 entries
+    /// then the correlation is computed on the GPU if one is available.
+    pub fn filter<I, P, F, Q>(&self, image: &I, f: F) -> Image<Q>
     where
+        I: GenericImageView<Pixel = P>,
         P: Pixel + 'static,
         <P as Pixel>::Subpixel: ValueInto<K>,
         Q: Pixel + 'static,
         F: FnMut(&mut Q::Subpixel, K),
+        K: 'static,
     {
-        let (width, height) = image.dimensions();
-        let mut out = Image::<Q>::new(width, height);
-        let num_channels = P::CHANNEL_COUNT as usize;
-        let zero = K::zero();
-        let mut acc = vec![zero; num_channels];
-        let (k_width, k_height) = (self.width as i64, self.height as i64);
-        let (width, height) = (width as i64, height as i64);
+        let mut out = Image::<Q>::new(image.width(), image.height());
+        self.filter_into(image, f, &mut out);
+        out
+    }
 
-        for y in 0..height {
-            for x in 0..width {
-                for k_y in 0..k_height {
-                    let y_p = min(height - 1, max(0, y + k_y - k_height / 2)) as u32;
-                    for k_x in 0..k_width {
-                        let x_p = min(width - 1, max(0, x + k_x - k_width / 2)) as u32;
-                        accumulate(
-                            &mut acc,
-                            unsafe { &image.unsafe_get_pixel(x_p, y_p) },
-                            unsafe { *self.data.get_unchecked((k_y * k_width + k_x) as usize) },
-                        );
-                    }
-                }
-                let out_channels = out.get_pixel_mut(x as u32, y as u32).channels_mut();
-                for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                    f(c, *a);
-                    *a = zero;
+    /// Computes the 2d correlation of an image, writing the result to `out`, which must
+    /// have the same dimensions as `image`.
+    ///
+    /// See the [`filter`](#method.filter) documentation for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` does not have the same dimensions as `image`.
+    pub fn filter_into<I, P, F, Q>(&self, image: &I, f: F, out: &mut Image<Q>)
+    where
+        I: GenericImageView<Pixel = P>,
+        P: Pixel + 'static,
+        <P as Pixel>::Subpixel: ValueInto<K>,
+        Q: Pixel + 'static,
+        F: FnMut(&mut Q::Subpixel, K),
+        K: 'static,
+    {
+        self.try_filter_into(image, f, out)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Computes the 2d correlation of an image, writing the result to `out`. Returns an
+    /// error if `out` does not have the same dimensions as `image`.
+    ///
+    /// See the [`filter`](#method.filter) documentation for more information.
+    pub fn try_filter_into<I, P, F, Q>(
+        &self,
+        image: &I,
+        mut f: F,
+        out: &mut Image<Q>,
+    ) -> Result<(), Error>
+    where
+        I: GenericImageView<Pixel = P>,
+        P: Pixel + 'static,
+        <P as Pixel>::Subpixel: ValueInto<K>,
+        Q: Pixel + 'static,
+        F: FnMut(&mut Q::Subpixel, K),
+        K: 'static,
+    {
+        check_dimensions(out.dimensions(), image.dimensions())?;
+        if P::CHANNEL_COUNT == Q::CHANNEL_COUNT {
+            if let Some(sums) = gpu_filter(image, self.data, self.width, self.height) {
+                for (c, sum) in out.iter_mut().zip(sums) {
+                    f(c, sum);
                 }
+                return Ok(());
             }
         }
+        let pitch = Q::CHANNEL_COUNT as usize * image.width() as usize;
+        if pitch == 0 {
+            return Ok(());
+        }
+        let mut acc = vec![K::zero(); P::CHANNEL_COUNT as usize];
 
-        out
+        for (y, row) in out.chunks_mut(pitch).enumerate() {
+            self.filter_row::<I, P, F, Q>(image, y as u32, &mut acc, row, &mut f);
+        }
+        Ok(())
+    }
+
+    // Equivalent to filter_into, but computes rows in parallel if the rayon feature is enabled.
+    fn filter_parallel_into<I, P, F, Q>(&self, image: &I, f: F, out: &mut Image<Q>)
+    where
+        I: GenericImageView<Pixel = P> + MaybeSync,
+        P: Pixel + MaybeSync + 'static,
+        <P as Pixel>::Subpixel: ValueInto<K> + MaybeSync,
+        Q: Pixel + 'static,
+        Q::Subpixel: MaybeSend,
+        K: MaybeSync,
+        F: Fn(&mut Q::Subpixel, K) + MaybeSync,
+    {
+        assert_dimensions_match!(out, image);
+        let pitch = Q::CHANNEL_COUNT as usize * image.width() as usize;
+        if pitch == 0 {
+            return;
+        }
+
+        for_each_row_init(
+            out,
+            pitch,
+            || vec![K::zero(); P::CHANNEL_COUNT as usize],
+            |acc, y, row| {
+                self.filter_row::<I, P, _, Q>(image, y as u32, acc, row, &mut |c, a| f(c, a));
+            },
+        );
+    }
+
+    // Computes row y of the output of filter, writing it to row.
+    fn filter_row<I, P, F, Q>(
+        &self,
+        image: &I,
+        y: u32,
+        acc: &mut [K],
+        row: &mut [Q::Subpixel],
+        f: &mut F,
+    ) where
+        I: GenericImageView<Pixel = P>,
+        P: Pixel + 'static,
+        <P as Pixel>::Subpixel: ValueInto<K>,
+        Q: Pixel + 'static,
+        F: FnMut(&mut Q::Subpixel, K),
+    {
+        let zero = K::zero();
+        let (k_width, k_height) = (self.width as i64, self.height as i64);
+        let (width, height) = (image.width() as i64, image.height() as i64);
+        let y = y as i64;
+
+        for (x, out_channels) in row.chunks_mut(Q::CHANNEL_COUNT as usize).enumerate() {
+            let x = x as i64;
+            for k_y in 0..k_height {
+                let y_p = min(height - 1, max(0, y + k_y - k_height / 2)) as u32;
+                for k_x in 0..k_width {
+                    let x_p = min(width - 1, max(0, x + k_x - k_width / 2)) as u32;
+                    accumulate(acc, unsafe { &image.unsafe_get_pixel(x_p, y_p) }, unsafe {
+                        *self.data.get_unchecked((k_y * k_width + k_x) as usize)
+                    });
+                }
+            }
+            for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
+                f(c, *a);
+                *a = zero;
+            }
+        }
     }
 }
 
@@ -304,247 +567,655 @@ fn gaussian_kernel_f32(sigma: f32) -> Vec<f32> {
 ///
 /// Panics if `sigma <= 0.0`.
 // TODO: Integer type kernel, approximations via repeated box filter.
-pub fn gaussian_blur_f32<P>(image: &Image<P>, sigma: f32) -> Image<P>
+pub fn gaussian_blur_f32<I, P>(image: &I, sigma: f32) -> Image<P>
 where
-    P: Pixel + 'static,
-    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32> + MaybeSend + MaybeSync,
 {
-    assert!(sigma > 0.0, "sigma must be > 0.0");
+    try_gaussian_blur_f32(image, sigma).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Blurs an image using a Gaussian of standard deviation sigma, returning an error if
+/// `sigma` is not strictly positive.
+///
+/// See the [`gaussian_blur_f32`](fn.gaussian_blur_f32.html) documentation for more information.
+pub fn try_gaussian_blur_f32<I, P>(image: &I, sigma: f32) -> Result<Image<P>, Error>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32> + MaybeSend + MaybeSync,
+{
+    check_sigma(sigma)?;
     let kernel = gaussian_kernel_f32(sigma);
-    separable_filter_equal(image, &kernel)
+    Ok(separable_filter_equal(image, &kernel))
+}
+
+fn check_sigma(sigma: f32) -> Result<(), Error> {
+    if sigma > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::InvalidParameter {
+            name: "sigma",
+            reason: "must be > 0.0".to_string(),
+        })
+    }
+}
+
+/// Blurs an image using a Gaussian of standard deviation sigma, writing the result
+/// to `out` and using `buffer` to store the result of the horizontal pass.
+///
+/// Both `buffer` and `out` must have the same dimensions as `image`. See the
+/// [`gaussian_blur_f32`](fn.gaussian_blur_f32.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `sigma <= 0.0`, or if `buffer` or `out` do not have the same dimensions as `image`.
+pub fn gaussian_blur_f32_into<I, P>(
+    image: &I,
+    sigma: f32,
+    buffer: &mut Image<P>,
+    out: &mut Image<P>,
+) where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32> + MaybeSend + MaybeSync,
+{
+    try_gaussian_blur_f32_into(image, sigma, buffer, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Blurs an image using a Gaussian of standard deviation sigma, writing the result
+/// to `out` and using `buffer` to store the result of the horizontal pass. Returns an
+/// error if `sigma` is not strictly positive, or if `buffer` or `out` do not have the same
+/// dimensions as `image`.
+///
+/// See the [`gaussian_blur_f32`](fn.gaussian_blur_f32.html) documentation for more information.
+pub fn try_gaussian_blur_f32_into<I, P>(
+    image: &I,
+    sigma: f32,
+    buffer: &mut Image<P>,
+    out: &mut Image<P>,
+) -> Result<(), Error>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32> + MaybeSend + MaybeSync,
+{
+    check_sigma(sigma)?;
+    let kernel = gaussian_kernel_f32(sigma);
+    try_separable_filter_into(image, &kernel, &kernel, buffer, out)
 }
 
 /// Returns 2d correlation of view with the outer product of the 1d
 /// kernels `h_kernel` and `v_kernel`.
-pub fn separable_filter<P, K>(image: &Image<P>, h_kernel: &[K], v_kernel: &[K]) -> Image<P>
+pub fn separable_filter<I, P, K>(image: &I, h_kernel: &[K], v_kernel: &[K]) -> Image<P>
 where
-    P: Pixel + 'static,
-    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K>,
-    K: Num + Copy,
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
 {
     let h = horizontal_filter(image, h_kernel);
     vertical_filter(&h, v_kernel)
 }
 
+/// Computes the 2d correlation of an image with the outer product of the 1d kernels
+/// `h_kernel` and `v_kernel`, writing the result to `out` and using `buffer` to store
+/// the result of the horizontal pass.
+///
+/// Both `buffer` and `out` must have the same dimensions as `image`.
+///
+/// # Panics
+///
+/// Panics if `buffer` or `out` do not have the same dimensions as `image`.
+pub fn separable_filter_into<I, P, K>(
+    image: &I,
+    h_kernel: &[K],
+    v_kernel: &[K],
+    buffer: &mut Image<P>,
+    out: &mut Image<P>,
+) where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    try_separable_filter_into(image, h_kernel, v_kernel, buffer, out)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Computes the 2d correlation of an image with the outer product of the 1d kernels
+/// `h_kernel` and `v_kernel`, writing the result to `out` and using `buffer` to store
+/// the result of the horizontal pass. Returns an error if `buffer` or `out` do not have the
+/// same dimensions as `image`.
+pub fn try_separable_filter_into<I, P, K>(
+    image: &I,
+    h_kernel: &[K],
+    v_kernel: &[K],
+    buffer: &mut Image<P>,
+    out: &mut Image<P>,
+) -> Result<(), Error>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    try_horizontal_filter_into(image, h_kernel, buffer)?;
+    try_vertical_filter_into(buffer, v_kernel, out)
+}
+
 /// Returns 2d correlation of an image with the outer product of the 1d
 /// kernel filter with itself.
-pub fn separable_filter_equal<P, K>(image: &Image<P>, kernel: &[K]) -> Image<P>
+pub fn separable_filter_equal<I, P, K>(image: &I, kernel: &[K]) -> Image<P>
 where
-    P: Pixel + 'static,
-    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K>,
-    K: Num + Copy,
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
 {
     separable_filter(image, kernel, kernel)
 }
 
 /// Returns 2d correlation of an image with a 3x3 row-major kernel. Intermediate calculations are
 /// performed at type K, and the results clamped to subpixel type S. Pads by continuity.
-pub fn filter3x3<P, K, S>(image: &Image<P>, kernel: &[K]) -> Image<ChannelMap<P, S>>
+pub fn filter3x3<I, P, K, S>(image: &I, kernel: &[K]) -> Image<ChannelMap<P, S>>
 where
-    P::Subpixel: ValueInto<K>,
-    S: Clamp<K> + Primitive + 'static,
-    P: WithChannel<S> + 'static,
-    K: Num + Copy,
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P::Subpixel: ValueInto<K> + MaybeSync,
+    S: Clamp<K> + Primitive + MaybeSend + 'static,
+    P: WithChannel<S> + MaybeSync + 'static,
+    K: Num + Copy + MaybeSync,
+{
+    let mut out = Image::new(image.width(), image.height());
+    filter3x3_into(image, kernel, &mut out);
+    out
+}
+
+/// Computes the 2d correlation of an image with a 3x3 row-major kernel, writing the
+/// result to `out`, which must have the same dimensions as `image`.
+///
+/// See the [`filter3x3`](fn.filter3x3.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn filter3x3_into<I, P, K, S>(image: &I, kernel: &[K], out: &mut Image<ChannelMap<P, S>>)
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P::Subpixel: ValueInto<K> + MaybeSync,
+    S: Clamp<K> + Primitive + MaybeSend + 'static,
+    P: WithChannel<S> + MaybeSync + 'static,
+    K: Num + Copy + MaybeSync,
+{
+    try_filter3x3_into(image, kernel, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Computes the 2d correlation of an image with a 3x3 row-major kernel, writing the
+/// result to `out`. Returns an error if `out` does not have the same dimensions as `image`.
+///
+/// See the [`filter3x3`](fn.filter3x3.html) documentation for more information.
+pub fn try_filter3x3_into<I, P, K, S>(
+    image: &I,
+    kernel: &[K],
+    out: &mut Image<ChannelMap<P, S>>,
+) -> Result<(), Error>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P::Subpixel: ValueInto<K> + MaybeSync,
+    S: Clamp<K> + Primitive + MaybeSend + 'static,
+    P: WithChannel<S> + MaybeSync + 'static,
+    K: Num + Copy + MaybeSync,
 {
+    check_dimensions(out.dimensions(), image.dimensions())?;
     let kernel = Kernel::new(kernel, 3, 3);
-    kernel.filter(image, |channel, acc| *channel = S::clamp(acc))
+    kernel.filter_parallel_into(image, |channel, acc| *channel = S::clamp(acc), out);
+    Ok(())
 }
 
 /// Returns horizontal correlations between an image and a 1d kernel.
 /// Pads by continuity. Intermediate calculations are performed at
 /// type K.
-pub fn horizontal_filter<P, K>(image: &Image<P>, kernel: &[K]) -> Image<P>
+pub fn horizontal_filter<I, P, K>(image: &I, kernel: &[K]) -> Image<P>
 where
-    P: Pixel + 'static,
-    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K>,
-    K: Num + Copy,
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    let mut out = Image::new(image.width(), image.height());
+    horizontal_filter_into(image, kernel, &mut out);
+    out
+}
+
+/// Computes horizontal correlations between an image and a 1d kernel, writing the
+/// result to `out`, which must have the same dimensions as `image`.
+///
+/// See the [`horizontal_filter`](fn.horizontal_filter.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn horizontal_filter_into<I, P, K>(image: &I, kernel: &[K], out: &mut Image<P>)
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    try_horizontal_filter_into(image, kernel, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Computes horizontal correlations between an image and a 1d kernel, writing the
+/// result to `out`. Returns an error if `out` does not have the same dimensions as `image`.
+///
+/// See the [`horizontal_filter`](fn.horizontal_filter.html) documentation for more information.
+pub fn try_horizontal_filter_into<I, P, K>(
+    image: &I,
+    kernel: &[K],
+    out: &mut Image<P>,
+) -> Result<(), Error>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    if let Some(sums) = gpu_filter(image, kernel, kernel.len() as u32, 1) {
+        for (c, sum) in out.iter_mut().zip(sums) {
+            *c = <P as Pixel>::Subpixel::clamp(sum);
+        }
+        return Ok(());
+    }
+    if let Some((gray, kernel, gray_out)) = as_gray_and_f32(image, kernel, out) {
+        if simd::horizontal_filter_u8_f32(gray, kernel, gray_out) {
+            return Ok(());
+        }
+    }
+    horizontal_filter_scalar(image, kernel, out);
+    Ok(())
+}
+
+fn horizontal_filter_scalar<I, P, K>(image: &I, kernel: &[K], out: &mut Image<P>)
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync,
 {
     // Don't replace this with a call to Kernel::filter without
     // checking the benchmark results. At the time of writing this
     // specialised implementation is faster.
-    let (width, height) = image.dimensions();
-    let mut out = Image::<P>::new(width, height);
+    let width = image.width();
+    let pitch = P::CHANNEL_COUNT as usize * width as usize;
+    if pitch == 0 {
+        return;
+    }
+
     let zero = K::zero();
-    let mut acc = vec![zero; P::CHANNEL_COUNT as usize];
-    let k_width = kernel.len() as i32;
+    let init = || vec![zero; P::CHANNEL_COUNT as usize];
 
-    // Typically the image side will be much larger than the kernel length.
-    // In that case we can remove a lot of bounds checks for most pixels.
-    if k_width >= width as i32 {
-        for y in 0..height {
-            for x in 0..width {
+    for_each_row_init(out, pitch, init, |acc, y, row| {
+        let k_width = kernel.len() as i32;
+        let half_k = k_width / 2;
+
+        for (x, out_channels) in row.chunks_mut(P::CHANNEL_COUNT as usize).enumerate() {
+            let x = x as i32;
+            // Typically the image side will be much larger than the kernel length.
+            // In that case we can remove a lot of bounds checks for most pixels.
+            if x >= half_k && x < width as i32 - half_k {
                 for (i, k) in kernel.iter().enumerate() {
-                    let x_unchecked = (x as i32) + i as i32 - k_width / 2;
-                    let x_p = max(0, min(x_unchecked, width as i32 - 1)) as u32;
-                    let p = unsafe { image.unsafe_get_pixel(x_p, y) };
-                    accumulate(&mut acc, &p, *k);
+                    let x_p = (x + i as i32 - half_k) as u32;
+                    let p = unsafe { image.unsafe_get_pixel(x_p, y as u32) };
+                    accumulate(acc, &p, *k);
                 }
-
-                let out_channels = out.get_pixel_mut(x, y).channels_mut();
-                for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                    *c = <P as Pixel>::Subpixel::clamp(*a);
-                    *a = zero;
+            } else {
+                for (i, k) in kernel.iter().enumerate() {
+                    let x_unchecked = x + i as i32 - half_k;
+                    let x_p = max(0, min(x_unchecked, width as i32 - 1)) as u32;
+                    let p = unsafe { image.unsafe_get_pixel(x_p, y as u32) };
+                    accumulate(acc, &p, *k);
                 }
             }
-        }
-
-        return out;
-    }
-
-    let half_k = k_width / 2;
 
-    for y in 0..height {
-        // Left margin - need to check lower bound only
-        for x in 0..half_k {
-            for (i, k) in kernel.iter().enumerate() {
-                let x_unchecked = (x as i32) + i as i32 - k_width / 2;
-                let x_p = max(0, x_unchecked) as u32;
-                let p = unsafe { image.unsafe_get_pixel(x_p, y) };
-                accumulate(&mut acc, &p, *k);
-            }
-
-            let out_channels = out.get_pixel_mut(x as u32, y).channels_mut();
             for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
                 *c = <P as Pixel>::Subpixel::clamp(*a);
                 *a = zero;
             }
         }
+    });
+}
 
-        // Neither margin - don't need bounds check on either side
-        for x in half_k..(width as i32 - half_k) {
-            for (i, k) in kernel.iter().enumerate() {
-                let x_unchecked = (x as i32) + i as i32 - k_width / 2;
-                let x_p = x_unchecked as u32;
-                let p = unsafe { image.unsafe_get_pixel(x_p, y) };
-                accumulate(&mut acc, &p, *k);
-            }
-
-            let out_channels = out.get_pixel_mut(x as u32, y).channels_mut();
-            for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                *c = <P as Pixel>::Subpixel::clamp(*a);
-                *a = zero;
-            }
-        }
+/// Returns horizontal correlations between an image and a 1d kernel.
+/// Pads by continuity.
+pub fn vertical_filter<I, P, K>(image: &I, kernel: &[K]) -> Image<P>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    let mut out = Image::new(image.width(), image.height());
+    vertical_filter_into(image, kernel, &mut out);
+    out
+}
 
-        // Right margin - need to check upper bound only
-        for x in (width as i32 - half_k)..(width as i32) {
-            for (i, k) in kernel.iter().enumerate() {
-                let x_unchecked = (x as i32) + i as i32 - k_width / 2;
-                let x_p = min(x_unchecked, width as i32 - 1) as u32;
-                let p = unsafe { image.unsafe_get_pixel(x_p, y) };
-                accumulate(&mut acc, &p, *k);
-            }
+/// Computes vertical correlations between an image and a 1d kernel, writing the
+/// result to `out`, which must have the same dimensions as `image`.
+///
+/// See the [`vertical_filter`](fn.vertical_filter.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn vertical_filter_into<I, P, K>(image: &I, kernel: &[K], out: &mut Image<P>)
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    try_vertical_filter_into(image, kernel, out).unwrap_or_else(|e| panic!("{}", e))
+}
 
-            let out_channels = out.get_pixel_mut(x as u32, y).channels_mut();
-            for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                *c = <P as Pixel>::Subpixel::clamp(*a);
-                *a = zero;
-            }
+/// Computes vertical correlations between an image and a 1d kernel, writing the
+/// result to `out`. Returns an error if `out` does not have the same dimensions as `image`.
+///
+/// See the [`vertical_filter`](fn.vertical_filter.html) documentation for more information.
+pub fn try_vertical_filter_into<I, P, K>(
+    image: &I,
+    kernel: &[K],
+    out: &mut Image<P>,
+) -> Result<(), Error>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync + 'static,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    if let Some(sums) = gpu_filter(image, kernel, 1, kernel.len() as u32) {
+        for (c, sum) in out.iter_mut().zip(sums) {
+            *c = <P as Pixel>::Subpixel::clamp(sum);
         }
+        return Ok(());
     }
-
-    out
+    if let Some((gray, kernel, gray_out)) = as_gray_and_f32(image, kernel, out) {
+        if simd::vertical_filter_u8_f32(gray, kernel, gray_out) {
+            return Ok(());
+        }
+    }
+    vertical_filter_scalar(image, kernel, out);
+    Ok(())
 }
 
-/// Returns horizontal correlations between an image and a 1d kernel.
-/// Pads by continuity.
-pub fn vertical_filter<P, K>(image: &Image<P>, kernel: &[K]) -> Image<P>
+fn vertical_filter_scalar<I, P, K>(image: &I, kernel: &[K], out: &mut Image<P>)
 where
-    P: Pixel + 'static,
-    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K>,
-    K: Num + Copy,
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel + MaybeSync + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K> + Clamp<K> + MaybeSend + MaybeSync,
+    K: Num + Copy + MaybeSync,
 {
     // Don't replace this with a call to Kernel::filter without
     // checking the benchmark results. At the time of writing this
     // specialised implementation is faster.
     let (width, height) = image.dimensions();
-    let mut out = Image::<P>::new(width, height);
-    let zero = K::zero();
-    let mut acc = vec![zero; P::CHANNEL_COUNT as usize];
-    let k_height = kernel.len() as i32;
+    let pitch = P::CHANNEL_COUNT as usize * width as usize;
+    if pitch == 0 {
+        return;
+    }
 
-    // Typically the image side will be much larger than the kernel length.
-    // In that case we can remove a lot of bounds checks for most pixels.
-    if k_height >= height as i32 {
-        for y in 0..height {
-            for x in 0..width {
+    let zero = K::zero();
+    let init = || vec![zero; P::CHANNEL_COUNT as usize];
+
+    for_each_row_init(out, pitch, init, |acc, y, row| {
+        let k_height = kernel.len() as i32;
+        let half_k = k_height / 2;
+        let y = y as i32;
+        // Typically the image side will be much larger than the kernel length.
+        // In that case we can remove a lot of bounds checks for most rows.
+        let unchecked = y >= half_k && y < height as i32 - half_k;
+
+        for (x, out_channels) in row.chunks_mut(P::CHANNEL_COUNT as usize).enumerate() {
+            let x = x as u32;
+            if unchecked {
+                for (i, k) in kernel.iter().enumerate() {
+                    let y_p = (y + i as i32 - half_k) as u32;
+                    let p = unsafe { image.unsafe_get_pixel(x, y_p) };
+                    accumulate(acc, &p, *k);
+                }
+            } else {
                 for (i, k) in kernel.iter().enumerate() {
-                    let y_unchecked = (y as i32) + i as i32 - k_height / 2;
+                    let y_unchecked = y + i as i32 - half_k;
                     let y_p = max(0, min(y_unchecked, height as i32 - 1)) as u32;
                     let p = unsafe { image.unsafe_get_pixel(x, y_p) };
-                    accumulate(&mut acc, &p, *k);
+                    accumulate(acc, &p, *k);
                 }
+            }
 
-                let out_channels = out.get_pixel_mut(x, y).channels_mut();
-                for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                    *c = <P as Pixel>::Subpixel::clamp(*a);
-                    *a = zero;
-                }
+            for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
+                *c = <P as Pixel>::Subpixel::clamp(*a);
+                *a = zero;
             }
         }
+    });
+}
+
+// Returns the input image, kernel and output image at their concrete types if they are
+// grayscale u8 images or views of them and an f32 kernel, which are the types the SIMD
+// implementations support.
+fn as_gray_and_f32<'a, I, P, K>(
+    image: &'a I,
+    kernel: &'a [K],
+    out: &'a mut Image<P>,
+) -> Option<(GrayRegion<'a>, &'a [f32], &'a mut GrayImage)>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel + 'static,
+    K: 'static,
+{
+    let gray = GrayRegion::of(image)?;
+    let gray_out = (out as &mut dyn Any).downcast_mut::<GrayImage>()?;
+    let kernel = same_type_slice::<K, f32>(kernel)?;
+    Some((gray, kernel, gray_out))
+}
+
+// A rectangular region of a GrayImage, which lets the specialised u8 implementations read
+// rows of views of images without copying them.
+#[derive(Clone, Copy)]
+struct GrayRegion<'a> {
+    image: &'a GrayImage,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+}
 
-        return out;
+impl<'a> GrayRegion<'a> {
+    // Returns the region of the underlying image covered by image, if image is a GrayImage
+    // or a SubImage of one. Other views are not supported, as GenericImageView does not
+    // guarantee that their inner image and bounds describe the pixels they return.
+    fn of<I: GenericImageView>(image: &'a I) -> Option<GrayRegion<'a>> {
+        if erased_type_id::<I>() == erased_type_id::<GrayImage>() {
+            // JUSTIFICATION
+            //  Benefit
+            //      Lets the generic filters use the specialised u8 implementations.
+            //  Correctness
+            //      I is GrayImage, as checked above.
+            let image = unsafe { &*(image as *const I as *const GrayImage) };
+            return Some(GrayRegion::from(image));
+        }
+        if erased_type_id::<I>() != erased_type_id::<SubImage<&GrayImage>>() {
+            return None;
+        }
+        // JUSTIFICATION
+        //  Benefit
+        //      Lets the generic filters use the specialised u8 implementations for views.
+        //  Correctness
+        //      I is SubImage<&'b GrayImage> for some lifetime 'b, as checked above, and 'b
+        //      outlives 'a as image is borrowed for 'a.
+        let view = unsafe { &*(image as *const I as *const SubImage<&'a GrayImage>) };
+        let inner = view.inner();
+        let (left, top, width, height) = view.bounds();
+        if left as u64 + width as u64 > inner.width() as u64
+            || top as u64 + height as u64 > inner.height() as u64
+        {
+            return None;
+        }
+        Some(GrayRegion {
+            image: inner,
+            left: left as usize,
+            top: top as usize,
+            width: width as usize,
+            height: height as usize,
+        })
     }
 
-    let half_k = k_height / 2;
+    fn width(&self) -> usize {
+        self.width
+    }
 
-    // Top margin - need to check lower bound only
-    for y in 0..half_k {
-        for x in 0..width {
-            for (i, k) in kernel.iter().enumerate() {
-                let y_unchecked = (y as i32) + i as i32 - k_height / 2;
-                let y_p = max(0, y_unchecked) as u32;
-                let p = unsafe { image.unsafe_get_pixel(x, y_p) };
-                accumulate(&mut acc, &p, *k);
-            }
+    fn height(&self) -> usize {
+        self.height
+    }
 
-            let out_channels = out.get_pixel_mut(x, y as u32).channels_mut();
-            for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                *c = <P as Pixel>::Subpixel::clamp(*a);
-                *a = zero;
-            }
+    // Returns row y of the region.
+    fn row(&self, y: usize) -> &'a [u8] {
+        let start = (self.top + y) * self.image.width() as usize + self.left;
+        &self.image.as_raw()[start..start + self.width]
+    }
+}
+
+impl<'a> From<&'a GrayImage> for GrayRegion<'a> {
+    fn from(image: &'a GrayImage) -> GrayRegion<'a> {
+        GrayRegion {
+            image,
+            left: 0,
+            top: 0,
+            width: image.width() as usize,
+            height: image.height() as usize,
         }
     }
+}
 
-    // Neither margin - don't need bounds check on either side
-    for y in half_k..(height as i32 - half_k) {
-        for x in 0..width {
-            for (i, k) in kernel.iter().enumerate() {
-                let y_unchecked = (y as i32) + i as i32 - k_height / 2;
-                let y_p = y_unchecked as u32;
-                let p = unsafe { image.unsafe_get_pixel(x, y_p) };
-                accumulate(&mut acc, &p, *k);
-            }
+// Returns the correlation of an image with a row-major kernel, padded by continuity, computed
+// on the GPU. Returns None if the gpu feature is not enabled, no GPU is available, K is not
+// f32 or the kernel is too small for the GPU to be worth using.
+fn gpu_filter<I, P, K>(
+    image: &I,
+    kernel: &[K],
+    kernel_width: u32,
+    kernel_height: u32,
+) -> Option<Vec<K>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel + 'static,
+    <P as Pixel>::Subpixel: ValueInto<K>,
+    K: Copy + 'static,
+{
+    let kernel = same_type_slice::<K, f32>(kernel)?;
+    if kernel.len() < gpu::MIN_KERNEL_LEN || !gpu::is_available() {
+        return None;
+    }
+    let mut pixels: Vec<K> = Vec::with_capacity(
+        image.width() as usize * image.height() as usize * P::CHANNEL_COUNT as usize,
+    );
+    for (_, _, p) in image.pixels() {
+        pixels.extend(p.channels().iter().map(|c| cast::<_, K>(*c)));
+    }
+    let sums = gpu::filter_f32(
+        same_type_slice::<K, f32>(&pixels)?,
+        image.width(),
+        image.height(),
+        P::CHANNEL_COUNT as u32,
+        kernel,
+        kernel_width,
+        kernel_height,
+    )?;
+    Some(same_type_slice::<f32, K>(&sums)?.to_vec())
+}
 
-            let out_channels = out.get_pixel_mut(x, y as u32).channels_mut();
-            for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                *c = <P as Pixel>::Subpixel::clamp(*a);
-                *a = zero;
-            }
+// Returns the TypeId of T with all lifetimes erased, so that types which borrow, such as
+// views of images, can be compared without requiring them to be 'static. This is the
+// technique used by the typeid crate.
+fn erased_type_id<T: ?Sized>() -> TypeId {
+    trait NonStaticAny {
+        fn get_type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn get_type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<T>()
         }
     }
 
-    // Right margin - need to check upper bound only
-    for y in (height as i32 - half_k)..(height as i32) {
-        for x in 0..width {
-            for (i, k) in kernel.iter().enumerate() {
-                let y_unchecked = (y as i32) + i as i32 - k_height / 2;
-                let y_p = min(y_unchecked, height as i32 - 1) as u32;
-                let p = unsafe { image.unsafe_get_pixel(x, y_p) };
-                accumulate(&mut acc, &p, *k);
-            }
+    let phantom = PhantomData::<T>;
+    // JUSTIFICATION
+    //  Benefit
+    //      Lets GrayRegion::of recognise views of GrayImages without a 'static bound.
+    //  Correctness
+    //      Lifetimes do not affect the layout or behaviour of PhantomData<T>. The returned
+    //      TypeId is only compared, so types differing only in their lifetimes are never
+    //      used at the wrong type.
+    let phantom = unsafe {
+        std::mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom)
+    };
+    phantom.get_type_id()
+}
 
-            let out_channels = out.get_pixel_mut(x, y as u32).channels_mut();
-            for (a, c) in acc.iter_mut().zip(out_channels.iter_mut()) {
-                *c = <P as Pixel>::Subpixel::clamp(*a);
-                *a = zero;
-            }
-        }
+// Returns values as a slice of U if T and U are the same type.
+fn same_type_slice<T: 'static, U: 'static>(values: &[T]) -> Option<&[U]> {
+    if TypeId::of::<T>() != TypeId::of::<U>() {
+        return None;
     }
+    // JUSTIFICATION
+    //  Benefit
+    //      Lets the generic filters use the SIMD and GPU implementations for f32 kernels.
+    //  Correctness
+    //      T and U are the same type, as checked above.
+    Some(unsafe { std::slice::from_raw_parts(values.as_ptr() as *const U, values.len()) })
+}
 
+/// Returns 2d correlation of an image with a 3x3 row-major integer kernel, with results
+/// clamped to `i16`. Equivalent to `filter3x3::<_, _, i32, i16>`, but uses SIMD for grayscale
+/// images when the `simd` feature is enabled.
+pub(crate) fn filter3x3_i16<P>(image: &Image<P>, kernel: &[i32; 9]) -> Image<ChannelMap<P, i16>>
+where
+    P::Subpixel: ValueInto<i32> + MaybeSync,
+    P: WithChannel<i16> + MaybeSync + 'static,
+{
+    let mut out = Image::<ChannelMap<P, i16>>::new(image.width(), image.height());
+    filter3x3_i16_into(image, kernel, &mut out);
     out
 }
 
+// Equivalent to filter3x3_i16, but writes to `out`, which must have the same dimensions
+// as `image`.
+pub(crate) fn filter3x3_i16_into<P>(
+    image: &Image<P>,
+    kernel: &[i32; 9],
+    out: &mut Image<ChannelMap<P, i16>>,
+) where
+    P::Subpixel: ValueInto<i32> + MaybeSync,
+    P: WithChannel<i16> + MaybeSync + 'static,
+{
+    let gray = (image as &dyn Any).downcast_ref::<GrayImage>();
+    let gray_out = (out as &mut dyn Any).downcast_mut::<Image<Luma<i16>>>();
+    if let (Some(gray), Some(gray_out)) = (gray, gray_out) {
+        if simd::filter3x3_u8_i16(gray.into(), kernel, gray_out) {
+            return;
+        }
+    }
+    filter3x3_into(image, kernel, out);
+}
+
 fn accumulate<P, K>(acc: &mut [K], pixel: &P, weight: K)
 where
     P: Pixel,
@@ -560,6 +1231,8 @@ where
 mod tests {
     use super::*;
     use crate::definitions::{Clamp, Image};
+    use crate::map::map_subpixels;
+    use crate::rect::{view, Rect};
     use crate::utils::{gray_bench_image, rgb_bench_image};
     use image::imageops::blur;
     use image::{GenericImage, GrayImage, ImageBuffer, Luma, Rgb};
@@ -601,6 +1274,46 @@ mod tests {
         assert_pixels_eq!(box_filter(&image, 1, 1), expected);
     }
 
+    #[test]
+    fn test_box_filter_u16() {
+        let image = gray_image!(type: u16,
+            1000, 2000, 3000;
+            4000, 5000, 6000;
+            7000, 8000, 9000);
+
+        let expected = gray_image!(type: u16,
+            2333, 3000, 3666;
+            4333, 5000, 5666;
+            6333, 7000, 7666);
+
+        assert_pixels_eq!(box_filter(&image, 1, 1), expected);
+    }
+
+    #[test]
+    fn test_box_filter_f32() {
+        let image = gray_image!(type: f32,
+            1.0, 2.0, 3.0;
+            4.0, 5.0, 6.0;
+            7.0, 8.0, 9.0);
+
+        // Unlike the integer case, no rounding takes place between the two passes.
+        let third = 1.0 / 3.0;
+        let expected = gray_image!(type: f32,
+            2.0 + third, 3.0, 4.0 - third;
+            4.0 + third, 5.0, 6.0 - third;
+            6.0 + third, 7.0, 8.0 - third);
+
+        assert_pixels_eq_within!(box_filter(&image, 1, 1), expected, 1e-5);
+    }
+
+    #[test]
+    fn test_box_filter_u8_matches_generic_implementation() {
+        let image = gray_bench_image(23, 17);
+        let image_u16: Image<Luma<u16>> = map_subpixels(&image, |p| p as u16);
+        let expected: GrayImage = map_subpixels(&box_filter(&image_u16, 4, 2), |p| p as u8);
+        assert_pixels_eq!(box_filter(&image, 4, 2), expected);
+    }
+
     #[bench]
     fn bench_box_filter(b: &mut Bencher) {
         let image = gray_bench_image(500, 500);
@@ -929,7 +1642,7 @@ mod tests {
 
         b.iter(|| {
             let filtered: ImageBuffer<Luma<i16>, Vec<i16>> =
-                filter3x3::<_, _, i16>(&image, &kernel);
+                filter3x3::<_, _, _, i16>(&image, &kernel);
             black_box(filtered);
         });
     }
@@ -1000,6 +1713,184 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_gaussian_blur_f32_u16_and_f32_images_agree() {
+        let image = gray_bench_image(30, 20);
+        let image_u16: Image<Luma<u16>> = map_subpixels(&image, |p| p as u16 * 256);
+        let image_f32: Image<Luma<f32>> = map_subpixels(&image, |p| p as f32 * 256.0);
+
+        let blurred_u16 = gaussian_blur_f32(&image_u16, 2.5);
+        let blurred_f32 = gaussian_blur_f32(&image_f32, 2.5);
+
+        // The u16 results are rounded down after each of the two passes.
+        for (p, q) in blurred_u16.iter().zip(blurred_f32.iter()) {
+            assert!((*p as f32 - q).abs() < 2.0, "{} vs {}", p, q);
+        }
+    }
+
+    #[test]
+    fn test_filters_accept_views() {
+        let image = gray_bench_image(30, 25);
+        let roi = view(&image, Rect::at(4, 3).of_size(17, 12)).unwrap();
+        let crop = roi.to_image();
+
+        assert_pixels_eq!(gaussian_blur_f32(&roi, 1.5), gaussian_blur_f32(&crop, 1.5));
+        assert_pixels_eq!(box_filter(&roi, 2, 1), box_filter(&crop, 2, 1));
+        assert_pixels_eq!(median_filter(&roi, 1, 2), median_filter(&crop, 1, 2));
+        assert_pixels_eq!(sharpen3x3(&roi), sharpen3x3(&crop));
+        assert_pixels_eq!(
+            bilateral_filter(&roi, 5, 10.0, 3.0),
+            bilateral_filter(&crop, 5, 10.0, 3.0)
+        );
+
+        let kernel = [1i32, 2, 1, 0, 0, 0, -1, -2, -1];
+        let from_view: Image<Luma<i16>> = filter3x3(&roi, &kernel);
+        let from_crop: Image<Luma<i16>> = filter3x3(&crop, &kernel);
+        assert_pixels_eq!(from_view, from_crop);
+
+        let image = rgb_bench_image(30, 25);
+        let roi = view(&image, Rect::at(7, 2).of_size(11, 20)).unwrap();
+        let crop = roi.to_image();
+        let kernel = [0.1f32, 0.2, 0.4, 0.2, 0.1];
+        assert_pixels_eq!(
+            separable_filter(&roi, &kernel, &kernel),
+            separable_filter(&crop, &kernel, &kernel)
+        );
+    }
+
+    // A view which flips an image horizontally, and so whose pixels are not the rows of its
+    // inner image at the offsets given by its bounds.
+    struct FlippedView<'a>(&'a GrayImage);
+
+    impl<'a> GenericImageView for FlippedView<'a> {
+        type Pixel = Luma<u8>;
+        type InnerImageView = GrayImage;
+
+        fn dimensions(&self) -> (u32, u32) {
+            self.0.dimensions()
+        }
+
+        fn bounds(&self) -> (u32, u32, u32, u32) {
+            self.0.bounds()
+        }
+
+        fn get_pixel(&self, x: u32, y: u32) -> Luma<u8> {
+            *self.0.get_pixel(self.0.width() - 1 - x, y)
+        }
+
+        fn inner(&self) -> &GrayImage {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_filters_accept_custom_views() {
+        let image = gray_bench_image(30, 25);
+        let flipped = FlippedView(&image);
+        let copy = GrayImage::from_fn(30, 25, |x, y| flipped.get_pixel(x, y));
+        let kernel = [0.1f32, 0.2, 0.4, 0.2, 0.1];
+
+        assert!(GrayRegion::of(&image).is_some());
+        assert!(GrayRegion::of(&view(&image, Rect::at(1, 2).of_size(5, 5)).unwrap()).is_some());
+        assert!(GrayRegion::of(&flipped).is_none());
+
+        assert_pixels_eq!(box_filter(&flipped, 2, 1), box_filter(&copy, 2, 1));
+        assert_pixels_eq!(
+            gaussian_blur_f32(&flipped, 1.5),
+            gaussian_blur_f32(&copy, 1.5)
+        );
+        assert_pixels_eq!(
+            separable_filter_equal(&flipped, &kernel),
+            separable_filter_equal(&copy, &kernel)
+        );
+        assert_pixels_eq!(sharpen3x3(&flipped), sharpen3x3(&copy));
+    }
+
+    #[test]
+    fn test_large_kernels_match_scalar_implementations() {
+        // Large f32 kernels are applied on the GPU if the gpu feature is enabled, which
+        // may round differently to the scalar implementations.
+        let image = rgb_bench_image(40, 30);
+        let kernel = gaussian_kernel_f32(15.0);
+        assert!(kernel.len() >= gpu::MIN_KERNEL_LEN);
+
+        let mut expected = Image::new(40, 30);
+        horizontal_filter_scalar(&image, &kernel, &mut expected);
+        assert_pixels_eq_within!(horizontal_filter(&image, &kernel), expected, 1);
+        vertical_filter_scalar(&image, &kernel, &mut expected);
+        assert_pixels_eq_within!(vertical_filter(&image, &kernel), expected, 1);
+
+        let weights: Vec<f32> = (0..63).map(|i| (i % 5) as f32 - 1.5).collect();
+        let kernel = Kernel::new(&weights, 9, 7);
+        let mut expected = Image::<Rgb<f32>>::new(40, 30);
+        kernel.filter_parallel_into(&image, |c, a| *c = a, &mut expected);
+        let actual: Image<Rgb<f32>> = kernel.filter(&image, |c, a| *c = a);
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() <= 1e-3 * e.abs().max(1.0), "{} != {}", a, e);
+        }
+    }
+
+    #[test]
+    fn test_separable_filter_f32() {
+        let image = gray_image!(type: f32,
+            0.0, 0.0, 0.0;
+            0.0, 3.0, 0.0;
+            0.0, 0.0, 0.0);
+
+        let expected = gray_image!(type: f32,
+            0.1875, 0.375, 0.1875;
+            0.375, 0.75, 0.375;
+            0.1875, 0.375, 0.1875);
+
+        let filtered = separable_filter_equal(&image, &[0.25f32, 0.5, 0.25]);
+        assert_pixels_eq!(filtered, expected);
+    }
+
+    #[test]
+    fn test_filter3x3_u16() {
+        let image = gray_image!(type: u16,
+            40000, 40000, 40000;
+            40000, 65535, 40000;
+            40000, 40000, 40000);
+
+        let kernel = [0i32, -1, 0, -1, 5, -1, 0, -1, 0];
+
+        // Sharpening saturates at the bounds of u16.
+        let expected = gray_image!(type: u16,
+            40000, 14465, 40000;
+            14465, 65535, 14465;
+            40000, 14465, 40000);
+
+        let filtered: Image<Luma<u16>> = filter3x3(&image, &kernel);
+        assert_pixels_eq!(filtered, expected);
+    }
+
+    #[test]
+    fn test_try_gaussian_blur_f32() {
+        let image = gray_bench_image(10, 8);
+        assert_eq!(
+            try_gaussian_blur_f32(&image, 1.5),
+            Ok(gaussian_blur_f32(&image, 1.5))
+        );
+        for &sigma in &[0.0, -1.0, f32::NAN] {
+            match try_gaussian_blur_f32(&image, sigma) {
+                Err(Error::InvalidParameter { name: "sigma", .. }) => {}
+                other => panic!("unexpected result for sigma {}: {:?}", sigma, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernel_try_new() {
+        let data = [1i32, 2, 3, 4, 5, 6];
+        assert!(Kernel::try_new(&data, 3, 2).is_ok());
+        assert!(Kernel::try_new(&data, 0, 2).is_err());
+        assert_eq!(
+            Kernel::try_new(&data, 2, 2).err().map(|e| e.to_string()),
+            Some("invalid kernel data: expected length 4, found 6".to_string())
+        );
+    }
+
     #[test]
     #[should_panic]
     fn test_gaussian_blur_f32_rejects_zero_sigma() {
@@ -1021,4 +1912,101 @@ mod tests {
         );
         let _ = gaussian_blur_f32(&image, -0.5);
     }
+
+    #[test]
+    fn test_into_variants_match_allocating_variants() {
+        let image = gray_bench_image(23, 17);
+        let kernel = [0.1f32, 0.3, 0.4, 0.2];
+        let kernel3x3 = [1i32, 2, 1, 0, 0, 0, -1, -2, -1];
+
+        // Outputs are deliberately reused and non-zero, to check that they are overwritten
+        let mut out = GrayImage::from_pixel(23, 17, Luma([7]));
+        let mut buffer = GrayImage::from_pixel(23, 17, Luma([9]));
+
+        horizontal_filter_into(&image, &kernel, &mut out);
+        assert_pixels_eq!(out, horizontal_filter(&image, &kernel));
+
+        vertical_filter_into(&image, &kernel, &mut out);
+        assert_pixels_eq!(out, vertical_filter(&image, &kernel));
+
+        separable_filter_into(&image, &kernel, &kernel[..3], &mut buffer, &mut out);
+        assert_pixels_eq!(out, separable_filter(&image, &kernel, &kernel[..3]));
+
+        gaussian_blur_f32_into(&image, 1.5, &mut buffer, &mut out);
+        assert_pixels_eq!(out, gaussian_blur_f32(&image, 1.5));
+
+        let mut out = Image::<Luma<i16>>::from_pixel(23, 17, Luma([-3]));
+        filter3x3_into(&image, &kernel3x3, &mut out);
+        let expected: Image<Luma<i16>> = filter3x3(&image, &kernel3x3);
+        assert_pixels_eq!(out, expected);
+
+        let mut out = Image::<Luma<i32>>::from_pixel(23, 17, Luma([5]));
+        let kernel = Kernel::new(&kernel3x3, 3, 3);
+        kernel.filter_into(&image, |c, a| *c = a, &mut out);
+        let expected: Image<Luma<i32>> = kernel.filter(&image, |c, a| *c = a);
+        assert_pixels_eq!(out, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_horizontal_filter_into_rejects_wrong_output_size() {
+        let image = GrayImage::new(4, 3);
+        let mut out = GrayImage::new(3, 4);
+        horizontal_filter_into(&image, &[1.0f32], &mut out);
+    }
+
+    #[test]
+    fn test_try_into_variants_check_dimensions() {
+        let image = gray_bench_image(5, 4);
+        let kernel = [0.25f32, 0.5, 0.25];
+        let kernel3x3 = [1i32; 9];
+        let expected = Err(Error::DimensionMismatch {
+            expected: (5, 4),
+            actual: (4, 5),
+        });
+
+        let mut good = GrayImage::new(5, 4);
+        let mut bad = GrayImage::new(4, 5);
+        assert_eq!(
+            try_horizontal_filter_into(&image, &kernel, &mut good),
+            Ok(())
+        );
+        assert_eq!(
+            try_horizontal_filter_into(&image, &kernel, &mut bad),
+            expected
+        );
+        assert_eq!(
+            try_vertical_filter_into(&image, &kernel, &mut bad),
+            expected
+        );
+        assert_eq!(try_box_filter_into(&image, 1, 2, &mut good), Ok(()));
+        assert_pixels_eq!(good, box_filter(&image, 1, 2));
+        assert_eq!(try_box_filter_into(&image, 1, 2, &mut bad), expected);
+        assert_eq!(try_median_filter_into(&image, 1, 2, &mut good), Ok(()));
+        assert_pixels_eq!(good, median_filter(&image, 1, 2));
+        assert_eq!(try_median_filter_into(&image, 1, 2, &mut bad), expected);
+
+        let mut buffer = GrayImage::new(5, 4);
+        assert_eq!(
+            try_separable_filter_into(&image, &kernel, &kernel, &mut buffer, &mut bad),
+            expected
+        );
+        assert_eq!(
+            try_separable_filter_into(&image, &kernel, &kernel, &mut bad, &mut good),
+            expected
+        );
+        assert_eq!(
+            try_gaussian_blur_f32_into(&image, 1.0, &mut buffer, &mut bad),
+            expected
+        );
+        assert!(try_gaussian_blur_f32_into(&image, 0.0, &mut buffer, &mut good).is_err());
+
+        let mut bad = Image::<Luma<i16>>::new(4, 5);
+        assert_eq!(try_filter3x3_into(&image, &kernel3x3, &mut bad), expected);
+        let kernel = Kernel::new(&kernel3x3, 3, 3);
+        assert_eq!(
+            kernel.try_filter_into(&image, |c, a| *c = Clamp::clamp(a), &mut bad),
+            expected
+        );
+    }
 }
@@ -1,12 +1,15 @@
 use super::{filter3x3, gaussian_blur_f32};
 use crate::{
-    definitions::{Clamp, Image},
+    definitions::{Clamp, Image, MaybeSync},
     map::{map_colors2, map_subpixels},
 };
-use image::{GrayImage, Luma};
+use image::{GenericImageView, GrayImage, Luma};
 
 /// Sharpens a grayscale image by applying a 3x3 approximation to the Laplacian.
-pub fn sharpen3x3(image: &GrayImage) -> GrayImage {
+pub fn sharpen3x3<I>(image: &I) -> GrayImage
+where
+    I: GenericImageView<Pixel = Luma<u8>> + MaybeSync,
+{
     let identity_minus_laplacian = [0, -1, 0, -1, 5, -1, 0, -1, 0];
     filter3x3(image, &identity_minus_laplacian)
 }
@@ -16,7 +19,10 @@ pub fn sharpen3x3(image: &GrayImage) -> GrayImage {
 /// * `sigma` is the standard deviation of the Gaussian filter used.
 /// * `amount` controls the level of sharpening. `output = input + amount * edges`.
 // TODO: remove unnecessary allocations, support colour images
-pub fn sharpen_gaussian(image: &GrayImage, sigma: f32, amount: f32) -> GrayImage {
+pub fn sharpen_gaussian<I>(image: &I, sigma: f32, amount: f32) -> GrayImage
+where
+    I: GenericImageView<Pixel = Luma<u8>>,
+{
     let image = map_subpixels(image, |x| x as f32);
     let smooth: Image<Luma<f32>> = gaussian_blur_f32(&image, sigma);
     map_colors2(&image, &smooth, |p, q| {
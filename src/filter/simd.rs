@@ -0,0 +1,389 @@
+//! Explicit SIMD implementations of the inner loops of 3x3 and separable filters
+//! on `u8` images.
+//!
+//! These are only compiled when the `simd` feature is enabled on `x86_64`, where SSE2
+//! is always available. Each function writes to an output image of the same dimensions
+//! as its input and returns `true`, or returns `false` without writing anything if SIMD
+//! support is not compiled in or if it cannot guarantee results that are bit-identical
+//! to the generic scalar implementation, in which case the caller should fall back to the
+//! scalar code.
+
+use super::GrayRegion;
+use crate::definitions::Image;
+use image::{GrayImage, Luma};
+
+/// Correlates a grayscale image with a 3x3 row-major integer kernel, padding by continuity
+/// and clamping the results to `i16`. Equivalent to `filter3x3::<_, _, i32, i16>`.
+///
+/// Accumulation is performed in 16 bits, so this returns `false` unless the result of
+/// the filter is guaranteed to fit in an `i16` without clamping.
+pub(crate) fn filter3x3_u8_i16(
+    image: GrayRegion<'_>,
+    kernel: &[i32; 9],
+    out: &mut Image<Luma<i16>>,
+) -> bool {
+    let bound: i64 = kernel.iter().map(|k| (*k as i64).abs()).sum::<i64>() * 255;
+    if bound > i16::MAX as i64 {
+        return false;
+    }
+    imp::filter3x3_u8_i16(image, kernel, out)
+}
+
+/// Horizontally correlates a grayscale image with an `f32` kernel, padding by continuity.
+/// Equivalent to `horizontal_filter::<GrayImage, Luma<u8>, f32>`.
+pub(crate) fn horizontal_filter_u8_f32(
+    image: GrayRegion<'_>,
+    kernel: &[f32],
+    out: &mut GrayImage,
+) -> bool {
+    is_exact_f32_kernel(kernel) && imp::horizontal_filter_u8_f32(image, kernel, out)
+}
+
+/// Vertically correlates a grayscale image with an `f32` kernel, padding by continuity.
+/// Equivalent to `vertical_filter::<GrayImage, Luma<u8>, f32>`.
+pub(crate) fn vertical_filter_u8_f32(
+    image: GrayRegion<'_>,
+    kernel: &[f32],
+    out: &mut GrayImage,
+) -> bool {
+    is_exact_f32_kernel(kernel) && imp::vertical_filter_u8_f32(image, kernel, out)
+}
+
+// The vectorised conversion from f32 to u8 saturates through i32, so it only matches
+// Clamp<f32> for u8 if no accumulated value can be NaN or exceed the range of an i32.
+fn is_exact_f32_kernel(kernel: &[f32]) -> bool {
+    kernel.iter().all(|k| k.is_finite())
+        && kernel.iter().map(|k| k.abs() as f64).sum::<f64>() * 255.0 < (1u64 << 30) as f64
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+mod imp {
+    use super::GrayRegion;
+    use crate::definitions::Image;
+    use image::{GrayImage, Luma};
+
+    pub(super) fn filter3x3_u8_i16(
+        _: GrayRegion<'_>,
+        _: &[i32; 9],
+        _: &mut Image<Luma<i16>>,
+    ) -> bool {
+        false
+    }
+
+    pub(super) fn horizontal_filter_u8_f32(
+        _: GrayRegion<'_>,
+        _: &[f32],
+        _: &mut GrayImage,
+    ) -> bool {
+        false
+    }
+
+    pub(super) fn vertical_filter_u8_f32(_: GrayRegion<'_>, _: &[f32], _: &mut GrayImage) -> bool {
+        false
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod imp {
+    use super::GrayRegion;
+    use crate::definitions::{Clamp, Image};
+    use crate::parallel::for_each_row_init;
+    use image::{GrayImage, Luma};
+    use std::arch::x86_64::*;
+
+    pub(super) fn filter3x3_u8_i16(
+        image: GrayRegion<'_>,
+        kernel: &[i32; 9],
+        out: &mut Image<Luma<i16>>,
+    ) -> bool {
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 {
+            return true;
+        }
+
+        // The caller has checked that every weight times 255 fits in an i16.
+        let mut weights = [0i16; 9];
+        for (w, k) in weights.iter_mut().zip(kernel.iter()) {
+            *w = *k as i16;
+        }
+        // Buffers for the three source rows, widened to i16 and with one pixel of padding
+        // on each side.
+        let init = || {
+            [
+                vec![0i16; width + 2],
+                vec![0i16; width + 2],
+                vec![0i16; width + 2],
+            ]
+        };
+
+        for_each_row_init(out, width, init, |padded, y, row| {
+            for (k_y, p) in padded.iter_mut().enumerate() {
+                let y_p = (y + k_y).saturating_sub(1).min(height - 1);
+                pad_row(image.row(y_p), 1, p, |v| v as i16);
+            }
+
+            let mut x = 0;
+            // JUSTIFICATION
+            //  Benefit
+            //      This is the reason this module exists.
+            //  Correctness
+            //      SSE2 is available on all x86_64 targets. Each load reads the
+            //      8 values starting at x + k_x <= width - 6 in a row of length
+            //      width + 2, and each store writes 8 values starting at x <= width - 8.
+            unsafe {
+                while x + 8 <= width {
+                    let mut acc = _mm_setzero_si128();
+                    for (k_y, p) in padded.iter().enumerate() {
+                        for k_x in 0..3 {
+                            let v = _mm_loadu_si128(p.as_ptr().add(x + k_x) as *const __m128i);
+                            let w = _mm_set1_epi16(weights[k_y * 3 + k_x]);
+                            acc = _mm_add_epi16(acc, _mm_mullo_epi16(v, w));
+                        }
+                    }
+                    _mm_storeu_si128(row.as_mut_ptr().add(x) as *mut __m128i, acc);
+                    x += 8;
+                }
+            }
+            for x in x..width {
+                let mut acc = 0i16;
+                for (k_y, p) in padded.iter().enumerate() {
+                    for k_x in 0..3 {
+                        acc += p[x + k_x] * weights[k_y * 3 + k_x];
+                    }
+                }
+                row[x] = acc;
+            }
+        });
+
+        true
+    }
+
+    pub(super) fn horizontal_filter_u8_f32(
+        image: GrayRegion<'_>,
+        kernel: &[f32],
+        out: &mut GrayImage,
+    ) -> bool {
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 {
+            return true;
+        }
+        if kernel.is_empty() {
+            out.iter_mut().for_each(|p| *p = 0);
+            return true;
+        }
+
+        let half_k = kernel.len() / 2;
+        let init = || vec![0f32; width + kernel.len() - 1];
+
+        for_each_row_init(out, width, init, |padded, y, row| {
+            pad_row(image.row(y), half_k, padded, |v| v as f32);
+
+            let mut x = 0;
+            // JUSTIFICATION
+            //  Benefit
+            //      This is the reason this module exists.
+            //  Correctness
+            //      SSE2 is available on all x86_64 targets. Each load reads the 4 values
+            //      starting at x + i <= width + kernel.len() - 5 in a row of length
+            //      width + kernel.len() - 1, and each store writes 4 values starting
+            //      at x <= width - 4.
+            unsafe {
+                while x + 4 <= width {
+                    let mut acc = _mm_setzero_ps();
+                    for (i, k) in kernel.iter().enumerate() {
+                        let v = _mm_loadu_ps(padded.as_ptr().add(x + i));
+                        acc = _mm_add_ps(acc, _mm_mul_ps(v, _mm_set1_ps(*k)));
+                    }
+                    store_u8x4(acc, row.as_mut_ptr().add(x));
+                    x += 4;
+                }
+            }
+            for x in x..width {
+                let mut acc = 0f32;
+                for (i, k) in kernel.iter().enumerate() {
+                    acc += padded[x + i] * k;
+                }
+                row[x] = <u8 as Clamp<f32>>::clamp(acc);
+            }
+        });
+
+        true
+    }
+
+    pub(super) fn vertical_filter_u8_f32(
+        image: GrayRegion<'_>,
+        kernel: &[f32],
+        out: &mut GrayImage,
+    ) -> bool {
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 {
+            return true;
+        }
+        if kernel.is_empty() {
+            out.iter_mut().for_each(|p| *p = 0);
+            return true;
+        }
+
+        let half_k = kernel.len() / 2;
+        let init = || Vec::with_capacity(kernel.len());
+
+        for_each_row_init(out, width, init, |rows: &mut Vec<&[u8]>, y, row| {
+            rows.clear();
+            rows.extend((0..kernel.len()).map(|i| {
+                let y_p = (y + i).saturating_sub(half_k).min(height - 1);
+                image.row(y_p)
+            }));
+
+            let mut x = 0;
+            // JUSTIFICATION
+            //  Benefit
+            //      This is the reason this module exists.
+            //  Correctness
+            //      SSE2 is available on all x86_64 targets. Each load reads the 4 values
+            //      starting at x <= width - 4 from a row of length width, as does each store.
+            unsafe {
+                while x + 4 <= width {
+                    let mut acc = _mm_setzero_ps();
+                    for (r, k) in rows.iter().zip(kernel.iter()) {
+                        let v = load_u8x4(r.as_ptr().add(x));
+                        acc = _mm_add_ps(acc, _mm_mul_ps(v, _mm_set1_ps(*k)));
+                    }
+                    store_u8x4(acc, row.as_mut_ptr().add(x));
+                    x += 4;
+                }
+            }
+            for x in x..width {
+                let mut acc = 0f32;
+                for (r, k) in rows.iter().zip(kernel.iter()) {
+                    acc += r[x] as f32 * k;
+                }
+                row[x] = <u8 as Clamp<f32>>::clamp(acc);
+            }
+        });
+
+        true
+    }
+
+    // Writes row into padded, converting with f and replicating its first and last
+    // values into the left padding of length left and the remaining right padding.
+    fn pad_row<T: Copy, F: Fn(u8) -> T>(row: &[u8], left: usize, padded: &mut [T], f: F) {
+        let last = row.len() - 1;
+        for (j, p) in padded.iter_mut().enumerate() {
+            *p = f(row[j.saturating_sub(left).min(last)]);
+        }
+    }
+
+    // Loads 4 u8 values as f32s.
+    #[inline]
+    unsafe fn load_u8x4(src: *const u8) -> __m128 {
+        let bytes = _mm_cvtsi32_si128((src as *const i32).read_unaligned());
+        let zero = _mm_setzero_si128();
+        let words = _mm_unpacklo_epi8(bytes, zero);
+        _mm_cvtepi32_ps(_mm_unpacklo_epi16(words, zero))
+    }
+
+    // Stores 4 f32 values as u8s, matching Clamp<f32> for u8 for inputs that are not
+    // NaN and lie within the range of an i32.
+    #[inline]
+    unsafe fn store_u8x4(values: __m128, dst: *mut u8) {
+        let ints = _mm_cvttps_epi32(values);
+        let words = _mm_packs_epi32(ints, ints);
+        let bytes = _mm_packus_epi16(words, words);
+        (dst as *mut i32).write_unaligned(_mm_cvtsi128_si32(bytes));
+    }
+}
+
+#[cfg(all(test, feature = "simd", target_arch = "x86_64"))]
+mod tests {
+    use super::*;
+    use crate::filter::{filter3x3, horizontal_filter_scalar, vertical_filter_scalar};
+    use crate::gradients::{HORIZONTAL_SCHARR, HORIZONTAL_SOBEL, VERTICAL_SOBEL};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_image(rng: &mut StdRng, width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |_, _| Luma([rng.gen()]))
+    }
+
+    // Odd sizes exercise the scalar tails, and tiny sizes the edge padding.
+    const SIZES: [(u32, u32); 6] = [(1, 1), (3, 2), (7, 5), (8, 8), (17, 9), (64, 33)];
+
+    #[test]
+    fn test_filter3x3_matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let kernels = [
+            HORIZONTAL_SOBEL,
+            VERTICAL_SOBEL,
+            HORIZONTAL_SCHARR,
+            [1, -2, 3, -4, 5, -6, 7, -8, 9],
+            [-128, 0, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        for &(width, height) in &SIZES {
+            let image = random_image(&mut rng, width, height);
+            for kernel in &kernels {
+                let expected: Image<Luma<i16>> = filter3x3(&image, kernel);
+                let mut actual = Image::new(width, height);
+                assert!(filter3x3_u8_i16((&image).into(), kernel, &mut actual));
+                assert_eq!(actual, expected, "{}x{} {:?}", width, height, kernel);
+            }
+        }
+    }
+
+    #[test]
+    fn test_filter3x3_rejects_kernels_that_could_overflow() {
+        let image = GrayImage::new(4, 4);
+        let mut out = Image::new(4, 4);
+        assert!(!filter3x3_u8_i16(
+            (&image).into(),
+            &[100, 100, 0, 0, 0, 0, 0, 0, 0],
+            &mut out
+        ));
+    }
+
+    #[test]
+    fn test_separable_passes_match_scalar() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let kernels: Vec<Vec<f32>> = vec![
+            vec![1.0],
+            vec![0.25, 0.5, 0.25],
+            vec![-1.0, 0.0, 1.0],
+            vec![0.1, 0.2, 0.3, 0.4],
+            vec![0.3, -0.7, 1.9, 2.3, -0.1, 0.05, 0.6],
+            (0..41).map(|_| rng.gen_range(-0.5f32, 0.5)).collect(),
+        ];
+        for &(width, height) in &SIZES {
+            let image = random_image(&mut rng, width, height);
+            for kernel in &kernels {
+                let mut expected = GrayImage::new(width, height);
+                let mut actual = GrayImage::new(width, height);
+
+                horizontal_filter_scalar(&image, kernel, &mut expected);
+                assert!(horizontal_filter_u8_f32(
+                    (&image).into(),
+                    kernel,
+                    &mut actual
+                ));
+                assert_eq!(actual, expected, "{}x{} {:?}", width, height, kernel);
+
+                vertical_filter_scalar(&image, kernel, &mut expected);
+                assert!(vertical_filter_u8_f32((&image).into(), kernel, &mut actual));
+                assert_eq!(actual, expected, "{}x{} {:?}", width, height, kernel);
+            }
+        }
+    }
+
+    #[test]
+    fn test_separable_passes_reject_non_finite_kernels() {
+        let image = GrayImage::new(4, 4);
+        let mut out = GrayImage::new(4, 4);
+        assert!(!horizontal_filter_u8_f32(
+            (&image).into(),
+            &[1.0, f32::NAN],
+            &mut out
+        ));
+        assert!(!vertical_filter_u8_f32(
+            (&image).into(),
+            &[f32::INFINITY],
+            &mut out
+        ));
+    }
+}
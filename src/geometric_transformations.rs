@@ -1,10 +1,11 @@
 //! Geometric transformations of images. This includes rotations, translation, and general
 //! projective transformations.
 
-use crate::definitions::{Clamp, Image};
+use crate::definitions::{BorderMode, Clamp, Image};
+use crate::error::{check_dimensions, Error};
 use crate::math::cast;
 use conv::ValueInto;
-use image::{GenericImageView, ImageBuffer, Pixel};
+use image::{GenericImageView, ImageBuffer, Luma, Pixel};
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 use std::{cmp, ops::Mul};
@@ -55,6 +56,23 @@ impl Projection {
         })
     }
 
+    /// The row-major 3x3 matrix in homogeneous coordinates defining this transformation,
+    /// normalised so that the bottom-right entry is 1.
+    ///
+    /// # Examples
+    /// ```
+    /// use imageproc::geometric_transformations::Projection;
+    ///
+    /// let p = Projection::translate(2.0, 3.0);
+    /// assert_eq!(p.matrix(), [1.0, 0.0, 2.0, 0.0, 1.0, 3.0, 0.0, 0.0, 1.0]);
+    ///
+    /// let q = Projection::from_matrix([2.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 2.0]).unwrap();
+    /// assert_eq!(q.matrix(), [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+    /// ```
+    pub fn matrix(&self) -> [f32; 9] {
+        self.transform
+    }
+
     /// Combine the transformation with another one. The resulting transformation is equivalent to
     /// applying this transformation followed by the `other` transformation.
     pub fn and_then(self, other: Projection) -> Projection {
@@ -129,6 +147,34 @@ impl Projection {
     }
 
     /// Calculates a projection from a set of four control point pairs.
+    ///
+    /// The returned projection maps each point in `from` to the corresponding point in `to`.
+    /// This is equivalent to OpenCV's `getPerspectiveTransform`. Returns `None` if three of
+    /// the points in either set are colinear.
+    ///
+    /// # Examples
+    /// Deskewing a quadrilateral region of an image into a rectangular output image.
+    /// ```
+    /// use image::{GrayImage, Luma};
+    /// use imageproc::geometric_transformations::{warp_into, Interpolation, Projection};
+    ///
+    /// let image = GrayImage::new(100, 100);
+    ///
+    /// // Corners of the region, e.g. from a document detector, in clockwise order
+    /// // starting from the top left.
+    /// let quad = [(12.0, 20.0), (80.0, 10.0), (90.0, 75.0), (5.0, 90.0)];
+    /// let (width, height) = (60, 40);
+    /// let rect = [
+    ///     (0.0, 0.0),
+    ///     ((width - 1) as f32, 0.0),
+    ///     ((width - 1) as f32, (height - 1) as f32),
+    ///     (0.0, (height - 1) as f32),
+    /// ];
+    ///
+    /// let projection = Projection::from_control_points(quad, rect).unwrap();
+    /// let mut deskewed = GrayImage::new(width, height);
+    /// warp_into(&image, &projection, Interpolation::Bilinear, Luma([0]), &mut deskewed);
+    /// ```
     pub fn from_control_points(from: [(f32, f32); 4], to: [(f32, f32); 4]) -> Option<Projection> {
         use approx::AbsDiffEq;
         use nalgebra::{linalg::SVD, OMatrix, OVector, U8};
@@ -195,6 +241,73 @@ impl Projection {
             })
     }
 
+    /// Calculates an affine transformation from three or more control point pairs.
+    ///
+    /// If exactly three pairs are given then the returned transformation maps each point
+    /// in `from` to the corresponding point in `to`. If more pairs are given then the
+    /// transformation minimising the sum of squared distances between the mapped `from`
+    /// points and the `to` points is returned.
+    ///
+    /// Returns `None` if fewer than three pairs are given, if `from` and `to` have
+    /// different lengths, or if all points in `from` are colinear.
+    ///
+    /// # Examples
+    /// ```
+    /// use imageproc::geometric_transformations::Projection;
+    ///
+    /// let from = [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)];
+    /// let to = [(5.0, 5.0), (25.0, 5.0), (5.0, 15.0)];
+    ///
+    /// let p = Projection::affine_from_control_points(&from, &to).unwrap();
+    /// let (x, y) = p * (10.0, 10.0);
+    /// assert!((x - 25.0).abs() < 1e-4 && (y - 15.0).abs() < 1e-4);
+    /// ```
+    pub fn affine_from_control_points(
+        from: &[(f32, f32)],
+        to: &[(f32, f32)],
+    ) -> Option<Projection> {
+        use nalgebra::{linalg::SVD, DMatrix, DVector};
+
+        if from.len() < 3 || from.len() != to.len() {
+            return None;
+        }
+
+        let a = DMatrix::<f64>::from_fn(from.len(), 3, |r, c| match c {
+            0 => from[r].0 as f64,
+            1 => from[r].1 as f64,
+            _ => 1.0,
+        });
+        let bx = DVector::<f64>::from_iterator(to.len(), to.iter().map(|p| p.0 as f64));
+        let by = DVector::<f64>::from_iterator(to.len(), to.iter().map(|p| p.1 as f64));
+
+        let svd = SVD::new(a, true, true);
+        let max_singular_value = svd.singular_values.max();
+        if svd.rank(max_singular_value * 1e-9) < 3 {
+            return None;
+        }
+
+        let hx = svd.solve(&bx, 0.0).ok()?;
+        let hy = svd.solve(&by, 0.0).ok()?;
+
+        let transform = [
+            hx[0] as f32,
+            hx[1] as f32,
+            hx[2] as f32,
+            hy[0] as f32,
+            hy[1] as f32,
+            hy[2] as f32,
+            0.0,
+            0.0,
+            1.0,
+        ];
+
+        try_inverse(&transform).map(|inverse| Projection {
+            transform,
+            inverse,
+            class: TransformationClass::Affine,
+        })
+    }
+
     // Helper functions used as optimization in warp.
     #[inline(always)]
     fn map_projective(&self, x: f32, y: f32) -> (f32, f32) {
@@ -312,11 +425,108 @@ where
     P: Pixel + Send + Sync + 'static,
     <P as Pixel>::Subpixel: Send + Sync,
     <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(width, height);
+    rotate_into(image, center, theta, interpolation, default, &mut out);
+    out
+}
+
+/// Rotates an image clockwise about the provided center by theta radians, writing the
+/// result to `out`.
+///
+/// The output image need not have the same dimensions as `image`. Output pixels whose
+/// pre-image lies outside the input image are set to `default`.
+pub fn rotate_into<P>(
+    image: &Image<P>,
+    center: (f32, f32),
+    theta: f32,
+    interpolation: Interpolation,
+    default: P,
+    out: &mut Image<P>,
+) where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
 {
     let (cx, cy) = center;
     let projection =
         Projection::translate(cx, cy) * Projection::rotate(theta) * Projection::translate(-cx, -cy);
-    warp(image, &projection, interpolation, default)
+    warp_into(image, &projection, interpolation, default, out);
+}
+
+/// Rotates an image clockwise about the provided center by theta radians, enlarging
+/// the output so that no part of the rotated image is cropped.
+///
+/// Returns the rotated image and the offset `(dx, dy)` that was added to rotated
+/// coordinates to place them in the output image, i.e. a point `p` in the input image is
+/// mapped to `rotation * p + (dx, dy)` in the output, where `rotation` is the rotation
+/// applied by [`rotate`](fn.rotate.html). Output pixels whose pre-image lies outside the
+/// input image are set to `default`.
+///
+/// # Examples
+/// ```
+/// use image::Luma;
+/// use imageproc::geometric_transformations::{rotate_to_fit, Interpolation};
+/// use std::f32::consts::PI;
+///
+/// let image = image::GrayImage::new(40, 20);
+/// let (rotated, offset) = rotate_to_fit(
+///     &image,
+///     (0.0, 0.0),
+///     PI / 2.0,
+///     Interpolation::Nearest,
+///     Luma([0u8]),
+/// );
+///
+/// assert_eq!(rotated.dimensions(), (20, 40));
+/// assert!((offset.0 - 19.0).abs() < 1e-4 && offset.1.abs() < 1e-4);
+/// ```
+pub fn rotate_to_fit<P>(
+    image: &Image<P>,
+    center: (f32, f32),
+    theta: f32,
+    interpolation: Interpolation,
+    default: P,
+) -> (Image<P>, (f32, f32))
+where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (cx, cy) = center;
+    let rotation =
+        Projection::translate(cx, cy) * Projection::rotate(theta) * Projection::translate(-cx, -cy);
+
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return (ImageBuffer::new(width, height), (0.0, 0.0));
+    }
+
+    // Pixel centres lie at integer coordinates, so the rotated image is bounded by
+    // the images of the centres of the corner pixels.
+    let (right, bottom) = ((width - 1) as f32, (height - 1) as f32);
+    let corners = [(0.0, 0.0), (right, 0.0), (0.0, bottom), (right, bottom)];
+    let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+    let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for corner in &corners {
+        let (x, y) = rotation * *corner;
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+
+    // Allow for rounding errors in the rotation, e.g. for multiples of PI / 2
+    const EPSILON: f32 = 1e-3;
+    let out_width = (max_x - min_x - EPSILON).ceil().max(0.0) as u32 + 1;
+    let out_height = (max_y - min_y - EPSILON).ceil().max(0.0) as u32 + 1;
+    let offset = (-min_x, -min_y);
+
+    let projection = Projection::translate(offset.0, offset.1) * rotation;
+    let mut out = ImageBuffer::new(out_width, out_height);
+    warp_into(image, &projection, interpolation, default, &mut out);
+    (out, offset)
 }
 
 /// Translates the input image by t. Note that image coordinates increase from
@@ -367,6 +577,169 @@ where
     out
 }
 
+/// Flips an image horizontally, i.e. reverses the order of the pixels in each row.
+pub fn flip_horizontal<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    let (width, height) = image.dimensions();
+    let channels = P::CHANNEL_COUNT as usize;
+    let mut out = ImageBuffer::new(width, height);
+    if width == 0 {
+        return out;
+    }
+    let pitch = width as usize * channels;
+    let in_rows = image.as_ref().chunks(pitch);
+    let out_rows = out.as_mut().chunks_mut(pitch);
+    for (in_row, out_row) in in_rows.zip(out_rows) {
+        let in_pixels = in_row.chunks(channels).rev();
+        for (i, o) in in_pixels.zip(out_row.chunks_mut(channels)) {
+            o.copy_from_slice(i);
+        }
+    }
+    out
+}
+
+/// Flips an image vertically, i.e. reverses the order of its rows.
+pub fn flip_vertical<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(width, height);
+    if width == 0 {
+        return out;
+    }
+    let pitch = width as usize * P::CHANNEL_COUNT as usize;
+    let in_rows = image.as_ref().chunks(pitch).rev();
+    for (in_row, out_row) in in_rows.zip(out.as_mut().chunks_mut(pitch)) {
+        out_row.copy_from_slice(in_row);
+    }
+    out
+}
+
+/// Rotates an image by 180 degrees.
+pub fn rotate180<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    let (width, height) = image.dimensions();
+    let channels = P::CHANNEL_COUNT as usize;
+    let mut out = ImageBuffer::new(width, height);
+    let in_pixels = image.as_ref().chunks(channels).rev();
+    for (i, o) in in_pixels.zip(out.as_mut().chunks_mut(channels)) {
+        o.copy_from_slice(i);
+    }
+    out
+}
+
+/// Transposes an image, i.e. reflects it in the line through the top-left corner
+/// at 45 degrees to the axes. The output pixel at `(x, y)` is the input pixel at `(y, x)`.
+pub fn transpose<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(height, width);
+    transpose_into(image, &mut out);
+    out
+}
+
+/// Transposes an image, writing the result to `out`, which must have the dimensions
+/// of `image` swapped.
+///
+/// Pixels are copied in square tiles to improve cache locality.
+///
+/// # Panics
+///
+/// Panics if `out.dimensions()` is not `(image.height(), image.width())`.
+pub fn transpose_into<P>(image: &Image<P>, out: &mut Image<P>)
+where
+    P: Pixel + 'static,
+{
+    try_transpose_into(image, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Transposes an image, writing the result to `out`. Returns an error if `out.dimensions()`
+/// is not `(image.height(), image.width())`.
+///
+/// See the [`transpose_into`](fn.transpose_into.html) documentation for more information.
+pub fn try_transpose_into<P>(image: &Image<P>, out: &mut Image<P>) -> Result<(), Error>
+where
+    P: Pixel + 'static,
+{
+    const TILE_SIZE: usize = 32;
+    let (width, height) = image.dimensions();
+    check_dimensions(out.dimensions(), (height, width))?;
+    let (width, height) = (width as usize, height as usize);
+    let channels = P::CHANNEL_COUNT as usize;
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+    let (raw_in, raw_out): (&[P::Subpixel], &mut [P::Subpixel]) = (image.as_ref(), out.as_mut());
+
+    for x_start in (0..width).step_by(TILE_SIZE) {
+        let x_end = (x_start + TILE_SIZE).min(width);
+        for y_start in (0..height).step_by(TILE_SIZE) {
+            let y_end = (y_start + TILE_SIZE).min(height);
+            for x in x_start..x_end {
+                let out_row = &mut raw_out[x * height * channels..][..height * channels];
+                for y in y_start..y_end {
+                    let i = (y * width + x) * channels;
+                    out_row[y * channels..][..channels].copy_from_slice(&raw_in[i..][..channels]);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rotates an image clockwise by 90 degrees.
+pub fn rotate90<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    let (width, height) = image.dimensions();
+    permute_tiled(image, height, width, |x, y| (height - 1 - y, x))
+}
+
+/// Rotates an image clockwise by 270 degrees, i.e. anticlockwise by 90 degrees.
+pub fn rotate270<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    let (width, height) = image.dimensions();
+    permute_tiled(image, height, width, |x, y| (y, width - 1 - x))
+}
+
+// Creates an image of the given dimensions by moving each input pixel (x, y) to
+// location f(x, y). Pixels are visited in square tiles to improve cache locality.
+fn permute_tiled<P, F>(image: &Image<P>, width: u32, height: u32, f: F) -> Image<P>
+where
+    P: Pixel + 'static,
+    F: Fn(u32, u32) -> (u32, u32),
+{
+    const TILE_SIZE: u32 = 32;
+    let (in_width, in_height) = image.dimensions();
+    let channels = P::CHANNEL_COUNT as usize;
+    let mut out = ImageBuffer::new(width, height);
+    let (raw_in, raw_out): (&[P::Subpixel], &mut [P::Subpixel]) = (image.as_ref(), out.as_mut());
+
+    for tile_y in (0..in_height).step_by(TILE_SIZE as usize) {
+        for tile_x in (0..in_width).step_by(TILE_SIZE as usize) {
+            for y in tile_y..(tile_y + TILE_SIZE).min(in_height) {
+                for x in tile_x..(tile_x + TILE_SIZE).min(in_width) {
+                    let (ox, oy) = f(x, y);
+                    let i = (y as usize * in_width as usize + x as usize) * channels;
+                    let o = (oy as usize * width as usize + ox as usize) * channels;
+                    raw_out[o..o + channels].copy_from_slice(&raw_in[i..i + channels]);
+                }
+            }
+        }
+    }
+    out
+}
+
 /// Applies a projective transformation to an image.
 ///
 /// The returned image has the same dimensions as `image`. Output pixels
@@ -391,9 +764,69 @@ where
     out
 }
 
+/// Applies a projective transformation to an image, producing an output of the given size.
+///
+/// Output pixels whose pre-image lies outside the input image are set to `default`.
+/// The provided projection defines a mapping from locations in the input image to their
+/// corresponding location in the output image, so an output that is larger than the input
+/// can be shifted to include locations with negative coordinates by composing the
+/// projection with a translation. If you instead have a mapping from output locations to
+/// input locations then pass its [`invert`](struct.Projection.html#method.invert).
+///
+/// # Examples
+/// Rendering two images onto a shared canvas, e.g. when stitching a panorama.
+/// ```
+/// use image::{Rgb, RgbImage};
+/// use imageproc::definitions::BorderMode;
+/// use imageproc::geometric_transformations::{
+///     warp_into_with_border, warp_to_size, Interpolation, Projection,
+/// };
+///
+/// let left = RgbImage::new(200, 100);
+/// let right = RgbImage::new(200, 100);
+///
+/// // A homography mapping the right image into the coordinates of the left image,
+/// // e.g. estimated from matched features.
+/// let right_to_left = Projection::translate(150.0, -20.0);
+///
+/// // The canvas covers both images, with its origin at (0, -20) in left image coordinates.
+/// let offset = Projection::translate(0.0, 20.0);
+/// let (width, height) = (350, 120);
+///
+/// let background = Rgb([0, 0, 0]);
+/// let mut canvas = warp_to_size(&left, &offset, width, height, Interpolation::Nearest, background);
+///
+/// // Draw the right image over the canvas, leaving pixels outside it unchanged.
+/// warp_into_with_border(
+///     &right,
+///     &(offset * right_to_left),
+///     Interpolation::Bilinear,
+///     BorderMode::Transparent,
+///     &mut canvas,
+/// );
+/// ```
+pub fn warp_to_size<P>(
+    image: &Image<P>,
+    projection: &Projection,
+    width: u32,
+    height: u32,
+    interpolation: Interpolation,
+    default: P,
+) -> Image<P>
+where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let mut out = ImageBuffer::new(width, height);
+    warp_into(image, projection, interpolation, default, &mut out);
+    out
+}
+
 /// Applies a projective transformation to an image, writing to a provided output.
 ///
-/// See the [`warp`](fn.warp.html) documentation for more information.
+/// The output image need not have the same dimensions as `image`. See the
+/// [`warp`](fn.warp.html) documentation for more information.
 pub fn warp_into<P>(
     image: &Image<P>,
     projection: &Projection,
@@ -409,6 +842,7 @@ pub fn warp_into<P>(
     let nn = |x, y| interpolate_nearest(image, x, y, default);
     let bl = |x, y| interpolate_bilinear(image, x, y, default);
     let bc = |x, y| interpolate_bicubic(image, x, y, default);
+    let lz = |x, y| interpolate_lanczos3(image, x, y, default);
     let wp = |x, y| projection.map_projective(x, y);
     let wa = |x, y| projection.map_affine(x, y);
     let wt = |x, y| projection.map_translation(x, y);
@@ -425,11 +859,20 @@ pub fn warp_into<P>(
         (I::Bicubic, TC::Translation) => warp_inner(out, wt, bc),
         (I::Bicubic, TC::Affine) => warp_inner(out, wa, bc),
         (I::Bicubic, TC::Projection) => warp_inner(out, wp, bc),
+        (I::Lanczos3, TC::Translation) => warp_inner(out, wt, lz),
+        (I::Lanczos3, TC::Affine) => warp_inner(out, wa, lz),
+        (I::Lanczos3, TC::Projection) => warp_inner(out, wp, lz),
     }
 }
 
 /// Warps an image using the provided function to define the pre-image of each output pixel.
 ///
+/// `border` determines the values of pixels outside the input image, and may be either a
+/// [`BorderMode`](../definitions/enum.BorderMode.html) or a pixel value. Passing a pixel value
+/// `p` is equivalent to passing `BorderMode::Constant(p)`: output pixels for which any of the
+/// pixels required for interpolation lie outside the input image are set to `p`. If `border`
+/// is `BorderMode::Transparent` then such output pixels are left unchanged.
+///
 /// # Examples
 /// Applying a wave pattern.
 /// ```
@@ -445,350 +888,2349 @@ pub fn warp_into<P>(
 ///     Luma([0u8])
 /// );
 /// ```
-pub fn warp_with<P, F>(
+pub fn warp_with<P, F, B>(
     image: &Image<P>,
     mapping: F,
     interpolation: Interpolation,
-    default: P,
+    border: B,
 ) -> Image<P>
 where
     F: Fn(f32, f32) -> (f32, f32) + Sync + Send,
+    B: Into<BorderMode<P>>,
     P: Pixel + Send + Sync + 'static,
     <P as Pixel>::Subpixel: Send + Sync,
     <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
 {
     let (width, height) = image.dimensions();
     let mut out = ImageBuffer::new(width, height);
-    warp_into_with(image, mapping, interpolation, default, &mut out);
+    warp_into_with(image, mapping, interpolation, border, &mut out);
     out
 }
 
 /// Warps an image using the provided function to define the pre-image of each output pixel,
 /// writing into a preallocated output.
 ///
+/// The output image need not have the same dimensions as `image`.
+///
 /// See the [`warp_with`](fn.warp_with.html) documentation for more information.
-pub fn warp_into_with<P, F>(
+pub fn warp_into_with<P, F, B>(
     image: &Image<P>,
     mapping: F,
     interpolation: Interpolation,
-    default: P,
+    border: B,
     out: &mut Image<P>,
 ) where
     F: Fn(f32, f32) -> (f32, f32) + Send + Sync,
+    B: Into<BorderMode<P>>,
     P: Pixel + Send + Sync + 'static,
     <P as Pixel>::Subpixel: Send + Sync,
     <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
 {
+    let default = match border.into() {
+        BorderMode::Constant(default) => default,
+        border => {
+            warp_inner_with_border(image, out, mapping, interpolation, border);
+            return;
+        }
+    };
     let nn = |x, y| interpolate_nearest(image, x, y, default);
     let bl = |x, y| interpolate_bilinear(image, x, y, default);
     let bc = |x, y| interpolate_bicubic(image, x, y, default);
+    let lz = |x, y| interpolate_lanczos3(image, x, y, default);
     use Interpolation as I;
 
     match interpolation {
         I::Nearest => warp_inner(out, mapping, nn),
         I::Bilinear => warp_inner(out, mapping, bl),
         I::Bicubic => warp_inner(out, mapping, bc),
+        I::Lanczos3 => warp_inner(out, mapping, lz),
     }
 }
 
-// Work horse of all warp functions
-// TODO: make faster by avoiding boundary checks in inner section of src image
-fn warp_inner<P, Fc, Fi>(out: &mut Image<P>, mapping: Fc, get_pixel: Fi)
+/// Resamples an image into polar coordinates about `center`.
+///
+/// The output image has the given dimensions. The output pixel at `(x, y)` is sampled from
+/// the point at distance `x * max_radius / width` from `center` and at a clockwise angle of
+/// `y * 2π / height` from the positive x-axis, so that each row of the output corresponds to
+/// a ray from `center` and each column to a circle around it. Output pixels whose pre-image
+/// lies outside the input image are set to `default`.
+///
+/// See [`warp_polar_inverse`](fn.warp_polar_inverse.html) for the inverse transformation.
+pub fn warp_polar<P>(
+    image: &Image<P>,
+    center: (f32, f32),
+    max_radius: f32,
+    width: u32,
+    height: u32,
+    interpolation: Interpolation,
+    default: P,
+) -> Image<P>
 where
-    P: Pixel + 'static,
+    P: Pixel + Send + Sync + 'static,
     <P as Pixel>::Subpixel: Send + Sync,
     <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
-    Fc: Fn(f32, f32) -> (f32, f32) + Send + Sync,
-    Fi: Fn(f32, f32) -> P + Send + Sync,
 {
-    let width = out.width();
-    let raw_out = out.as_mut();
-    let pitch = P::CHANNEL_COUNT as usize * width as usize;
-
-    #[cfg(feature = "rayon")]
-    let chunks = raw_out.par_chunks_mut(pitch);
-    #[cfg(not(feature = "rayon"))]
-    let chunks = raw_out.chunks_mut(pitch);
-
-    chunks.enumerate().for_each(|(y, row)| {
-        for (x, slice) in row.chunks_mut(P::CHANNEL_COUNT as usize).enumerate() {
-            let (px, py) = mapping(x as f32, y as f32);
-            *P::from_slice_mut(slice) = get_pixel(px, py);
-        }
-    });
+    let radius_scale = max_radius / width as f32;
+    let mut out = ImageBuffer::new(width, height);
+    warp_into_with(
+        image,
+        |x, y| from_polar(center, x * radius_scale, y, height),
+        interpolation,
+        default,
+        &mut out,
+    );
+    out
 }
 
-// Classifies transformation by looking up transformation matrix coefficients
-fn class_from_matrix(mx: [f32; 9]) -> TransformationClass {
-    if (mx[6] - 0.0).abs() < 1e-10 && (mx[7] - 0.0).abs() < 1e-10 && (mx[8] - 1.0).abs() < 1e-10 {
-        if (mx[0] - 1.0).abs() < 1e-10
-            && (mx[1] - 0.0).abs() < 1e-10
-            && (mx[3] - 0.0).abs() < 1e-10
-            && (mx[4] - 1.0).abs() < 1e-10
-        {
-            TransformationClass::Translation
-        } else {
-            TransformationClass::Affine
-        }
-    } else {
-        TransformationClass::Projection
-    }
+/// Resamples an image from polar coordinates about `center`, inverting
+/// [`warp_polar`](fn.warp_polar.html).
+///
+/// `polar` should be an image produced by `warp_polar` with the same `center` and
+/// `max_radius`. The output image has dimensions `width` and `height`. Output pixels
+/// further than `max_radius` from `center` are set to `default`.
+pub fn warp_polar_inverse<P>(
+    polar: &Image<P>,
+    center: (f32, f32),
+    max_radius: f32,
+    width: u32,
+    height: u32,
+    interpolation: Interpolation,
+    default: P,
+) -> Image<P>
+where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (polar_width, polar_height) = polar.dimensions();
+    let radius_scale = polar_width as f32 / max_radius;
+    let mut out = ImageBuffer::new(width, height);
+    warp_into_with(
+        polar,
+        |x, y| {
+            let (r, a) = to_polar(center, x, y, polar_height);
+            (r * radius_scale, a)
+        },
+        interpolation,
+        default,
+        &mut out,
+    );
+    out
 }
 
-fn normalize(mx: [f32; 9]) -> [f32; 9] {
-    [
-        mx[0] / mx[8],
-        mx[1] / mx[8],
-        mx[2] / mx[8],
-        mx[3] / mx[8],
-        mx[4] / mx[8],
-        mx[5] / mx[8],
-        mx[6] / mx[8],
-        mx[7] / mx[8],
-        1.0,
-    ]
+/// Resamples an image into log-polar coordinates about `center`.
+///
+/// This is identical to [`warp_polar`](fn.warp_polar.html), except that the output pixel at
+/// `(x, y)` is sampled from the point at distance `max_radius.powf(x / width)` from `center`.
+/// Rotations and scalings about `center` in the input image correspond to translations in the
+/// output, which allows e.g. rotation and scale invariant template matching.
+///
+/// See [`warp_log_polar_inverse`](fn.warp_log_polar_inverse.html) for the inverse transformation.
+pub fn warp_log_polar<P>(
+    image: &Image<P>,
+    center: (f32, f32),
+    max_radius: f32,
+    width: u32,
+    height: u32,
+    interpolation: Interpolation,
+    default: P,
+) -> Image<P>
+where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let log_scale = max_radius.ln() / width as f32;
+    let mut out = ImageBuffer::new(width, height);
+    warp_into_with(
+        image,
+        |x, y| from_polar(center, (x * log_scale).exp(), y, height),
+        interpolation,
+        default,
+        &mut out,
+    );
+    out
 }
 
-// TODO: write me in f64
-fn try_inverse(t: &[f32; 9]) -> Option<[f32; 9]> {
-    let [t00, t01, t02, t10, t11, t12, t20, t21, t22] = t;
-
-    let m00 = t11 * t22 - t12 * t21;
-    let m01 = t10 * t22 - t12 * t20;
-    let m02 = t10 * t21 - t11 * t20;
+/// Resamples an image from log-polar coordinates about `center`, inverting
+/// [`warp_log_polar`](fn.warp_log_polar.html).
+///
+/// `log_polar` should be an image produced by `warp_log_polar` with the same `center` and
+/// `max_radius`. The output image has dimensions `width` and `height`. Output pixels
+/// closer than 1 or further than `max_radius` from `center` are set to `default`.
+pub fn warp_log_polar_inverse<P>(
+    log_polar: &Image<P>,
+    center: (f32, f32),
+    max_radius: f32,
+    width: u32,
+    height: u32,
+    interpolation: Interpolation,
+    default: P,
+) -> Image<P>
+where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (polar_width, polar_height) = log_polar.dimensions();
+    let log_scale = polar_width as f32 / max_radius.ln();
+    let mut out = ImageBuffer::new(width, height);
+    warp_into_with(
+        log_polar,
+        |x, y| {
+            let (r, a) = to_polar(center, x, y, polar_height);
+            (r.ln() * log_scale, a)
+        },
+        interpolation,
+        default,
+        &mut out,
+    );
+    out
+}
 
-    let det = t00 * m00 - t01 * m01 + t02 * m02;
+// The point at the given distance from center, at the angle corresponding to
+// row y of a polar image with the given height.
+fn from_polar(center: (f32, f32), radius: f32, y: f32, height: u32) -> (f32, f32) {
+    let angle = y * 2.0 * std::f32::consts::PI / height as f32;
+    let (sin, cos) = angle.sin_cos();
+    (center.0 + radius * cos, center.1 + radius * sin)
+}
 
-    if det.abs() < 1e-10 {
-        return None;
+// The distance of (x, y) from center, and the row of a polar image with the given
+// height corresponding to its angle about center.
+fn to_polar(center: (f32, f32), x: f32, y: f32, height: u32) -> (f32, f32) {
+    let (dx, dy) = (x - center.0, y - center.1);
+    let mut angle = dy.atan2(dx);
+    if angle < 0.0 {
+        angle += 2.0 * std::f32::consts::PI;
+    }
+    let mut row = angle * height as f32 / (2.0 * std::f32::consts::PI);
+    // Angles just below 2π are closer to the first row than the last
+    if row > height as f32 - 0.5 {
+        row -= height as f32;
     }
+    ((dx * dx + dy * dy).sqrt(), row)
+}
 
-    let m10 = t01 * t22 - t02 * t21;
-    let m11 = t00 * t22 - t02 * t20;
-    let m12 = t00 * t21 - t01 * t20;
-    let m20 = t01 * t12 - t02 * t11;
-    let m21 = t00 * t12 - t02 * t10;
-    let m22 = t00 * t11 - t01 * t10;
+/// Resizes an image by area averaging.
+///
+/// Each output pixel covers a rectangular region of the input image, and its value is the
+/// average of the input pixels in that region, weighted by the area of their overlap with it.
+/// This avoids the aliasing produced by point sampling methods when shrinking an image by a
+/// large factor, and is equivalent to OpenCV's `INTER_AREA` when shrinking.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::geometric_transformations::resize_area;
+///
+/// let image = gray_image!(
+///     0, 2, 4, 6;
+///     2, 4, 6, 8);
+///
+/// let expected = gray_image!(
+///     2, 6);
+///
+/// assert_pixels_eq!(resize_area(&image, 2, 1), expected);
+/// # }
+/// ```
+pub fn resize_area<P>(image: &Image<P>, width: u32, height: u32) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (in_width, in_height) = image.dimensions();
+    let x_scale = in_width as f32 / width as f32;
+    let y_scale = in_height as f32 / height as f32;
+    crate::pyramid::resample(
+        image,
+        width,
+        height,
+        |x, len, taps| area_taps(x, len, x_scale, taps),
+        |y, len, taps| area_taps(y, len, y_scale, taps),
+    )
+}
 
-    #[rustfmt::skip]
-    let inv = [
-         m00 / det, -m10 / det,  m20 / det,
-        -m01 / det,  m11 / det, -m21 / det,
-         m02 / det, -m12 / det,  m22 / det,
-    ];
+// Appends the input indices and weights contributing to the output index x when
+// resizing by area averaging, where each output pixel covers scale input pixels.
+fn area_taps(x: usize, len: usize, scale: f32, taps: &mut Vec<(usize, f32)>) {
+    let start = x as f32 * scale;
+    let end = ((x + 1) as f32 * scale).min(len as f32);
+    let mut i = start.floor() as usize;
+    while (i as f32) < end && i < len {
+        let overlap = end.min((i + 1) as f32) - start.max(i as f32);
+        if overlap > 0.0 {
+            taps.push((i, overlap / (end - start)));
+        }
+        i += 1;
+    }
+}
 
-    Some(normalize(inv))
+/// Resamples an image using precomputed coordinate maps.
+///
+/// The output image has the same dimensions as the maps, and the output pixel at `(x, y)`
+/// is sampled from the location `(map_x[(x, y)], map_y[(x, y)])` in the input image.
+/// `border` determines the values of pixels outside the input image. Output pixels that
+/// depend on pixels outside the input image are set to `p` for `BorderMode::Constant(p)`,
+/// and are left unchanged for `BorderMode::Transparent` (i.e. are black for `remap`).
+///
+/// This is equivalent to calling [`warp_with`](fn.warp_with.html) with a mapping that looks
+/// up each pre-image in the maps, but allows an expensive mapping to be computed once and
+/// then applied to many images, e.g. to each frame of a video.
+///
+/// # Panics
+/// If `map_x` and `map_y` have different dimensions.
+///
+/// # Examples
+/// ```
+/// use image::{GrayImage, ImageBuffer, Luma};
+/// use imageproc::definitions::BorderMode;
+/// use imageproc::geometric_transformations::{remap, Interpolation};
+///
+/// // Maps that flip an image horizontally.
+/// let (width, height) = (64, 48);
+/// let map_x = ImageBuffer::from_fn(width, height, |x, _| Luma([(width - 1 - x) as f32]));
+/// let map_y = ImageBuffer::from_fn(width, height, |_, y| Luma([y as f32]));
+///
+/// let frame = GrayImage::new(width, height);
+/// let flipped = remap(&frame, &map_x, &map_y, Interpolation::Nearest, BorderMode::Replicate);
+/// ```
+pub fn remap<P, B>(
+    image: &Image<P>,
+    map_x: &Image<Luma<f32>>,
+    map_y: &Image<Luma<f32>>,
+    interpolation: Interpolation,
+    border: B,
+) -> Image<P>
+where
+    B: Into<BorderMode<P>>,
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    try_remap(image, map_x, map_y, interpolation, border).unwrap_or_else(|e| panic!("{}", e))
 }
 
-fn mul3x3(a: [f32; 9], b: [f32; 9]) -> [f32; 9] {
-    let [a00, a01, a02, a10, a11, a12, a20, a21, a22] = a;
-    let [b00, b01, b02, b10, b11, b12, b20, b21, b22] = b;
-    [
-        a00 * b00 + a01 * b10 + a02 * b20,
-        a00 * b01 + a01 * b11 + a02 * b21,
-        a00 * b02 + a01 * b12 + a02 * b22,
-        a10 * b00 + a11 * b10 + a12 * b20,
-        a10 * b01 + a11 * b11 + a12 * b21,
-        a10 * b02 + a11 * b12 + a12 * b22,
-        a20 * b00 + a21 * b10 + a22 * b20,
-        a20 * b01 + a21 * b11 + a22 * b21,
-        a20 * b02 + a21 * b12 + a22 * b22,
-    ]
+/// Resamples an image using per-pixel coordinate maps, writing the result to `out`,
+/// which must have the same dimensions as `map_x` and `map_y`.
+///
+/// See the [`remap`](fn.remap.html) documentation for more information.
+///
+/// # Panics
+/// If `map_x`, `map_y` and `out` do not all have the same dimensions.
+pub fn remap_into<P, B>(
+    image: &Image<P>,
+    map_x: &Image<Luma<f32>>,
+    map_y: &Image<Luma<f32>>,
+    interpolation: Interpolation,
+    border: B,
+    out: &mut Image<P>,
+) where
+    B: Into<BorderMode<P>>,
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    try_remap_into(image, map_x, map_y, interpolation, border, out)
+        .unwrap_or_else(|e| panic!("{}", e))
 }
 
-fn blend_cubic<P>(px0: &P, px1: &P, px2: &P, px3: &P, x: f32) -> P
+/// Resamples an image using precomputed coordinate maps, returning an error if `map_x`
+/// and `map_y` have different dimensions.
+///
+/// See the [`remap`](fn.remap.html) documentation for more information.
+pub fn try_remap<P, B>(
+    image: &Image<P>,
+    map_x: &Image<Luma<f32>>,
+    map_y: &Image<Luma<f32>>,
+    interpolation: Interpolation,
+    border: B,
+) -> Result<Image<P>, Error>
 where
-    P: Pixel,
-    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+    B: Into<BorderMode<P>>,
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
 {
-    let mut outp = *px0;
-
-    for i in 0..(P::CHANNEL_COUNT as usize) {
-        let p0 = cast(px0.channels()[i]);
-        let p1 = cast(px1.channels()[i]);
-        let p2 = cast(px2.channels()[i]);
-        let p3 = cast(px3.channels()[i]);
-        #[rustfmt::skip]
-        let pval = p1 + 0.5 * x * (p2 - p0 + x * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3 + x * (3.0 * (p1 - p2) + p3 - p0)));
-        outp.channels_mut()[i] = <P as Pixel>::Subpixel::clamp(pval);
-    }
+    let (width, height) = map_x.dimensions();
+    let mut out = ImageBuffer::new(width, height);
+    try_remap_into(image, map_x, map_y, interpolation, border, &mut out)?;
+    Ok(out)
+}
 
-    outp
+/// Resamples an image using per-pixel coordinate maps, writing the result to `out`.
+/// Returns an error if `map_x`, `map_y` and `out` do not all have the same dimensions.
+///
+/// See the [`remap`](fn.remap.html) documentation for more information.
+pub fn try_remap_into<P, B>(
+    image: &Image<P>,
+    map_x: &Image<Luma<f32>>,
+    map_y: &Image<Luma<f32>>,
+    interpolation: Interpolation,
+    border: B,
+    out: &mut Image<P>,
+) -> Result<(), Error>
+where
+    B: Into<BorderMode<P>>,
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    check_dimensions(map_y.dimensions(), map_x.dimensions())?;
+    check_dimensions(out.dimensions(), map_x.dimensions())?;
+    let width = map_x.width();
+    let (xs, ys): (&[f32], &[f32]) = (map_x.as_ref(), map_y.as_ref());
+    warp_into_with(
+        image,
+        |x, y| {
+            let i = y as usize * width as usize + x as usize;
+            (xs[i], ys[i])
+        },
+        interpolation,
+        border,
+        out,
+    );
+    Ok(())
 }
 
-fn interpolate_bicubic<P>(image: &Image<P>, x: f32, y: f32, default: P) -> P
+/// Warps an image by applying a separate affine transformation to each triangle of a mesh.
+///
+/// `triangles` lists triples of indices into `from` and `to`. Each triangle with vertices
+/// taken from `to` in the output image is filled by the affine image of the corresponding
+/// triangle with vertices taken from `from` in the input image. This is the standard way of
+/// warping between two sets of landmarks, e.g. for face morphing. A suitable mesh can be
+/// computed using [`delaunay_triangulation`](../geometry/fn.delaunay_triangulation.html).
+///
+/// The returned image has the same dimensions as `image`. Output pixels that lie outside
+/// every triangle, or whose pre-image lies outside the input image, are set to `default`.
+///
+/// # Panics
+/// If `from` and `to` have different lengths, or if any index in `triangles` is out of bounds.
+///
+/// # Examples
+/// ```
+/// use image::{GrayImage, Luma};
+/// use imageproc::geometric_transformations::{warp_piecewise_affine, Interpolation};
+/// use imageproc::geometry::delaunay_triangulation;
+/// use imageproc::point::Point;
+///
+/// let image = GrayImage::new(100, 100);
+///
+/// // Move the centre of the image up and to the right, keeping the corners fixed.
+/// let from = [(0.0, 0.0), (99.0, 0.0), (99.0, 99.0), (0.0, 99.0), (50.0, 50.0)];
+/// let to = [(0.0, 0.0), (99.0, 0.0), (99.0, 99.0), (0.0, 99.0), (60.0, 40.0)];
+///
+/// let points: Vec<_> = to.iter().map(|p| Point::new(p.0, p.1)).collect();
+/// let triangles = delaunay_triangulation(&points);
+///
+/// let warped = warp_piecewise_affine(
+///     &image,
+///     &from,
+///     &to,
+///     &triangles,
+///     Interpolation::Bilinear,
+///     Luma([0]),
+/// );
+/// ```
+pub fn warp_piecewise_affine<P>(
+    image: &Image<P>,
+    from: &[(f32, f32)],
+    to: &[(f32, f32)],
+    triangles: &[[usize; 3]],
+    interpolation: Interpolation,
+    default: P,
+) -> Image<P>
 where
     P: Pixel + 'static,
     <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
 {
-    let left = x.floor() - 1f32;
-    let right = left + 4f32;
-    let top = y.floor() - 1f32;
-    let bottom = top + 4f32;
-
-    let x_weight = x - (left + 1f32);
-    let y_weight = y - (top + 1f32);
-
-    let mut col: [P; 4] = [default, default, default, default];
+    assert_eq!(
+        from.len(),
+        to.len(),
+        "from and to must contain the same number of points"
+    );
 
     let (width, height) = image.dimensions();
-    if left < 0f32 || right >= width as f32 || top < 0f32 || bottom >= height as f32 {
-        default
-    } else {
-        for row in top as u32..bottom as u32 {
-            let (p0, p1, p2, p3): (P, P, P, P) = unsafe {
-                (
-                    image.unsafe_get_pixel(left as u32, row),
-                    image.unsafe_get_pixel(left as u32 + 1, row),
-                    image.unsafe_get_pixel(left as u32 + 2, row),
-                    image.unsafe_get_pixel(left as u32 + 3, row),
-                )
-            };
+    let mut out = ImageBuffer::from_pixel(width, height, default);
+    if width == 0 || height == 0 {
+        return out;
+    }
 
-            let c = blend_cubic(&p0, &p1, &p2, &p3, x_weight);
-            col[row as usize - top as usize] = c;
-        }
+    for t in triangles {
+        let src = [from[t[0]], from[t[1]], from[t[2]]];
+        let dst = [to[t[0]], to[t[1]], to[t[2]]];
+        let mapping = match Projection::affine_from_control_points(&dst, &src) {
+            Some(mapping) => mapping,
+            None => continue,
+        };
 
-        blend_cubic(&col[0], &col[1], &col[2], &col[3], y_weight)
+        let min_x = dst.iter().map(|p| p.0).fold(f32::INFINITY, f32::min);
+        let max_x = dst.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+        let min_y = dst.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+        let max_y = dst.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+        if max_x < 0.0 || max_y < 0.0 || min_x > (width - 1) as f32 || min_y > (height - 1) as f32 {
+            continue;
+        }
+        let x0 = min_x.max(0.0).ceil() as u32;
+        let y0 = min_y.max(0.0).ceil() as u32;
+        let x1 = max_x.min((width - 1) as f32).floor() as u32;
+        let y1 = max_y.min((height - 1) as f32).floor() as u32;
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let p = (x as f32, y as f32);
+                if !in_triangle(p, &dst) {
+                    continue;
+                }
+                let (sx, sy) = mapping.map_affine(p.0, p.1);
+                out.put_pixel(x, y, interpolate(image, sx, sy, interpolation, default));
+            }
+        }
     }
-}
 
-fn blend_bilinear<P>(
-    top_left: P,
-    top_right: P,
-    bottom_left: P,
-    bottom_right: P,
-    right_weight: f32,
-    bottom_weight: f32,
-) -> P
-where
-    P: Pixel,
-    P::Subpixel: ValueInto<f32> + Clamp<f32>,
-{
-    let top = top_left.map2(&top_right, |u, v| {
-        P::Subpixel::clamp((1f32 - right_weight) * cast(u) + right_weight * cast(v))
-    });
+    out
+}
 
-    let bottom = bottom_left.map2(&bottom_right, |u, v| {
-        P::Subpixel::clamp((1f32 - right_weight) * cast(u) + right_weight * cast(v))
-    });
+// Returns true if p lies inside or on the boundary of the triangle t.
+fn in_triangle(p: (f32, f32), t: &[(f32, f32); 3]) -> bool {
+    const EPSILON: f32 = 1e-4;
+    let edge = |a: (f32, f32), b: (f32, f32)| (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+    let (d0, d1, d2) = (edge(t[0], t[1]), edge(t[1], t[2]), edge(t[2], t[0]));
+    (d0 >= -EPSILON && d1 >= -EPSILON && d2 >= -EPSILON)
+        || (d0 <= EPSILON && d1 <= EPSILON && d2 <= EPSILON)
+}
 
-    top.map2(&bottom, |u, v| {
-        P::Subpixel::clamp((1f32 - bottom_weight) * cast(u) + bottom_weight * cast(v))
-    })
+/// A [thin-plate spline] interpolating a set of control point correspondences.
+///
+/// A thin-plate spline is the smoothest mapping of the plane, in the sense of minimising
+/// its bending energy, that sends each control point to its target location. It is commonly
+/// used for non-rigid registration and shape normalisation.
+///
+/// [thin-plate spline]: https://en.wikipedia.org/wiki/Thin_plate_spline
+#[derive(Clone, Debug)]
+pub struct ThinPlateSpline {
+    control_points: Vec<(f64, f64)>,
+    // Weights of the radial basis function centred at each control point,
+    // for the x and y coordinates respectively.
+    weights: Vec<(f64, f64)>,
+    // Coefficients of 1, x and y in the affine part of the mapping.
+    affine: [(f64, f64); 3],
 }
 
-fn interpolate_bilinear<P>(image: &Image<P>, x: f32, y: f32, default: P) -> P
-where
-    P: Pixel + 'static,
-    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
-{
-    let left = x.floor();
-    let right = left + 1f32;
-    let top = y.floor();
-    let bottom = top + 1f32;
+impl ThinPlateSpline {
+    /// Computes the thin-plate spline mapping each point in `from` to the corresponding
+    /// point in `to`.
+    ///
+    /// Returns `None` if fewer than three pairs are given, if `from` and `to` have different
+    /// lengths, or if the spline is not uniquely defined, e.g. because `from` contains
+    /// duplicate points or all points in `from` are colinear.
+    pub fn new(from: &[(f32, f32)], to: &[(f32, f32)]) -> Option<ThinPlateSpline> {
+        use nalgebra::{linalg::SVD, DMatrix};
+
+        let n = from.len();
+        if n < 3 || n != to.len() {
+            return None;
+        }
+        let control_points: Vec<(f64, f64)> =
+            from.iter().map(|p| (p.0 as f64, p.1 as f64)).collect();
+
+        // Solve [K P; P^T 0] [w; a] = [v; 0], where K holds the radial basis function
+        // evaluated between each pair of control points and each row of P is (1, x, y).
+        let mut a = DMatrix::<f64>::zeros(n + 3, n + 3);
+        for i in 0..n {
+            for j in 0..n {
+                a[(i, j)] = tps_kernel(control_points[i], control_points[j]);
+            }
+            let (x, y) = control_points[i];
+            for (k, v) in [1.0, x, y].iter().enumerate() {
+                a[(i, n + k)] = *v;
+                a[(n + k, i)] = *v;
+            }
+        }
+        let mut b = DMatrix::<f64>::zeros(n + 3, 2);
+        for (i, p) in to.iter().enumerate() {
+            b[(i, 0)] = p.0 as f64;
+            b[(i, 1)] = p.1 as f64;
+        }
 
-    let right_weight = x - left;
-    let bottom_weight = y - top;
+        let svd = SVD::new(a, true, true);
+        let max_singular_value = svd.singular_values.max();
+        if svd.rank(max_singular_value * 1e-12) < n + 3 {
+            return None;
+        }
+        let solution = svd.solve(&b, 0.0).ok()?;
+
+        let weights = (0..n)
+            .map(|i| (solution[(i, 0)], solution[(i, 1)]))
+            .collect();
+        let mut affine = [(0.0, 0.0); 3];
+        for (k, c) in affine.iter_mut().enumerate() {
+            *c = (solution[(n + k, 0)], solution[(n + k, 1)]);
+        }
+
+        Some(ThinPlateSpline {
+            control_points,
+            weights,
+            affine,
+        })
+    }
+
+    /// Evaluates the spline at the point `(x, y)`.
+    pub fn evaluate(&self, x: f32, y: f32) -> (f32, f32) {
+        let p = (x as f64, y as f64);
+        let [c, cx, cy] = self.affine;
+        let mut u = c.0 + cx.0 * p.0 + cy.0 * p.1;
+        let mut v = c.1 + cx.1 * p.0 + cy.1 * p.1;
+        for (q, w) in self.control_points.iter().zip(&self.weights) {
+            let k = tps_kernel(p, *q);
+            u += w.0 * k;
+            v += w.1 * k;
+        }
+        (u as f32, v as f32)
+    }
+}
+
+// The thin-plate spline radial basis function r^2 log(r^2).
+fn tps_kernel(p: (f64, f64), q: (f64, f64)) -> f64 {
+    let r2 = (p.0 - q.0).powi(2) + (p.1 - q.1).powi(2);
+    if r2 == 0.0 {
+        0.0
+    } else {
+        r2 * r2.ln()
+    }
+}
+
+/// Warps an image using a thin-plate spline, so that each point in `from` in the input
+/// image is moved to the corresponding point in `to` in the output image.
+///
+/// Returns `None` if the spline from `to` to `from` cannot be computed. See
+/// [`ThinPlateSpline::new`](struct.ThinPlateSpline.html#method.new) for details.
+///
+/// The returned image has the same dimensions as `image`. Output pixels whose pre-image
+/// lies outside the input image are set to `default`.
+///
+/// # Examples
+/// ```
+/// use image::{GrayImage, Luma};
+/// use imageproc::geometric_transformations::{warp_thin_plate_spline, Interpolation};
+///
+/// let image = GrayImage::new(100, 100);
+///
+/// // Pull the centre of the image towards the top-right, keeping the corners fixed.
+/// let from = [(0.0, 0.0), (99.0, 0.0), (99.0, 99.0), (0.0, 99.0), (50.0, 50.0)];
+/// let to = [(0.0, 0.0), (99.0, 0.0), (99.0, 99.0), (0.0, 99.0), (60.0, 40.0)];
+///
+/// let warped = warp_thin_plate_spline(&image, &from, &to, Interpolation::Bilinear, Luma([0]));
+/// assert!(warped.is_some());
+/// ```
+pub fn warp_thin_plate_spline<P>(
+    image: &Image<P>,
+    from: &[(f32, f32)],
+    to: &[(f32, f32)],
+    interpolation: Interpolation,
+    default: P,
+) -> Option<Image<P>>
+where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    // Each output pixel is sampled from its pre-image, so fit the inverse mapping.
+    let spline = ThinPlateSpline::new(to, from)?;
+    Some(warp_with(
+        image,
+        |x, y| spline.evaluate(x, y),
+        interpolation,
+        default,
+    ))
+}
+
+/// The intrinsic parameters of a pinhole camera, i.e. the non-zero entries of the camera matrix
+/// `[fx, 0, cx; 0, fy, cy; 0, 0, 1]`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraMatrix {
+    /// Focal length in the x direction, in pixels.
+    pub fx: f32,
+    /// Focal length in the y direction, in pixels.
+    pub fy: f32,
+    /// The x coordinate of the principal point.
+    pub cx: f32,
+    /// The y coordinate of the principal point.
+    pub cy: f32,
+}
+
+/// Brown–Conrady lens distortion coefficients, as produced by e.g. OpenCV's `calibrateCamera`.
+///
+/// `k1`, `k2` and `k3` are the radial distortion coefficients and `p1` and `p2` are the
+/// tangential distortion coefficients.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Distortion {
+    /// First radial distortion coefficient.
+    pub k1: f32,
+    /// Second radial distortion coefficient.
+    pub k2: f32,
+    /// Third radial distortion coefficient.
+    pub k3: f32,
+    /// First tangential distortion coefficient.
+    pub p1: f32,
+    /// Second tangential distortion coefficient.
+    pub p2: f32,
+}
+
+impl Distortion {
+    /// Applies this distortion to a point in normalised camera coordinates, i.e. with the
+    /// principal point at the origin and unit focal length.
+    pub fn distort(&self, x: f32, y: f32) -> (f32, f32) {
+        let r2 = x * x + y * y;
+        let radial = 1.0 + r2 * (self.k1 + r2 * (self.k2 + r2 * self.k3));
+        (
+            x * radial + 2.0 * self.p1 * x * y + self.p2 * (r2 + 2.0 * x * x),
+            y * radial + self.p1 * (r2 + 2.0 * y * y) + 2.0 * self.p2 * x * y,
+        )
+    }
+}
+
+/// A precomputed mapping from the pixels of an undistorted image to their locations in a
+/// distorted image captured by a camera with known calibration.
+///
+/// Computing the map is relatively expensive, so when undistorting many frames from the same
+/// camera create a single `UndistortMap` and call [`apply`](#method.apply) for each frame.
+#[derive(Clone, Debug)]
+pub struct UndistortMap {
+    width: u32,
+    height: u32,
+    coords: Vec<(f32, f32)>,
+}
+
+impl UndistortMap {
+    /// Computes the map for images of the given size. The undistorted output uses the same
+    /// camera matrix as the distorted input.
+    pub fn new(camera: &CameraMatrix, distortion: &Distortion, width: u32, height: u32) -> Self {
+        let mut coords = Vec::with_capacity(width as usize * height as usize);
+        for v in 0..height {
+            let y = (v as f32 - camera.cy) / camera.fy;
+            for u in 0..width {
+                let x = (u as f32 - camera.cx) / camera.fx;
+                let (xd, yd) = distortion.distort(x, y);
+                coords.push((camera.fx * xd + camera.cx, camera.fy * yd + camera.cy));
+            }
+        }
+        UndistortMap {
+            width,
+            height,
+            coords,
+        }
+    }
+
+    /// The dimensions of the images this map applies to.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// The location in the distorted image of the undistorted pixel `(x, y)`.
+    pub fn source(&self, x: u32, y: u32) -> (f32, f32) {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        self.coords[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Returns coordinate maps suitable for use with [`remap`](fn.remap.html).
+    pub fn to_maps(&self) -> (Image<Luma<f32>>, Image<Luma<f32>>) {
+        let width = self.width as usize;
+        let map_x = ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            Luma([self.coords[y as usize * width + x as usize].0])
+        });
+        let map_y = ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            Luma([self.coords[y as usize * width + x as usize].1])
+        });
+        (map_x, map_y)
+    }
+
+    /// Undistorts an image. Output pixels whose pre-image lies outside the input image
+    /// are set to `default`.
+    ///
+    /// # Panics
+    /// If the dimensions of `image` do not match the dimensions of this map.
+    pub fn apply<P>(&self, image: &Image<P>, interpolation: Interpolation, default: P) -> Image<P>
+    where
+        P: Pixel + Send + Sync + 'static,
+        <P as Pixel>::Subpixel: Send + Sync,
+        <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+    {
+        assert_eq!(
+            image.dimensions(),
+            self.dimensions(),
+            "image dimensions do not match map dimensions"
+        );
+        let width = self.width as usize;
+        let mut out = ImageBuffer::new(self.width, self.height);
+        warp_into_with(
+            image,
+            |x, y| self.coords[y as usize * width + x as usize],
+            interpolation,
+            default,
+            &mut out,
+        );
+        out
+    }
+}
+
+/// Corrects radial and tangential lens distortion in an image captured by a camera
+/// with known calibration.
+///
+/// Output pixels whose pre-image lies outside the input image are set to `default`.
+/// When undistorting many images from the same camera use an
+/// [`UndistortMap`](struct.UndistortMap.html) instead, to avoid recomputing the mapping
+/// for each image.
+///
+/// # Examples
+/// ```
+/// use image::{GrayImage, Luma};
+/// use imageproc::geometric_transformations::*;
+///
+/// let camera = CameraMatrix { fx: 500.0, fy: 500.0, cx: 320.0, cy: 240.0 };
+/// let distortion = Distortion { k1: -0.2, k2: 0.05, ..Default::default() };
+///
+/// let image = GrayImage::new(640, 480);
+/// let undistorted = undistort(&image, &camera, &distortion, Interpolation::Bilinear, Luma([0]));
+/// ```
+pub fn undistort<P>(
+    image: &Image<P>,
+    camera: &CameraMatrix,
+    distortion: &Distortion,
+    interpolation: Interpolation,
+    default: P,
+) -> Image<P>
+where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (width, height) = image.dimensions();
+    UndistortMap::new(camera, distortion, width, height).apply(image, interpolation, default)
+}
+
+/// Applies a projective transformation to an image, using `border` to determine the
+/// values of pixels outside the input image.
+///
+/// The returned image has the same dimensions as `image`. See the
+/// [`warp`](fn.warp.html) documentation for more information. If `border` is
+/// `BorderMode::Transparent` then output pixels whose pre-image lies outside the input
+/// image are set to zero.
+///
+/// # Examples
+/// Rotating a tileable texture so that the result is still tileable.
+/// ```
+/// use image::{GrayImage, Luma};
+/// use imageproc::definitions::BorderMode;
+/// use imageproc::geometric_transformations::{warp_with_border, Interpolation, Projection};
+///
+/// let texture = GrayImage::from_fn(64, 64, |x, y| Luma([((x + 2 * y) % 64) as u8]));
+///
+/// let rotation = Projection::translate(32.0, 32.0)
+///     * Projection::rotate(std::f32::consts::PI / 4.0)
+///     * Projection::translate(-32.0, -32.0);
+///
+/// let rotated = warp_with_border(&texture, &rotation, Interpolation::Bilinear, BorderMode::Wrap);
+/// ```
+pub fn warp_with_border<P, B>(
+    image: &Image<P>,
+    projection: &Projection,
+    interpolation: Interpolation,
+    border: B,
+) -> Image<P>
+where
+    B: Into<BorderMode<P>>,
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(width, height);
+    warp_into_with_border(image, projection, interpolation, border, &mut out);
+    out
+}
+
+/// Applies a projective transformation to an image, writing to a provided output and using
+/// `border` to determine the values of pixels outside the input image.
+///
+/// If `border` is `BorderMode::Transparent` then output pixels whose pre-image lies outside
+/// the input image are left unchanged, so this can be used to draw a transformed image on
+/// top of another. See the [`warp`](fn.warp.html) documentation for more information.
+pub fn warp_into_with_border<P, B>(
+    image: &Image<P>,
+    projection: &Projection,
+    interpolation: Interpolation,
+    border: B,
+    out: &mut Image<P>,
+) where
+    B: Into<BorderMode<P>>,
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let projection = projection.invert();
+    warp_inner_with_border(
+        image,
+        out,
+        |x, y| projection * (x, y),
+        interpolation,
+        border.into(),
+    );
+}
+
+// Samples each output pixel using interpolate_with_border, leaving pixels for which
+// it returns None unchanged.
+fn warp_inner_with_border<P, Fc>(
+    image: &Image<P>,
+    out: &mut Image<P>,
+    mapping: Fc,
+    interpolation: Interpolation,
+    border: BorderMode<P>,
+) where
+    P: Pixel + Send + Sync + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+    Fc: Fn(f32, f32) -> (f32, f32) + Send + Sync,
+{
+    let width = out.width();
+    let raw_out = out.as_mut();
+    let pitch = P::CHANNEL_COUNT as usize * width as usize;
+    if pitch == 0 {
+        return;
+    }
+
+    #[cfg(feature = "rayon")]
+    let chunks = raw_out.par_chunks_mut(pitch);
+    #[cfg(not(feature = "rayon"))]
+    let chunks = raw_out.chunks_mut(pitch);
+
+    chunks.enumerate().for_each(|(y, row)| {
+        for (x, slice) in row.chunks_mut(P::CHANNEL_COUNT as usize).enumerate() {
+            let (px, py) = mapping(x as f32, y as f32);
+            if let Some(p) = interpolate_with_border(image, px, py, interpolation, border) {
+                *P::from_slice_mut(slice) = p;
+            }
+        }
+    });
+}
+
+// Work horse of all warp functions
+// TODO: make faster by avoiding boundary checks in inner section of src image
+fn warp_inner<P, Fc, Fi>(out: &mut Image<P>, mapping: Fc, get_pixel: Fi)
+where
+    P: Pixel + 'static,
+    <P as Pixel>::Subpixel: Send + Sync,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+    Fc: Fn(f32, f32) -> (f32, f32) + Send + Sync,
+    Fi: Fn(f32, f32) -> P + Send + Sync,
+{
+    let width = out.width();
+    let raw_out = out.as_mut();
+    let pitch = P::CHANNEL_COUNT as usize * width as usize;
+
+    #[cfg(feature = "rayon")]
+    let chunks = raw_out.par_chunks_mut(pitch);
+    #[cfg(not(feature = "rayon"))]
+    let chunks = raw_out.chunks_mut(pitch);
+
+    chunks.enumerate().for_each(|(y, row)| {
+        for (x, slice) in row.chunks_mut(P::CHANNEL_COUNT as usize).enumerate() {
+            let (px, py) = mapping(x as f32, y as f32);
+            *P::from_slice_mut(slice) = get_pixel(px, py);
+        }
+    });
+}
+
+// Classifies transformation by looking up transformation matrix coefficients
+fn class_from_matrix(mx: [f32; 9]) -> TransformationClass {
+    if (mx[6] - 0.0).abs() < 1e-10 && (mx[7] - 0.0).abs() < 1e-10 && (mx[8] - 1.0).abs() < 1e-10 {
+        if (mx[0] - 1.0).abs() < 1e-10
+            && (mx[1] - 0.0).abs() < 1e-10
+            && (mx[3] - 0.0).abs() < 1e-10
+            && (mx[4] - 1.0).abs() < 1e-10
+        {
+            TransformationClass::Translation
+        } else {
+            TransformationClass::Affine
+        }
+    } else {
+        TransformationClass::Projection
+    }
+}
+
+fn normalize(mx: [f32; 9]) -> [f32; 9] {
+    [
+        mx[0] / mx[8],
+        mx[1] / mx[8],
+        mx[2] / mx[8],
+        mx[3] / mx[8],
+        mx[4] / mx[8],
+        mx[5] / mx[8],
+        mx[6] / mx[8],
+        mx[7] / mx[8],
+        1.0,
+    ]
+}
+
+// TODO: write me in f64
+fn try_inverse(t: &[f32; 9]) -> Option<[f32; 9]> {
+    let [t00, t01, t02, t10, t11, t12, t20, t21, t22] = t;
+
+    let m00 = t11 * t22 - t12 * t21;
+    let m01 = t10 * t22 - t12 * t20;
+    let m02 = t10 * t21 - t11 * t20;
+
+    let det = t00 * m00 - t01 * m01 + t02 * m02;
+
+    if det.abs() < 1e-10 {
+        return None;
+    }
+
+    let m10 = t01 * t22 - t02 * t21;
+    let m11 = t00 * t22 - t02 * t20;
+    let m12 = t00 * t21 - t01 * t20;
+    let m20 = t01 * t12 - t02 * t11;
+    let m21 = t00 * t12 - t02 * t10;
+    let m22 = t00 * t11 - t01 * t10;
+
+    #[rustfmt::skip]
+    let inv = [
+         m00 / det, -m10 / det,  m20 / det,
+        -m01 / det,  m11 / det, -m21 / det,
+         m02 / det, -m12 / det,  m22 / det,
+    ];
+
+    Some(normalize(inv))
+}
+
+fn mul3x3(a: [f32; 9], b: [f32; 9]) -> [f32; 9] {
+    let [a00, a01, a02, a10, a11, a12, a20, a21, a22] = a;
+    let [b00, b01, b02, b10, b11, b12, b20, b21, b22] = b;
+    [
+        a00 * b00 + a01 * b10 + a02 * b20,
+        a00 * b01 + a01 * b11 + a02 * b21,
+        a00 * b02 + a01 * b12 + a02 * b22,
+        a10 * b00 + a11 * b10 + a12 * b20,
+        a10 * b01 + a11 * b11 + a12 * b21,
+        a10 * b02 + a11 * b12 + a12 * b22,
+        a20 * b00 + a21 * b10 + a22 * b20,
+        a20 * b01 + a21 * b11 + a22 * b21,
+        a20 * b02 + a21 * b12 + a22 * b22,
+    ]
+}
+
+fn blend_cubic<P>(px0: &P, px1: &P, px2: &P, px3: &P, x: f32) -> P
+where
+    P: Pixel,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let mut outp = *px0;
+
+    for i in 0..(P::CHANNEL_COUNT as usize) {
+        let p0 = cast(px0.channels()[i]);
+        let p1 = cast(px1.channels()[i]);
+        let p2 = cast(px2.channels()[i]);
+        let p3 = cast(px3.channels()[i]);
+        #[rustfmt::skip]
+        let pval = p1 + 0.5 * x * (p2 - p0 + x * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3 + x * (3.0 * (p1 - p2) + p3 - p0)));
+        outp.channels_mut()[i] = <P as Pixel>::Subpixel::clamp(pval);
+    }
+
+    outp
+}
+
+fn interpolate_bicubic<P>(image: &Image<P>, x: f32, y: f32, default: P) -> P
+where
+    P: Pixel + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let left = x.floor() - 1f32;
+    let right = left + 4f32;
+    let top = y.floor() - 1f32;
+    let bottom = top + 4f32;
+
+    let x_weight = x - (left + 1f32);
+    let y_weight = y - (top + 1f32);
+
+    let mut col: [P; 4] = [default, default, default, default];
+
+    let (width, height) = image.dimensions();
+    if left < 0f32 || right >= width as f32 || top < 0f32 || bottom >= height as f32 {
+        default
+    } else {
+        for row in top as u32..bottom as u32 {
+            let (p0, p1, p2, p3): (P, P, P, P) = unsafe {
+                (
+                    image.unsafe_get_pixel(left as u32, row),
+                    image.unsafe_get_pixel(left as u32 + 1, row),
+                    image.unsafe_get_pixel(left as u32 + 2, row),
+                    image.unsafe_get_pixel(left as u32 + 3, row),
+                )
+            };
+
+            let c = blend_cubic(&p0, &p1, &p2, &p3, x_weight);
+            col[row as usize - top as usize] = c;
+        }
+
+        blend_cubic(&col[0], &col[1], &col[2], &col[3], y_weight)
+    }
+}
+
+// The Lanczos kernel with a = 3.
+fn lanczos3_kernel(x: f32) -> f32 {
+    if x == 0.0 {
+        1.0
+    } else if x.fract() == 0.0 {
+        // Avoid rounding errors in sin at non-zero integers
+        0.0
+    } else if x.abs() < 3.0 {
+        let px = std::f32::consts::PI * x;
+        3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+    } else {
+        0.0
+    }
+}
+
+fn interpolate_lanczos3<P>(image: &Image<P>, x: f32, y: f32, default: P) -> P
+where
+    P: Pixel + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let left = x.floor() - 2f32;
+    let right = left + 6f32;
+    let top = y.floor() - 2f32;
+    let bottom = top + 6f32;
+
+    let (width, height) = image.dimensions();
+    if left < 0f32 || right > width as f32 || top < 0f32 || bottom > height as f32 {
+        return default;
+    }
+
+    let mut x_weights = [0f32; 6];
+    let mut y_weights = [0f32; 6];
+    for i in 0..6 {
+        x_weights[i] = lanczos3_kernel(x - (left + i as f32));
+        y_weights[i] = lanczos3_kernel(y - (top + i as f32));
+    }
+    let norm = x_weights.iter().sum::<f32>() * y_weights.iter().sum::<f32>();
+
+    let (left, top) = (left as u32, top as u32);
+    let mut out = default;
+    for c in 0..(P::CHANNEL_COUNT as usize) {
+        let mut sum = 0f32;
+        for (dy, wy) in y_weights.iter().enumerate() {
+            for (dx, wx) in x_weights.iter().enumerate() {
+                let p = unsafe { image.unsafe_get_pixel(left + dx as u32, top + dy as u32) };
+                let v: f32 = cast(p.channels()[c]);
+                sum += wx * wy * v;
+            }
+        }
+        out.channels_mut()[c] = <P as Pixel>::Subpixel::clamp(sum / norm);
+    }
+    out
+}
+
+fn blend_bilinear<P>(
+    top_left: P,
+    top_right: P,
+    bottom_left: P,
+    bottom_right: P,
+    right_weight: f32,
+    bottom_weight: f32,
+) -> P
+where
+    P: Pixel,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let top = top_left.map2(&top_right, |u, v| {
+        P::Subpixel::clamp((1f32 - right_weight) * cast(u) + right_weight * cast(v))
+    });
+
+    let bottom = bottom_left.map2(&bottom_right, |u, v| {
+        P::Subpixel::clamp((1f32 - right_weight) * cast(u) + right_weight * cast(v))
+    });
+
+    top.map2(&bottom, |u, v| {
+        P::Subpixel::clamp((1f32 - bottom_weight) * cast(u) + bottom_weight * cast(v))
+    })
+}
+
+fn interpolate_bilinear<P>(image: &Image<P>, x: f32, y: f32, default: P) -> P
+where
+    P: Pixel + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let left = x.floor();
+    let right = left + 1f32;
+    let top = y.floor();
+    let bottom = top + 1f32;
+
+    let right_weight = x - left;
+    let bottom_weight = y - top;
+
+    // default if out of bound
+    let (width, height) = image.dimensions();
+    if left < 0f32 || right >= width as f32 || top < 0f32 || bottom >= height as f32 {
+        default
+    } else {
+        let (tl, tr, bl, br) = unsafe {
+            (
+                image.unsafe_get_pixel(left as u32, top as u32),
+                image.unsafe_get_pixel(right as u32, top as u32),
+                image.unsafe_get_pixel(left as u32, bottom as u32),
+                image.unsafe_get_pixel(right as u32, bottom as u32),
+            )
+        };
+        blend_bilinear(tl, tr, bl, br, right_weight, bottom_weight)
+    }
+}
+
+/// Samples an image at a sub-pixel location using the given interpolation method.
+///
+/// Returns `default` if any of the pixels required to interpolate at (x, y)
+/// lie outside the image.
+///
+/// # Examples
+/// ```
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::Luma;
+/// use imageproc::geometric_transformations::{interpolate, Interpolation};
+///
+/// let image = gray_image!(
+///     10, 20;
+///     30, 40);
+///
+/// let default = Luma([0u8]);
+/// assert_eq!(interpolate(&image, 0.5, 0.5, Interpolation::Bilinear, default), Luma([25u8]));
+/// assert_eq!(interpolate(&image, 0.6, 0.2, Interpolation::Nearest, default), Luma([20u8]));
+/// assert_eq!(interpolate(&image, 1.5, 0.0, Interpolation::Bilinear, default), default);
+/// # }
+/// ```
+pub fn interpolate<P>(
+    image: &Image<P>,
+    x: f32,
+    y: f32,
+    interpolation: Interpolation,
+    default: P,
+) -> P
+where
+    P: Pixel + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    match interpolation {
+        Interpolation::Nearest => interpolate_nearest(image, x, y, default),
+        Interpolation::Bilinear => interpolate_bilinear(image, x, y, default),
+        Interpolation::Bicubic => interpolate_bicubic(image, x, y, default),
+        Interpolation::Lanczos3 => interpolate_lanczos3(image, x, y, default),
+    }
+}
+
+/// Samples an image at a sub-pixel location using the given interpolation method,
+/// using `border` to determine the values of any required pixels outside the image.
+///
+/// Returns `None` if `border` is `BorderMode::Transparent` and any of the pixels
+/// required to interpolate at (x, y) lie outside the image.
+pub fn interpolate_with_border<P, B>(
+    image: &Image<P>,
+    x: f32,
+    y: f32,
+    interpolation: Interpolation,
+    border: B,
+) -> Option<P>
+where
+    B: Into<BorderMode<P>>,
+    P: Pixel + 'static,
+    <P as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let border = border.into();
+    let (width, height) = image.dimensions();
+    let fetch = |px: f32, py: f32| -> Option<P> {
+        let (px, py) = (px as i64, py as i64);
+        match (
+            border.map_coordinate(px, width),
+            border.map_coordinate(py, height),
+        ) {
+            (Some(px), Some(py)) => Some(unsafe { image.unsafe_get_pixel(px, py) }),
+            _ => match border {
+                BorderMode::Constant(p) => Some(p),
+                _ => None,
+            },
+        }
+    };
+
+    match interpolation {
+        Interpolation::Nearest => fetch(x.round(), y.round()),
+        Interpolation::Bilinear => {
+            let (left, top) = (x.floor(), y.floor());
+            Some(blend_bilinear(
+                fetch(left, top)?,
+                fetch(left + 1.0, top)?,
+                fetch(left, top + 1.0)?,
+                fetch(left + 1.0, top + 1.0)?,
+                x - left,
+                y - top,
+            ))
+        }
+        Interpolation::Bicubic => {
+            let (left, top) = (x.floor() - 1.0, y.floor() - 1.0);
+            let mut col = [None; 4];
+            for (i, c) in col.iter_mut().enumerate() {
+                let row = top + i as f32;
+                *c = Some(blend_cubic(
+                    &fetch(left, row)?,
+                    &fetch(left + 1.0, row)?,
+                    &fetch(left + 2.0, row)?,
+                    &fetch(left + 3.0, row)?,
+                    x - (left + 1.0),
+                ));
+            }
+            Some(blend_cubic(
+                &col[0]?,
+                &col[1]?,
+                &col[2]?,
+                &col[3]?,
+                y - (top + 1.0),
+            ))
+        }
+        Interpolation::Lanczos3 => {
+            let (left, top) = (x.floor() - 2.0, y.floor() - 2.0);
+            let mut x_weights = [0f32; 6];
+            let mut y_weights = [0f32; 6];
+            for i in 0..6 {
+                x_weights[i] = lanczos3_kernel(x - (left + i as f32));
+                y_weights[i] = lanczos3_kernel(y - (top + i as f32));
+            }
+            let norm = x_weights.iter().sum::<f32>() * y_weights.iter().sum::<f32>();
+
+            let mut sums = vec![0f32; P::CHANNEL_COUNT as usize];
+            let mut out = None;
+            for (dy, wy) in y_weights.iter().enumerate() {
+                for (dx, wx) in x_weights.iter().enumerate() {
+                    let p = fetch(left + dx as f32, top + dy as f32)?;
+                    for (sum, c) in sums.iter_mut().zip(p.channels()) {
+                        let v: f32 = cast(*c);
+                        *sum += wx * wy * v;
+                    }
+                    out = Some(p);
+                }
+            }
+            let mut out = out?;
+            for (c, sum) in out.channels_mut().iter_mut().zip(&sums) {
+                *c = <P as Pixel>::Subpixel::clamp(sum / norm);
+            }
+            Some(out)
+        }
+    }
+}
+
+#[inline(always)]
+fn interpolate_nearest<P: Pixel + 'static>(image: &Image<P>, x: f32, y: f32, default: P) -> P {
+    let rx = x.round();
+    let ry = y.round();
+
+    let (width, height) = image.dimensions();
+    if rx < 0f32 || rx >= width as f32 || ry < 0f32 || ry >= height as f32 {
+        default
+    } else {
+        unsafe { image.unsafe_get_pixel(rx as u32, ry as u32) }
+    }
+}
+
+/// How to handle pixels whose pre-image lies between input pixels.
+///
+/// New methods may be added in future releases, so matches on this type must include a
+/// wildcard arm.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum Interpolation {
+    /// Choose the nearest pixel to the pre-image of the
+    /// output pixel.
+    Nearest,
+    /// Bilinearly interpolate between the four pixels
+    /// closest to the pre-image of the output pixel.
+    Bilinear,
+    /// Bicubicly interpolate between the four pixels
+    /// closest to the pre-image of the output pixel.
+    Bicubic,
+    /// Interpolate between the 6x6 block of pixels closest to
+    /// the pre-image of the output pixel using a
+    /// [Lanczos](https://en.wikipedia.org/wiki/Lanczos_resampling)
+    /// kernel with a = 3.
+    Lanczos3,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gray_bench_image;
+    use image::{GrayImage, Luma, RgbImage};
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn test_rotate_nearest_zero_radians() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        let rotated = rotate(
+            &image,
+            (0f32, 0f32),
+            0f32,
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        assert_pixels_eq!(rotated, image);
+    }
+
+    #[test]
+    fn text_rotate_nearest_quarter_turn_clockwise() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        let expected = gray_image!(
+            11, 01, 99;
+            12, 02, 99);
+        let c = Projection::translate(1.0, 0.0);
+        let rot = c * Projection::rotate(90f32.to_radians()) * c.invert();
+
+        let rotated = warp(&image, &rot, Interpolation::Nearest, Luma([99u8]));
+        assert_pixels_eq!(rotated, expected);
+    }
+
+    #[test]
+    fn text_rotate_nearest_half_turn_anticlockwise() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        let expected = gray_image!(
+            12, 11, 10;
+            02, 01, 00);
+        let c = Projection::translate(1.0, 0.5);
+
+        let rot = c * Projection::rotate((-180f32).to_radians()) * c.invert();
+
+        let rotated = warp(&image, &rot, Interpolation::Nearest, Luma([99u8]));
+        assert_pixels_eq!(rotated, expected);
+    }
+
+    #[test]
+    fn test_rotate_to_fit_zero_radians() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        let (rotated, offset) = rotate_to_fit(
+            &image,
+            (1.0, 1.0),
+            0.0,
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        assert_pixels_eq!(rotated, image);
+        assert_eq!(offset, (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_rotate_to_fit_quarter_turn() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        let expected = gray_image!(
+            10, 00;
+            11, 01;
+            12, 02);
+
+        let (rotated, offset) = rotate_to_fit(
+            &image,
+            (1.0, 0.5),
+            std::f32::consts::PI / 2.0,
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        assert_pixels_eq!(rotated, expected);
+
+        // The top-left pixel of the input ends up in the top-right of the output
+        let rotation = Projection::translate(1.0, 0.5)
+            * Projection::rotate(std::f32::consts::PI / 2.0)
+            * Projection::translate(-1.0, -0.5);
+        let (x, y) = rotation * (0.0, 0.0);
+        assert!((x + offset.0 - 1.0).abs() < 1e-4);
+        assert!((y + offset.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_rotate_to_fit_does_not_crop() {
+        let image = GrayImage::from_pixel(20, 10, Luma([255u8]));
+        let (rotated, _) = rotate_to_fit(
+            &image,
+            (10.0, 5.0),
+            std::f32::consts::PI / 4.0,
+            Interpolation::Nearest,
+            Luma([0u8]),
+        );
+        // The corner pixel centres of the input span (19 + 9) / sqrt(2) in each direction
+        assert_eq!(rotated.dimensions(), (21, 21));
+        let count = rotated.pixels().filter(|p| p[0] == 255).count();
+        assert!(
+            count >= 190,
+            "expected most input pixels to be kept, got {}",
+            count
+        );
+    }
+
+    #[test]
+    fn test_warp_piecewise_affine_translation() {
+        let image = gray_image!(
+            00, 01, 02, 03, 04;
+            10, 11, 12, 13, 14;
+            20, 21, 22, 23, 24;
+            30, 31, 32, 33, 34);
+
+        let to = [(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (0.0, 3.0)];
+        let from = [(1.0, 0.0), (5.0, 0.0), (5.0, 3.0), (1.0, 3.0)];
+        let triangles = [[0, 1, 2], [0, 2, 3]];
+
+        let expected = gray_image!(
+            01, 02, 03, 04, 99;
+            11, 12, 13, 14, 99;
+            21, 22, 23, 24, 99;
+            31, 32, 33, 34, 99);
+
+        let warped = warp_piecewise_affine(
+            &image,
+            &from,
+            &to,
+            &triangles,
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        assert_pixels_eq!(warped, expected);
+    }
+
+    #[test]
+    fn test_warp_piecewise_affine_outside_mesh() {
+        let image = GrayImage::from_pixel(4, 4, Luma([1u8]));
+        let points = [(0.0, 0.0), (3.0, 0.0), (0.0, 3.0)];
+
+        let expected = gray_image!(
+            1, 1, 1, 1;
+            1, 1, 1, 0;
+            1, 1, 0, 0;
+            1, 0, 0, 0);
+
+        let warped = warp_piecewise_affine(
+            &image,
+            &points,
+            &points,
+            &[[0, 1, 2]],
+            Interpolation::Nearest,
+            Luma([0u8]),
+        );
+        assert_pixels_eq!(warped, expected);
+    }
+
+    #[test]
+    fn test_thin_plate_spline_interpolates_control_points() {
+        let from = [
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (5.0, 5.0),
+        ];
+        let to = [
+            (1.0, 0.0),
+            (10.0, 2.0),
+            (9.0, 11.0),
+            (0.0, 10.0),
+            (7.0, 3.0),
+        ];
+
+        let spline = ThinPlateSpline::new(&from, &to).unwrap();
+        for (p, q) in from.iter().zip(&to) {
+            let (x, y) = spline.evaluate(p.0, p.1);
+            assert_approx_eq!(x, q.0, 1e-3);
+            assert_approx_eq!(y, q.1, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_thin_plate_spline_affine() {
+        // A thin-plate spline through points related by an affine transformation
+        // is that transformation.
+        let p = Projection::translate(3.0, -2.0) * Projection::rotate(0.3);
+        let from = [
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (3.0, 7.0),
+        ];
+        let to: Vec<(f32, f32)> = from.iter().map(|q| p * *q).collect();
+
+        let spline = ThinPlateSpline::new(&from, &to).unwrap();
+        for &pt in &[(2.0, 2.0), (-5.0, 4.0), (20.0, 13.0)] {
+            let (x, y) = spline.evaluate(pt.0, pt.1);
+            assert_approx_eq!(x, (p * pt).0, 1e-3);
+            assert_approx_eq!(y, (p * pt).1, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_thin_plate_spline_invalid() {
+        let colinear = [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)];
+        assert!(ThinPlateSpline::new(&colinear, &colinear).is_none());
+
+        let duplicates = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (0.0, 0.0)];
+        let to = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
+        assert!(ThinPlateSpline::new(&duplicates, &to).is_none());
+    }
+
+    #[test]
+    fn test_warp_thin_plate_spline_translation() {
+        let image = gray_image!(
+            00, 01, 02, 03;
+            10, 11, 12, 13;
+            20, 21, 22, 23);
+
+        let expected = gray_image!(
+            99, 00, 01, 02;
+            99, 10, 11, 12;
+            99, 20, 21, 22);
+
+        let from = [(0.0, 0.0), (3.0, 0.0), (0.0, 2.0), (3.0, 2.0)];
+        let to = [(1.0, 0.0), (4.0, 0.0), (1.0, 2.0), (4.0, 2.0)];
+        let warped =
+            warp_thin_plate_spline(&image, &from, &to, Interpolation::Nearest, Luma([99u8]))
+                .unwrap();
+        assert_pixels_eq!(warped, expected);
+    }
+
+    #[test]
+    fn test_remap() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        // Transpose, and sample one pixel from outside the image
+        let map_x = ImageBuffer::from_raw(2, 3, vec![0.0, 0.0, 1.0, 1.0, 2.0, 5.0]).unwrap();
+        let map_y = ImageBuffer::from_raw(2, 3, vec![0.0, 1.0, 0.0, 1.0, 0.0, 1.0]).unwrap();
+
+        let expected = gray_image!(
+            00, 10;
+            01, 11;
+            02, 99);
+
+        let actual = remap(
+            &image,
+            &map_x,
+            &map_y,
+            Interpolation::Nearest,
+            BorderMode::Constant(Luma([99u8])),
+        );
+        assert_pixels_eq!(actual, expected);
+
+        // A pixel is accepted as a constant border
+        let actual = remap(&image, &map_x, &map_y, Interpolation::Nearest, Luma([99u8]));
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_remap_border_modes() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        // Shift left by one pixel
+        let map_x = ImageBuffer::from_fn(3, 2, |x, _| Luma([x as f32 + 1.0]));
+        let map_y = ImageBuffer::from_fn(3, 2, |_, y| Luma([y as f32]));
+        let remap = |border| remap(&image, &map_x, &map_y, Interpolation::Nearest, border);
+
+        assert_pixels_eq!(
+            remap(BorderMode::Replicate),
+            gray_image!(
+                01, 02, 02;
+                11, 12, 12)
+        );
+        assert_pixels_eq!(
+            remap(BorderMode::Wrap),
+            gray_image!(
+                01, 02, 00;
+                11, 12, 10)
+        );
+
+        let mut out = GrayImage::from_pixel(3, 2, Luma([99]));
+        remap_into(
+            &image,
+            &map_x,
+            &map_y,
+            Interpolation::Nearest,
+            BorderMode::Transparent,
+            &mut out,
+        );
+        assert_pixels_eq!(
+            out,
+            gray_image!(
+                01, 02, 99;
+                11, 12, 99)
+        );
+    }
+
+    #[test]
+    fn test_warp_with_border_mode() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        let reflected = warp_with(
+            &image,
+            |x, y| (x - 1.0, y),
+            Interpolation::Nearest,
+            BorderMode::Reflect,
+        );
+        assert_pixels_eq!(
+            reflected,
+            gray_image!(
+                00, 00, 01;
+                10, 10, 11)
+        );
+
+        let constant = warp_with(
+            &image,
+            |x, y| (x - 1.0, y),
+            Interpolation::Nearest,
+            Luma([9]),
+        );
+        assert_pixels_eq!(
+            constant,
+            gray_image!(
+                09, 00, 01;
+                09, 10, 11)
+        );
+    }
+
+    #[test]
+    fn test_remap_into() {
+        let image = gray_bench_image(20, 16);
+        let map_x = ImageBuffer::from_fn(12, 10, |x, y| Luma([x as f32 * 1.3 + y as f32 * 0.2]));
+        let map_y = ImageBuffer::from_fn(12, 10, |x, y| Luma([y as f32 * 1.1 - x as f32 * 0.3]));
+
+        let mut out = GrayImage::from_pixel(12, 10, Luma([7]));
+        remap_into(
+            &image,
+            &map_x,
+            &map_y,
+            Interpolation::Bilinear,
+            BorderMode::Constant(Luma([0])),
+            &mut out,
+        );
+        assert_pixels_eq!(
+            out,
+            remap(
+                &image,
+                &map_x,
+                &map_y,
+                Interpolation::Bilinear,
+                BorderMode::Constant(Luma([0]))
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_remap_into_rejects_wrong_output_size() {
+        let image = GrayImage::new(4, 4);
+        let map = ImageBuffer::new(3, 3);
+        remap_into(
+            &image,
+            &map,
+            &map,
+            Interpolation::Nearest,
+            BorderMode::Constant(Luma([0])),
+            &mut GrayImage::new(4, 4),
+        );
+    }
+
+    #[test]
+    fn test_try_remap() {
+        let image = gray_bench_image(4, 4);
+        let map_x = ImageBuffer::from_fn(3, 2, |x, _| Luma([x as f32]));
+        let map_y = ImageBuffer::from_fn(3, 2, |_, y| Luma([y as f32]));
+        assert_eq!(
+            try_remap(
+                &image,
+                &map_x,
+                &map_y,
+                Interpolation::Nearest,
+                BorderMode::Constant(Luma([0]))
+            ),
+            Ok(remap(
+                &image,
+                &map_x,
+                &map_y,
+                Interpolation::Nearest,
+                BorderMode::Constant(Luma([0]))
+            ))
+        );
+        assert_eq!(
+            try_remap(
+                &image,
+                &map_x,
+                &ImageBuffer::new(2, 3),
+                Interpolation::Nearest,
+                BorderMode::Constant(Luma([0]))
+            ),
+            Err(Error::DimensionMismatch {
+                expected: (3, 2),
+                actual: (2, 3)
+            })
+        );
+        assert_eq!(
+            try_remap_into(
+                &image,
+                &map_x,
+                &map_y,
+                Interpolation::Nearest,
+                BorderMode::Constant(Luma([0])),
+                &mut GrayImage::new(4, 4)
+            ),
+            Err(Error::DimensionMismatch {
+                expected: (3, 2),
+                actual: (4, 4)
+            })
+        );
+    }
+
+    #[test]
+    fn test_rotate_into() {
+        let image = gray_bench_image(20, 16);
+        let mut out = GrayImage::from_pixel(20, 16, Luma([7]));
+        rotate_into(
+            &image,
+            (8.0, 5.0),
+            0.7,
+            Interpolation::Bilinear,
+            Luma([3]),
+            &mut out,
+        );
+        assert_pixels_eq!(
+            out,
+            rotate(&image, (8.0, 5.0), 0.7, Interpolation::Bilinear, Luma([3]))
+        );
+    }
+
+    #[test]
+    fn test_remap_undistort_maps() {
+        let camera = CameraMatrix {
+            fx: 20.0,
+            fy: 20.0,
+            cx: 10.0,
+            cy: 8.0,
+        };
+        let distortion = Distortion {
+            k1: -0.3,
+            p1: 0.01,
+            ..Default::default()
+        };
+        let image = gray_bench_image(20, 16);
+        let map = UndistortMap::new(&camera, &distortion, 20, 16);
+        let (map_x, map_y) = map.to_maps();
+
+        assert_pixels_eq!(
+            remap(
+                &image,
+                &map_x,
+                &map_y,
+                Interpolation::Bilinear,
+                BorderMode::Constant(Luma([0u8]))
+            ),
+            map.apply(&image, Interpolation::Bilinear, Luma([0u8]))
+        );
+    }
+
+    #[test]
+    fn test_resize_area_non_integer_scale() {
+        let image = gray_image!(0, 30, 60);
+
+        // Each output pixel covers one and a half input pixels
+        let expected = gray_image!(10, 50);
+
+        assert_pixels_eq!(resize_area(&image, 2, 1), expected);
+    }
+
+    #[test]
+    fn test_resize_area_identity() {
+        let image = gray_bench_image(9, 7);
+        assert_pixels_eq!(resize_area(&image, 9, 7), image);
+    }
+
+    #[test]
+    fn test_resize_area_removes_checkerboard() {
+        let image = GrayImage::from_fn(64, 64, |x, y| {
+            Luma([if (x + y) % 2 == 0 { 0 } else { 200 }])
+        });
+        let expected = GrayImage::from_pixel(8, 8, Luma([100]));
+        assert_pixels_eq!(resize_area(&image, 8, 8), expected);
+    }
+
+    #[test]
+    fn test_warp_polar() {
+        let image = gray_image!(
+            00, 01, 02, 03, 04;
+            10, 11, 12, 13, 14;
+            20, 21, 22, 23, 24;
+            30, 31, 32, 33, 34;
+            40, 41, 42, 43, 44);
+
+        // Rows are rays at angles 0, π/2, π and 3π/2 from the centre
+        let expected = gray_image!(
+            22, 23, 24, 99;
+            22, 32, 42, 99;
+            22, 21, 20, 99;
+            22, 12, 02, 99);
+
+        let polar = warp_polar(
+            &image,
+            (2.0, 2.0),
+            4.0,
+            4,
+            4,
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        assert_pixels_eq!(polar, expected);
+    }
+
+    #[test]
+    fn test_warp_log_polar() {
+        let image = GrayImage::from_fn(20, 1, |x, _| Luma([x as u8]));
+
+        // Radius 16 over 4 columns gives radii 1, 2, 4 and 8
+        let log_polar = warp_log_polar(
+            &image,
+            (0.0, 0.0),
+            16.0,
+            4,
+            4,
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        let first_row: Vec<u8> = (0..4).map(|x| log_polar.get_pixel(x, 0)[0]).collect();
+        assert_eq!(first_row, vec![1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_warp_polar_inverse() {
+        // A polar image that depends only on radius gives concentric rings
+        let polar = GrayImage::from_fn(8, 16, |x, _| Luma([10 * x as u8]));
+        let image = warp_polar_inverse(
+            &polar,
+            (3.0, 3.0),
+            8.0,
+            7,
+            7,
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        assert_eq!(image.get_pixel(3, 3)[0], 0);
+        assert_eq!(image.get_pixel(6, 3)[0], 30);
+        assert_eq!(image.get_pixel(3, 0)[0], 30);
+        assert_eq!(image.get_pixel(5, 5)[0], 30);
+        assert_eq!(image.get_pixel(3, 5)[0], 20);
+    }
+
+    #[test]
+    fn test_warp_log_polar_round_trip() {
+        let image = GrayImage::from_fn(41, 41, |x, y| {
+            let r = ((x as f32 - 20.0).powi(2) + (y as f32 - 20.0).powi(2)).sqrt();
+            Luma([(r * 10.0).min(255.0) as u8])
+        });
+        let center = (20.0, 20.0);
+        let log_polar = warp_log_polar(
+            &image,
+            center,
+            20.0,
+            200,
+            360,
+            Interpolation::Bilinear,
+            Luma([0u8]),
+        );
+        let round_trip = warp_log_polar_inverse(
+            &log_polar,
+            center,
+            20.0,
+            41,
+            41,
+            Interpolation::Bilinear,
+            Luma([0u8]),
+        );
+        for &(x, y) in &[(25, 20), (20, 30), (10, 12), (31, 27)] {
+            let diff = image.get_pixel(x, y)[0] as i32 - round_trip.get_pixel(x, y)[0] as i32;
+            assert!(diff.abs() <= 2, "pixel ({}, {}) differs by {}", x, y, diff);
+        }
+    }
+
+    #[test]
+    fn test_flips_and_quarter_turns() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        assert_pixels_eq!(
+            flip_horizontal(&image),
+            gray_image!(
+                02, 01, 00;
+                12, 11, 10)
+        );
+        assert_pixels_eq!(
+            flip_vertical(&image),
+            gray_image!(
+                10, 11, 12;
+                00, 01, 02)
+        );
+        assert_pixels_eq!(
+            rotate180(&image),
+            gray_image!(
+                12, 11, 10;
+                02, 01, 00)
+        );
+        assert_pixels_eq!(
+            transpose(&image),
+            gray_image!(
+                00, 10;
+                01, 11;
+                02, 12)
+        );
+        assert_pixels_eq!(
+            rotate90(&image),
+            gray_image!(
+                10, 00;
+                11, 01;
+                12, 02)
+        );
+        assert_pixels_eq!(
+            rotate270(&image),
+            gray_image!(
+                02, 12;
+                01, 11;
+                00, 10)
+        );
+    }
 
-    // default if out of bound
-    let (width, height) = image.dimensions();
-    if left < 0f32 || right >= width as f32 || top < 0f32 || bottom >= height as f32 {
-        default
-    } else {
-        let (tl, tr, bl, br) = unsafe {
-            (
-                image.unsafe_get_pixel(left as u32, top as u32),
-                image.unsafe_get_pixel(right as u32, top as u32),
-                image.unsafe_get_pixel(left as u32, bottom as u32),
-                image.unsafe_get_pixel(right as u32, bottom as u32),
-            )
-        };
-        blend_bilinear(tl, tr, bl, br, right_weight, bottom_weight)
+    #[test]
+    fn test_quarter_turns_match_rotate_to_fit() {
+        use image::Rgb;
+        let image = RgbImage::from_fn(45, 37, |x, y| Rgb([x as u8, y as u8, (x * y) as u8]));
+        let (expected, _) = rotate_to_fit(
+            &image,
+            (0.0, 0.0),
+            std::f32::consts::PI / 2.0,
+            Interpolation::Nearest,
+            Rgb([0, 0, 0]),
+        );
+        assert_pixels_eq!(rotate90(&image), expected);
+        assert_pixels_eq!(rotate270(&rotate90(&image)), image);
+        assert_pixels_eq!(transpose(&transpose(&image)), image);
+        assert_pixels_eq!(
+            rotate90(&rotate90(&image)),
+            flip_vertical(&flip_horizontal(&image))
+        );
+        assert_pixels_eq!(rotate180(&image), rotate90(&rotate90(&image)));
     }
-}
 
-#[inline(always)]
-fn interpolate_nearest<P: Pixel + 'static>(image: &Image<P>, x: f32, y: f32, default: P) -> P {
-    let rx = x.round();
-    let ry = y.round();
+    #[test]
+    fn test_transpose_into() {
+        let image = gray_bench_image(77, 45);
+        let mut out = GrayImage::from_pixel(45, 77, Luma([3]));
+        transpose_into(&image, &mut out);
+        for (x, y, p) in image.enumerate_pixels() {
+            assert_eq!(out.get_pixel(y, x), p);
+        }
+    }
 
-    let (width, height) = image.dimensions();
-    if rx < 0f32 || rx >= width as f32 || ry < 0f32 || ry >= height as f32 {
-        default
-    } else {
-        unsafe { image.unsafe_get_pixel(rx as u32, ry as u32) }
+    #[test]
+    #[should_panic]
+    fn test_transpose_into_rejects_wrong_output_size() {
+        transpose_into(&GrayImage::new(3, 4), &mut GrayImage::new(3, 4));
     }
-}
 
-/// How to handle pixels whose pre-image lies between input pixels.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-pub enum Interpolation {
-    /// Choose the nearest pixel to the pre-image of the
-    /// output pixel.
-    Nearest,
-    /// Bilinearly interpolate between the four pixels
-    /// closest to the pre-image of the output pixel.
-    Bilinear,
-    /// Bicubicly interpolate between the four pixels
-    /// closest to the pre-image of the output pixel.
-    Bicubic,
-}
+    #[test]
+    fn test_try_transpose_into() {
+        let image = gray_bench_image(5, 3);
+        let mut out = GrayImage::new(3, 5);
+        assert_eq!(try_transpose_into(&image, &mut out), Ok(()));
+        assert_pixels_eq!(out, transpose(&image));
+        assert_eq!(
+            try_transpose_into(&image, &mut GrayImage::new(5, 3)),
+            Err(Error::DimensionMismatch {
+                expected: (3, 5),
+                actual: (5, 3)
+            })
+        );
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::utils::gray_bench_image;
-    use image::{GrayImage, Luma};
-    use test::{black_box, Bencher};
+    #[test]
+    fn test_flips_empty_image() {
+        let image = GrayImage::new(0, 3);
+        assert_eq!(flip_horizontal(&image).dimensions(), (0, 3));
+        assert_eq!(flip_vertical(&image).dimensions(), (0, 3));
+        assert_eq!(rotate90(&image).dimensions(), (3, 0));
+    }
 
     #[test]
-    fn test_rotate_nearest_zero_radians() {
+    fn test_warp_to_size_with_offset() {
         let image = gray_image!(
-            00, 01, 02;
-            10, 11, 12);
+            1, 2;
+            3, 4);
 
-        let rotated = rotate(
+        // Place the image at (1, 2) in a larger canvas
+        let expected = gray_image!(
+            0, 0, 0, 0;
+            0, 0, 0, 0;
+            0, 1, 2, 0;
+            0, 3, 4, 0;
+            0, 0, 0, 0);
+
+        let warped = warp_to_size(
             &image,
-            (0f32, 0f32),
-            0f32,
+            &Projection::translate(1.0, 2.0),
+            4,
+            5,
             Interpolation::Nearest,
-            Luma([99u8]),
+            Luma([0u8]),
         );
-        assert_pixels_eq!(rotated, image);
+        assert_pixels_eq!(warped, expected);
     }
 
     #[test]
-    fn text_rotate_nearest_quarter_turn_clockwise() {
+    fn test_warp_into_with_larger_output() {
         let image = gray_image!(
-            00, 01, 02;
-            10, 11, 12);
+            1, 2;
+            3, 4);
 
         let expected = gray_image!(
-            11, 01, 99;
-            12, 02, 99);
-        let c = Projection::translate(1.0, 0.0);
-        let rot = c * Projection::rotate(90f32.to_radians()) * c.invert();
+            4, 3, 9;
+            2, 1, 9);
 
-        let rotated = warp(&image, &rot, Interpolation::Nearest, Luma([99u8]));
-        assert_pixels_eq!(rotated, expected);
+        let mut out = GrayImage::new(3, 2);
+        warp_into_with(
+            &image,
+            |x, y| (1.0 - x, 1.0 - y),
+            Interpolation::Nearest,
+            Luma([9u8]),
+            &mut out,
+        );
+        assert_pixels_eq!(out, expected);
     }
 
     #[test]
-    fn text_rotate_nearest_half_turn_anticlockwise() {
+    fn test_warp_with_border_modes() {
         let image = gray_image!(
-            00, 01, 02;
-            10, 11, 12);
+            1, 2, 3;
+            4, 5, 6);
+        let t = Projection::translate(-2.0, 1.0);
+
+        let warp = |border| warp_with_border(&image, &t, Interpolation::Nearest, border);
+
+        assert_pixels_eq!(
+            warp(BorderMode::Constant(Luma([9]))),
+            gray_image!(
+                9, 9, 9;
+                3, 9, 9)
+        );
+        assert_pixels_eq!(
+            warp(BorderMode::Replicate),
+            gray_image!(
+                3, 3, 3;
+                3, 3, 3)
+        );
+        assert_pixels_eq!(
+            warp(BorderMode::Reflect),
+            gray_image!(
+                3, 3, 2;
+                3, 3, 2)
+        );
+        assert_pixels_eq!(
+            warp(BorderMode::Wrap),
+            gray_image!(
+                6, 4, 5;
+                3, 1, 2)
+        );
+    }
+
+    #[test]
+    fn test_warp_into_with_border_transparent() {
+        let image = gray_image!(
+            1, 2;
+            3, 4);
+        let mut out = GrayImage::from_pixel(3, 3, Luma([7]));
+
+        warp_into_with_border(
+            &image,
+            &Projection::translate(1.0, 1.0),
+            Interpolation::Nearest,
+            BorderMode::Transparent,
+            &mut out,
+        );
 
         let expected = gray_image!(
-            12, 11, 10;
-            02, 01, 00);
-        let c = Projection::translate(1.0, 0.5);
+            7, 7, 7;
+            7, 1, 2;
+            7, 3, 4);
+        assert_pixels_eq!(out, expected);
+    }
 
-        let rot = c * Projection::rotate((-180f32).to_radians()) * c.invert();
+    #[test]
+    fn test_interpolate_with_border_matches_interpolate() {
+        let image = gray_bench_image(20, 20);
+        let default = Luma([0u8]);
+        for &interpolation in &[
+            Interpolation::Nearest,
+            Interpolation::Bilinear,
+            Interpolation::Bicubic,
+            Interpolation::Lanczos3,
+        ] {
+            for &(x, y) in &[(5.3, 7.8), (10.0, 10.0), (12.5, 3.25)] {
+                assert_eq!(
+                    interpolate_with_border(&image, x, y, interpolation, BorderMode::Transparent),
+                    Some(interpolate(&image, x, y, interpolation, default))
+                );
+            }
+        }
+    }
 
-        let rotated = warp(&image, &rot, Interpolation::Nearest, Luma([99u8]));
-        assert_pixels_eq!(rotated, expected);
+    #[test]
+    fn test_interpolate_with_border_wrap_bilinear() {
+        let image = gray_image!(10, 20, 30);
+        let sample =
+            interpolate_with_border(&image, 2.5, 0.0, Interpolation::Bilinear, BorderMode::Wrap);
+        assert_eq!(sample, Some(Luma([20])));
+    }
+
+    #[test]
+    fn test_undistort_zero_distortion() {
+        let image = gray_bench_image(20, 15);
+        let camera = CameraMatrix {
+            fx: 10.0,
+            fy: 12.0,
+            cx: 9.5,
+            cy: 7.0,
+        };
+        let undistorted = undistort(
+            &image,
+            &camera,
+            &Distortion::default(),
+            Interpolation::Nearest,
+            Luma([0u8]),
+        );
+        assert_pixels_eq!(undistorted, image);
+    }
+
+    #[test]
+    fn test_distort() {
+        let radial = Distortion {
+            k1: 0.1,
+            k2: 0.01,
+            k3: 0.001,
+            ..Default::default()
+        };
+        // r^2 = 2, so the radial factor is 1 + 0.2 + 0.04 + 0.008
+        let (x, y) = radial.distort(1.0, 1.0);
+        assert_approx_eq!(x, 1.248, 1e-6);
+        assert_approx_eq!(y, 1.248, 1e-6);
+
+        let tangential = Distortion {
+            p1: 0.1,
+            p2: 0.2,
+            ..Default::default()
+        };
+        let (x, y) = tangential.distort(1.0, 0.0);
+        assert_approx_eq!(x, 1.6, 1e-6);
+        assert_approx_eq!(y, 0.1, 1e-6);
+    }
+
+    #[test]
+    fn test_undistort_map() {
+        let camera = CameraMatrix {
+            fx: 4.0,
+            fy: 4.0,
+            cx: 2.0,
+            cy: 2.0,
+        };
+        let distortion = Distortion {
+            k1: 0.5,
+            ..Default::default()
+        };
+        let map = UndistortMap::new(&camera, &distortion, 5, 5);
+
+        // The principal point is fixed, and other points are pushed outwards
+        assert_eq!(map.source(2, 2), (2.0, 2.0));
+        let (x, y) = map.source(4, 2);
+        assert_approx_eq!(x, 4.25, 1e-6);
+        assert_approx_eq!(y, 2.0, 1e-6);
+
+        let image = gray_image!(
+            1, 1, 1, 1, 1;
+            1, 1, 1, 1, 1;
+            1, 1, 9, 1, 1;
+            1, 1, 1, 1, 1;
+            1, 1, 1, 1, 1);
+        let undistorted = map.apply(&image, Interpolation::Nearest, Luma([0u8]));
+        assert_eq!(undistorted.get_pixel(2, 2)[0], 9);
+        assert_eq!(undistorted.get_pixel(0, 0)[0], 0);
+    }
+
+    #[bench]
+    fn bench_remap(b: &mut Bencher) {
+        let image = gray_bench_image(500, 500);
+        let map_x =
+            ImageBuffer::from_fn(500, 500, |x, y| Luma([x as f32 + (y as f32 / 30.0).sin()]));
+        let map_y = ImageBuffer::from_fn(500, 500, |_, y| Luma([y as f32]));
+        b.iter(|| {
+            let remapped = remap(
+                &image,
+                &map_x,
+                &map_y,
+                Interpolation::Bilinear,
+                BorderMode::Constant(Luma([0u8])),
+            );
+            black_box(remapped);
+        });
+    }
+
+    #[bench]
+    fn bench_resize_area(b: &mut Bencher) {
+        let image = gray_bench_image(500, 500);
+        b.iter(|| {
+            let resized = resize_area(&image, 123, 77);
+            black_box(resized);
+        });
+    }
+
+    #[bench]
+    fn bench_rotate90(b: &mut Bencher) {
+        let image = gray_bench_image(500, 500);
+        b.iter(|| {
+            let rotated = rotate90(&image);
+            black_box(rotated);
+        });
+    }
+
+    #[bench]
+    fn bench_transpose_rgb(b: &mut Bencher) {
+        let image = RgbImage::from_pixel(500, 500, image::Rgb([1, 2, 3]));
+        b.iter(|| {
+            let transposed = transpose(&image);
+            black_box(transposed);
+        });
     }
 
     #[bench]
@@ -824,6 +3266,17 @@ mod tests {
         });
     }
 
+    #[bench]
+    fn bench_rotate_lanczos3(b: &mut Bencher) {
+        let image = GrayImage::from_pixel(200, 200, Luma([15u8]));
+        let c = Projection::translate(3.0, 3.0);
+        let rot = c * Projection::rotate(1f32.to_degrees()) * c.invert();
+        b.iter(|| {
+            let rotated = warp(&image, &rot, Interpolation::Lanczos3, Luma([98u8]));
+            black_box(rotated);
+        });
+    }
+
     #[test]
     fn test_translate_positive_x_positive_y() {
         let image = gray_image!(
@@ -973,6 +3426,94 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_interpolate_lanczos3_at_pixel_centres() {
+        let image = gray_bench_image(8, 8);
+        for y in 2..5 {
+            for x in 2..5 {
+                let actual = interpolate(
+                    &image,
+                    x as f32,
+                    y as f32,
+                    Interpolation::Lanczos3,
+                    Luma([0u8]),
+                );
+                assert_eq!(actual, *image.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpolate_lanczos3() {
+        let image = GrayImage::from_fn(6, 6, |x, _| Luma([if x < 3 { 0u8 } else { 200u8 }]));
+        let default = Luma([7u8]);
+
+        // Symmetric about the edge
+        assert_eq!(
+            interpolate(&image, 2.5, 2.5, Interpolation::Lanczos3, default),
+            Luma([100u8])
+        );
+        // Requires pixels outside the image
+        assert_eq!(
+            interpolate(&image, 1.5, 2.5, Interpolation::Lanczos3, default),
+            default
+        );
+    }
+
+    #[test]
+    fn test_warp_lanczos3_translation() {
+        let image = gray_bench_image(10, 10);
+        let translated = warp(
+            &image,
+            &Projection::translate(1.0, 0.0),
+            Interpolation::Lanczos3,
+            Luma([0u8]),
+        );
+        for y in 2..7 {
+            for x in 3..8 {
+                assert_eq!(translated.get_pixel(x, y), image.get_pixel(x - 1, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_warp_with_flip() {
+        let image = gray_image!(
+            00, 01, 02;
+            10, 11, 12);
+
+        let expected = gray_image!(
+            02, 01, 00;
+            12, 11, 10);
+
+        let flipped = warp_with(
+            &image,
+            |x, y| (2.0 - x, y),
+            Interpolation::Nearest,
+            Luma([99u8]),
+        );
+        assert_pixels_eq!(flipped, expected);
+    }
+
+    #[test]
+    fn test_warp_with_bilinear_sub_pixel() {
+        let image = gray_image!(
+            00, 10, 20;
+            40, 50, 60);
+
+        let expected = gray_image!(
+            25, 35, 99;
+            99, 99, 99);
+
+        let warped = warp_with(
+            &image,
+            |x, y| (x + 0.5, y + 0.5),
+            Interpolation::Bilinear,
+            Luma([99u8]),
+        );
+        assert_pixels_eq!(warped, expected);
+    }
+
     #[bench]
     fn bench_translate_with(b: &mut Bencher) {
         let image = gray_bench_image(500, 500);
@@ -1016,6 +3557,32 @@ mod tests {
         assert_pixels_eq!(translated_bilinear, expected);
     }
 
+    #[test]
+    fn test_projective() {
+        let image = gray_image!(
+            00, 01, 02, 03;
+            10, 11, 12, 13;
+            20, 21, 22, 23;
+            30, 31, 32, 33);
+
+        // Maps (x, y) to (x, y) / (0.25 * x + 1)
+        #[rustfmt::skip]
+        let proj = Projection::from_matrix([
+            1.0,  0.0, 0.0,
+            0.0,  1.0, 0.0,
+            0.25, 0.0, 1.0
+        ]).unwrap();
+
+        let expected = gray_image!(
+            00, 01, 99, 99;
+            10, 11, 99, 99;
+            20, 31, 99, 99;
+            30, 99, 99, 99);
+
+        let warped = warp(&image, &proj, Interpolation::Nearest, Luma([99u8]));
+        assert_pixels_eq!(warped, expected);
+    }
+
     #[test]
     fn test_affine_bicubic() {
         let image = gray_image!(
@@ -1172,6 +3739,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_affine_from_control_points_exact() {
+        let t = Projection::translate(10f32, 10f32);
+        let p = t * Projection::rotate(30f32.to_radians()) * Projection::scale(2.0, 0.5);
+
+        let from = [(0f32, 0.0), (50.0, 50.0), (50.0, 0.0)];
+        let to = [p * from[0], p * from[1], p * from[2]];
+
+        let p_est = Projection::affine_from_control_points(&from, &to).unwrap();
+
+        for i in 0..50 {
+            for j in 0..50 {
+                let pt = (i as f32, j as f32);
+                assert_approx_eq!((p * pt).0, (p_est * pt).0, 1e-3);
+                assert_approx_eq!((p * pt).1, (p_est * pt).1, 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_affine_from_control_points_least_squares() {
+        // Each point is translated by (3, 4) plus some symmetric noise, so the
+        // best fit is the translation itself.
+        let from = [(0f32, 0.0), (10.0, 0.0), (0.0, 10.0), (10.0, 10.0)];
+        let to = [(3.5f32, 4.0), (12.5, 4.0), (2.5, 14.0), (13.5, 14.0)];
+
+        let p = Projection::affine_from_control_points(&from, &to).unwrap();
+        let (x, y) = p * (5.0, 5.0);
+        assert_approx_eq!(x, 8.0, 1e-4);
+        assert_approx_eq!(y, 9.0, 1e-4);
+    }
+
+    #[test]
+    fn test_affine_from_control_points_invalid() {
+        let colinear = [(0f32, 0.0), (1.0, 1.0), (2.0, 2.0)];
+        let to = [(0f32, 0.0), (1.0, 0.0), (0.0, 1.0)];
+        assert!(Projection::affine_from_control_points(&colinear, &to).is_none());
+
+        let too_few = [(0f32, 0.0), (1.0, 0.0)];
+        assert!(Projection::affine_from_control_points(&too_few, &too_few).is_none());
+
+        assert!(Projection::affine_from_control_points(&to, &too_few).is_none());
+    }
+
+    #[test]
+    fn test_from_control_points_deskew() {
+        let quad = [(12f32, 20.0), (80.0, 10.0), (90.0, 75.0), (5.0, 90.0)];
+        let rect = [(0f32, 0.0), (59.0, 0.0), (59.0, 39.0), (0.0, 39.0)];
+
+        let p = Projection::from_control_points(quad, rect).unwrap();
+        for i in 0..4 {
+            let (x, y) = p * quad[i];
+            assert_approx_eq!(x, rect[i].0, 1e-2);
+            assert_approx_eq!(y, rect[i].1, 1e-2);
+        }
+
+        // The pre-image of every output pixel lies inside the quadrilateral, and so inside the image
+        let image = GrayImage::from_pixel(100, 100, Luma([255u8]));
+        let mut deskewed = GrayImage::new(60, 40);
+        warp_into(
+            &image,
+            &p,
+            Interpolation::Nearest,
+            Luma([0u8]),
+            &mut deskewed,
+        );
+        assert!(deskewed.pixels().all(|p| p[0] == 255));
+    }
+
     #[test]
     fn test_from_control_points_colinear() {
         let from = [(0f32, 0.0), (50.0, 50.0), (50.0, 0.0), (0.0, 50.0)];
@@ -228,6 +228,89 @@ where
     stack
 }
 
+/// Computes the [Delaunay triangulation] of a set of points, using the Bowyer–Watson algorithm.
+///
+/// Returns the triangles as triples of indices into `points`. Each triangle is listed with a
+/// consistent winding order, and no point lies strictly inside the circumcircle of any
+/// triangle. Duplicate points are ignored, and if all points are collinear then no triangles
+/// are returned.
+///
+/// [Delaunay triangulation]: https://en.wikipedia.org/wiki/Delaunay_triangulation
+pub fn delaunay_triangulation<T>(points: &[Point<T>]) -> Vec<[usize; 3]>
+where
+    T: NumCast + Copy,
+{
+    let n = points.len();
+    if n < 3 {
+        return Vec::new();
+    }
+
+    let mut pts: Vec<(f64, f64)> = points
+        .iter()
+        .map(|p| {
+            (
+                cast(p.x).expect("point coordinate out of range"),
+                cast(p.y).expect("point coordinate out of range"),
+            )
+        })
+        .collect();
+
+    // Add the vertices of a triangle containing all the points
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for &(x, y) in &pts {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let size = (max_x - min_x).max(max_y - min_y).max(1.0) * 1000.0;
+    let (mid_x, mid_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    pts.push((mid_x - size, mid_y - size));
+    pts.push((mid_x + size, mid_y - size));
+    pts.push((mid_x, mid_y + size));
+
+    let mut triangles = vec![[n, n + 1, n + 2]];
+    let mut edges = Vec::new();
+
+    for i in 0..n {
+        if pts[..i].contains(&pts[i]) {
+            continue;
+        }
+
+        // Remove all triangles whose circumcircle contains the new point, and
+        // connect the new point to the boundary of the resulting cavity
+        edges.clear();
+        triangles.retain(|t: &[usize; 3]| {
+            if in_circumcircle(&pts, t, pts[i]) {
+                edges.extend_from_slice(&[(t[0], t[1]), (t[1], t[2]), (t[2], t[0])]);
+                false
+            } else {
+                true
+            }
+        });
+        for &(a, b) in &edges {
+            if !edges.contains(&(b, a)) {
+                triangles.push([a, b, i]);
+            }
+        }
+    }
+
+    triangles.retain(|t| t.iter().all(|&v| v < n));
+    triangles
+}
+
+// Returns true if p lies strictly inside the circumcircle of the
+// counter-clockwise triangle t.
+fn in_circumcircle(pts: &[(f64, f64)], t: &[usize; 3], p: (f64, f64)) -> bool {
+    let (ax, ay) = (pts[t[0]].0 - p.0, pts[t[0]].1 - p.1);
+    let (bx, by) = (pts[t[1]].0 - p.0, pts[t[1]].1 - p.1);
+    let (cx, cy) = (pts[t[2]].0 - p.0, pts[t[2]].1 - p.1);
+    let det = (ax * ax + ay * ay) * (bx * cy - cx * by) - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay);
+    det > 0.0
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Orientation {
     Collinear,
@@ -360,4 +443,86 @@ mod tests {
             ]
         )
     }
+
+    fn triangle_area(points: &[Point<f64>], t: &[usize; 3]) -> f64 {
+        let (a, b, c) = (points[t[0]], points[t[1]], points[t[2]]);
+        ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)) / 2.0
+    }
+
+    #[test]
+    fn test_delaunay_triangulation_square_with_centre() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 0.0),
+            Point::new(2.0, 2.0),
+            Point::new(0.0, 2.0),
+            Point::new(1.0, 1.0),
+        ];
+        let triangles = delaunay_triangulation(&points);
+        assert_eq!(triangles.len(), 4);
+        for t in &triangles {
+            assert!(t.contains(&4));
+            assert_eq!(triangle_area(&points, t), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_delaunay_triangulation_is_delaunay() {
+        let points: Vec<Point<f64>> = (0..30)
+            .map(|i| {
+                let i = i as f64;
+                Point::new((i * 7.3) % 11.0, (i * 3.7) % 13.0)
+            })
+            .collect();
+        let triangles = delaunay_triangulation(&points);
+
+        let pts: Vec<(f64, f64)> = points.iter().map(|p| (p.x, p.y)).collect();
+        for t in &triangles {
+            assert!(triangle_area(&points, t) > 0.0);
+            for p in &pts {
+                assert!(!in_circumcircle(&pts, t, *p));
+            }
+        }
+
+        // The triangles exactly cover the convex hull
+        let hull = convex_hull(
+            &points
+                .iter()
+                .map(|p| Point::new((p.x * 10.0).round() as i32, (p.y * 10.0).round() as i32))
+                .collect::<Vec<_>>(),
+        );
+        let hull: Vec<Point<f64>> = hull
+            .iter()
+            .map(|p| Point::new(p.x as f64 / 10.0, p.y as f64 / 10.0))
+            .collect();
+        let hull_area: f64 = (0..hull.len())
+            .map(|i| {
+                let (p, q) = (hull[i], hull[(i + 1) % hull.len()]);
+                p.x * q.y - q.x * p.y
+            })
+            .sum::<f64>()
+            .abs()
+            / 2.0;
+        let total_area: f64 = triangles.iter().map(|t| triangle_area(&points, t)).sum();
+        assert!((hull_area - total_area).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_delaunay_triangulation_degenerate() {
+        let collinear = [
+            Point::new(0, 0),
+            Point::new(1, 1),
+            Point::new(2, 2),
+            Point::new(3, 3),
+        ];
+        assert!(delaunay_triangulation(&collinear).is_empty());
+
+        let duplicates = [
+            Point::new(0, 0),
+            Point::new(1, 0),
+            Point::new(0, 0),
+            Point::new(0, 1),
+        ];
+        assert_eq!(delaunay_triangulation(&duplicates), vec![[0, 1, 3]]);
+    }
 }
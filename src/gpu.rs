@@ -0,0 +1,465 @@
+//! Compute shader implementations of large correlations, used by the linear filters and
+//! template matching when the `gpu` feature is enabled.
+//!
+//! Correlations are computed at `f32` by a compute shader run via
+//! [wgpu](https://github.com/gfx-rs/wgpu) on the platform's default adapter, which is
+//! initialised on first use. Each function returns `None` without doing any work if GPU
+//! support is not compiled in, if no adapter supporting compute shaders is available, if
+//! the kernel is too small for the GPU to be worth using, or if the inputs exceed the
+//! adapter's limits, in which case the caller should fall back to its CPU implementation.
+//!
+//! Products are accumulated in the same order as in the CPU implementations, but results
+//! are not guaranteed to be bit-identical to them as GPUs may fuse multiplies and adds.
+
+/// Kernels with fewer entries than this are applied on the CPU, as the cost of copying
+/// images to and from the GPU outweighs the cost of filtering them.
+pub(crate) const MIN_KERNEL_LEN: usize = 49;
+
+/// Returns true if GPU support is compiled in and a suitable adapter is available.
+pub(crate) fn is_available() -> bool {
+    imp::is_available()
+}
+
+/// Correlates an image with `channels` interleaved channels with a row-major kernel,
+/// padding by continuity. The kernel is centred on `(kernel_width / 2, kernel_height / 2)`,
+/// as for [`Kernel::filter`](../filter/struct.Kernel.html#method.filter).
+pub(crate) fn filter_f32(
+    image: &[f32],
+    width: u32,
+    height: u32,
+    channels: u32,
+    kernel: &[f32],
+    kernel_width: u32,
+    kernel_height: u32,
+) -> Option<Vec<f32>> {
+    if kernel.len() < MIN_KERNEL_LEN {
+        return None;
+    }
+    let params = Params {
+        width,
+        height,
+        channels,
+        kernel_width,
+        kernel_height,
+        out_width: width,
+        out_height: height,
+        offset_x: kernel_width / 2,
+        offset_y: kernel_height / 2,
+    };
+    imp::correlate(&params, image, kernel)
+}
+
+/// Correlates a single channel image with a row-major template at each position where
+/// the template lies entirely within the image. The result has dimensions
+/// `width - template_width + 1` by `height - template_height + 1`.
+pub(crate) fn template_correlation_f32(
+    image: &[f32],
+    width: u32,
+    height: u32,
+    template: &[f32],
+    template_width: u32,
+    template_height: u32,
+) -> Option<Vec<f32>> {
+    if template.len() < MIN_KERNEL_LEN || template_width > width || template_height > height {
+        return None;
+    }
+    let params = Params {
+        width,
+        height,
+        channels: 1,
+        kernel_width: template_width,
+        kernel_height: template_height,
+        out_width: width - template_width + 1,
+        out_height: height - template_height + 1,
+        offset_x: 0,
+        offset_y: 0,
+    };
+    imp::correlate(&params, image, template)
+}
+
+// Output pixel (x, y) is the sum over kernel positions (kx, ky) of
+// kernel(kx, ky) * image(x + kx - offset_x, y + ky - offset_y), with coordinates clamped
+// to the image.
+#[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+struct Params {
+    width: u32,
+    height: u32,
+    channels: u32,
+    kernel_width: u32,
+    kernel_height: u32,
+    out_width: u32,
+    out_height: u32,
+    offset_x: u32,
+    offset_y: u32,
+}
+
+#[cfg(not(feature = "gpu"))]
+mod imp {
+    use super::Params;
+
+    pub(super) fn is_available() -> bool {
+        false
+    }
+
+    pub(super) fn correlate(_: &Params, _: &[f32], _: &[f32]) -> Option<Vec<f32>> {
+        None
+    }
+}
+
+#[cfg(feature = "gpu")]
+mod imp {
+    use super::Params;
+    use std::sync::mpsc::channel;
+    use std::sync::{Mutex, OnceLock};
+    use wgpu::util::DeviceExt;
+
+    const WORKGROUP_SIZE: u32 = 8;
+
+    const SHADER: &str = r#"
+struct Params {
+    width: u32,
+    height: u32,
+    channels: u32,
+    kernel_width: u32,
+    kernel_height: u32,
+    out_width: u32,
+    out_height: u32,
+    offset_x: u32,
+    offset_y: u32,
+    pad0: u32,
+    pad1: u32,
+    pad2: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> pixels: array<f32>;
+@group(0) @binding(2) var<storage, read> weights: array<f32>;
+@group(0) @binding(3) var<storage, read_write> sums: array<f32>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.out_width || id.y >= params.out_height) {
+        return;
+    }
+    let last_x = i32(params.width) - 1;
+    let last_y = i32(params.height) - 1;
+    for (var c = 0u; c < params.channels; c += 1u) {
+        var acc = 0.0;
+        for (var ky = 0u; ky < params.kernel_height; ky += 1u) {
+            let y = u32(clamp(i32(id.y + ky) - i32(params.offset_y), 0, last_y));
+            for (var kx = 0u; kx < params.kernel_width; kx += 1u) {
+                let x = u32(clamp(i32(id.x + kx) - i32(params.offset_x), 0, last_x));
+                let pixel = pixels[(y * params.width + x) * params.channels + c];
+                acc += pixel * weights[ky * params.kernel_width + kx];
+            }
+        }
+        sums[(id.y * params.out_width + id.x) * params.channels + c] = acc;
+    }
+}
+"#;
+
+    struct Context {
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        pipeline: wgpu::ComputePipeline,
+    }
+
+    impl Context {
+        async fn new() -> Option<Context> {
+            let instance = wgpu::Instance::default();
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    ..Default::default()
+                })
+                .await?;
+            let flags = adapter.get_downlevel_capabilities().flags;
+            if !flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS) {
+                return None;
+            }
+            let (device, queue) = adapter
+                .request_device(
+                    &wgpu::DeviceDescriptor {
+                        label: None,
+                        required_features: wgpu::Features::empty(),
+                        required_limits: adapter.limits(),
+                    },
+                    None,
+                )
+                .await
+                .ok()?;
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+            let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &module,
+                entry_point: "main",
+            });
+            Some(Context {
+                device,
+                queue,
+                pipeline,
+            })
+        }
+    }
+
+    // The device is shared by all threads, and error scopes apply to the whole device, so
+    // correlations are run one at a time.
+    fn context() -> Option<&'static Mutex<Context>> {
+        static CONTEXT: OnceLock<Option<Mutex<Context>>> = OnceLock::new();
+        CONTEXT
+            .get_or_init(|| pollster::block_on(Context::new()).map(Mutex::new))
+            .as_ref()
+    }
+
+    pub(super) fn is_available() -> bool {
+        context().is_some()
+    }
+
+    pub(super) fn correlate(params: &Params, image: &[f32], kernel: &[f32]) -> Option<Vec<f32>> {
+        let context = context()?.lock().ok()?;
+        let Context {
+            device,
+            queue,
+            pipeline,
+        } = &*context;
+
+        let limits = device.limits();
+        let max_len =
+            (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size) / 4;
+        let out_len = params.out_width as u64 * params.out_height as u64 * params.channels as u64;
+        let lengths = [image.len() as u64, kernel.len() as u64, out_len];
+        if lengths.iter().any(|&len| len == 0 || len > max_len) {
+            return None;
+        }
+        let groups_x = params.out_width.div_ceil(WORKGROUP_SIZE);
+        let groups_y = params.out_height.div_ceil(WORKGROUP_SIZE);
+        if groups_x.max(groups_y) > limits.max_compute_workgroups_per_dimension {
+            return None;
+        }
+
+        device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let uniform = [
+            params.width,
+            params.height,
+            params.channels,
+            params.kernel_width,
+            params.kernel_height,
+            params.out_width,
+            params.out_height,
+            params.offset_x,
+            params.offset_y,
+            0,
+            0,
+            0,
+        ];
+        let uniform: Vec<u8> = uniform.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        let init_buffer = |contents: &[u8], usage| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents,
+                usage,
+            })
+        };
+        let params = init_buffer(&uniform, wgpu::BufferUsages::UNIFORM);
+        let pixels = init_buffer(&to_bytes(image), wgpu::BufferUsages::STORAGE);
+        let weights = init_buffer(&to_bytes(kernel), wgpu::BufferUsages::STORAGE);
+        let size = out_len * 4;
+        let sums = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: pixels.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: weights.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: sums.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups_x, groups_y, 1);
+        }
+        encoder.copy_buffer_to_buffer(&sums, 0, &staging, 0, size);
+        queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = channel();
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+
+        let validation_error = pollster::block_on(device.pop_error_scope());
+        let out_of_memory_error = pollster::block_on(device.pop_error_scope());
+        if validation_error.is_some() || out_of_memory_error.is_some() {
+            return None;
+        }
+        receiver.recv().ok()?.ok()?;
+
+        let result = slice
+            .get_mapped_range()
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        staging.unmap();
+        Some(result)
+    }
+
+    fn to_bytes(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_ne_bytes()).collect()
+    }
+}
+
+#[cfg(all(test, feature = "gpu"))]
+mod tests {
+    use super::*;
+
+    // Returns the correlation computed on the CPU, in the same order as the shader.
+    fn correlate_reference(params: &Params, image: &[f32], kernel: &[f32]) -> Vec<f32> {
+        let clamp = |v: i64, size: u32| v.max(0).min(size as i64 - 1) as usize;
+        let mut out = Vec::new();
+        for y in 0..params.out_height as i64 {
+            for x in 0..params.out_width as i64 {
+                for c in 0..params.channels as usize {
+                    let mut acc = 0f32;
+                    for ky in 0..params.kernel_height as i64 {
+                        let y_p = clamp(y + ky - params.offset_y as i64, params.height);
+                        for kx in 0..params.kernel_width as i64 {
+                            let x_p = clamp(x + kx - params.offset_x as i64, params.width);
+                            let pixel = image[(y_p * params.width as usize + x_p)
+                                * params.channels as usize
+                                + c];
+                            acc += pixel * kernel[(ky * params.kernel_width as i64 + kx) as usize];
+                        }
+                    }
+                    out.push(acc);
+                }
+            }
+        }
+        out
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= 1e-4 * e.abs().max(1.0), "{} != {}", a, e);
+        }
+    }
+
+    fn test_image(width: u32, height: u32, channels: u32) -> Vec<f32> {
+        (0..width * height * channels)
+            .map(|i| ((i * 37 + i / 5) % 256) as f32)
+            .collect()
+    }
+
+    // Run with `cargo test --features gpu -- --ignored` on a machine with a GPU adapter.
+    #[test]
+    #[ignore = "requires a GPU adapter"]
+    fn test_filter_f32_matches_reference() {
+        assert!(is_available(), "no GPU adapter is available");
+        let (width, height, channels) = (23, 17, 3);
+        let image = test_image(width, height, channels);
+        for &(kernel_width, kernel_height) in &[(7, 7), (8, 9), (49, 1), (1, 50), (30, 30)] {
+            let kernel: Vec<f32> = (0..kernel_width * kernel_height)
+                .map(|i| ((i % 11) as f32 - 5.0) / 10.0)
+                .collect();
+            let actual = filter_f32(
+                &image,
+                width,
+                height,
+                channels,
+                &kernel,
+                kernel_width,
+                kernel_height,
+            )
+            .unwrap();
+            let params = Params {
+                width,
+                height,
+                channels,
+                kernel_width,
+                kernel_height,
+                out_width: width,
+                out_height: height,
+                offset_x: kernel_width / 2,
+                offset_y: kernel_height / 2,
+            };
+            assert_close(&actual, &correlate_reference(&params, &image, &kernel));
+        }
+    }
+
+    // Run with `cargo test --features gpu -- --ignored` on a machine with a GPU adapter.
+    #[test]
+    #[ignore = "requires a GPU adapter"]
+    fn test_template_correlation_f32_matches_reference() {
+        assert!(is_available(), "no GPU adapter is available");
+        let (width, height) = (31, 20);
+        let image = test_image(width, height, 1);
+        let (template_width, template_height) = (9, 7);
+        let template: Vec<f32> = (0..template_width * template_height)
+            .map(|i| (i % 13) as f32 - 6.5)
+            .collect();
+        let actual = template_correlation_f32(
+            &image,
+            width,
+            height,
+            &template,
+            template_width,
+            template_height,
+        )
+        .unwrap();
+        let params = Params {
+            width,
+            height,
+            channels: 1,
+            kernel_width: template_width,
+            kernel_height: template_height,
+            out_width: width - template_width + 1,
+            out_height: height - template_height + 1,
+            offset_x: 0,
+            offset_y: 0,
+        };
+        assert_close(&actual, &correlate_reference(&params, &image, &template));
+    }
+
+    #[test]
+    fn test_small_kernels_are_not_run_on_the_gpu() {
+        let image = test_image(5, 5, 1);
+        assert!(filter_f32(&image, 5, 5, 1, &[1.0; 9], 3, 3).is_none());
+        assert!(template_correlation_f32(&image, 5, 5, &[1.0; 4], 2, 2).is_none());
+    }
+}
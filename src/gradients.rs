@@ -1,8 +1,10 @@
 //! Functions for computing gradients of image intensities.
 
-use crate::definitions::{HasBlack, Image};
-use crate::filter::filter3x3;
+use crate::definitions::{HasBlack, Image, MaybeSync};
+use crate::error::{check_dimensions, Error};
+use crate::filter::{filter3x3_i16, filter3x3_i16_into, filter3x3_into};
 use crate::map::{ChannelMap, WithChannel};
+use conv::ValueInto;
 use image::{GenericImage, GenericImageView, GrayImage, Luma, Pixel};
 use itertools::multizip;
 
@@ -63,37 +65,37 @@ pub static HORIZONTAL_PREWITT: [i32; 9] = [
 /// Convolves an image with the [`HORIZONTAL_SOBEL`](static.HORIZONTAL_SOBEL.html)
 /// kernel to detect horizontal gradients.
 pub fn horizontal_sobel(image: &GrayImage) -> Image<Luma<i16>> {
-    filter3x3(image, &HORIZONTAL_SOBEL)
+    filter3x3_i16(image, &HORIZONTAL_SOBEL)
 }
 
 /// Convolves an image with the [`VERTICAL_SOBEL`](static.VERTICAL_SOBEL.html)
 /// kernel to detect vertical gradients.
 pub fn vertical_sobel(image: &GrayImage) -> Image<Luma<i16>> {
-    filter3x3(image, &VERTICAL_SOBEL)
+    filter3x3_i16(image, &VERTICAL_SOBEL)
 }
 
 /// Convolves an image with the [`HORIZONTAL_SCHARR`](static.HORIZONTAL_SCHARR.html)
 /// kernel to detect horizontal gradients.
 pub fn horizontal_scharr(image: &GrayImage) -> Image<Luma<i16>> {
-    filter3x3(image, &HORIZONTAL_SCHARR)
+    filter3x3_i16(image, &HORIZONTAL_SCHARR)
 }
 
 /// Convolves an image with the [`VERTICAL_SCHARR`](static.VERTICAL_SCHARR.html)
 /// kernel to detect vertical gradients.
 pub fn vertical_scharr(image: &GrayImage) -> Image<Luma<i16>> {
-    filter3x3(image, &VERTICAL_SCHARR)
+    filter3x3_i16(image, &VERTICAL_SCHARR)
 }
 
 /// Convolves an image with the [`HORIZONTAL_PREWITT`](static.HORIZONTAL_PREWITT.html)
 /// kernel to detect horizontal gradients.
 pub fn horizontal_prewitt(image: &GrayImage) -> Image<Luma<i16>> {
-    filter3x3(image, &HORIZONTAL_PREWITT)
+    filter3x3_i16(image, &HORIZONTAL_PREWITT)
 }
 
 /// Convolves an image with the [`VERTICAL_PREWITT`](static.VERTICAL_PREWITT.html)
 /// kernel to detect vertical gradients.
 pub fn vertical_prewitt(image: &GrayImage) -> Image<Luma<i16>> {
-    filter3x3(image, &VERTICAL_PREWITT)
+    filter3x3_i16(image, &VERTICAL_PREWITT)
 }
 
 /// Returns the magnitudes of gradients in an image using Sobel filters.
@@ -101,6 +103,46 @@ pub fn sobel_gradients(image: &GrayImage) -> Image<Luma<u16>> {
     gradients(image, &HORIZONTAL_SOBEL, &VERTICAL_SOBEL, |p| p)
 }
 
+/// Computes the magnitudes of gradients in an image using Sobel filters, writing the
+/// result to `out` and using `horizontal` and `vertical` to store the directional gradients.
+///
+/// `horizontal`, `vertical` and `out` must all have the same dimensions as `image`.
+/// See the [`sobel_gradients`](fn.sobel_gradients.html) documentation for more information.
+///
+/// # Panics
+/// If `horizontal`, `vertical` or `out` do not have the same dimensions as `image`.
+pub fn sobel_gradients_into(
+    image: &GrayImage,
+    horizontal: &mut Image<Luma<i16>>,
+    vertical: &mut Image<Luma<i16>>,
+    out: &mut Image<Luma<u16>>,
+) {
+    try_sobel_gradients_into(image, horizontal, vertical, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Computes the magnitudes of gradients in an image using Sobel filters, writing the
+/// result to `out` and using `horizontal` and `vertical` to store the directional gradients.
+/// Returns an error if `horizontal`, `vertical` or `out` do not have the same dimensions
+/// as `image`.
+///
+/// See the [`sobel_gradients`](fn.sobel_gradients.html) documentation for more information.
+pub fn try_sobel_gradients_into(
+    image: &GrayImage,
+    horizontal: &mut Image<Luma<i16>>,
+    vertical: &mut Image<Luma<i16>>,
+    out: &mut Image<Luma<u16>>,
+) -> Result<(), Error> {
+    gradients_into(
+        image,
+        &HORIZONTAL_SOBEL,
+        &VERTICAL_SOBEL,
+        |p| p,
+        horizontal,
+        vertical,
+        out,
+    )
+}
+
 /// Computes per-channel gradients using Sobel filters and calls `f`
 /// to compute each output pixel.
 ///
@@ -169,7 +211,7 @@ pub fn sobel_gradients(image: &GrayImage) -> Image<Luma<u16>> {
 /// # }
 pub fn sobel_gradient_map<P, F, Q>(image: &Image<P>, f: F) -> Image<Q>
 where
-    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + 'static,
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
     Q: Pixel + 'static,
     ChannelMap<P, u16>: HasBlack,
     F: Fn(ChannelMap<P, u16>) -> Q,
@@ -182,6 +224,220 @@ pub fn prewitt_gradients(image: &GrayImage) -> Image<Luma<u16>> {
     gradients(image, &HORIZONTAL_PREWITT, &VERTICAL_PREWITT, |p| p)
 }
 
+/// Computes the magnitudes of gradients in an image using Prewitt filters, writing the
+/// result to `out` and using `horizontal` and `vertical` to store the directional gradients.
+///
+/// See the [`sobel_gradients_into`](fn.sobel_gradients_into.html) documentation for more
+/// information.
+///
+/// # Panics
+/// If `horizontal`, `vertical` or `out` do not have the same dimensions as `image`.
+pub fn prewitt_gradients_into(
+    image: &GrayImage,
+    horizontal: &mut Image<Luma<i16>>,
+    vertical: &mut Image<Luma<i16>>,
+    out: &mut Image<Luma<u16>>,
+) {
+    try_prewitt_gradients_into(image, horizontal, vertical, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Computes the magnitudes of gradients in an image using Prewitt filters, writing the
+/// result to `out` and using `horizontal` and `vertical` to store the directional gradients.
+/// Returns an error if `horizontal`, `vertical` or `out` do not have the same dimensions
+/// as `image`.
+///
+/// See the [`sobel_gradients_into`](fn.sobel_gradients_into.html) documentation for more
+/// information.
+pub fn try_prewitt_gradients_into(
+    image: &GrayImage,
+    horizontal: &mut Image<Luma<i16>>,
+    vertical: &mut Image<Luma<i16>>,
+    out: &mut Image<Luma<u16>>,
+) -> Result<(), Error> {
+    gradients_into(
+        image,
+        &HORIZONTAL_PREWITT,
+        &VERTICAL_PREWITT,
+        |p| p,
+        horizontal,
+        vertical,
+        out,
+    )
+}
+
+/// Returns the magnitudes of gradients in an image using Sobel filters, computed
+/// per channel at `f32` precision.
+///
+/// Unlike [`sobel_gradients`](fn.sobel_gradients.html) this accepts images of any
+/// subpixel type convertible to `f32`, e.g. 16-bit or `f32` images, and the
+/// magnitudes are not truncated or saturated.
+///
+/// Directional gradients at `f32` precision can be computed by passing one of the
+/// kernels defined in this module to [`filter3x3`](../filter/fn.filter3x3.html).
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::gradients::sobel_gradients_f32;
+///
+/// let image = gray_image!(type: u16,
+///     0, 1000, 2000;
+///     0, 1000, 2000;
+///     0, 1000, 2000);
+///
+/// let gradients = gray_image!(type: f32,
+///     4000.0, 8000.0, 4000.0;
+///     4000.0, 8000.0, 4000.0;
+///     4000.0, 8000.0, 4000.0);
+///
+/// assert_pixels_eq!(sobel_gradients_f32(&image), gradients);
+/// # }
+/// ```
+pub fn sobel_gradients_f32<P>(image: &Image<P>) -> Image<ChannelMap<P, f32>>
+where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    gradients_f32(image, &HORIZONTAL_SOBEL, &VERTICAL_SOBEL)
+}
+
+/// Computes the magnitudes of gradients in an image using Sobel filters at `f32` precision,
+/// writing the result to `out` and using `buffer` to store the vertical gradients.
+///
+/// Both `buffer` and `out` must have the same dimensions as `image`. See the
+/// [`sobel_gradients_f32`](fn.sobel_gradients_f32.html) documentation for more information.
+///
+/// # Panics
+/// If `buffer` or `out` do not have the same dimensions as `image`.
+pub fn sobel_gradients_f32_into<P>(
+    image: &Image<P>,
+    buffer: &mut Image<ChannelMap<P, f32>>,
+    out: &mut Image<ChannelMap<P, f32>>,
+) where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    try_sobel_gradients_f32_into(image, buffer, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Computes the magnitudes of gradients in an image using Sobel filters at `f32` precision,
+/// writing the result to `out` and using `buffer` to store the vertical gradients. Returns
+/// an error if `buffer` or `out` do not have the same dimensions as `image`.
+///
+/// See the [`sobel_gradients_f32`](fn.sobel_gradients_f32.html) documentation for more
+/// information.
+pub fn try_sobel_gradients_f32_into<P>(
+    image: &Image<P>,
+    buffer: &mut Image<ChannelMap<P, f32>>,
+    out: &mut Image<ChannelMap<P, f32>>,
+) -> Result<(), Error>
+where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    gradients_f32_into(image, &HORIZONTAL_SOBEL, &VERTICAL_SOBEL, buffer, out)
+}
+
+/// Returns the magnitudes of gradients in an image using Prewitt filters, computed
+/// per channel at `f32` precision.
+///
+/// See the [`sobel_gradients_f32`](fn.sobel_gradients_f32.html) documentation for more information.
+pub fn prewitt_gradients_f32<P>(image: &Image<P>) -> Image<ChannelMap<P, f32>>
+where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    gradients_f32(image, &HORIZONTAL_PREWITT, &VERTICAL_PREWITT)
+}
+
+/// Computes the magnitudes of gradients in an image using Prewitt filters at `f32` precision,
+/// writing the result to `out` and using `buffer` to store the vertical gradients.
+///
+/// See the [`sobel_gradients_f32_into`](fn.sobel_gradients_f32_into.html) documentation for
+/// more information.
+///
+/// # Panics
+/// If `buffer` or `out` do not have the same dimensions as `image`.
+pub fn prewitt_gradients_f32_into<P>(
+    image: &Image<P>,
+    buffer: &mut Image<ChannelMap<P, f32>>,
+    out: &mut Image<ChannelMap<P, f32>>,
+) where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    try_prewitt_gradients_f32_into(image, buffer, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Computes the magnitudes of gradients in an image using Prewitt filters at `f32` precision,
+/// writing the result to `out` and using `buffer` to store the vertical gradients. Returns
+/// an error if `buffer` or `out` do not have the same dimensions as `image`.
+///
+/// See the [`sobel_gradients_f32_into`](fn.sobel_gradients_f32_into.html) documentation for
+/// more information.
+pub fn try_prewitt_gradients_f32_into<P>(
+    image: &Image<P>,
+    buffer: &mut Image<ChannelMap<P, f32>>,
+    out: &mut Image<ChannelMap<P, f32>>,
+) -> Result<(), Error>
+where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    gradients_f32_into(image, &HORIZONTAL_PREWITT, &VERTICAL_PREWITT, buffer, out)
+}
+
+fn gradients_f32<P>(
+    image: &Image<P>,
+    horizontal_kernel: &[i32; 9],
+    vertical_kernel: &[i32; 9],
+) -> Image<ChannelMap<P, f32>>
+where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    let (width, height) = image.dimensions();
+    let mut buffer = Image::new(width, height);
+    let mut out = Image::new(width, height);
+    gradients_f32_into(
+        image,
+        horizontal_kernel,
+        vertical_kernel,
+        &mut buffer,
+        &mut out,
+    )
+    .expect("buffers have the same dimensions as image");
+    out
+}
+
+// Writes the horizontal gradients to out and the vertical gradients to buffer,
+// and then replaces out with the gradient magnitudes.
+fn gradients_f32_into<P>(
+    image: &Image<P>,
+    horizontal_kernel: &[i32; 9],
+    vertical_kernel: &[i32; 9],
+    buffer: &mut Image<ChannelMap<P, f32>>,
+    out: &mut Image<ChannelMap<P, f32>>,
+) -> Result<(), Error>
+where
+    P: WithChannel<f32> + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + MaybeSync,
+{
+    check_dimensions(buffer.dimensions(), image.dimensions())?;
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    let to_f32 = |kernel: &[i32; 9]| kernel.iter().map(|&k| k as f32).collect::<Vec<_>>();
+    filter3x3_into(image, &to_f32(horizontal_kernel), out);
+    filter3x3_into(image, &to_f32(vertical_kernel), buffer);
+
+    for (h, v) in out.iter_mut().zip(buffer.iter()) {
+        *h = (h.powi(2) + v.powi(2)).sqrt();
+    }
+    Ok(())
+}
+
 // TODO: Returns directions as well as magnitudes.
 // TODO: Support filtering without allocating a fresh image - filtering functions could
 // TODO: take some kind of pixel-sink. This would allow us to compute gradient magnitudes
@@ -193,16 +449,50 @@ fn gradients<P, F, Q>(
     f: F,
 ) -> Image<Q>
 where
-    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + 'static,
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
     Q: Pixel + 'static,
     ChannelMap<P, u16>: HasBlack,
     F: Fn(ChannelMap<P, u16>) -> Q,
 {
-    let horizontal: Image<ChannelMap<P, i16>> = filter3x3::<_, _, i16>(image, horizontal_kernel);
-    let vertical: Image<ChannelMap<P, i16>> = filter3x3::<_, _, i16>(image, vertical_kernel);
-
     let (width, height) = image.dimensions();
+    let mut horizontal = Image::new(width, height);
+    let mut vertical = Image::new(width, height);
     let mut out = Image::<Q>::new(width, height);
+    gradients_into(
+        image,
+        horizontal_kernel,
+        vertical_kernel,
+        f,
+        &mut horizontal,
+        &mut vertical,
+        &mut out,
+    )
+    .expect("buffers have the same dimensions as image");
+    out
+}
+
+fn gradients_into<P, F, Q>(
+    image: &Image<P>,
+    horizontal_kernel: &[i32; 9],
+    vertical_kernel: &[i32; 9],
+    f: F,
+    horizontal: &mut Image<ChannelMap<P, i16>>,
+    vertical: &mut Image<ChannelMap<P, i16>>,
+    out: &mut Image<Q>,
+) -> Result<(), Error>
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    Q: Pixel + 'static,
+    ChannelMap<P, u16>: HasBlack,
+    F: Fn(ChannelMap<P, u16>) -> Q,
+{
+    check_dimensions(horizontal.dimensions(), image.dimensions())?;
+    check_dimensions(vertical.dimensions(), image.dimensions())?;
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    filter3x3_i16_into(image, horizontal_kernel, horizontal);
+    filter3x3_i16_into(image, vertical_kernel, vertical);
+
+    let (width, height) = image.dimensions();
 
     // This would be more concise using itertools::multizip over image pixels, but that increased runtime by around 20%
     for y in 0..height {
@@ -213,8 +503,8 @@ where
             //      as measured by bench_sobel_gradients
             //  Correctness
             //      x and y are in bounds for image by construction,
-            //      vertical and horizontal are the result of calling filter3x3 on image,
-            //      and filter3x3 returns an image of the same size as its input
+            //      and vertical and horizontal were checked above to have the same
+            //      dimensions as image
             let (h, v) = unsafe {
                 (
                     horizontal.unsafe_get_pixel(x, y),
@@ -240,7 +530,7 @@ where
         }
     }
 
-    out
+    Ok(())
 }
 
 #[inline]
@@ -268,6 +558,100 @@ mod tests {
         assert_pixels_eq!(vertical_prewitt(&image), expected);
     }
 
+    #[test]
+    fn test_sobel_gradients_f32_matches_sobel_gradients_on_u8() {
+        let image = gray_bench_image(20, 15);
+        let expected = sobel_gradients(&image);
+        let actual = sobel_gradients_f32(&image);
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert_eq!(*e, *a as u16);
+        }
+    }
+
+    #[test]
+    fn test_into_variants_match_allocating_variants() {
+        let image = gray_bench_image(9, 7);
+        let mut horizontal = Image::new(9, 7);
+        let mut vertical = Image::new(9, 7);
+        let mut out = Image::new(9, 7);
+        sobel_gradients_into(&image, &mut horizontal, &mut vertical, &mut out);
+        assert_pixels_eq!(out, sobel_gradients(&image));
+        prewitt_gradients_into(&image, &mut horizontal, &mut vertical, &mut out);
+        assert_pixels_eq!(out, prewitt_gradients(&image));
+
+        let mut buffer = Image::new(9, 7);
+        let mut out = Image::new(9, 7);
+        sobel_gradients_f32_into(&image, &mut buffer, &mut out);
+        assert_pixels_eq!(out, sobel_gradients_f32(&image));
+        prewitt_gradients_f32_into(&image, &mut buffer, &mut out);
+        assert_pixels_eq!(out, prewitt_gradients_f32(&image));
+    }
+
+    #[test]
+    fn test_try_into_variants_check_dimensions() {
+        let image = gray_bench_image(5, 4);
+        let expected = Err(Error::DimensionMismatch {
+            expected: (5, 4),
+            actual: (4, 5),
+        });
+
+        let (mut good, mut bad) = (Image::new(5, 4), Image::new(4, 5));
+        let mut out = Image::new(5, 4);
+        assert_eq!(
+            try_sobel_gradients_into(&image, &mut good.clone(), &mut bad, &mut out),
+            expected
+        );
+        assert_eq!(
+            try_prewitt_gradients_into(&image, &mut bad, &mut good, &mut out),
+            expected
+        );
+        assert_eq!(
+            try_sobel_gradients_into(&image, &mut good.clone(), &mut good, &mut Image::new(4, 5)),
+            expected
+        );
+
+        let (mut good, mut bad) = (Image::new(5, 4), Image::new(4, 5));
+        assert_eq!(
+            try_sobel_gradients_f32_into(&image, &mut good.clone(), &mut bad),
+            expected
+        );
+        assert_eq!(
+            try_prewitt_gradients_f32_into(&image, &mut bad, &mut good),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_sobel_gradients_f32_u16() {
+        // Gradients of this image overflow the i16 intermediates used for 8-bit images.
+        let image = gray_image!(type: u16,
+            0, 0, 60000;
+            0, 0, 60000;
+            0, 0, 60000);
+
+        let expected = gray_image!(type: f32,
+            0.0, 240000.0, 240000.0;
+            0.0, 240000.0, 240000.0;
+            0.0, 240000.0, 240000.0);
+
+        assert_pixels_eq!(sobel_gradients_f32(&image), expected);
+    }
+
+    #[test]
+    fn test_prewitt_gradients_f32_f32() {
+        let image = gray_image!(type: f32,
+            0.0, 0.0, 0.0;
+            0.0, 0.0, 0.0;
+            0.5, 0.5, 0.5);
+
+        let expected = gray_image!(type: f32,
+            0.0, 0.0, 0.0;
+            1.5, 1.5, 1.5;
+            1.5, 1.5, 1.5);
+
+        assert_pixels_eq!(prewitt_gradients_f32(&image), expected);
+    }
+
     #[test]
     fn test_horizontal_sobel_gradient_image() {
         let image = gray_image!(
@@ -3,6 +3,7 @@
 //! [Haar-like features]: https://en.wikipedia.org/wiki/Haar-like_features
 
 use crate::definitions::{HasBlack, HasWhite, Image};
+use crate::drawing::Canvas;
 use image::{GenericImage, GenericImageView, ImageBuffer, Luma};
 use itertools::Itertools;
 use std::marker::PhantomData;
@@ -383,7 +384,7 @@ fn num_features(image_side: u32, num_blocks: u32) -> u32 {
 /// with a positive sign white and those with a negative sign black.
 pub fn draw_haar_feature<I>(image: &I, feature: HaarFeature) -> Image<I::Pixel>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: HasBlack + HasWhite + 'static,
 {
     let mut out = ImageBuffer::new(image.width(), image.height());
@@ -394,10 +395,10 @@ where
 
 /// Draws the given Haar-like feature on an image in place, drawing pixels
 /// with a positive sign white and those with a negative sign black.
-pub fn draw_haar_feature_mut<I>(image: &mut I, feature: HaarFeature)
+pub fn draw_haar_feature_mut<C>(canvas: &mut C, feature: HaarFeature)
 where
-    I: GenericImage,
-    I::Pixel: HasBlack + HasWhite,
+    C: Canvas,
+    C::Pixel: HasBlack + HasWhite,
 {
     let parity_shift = if feature.sign == Sign::Positive { 0 } else { 1 };
 
@@ -405,15 +406,15 @@ where
         for h in 0..feature.blocks_high() {
             let parity = (w + h + parity_shift) % 2;
             let color = if parity == 0 {
-                I::Pixel::white()
+                C::Pixel::white()
             } else {
-                I::Pixel::black()
+                C::Pixel::black()
             };
             for x in 0..feature.block_width() {
                 for y in 0..feature.block_height() {
                     let px = feature.left + w * feature.block_width() + x;
                     let py = feature.top + h * feature.block_height() + y;
-                    image.put_pixel(px as u32, py as u32, color);
+                    canvas.draw_pixel(px as u32, py as u32, color);
                 }
             }
         }
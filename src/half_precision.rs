@@ -0,0 +1,303 @@
+//! A half-precision floating point subpixel type, enabled by the `half` feature.
+//!
+//! `image::Primitive` cannot be implemented for the [half](https://docs.rs/half) crate's
+//! `f16` type outside of either crate, so this module provides the newtype
+//! [`F16`](struct.F16.html), which can be used as the subpixel type of an image such as
+//! `Image<Luma<F16>>`. These images use half the memory of their `f32` equivalents.
+//!
+//! `F16` implements `ValueInto<f32>`, `ValueInto<f64>`, `Clamp<f32>` and `Clamp<f64>`, so it
+//! can be used with the functions in the `map`, `filter` and `geometric_transformations`
+//! modules that accept floating point images. These functions perform their intermediate
+//! calculations at higher precision and round the results to the nearest `F16`.
+//!
+//! # Examples
+//! ```
+//! use image::Luma;
+//! use imageproc::definitions::Image;
+//! use imageproc::filter::gaussian_blur_f32;
+//! use imageproc::half_precision::F16;
+//! use imageproc::map::map_subpixels;
+//!
+//! let image = Image::from_fn(10, 10, |x, y| Luma([F16::from_f32((x + y) as f32)]));
+//! let blurred = gaussian_blur_f32(&image, 1.5);
+//! let blurred: Image<Luma<f32>> = map_subpixels(&blurred, F16::to_f32);
+//! ```
+
+use crate::definitions::Clamp;
+use conv::{NoError, ValueFrom};
+use half::f16;
+use num::{Bounded, Num, NumCast, One, ToPrimitive, Zero};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// A half-precision floating point number, usable as an image subpixel.
+///
+/// Arithmetic is performed in `f32` and the result rounded to the nearest `F16`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct F16(pub f16);
+
+impl F16 {
+    /// Converts an `f32` to the nearest `F16`. Values outside the range of `F16` become
+    /// infinite.
+    pub fn from_f32(x: f32) -> F16 {
+        F16(f16::from_f32(x))
+    }
+
+    /// Converts to an `f32`. This conversion is exact.
+    pub fn to_f32(self) -> f32 {
+        self.0.to_f32()
+    }
+}
+
+impl From<f16> for F16 {
+    fn from(x: f16) -> F16 {
+        F16(x)
+    }
+}
+
+impl From<F16> for f16 {
+    fn from(x: F16) -> f16 {
+        x.0
+    }
+}
+
+impl From<F16> for f32 {
+    fn from(x: F16) -> f32 {
+        x.to_f32()
+    }
+}
+
+impl fmt::Display for F16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+macro_rules! implement_binary_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl $trait for F16 {
+            type Output = F16;
+
+            fn $method(self, rhs: F16) -> F16 {
+                F16::from_f32(self.to_f32() $op rhs.to_f32())
+            }
+        }
+    };
+}
+
+implement_binary_op!(Add, add, +);
+implement_binary_op!(Sub, sub, -);
+implement_binary_op!(Mul, mul, *);
+implement_binary_op!(Div, div, /);
+implement_binary_op!(Rem, rem, %);
+
+impl Neg for F16 {
+    type Output = F16;
+
+    fn neg(self) -> F16 {
+        F16(-self.0)
+    }
+}
+
+impl Zero for F16 {
+    fn zero() -> F16 {
+        F16(f16::ZERO)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.to_f32() == 0.0
+    }
+}
+
+impl One for F16 {
+    fn one() -> F16 {
+        F16(f16::ONE)
+    }
+}
+
+impl Num for F16 {
+    type FromStrRadixErr = <f32 as Num>::FromStrRadixErr;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<F16, Self::FromStrRadixErr> {
+        f32::from_str_radix(s, radix).map(F16::from_f32)
+    }
+}
+
+impl Bounded for F16 {
+    fn min_value() -> F16 {
+        F16(f16::MIN)
+    }
+
+    fn max_value() -> F16 {
+        F16(f16::MAX)
+    }
+}
+
+impl ToPrimitive for F16 {
+    fn to_i64(&self) -> Option<i64> {
+        self.0.to_f32().to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.0.to_f32().to_u64()
+    }
+
+    fn to_f32(&self) -> Option<f32> {
+        Some(self.0.to_f32())
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.0.to_f64())
+    }
+}
+
+impl NumCast for F16 {
+    fn from<T: ToPrimitive>(n: T) -> Option<F16> {
+        n.to_f32().map(F16::from_f32)
+    }
+}
+
+impl image::Primitive for F16 {}
+
+impl Clamp<f32> for F16 {
+    fn clamp(x: f32) -> F16 {
+        if x < f16::MAX.to_f32() {
+            if x > f16::MIN.to_f32() {
+                F16::from_f32(x)
+            } else {
+                F16(f16::MIN)
+            }
+        } else {
+            F16(f16::MAX)
+        }
+    }
+}
+
+impl Clamp<f64> for F16 {
+    fn clamp(x: f64) -> F16 {
+        if x < f16::MAX.to_f64() {
+            if x > f16::MIN.to_f64() {
+                F16(f16::from_f64(x))
+            } else {
+                F16(f16::MIN)
+            }
+        } else {
+            F16(f16::MAX)
+        }
+    }
+}
+
+impl Clamp<F16> for F16 {
+    fn clamp(x: F16) -> F16 {
+        x
+    }
+}
+
+impl ValueFrom<F16> for f32 {
+    type Err = NoError;
+
+    fn value_from(x: F16) -> Result<f32, NoError> {
+        Ok(x.to_f32())
+    }
+}
+
+impl ValueFrom<F16> for f64 {
+    type Err = NoError;
+
+    fn value_from(x: F16) -> Result<f64, NoError> {
+        Ok(x.to_f32() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::Image;
+    use crate::filter::{box_filter, gaussian_blur_f32};
+    use crate::geometric_transformations::{rotate_about_center, Interpolation};
+    use crate::map::map_subpixels;
+    use crate::utils::gray_bench_image;
+    use image::{GrayImage, Luma};
+
+    fn to_f16(image: &GrayImage) -> Image<Luma<F16>> {
+        map_subpixels(image, |p| F16::from_f32(p as f32))
+    }
+
+    fn to_f32(image: &Image<Luma<F16>>) -> Image<Luma<f32>> {
+        map_subpixels(image, F16::to_f32)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = F16::from_f32(1.5);
+        let b = F16::from_f32(2.0);
+        assert_eq!(a + b, F16::from_f32(3.5));
+        assert_eq!(a - b, F16::from_f32(-0.5));
+        assert_eq!(a * b, F16::from_f32(3.0));
+        assert_eq!(b / a, F16::from_f32(2.0 / 1.5));
+        assert_eq!(-a, F16::from_f32(-1.5));
+        assert!(F16::zero().is_zero());
+        assert_eq!(F16::one(), F16::from_f32(1.0));
+    }
+
+    #[test]
+    fn test_num_cast() {
+        assert_eq!(<F16 as NumCast>::from(300u32), Some(F16::from_f32(300.0)));
+        assert_eq!(F16::from_f32(12.75).to_u8(), Some(12));
+        assert_eq!(F16::from_f32(-1.0).to_u8(), None);
+        assert_eq!(F16::max_value().to_f32(), 65504.0);
+    }
+
+    #[test]
+    fn test_clamp_saturates() {
+        assert_eq!(<F16 as Clamp<f32>>::clamp(1e6), F16::max_value());
+        assert_eq!(<F16 as Clamp<f32>>::clamp(-1e6), F16::min_value());
+        assert_eq!(<F16 as Clamp<f64>>::clamp(1e6), F16::max_value());
+        assert_eq!(<F16 as Clamp<f32>>::clamp(0.5), F16::from_f32(0.5));
+    }
+
+    #[test]
+    fn test_map_round_trip() {
+        let image = gray_bench_image(13, 11);
+        let round_trip: GrayImage = map_subpixels(&to_f16(&image), |p| p.to_f32() as u8);
+        assert_eq!(round_trip, image);
+    }
+
+    fn assert_close(actual: &Image<Luma<f32>>, expected: &Image<Luma<f32>>) {
+        assert_eq!(actual.dimensions(), expected.dimensions());
+        for (a, e) in actual.pixels().zip(expected.pixels()) {
+            // f16 has an 11 bit significand, and our values are at most 255.
+            assert!((a[0] - e[0]).abs() <= 0.25, "{} != {}", a[0], e[0]);
+        }
+    }
+
+    #[test]
+    fn test_gaussian_blur_matches_f32() {
+        let image = gray_bench_image(20, 17);
+        let expected = gaussian_blur_f32(&map_subpixels(&image, |p| p as f32), 2.0);
+        let actual = gaussian_blur_f32(&to_f16(&image), 2.0);
+        assert_close(&to_f32(&actual), &expected);
+    }
+
+    #[test]
+    fn test_box_filter_matches_f32() {
+        let image = gray_bench_image(20, 17);
+        let expected = box_filter(&map_subpixels(&image, |p| p as f32), 2, 3);
+        let actual = box_filter(&to_f16(&image), 2, 3);
+        assert_close(&to_f32(&actual), &expected);
+    }
+
+    #[test]
+    fn test_rotate_matches_f32() {
+        let image = gray_bench_image(20, 17);
+        let default = Luma([F16::zero()]);
+        let expected = rotate_about_center(
+            &map_subpixels(&image, |p| p as f32),
+            0.7,
+            Interpolation::Bilinear,
+            Luma([0.0]),
+        );
+        let actual = rotate_about_center(&to_f16(&image), 0.7, Interpolation::Bilinear, default);
+        assert_close(&to_f32(&actual), &expected);
+    }
+}
@@ -0,0 +1,446 @@
+//! Functions for combining and displaying high dynamic range images.
+
+use crate::definitions::Image;
+use crate::filter::{filter3x3, separable_filter_equal};
+use crate::pyramid::{gaussian_pyramid, laplacian_pyramid, reconstruct_laplacian_pyramid};
+use image::{ImageBuffer, Luma, Rgb, RgbImage};
+
+/// Fuses a set of aligned images of the same scene taken at different exposures into a
+/// single well exposed image, using [Mertens' exposure fusion].
+///
+/// Each pixel of each input image is assigned a weight which is the product of measures of
+/// its contrast (the absolute response of a Laplacian filter on the grayscale image), its
+/// saturation (the standard deviation of its channels) and its well-exposedness (how close
+/// its channels are to the middle of the intensity range), raised to the powers
+/// `contrast_weight`, `saturation_weight` and `exposure_weight` respectively. The weights
+/// are normalized to sum to one at each pixel, and the images are blended by combining
+/// their Laplacian pyramids using Gaussian pyramids of the weights. This avoids the seams
+/// that a direct per-pixel blend would produce.
+///
+/// Setting all weight exponents to 1 gives the results of the original paper. Unlike HDR
+/// merging followed by tone mapping, no exposure times or camera response curve are needed.
+///
+/// [Mertens' exposure fusion]: https://doi.org/10.1111/j.1467-8659.2008.01171.x
+///
+/// # Panics
+/// If `images` is empty, or if the images do not all have the same dimensions.
+pub fn exposure_fusion(
+    images: &[RgbImage],
+    contrast_weight: f32,
+    saturation_weight: f32,
+    exposure_weight: f32,
+) -> RgbImage {
+    assert!(!images.is_empty(), "images must not be empty");
+    let (width, height) = images[0].dimensions();
+    assert!(
+        images.iter().all(|i| i.dimensions() == (width, height)),
+        "all images must have the same dimensions"
+    );
+    if width == 0 || height == 0 {
+        return RgbImage::new(width, height);
+    }
+
+    let mut weights: Vec<Image<Luma<f32>>> = images
+        .iter()
+        .map(|image| fusion_weights(image, contrast_weight, saturation_weight, exposure_weight))
+        .collect();
+
+    // Normalize the weights to sum to one at each pixel
+    for i in 0..(width * height) as usize {
+        let total: f32 = weights.iter().map(|w| w.as_raw()[i]).sum();
+        for w in weights.iter_mut() {
+            let data: &mut [f32] = w.as_mut();
+            data[i] /= total;
+        }
+    }
+
+    // Stop when the smallest level is a single pixel along its shorter side
+    let mut levels = 1;
+    let mut size = width.min(height);
+    while size > 1 {
+        size -= size / 2;
+        levels += 1;
+    }
+
+    let mut fused: Option<Vec<Image<Rgb<f32>>>> = None;
+    for (image, weight) in images.iter().zip(&weights) {
+        let weight_pyramid = gaussian_pyramid(weight, levels);
+        let mut pyramid = laplacian_pyramid(image, levels);
+        for (level, weight) in pyramid.iter_mut().zip(&weight_pyramid) {
+            for (p, w) in level.pixels_mut().zip(weight.iter()) {
+                for c in p.0.iter_mut() {
+                    *c *= *w;
+                }
+            }
+        }
+        fused = Some(match fused {
+            None => pyramid,
+            Some(mut fused) => {
+                for (f, level) in fused.iter_mut().zip(&pyramid) {
+                    let data: &mut [f32] = f.as_mut();
+                    for (f, l) in data.iter_mut().zip(level.iter()) {
+                        *f += *l;
+                    }
+                }
+                fused
+            }
+        });
+    }
+
+    let fused = reconstruct_laplacian_pyramid(&fused.unwrap());
+    let data = fused
+        .iter()
+        .map(|c| (c + 0.5).clamp(0.0, 255.0) as u8)
+        .collect();
+    ImageBuffer::from_raw(width, height, data).unwrap()
+}
+
+// The unnormalized Mertens weight of each pixel of an image.
+fn fusion_weights(
+    image: &RgbImage,
+    contrast_weight: f32,
+    saturation_weight: f32,
+    exposure_weight: f32,
+) -> Image<Luma<f32>> {
+    // Avoids all weights being zero at a pixel
+    const EPSILON: f32 = 1e-12;
+    const SIGMA: f32 = 0.2;
+
+    let gray: Image<Luma<f32>> = ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let p = image.get_pixel(x, y);
+        Luma([(0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32) / 255.0])
+    });
+    let laplacian: Image<Luma<f32>> =
+        filter3x3(&gray, &[0.0, 1.0, 0.0, 1.0, -4.0, 1.0, 0.0, 1.0, 0.0]);
+
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let channels = image.get_pixel(x, y).0.map(|c| c as f32 / 255.0);
+        let mean = channels.iter().sum::<f32>() / 3.0;
+        let saturation = (channels.iter().map(|c| (c - mean).powi(2)).sum::<f32>() / 3.0).sqrt();
+        let exposedness: f32 = channels
+            .iter()
+            .map(|c| (-(c - 0.5).powi(2) / (2.0 * SIGMA * SIGMA)).exp())
+            .product();
+        let contrast = laplacian.get_pixel(x, y)[0].abs();
+
+        Luma([contrast.powf(contrast_weight)
+            * saturation.powf(saturation_weight)
+            * exposedness.powf(exposure_weight)
+            + EPSILON])
+    })
+}
+
+/// Tone maps a high dynamic range image with linear RGB channels to a displayable sRGB image,
+/// using the global operator of [Reinhard et al].
+///
+/// Luminances are first scaled so that the log-average luminance of the image maps to `key`,
+/// which is typically 0.18. Lower keys give darker results and higher keys lighter ones.
+/// Each scaled luminance `l` is then mapped to `l * (1 + l / white^2) / (1 + l)`, where
+/// `white` is the smallest scaled luminance that is mapped to pure white. If `white` is `None`
+/// then the maximum scaled luminance in the image is used.
+///
+/// Colors are scaled to match the mapped luminance, clipped to `[0, 1]` and converted to sRGB.
+///
+/// [Reinhard et al]: https://doi.org/10.1145/566654.566575
+pub fn tone_map_reinhard(image: &Image<Rgb<f32>>, key: f32, white: Option<f32>) -> RgbImage {
+    let scale = key / log_average_luminance(image);
+    let white = white.unwrap_or_else(|| {
+        image
+            .pixels()
+            .map(|p| scale * luminance(p))
+            .fold(0.0, f32::max)
+    });
+    let white_squared = (white * white).max(f32::MIN_POSITIVE);
+    map_luminance(image, |_, _, l| {
+        let l = scale * l;
+        l * (1.0 + l / white_squared) / (1.0 + l)
+    })
+}
+
+/// Tone maps a high dynamic range image with linear RGB channels to a displayable sRGB image,
+/// using the local dodging-and-burning operator of [Reinhard et al].
+///
+/// Luminances are first scaled so that the log-average luminance of the image maps to `key`,
+/// as in [`tone_map_reinhard`](fn.tone_map_reinhard.html). Each scaled luminance `l` is then
+/// mapped to `l / (1 + v)`, where `v` is a Gaussian-weighted average of the scaled luminance
+/// around the pixel at the largest of 8 scales over which the local luminance is roughly
+/// constant. This compresses the dynamic range more strongly than the global operator
+/// while preserving local contrast, in both very dark and very bright regions.
+///
+/// Colors are scaled to match the mapped luminance, clipped to `[0, 1]` and converted to sRGB.
+///
+/// [Reinhard et al]: https://doi.org/10.1145/566654.566575
+pub fn tone_map_reinhard_local(image: &Image<Rgb<f32>>, key: f32) -> RgbImage {
+    // The sharpening parameter and scale selection threshold suggested by Reinhard et al
+    const PHI: f32 = 8.0;
+    const EPSILON: f32 = 0.05;
+    const SCALES: usize = 8;
+
+    let scale = key / log_average_luminance(image);
+    let scaled: Image<Luma<f32>> = ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        Luma([scale * luminance(image.get_pixel(x, y))])
+    });
+
+    // The centre surround ratio of Reinhard et al uses Gaussians with standard
+    // deviations of 0.35 * 1.6^i times the pixel width
+    let blurred: Vec<Image<Luma<f32>>> = (0..=SCALES)
+        .map(|i| normalized_gaussian_blur(&scaled, 0.35 * 1.6f32.powi(i as i32)))
+        .collect();
+
+    map_luminance(image, |x, y, _| {
+        let l = scaled.get_pixel(x, y)[0];
+        let mut local = blurred[0].get_pixel(x, y)[0];
+        for i in 0..SCALES {
+            let s = 1.6f32.powi(i as i32);
+            let v1 = blurred[i].get_pixel(x, y)[0];
+            let v2 = blurred[i + 1].get_pixel(x, y)[0];
+            let v = (v1 - v2) / (2f32.powf(PHI) * key / (s * s) + v1);
+            if v.abs() > EPSILON {
+                break;
+            }
+            local = v1;
+        }
+        l / (1.0 + local)
+    })
+}
+
+/// Tone maps a high dynamic range image with linear RGB channels to a displayable sRGB image,
+/// using the adaptive logarithmic mapping of [Drago et al].
+///
+/// Luminances are divided by the log-average luminance of the image and then mapped
+/// logarithmically, with the base of the logarithm varying from 2 for the darkest pixels to
+/// 10 for the brightest. The maximum luminance in the image is mapped to white. `bias`
+/// controls how quickly the base varies and is typically between 0.7 and 0.9, with
+/// smaller values giving brighter results and 0.85 the suggested default.
+///
+/// Colors are scaled to match the mapped luminance, clipped to `[0, 1]` and converted to sRGB.
+///
+/// [Drago et al]: https://doi.org/10.1111/1467-8659.00689
+///
+/// # Panics
+/// If `bias` is not in the range `(0, 1)`.
+pub fn tone_map_drago(image: &Image<Rgb<f32>>, bias: f32) -> RgbImage {
+    assert!(bias > 0.0 && bias < 1.0, "bias must be in the range (0, 1)");
+    let average = log_average_luminance(image);
+    let max = image
+        .pixels()
+        .map(|p| luminance(p) / average)
+        .fold(0.0, f32::max)
+        .max(f32::MIN_POSITIVE);
+    let exponent = bias.ln() / 0.5f32.ln();
+    let normalization = (max + 1.0).log10();
+
+    map_luminance(image, |_, _, l| {
+        let l = l / average;
+        (l + 1.0).ln() / (2.0 + 8.0 * (l / max).powf(exponent)).ln() / normalization
+    })
+}
+
+// The luminance of a linear RGB pixel, using the Rec. 709 primaries. Negative luminances
+// are treated as zero.
+fn luminance(p: &Rgb<f32>) -> f32 {
+    (0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]).max(0.0)
+}
+
+// The geometric mean of the luminances of an image, offset slightly to handle black pixels.
+fn log_average_luminance(image: &Image<Rgb<f32>>) -> f32 {
+    const DELTA: f32 = 1e-4;
+    let count = (image.width() * image.height()).max(1) as f64;
+    let sum: f64 = image
+        .pixels()
+        .map(|p| (DELTA + luminance(p)).ln() as f64)
+        .sum();
+    ((sum / count).exp() as f32).max(f32::MIN_POSITIVE)
+}
+
+// Scales each pixel so that its luminance is f(x, y, luminance), then clips
+// and converts to sRGB.
+fn map_luminance<F>(image: &Image<Rgb<f32>>, f: F) -> RgbImage
+where
+    F: Fn(u32, u32, f32) -> f32,
+{
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let p = image.get_pixel(x, y);
+        let l = luminance(p);
+        let mapped = f(x, y, l);
+        let ratio = if l > 0.0 { mapped / l } else { 0.0 };
+        Rgb(p.0.map(|c| linear_to_srgb(c * ratio)))
+    })
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = if c.is_nan() { 0.0 } else { c.clamp(0.0, 1.0) };
+    let encoded = if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (255.0 * encoded + 0.5) as u8
+}
+
+// A Gaussian blur whose kernel is truncated at three standard deviations and normalized
+// to sum to one, so that blurring preserves mean luminance even for small sigma.
+fn normalized_gaussian_blur(image: &Image<Luma<f32>>, sigma: f32) -> Image<Luma<f32>> {
+    let radius = (3.0 * sigma).ceil() as i32;
+    let mut kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    for k in kernel.iter_mut() {
+        *k /= total;
+    }
+    separable_filter_equal(image, &kernel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rgb_bench_image;
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn test_exposure_fusion_single_image() {
+        let image = rgb_bench_image(17, 12);
+        let fused = exposure_fusion(std::slice::from_ref(&image), 1.0, 1.0, 1.0);
+        assert_pixels_eq_within!(fused, image, 1);
+    }
+
+    #[test]
+    fn test_exposure_fusion_of_identical_images() {
+        let image = rgb_bench_image(16, 16);
+        let fused = exposure_fusion(&[image.clone(), image.clone()], 1.0, 1.0, 1.0);
+        assert_pixels_eq_within!(fused, image, 1);
+    }
+
+    #[test]
+    fn test_exposure_fusion_prefers_well_exposed_regions() {
+        // The left half of the scene is only well exposed in the long exposure
+        // and the right half only in the short exposure
+        let scene = |x: u32, y: u32| 0.4 + 0.2 * (((x + y) % 4) as f32 / 3.0);
+        let exposure = |left: f32, right: f32| {
+            RgbImage::from_fn(128, 32, |x, y| {
+                let brightness = if x < 64 { left } else { right };
+                let v = (255.0 * scene(x, y) * brightness).min(255.0) as u8;
+                Rgb([v, v - v / 10, v - v / 5])
+            })
+        };
+        let long = exposure(1.0, 3.0);
+        let short = exposure(0.3, 1.0);
+        let fused = exposure_fusion(&[long.clone(), short.clone()], 1.0, 1.0, 1.0);
+
+        let mean_green = |image: &RgbImage, x0: u32| {
+            let mut sum = 0.0;
+            for y in 8..24 {
+                for x in x0..x0 + 16 {
+                    sum += image.get_pixel(x, y)[1] as f32;
+                }
+            }
+            sum / 256.0
+        };
+        // The fused image follows the well exposed input in the interior of each half
+        assert!((mean_green(&fused, 24) - mean_green(&long, 24)).abs() < 15.0);
+        assert!((mean_green(&fused, 88) - mean_green(&short, 88)).abs() < 15.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_exposure_fusion_rejects_mismatched_dimensions() {
+        exposure_fusion(&[RgbImage::new(2, 2), RgbImage::new(3, 2)], 1.0, 1.0, 1.0);
+    }
+
+    // A scene with a dark textured region on the left and a very bright
+    // textured region on the right
+    fn hdr_scene() -> Image<Rgb<f32>> {
+        ImageBuffer::from_fn(64, 16, |x, y| {
+            let texture = if (x / 2 + y / 2) % 2 == 0 { 1.0 } else { 2.0 };
+            let illumination = if x < 32 { 0.05 } else { 20.0 };
+            let l = illumination * texture;
+            Rgb([l, 0.8 * l, 0.6 * l])
+        })
+    }
+
+    // The maximum minus the minimum green value in a square in the interior of a
+    // region of hdr_scene
+    fn local_range(image: &RgbImage, x0: u32) -> u8 {
+        let values: Vec<u8> = (x0..x0 + 8)
+            .flat_map(|x| (4..12).map(move |y| (x, y)))
+            .map(|(x, y)| image.get_pixel(x, y)[1])
+            .collect();
+        values.iter().max().unwrap() - values.iter().min().unwrap()
+    }
+
+    #[test]
+    fn test_tone_map_reinhard_constant() {
+        let image: Image<Rgb<f32>> = ImageBuffer::from_pixel(3, 3, Rgb([5.0, 5.0, 5.0]));
+        // The log-average luminance maps to the key, which maps to 0.18 / 1.18 without
+        // a white point. This is 0.4275 after sRGB encoding.
+        let mapped = tone_map_reinhard(&image, 0.18, Some(f32::INFINITY));
+        assert_pixels_eq_within!(mapped, RgbImage::from_pixel(3, 3, Rgb([109, 109, 109])), 1);
+        // By default the brightest pixel is mapped to white
+        let mapped = tone_map_reinhard(&image, 0.18, None);
+        assert_pixels_eq!(mapped, RgbImage::from_pixel(3, 3, Rgb([255, 255, 255])));
+    }
+
+    #[test]
+    fn test_tone_map_operators_are_monotonic() {
+        let image: Image<Rgb<f32>> =
+            ImageBuffer::from_fn(100, 1, |x, _| Rgb([1.15f32.powi(x as i32) * 0.001; 3]));
+        for mapped in [
+            tone_map_reinhard(&image, 0.18, None),
+            tone_map_drago(&image, 0.85),
+        ] {
+            for x in 1..100 {
+                let (p, q) = (mapped.get_pixel(x - 1, 0), mapped.get_pixel(x, 0));
+                assert!(q[0] >= p[0]);
+                assert_eq!(q[0], q[1]);
+                assert_eq!(q[1], q[2]);
+            }
+            assert_eq!(mapped.get_pixel(99, 0)[0], 255);
+        }
+    }
+
+    #[test]
+    fn test_tone_map_reinhard_local_preserves_detail() {
+        let image = hdr_scene();
+        let mapped = tone_map_reinhard_local(&image, 0.18);
+        assert!(local_range(&mapped, 12) > 5);
+        assert!(local_range(&mapped, 44) > 5);
+    }
+
+    #[test]
+    fn test_tone_map_drago_preserves_detail() {
+        let image = hdr_scene();
+        let mapped = tone_map_drago(&image, 0.85);
+        assert!(local_range(&mapped, 12) > 5);
+        assert!(local_range(&mapped, 44) > 5);
+    }
+
+    #[bench]
+    fn bench_tone_map_reinhard_local(b: &mut Bencher) {
+        let image: Image<Rgb<f32>> = ImageBuffer::from_fn(200, 200, |x, y| {
+            let l = 0.01 * 1.05f32.powi(((x + y) / 2) as i32);
+            Rgb([l, l, l])
+        });
+        b.iter(|| {
+            let mapped = tone_map_reinhard_local(&image, 0.18);
+            black_box(mapped);
+        });
+    }
+
+    #[bench]
+    fn bench_exposure_fusion(b: &mut Bencher) {
+        let images: Vec<RgbImage> = (0..3)
+            .map(|i| {
+                let mut image = rgb_bench_image(200, 200);
+                for c in image.iter_mut() {
+                    *c = (*c as u32 * (i + 1) / 2).min(255) as u8;
+                }
+                image
+            })
+            .collect();
+        b.iter(|| {
+            let fused = exposure_fusion(&images, 1.0, 1.0, 1.0);
+            black_box(fused);
+        });
+    }
+}
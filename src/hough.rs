@@ -3,7 +3,7 @@
 //! [Hough transform]: https://en.wikipedia.org/wiki/Hough_transform
 
 use crate::definitions::Image;
-use crate::drawing::draw_line_segment_mut;
+use crate::drawing::{draw_line_segment_mut, Canvas};
 use crate::suppress::suppress_non_maximum;
 use image::{GenericImage, GenericImageView, GrayImage, ImageBuffer, Luma, Pixel};
 use std::f32;
@@ -108,21 +108,23 @@ where
 /// Draws each element of `lines` on `image` in the provided `color`.
 ///
 /// See ./examples/hough.rs for example usage.
-pub fn draw_polar_lines_mut<P>(image: &mut Image<P>, lines: &[PolarLine], color: P)
+pub fn draw_polar_lines_mut<C>(canvas: &mut C, lines: &[PolarLine], color: C::Pixel)
 where
-    P: Pixel + 'static,
+    C: Canvas,
+    C::Pixel: 'static,
 {
     for line in lines {
-        draw_polar_line(image, *line, color);
+        draw_polar_line(canvas, *line, color);
     }
 }
 
-fn draw_polar_line<P>(image: &mut Image<P>, line: PolarLine, color: P)
+fn draw_polar_line<C>(canvas: &mut C, line: PolarLine, color: C::Pixel)
 where
-    P: Pixel + 'static,
+    C: Canvas,
+    C::Pixel: 'static,
 {
-    if let Some((s, e)) = intersection_points(line, image.width(), image.height()) {
-        draw_line_segment_mut(image, s, e, color);
+    if let Some((s, e)) = intersection_points(line, canvas.width(), canvas.height()) {
+        draw_line_segment_mut(canvas, s, e, color);
     }
 }
 
@@ -3,7 +3,9 @@
 
 use crate::definitions::Image;
 use crate::map::{ChannelMap, WithChannel};
-use image::{GenericImageView, GrayImage, Luma, Pixel, Primitive, Rgb, Rgba};
+use crate::rect::Rect;
+use image::{Bgr, Bgra, GenericImageView, GrayImage, Luma, LumaA, Pixel, Primitive, Rgb, Rgba};
+use std::cmp::min;
 use std::ops::AddAssign;
 
 /// Computes the 2d running sum of an image. Channels are summed independently.
@@ -21,6 +23,12 @@ use std::ops::AddAssign;
 /// the sum of the pixels in this rectangle is
 /// I(r + 1, b + 1) - I(r + 1, t) - I(l, b + 1) + I(l, t).
 ///
+/// The accumulator type `T` must be large enough to hold the sum of all pixels in the image.
+/// A `u32` accumulator can hold the sum of any image with at most `(2^32 - 1) / 255`
+/// (approximately 16.8 million) pixels, and a `u64` accumulator the sum of any image that fits
+/// in memory. `f32` accumulators do not overflow, but lose precision for large images.
+/// In debug builds this function panics if the sum of the image overflows `T`.
+///
 /// # Examples
 /// ```
 /// # extern crate image;
@@ -60,6 +68,11 @@ where
 ///
 /// See the [`integral_image`](fn.integral_image.html) documentation for more information on integral images.
 ///
+/// Squared intensities can be as large as 255^2, so a `u32` accumulator can only hold the sum of
+/// images with at most `(2^32 - 1) / 255^2` (approximately 66,000) pixels. Use a `u64` or `f64`
+/// accumulator for larger images. In debug builds this function panics if the sum of the image
+/// overflows `T`.
+///
 /// # Examples
 /// ```
 /// # extern crate image;
@@ -124,7 +137,7 @@ where
             let input = unsafe { image.unsafe_get_pixel(x, y) };
             for (s, c) in sum.iter_mut().zip(input.channels()) {
                 let pix: T = (*c).into();
-                *s += if square { pix * pix } else { pix };
+                *s = checked_sum(*s, if square { pix * pix } else { pix });
             }
 
             // JUSTIFICATION
@@ -139,7 +152,8 @@ where
             let current = out.get_pixel_mut(x + 1, y + 1);
             // Using zip here makes this slower.
             for c in 0..P::CHANNEL_COUNT {
-                current.channels_mut()[c as usize] = above.channels()[c as usize] + sum[c as usize];
+                current.channels_mut()[c as usize] =
+                    checked_sum(above.channels()[c as usize], sum[c as usize]);
             }
         }
     }
@@ -147,6 +161,16 @@ where
     out
 }
 
+// Adds two non-negative values. In debug builds, panics if the result overflows T.
+#[inline(always)]
+fn checked_sum<T: Primitive>(a: T, b: T) -> T {
+    debug_assert!(
+        a <= T::max_value() - b,
+        "integral image overflowed the accumulator type, use a wider type such as u64 or f64"
+    );
+    a + b
+}
+
 /// Hack to get around lack of const generics. See comment on `sum_image_pixels`.
 pub trait ArrayData {
     /// The type of the data for this array.
@@ -226,13 +250,77 @@ impl<T: Primitive + 'static> ArrayData for Rgba<T> {
     }
 }
 
+impl<T: Primitive + 'static> ArrayData for LumaA<T> {
+    type DataType = [T; 2];
+
+    fn data(&self) -> Self::DataType {
+        [self.channels()[0], self.channels()[1]]
+    }
+
+    fn add(lhs: Self::DataType, rhs: Self::DataType) -> Self::DataType {
+        [lhs[0] + rhs[0], lhs[1] + rhs[1]]
+    }
+
+    fn sub(lhs: Self::DataType, rhs: Self::DataType) -> Self::DataType {
+        [lhs[0] - rhs[0], lhs[1] - rhs[1]]
+    }
+}
+
+impl<T: Primitive + 'static> ArrayData for Bgr<T> {
+    type DataType = [T; 3];
+
+    fn data(&self) -> Self::DataType {
+        [self.channels()[0], self.channels()[1], self.channels()[2]]
+    }
+
+    fn add(lhs: Self::DataType, rhs: Self::DataType) -> Self::DataType {
+        [lhs[0] + rhs[0], lhs[1] + rhs[1], lhs[2] + rhs[2]]
+    }
+
+    fn sub(lhs: Self::DataType, rhs: Self::DataType) -> Self::DataType {
+        [lhs[0] - rhs[0], lhs[1] - rhs[1], lhs[2] - rhs[2]]
+    }
+}
+
+impl<T: Primitive + 'static> ArrayData for Bgra<T> {
+    type DataType = [T; 4];
+
+    fn data(&self) -> Self::DataType {
+        [
+            self.channels()[0],
+            self.channels()[1],
+            self.channels()[2],
+            self.channels()[3],
+        ]
+    }
+
+    fn add(lhs: Self::DataType, rhs: Self::DataType) -> Self::DataType {
+        [
+            lhs[0] + rhs[0],
+            lhs[1] + rhs[1],
+            lhs[2] + rhs[2],
+            lhs[3] + rhs[3],
+        ]
+    }
+
+    fn sub(lhs: Self::DataType, rhs: Self::DataType) -> Self::DataType {
+        [
+            lhs[0] - rhs[0],
+            lhs[1] - rhs[1],
+            lhs[2] - rhs[2],
+            lhs[3] - rhs[3],
+        ]
+    }
+}
+
 /// Sums the pixels in positions [left, right] * [top, bottom] in F, where `integral_image` is the
 /// integral image of F.
 ///
 /// The of `ArrayData` here is due to lack of const generics. This library contains
-/// implementations of `ArrayData` for `Luma`, `Rgb` and `Rgba` for any element type `T` that
-/// implements `Primitive`. In that case, this function returns `[T; 1]` for an image
-/// whose pixels are of type `Luma`, `[T; 3]` for `Rgb` pixels and `[T; 4]` for `Rgba` pixels.
+/// implementations of `ArrayData` for `Luma`, `LumaA`, `Rgb`, `Rgba`, `Bgr` and `Bgra` for any
+/// element type `T` that implements `Primitive`. In that case, this function returns `[T; 1]`
+/// for an image whose pixels are of type `Luma`, `[T; 2]` for `LumaA` pixels, `[T; 3]` for
+/// `Rgb` and `Bgr` pixels and `[T; 4]` for `Rgba` and `Bgra` pixels.
 ///
 /// See the [`integral_image`](fn.integral_image.html) documentation for examples.
 pub fn sum_image_pixels<P>(
@@ -256,6 +344,60 @@ where
     P::sub(P::sub(P::add(a, b), c), d)
 }
 
+/// Sums the pixels of F in `rect`, where `integral_image` is the integral image of F.
+/// Channels are summed independently.
+///
+/// This is equivalent to [`sum_image_pixels`](fn.sum_image_pixels.html) for the rectangle
+/// `[rect.left(), rect.right()] * [rect.top(), rect.bottom()]`.
+///
+/// # Panics
+///
+/// If `rect` does not lie within F.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::integral_image::{integral_image, sum_image_pixels_in_rect};
+/// use imageproc::rect::Rect;
+///
+/// let image = rgb_image!(
+///     [1, 10, 100], [2, 20, 200];
+///     [3, 30, 110], [4, 40, 210]);
+///
+/// let integral = integral_image::<_, u32>(&image);
+///
+/// // Sum each channel of the right column
+/// let right_column = Rect::at(1, 0).of_size(1, 2);
+/// assert_eq!(sum_image_pixels_in_rect(&integral, right_column), [6, 60, 410]);
+/// # }
+/// ```
+pub fn sum_image_pixels_in_rect<P>(integral_image: &Image<P>, rect: Rect) -> P::DataType
+where
+    P: Pixel + ArrayData + Copy + 'static,
+{
+    let (width, height) = (integral_image.width() - 1, integral_image.height() - 1);
+    assert!(
+        rect.left() >= 0
+            && rect.top() >= 0
+            && rect.right() < width as i32
+            && rect.bottom() < height as i32,
+        "{:?} is outside image of size {}x{}",
+        rect,
+        width,
+        height
+    );
+    sum_image_pixels(
+        integral_image,
+        rect.left() as u32,
+        rect.top() as u32,
+        rect.right() as u32,
+        rect.bottom() as u32,
+    )
+}
+
 /// Computes the variance of [left, right] * [top, bottom] in F, where `integral_image` is the
 /// integral image of F and `integral_squared_image` is the integral image of the squares of the
 /// pixels in F.
@@ -275,8 +417,8 @@ where
 ///     1, 2, 3;
 ///     4, 5, 6);
 ///
-/// let integral = integral_image(&image);
-/// let integral_squared = integral_squared_image(&image);
+/// let integral = integral_image::<_, u32>(&image);
+/// let integral_squared = integral_squared_image::<_, u32>(&image);
 ///
 /// // Compute the variance of the pixels in the right two columns
 /// let mean: f64 = (2.0 + 3.0 + 5.0 + 6.0) / 4.0;
@@ -288,19 +430,238 @@ where
 /// assert_eq!(variance(&integral, &integral_squared, 1, 0, 2, 1), var);
 /// # }
 /// ```
-pub fn variance(
-    integral_image: &Image<Luma<u32>>,
-    integral_squared_image: &Image<Luma<u32>>,
+pub fn variance<T>(
+    integral_image: &Image<Luma<T>>,
+    integral_squared_image: &Image<Luma<T>>,
     left: u32,
     top: u32,
     right: u32,
     bottom: u32,
-) -> f64 {
+) -> f64
+where
+    T: Primitive + 'static,
+{
     // TODO: same improvements as for sum_image_pixels, plus check that the given rect is valid.
     let n = (right - left + 1) as f64 * (bottom - top + 1) as f64;
     let sum_sq = sum_image_pixels(integral_squared_image, left, top, right, bottom)[0];
     let sum = sum_image_pixels(integral_image, left, top, right, bottom)[0];
-    (sum_sq as f64 - (sum as f64).powi(2) / n) / n
+    let (sum_sq, sum) = (sum_sq.to_f64().unwrap(), sum.to_f64().unwrap());
+    (sum_sq - sum.powi(2) / n) / n
+}
+
+/// Computes the mean and variance of the intensities in the (2 * `block_radius` + 1) square
+/// block centred on each pixel of an image. Blocks are truncated at the image boundaries.
+///
+/// Returns images of local means and local variances, in that order. Both are computed from
+/// integral images of the image and its squares, so the running time does not depend on
+/// `block_radius`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::integral_image::local_mean_variance;
+///
+/// let image = gray_image!(
+///     2, 4, 6;
+///     4, 6, 8);
+///
+/// let (mean, variance) = local_mean_variance(&image, 1);
+///
+/// let expected_mean = gray_image!(type: f32,
+///     4.0, 5.0, 6.0;
+///     4.0, 5.0, 6.0);
+///
+/// let expected_variance = gray_image!(type: f32,
+///     2.0, 11.0 / 3.0, 2.0;
+///     2.0, 11.0 / 3.0, 2.0);
+///
+/// assert_pixels_eq_within!(mean, expected_mean, 1e-5);
+/// assert_pixels_eq_within!(variance, expected_variance, 1e-5);
+/// # }
+/// ```
+pub fn local_mean_variance(
+    image: &GrayImage,
+    block_radius: u32,
+) -> (Image<Luma<f32>>, Image<Luma<f32>>) {
+    let (width, height) = image.dimensions();
+    let mut means = Image::new(width, height);
+    let mut variances = Image::new(width, height);
+    if width == 0 || height == 0 {
+        return (means, variances);
+    }
+
+    let integral = integral_image::<_, u64>(image);
+    let integral_squared = integral_squared_image::<_, u64>(image);
+
+    for y in 0..height {
+        let (top, bottom) = (
+            y.saturating_sub(block_radius),
+            min(height - 1, y + block_radius),
+        );
+        for x in 0..width {
+            let (left, right) = (
+                x.saturating_sub(block_radius),
+                min(width - 1, x + block_radius),
+            );
+            let n = (right - left + 1) as f64 * (bottom - top + 1) as f64;
+            let sum = sum_image_pixels(&integral, left, top, right, bottom)[0] as f64;
+            let sum_sq = sum_image_pixels(&integral_squared, left, top, right, bottom)[0] as f64;
+            let mean = sum / n;
+            // Clamp to avoid small negative values due to rounding errors
+            let variance = (sum_sq / n - mean * mean).max(0.0);
+            means.put_pixel(x, y, Luma([mean as f32]));
+            variances.put_pixel(x, y, Luma([variance as f32]));
+        }
+    }
+
+    (means, variances)
+}
+
+/// Computes the 45° rotated (tilted) integral image of an image, as used to evaluate
+/// rotated Haar-like features in constant time.
+///
+/// The tilted integral image T has width and height one greater than its source image F.
+/// Treating (x, y) as the top left corner of pixel (x, y), T(x, y) is the sum of all pixels
+/// in F whose centres lie in the region above (x, y) bounded by the lines at 45° through
+/// (x, y), i.e. the sum of F(x', y') for y' < y and |x' + 0.5 - x| <= y - y' - 0.5.
+///
+/// See [`sum_tilted_image_pixels`](fn.sum_tilted_image_pixels.html) for how to use this
+/// to sum the pixels in a rotated rectangle.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::integral_image::tilted_integral_image;
+///
+/// let image = gray_image!(
+///     1, 2, 3;
+///     4, 5, 6);
+///
+/// let tilted = gray_image!(type: u32,
+///     0,  0,  0,  0;
+///     1,  3,  5,  3;
+///     7, 15, 17, 11);
+///
+/// assert_pixels_eq!(tilted_integral_image::<u32>(&image), tilted);
+/// # }
+/// ```
+pub fn tilted_integral_image<T>(image: &GrayImage) -> Image<Luma<T>>
+where
+    T: From<u8> + Primitive + 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut out = Image::<Luma<T>>::new(width + 1, height + 1);
+
+    // The cones for corners near the left and right edges of the image depend on cones
+    // for corners outside the image, so we compute rows of a wider buffer with `height`
+    // extra columns on each side. The corner at x has index x + height.
+    let padding = height as usize;
+    let row_len = width as usize + 1 + 2 * padding;
+    let mut two_above = vec![T::zero(); row_len];
+    let mut above = vec![T::zero(); row_len];
+    let mut current = vec![T::zero(); row_len];
+
+    let pixel = |x: i64, y: u32| -> T {
+        if x >= 0 && x < width as i64 {
+            image.get_pixel(x as u32, y)[0].into()
+        } else {
+            T::zero()
+        }
+    };
+
+    for y in 1..=height {
+        for i in 0..row_len {
+            let x = i as i64 - padding as i64;
+            let left = if i > 0 { above[i - 1] } else { T::zero() };
+            let right = if i + 1 < row_len {
+                above[i + 1]
+            } else {
+                T::zero()
+            };
+            let new_row = pixel(x - 1, y - 1) + pixel(x, y - 1);
+            current[i] = left + right + new_row - two_above[i];
+        }
+        for x in 0..=width {
+            out.put_pixel(x, y, Luma([current[x as usize + padding]]));
+        }
+        std::mem::swap(&mut two_above, &mut above);
+        std::mem::swap(&mut above, &mut current);
+    }
+
+    out
+}
+
+/// Sums the pixels of F in a rectangle rotated by 45°, where `tilted_integral_image` is the
+/// [tilted integral image](fn.tilted_integral_image.html) of F.
+///
+/// Treating (x, y) as the top left corner of pixel (x, y), the rectangle has top corner
+/// (x, y) and sides which extend `width` steps down and to the right and `height` steps down
+/// and to the left, i.e. its other corners are (x + width, y + width), (x - height, y + height)
+/// and (x + width - height, y + width + height). The sum includes the pixels whose centres lie
+/// inside the rectangle or on its lower two sides, of which there are `2 * width * height`.
+///
+/// # Panics
+///
+/// If the rectangle does not lie within F.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::integral_image::{tilted_integral_image, sum_tilted_image_pixels};
+///
+/// let image = gray_image!(
+///     1, 2, 3, 4;
+///     5, 6, 7, 8;
+///     9, 8, 7, 6);
+///
+/// let tilted = tilted_integral_image::<u32>(&image);
+///
+/// // The square with corners (2, 0), (3, 1), (2, 2) and (1, 1) has the centres of
+/// // pixels (1, 0) and (2, 0) on its upper sides and of (1, 1) and (2, 1) on its lower sides
+/// assert_eq!(sum_tilted_image_pixels(&tilted, 2, 0, 1, 1), 6 + 7);
+///
+/// // The rectangle with corners (2, 0), (4, 2), (3, 3) and (1, 1) contains
+/// // pixels (1, 1), (2, 1), (2, 2) and (3, 2)
+/// assert_eq!(sum_tilted_image_pixels(&tilted, 2, 0, 2, 1), 6 + 7 + 7 + 6);
+/// # }
+/// ```
+pub fn sum_tilted_image_pixels<T>(
+    tilted_integral_image: &Image<Luma<T>>,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> T
+where
+    T: Primitive + 'static,
+{
+    let (image_width, image_height) = (
+        tilted_integral_image.width() - 1,
+        tilted_integral_image.height() - 1,
+    );
+    assert!(
+        height <= x && x + width <= image_width && y + width + height <= image_height,
+        "rotated rectangle with top corner ({}, {}), width {} and height {} is outside image of size {}x{}",
+        x,
+        y,
+        width,
+        height,
+        image_width,
+        image_height
+    );
+    let t = |x: u32, y: u32| tilted_integral_image.get_pixel(x, y)[0];
+    t(x + width - height, y + width + height) + t(x, y)
+        - t(x - height, y + height)
+        - t(x + width, y + width)
 }
 
 /// Computes the running sum of one row of image, padded
@@ -456,7 +817,7 @@ mod tests {
     use crate::property_testing::GrayTestImage;
     use crate::utils::{gray_bench_image, pixel_diff_summary, rgb_bench_image};
     use ::test;
-    use image::{GenericImage, ImageBuffer, Luma};
+    use image::{GenericImage, ImageBuffer, Luma, RgbImage};
     use quickcheck::{quickcheck, TestResult};
 
     #[test]
@@ -543,6 +904,104 @@ mod tests {
         assert_eq!(sum_image_pixels(&integral, 1, 1, 1, 1), [10, 11, 12]);
     }
 
+    #[test]
+    fn test_integral_image_accumulator_types() {
+        let image = gray_image!(
+            1, 2, 3;
+            4, 5, 6);
+
+        let wide = integral_squared_image::<_, u64>(&image);
+        let float = integral_squared_image::<_, f64>(&image);
+        assert_eq!(sum_image_pixels(&wide, 0, 0, 2, 1)[0], 91);
+        assert_eq!(sum_image_pixels(&float, 0, 0, 2, 1)[0], 91.0);
+
+        let integral = integral_image::<_, f64>(&image);
+        let variance = variance(&integral, &float, 0, 0, 2, 1);
+        assert!((variance - 35.0 / 12.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_integral_squared_image_small_values_do_not_overflow() {
+        let image = GrayImage::from_pixel(300, 300, Luma([2u8]));
+        let integral = integral_squared_image::<_, u32>(&image);
+        assert_eq!(integral.get_pixel(300, 300)[0], 300 * 300 * 4);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_integral_squared_image_overflow_check() {
+        let image = GrayImage::from_pixel(300, 300, Luma([255u8]));
+        integral_squared_image::<_, u32>(&image);
+    }
+
+    #[test]
+    fn test_integral_squared_image_large_u64() {
+        let image = GrayImage::from_pixel(300, 300, Luma([255u8]));
+        let integral = integral_squared_image::<_, u64>(&image);
+        assert_eq!(integral.get_pixel(300, 300)[0], 300 * 300 * 255 * 255);
+    }
+
+    #[test]
+    fn test_local_mean_variance_matches_variance() {
+        let image = GrayImage::from_fn(7, 5, |x, y| Luma([(x * x * 17 + y * 31) as u8]));
+        let integral = integral_image::<_, u64>(&image);
+        let integral_squared = integral_squared_image::<_, u64>(&image);
+        let (means, variances) = local_mean_variance(&image, 2);
+
+        for (x, y, m) in means.enumerate_pixels() {
+            let (left, top) = (x.saturating_sub(2), y.saturating_sub(2));
+            let (right, bottom) = (min(6, x + 2), min(4, y + 2));
+            let n = ((right - left + 1) * (bottom - top + 1)) as f32;
+            let sum = sum_image_pixels(&integral, left, top, right, bottom)[0] as f32;
+            let expected = variance(&integral, &integral_squared, left, top, right, bottom);
+
+            assert!((m[0] - sum / n).abs() < 1e-3);
+            assert!((variances.get_pixel(x, y)[0] - expected as f32).abs() < 1e-2);
+        }
+    }
+
+    #[bench]
+    fn bench_local_mean_variance(b: &mut test::Bencher) {
+        let image = gray_bench_image(500, 500);
+        b.iter(|| {
+            let stats = local_mean_variance(&image, 10);
+            test::black_box(stats);
+        });
+    }
+
+    #[test]
+    fn test_sum_image_pixels_luma_alpha() {
+        let image: Image<LumaA<u8>> = ImageBuffer::from_fn(3, 2, |x, y| {
+            LumaA([(x + 3 * y) as u8, 10 * (x + 3 * y) as u8])
+        });
+        let integral = integral_image::<_, u32>(&image);
+        assert_eq!(sum_image_pixels(&integral, 0, 0, 2, 1), [15, 150]);
+        assert_eq!(sum_image_pixels(&integral, 1, 1, 2, 1), [9, 90]);
+    }
+
+    #[test]
+    fn test_sum_image_pixels_in_rect_matches_sum_image_pixels() {
+        let image = RgbImage::from_fn(5, 4, |x, y| Rgb([x as u8, y as u8, (x * y) as u8]));
+        let integral = integral_image::<_, u64>(&image);
+        for &(left, top, right, bottom) in &[(0, 0, 4, 3), (1, 2, 3, 2), (4, 3, 4, 3)] {
+            let rect =
+                Rect::at(left as i32, top as i32).of_size(right - left + 1, bottom - top + 1);
+            assert_eq!(
+                sum_image_pixels_in_rect(&integral, rect),
+                sum_image_pixels(&integral, left, top, right, bottom)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sum_image_pixels_in_rect_outside_image() {
+        let image = GrayImage::new(3, 3);
+        let integral = integral_image::<_, u32>(&image);
+        sum_image_pixels_in_rect(&integral, Rect::at(1, 1).of_size(3, 1));
+    }
+
     #[bench]
     fn bench_integral_image_gray(b: &mut test::Bencher) {
         let image = gray_bench_image(500, 500);
@@ -600,6 +1059,76 @@ mod tests {
         quickcheck(prop as fn(GrayTestImage) -> TestResult);
     }
 
+    fn tilted_integral_image_ref(image: &GrayImage) -> Image<Luma<u32>> {
+        let (width, height) = image.dimensions();
+        ImageBuffer::from_fn(width + 1, height + 1, |x, y| {
+            let mut sum = 0u32;
+            for (px, py, p) in image.enumerate_pixels() {
+                let dx = (2 * px as i64 + 1 - 2 * x as i64).abs();
+                if py < y && dx < 2 * (y - py) as i64 {
+                    sum += p[0] as u32;
+                }
+            }
+            Luma([sum])
+        })
+    }
+
+    #[test]
+    fn test_tilted_integral_image_matches_reference_implementation() {
+        fn prop(image: GrayTestImage) -> TestResult {
+            let expected = tilted_integral_image_ref(&image.0);
+            let actual = tilted_integral_image::<u32>(&image.0);
+            match pixel_diff_summary(&actual, &expected) {
+                None => TestResult::passed(),
+                Some(err) => TestResult::error(err),
+            }
+        }
+        quickcheck(prop as fn(GrayTestImage) -> TestResult);
+    }
+
+    #[test]
+    fn test_sum_tilted_image_pixels() {
+        let image = GrayImage::from_fn(9, 8, |x, y| Luma([(x * 7 + y * 13) as u8]));
+        let tilted = tilted_integral_image::<u32>(&image);
+
+        for &(x, y, w, h) in &[(2, 0, 1, 1), (3, 1, 2, 3), (4, 0, 5, 3), (0, 3, 4, 0)] {
+            let (x, y, w, h): (i64, i64, i64, i64) = (x, y, w, h);
+            let mut expected = 0;
+            let mut count = 0;
+            for (px, py, p) in image.enumerate_pixels() {
+                // Pixel centre relative to the top corner, in rotated coordinates
+                let (cx, cy) = (px as i64 * 2 + 1 - 2 * x, py as i64 * 2 + 1 - 2 * y);
+                let (u, v) = (cx + cy, cy - cx);
+                if u > 0 && u <= 4 * w && v > 0 && v <= 4 * h {
+                    expected += p[0] as u32;
+                    count += 1;
+                }
+            }
+            assert_eq!(count, 2 * w * h);
+            assert_eq!(
+                sum_tilted_image_pixels(&tilted, x as u32, y as u32, w as u32, h as u32),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sum_tilted_image_pixels_outside_image() {
+        let image = GrayImage::new(4, 4);
+        let tilted = tilted_integral_image::<u32>(&image);
+        sum_tilted_image_pixels(&tilted, 1, 0, 1, 2);
+    }
+
+    #[bench]
+    fn bench_tilted_integral_image(b: &mut test::Bencher) {
+        let image = gray_bench_image(500, 500);
+        b.iter(|| {
+            let integral = tilted_integral_image::<u32>(&image);
+            test::black_box(integral);
+        });
+    }
+
     #[bench]
     fn bench_row_running_sum(b: &mut test::Bencher) {
         let image = gray_bench_image(1000, 1);
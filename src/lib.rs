@@ -31,36 +31,49 @@ extern crate assert_approx_eq;
 
 #[macro_use]
 pub mod utils;
+pub mod blobs;
 pub mod contours;
 pub mod contrast;
 pub mod corners;
 pub mod definitions;
+pub mod detection;
 pub mod distance_transform;
 pub mod drawing;
 pub mod edges;
+pub mod error;
 pub mod filter;
 pub mod geometric_transformations;
 pub mod geometry;
+mod gpu;
 pub mod gradients;
 pub mod haar;
+#[cfg(feature = "half")]
+pub mod half_precision;
+pub mod hdr;
 pub mod hog;
 pub mod hough;
 pub mod integral_image;
 pub mod local_binary_patterns;
 pub mod map;
 pub mod math;
+pub mod moments;
 pub mod morphology;
 pub mod noise;
+mod parallel;
+pub mod patches;
 pub mod pixelops;
 pub mod point;
 #[cfg(any(feature = "property-testing", test))]
 pub mod property_testing;
+pub mod pyramid;
 pub mod rect;
 pub mod region_labelling;
 pub mod seam_carving;
+pub mod segmentation;
 pub mod stats;
 pub mod suppress;
 pub mod template_matching;
+pub mod tiling;
 pub mod union_find;
 #[cfg(feature = "display-window")]
 pub mod window;
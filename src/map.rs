@@ -1,8 +1,15 @@
 //! Functions for mapping over pixels, colors or subpixels of images.
+//!
+//! The `_parallel` variants compute the rows of their output in parallel when the `rayon`
+//! feature is enabled.
 
-use image::{Bgr, Bgra, GenericImage, ImageBuffer, Luma, LumaA, Pixel, Primitive, Rgb, Rgba};
+use image::{
+    Bgr, Bgra, GenericImage, GenericImageView, ImageBuffer, Luma, LumaA, Pixel, Primitive, Rgb,
+    Rgba,
+};
 
-use crate::definitions::Image;
+use crate::definitions::{Image, MaybeSend, MaybeSync};
+use crate::parallel::for_each_row;
 
 /// The type obtained by replacing the channel type of a given `Pixel` type.
 /// The output type must have the same name of channels as the input type, or
@@ -88,7 +95,7 @@ where
 /// ```
 pub fn map_subpixels<I, P, F, S>(image: &I, f: F) -> Image<ChannelMap<P, S>>
 where
-    I: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
     P: WithChannel<S> + 'static,
     S: Primitive + 'static,
     F: Fn(P::Subpixel) -> S,
@@ -113,6 +120,32 @@ where
     out
 }
 
+/// Equivalent to [`map_subpixels`](fn.map_subpixels.html), but computes the rows of the output in
+/// parallel if the `rayon` feature is enabled. This requires `f` and the input
+/// image to be `Sync`.
+pub fn map_subpixels_parallel<I, P, F, S>(image: &I, f: F) -> Image<ChannelMap<P, S>>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: WithChannel<S> + 'static,
+    S: Primitive + MaybeSend + 'static,
+    F: Fn(P::Subpixel) -> S + MaybeSync,
+{
+    let (width, height) = image.dimensions();
+    let mut out: ImageBuffer<ChannelMap<P, S>, Vec<S>> = ImageBuffer::new(width, height);
+    let channels = P::CHANNEL_COUNT as usize;
+
+    for_each_row(&mut out, width as usize * channels, |y, row| {
+        for (x, out_channels) in row.chunks_exact_mut(channels).enumerate() {
+            let pix = unsafe { image.unsafe_get_pixel(x as u32, y as u32) };
+            for (out_c, &c) in out_channels.iter_mut().zip(pix.channels()) {
+                *out_c = f(c);
+            }
+        }
+    });
+
+    out
+}
+
 /// Applies `f` to each subpixel of the input image in place.
 ///
 /// # Examples
@@ -186,7 +219,7 @@ where
 /// ```
 pub fn map_colors<I, P, Q, F>(image: &I, f: F) -> Image<Q>
 where
-    I: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
     P: Pixel,
     Q: Pixel + 'static,
     F: Fn(P) -> Q,
@@ -206,6 +239,31 @@ where
     out
 }
 
+/// Equivalent to [`map_colors`](fn.map_colors.html), but computes the rows of the output in
+/// parallel if the `rayon` feature is enabled. This requires `f` and the input
+/// image to be `Sync`.
+pub fn map_colors_parallel<I, P, Q, F>(image: &I, f: F) -> Image<Q>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel,
+    Q: Pixel + 'static,
+    Q::Subpixel: MaybeSend,
+    F: Fn(P) -> Q + MaybeSync,
+{
+    let (width, height) = image.dimensions();
+    let mut out: ImageBuffer<Q, Vec<Q::Subpixel>> = ImageBuffer::new(width, height);
+    let channels = Q::CHANNEL_COUNT as usize;
+
+    for_each_row(&mut out, width as usize * channels, |y, row| {
+        for (x, out_pixel) in row.chunks_exact_mut(channels).enumerate() {
+            let pix = unsafe { image.unsafe_get_pixel(x as u32, y as u32) };
+            *Q::from_slice_mut(out_pixel) = f(pix);
+        }
+    });
+
+    out
+}
+
 /// Applies `f` to the color of each pixel in the input image in place.
 ///
 /// # Examples
@@ -285,8 +343,8 @@ where
 /// ```
 pub fn map_colors2<I, J, P, Q, R, F>(image1: &I, image2: &J, f: F) -> Image<R>
 where
-    I: GenericImage<Pixel = P>,
-    J: GenericImage<Pixel = Q>,
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = Q>,
     P: Pixel,
     Q: Pixel,
     R: Pixel + 'static,
@@ -310,6 +368,179 @@ where
     out
 }
 
+/// Equivalent to [`map_colors2`](fn.map_colors2.html), but computes the rows of the output in
+/// parallel if the `rayon` feature is enabled. This requires `f` and the input
+/// images to be `Sync`.
+pub fn map_colors2_parallel<I, J, P, Q, R, F>(image1: &I, image2: &J, f: F) -> Image<R>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    J: GenericImageView<Pixel = Q> + MaybeSync,
+    P: Pixel,
+    Q: Pixel,
+    R: Pixel + 'static,
+    R::Subpixel: MaybeSend,
+    F: Fn(P, Q) -> R + MaybeSync,
+{
+    assert_eq!(image1.dimensions(), image2.dimensions());
+
+    let (width, height) = image1.dimensions();
+    let mut out: ImageBuffer<R, Vec<R::Subpixel>> = ImageBuffer::new(width, height);
+    let channels = R::CHANNEL_COUNT as usize;
+
+    for_each_row(&mut out, width as usize * channels, |y, row| {
+        for (x, out_pixel) in row.chunks_exact_mut(channels).enumerate() {
+            let (p, q) = unsafe {
+                (
+                    image1.unsafe_get_pixel(x as u32, y as u32),
+                    image2.unsafe_get_pixel(x as u32, y as u32),
+                )
+            };
+            *R::from_slice_mut(out_pixel) = f(p, q);
+        }
+    });
+
+    out
+}
+
+/// Applies `f` to each pair of corresponding subpixels of the input images.
+///
+/// Requires `image1` and `image2` to have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::map::zip_map;
+///
+/// let image1 = rgb_image!(
+///     [1, 2, 3], [4, 5, 6]);
+///
+/// let image2 = rgb_image!(
+///     [3, 2, 1], [9, 9, 9]);
+///
+/// let difference = rgb_image!(type: i16,
+///     [-2, 0, 2], [-5, -4, -3]);
+///
+/// assert_pixels_eq!(
+///     zip_map(&image1, &image2, |p, q| p as i16 - q as i16),
+///     difference
+/// );
+/// # }
+/// ```
+pub fn zip_map<I, J, P, F, S>(image1: &I, image2: &J, f: F) -> Image<ChannelMap<P, S>>
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: WithChannel<S> + 'static,
+    S: Primitive + 'static,
+    F: Fn(P::Subpixel, P::Subpixel) -> S,
+{
+    assert_eq!(image1.dimensions(), image2.dimensions());
+
+    let (width, height) = image1.dimensions();
+    let mut out: ImageBuffer<ChannelMap<P, S>, Vec<S>> = ImageBuffer::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (p, q) = unsafe { (image1.unsafe_get_pixel(x, y), image2.unsafe_get_pixel(x, y)) };
+            let out_channels = out.get_pixel_mut(x, y).channels_mut();
+            for c in 0..P::CHANNEL_COUNT as usize {
+                out_channels[c] = f(p.channels()[c], q.channels()[c]);
+            }
+        }
+    }
+
+    out
+}
+
+/// Equivalent to [`zip_map`](fn.zip_map.html), but computes the rows of the output in
+/// parallel if the `rayon` feature is enabled. This requires `f` and the input
+/// images to be `Sync`.
+pub fn zip_map_parallel<I, J, P, F, S>(image1: &I, image2: &J, f: F) -> Image<ChannelMap<P, S>>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    J: GenericImageView<Pixel = P> + MaybeSync,
+    P: WithChannel<S> + 'static,
+    S: Primitive + MaybeSend + 'static,
+    F: Fn(P::Subpixel, P::Subpixel) -> S + MaybeSync,
+{
+    assert_eq!(image1.dimensions(), image2.dimensions());
+
+    let (width, height) = image1.dimensions();
+    let mut out: ImageBuffer<ChannelMap<P, S>, Vec<S>> = ImageBuffer::new(width, height);
+    let channels = P::CHANNEL_COUNT as usize;
+
+    for_each_row(&mut out, width as usize * channels, |y, row| {
+        for (x, out_channels) in row.chunks_exact_mut(channels).enumerate() {
+            let (p, q) = unsafe {
+                (
+                    image1.unsafe_get_pixel(x as u32, y as u32),
+                    image2.unsafe_get_pixel(x as u32, y as u32),
+                )
+            };
+            for (c, out_c) in out_channels.iter_mut().enumerate() {
+                *out_c = f(p.channels()[c], q.channels()[c]);
+            }
+        }
+    });
+
+    out
+}
+
+/// Applies `f` to each pair of corresponding subpixels of the input images, writing
+/// the results into `image1`.
+///
+/// Requires `image1` and `image2` to have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::map::zip_map_mut;
+///
+/// let mut image1 = gray_image!(
+///     1, 2;
+///     3, 4);
+///
+/// let image2 = gray_image!(
+///     10, 20;
+///     30, 40);
+///
+/// let want = gray_image!(
+///     11, 22;
+///     33, 44);
+///
+/// zip_map_mut(&mut image1, &image2, |p, q| p + q);
+///
+/// assert_pixels_eq!(
+///     image1,
+///     want);
+/// # }
+/// ```
+pub fn zip_map_mut<I, J, P, F>(image1: &mut I, image2: &J, f: F)
+where
+    I: GenericImage<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+    F: Fn(P::Subpixel, P::Subpixel) -> P::Subpixel,
+{
+    assert_eq!(image1.dimensions(), image2.dimensions());
+
+    let (width, height) = image1.dimensions();
+
+    for y in 0..height {
+        for x in 0..width {
+            let q = unsafe { image2.unsafe_get_pixel(x, y) };
+            let p = image1.get_pixel_mut(x, y);
+            *p = p.map2(&q, &f);
+        }
+    }
+}
+
 /// Applies `f` to each pixel in the input image.
 ///
 /// # Examples
@@ -338,7 +569,7 @@ where
 /// ```
 pub fn map_pixels<I, P, Q, F>(image: &I, f: F) -> Image<Q>
 where
-    I: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
     P: Pixel,
     Q: Pixel + 'static,
     F: Fn(u32, u32, P) -> Q,
@@ -358,6 +589,33 @@ where
     out
 }
 
+/// Equivalent to [`map_pixels`](fn.map_pixels.html), but computes the rows of the output in
+/// parallel if the `rayon` feature is enabled. This requires `f` and the input
+/// image to be `Sync`.
+pub fn map_pixels_parallel<I, P, Q, F>(image: &I, f: F) -> Image<Q>
+where
+    I: GenericImageView<Pixel = P> + MaybeSync,
+    P: Pixel,
+    Q: Pixel + 'static,
+    Q::Subpixel: MaybeSend,
+    F: Fn(u32, u32, P) -> Q + MaybeSync,
+{
+    let (width, height) = image.dimensions();
+    let mut out: ImageBuffer<Q, Vec<Q::Subpixel>> = ImageBuffer::new(width, height);
+    let channels = Q::CHANNEL_COUNT as usize;
+
+    for_each_row(&mut out, width as usize * channels, |y, row| {
+        let y = y as u32;
+        for (x, out_pixel) in row.chunks_exact_mut(channels).enumerate() {
+            let x = x as u32;
+            let pix = unsafe { image.unsafe_get_pixel(x, y) };
+            *Q::from_slice_mut(out_pixel) = f(x, y, pix);
+        }
+    });
+
+    out
+}
+
 /// Applies `f` to each pixel in the input image in place.
 ///
 /// # Examples
@@ -429,7 +687,7 @@ where
 /// ```
 pub fn red_channel<I, C>(image: &I) -> Image<Luma<C>>
 where
-    I: GenericImage<Pixel = Rgb<C>>,
+    I: GenericImageView<Pixel = Rgb<C>>,
     C: Primitive + 'static,
 {
     map_colors(image, |p| Luma([p[0]]))
@@ -460,7 +718,7 @@ where
 /// ```
 pub fn as_red_channel<I, C>(image: &I) -> Image<Rgb<C>>
 where
-    I: GenericImage<Pixel = Luma<C>>,
+    I: GenericImageView<Pixel = Luma<C>>,
     C: Primitive + 'static,
 {
     map_colors(image, |p| {
@@ -495,7 +753,7 @@ where
 /// ```
 pub fn green_channel<I, C>(image: &I) -> Image<Luma<C>>
 where
-    I: GenericImage<Pixel = Rgb<C>>,
+    I: GenericImageView<Pixel = Rgb<C>>,
     C: Primitive + 'static,
 {
     map_colors(image, |p| Luma([p[1]]))
@@ -526,7 +784,7 @@ where
 /// ```
 pub fn as_green_channel<I, C>(image: &I) -> Image<Rgb<C>>
 where
-    I: GenericImage<Pixel = Luma<C>>,
+    I: GenericImageView<Pixel = Luma<C>>,
     C: Primitive + 'static,
 {
     map_colors(image, |p| {
@@ -561,7 +819,7 @@ where
 /// ```
 pub fn blue_channel<I, C>(image: &I) -> Image<Luma<C>>
 where
-    I: GenericImage<Pixel = Rgb<C>>,
+    I: GenericImageView<Pixel = Rgb<C>>,
     C: Primitive + 'static,
 {
     map_colors(image, |p| Luma([p[2]]))
@@ -592,7 +850,7 @@ where
 /// ```
 pub fn as_blue_channel<I, C>(image: &I) -> Image<Rgb<C>>
 where
-    I: GenericImage<Pixel = Luma<C>>,
+    I: GenericImageView<Pixel = Luma<C>>,
     C: Primitive + 'static,
 {
     map_colors(image, |p| {
@@ -601,3 +859,140 @@ where
         Rgb(cs)
     })
 }
+
+/// Splits an image into a grayscale image for each of its channels.
+///
+/// This allows single-channel algorithms to be run separately on each channel of a
+/// color image. The channels can be recombined using
+/// [`merge_channels`](fn.merge_channels.html).
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::map::split_channels;
+///
+/// let image = rgb_image!(
+///     [1, 2, 3], [4, 5, 6]);
+///
+/// let channels = split_channels(&image);
+/// assert_eq!(channels.len(), 3);
+/// assert_pixels_eq!(channels[0], gray_image!(1, 4));
+/// assert_pixels_eq!(channels[1], gray_image!(2, 5));
+/// assert_pixels_eq!(channels[2], gray_image!(3, 6));
+/// # }
+/// ```
+pub fn split_channels<I, P>(image: &I) -> Vec<Image<Luma<P::Subpixel>>>
+where
+    I: GenericImageView<Pixel = P>,
+    P: Pixel,
+    P::Subpixel: 'static,
+{
+    (0..P::CHANNEL_COUNT as usize)
+        .map(|c| map_colors(image, |p| Luma([p.channels()[c]])))
+        .collect()
+}
+
+/// Combines grayscale images into a single image with one channel per input image.
+///
+/// This is the inverse of [`split_channels`](fn.split_channels.html). The output pixel
+/// type is typically determined by inference or a type annotation.
+///
+/// # Panics
+/// If the number of images is not equal to the number of channels of `P`, or if the
+/// images do not all have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::RgbaImage;
+/// use imageproc::map::merge_channels;
+///
+/// let red = gray_image!(255, 0);
+/// let green = gray_image!(0, 255);
+/// let blue = gray_image!(0, 0);
+/// let alpha = gray_image!(255, 128);
+///
+/// let image: RgbaImage = merge_channels(&[red, green, blue, alpha]);
+///
+/// assert_pixels_eq!(image, rgba_image!([255, 0, 0, 255], [0, 255, 0, 128]));
+/// # }
+/// ```
+pub fn merge_channels<P>(channels: &[Image<Luma<P::Subpixel>>]) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: 'static,
+{
+    assert_eq!(
+        channels.len(),
+        P::CHANNEL_COUNT as usize,
+        "expected one image per channel"
+    );
+    let (width, height) = channels[0].dimensions();
+    let mut out = ImageBuffer::new(width, height);
+    for (c, channel) in channels.iter().enumerate() {
+        assert_eq!(channel.dimensions(), (width, height));
+        for (p, q) in out.pixels_mut().zip(channel.pixels()) {
+            let p: &mut P = p;
+            p.channels_mut()[c] = q[0];
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{gray_bench_image, rgb_bench_image};
+    use image::{GrayImage, RgbImage};
+    use std::cell::Cell;
+
+    #[test]
+    fn test_parallel_variants_match_sequential_variants() {
+        let gray = gray_bench_image(37, 23);
+        let rgb = rgb_bench_image(37, 23);
+
+        let f = |c: u8| c as u16 * 3;
+        assert_pixels_eq!(map_subpixels_parallel(&rgb, f), map_subpixels(&rgb, f));
+
+        let f = |p: Rgb<u8>| Luma([p[0] / 2 + p[2] / 2]);
+        assert_pixels_eq!(map_colors_parallel(&rgb, f), map_colors(&rgb, f));
+
+        let f = |p: Rgb<u8>, q: Luma<u8>| Rgb([p[1], q[0], p[0]]);
+        assert_pixels_eq!(
+            map_colors2_parallel(&rgb, &gray, f),
+            map_colors2(&rgb, &gray, f)
+        );
+
+        let f = |a: u8, b: u8| a.wrapping_sub(b);
+        assert_pixels_eq!(zip_map_parallel(&rgb, &rgb, f), zip_map(&rgb, &rgb, f));
+
+        let f = |x: u32, y: u32, p: Luma<u8>| Luma([p[0].wrapping_add((x * y) as u8)]);
+        assert_pixels_eq!(map_pixels_parallel(&gray, f), map_pixels(&gray, f));
+    }
+
+    #[test]
+    fn test_sequential_variants_accept_non_sync_closures() {
+        let gray = gray_bench_image(5, 4);
+        let calls = Cell::new(0);
+
+        let _: GrayImage = map_subpixels(&gray, |c| {
+            calls.set(calls.get() + 1);
+            c
+        });
+        let _: RgbImage = map_colors(&gray, |p| {
+            calls.set(calls.get() + 1);
+            Rgb([p[0]; 3])
+        });
+        let _: GrayImage = map_pixels(&gray, |_, _, p| {
+            calls.set(calls.get() + 1);
+            p
+        });
+        assert_eq!(calls.get(), 60);
+    }
+}
@@ -0,0 +1,336 @@
+//! Functions for computing [image moments], which describe the shape and intensity
+//! distribution of an image or region.
+//!
+//! [image moments]: https://en.wikipedia.org/wiki/Image_moment
+
+use crate::definitions::Image;
+use image::{GrayImage, Luma};
+
+/// The spatial, central and normalized central moments of an image or region, up to
+/// third order.
+///
+/// The spatial moment `m_pq` is the sum of `x^p * y^q * w(x, y)` over all pixels, where
+/// `w(x, y)` is the weight of the pixel at `(x, y)`. The central moment `mu_pq` is the same
+/// sum with `x` and `y` measured from the centroid, and is invariant to translation. The
+/// normalized central moment `nu_pq` is `mu_pq / m00^(1 + (p + q) / 2)`, and is also invariant
+/// to scale.
+///
+/// `mu00 = m00`, `mu10 = mu01 = 0` and `nu00 = 1`, `nu10 = nu01 = 0` so these are not stored.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Moments {
+    /// Spatial moment `m_00`, the total weight.
+    pub m00: f64,
+    /// Spatial moment `m_10`.
+    pub m10: f64,
+    /// Spatial moment `m_01`.
+    pub m01: f64,
+    /// Spatial moment `m_20`.
+    pub m20: f64,
+    /// Spatial moment `m_11`.
+    pub m11: f64,
+    /// Spatial moment `m_02`.
+    pub m02: f64,
+    /// Spatial moment `m_30`.
+    pub m30: f64,
+    /// Spatial moment `m_21`.
+    pub m21: f64,
+    /// Spatial moment `m_12`.
+    pub m12: f64,
+    /// Spatial moment `m_03`.
+    pub m03: f64,
+    /// Central moment `mu_20`.
+    pub mu20: f64,
+    /// Central moment `mu_11`.
+    pub mu11: f64,
+    /// Central moment `mu_02`.
+    pub mu02: f64,
+    /// Central moment `mu_30`.
+    pub mu30: f64,
+    /// Central moment `mu_21`.
+    pub mu21: f64,
+    /// Central moment `mu_12`.
+    pub mu12: f64,
+    /// Central moment `mu_03`.
+    pub mu03: f64,
+    /// Normalized central moment `nu_20`.
+    pub nu20: f64,
+    /// Normalized central moment `nu_11`.
+    pub nu11: f64,
+    /// Normalized central moment `nu_02`.
+    pub nu02: f64,
+    /// Normalized central moment `nu_30`.
+    pub nu30: f64,
+    /// Normalized central moment `nu_21`.
+    pub nu21: f64,
+    /// Normalized central moment `nu_12`.
+    pub nu12: f64,
+    /// Normalized central moment `nu_03`.
+    pub nu03: f64,
+}
+
+impl Moments {
+    /// The weighted mean position `(m10 / m00, m01 / m00)`, or `None` if the total
+    /// weight is zero.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if self.m00 == 0.0 {
+            None
+        } else {
+            Some((self.m10 / self.m00, self.m01 / self.m00))
+        }
+    }
+
+    /// The angle in radians between the x-axis and the major axis of the ellipse with
+    /// the same second order central moments, in the range `[-pi / 2, pi / 2]`. Angles
+    /// increase clockwise, as the y-axis points down.
+    pub fn orientation(&self) -> f64 {
+        0.5 * (2.0 * self.mu11).atan2(self.mu20 - self.mu02)
+    }
+
+    /// The seven [Hu moment invariants], which are invariant to translation, scale and
+    /// rotation. The seventh changes sign under reflection.
+    ///
+    /// [Hu moment invariants]: https://en.wikipedia.org/wiki/Image_moment#Rotation_invariants
+    pub fn hu_moments(&self) -> [f64; 7] {
+        let (n20, n11, n02) = (self.nu20, self.nu11, self.nu02);
+        let (n30, n21, n12, n03) = (self.nu30, self.nu21, self.nu12, self.nu03);
+
+        let a = n30 + n12;
+        let b = n21 + n03;
+        let c = n30 - 3.0 * n12;
+        let d = 3.0 * n21 - n03;
+
+        [
+            n20 + n02,
+            (n20 - n02).powi(2) + 4.0 * n11 * n11,
+            c * c + d * d,
+            a * a + b * b,
+            c * a * (a * a - 3.0 * b * b) + d * b * (3.0 * a * a - b * b),
+            (n20 - n02) * (a * a - b * b) + 4.0 * n11 * a * b,
+            d * a * (a * a - 3.0 * b * b) - c * b * (3.0 * a * a - b * b),
+        ]
+    }
+
+    // Computes moments from the weights of the pixels in a width x height grid.
+    fn from_weights<F>(width: u32, height: u32, weight: F) -> Moments
+    where
+        F: Fn(u32, u32) -> f64,
+    {
+        let mut m = Moments::default();
+        for y in 0..height {
+            for x in 0..width {
+                let w = weight(x, y);
+                if w == 0.0 {
+                    continue;
+                }
+                let (x, y) = (x as f64, y as f64);
+                m.m00 += w;
+                m.m10 += x * w;
+                m.m01 += y * w;
+                m.m20 += x * x * w;
+                m.m11 += x * y * w;
+                m.m02 += y * y * w;
+                m.m30 += x * x * x * w;
+                m.m21 += x * x * y * w;
+                m.m12 += x * y * y * w;
+                m.m03 += y * y * y * w;
+            }
+        }
+
+        let (cx, cy) = match m.centroid() {
+            Some(c) => c,
+            None => return m,
+        };
+
+        // Central moments are computed directly, rather than from the spatial moments,
+        // to avoid cancellation errors for regions far from the origin.
+        for y in 0..height {
+            for x in 0..width {
+                let w = weight(x, y);
+                if w == 0.0 {
+                    continue;
+                }
+                let (dx, dy) = (x as f64 - cx, y as f64 - cy);
+                m.mu20 += dx * dx * w;
+                m.mu11 += dx * dy * w;
+                m.mu02 += dy * dy * w;
+                m.mu30 += dx * dx * dx * w;
+                m.mu21 += dx * dx * dy * w;
+                m.mu12 += dx * dy * dy * w;
+                m.mu03 += dy * dy * dy * w;
+            }
+        }
+
+        let second = m.m00 * m.m00;
+        let third = second * m.m00.sqrt();
+        m.nu20 = m.mu20 / second;
+        m.nu11 = m.mu11 / second;
+        m.nu02 = m.mu02 / second;
+        m.nu30 = m.mu30 / third;
+        m.nu21 = m.mu21 / third;
+        m.nu12 = m.mu12 / third;
+        m.nu03 = m.mu03 / third;
+        m
+    }
+}
+
+/// Computes the moments of a grayscale image, where each pixel is weighted by its intensity.
+///
+/// Pass a binary image to compute the moments of the shape formed by its non-zero pixels,
+/// scaled by the foreground intensity.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::moments::moments;
+///
+/// let image = gray_image!(
+///     0, 0, 0, 0;
+///     0, 1, 1, 1;
+///     0, 0, 0, 0);
+///
+/// let m = moments(&image);
+/// assert_eq!(m.m00, 3.0);
+/// assert_eq!(m.centroid(), Some((2.0, 1.0)));
+/// assert_eq!(m.mu20, 2.0);
+/// assert_eq!(m.mu02, 0.0);
+/// assert_eq!(m.orientation(), 0.0);
+/// # }
+/// ```
+pub fn moments(image: &GrayImage) -> Moments {
+    Moments::from_weights(image.width(), image.height(), |x, y| {
+        image.get_pixel(x, y)[0] as f64
+    })
+}
+
+/// Computes the moments of the region of pixels with the given label, with each pixel in
+/// the region having weight 1.
+///
+/// `labels` is typically the output of
+/// [`connected_components`](../region_labelling/fn.connected_components.html).
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::moments::component_moments;
+///
+/// let labels = gray_image!(type: u32,
+///     1, 1, 0;
+///     1, 1, 0;
+///     0, 0, 2);
+///
+/// let m = component_moments(&labels, 1);
+/// assert_eq!(m.m00, 4.0);
+/// assert_eq!(m.centroid(), Some((0.5, 0.5)));
+///
+/// assert_eq!(component_moments(&labels, 2).centroid(), Some((2.0, 2.0)));
+/// assert_eq!(component_moments(&labels, 3).centroid(), None);
+/// # }
+/// ```
+pub fn component_moments(labels: &Image<Luma<u32>>, label: u32) -> Moments {
+    Moments::from_weights(labels.width(), labels.height(), |x, y| {
+        (labels.get_pixel(x, y)[0] == label) as u8 as f64
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gray_bench_image;
+    use test::{black_box, Bencher};
+
+    // A filled ellipse with the given semi-axes, rotated by angle radians and offset
+    // by (cx, cy).
+    fn ellipse(size: u32, cx: f64, cy: f64, a: f64, b: f64, angle: f64) -> GrayImage {
+        GrayImage::from_fn(size, size, |x, y| {
+            let (dx, dy) = (x as f64 - cx, y as f64 - cy);
+            let u = dx * angle.cos() + dy * angle.sin();
+            let v = -dx * angle.sin() + dy * angle.cos();
+            Luma([((u / a).powi(2) + (v / b).powi(2) <= 1.0) as u8])
+        })
+    }
+
+    #[test]
+    fn test_moments_of_empty_image() {
+        let m = moments(&GrayImage::new(3, 3));
+        assert_eq!(m, Moments::default());
+        assert_eq!(m.centroid(), None);
+    }
+
+    #[test]
+    fn test_central_moments_match_spatial_moments() {
+        let image = GrayImage::from_fn(9, 7, |x, y| Luma([((x * 7 + y * 3) % 11) as u8]));
+        let m = moments(&image);
+        let (cx, cy) = m.centroid().unwrap();
+
+        assert_approx_eq!(m.mu20, m.m20 - cx * m.m10, 1e-6);
+        assert_approx_eq!(m.mu11, m.m11 - cx * m.m01, 1e-6);
+        assert_approx_eq!(m.mu02, m.m02 - cy * m.m01, 1e-6);
+        assert_approx_eq!(
+            m.mu30,
+            m.m30 - 3.0 * cx * m.m20 + 2.0 * cx * cx * m.m10,
+            1e-6
+        );
+        assert_approx_eq!(
+            m.mu21,
+            m.m21 - 2.0 * cx * m.m11 - cy * m.m20 + 2.0 * cx * cx * m.m01,
+            1e-6
+        );
+        assert_approx_eq!(m.nu20, m.mu20 / (m.m00 * m.m00), 1e-12);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let angle = 0.5;
+        let m = moments(&ellipse(60, 30.0, 30.0, 20.0, 8.0, angle));
+        assert!((m.orientation() - angle).abs() < 0.02);
+
+        let m = moments(&ellipse(60, 30.0, 30.0, 20.0, 8.0, -1.0));
+        assert!((m.orientation() + 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_hu_moments_are_invariant() {
+        let reference = moments(&ellipse(80, 30.0, 30.0, 16.0, 8.0, 0.0)).hu_moments();
+        let moved = moments(&ellipse(80, 45.0, 40.0, 16.0, 8.0, 0.0)).hu_moments();
+        let rotated = moments(&ellipse(80, 40.0, 40.0, 16.0, 8.0, 1.0)).hu_moments();
+        let scaled = moments(&ellipse(80, 40.0, 40.0, 32.0, 16.0, 0.3)).hu_moments();
+
+        for other in &[moved, rotated, scaled] {
+            for i in 0..2 {
+                assert!(
+                    (reference[i] - other[i]).abs() < 0.05 * reference[i].abs(),
+                    "{:?} {:?}",
+                    reference,
+                    other
+                );
+            }
+        }
+
+        // For a filled ellipse, the first invariant is (a^2 + b^2) / (4 pi a b)
+        let expected = (16.0f64.powi(2) + 8.0f64.powi(2)) / (4.0 * std::f64::consts::PI * 128.0);
+        assert!((reference[0] - expected).abs() < 0.01 * expected);
+    }
+
+    #[test]
+    fn test_component_moments_match_moments() {
+        let labels = Image::from_fn(6, 5, |x, y| Luma([(x + y) % 3]));
+        let binary =
+            GrayImage::from_fn(6, 5, |x, y| Luma([(labels.get_pixel(x, y)[0] == 2) as u8]));
+        assert_eq!(component_moments(&labels, 2), moments(&binary));
+    }
+
+    #[bench]
+    fn bench_moments(b: &mut Bencher) {
+        let image = gray_bench_image(200, 200);
+        b.iter(|| {
+            let m = moments(&image);
+            black_box(m);
+        });
+    }
+}
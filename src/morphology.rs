@@ -2,15 +2,17 @@
 //!
 //! [morphological operators]: https://homepages.inf.ed.ac.uk/rbf/HIPR2/morops.htm
 
-use crate::distance_transform::{
-    distance_transform_impl, distance_transform_mut, DistanceFrom, Norm,
-};
-use image::GrayImage;
+use crate::definitions::{HasBlack, HasWhite, Image};
+use crate::distance_transform::{within_distance_impl, DistanceFrom, Norm};
+use crate::error::{check_dimensions, Error};
+use image::{GrayImage, Luma, Primitive};
+use std::any::Any;
 use std::u8;
 
 /// Sets all pixels within distance `k` of a foreground pixel to white.
 ///
 /// A pixel is treated as belonging to the foreground if it has non-zero intensity.
+/// All other pixels are set to black. For `f32` images white is `1.0`.
 ///
 /// # Examples
 /// ```
@@ -53,7 +55,11 @@ use std::u8;
 /// assert_pixels_eq!(dilate(&image, Norm::LInf, 1), linf_dilated);
 /// # }
 /// ```
-pub fn dilate(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
+pub fn dilate<T>(image: &Image<Luma<T>>, norm: Norm, k: u8) -> Image<Luma<T>>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
     let mut out = image.clone();
     dilate_mut(&mut out, norm, k);
     out
@@ -64,11 +70,48 @@ pub fn dilate(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
 /// A pixel is treated as belonging to the foreground if it has non-zero intensity.
 ///
 /// See the [`dilate`](fn.dilate.html) documentation for examples.
-pub fn dilate_mut(image: &mut GrayImage, norm: Norm, k: u8) {
-    distance_transform_mut(image, norm);
-    for p in image.iter_mut() {
-        *p = if *p <= k { 255 } else { 0 };
-    }
+pub fn dilate_mut<T>(image: &mut Image<Luma<T>>, norm: Norm, k: u8)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    binarize_by_distance(image, norm, DistanceFrom::Foreground, k, true);
+}
+
+/// Sets all pixels within distance `k` of a foreground pixel to white, writing the result to `out`, which must have the
+/// same dimensions as `image`.
+///
+/// See the [`dilate`](fn.dilate.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn dilate_into<T>(image: &Image<Luma<T>>, norm: Norm, k: u8, out: &mut Image<Luma<T>>)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    try_dilate_into(image, norm, k, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Sets all pixels within distance `k` of a foreground pixel to white, writing the result to `out`. Returns an error if `out` does not have the same
+/// dimensions as `image`.
+///
+/// See the [`dilate`](fn.dilate.html) documentation for more information.
+pub fn try_dilate_into<T>(
+    image: &Image<Luma<T>>,
+    norm: Norm,
+    k: u8,
+    out: &mut Image<Luma<T>>,
+) -> Result<(), Error>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    out.copy_from_slice(image);
+    dilate_mut(out, norm, k);
+    Ok(())
 }
 
 /// Sets all pixels within distance `k` of a background pixel to black.
@@ -131,7 +174,11 @@ pub fn dilate_mut(image: &mut GrayImage, norm: Norm, k: u8) {
 /// assert_pixels_eq!(erode(&image, Norm::LInf, 1), linf_eroded);
 /// # }
 /// ```
-pub fn erode(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
+pub fn erode<T>(image: &Image<Luma<T>>, norm: Norm, k: u8) -> Image<Luma<T>>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
     let mut out = image.clone();
     erode_mut(&mut out, norm, k);
     out
@@ -142,11 +189,48 @@ pub fn erode(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
 /// A pixel is treated as belonging to the foreground if it has non-zero intensity.
 ///
 /// See the [`erode`](fn.erode.html) documentation for examples.
-pub fn erode_mut(image: &mut GrayImage, norm: Norm, k: u8) {
-    distance_transform_impl(image, norm, DistanceFrom::Background);
-    for p in image.iter_mut() {
-        *p = if *p <= k { 0 } else { 255 };
-    }
+pub fn erode_mut<T>(image: &mut Image<Luma<T>>, norm: Norm, k: u8)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    binarize_by_distance(image, norm, DistanceFrom::Background, k, false);
+}
+
+/// Sets all pixels within distance `k` of a background pixel to black, writing the result to `out`, which must have the
+/// same dimensions as `image`.
+///
+/// See the [`erode`](fn.erode.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn erode_into<T>(image: &Image<Luma<T>>, norm: Norm, k: u8, out: &mut Image<Luma<T>>)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    try_erode_into(image, norm, k, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Sets all pixels within distance `k` of a background pixel to black, writing the result to `out`. Returns an error if `out` does not have the same
+/// dimensions as `image`.
+///
+/// See the [`erode`](fn.erode.html) documentation for more information.
+pub fn try_erode_into<T>(
+    image: &Image<Luma<T>>,
+    norm: Norm,
+    k: u8,
+    out: &mut Image<Luma<T>>,
+) -> Result<(), Error>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    out.copy_from_slice(image);
+    erode_mut(out, norm, k);
+    Ok(())
 }
 
 /// Erosion followed by dilation.
@@ -200,7 +284,11 @@ pub fn erode_mut(image: &mut GrayImage, norm: Norm, k: u8) {
 /// );
 /// # }
 /// ```
-pub fn open(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
+pub fn open<T>(image: &Image<Luma<T>>, norm: Norm, k: u8) -> Image<Luma<T>>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
     let mut out = image.clone();
     open_mut(&mut out, norm, k);
     out
@@ -211,11 +299,51 @@ pub fn open(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
 /// See the [`open`](fn.open.html) documentation for examples,
 /// and the [`erode`](fn.erode.html) and [`dilate`](fn.dilate.html)
 /// documentation for definitions of dilation and erosion.
-pub fn open_mut(image: &mut GrayImage, norm: Norm, k: u8) {
+pub fn open_mut<T>(image: &mut Image<Luma<T>>, norm: Norm, k: u8)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
     erode_mut(image, norm, k);
     dilate_mut(image, norm, k);
 }
 
+/// Erosion followed by dilation, writing the result to `out`, which must have the
+/// same dimensions as `image`.
+///
+/// See the [`open`](fn.open.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn open_into<T>(image: &Image<Luma<T>>, norm: Norm, k: u8, out: &mut Image<Luma<T>>)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    try_open_into(image, norm, k, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Erosion followed by dilation, writing the result to `out`. Returns an error if `out` does not have the same
+/// dimensions as `image`.
+///
+/// See the [`open`](fn.open.html) documentation for more information.
+pub fn try_open_into<T>(
+    image: &Image<Luma<T>>,
+    norm: Norm,
+    k: u8,
+    out: &mut Image<Luma<T>>,
+) -> Result<(), Error>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    out.copy_from_slice(image);
+    open_mut(out, norm, k);
+    Ok(())
+}
+
 /// Dilation followed by erosion.
 ///
 /// See the [`erode`](fn.erode.html) and [`dilate`](fn.dilate.html)
@@ -305,7 +433,11 @@ pub fn open_mut(image: &mut GrayImage, norm: Norm, k: u8) {
 /// );
 /// # }
 /// ```
-pub fn close(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
+pub fn close<T>(image: &Image<Luma<T>>, norm: Norm, k: u8) -> Image<Luma<T>>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
     let mut out = image.clone();
     close_mut(&mut out, norm, k);
     out
@@ -316,18 +448,241 @@ pub fn close(image: &GrayImage, norm: Norm, k: u8) -> GrayImage {
 /// See the [`close`](fn.close.html) documentation for examples,
 /// and the [`erode`](fn.erode.html) and [`dilate`](fn.dilate.html)
 /// documentation for definitions of dilation and erosion.
-pub fn close_mut(image: &mut GrayImage, norm: Norm, k: u8) {
+pub fn close_mut<T>(image: &mut Image<Luma<T>>, norm: Norm, k: u8)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
     dilate_mut(image, norm, k);
     erode_mut(image, norm, k);
 }
 
+/// Dilation followed by erosion, writing the result to `out`, which must have the
+/// same dimensions as `image`.
+///
+/// See the [`close`](fn.close.html) documentation for more information.
+///
+/// # Panics
+///
+/// Panics if `out` does not have the same dimensions as `image`.
+pub fn close_into<T>(image: &Image<Luma<T>>, norm: Norm, k: u8, out: &mut Image<Luma<T>>)
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    try_close_into(image, norm, k, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Dilation followed by erosion, writing the result to `out`. Returns an error if `out` does not have the same
+/// dimensions as `image`.
+///
+/// See the [`close`](fn.close.html) documentation for more information.
+pub fn try_close_into<T>(
+    image: &Image<Luma<T>>,
+    norm: Norm,
+    k: u8,
+    out: &mut Image<Luma<T>>,
+) -> Result<(), Error>
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
+    out.copy_from_slice(image);
+    close_mut(out, norm, k);
+    Ok(())
+}
+
+// Sets each pixel within distance k of the nearest foreground or background pixel to white
+// if within_is_white is true and to black otherwise, and all other pixels to the other colour.
+fn binarize_by_distance<T>(
+    image: &mut Image<Luma<T>>,
+    norm: Norm,
+    from: DistanceFrom,
+    k: u8,
+    within_is_white: bool,
+) where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    let (within, beyond) = if within_is_white {
+        (u8::MAX, 0)
+    } else {
+        (0, u8::MAX)
+    };
+
+    // Compute the distance transform of 8 bit images in place, avoiding a copy
+    if let Some(gray) = (image as &mut dyn Any).downcast_mut::<GrayImage>() {
+        within_distance_impl(gray, norm, from, k, within, beyond);
+        return;
+    }
+
+    let (black, white) = (Luma::<T>::black()[0], Luma::<T>::white()[0]);
+    let zero = T::zero();
+    let mut mask = GrayImage::from_raw(
+        image.width(),
+        image.height(),
+        image
+            .iter()
+            .map(|p| if *p == zero { 0 } else { u8::MAX })
+            .collect(),
+    )
+    .unwrap();
+    within_distance_impl(&mut mask, norm, from, k, within, beyond);
+    for (p, m) in image.iter_mut().zip(mask.iter()) {
+        *p = if *m > 0 { white } else { black };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::distance_transform::distance_transform;
+    use crate::property_testing::GrayTestImage;
+    use crate::utils::pixel_diff_summary;
     use ::test::*;
     use image::{GrayImage, Luma};
+    use quickcheck::{quickcheck, TestResult};
     use std::cmp::{max, min};
 
+    #[test]
+    fn test_dilate_and_erode_match_distance_transform() {
+        fn prop(image: GrayTestImage, k: u8) -> TestResult {
+            // Use sparse foregrounds and small distances, so that most outputs are not constant
+            let image = crate::map::map_colors(&image.0, |p| Luma([(p[0] > 200) as u8]));
+            let k = k % 8;
+            let inverted = GrayImage::from_fn(image.width(), image.height(), |x, y| {
+                Luma([(image.get_pixel(x, y)[0] == 0) as u8])
+            });
+            let binarize = |distances: &GrayImage, within: u8, beyond: u8| {
+                GrayImage::from_fn(image.width(), image.height(), |x, y| {
+                    Luma([if distances.get_pixel(x, y)[0] <= k {
+                        within
+                    } else {
+                        beyond
+                    }])
+                })
+            };
+            for &norm in &[Norm::L1, Norm::LInf, Norm::Chamfer34, Norm::Chamfer5711] {
+                let expected = binarize(&distance_transform(&image, norm), 255, 0);
+                if let Some(err) = pixel_diff_summary(&dilate(&image, norm, k), &expected) {
+                    return TestResult::error(err);
+                }
+                let expected = binarize(&distance_transform(&inverted, norm), 0, 255);
+                if let Some(err) = pixel_diff_summary(&erode(&image, norm, k), &expected) {
+                    return TestResult::error(err);
+                }
+            }
+            TestResult::passed()
+        }
+        quickcheck(prop as fn(GrayTestImage, u8) -> TestResult);
+    }
+
+    #[test]
+    fn test_into_variants_match_allocating_variants() {
+        let image = GrayImage::from_fn(12, 9, |x, y| {
+            Luma([if (x * y + x) % 5 < 2 { 255 } else { 0 }])
+        });
+        let mut out = GrayImage::from_pixel(12, 9, Luma([17]));
+
+        for &norm in &[Norm::L1, Norm::LInf] {
+            dilate_into(&image, norm, 1, &mut out);
+            assert_pixels_eq!(out, dilate(&image, norm, 1));
+            erode_into(&image, norm, 1, &mut out);
+            assert_pixels_eq!(out, erode(&image, norm, 1));
+            open_into(&image, norm, 1, &mut out);
+            assert_pixels_eq!(out, open(&image, norm, 1));
+            close_into(&image, norm, 1, &mut out);
+            assert_pixels_eq!(out, close(&image, norm, 1));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dilate_into_rejects_wrong_output_size() {
+        dilate_into(
+            &GrayImage::new(3, 3),
+            Norm::L1,
+            1,
+            &mut GrayImage::new(3, 4),
+        );
+    }
+
+    #[test]
+    fn test_try_into_variants_check_dimensions() {
+        let image = GrayImage::from_fn(5, 4, |x, y| Luma([if x == y { 255 } else { 0 }]));
+        let mut out = GrayImage::new(5, 4);
+        assert_eq!(try_dilate_into(&image, Norm::L1, 1, &mut out), Ok(()));
+        assert_pixels_eq!(out, dilate(&image, Norm::L1, 1));
+
+        let expected = Err(Error::DimensionMismatch {
+            expected: (5, 4),
+            actual: (4, 5),
+        });
+        let mut out = GrayImage::new(4, 5);
+        assert_eq!(try_dilate_into(&image, Norm::L1, 1, &mut out), expected);
+        assert_eq!(try_erode_into(&image, Norm::L1, 1, &mut out), expected);
+        assert_eq!(try_open_into(&image, Norm::L1, 1, &mut out), expected);
+        assert_eq!(try_close_into(&image, Norm::L1, 1, &mut out), expected);
+    }
+
+    #[test]
+    fn test_dilate_u16() {
+        let image = gray_image!(type: u16,
+            0, 0,   0, 0;
+            0, 0, 300, 0;
+            0, 0,   0, 0);
+
+        let expected = gray_image!(type: u16,
+            0,     0, 65535,     0;
+            0, 65535, 65535, 65535;
+            0,     0, 65535,     0);
+
+        assert_pixels_eq!(dilate(&image, Norm::L1, 1), expected);
+    }
+
+    #[test]
+    fn test_erode_u16() {
+        let image = gray_image!(type: u16,
+            0, 7, 7, 7;
+            0, 7, 7, 7;
+            0, 7, 7, 7);
+
+        let expected = gray_image!(type: u16,
+            0, 0, 65535, 65535;
+            0, 0, 65535, 65535;
+            0, 0, 65535, 65535);
+
+        assert_pixels_eq!(erode(&image, Norm::L1, 1), expected);
+    }
+
+    #[test]
+    fn test_dilate_and_erode_f32() {
+        let image = gray_image!(type: f32,
+            0.0, 0.0, 0.0, 0.0, 0.0;
+            0.0, 0.1, 0.5, 0.9, 0.0;
+            0.0, 0.2, 0.6, 1.5, 0.0;
+            0.0, 0.3, 0.7, 1.0, 0.0;
+            0.0, 0.0, 0.0, 0.0, 0.0);
+
+        let dilated = gray_image!(type: f32,
+            1.0, 1.0, 1.0, 1.0, 1.0;
+            1.0, 1.0, 1.0, 1.0, 1.0;
+            1.0, 1.0, 1.0, 1.0, 1.0;
+            1.0, 1.0, 1.0, 1.0, 1.0;
+            1.0, 1.0, 1.0, 1.0, 1.0);
+
+        let eroded = gray_image!(type: f32,
+            0.0, 0.0, 0.0, 0.0, 0.0;
+            0.0, 0.0, 0.0, 0.0, 0.0;
+            0.0, 0.0, 1.0, 0.0, 0.0;
+            0.0, 0.0, 0.0, 0.0, 0.0;
+            0.0, 0.0, 0.0, 0.0, 0.0);
+
+        assert_pixels_eq!(dilate(&image, Norm::LInf, 1), dilated);
+        assert_pixels_eq!(erode(&image, Norm::LInf, 1), eroded);
+    }
+
     #[test]
     fn test_dilate_point_l1_1() {
         let image = gray_image!(
@@ -1,11 +1,11 @@
-//! Functions for adding synthetic noise to images.
+//! Functions for adding synthetic noise to images, and for generating procedural noise images.
 
 use crate::definitions::{Clamp, HasBlack, HasWhite, Image};
 use crate::math::cast;
 use conv::ValueInto;
-use image::Pixel;
-use rand::{rngs::StdRng, SeedableRng};
-use rand_distr::{Distribution, Normal, Uniform};
+use image::{GrayImage, Luma, Pixel};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal, Poisson, Uniform};
 
 /// Adds independent additive Gaussian noise to all channels
 /// of an image, with the given mean and standard deviation.
@@ -27,16 +27,80 @@ where
     P::Subpixel: ValueInto<f64> + Clamp<f64>,
 {
     let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    gaussian_noise_with_rng_mut(image, mean, stddev, &mut rng);
+}
+
+/// Adds independent additive Gaussian noise to all channels
+/// of an image in place, with the given mean and standard deviation,
+/// using the provided random number generator.
+///
+/// This allows noise to be drawn from a single generator across several
+/// calls, or from a generator other than `StdRng`.
+pub fn gaussian_noise_with_rng_mut<P, R>(image: &mut Image<P>, mean: f64, stddev: f64, rng: &mut R)
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + Clamp<f64>,
+    R: Rng + ?Sized,
+{
     let normal = Normal::new(mean, stddev).unwrap();
 
     for p in image.pixels_mut() {
         for c in p.channels_mut() {
-            let noise = normal.sample(&mut rng);
+            let noise = normal.sample(rng);
             *c = P::Subpixel::clamp(cast(*c) + noise);
         }
     }
 }
 
+/// Adds independent multiplicative (speckle) noise to all channels of an image.
+///
+/// Each channel value `v` is replaced by `v + v * n`, where `n` is drawn from a Gaussian
+/// distribution with mean 0 and the given standard deviation, so the noise has variance
+/// `(v * stddev)^2`. This is a common model for the noise in ultrasound and synthetic
+/// aperture radar images.
+pub fn speckle_noise<P>(image: &Image<P>, stddev: f64, seed: u64) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + Clamp<f64>,
+{
+    let mut out = image.clone();
+    speckle_noise_mut(&mut out, stddev, seed);
+    out
+}
+
+/// Adds independent multiplicative (speckle) noise to all channels of an image in place.
+///
+/// See [`speckle_noise`](fn.speckle_noise.html) for details.
+pub fn speckle_noise_mut<P>(image: &mut Image<P>, stddev: f64, seed: u64)
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + Clamp<f64>,
+{
+    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    speckle_noise_with_rng_mut(image, stddev, &mut rng);
+}
+
+/// Adds independent multiplicative (speckle) noise to all channels of an image in place,
+/// using the provided random number generator.
+///
+/// See [`speckle_noise`](fn.speckle_noise.html) for details.
+pub fn speckle_noise_with_rng_mut<P, R>(image: &mut Image<P>, stddev: f64, rng: &mut R)
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + Clamp<f64>,
+    R: Rng + ?Sized,
+{
+    let normal = Normal::new(0.0, stddev).unwrap();
+
+    for p in image.pixels_mut() {
+        for c in p.channels_mut() {
+            let value = cast(*c);
+            let noise = normal.sample(rng);
+            *c = P::Subpixel::clamp(value + value * noise);
+        }
+    }
+}
+
 /// Converts pixels to black or white at the given `rate` (between 0.0 and 1.0).
 /// Black and white occur with equal probability.
 pub fn salt_and_pepper_noise<P>(image: &Image<P>, rate: f64, seed: u64) -> Image<P>
@@ -55,23 +119,398 @@ where
     P: Pixel + HasBlack + HasWhite + 'static,
 {
     let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    salt_and_pepper_noise_with_rng_mut(image, rate, &mut rng);
+}
+
+/// Converts pixels to black or white in place at the given `rate` (between 0.0 and 1.0),
+/// using the provided random number generator. Black and white occur with equal probability.
+///
+/// This allows noise to be drawn from a single generator across several
+/// calls, or from a generator other than `StdRng`.
+pub fn salt_and_pepper_noise_with_rng_mut<P, R>(image: &mut Image<P>, rate: f64, rng: &mut R)
+where
+    P: Pixel + HasBlack + HasWhite + 'static,
+    R: Rng + ?Sized,
+{
     let uniform = Uniform::new(0.0, 1.0);
 
     for p in image.pixels_mut() {
-        if uniform.sample(&mut rng) > rate {
+        if uniform.sample(rng) > rate {
             continue;
         }
-        let r = uniform.sample(&mut rng);
+        let r = uniform.sample(rng);
         *p = if r >= 0.5 { P::white() } else { P::black() };
     }
 }
 
+/// Replaces each channel of each pixel of an image with a sample from a Poisson
+/// distribution whose mean is the channel value, simulating photon shot noise.
+///
+/// Each channel value `v` is treated as an expected count of `v * photons_per_unit`
+/// photons. The output is the number of photons sampled, divided by `photons_per_unit`,
+/// so the noise has variance `v / photons_per_unit`. Smaller values of `photons_per_unit`
+/// therefore simulate noisier images, such as those captured in low light.
+///
+/// # Panics
+///
+/// If `photons_per_unit` is not positive.
+pub fn poisson_noise<P>(image: &Image<P>, photons_per_unit: f64, seed: u64) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + Clamp<f64>,
+{
+    let mut out = image.clone();
+    poisson_noise_mut(&mut out, photons_per_unit, seed);
+    out
+}
+
+/// Replaces each channel of each pixel of an image in place with a sample from a Poisson
+/// distribution whose mean is the channel value, simulating photon shot noise.
+///
+/// See [`poisson_noise`](fn.poisson_noise.html) for details.
+pub fn poisson_noise_mut<P>(image: &mut Image<P>, photons_per_unit: f64, seed: u64)
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + Clamp<f64>,
+{
+    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    poisson_noise_with_rng_mut(image, photons_per_unit, &mut rng);
+}
+
+/// Replaces each channel of each pixel of an image in place with a sample from a Poisson
+/// distribution whose mean is the channel value, using the provided random number generator.
+///
+/// See [`poisson_noise`](fn.poisson_noise.html) for details.
+pub fn poisson_noise_with_rng_mut<P, R>(image: &mut Image<P>, photons_per_unit: f64, rng: &mut R)
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + Clamp<f64>,
+    R: Rng + ?Sized,
+{
+    assert!(photons_per_unit > 0.0, "photons_per_unit must be positive");
+
+    for p in image.pixels_mut() {
+        for c in p.channels_mut() {
+            let expected = cast(*c) * photons_per_unit;
+            // Poisson::new fails for a mean of zero, which always produces zero photons
+            let photons: f64 = match Poisson::new(expected) {
+                Ok(poisson) => poisson.sample(rng),
+                Err(_) => 0.0,
+            };
+            *c = P::Subpixel::clamp(photons / photons_per_unit);
+        }
+    }
+}
+
+/// Estimates the standard deviation of additive white Gaussian noise in an image.
+///
+/// The image is split into 2x2 blocks, and the finest diagonal detail coefficient of
+/// the Haar wavelet transform is computed for each block. These coefficients are mostly
+/// determined by noise, as image structure rarely varies diagonally within two pixels.
+/// The noise level is estimated robustly from their median absolute value, as in
+/// [Donoho and Johnstone]. The result can be used to choose the parameters of
+/// denoising filters.
+///
+/// Estimates for images with very low noise are biased downwards by quantization, and the
+/// estimate is 0 for images smaller than 2x2.
+///
+/// [Donoho and Johnstone]: https://doi.org/10.1093/biomet/81.3.425
+pub fn estimate_noise_sigma(image: &GrayImage) -> f64 {
+    let (width, height) = image.dimensions();
+    let mut coefficients = Vec::with_capacity((width / 2 * (height / 2)) as usize);
+    for y in (0..height.saturating_sub(1)).step_by(2) {
+        for x in (0..width.saturating_sub(1)).step_by(2) {
+            let p = |dx, dy| image.get_pixel(x + dx, y + dy)[0] as f64;
+            let diagonal = (p(0, 0) - p(1, 0) - p(0, 1) + p(1, 1)) / 2.0;
+            coefficients.push(diagonal.abs());
+        }
+    }
+    if coefficients.is_empty() {
+        return 0.0;
+    }
+
+    let mid = coefficients.len() / 2;
+    coefficients.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let median = if coefficients.len() % 2 == 0 {
+        (coefficients[mid - 1] + coefficients[mid]) / 2.0
+    } else {
+        coefficients[mid]
+    };
+    // The median absolute value of a standard normal distribution
+    median / 0.6745
+}
+
+/// Generates an image of two-dimensional [Perlin noise], a smooth random texture.
+///
+/// Random gradients are placed on a square lattice with spacing `period` pixels, and
+/// interpolated between lattice points. Values lie in `[-1, 1]` and are zero at lattice
+/// points. Different seeds produce different textures.
+///
+/// [Perlin noise]: https://en.wikipedia.org/wiki/Perlin_noise
+///
+/// # Panics
+///
+/// If `period` is not positive.
+pub fn perlin_noise(width: u32, height: u32, period: f32, seed: u64) -> Image<Luma<f32>> {
+    fractal_noise(width, height, period, 1, 0.5, seed)
+}
+
+/// Generates an image of fractal Brownian motion, formed by summing `octaves` layers of
+/// [Perlin noise](fn.perlin_noise.html).
+///
+/// The first octave has lattice spacing `period` pixels. Each subsequent octave has half
+/// the spacing of the previous one and its amplitude multiplied by `persistence`, which is
+/// typically 0.5. The sum is divided by the total amplitude of all octaves, so values lie in
+/// `[-1, 1]`.
+///
+/// # Panics
+///
+/// If `period` is not positive or `octaves` is zero.
+pub fn fractal_noise(
+    width: u32,
+    height: u32,
+    period: f32,
+    octaves: u32,
+    persistence: f32,
+    seed: u64,
+) -> Image<Luma<f32>> {
+    assert!(period > 0.0, "period must be positive");
+    assert!(octaves > 0, "octaves must be non-zero");
+
+    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    let mut permutation: Vec<u8> = (0..=255).collect();
+    permutation.shuffle(&mut rng);
+
+    let mut out: Image<Luma<f32>> = Image::new(width, height);
+    let mut total_amplitude = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0 / period;
+
+    for _ in 0..octaves {
+        for (x, y, p) in out.enumerate_pixels_mut() {
+            let value = gradient_noise(&permutation, x as f32 * frequency, y as f32 * frequency);
+            p[0] += amplitude * value;
+        }
+        total_amplitude += amplitude;
+        amplitude *= persistence;
+        frequency *= 2.0;
+    }
+
+    if total_amplitude > 0.0 {
+        for p in out.iter_mut() {
+            *p /= total_amplitude;
+        }
+    }
+    out
+}
+
+// Perlin's improved noise at (x, y), using the given permutation of 0..256 to choose
+// gradients at lattice points.
+fn gradient_noise(permutation: &[u8], x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (ix, iy) = (x0 as i64 & 255, y0 as i64 & 255);
+
+    let hash = |i: i64, j: i64| {
+        let h = permutation[((i & 255) as usize + permutation[(j & 255) as usize] as usize) & 255];
+        h as usize
+    };
+    // Dot product of the offset (dx, dy) with one of eight gradients
+    let gradient = |h: usize, dx: f32, dy: f32| match h & 7 {
+        0 => dx + dy,
+        1 => dx - dy,
+        2 => -dx + dy,
+        3 => -dx - dy,
+        4 => dx,
+        5 => -dx,
+        6 => dy,
+        _ => -dy,
+    };
+    // Quintic interpolation, which has continuous first and second derivatives
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+
+    let n00 = gradient(hash(ix, iy), fx, fy);
+    let n10 = gradient(hash(ix + 1, iy), fx - 1.0, fy);
+    let n01 = gradient(hash(ix, iy + 1), fx, fy - 1.0);
+    let n11 = gradient(hash(ix + 1, iy + 1), fx - 1.0, fy - 1.0);
+
+    let (u, v) = (fade(fx), fade(fy));
+    let top = n00 + u * (n10 - n00);
+    let bottom = n01 + u * (n11 - n01);
+    top + v * (bottom - top)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use image::GrayImage;
+    use image::{GrayImage, Luma};
     use test::{black_box, Bencher};
 
+    #[test]
+    fn test_gaussian_noise_is_reproducible() {
+        let image = GrayImage::from_pixel(10, 10, Luma([100u8]));
+        let first = gaussian_noise(&image, 0.0, 20.0, 7);
+        let second = gaussian_noise(&image, 0.0, 20.0, 7);
+        assert_pixels_eq!(first, second);
+
+        let mut rng: StdRng = SeedableRng::seed_from_u64(7);
+        let mut with_rng = image.clone();
+        gaussian_noise_with_rng_mut(&mut with_rng, 0.0, 20.0, &mut rng);
+        assert_pixels_eq!(with_rng, first);
+    }
+
+    #[test]
+    fn test_salt_and_pepper_noise_with_shared_rng() {
+        let image = GrayImage::from_pixel(10, 10, Luma([100u8]));
+        let mut rng: StdRng = SeedableRng::seed_from_u64(3);
+
+        let mut first = image.clone();
+        salt_and_pepper_noise_with_rng_mut(&mut first, 0.5, &mut rng);
+        let mut second = image.clone();
+        salt_and_pepper_noise_with_rng_mut(&mut second, 0.5, &mut rng);
+
+        // Successive calls continue the same random sequence
+        assert_ne!(first, second);
+        assert_pixels_eq!(first, salt_and_pepper_noise(&image, 0.5, 3));
+    }
+
+    #[test]
+    fn test_poisson_noise_variance_scales_with_intensity() {
+        let dark = GrayImage::from_pixel(100, 100, Luma([10u8]));
+        let bright = GrayImage::from_pixel(100, 100, Luma([160u8]));
+
+        let sample_variance = |image: &GrayImage, mean: f64| {
+            let noisy = poisson_noise(image, 1.0, 5);
+            noisy
+                .iter()
+                .map(|&p| (p as f64 - mean).powi(2))
+                .sum::<f64>()
+                / (noisy.width() * noisy.height()) as f64
+        };
+
+        let dark_variance = sample_variance(&dark, 10.0);
+        let bright_variance = sample_variance(&bright, 160.0);
+        assert!((dark_variance - 10.0).abs() < 1.5, "{}", dark_variance);
+        assert!(
+            (bright_variance - 160.0).abs() < 20.0,
+            "{}",
+            bright_variance
+        );
+    }
+
+    #[test]
+    fn test_poisson_noise_preserves_black() {
+        let image = GrayImage::new(5, 5);
+        assert_pixels_eq!(poisson_noise(&image, 0.5, 1), image);
+    }
+
+    #[bench]
+    fn bench_poisson_noise_mut(b: &mut Bencher) {
+        let mut image = GrayImage::from_pixel(100, 100, Luma([100u8]));
+        b.iter(|| {
+            poisson_noise_mut(&mut image, 1.0, 1);
+        });
+        black_box(image);
+    }
+
+    #[test]
+    fn test_speckle_noise_is_multiplicative() {
+        let image = gray_image!(
+            0, 0, 0, 0;
+            0, 0, 0, 0);
+        assert_pixels_eq!(speckle_noise(&image, 0.5, 1), image);
+
+        let image = GrayImage::from_pixel(100, 100, Luma([100u8]));
+        let noisy = speckle_noise(&image, 0.2, 1);
+        let n = (noisy.width() * noisy.height()) as f64;
+        let mean = noisy.iter().map(|&p| p as f64).sum::<f64>() / n;
+        let variance = noisy
+            .iter()
+            .map(|&p| (p as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        assert!((mean - 100.0).abs() < 1.0, "{}", mean);
+        assert!((variance.sqrt() - 20.0).abs() < 1.0, "{}", variance.sqrt());
+    }
+
+    #[bench]
+    fn bench_speckle_noise_mut(b: &mut Bencher) {
+        let mut image = GrayImage::from_pixel(100, 100, Luma([100u8]));
+        b.iter(|| {
+            speckle_noise_mut(&mut image, 0.2, 1);
+        });
+        black_box(image);
+    }
+
+    #[test]
+    fn test_perlin_noise_properties() {
+        let noise = perlin_noise(64, 48, 16.0, 3);
+        assert_eq!(noise.dimensions(), (64, 48));
+        assert!(noise.iter().all(|v| (-1.0..=1.0).contains(v)));
+
+        // Zero at lattice points
+        for y in (0..48).step_by(16) {
+            for x in (0..64).step_by(16) {
+                assert_eq!(noise.get_pixel(x, y)[0], 0.0);
+            }
+        }
+
+        // Smooth: neighbouring pixels differ by a small fraction of the range
+        for y in 0..48 {
+            for x in 1..64 {
+                let d = noise.get_pixel(x, y)[0] - noise.get_pixel(x - 1, y)[0];
+                assert!(d.abs() <= 0.25);
+            }
+        }
+
+        // Not constant
+        assert!(noise.iter().any(|v| v.abs() > 0.1));
+    }
+
+    #[test]
+    fn test_fractal_noise_is_reproducible() {
+        let first = fractal_noise(32, 32, 16.0, 4, 0.5, 9);
+        let second = fractal_noise(32, 32, 16.0, 4, 0.5, 9);
+        let other = fractal_noise(32, 32, 16.0, 4, 0.5, 10);
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert!(first.iter().all(|v| (-1.0..=1.0).contains(v)));
+    }
+
+    #[bench]
+    fn bench_fractal_noise(b: &mut Bencher) {
+        b.iter(|| {
+            let noise = fractal_noise(100, 100, 32.0, 4, 0.5, 1);
+            black_box(noise);
+        });
+    }
+
+    #[test]
+    fn test_estimate_noise_sigma() {
+        // A smooth gradient with a strong vertical edge
+        let image = GrayImage::from_fn(200, 200, |x, y| {
+            let base = if x < 100 { 40 } else { 180 };
+            Luma([(base + y / 10) as u8])
+        });
+        assert!(estimate_noise_sigma(&image) < 0.5);
+
+        for &sigma in &[5.0, 10.0, 20.0] {
+            let noisy = gaussian_noise(&image, 0.0, sigma, 2);
+            let estimate = estimate_noise_sigma(&noisy);
+            assert!(
+                (estimate - sigma).abs() < 0.1 * sigma,
+                "sigma {}, estimate {}",
+                sigma,
+                estimate
+            );
+        }
+    }
+
+    #[test]
+    fn test_estimate_noise_sigma_small_image() {
+        assert_eq!(estimate_noise_sigma(&GrayImage::new(1, 5)), 0.0);
+    }
+
     #[bench]
     fn bench_gaussian_noise_mut(b: &mut Bencher) {
         let mut image = GrayImage::new(100, 100);
@@ -0,0 +1,52 @@
+//! Helpers for processing image rows in parallel when the `rayon` feature is enabled.
+
+use crate::definitions::{MaybeSend, MaybeSync};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Calls `f` with the index and contents of each row of `data`, where rows have length `pitch`.
+/// Rows are processed in parallel if the `rayon` feature is enabled.
+pub(crate) fn for_each_row<T, F>(data: &mut [T], pitch: usize, f: F)
+where
+    T: MaybeSend,
+    F: Fn(usize, &mut [T]) + MaybeSync + MaybeSend,
+{
+    for_each_row_init(data, pitch, || (), |_, y, row| f(y, row));
+}
+
+/// As [`for_each_row`](fn.for_each_row.html), but also passes `f` a scratch value created by
+/// `init`. Each value is reused across many rows, but never shared between threads.
+pub(crate) fn for_each_row_init<T, S, I, F>(data: &mut [T], pitch: usize, init: I, f: F)
+where
+    T: MaybeSend,
+    I: Fn() -> S + MaybeSync + MaybeSend,
+    F: Fn(&mut S, usize, &mut [T]) + MaybeSync + MaybeSend,
+{
+    if pitch == 0 {
+        return;
+    }
+    #[cfg(feature = "rayon")]
+    {
+        if data.len() > MIN_BAND_LEN {
+            // Process several rows per task, to amortise the cost of scheduling.
+            let rows_per_band = (MIN_BAND_LEN / pitch).max(1);
+            data.par_chunks_mut(rows_per_band * pitch)
+                .enumerate()
+                .for_each_init(init, |state, (band, rows)| {
+                    for (i, row) in rows.chunks_mut(pitch).enumerate() {
+                        f(state, band * rows_per_band + i, row);
+                    }
+                });
+            return;
+        }
+    }
+    let mut state = init();
+    for (y, row) in data.chunks_mut(pitch).enumerate() {
+        f(&mut state, y, row);
+    }
+}
+
+/// The minimum number of values processed by each parallel task. Smaller inputs are
+/// processed on the current thread.
+#[cfg(feature = "rayon")]
+const MIN_BAND_LEN: usize = 16 * 1024;
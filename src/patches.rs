@@ -0,0 +1,218 @@
+//! Iterators over rectangular patches of an image.
+//!
+//! Patches are yielded as read-only [`SubImage`](https://docs.rs/image/0.23/image/struct.SubImage.html)
+//! views, so no pixel data is copied. Functions in this crate that accept any `GenericImageView`
+//! can be applied to a patch directly, and `to_image` can be used to create an owned copy.
+
+use crate::detection::sliding_windows;
+use crate::point::Point;
+use crate::rect::{view, Rect};
+use image::{GenericImageView, SubImage};
+
+/// Returns an iterator over all `width` by `height` windows of an image whose top left
+/// corners are multiples of `stride` in each direction, in row-major order. Each item is
+/// the x and y coordinates of the top left corner of a window and a view of the window.
+///
+/// Windows are placed as by [`sliding_windows`](../detection/fn.sliding_windows.html).
+///
+/// Only windows lying entirely within the image are yielded, so if `stride` does not evenly
+/// divide the image dimensions then pixels near the right and bottom edges of the image may
+/// not be covered. If the window is larger than the image then no windows are yielded.
+///
+/// # Panics
+///
+/// Panics if `width`, `height` or `stride` is zero.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::GenericImageView;
+/// use imageproc::patches::windows;
+///
+/// let image = gray_image!(
+///     1, 2, 3, 4, 5;
+///     6, 7, 8, 9, 10;
+///     11, 12, 13, 14, 15);
+///
+/// let sums: Vec<(u32, u32, u32)> = windows(&image, 2, 2, 2)
+///     .map(|(x, y, window)| (x, y, window.pixels().map(|(_, _, p)| p[0] as u32).sum()))
+///     .collect();
+///
+/// assert_eq!(sums, vec![(0, 0, 16), (2, 0, 24)]);
+/// # }
+/// ```
+pub fn windows<I: GenericImageView>(
+    image: &I,
+    width: u32,
+    height: u32,
+    stride: u32,
+) -> impl ExactSizeIterator<Item = (u32, u32, SubImage<&I>)> + Clone {
+    sliding_windows(image.width(), image.height(), width, height, stride).map(move |rect| {
+        let window = view(image, rect).expect("sliding windows lie within the image");
+        (rect.left() as u32, rect.top() as u32, window)
+    })
+}
+
+/// Returns an iterator over the `size` by `size` patches of an image centred on each of
+/// the given points.
+///
+/// The patch for point `p` has its top left corner at `(p.x - size / 2, p.y - size / 2)`.
+/// One item is yielded per point, in order. Items are `None` for points whose patch does
+/// not lie entirely within the image, so the results can be zipped with `points` to match
+/// patches to their centres.
+///
+/// # Panics
+///
+/// Panics if `size` is zero.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::GenericImageView;
+/// use imageproc::patches::patches_centered;
+/// use imageproc::point::Point;
+///
+/// let image = gray_image!(
+///     1, 2, 3, 4;
+///     5, 6, 7, 8;
+///     9, 10, 11, 12);
+///
+/// let points = [Point::new(1, 1), Point::new(0, 1), Point::new(2, 1)];
+/// let patches: Vec<_> = patches_centered(&image, points.iter().cloned(), 3).collect();
+///
+/// assert_eq!(patches[0].as_ref().unwrap().get_pixel(0, 0)[0], 1);
+/// assert!(patches[1].is_none());
+/// assert_eq!(patches[2].as_ref().unwrap().get_pixel(0, 0)[0], 2);
+/// # }
+/// ```
+pub fn patches_centered<'a, I, P>(
+    image: &'a I,
+    points: P,
+    size: u32,
+) -> impl Iterator<Item = Option<SubImage<&'a I>>> + 'a
+where
+    I: GenericImageView,
+    P: IntoIterator<Item = Point<u32>>,
+    P::IntoIter: 'a,
+{
+    assert!(size > 0, "patch size must be non-zero");
+    let offset = (size / 2) as i64;
+    points.into_iter().map(move |p| {
+        let left = p.x as i64 - offset;
+        let top = p.y as i64 - offset;
+        if left < 0 || top < 0 || left > i32::MAX as i64 || top > i32::MAX as i64 {
+            return None;
+        }
+        view(image, Rect::at(left as i32, top as i32).of_size(size, size))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gray_bench_image;
+    use image::{GenericImageView, GrayImage, Luma};
+
+    #[test]
+    fn test_windows_positions() {
+        let image = GrayImage::new(7, 5);
+        let positions: Vec<(u32, u32)> = windows(&image, 3, 2, 2).map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(
+            positions,
+            vec![(0, 0), (2, 0), (4, 0), (0, 2), (2, 2), (4, 2)]
+        );
+    }
+
+    #[test]
+    fn test_windows_contents() {
+        let image = gray_bench_image(9, 8);
+        for (x, y, window) in windows(&image, 4, 3, 1) {
+            assert_eq!(window.dimensions(), (4, 3));
+            for (wx, wy, p) in window.pixels() {
+                assert_eq!(p, *image.get_pixel(x + wx, y + wy));
+            }
+        }
+    }
+
+    #[test]
+    fn test_windows_len() {
+        let image = GrayImage::new(10, 6);
+        let mut iter = windows(&image, 4, 4, 3);
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.count(), 2);
+    }
+
+    #[test]
+    fn test_windows_larger_than_image() {
+        let image = GrayImage::new(3, 3);
+        assert_eq!(windows(&image, 4, 1, 1).count(), 0);
+        assert_eq!(windows(&image, 1, 4, 1).count(), 0);
+        assert_eq!(windows(&image, 3, 3, 5).count(), 1);
+    }
+
+    #[test]
+    fn test_windows_of_view() {
+        let image = gray_bench_image(10, 10);
+        let roi = view(&image, Rect::at(2, 3).of_size(5, 4)).unwrap();
+        let corners: Vec<Luma<u8>> = windows(&roi, 2, 2, 2)
+            .map(|(_, _, w)| w.get_pixel(0, 0))
+            .collect();
+        let expected = vec![
+            *image.get_pixel(2, 3),
+            *image.get_pixel(4, 3),
+            *image.get_pixel(2, 5),
+            *image.get_pixel(4, 5),
+        ];
+        assert_eq!(corners, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_windows_rejects_zero_stride() {
+        let _ = windows(&GrayImage::new(3, 3), 1, 1, 0);
+    }
+
+    #[test]
+    fn test_patches_centered_at_borders() {
+        let image = GrayImage::new(5, 5);
+        let points = vec![
+            Point::new(2, 2),
+            Point::new(1, 1),
+            Point::new(0, 2),
+            Point::new(3, 3),
+            Point::new(4, 2),
+            Point::new(100, 100),
+        ];
+        let found: Vec<Option<(u32, u32)>> = patches_centered(&image, points, 3)
+            .map(|p| p.map(|p| p.bounds()).map(|(x, y, _, _)| (x, y)))
+            .collect();
+        assert_eq!(
+            found,
+            vec![Some((1, 1)), Some((0, 0)), None, Some((2, 2)), None, None]
+        );
+    }
+
+    #[test]
+    fn test_patches_centered_even_size() {
+        let image = gray_bench_image(6, 6);
+        let patch = patches_centered(&image, vec![Point::new(3, 3)], 4)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(patch.bounds(), (1, 1, 4, 4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_patches_centered_rejects_zero_size() {
+        let _ = patches_centered(&GrayImage::new(5, 5), vec![Point::new(2, 2)], 0);
+    }
+}
@@ -1,9 +1,10 @@
 //! Pixel manipulations.
 
-use crate::definitions::Clamp;
+use crate::definitions::{Clamp, Image, SaturatingOps};
 use crate::math::cast;
 use conv::ValueInto;
-use image::Pixel;
+use image::{GrayImage, Pixel, Rgba, RgbaImage};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 
 /// Adds pixels with the given weights. Results are clamped to prevent arithmetical overflows.
 ///
@@ -55,6 +56,467 @@ where
     weighted_sum(left, right, left_weight, 1.0 - left_weight)
 }
 
+/// Computes `alpha * a + beta * b + gamma` for each channel of each pixel of two images.
+/// Results are clamped to the range of the subpixel type.
+///
+/// This is the standard primitive for cross-fades and for overlaying heatmaps or other
+/// annotations on an image.
+///
+/// # Panics
+/// If `a` and `b` do not have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::pixelops::add_weighted;
+///
+/// let a = gray_image!(
+///     10, 20;
+///     30, 40);
+///
+/// let b = gray_image!(
+///     100, 100;
+///     200, 250);
+///
+/// let expected = gray_image!(
+///     60,  65;
+///     120, 150);
+///
+/// assert_pixels_eq!(add_weighted(&a, 0.5, &b, 0.5, 5.0), expected);
+///
+/// // Results are clamped
+/// let expected = gray_image!(
+///     110, 120;
+///     230, 255);
+///
+/// assert_pixels_eq!(add_weighted(&a, 1.0, &b, 1.0, 0.0), expected);
+/// # }
+/// ```
+pub fn add_weighted<P>(a: &Image<P>, alpha: f32, b: &Image<P>, beta: f32, gamma: f32) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    assert_dimensions_match!(a, b);
+    let mut out = a.clone();
+    for (p, q) in out.pixels_mut().zip(b.pixels()) {
+        *p = p.map2(q, |u, v| {
+            let u: f32 = cast(u);
+            let v: f32 = cast(v);
+            Clamp::clamp(alpha * u + beta * v + gamma)
+        });
+    }
+    out
+}
+
+/// Computes the bitwise AND of each channel of each pixel of two images.
+///
+/// If `mask` is provided then only pixels at which the mask is non-zero are affected, and
+/// all other pixels are copied from `a`.
+///
+/// # Panics
+/// If `a`, `b` and `mask` do not all have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::pixelops::bitwise_and;
+///
+/// let a = gray_image!(
+///     0b1100, 0b1100;
+///     0b1100, 0b1100);
+///
+/// let b = gray_image!(
+///     0b1010, 0b1010;
+///     0b1010, 0b1010);
+///
+/// let mask = gray_image!(
+///     255, 0;
+///     0, 255);
+///
+/// let expected = gray_image!(
+///     0b1000, 0b1100;
+///     0b1100, 0b1000);
+///
+/// assert_pixels_eq!(bitwise_and(&a, &b, Some(&mask)), expected);
+/// # }
+/// ```
+pub fn bitwise_and<P>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: BitAnd<Output = P::Subpixel>,
+{
+    zip_channels_masked(a, b, mask, |u, v| u & v)
+}
+
+/// Computes the bitwise OR of each channel of each pixel of two images.
+///
+/// Masking is handled as for [`bitwise_and`](fn.bitwise_and.html).
+///
+/// # Panics
+/// If `a`, `b` and `mask` do not all have the same dimensions.
+pub fn bitwise_or<P>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: BitOr<Output = P::Subpixel>,
+{
+    zip_channels_masked(a, b, mask, |u, v| u | v)
+}
+
+/// Computes the bitwise XOR of each channel of each pixel of two images.
+///
+/// Masking is handled as for [`bitwise_and`](fn.bitwise_and.html).
+///
+/// # Panics
+/// If `a`, `b` and `mask` do not all have the same dimensions.
+pub fn bitwise_xor<P>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: BitXor<Output = P::Subpixel>,
+{
+    zip_channels_masked(a, b, mask, |u, v| u ^ v)
+}
+
+/// Computes the bitwise NOT of each channel of each pixel of an image.
+///
+/// If `mask` is provided then only pixels at which the mask is non-zero are affected, and
+/// all other pixels are left unchanged.
+///
+/// # Panics
+/// If `mask` is provided and does not have the same dimensions as `image`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::pixelops::bitwise_not;
+///
+/// let image = gray_image!(0, 10, 255);
+/// let mask = gray_image!(1, 0, 1);
+///
+/// assert_pixels_eq!(bitwise_not(&image, None), gray_image!(255, 245, 0));
+/// assert_pixels_eq!(bitwise_not(&image, Some(&mask)), gray_image!(255, 10, 0));
+/// # }
+/// ```
+pub fn bitwise_not<P>(image: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: Not<Output = P::Subpixel>,
+{
+    zip_channels_masked(image, image, mask, |u, _| !u)
+}
+
+/// Adds each channel of each pixel of two images. Results saturate at the bounds of the
+/// subpixel type, as described in [`SaturatingOps`](../definitions/trait.SaturatingOps.html).
+///
+/// Masking is handled as for [`bitwise_and`](fn.bitwise_and.html).
+///
+/// # Panics
+/// If `a`, `b` and `mask` do not all have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::pixelops::add;
+///
+/// let a = gray_image!(10, 200, 200);
+/// let b = gray_image!(20, 100, 100);
+/// let mask = gray_image!(255, 255, 0);
+///
+/// assert_pixels_eq!(add(&a, &b, Some(&mask)), gray_image!(30, 255, 200));
+/// # }
+/// ```
+pub fn add<P>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: SaturatingOps,
+{
+    zip_channels_masked(a, b, mask, SaturatingOps::saturating_add)
+}
+
+/// Subtracts each channel of each pixel of `b` from that of `a`. Results saturate at the
+/// bounds of the subpixel type.
+///
+/// Masking is handled as for [`bitwise_and`](fn.bitwise_and.html).
+///
+/// # Panics
+/// If `a`, `b` and `mask` do not all have the same dimensions.
+pub fn subtract<P>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: SaturatingOps,
+{
+    zip_channels_masked(a, b, mask, SaturatingOps::saturating_sub)
+}
+
+/// Multiplies each channel of each pixel of two images. Results saturate at the bounds of
+/// the subpixel type.
+///
+/// Masking is handled as for [`bitwise_and`](fn.bitwise_and.html).
+///
+/// # Panics
+/// If `a`, `b` and `mask` do not all have the same dimensions.
+pub fn multiply<P>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: SaturatingOps,
+{
+    zip_channels_masked(a, b, mask, SaturatingOps::saturating_mul)
+}
+
+/// Divides each channel of each pixel of `a` by that of `b`. Results saturate at the bounds
+/// of the subpixel type, and channels divided by zero are set to zero.
+///
+/// Masking is handled as for [`bitwise_and`](fn.bitwise_and.html).
+///
+/// # Panics
+/// If `a`, `b` and `mask` do not all have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::pixelops::divide;
+///
+/// let a = gray_image!(type: f32, 1.0, 3.0, 5.0);
+/// let b = gray_image!(type: f32, 2.0, 0.0, 4.0);
+///
+/// assert_pixels_eq!(divide(&a, &b, None), gray_image!(type: f32, 0.5, 0.0, 1.25));
+/// # }
+/// ```
+pub fn divide<P>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: SaturatingOps,
+{
+    zip_channels_masked(a, b, mask, SaturatingOps::saturating_div)
+}
+
+/// Multiplies each channel of each pixel of an image by `factor`. Results are rounded to the
+/// nearest representable value and saturate at the bounds of the subpixel type.
+///
+/// Masking is handled as for [`bitwise_not`](fn.bitwise_not.html).
+///
+/// # Panics
+/// If `mask` is provided and does not have the same dimensions as `image`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::pixelops::scale;
+///
+/// let image = rgb_image!(type: u16,
+///     [100, 1000, 60000]);
+///
+/// assert_pixels_eq!(
+///     scale(&image, 1.5, None),
+///     rgb_image!(type: u16, [150, 1500, 65535]));
+///
+/// assert_pixels_eq!(
+///     scale(&image, -1.0, None),
+///     rgb_image!(type: u16, [0, 0, 0]));
+/// # }
+/// ```
+pub fn scale<P>(image: &Image<P>, factor: f32, mask: Option<&GrayImage>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: SaturatingOps,
+{
+    zip_channels_masked(image, image, mask, |u, _| u.saturating_scale(factor))
+}
+
+/// Computes the absolute difference of each channel of each pixel of two images.
+///
+/// # Panics
+/// If `a` and `b` do not have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::pixelops::abs_diff;
+///
+/// let a = gray_image!(10, 200, 50);
+/// let b = gray_image!(30, 100, 50);
+///
+/// assert_pixels_eq!(abs_diff(&a, &b), gray_image!(20, 100, 0));
+/// # }
+/// ```
+pub fn abs_diff<P>(a: &Image<P>, b: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    zip_channels_masked(a, b, None, |u, v| if u > v { u - v } else { v - u })
+}
+
+// Combines corresponding channels of a and b at each pixel where mask is non-zero,
+// copying pixels from a elsewhere.
+fn zip_channels_masked<P, F>(a: &Image<P>, b: &Image<P>, mask: Option<&GrayImage>, f: F) -> Image<P>
+where
+    P: Pixel + 'static,
+    F: Fn(P::Subpixel, P::Subpixel) -> P::Subpixel,
+{
+    assert_dimensions_match!(a, b);
+    if let Some(mask) = mask {
+        assert_dimensions_match!(a, mask);
+    }
+    let mut out = a.clone();
+    for (x, y, p) in out.enumerate_pixels_mut() {
+        if let Some(mask) = mask {
+            if mask.get_pixel(x, y)[0] == 0 {
+                continue;
+            }
+        }
+        *p = p.map2(b.get_pixel(x, y), &f);
+    }
+    out
+}
+
+/// How the colors of a source pixel are combined with those of the destination pixel
+/// when compositing using [`composite`](fn.composite.html) or
+/// [`blend_pixel`](fn.blend_pixel.html).
+///
+/// Each mode is described by the color it produces where both pixels are opaque, with
+/// channel values `s` for the source and `d` for the destination scaled to `[0, 1]`.
+/// These match the blend modes of the [W3C compositing specification].
+///
+/// [W3C compositing specification]: https://www.w3.org/TR/compositing-1/#blending
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The Porter-Duff "source over" operator: `s`.
+    Over,
+    /// `s * d`. Darkens the destination.
+    Multiply,
+    /// `s + d - s * d`. Lightens the destination.
+    Screen,
+    /// Multiplies dark destination colors and screens light ones, increasing contrast:
+    /// `2 * s * d` if `d <= 0.5` and `1 - 2 * (1 - s) * (1 - d)` otherwise.
+    Overlay,
+    /// `min(s + d, 1)`.
+    Add,
+    /// `|s - d|`.
+    Difference,
+}
+
+impl BlendMode {
+    fn apply(self, s: f32, d: f32) -> f32 {
+        match self {
+            BlendMode::Over => s,
+            BlendMode::Multiply => s * d,
+            BlendMode::Screen => s + d - s * d,
+            BlendMode::Overlay => {
+                if d <= 0.5 {
+                    2.0 * s * d
+                } else {
+                    1.0 - 2.0 * (1.0 - s) * (1.0 - d)
+                }
+            }
+            BlendMode::Add => (s + d).min(1.0),
+            BlendMode::Difference => (s - d).abs(),
+        }
+    }
+}
+
+/// Composites `src` over `dst` using the given blend mode.
+///
+/// The colors are first combined using `mode`, weighted by the destination alpha so that
+/// a source pixel over a fully transparent destination keeps its own color. The result is
+/// then composited over the destination using the source alpha, with the Porter-Duff
+/// "source over" operator. With `BlendMode::Over` this is standard alpha blending.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::Rgba;
+/// use imageproc::pixelops::{blend_pixel, BlendMode};
+///
+/// let dst = Rgba([200, 100, 0, 255]);
+/// let src = Rgba([0, 0, 255, 255]);
+/// assert_eq!(blend_pixel(dst, src, BlendMode::Over), src);
+/// assert_eq!(blend_pixel(dst, src, BlendMode::Add), Rgba([200, 100, 255, 255]));
+///
+/// // Semi-transparent sources are mixed with the destination
+/// let src = Rgba([0, 0, 255, 51]);
+/// assert_eq!(blend_pixel(dst, src, BlendMode::Over), Rgba([160, 80, 51, 255]));
+/// # }
+/// ```
+pub fn blend_pixel(dst: Rgba<u8>, src: Rgba<u8>, mode: BlendMode) -> Rgba<u8> {
+    let sa = src[3] as f32 / 255.0;
+    let da = dst[3] as f32 / 255.0;
+    let out_alpha = sa + da * (1.0 - sa);
+    if out_alpha == 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+
+    let mut out = [0u8; 4];
+    for c in 0..3 {
+        let s = src[c] as f32 / 255.0;
+        let d = dst[c] as f32 / 255.0;
+        let mixed = (1.0 - da) * s + da * mode.apply(s, d);
+        let value = (sa * mixed + (1.0 - sa) * da * d) / out_alpha;
+        out[c] = (value * 255.0).round().clamp(0.0, 255.0) as u8;
+    }
+    out[3] = (out_alpha * 255.0).round() as u8;
+    Rgba(out)
+}
+
+/// Composites `src` onto `dst` with its top left corner at `(x, y)`, using
+/// [`blend_pixel`](fn.blend_pixel.html) with the given blend mode for each pixel.
+///
+/// `x` and `y` may be negative, and any part of `src` lying outside `dst` is ignored.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::{Rgba, RgbaImage};
+/// use imageproc::pixelops::{composite, BlendMode};
+///
+/// let white = Rgba([255, 255, 255, 255]);
+/// let red = Rgba([255, 0, 0, 255]);
+///
+/// let mut image = RgbaImage::from_pixel(3, 3, white);
+/// let overlay = RgbaImage::from_pixel(2, 2, red);
+///
+/// composite(&mut image, &overlay, -1, 2, BlendMode::Multiply);
+/// assert_eq!(*image.get_pixel(0, 2), red);
+/// assert_eq!(*image.get_pixel(1, 2), white);
+/// assert_eq!(*image.get_pixel(0, 1), white);
+/// # }
+/// ```
+pub fn composite(dst: &mut RgbaImage, src: &RgbaImage, x: i32, y: i32, mode: BlendMode) {
+    let (dst_width, dst_height) = (dst.width() as i64, dst.height() as i64);
+    for (sx, sy, p) in src.enumerate_pixels() {
+        let (dx, dy) = (x as i64 + sx as i64, y as i64 + sy as i64);
+        if dx < 0 || dy < 0 || dx >= dst_width || dy >= dst_height {
+            continue;
+        }
+        let q = dst.get_pixel_mut(dx as u32, dy as u32);
+        *q = blend_pixel(*q, *p, mode);
+    }
+}
+
 #[inline(always)]
 fn weighted_channel_sum<C>(left: C, right: C, left_weight: f32, right_weight: f32) -> C
 where
@@ -66,7 +528,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use image::{Luma, Rgb};
+    use image::{GrayImage, ImageBuffer, Luma, LumaA, Rgb};
     use test::{black_box, Bencher};
 
     #[test]
@@ -79,6 +541,147 @@ mod tests {
         assert_eq!(weighted_channel_sum(150u8, 150u8, 1.8, 0.8), 255u8);
     }
 
+    #[test]
+    fn test_add_weighted_rgb_with_negative_weight() {
+        let a = rgb_image!([100, 50, 0], [200, 10, 30]);
+        let b = rgb_image!([10, 60, 0], [20, 0, 30]);
+        // Unsharp-mask style: 2a - b
+        let expected = rgb_image!([190, 40, 0], [255, 20, 30]);
+        assert_pixels_eq!(add_weighted(&a, 2.0, &b, -1.0, 0.0), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_weighted_rejects_mismatched_dimensions() {
+        let _ = add_weighted(&GrayImage::new(2, 3), 0.5, &GrayImage::new(3, 2), 0.5, 0.0);
+    }
+
+    #[test]
+    fn test_bitwise_ops_rgb() {
+        let a = rgb_image!([0b1100, 0, 255], [0b0101, 1, 2]);
+        let b = rgb_image!([0b1010, 0, 15], [0b0011, 3, 2]);
+        assert_pixels_eq!(
+            bitwise_or(&a, &b, None),
+            rgb_image!([0b1110, 0, 255], [0b0111, 3, 2])
+        );
+        assert_pixels_eq!(
+            bitwise_xor(&a, &b, None),
+            rgb_image!([0b0110, 0, 240], [0b0110, 2, 0])
+        );
+    }
+
+    #[test]
+    fn test_masked_arithmetic() {
+        let a = gray_image!(type: u16, 100, 100, 100, 60000);
+        let b = gray_image!(type: u16, 30, 300, 3, 2);
+        let mask = gray_image!(1, 1, 1, 0);
+        assert_pixels_eq!(
+            subtract(&a, &b, Some(&mask)),
+            gray_image!(type: u16, 70, 0, 97, 60000)
+        );
+        assert_pixels_eq!(
+            multiply(&a, &b, None),
+            gray_image!(type: u16, 3000, 30000, 300, 65535)
+        );
+        assert_pixels_eq!(
+            divide(&a, &b, Some(&mask)),
+            gray_image!(type: u16, 3, 0, 33, 60000)
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_on_float_luma_alpha() {
+        let a = ImageBuffer::from_pixel(2, 1, LumaA([0.5f32, 1.0]));
+        let b = ImageBuffer::from_pixel(2, 1, LumaA([2.0f32, 0.25]));
+        assert_pixels_eq!(
+            add(&a, &b, None),
+            ImageBuffer::from_pixel(2, 1, LumaA([2.5f32, 1.25]))
+        );
+        assert_pixels_eq!(
+            subtract(&a, &b, None),
+            ImageBuffer::from_pixel(2, 1, LumaA([-1.5f32, 0.75]))
+        );
+        assert_pixels_eq!(
+            scale(&a, 3.0, None),
+            ImageBuffer::from_pixel(2, 1, LumaA([1.5f32, 3.0]))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_masked_ops_reject_mismatched_mask() {
+        let image = GrayImage::new(2, 2);
+        let _ = add(&image, &image, Some(&GrayImage::new(2, 3)));
+    }
+
+    #[test]
+    fn test_blend_pixel_transparent_source_leaves_destination_unchanged() {
+        let dst = Rgba([12u8, 34, 56, 200]);
+        let src = Rgba([255u8, 255, 255, 0]);
+        for &mode in &[
+            BlendMode::Over,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::Overlay,
+            BlendMode::Add,
+            BlendMode::Difference,
+        ] {
+            assert_eq!(blend_pixel(dst, src, mode), dst);
+        }
+    }
+
+    #[test]
+    fn test_blend_pixel_over_transparent_destination_keeps_source_color() {
+        let dst = Rgba([255u8, 255, 255, 0]);
+        let src = Rgba([10u8, 20, 30, 128]);
+        assert_eq!(blend_pixel(dst, src, BlendMode::Multiply), src);
+        assert_eq!(blend_pixel(dst, src, BlendMode::Difference), src);
+    }
+
+    #[test]
+    fn test_blend_pixel_opaque_modes() {
+        let dst = Rgba([51u8, 204, 102, 255]);
+        let src = Rgba([153u8, 153, 255, 255]);
+        assert_eq!(
+            blend_pixel(dst, src, BlendMode::Multiply),
+            Rgba([31, 122, 102, 255])
+        );
+        assert_eq!(
+            blend_pixel(dst, src, BlendMode::Screen),
+            Rgba([173, 235, 255, 255])
+        );
+        assert_eq!(
+            blend_pixel(dst, src, BlendMode::Overlay),
+            Rgba([61, 214, 204, 255])
+        );
+        assert_eq!(
+            blend_pixel(dst, src, BlendMode::Difference),
+            Rgba([102, 51, 153, 255])
+        );
+    }
+
+    #[test]
+    fn test_blend_pixel_over_matches_image_blend() {
+        let dst = Rgba([10u8, 200, 30, 255]);
+        let src = Rgba([250u8, 0, 100, 100]);
+        let mut expected = dst;
+        expected.blend(&src);
+        let actual = blend_pixel(dst, src, BlendMode::Over);
+        for c in 0..4 {
+            assert!((actual[c] as i32 - expected[c] as i32).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn test_composite_clips_to_destination() {
+        let mut image = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        let overlay = RgbaImage::from_pixel(3, 3, Rgba([255, 255, 255, 255]));
+        composite(&mut image, &overlay, 1, -2, BlendMode::Over);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(1, 1), Rgba([0, 0, 0, 255]));
+    }
+
     #[bench]
     fn bench_weighted_sum_rgb(b: &mut Bencher) {
         b.iter(|| {
@@ -0,0 +1,332 @@
+//! Gaussian and Laplacian [image pyramids].
+//!
+//! [image pyramids]: https://en.wikipedia.org/wiki/Pyramid_(image_processing)
+
+use crate::definitions::{Clamp, Image};
+use crate::map::{map_subpixels, ChannelMap, WithChannel};
+use crate::math::cast;
+use conv::ValueInto;
+use image::{ImageBuffer, Pixel};
+
+// The 5-tap binomial approximation to a Gaussian used by all pyramid operations.
+const KERNEL: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+
+/// Blurs an image with a 5x5 Gaussian kernel and then discards every other row and column.
+///
+/// The output has dimensions `((width + 1) / 2, (height + 1) / 2)`. Pixels outside the image
+/// are treated as having the value of the nearest pixel inside it. This is equivalent to
+/// OpenCV's `pyrDown`.
+pub fn pyr_down<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (width, height) = image.dimensions();
+    resample(
+        image,
+        width - width / 2,
+        height - height / 2,
+        down_taps,
+        down_taps,
+    )
+}
+
+/// Doubles the width and height of an image, filling in new pixels by Gaussian interpolation.
+///
+/// This is approximately the inverse of [`pyr_down`](fn.pyr_down.html), and is equivalent
+/// to OpenCV's `pyrUp`. Use [`pyr_up_to_size`](fn.pyr_up_to_size.html) to upsample the output
+/// of `pyr_down` on an image with odd width or height back to the original dimensions.
+pub fn pyr_up<P>(image: &Image<P>) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (width, height) = image.dimensions();
+    pyr_up_to_size(image, 2 * width, 2 * height)
+}
+
+/// Upsamples an image to the given dimensions, filling in new pixels by Gaussian interpolation.
+///
+/// The output pixel at `(2 * x, 2 * y)` corresponds to the input pixel at `(x, y)`.
+///
+/// # Panics
+/// If `width` is not equal to `2 * image.width()` or `2 * image.width() - 1`, or
+/// similarly for `height`.
+pub fn pyr_up_to_size<P>(image: &Image<P>, width: u32, height: u32) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    let (in_width, in_height) = image.dimensions();
+    assert!(
+        width - width / 2 == in_width && height - height / 2 == in_height,
+        "pyr_up_to_size output dimensions must be within one of twice the input dimensions"
+    );
+    resample(image, width, height, up_taps, up_taps)
+}
+
+/// Computes a Gaussian pyramid with the given number of levels.
+///
+/// The first level is a copy of `image`, and each subsequent level is obtained by applying
+/// [`pyr_down`](fn.pyr_down.html) to its predecessor.
+///
+/// # Panics
+/// If `levels` is 0.
+pub fn gaussian_pyramid<P>(image: &Image<P>, levels: usize) -> Vec<Image<P>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+{
+    assert!(levels > 0, "levels must be > 0");
+    let mut pyramid = Vec::with_capacity(levels);
+    pyramid.push(image.clone());
+    for i in 1..levels {
+        let next = pyr_down(&pyramid[i - 1]);
+        pyramid.push(next);
+    }
+    pyramid
+}
+
+/// Computes a Laplacian pyramid with the given number of levels.
+///
+/// Each level except the last is the difference between a level of the Gaussian pyramid of
+/// `image` and the upsampled next level. The last level is the smallest level of the Gaussian
+/// pyramid. Levels are stored with `f32` channels, as differences can be negative.
+///
+/// The input image can be recovered using
+/// [`reconstruct_laplacian_pyramid`](fn.reconstruct_laplacian_pyramid.html).
+///
+/// # Panics
+/// If `levels` is 0.
+pub fn laplacian_pyramid<P>(image: &Image<P>, levels: usize) -> Vec<Image<ChannelMap<P, f32>>>
+where
+    P: WithChannel<f32> + 'static,
+    P::Subpixel: ValueInto<f32>,
+{
+    let image: Image<ChannelMap<P, f32>> = map_subpixels(image, cast);
+    let mut pyramid = gaussian_pyramid(&image, levels);
+    for i in 0..levels - 1 {
+        let (width, height) = pyramid[i].dimensions();
+        let upsampled = pyr_up_to_size(&pyramid[i + 1], width, height);
+        let level: &mut [f32] = pyramid[i].as_mut();
+        for (l, u) in level.iter_mut().zip(upsampled.iter()) {
+            *l -= *u;
+        }
+    }
+    pyramid
+}
+
+/// Reconstructs an image from its [`laplacian_pyramid`](fn.laplacian_pyramid.html).
+///
+/// # Panics
+/// If `pyramid` is empty, or if the dimensions of its levels are not consistent with
+/// its having been produced by `laplacian_pyramid`.
+pub fn reconstruct_laplacian_pyramid<P>(pyramid: &[Image<P>]) -> Image<P>
+where
+    P: Pixel<Subpixel = f32> + 'static,
+{
+    let (last, rest) = pyramid.split_last().expect("pyramid must not be empty");
+    let mut image = last.clone();
+    for level in rest.iter().rev() {
+        image = pyr_up_to_size(&image, level.width(), level.height());
+        let data: &mut [f32] = image.as_mut();
+        for (i, l) in data.iter_mut().zip(level.iter()) {
+            *i += *l;
+        }
+    }
+    image
+}
+
+// Appends the input indices and weights contributing to output index x of a downsampling pass.
+fn down_taps(x: usize, len: usize, taps: &mut Vec<(usize, f32)>) {
+    for (k, w) in KERNEL.iter().enumerate() {
+        let i = (2 * x + k) as isize - 2;
+        taps.push((i.max(0).min(len as isize - 1) as usize, *w));
+    }
+}
+
+// Appends the input indices and weights contributing to output index x of an upsampling pass.
+// This is equivalent to inserting zeros between input pixels and filtering with twice KERNEL.
+fn up_taps(x: usize, len: usize, taps: &mut Vec<(usize, f32)>) {
+    for (k, w) in KERNEL.iter().enumerate() {
+        let j = x as isize + k as isize - 2;
+        if j % 2 == 0 {
+            let i = j / 2;
+            taps.push((i.max(0).min(len as isize - 1) as usize, 2.0 * w));
+        }
+    }
+}
+
+// Resamples an image using a separable filter. The taps functions append the input
+// indices and weights contributing to a given output index along each axis, given the
+// length of the input along that axis.
+pub(crate) fn resample<P, Fx, Fy>(
+    image: &Image<P>,
+    width: u32,
+    height: u32,
+    x_taps: Fx,
+    y_taps: Fy,
+) -> Image<P>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32>,
+    Fx: Fn(usize, usize, &mut Vec<(usize, f32)>),
+    Fy: Fn(usize, usize, &mut Vec<(usize, f32)>),
+{
+    let (in_width, in_height) = image.dimensions();
+    if in_width == 0 || in_height == 0 {
+        return ImageBuffer::new(width, height);
+    }
+
+    let channels = P::CHANNEL_COUNT as usize;
+    let data: Vec<f32> = image.iter().map(|c| cast(*c)).collect();
+    let (in_width, in_height) = (in_width as usize, in_height as usize);
+    let (width, height) = (width as usize, height as usize);
+
+    let horizontal = transposed_pass(&data, in_width, in_height, channels, width, &x_taps);
+    let vertical = transposed_pass(&horizontal, in_height, width, channels, height, &y_taps);
+
+    let out = vertical.into_iter().map(P::Subpixel::clamp).collect();
+    ImageBuffer::from_raw(width as u32, height as u32, out).unwrap()
+}
+
+// Filters each row of a row-major image of the given dimensions, producing rows of
+// length out_width, and returns the transpose of the result.
+fn transposed_pass<F>(
+    data: &[f32],
+    width: usize,
+    height: usize,
+    channels: usize,
+    out_width: usize,
+    taps: &F,
+) -> Vec<f32>
+where
+    F: Fn(usize, usize, &mut Vec<(usize, f32)>),
+{
+    let mut out = vec![0.0; out_width * height * channels];
+    let mut x_taps = Vec::new();
+    for x in 0..out_width {
+        x_taps.clear();
+        taps(x, width, &mut x_taps);
+        for y in 0..height {
+            let row = &data[y * width * channels..(y + 1) * width * channels];
+            let o = (x * height + y) * channels;
+            for &(i, w) in &x_taps {
+                for c in 0..channels {
+                    out[o + c] += w * row[i * channels + c];
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::gray_bench_image;
+    use image::{GrayImage, Luma, Rgb, RgbImage};
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn test_pyr_down_dimensions() {
+        let image = GrayImage::new(5, 4);
+        assert_eq!(pyr_down(&image).dimensions(), (3, 2));
+        assert_eq!(pyr_down(&GrayImage::new(0, 0)).dimensions(), (0, 0));
+    }
+
+    #[test]
+    fn test_pyr_down_constant() {
+        let image = RgbImage::from_pixel(7, 6, Rgb([10, 20, 30]));
+        let expected = RgbImage::from_pixel(4, 3, Rgb([10, 20, 30]));
+        assert_pixels_eq!(pyr_down(&image), expected);
+    }
+
+    #[test]
+    fn test_pyr_down() {
+        let image = gray_image!(type: f32,
+            0.0, 0.0, 0.0, 0.0;
+            0.0, 0.0, 0.0, 0.0;
+            0.0, 0.0, 256.0, 0.0;
+            0.0, 0.0, 0.0, 0.0);
+
+        // Output pixel (x, y) is centred on input pixel (2x, 2y), and the kernel weights
+        // at offsets 0 and 2 are 6/16 and 1/16.
+        let expected = gray_image!(type: f32,
+            1.0, 6.0;
+            6.0, 36.0);
+
+        assert_pixels_eq!(pyr_down(&image), expected);
+    }
+
+    #[test]
+    fn test_pyr_up() {
+        let image = gray_image!(type: f32,
+            0.0, 8.0;
+            0.0, 8.0);
+
+        let expected_row = [1.0, 4.0, 7.0, 8.0];
+        let actual = pyr_up(&image);
+        assert_eq!(actual.dimensions(), (4, 4));
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(actual.get_pixel(x, y)[0], expected_row[x as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pyr_up_to_size_constant() {
+        let image = GrayImage::from_pixel(3, 2, Luma([7]));
+        let expected = GrayImage::from_pixel(5, 4, Luma([7]));
+        assert_pixels_eq!(pyr_up_to_size(&image, 5, 4), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pyr_up_to_size_invalid_dimensions() {
+        let image = GrayImage::new(3, 2);
+        pyr_up_to_size(&image, 7, 4);
+    }
+
+    #[test]
+    fn test_gaussian_pyramid() {
+        let image = GrayImage::new(13, 8);
+        let dimensions: Vec<_> = gaussian_pyramid(&image, 4)
+            .iter()
+            .map(|level| level.dimensions())
+            .collect();
+        assert_eq!(dimensions, vec![(13, 8), (7, 4), (4, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn test_laplacian_pyramid_reconstruction() {
+        let image = gray_bench_image(23, 17);
+        let pyramid = laplacian_pyramid(&image, 4);
+        assert_eq!(pyramid.len(), 4);
+        assert_eq!(pyramid[3].dimensions(), (3, 3));
+
+        let reconstructed = reconstruct_laplacian_pyramid(&pyramid);
+        for (r, i) in reconstructed.iter().zip(image.iter()) {
+            assert!((r - *i as f32).abs() < 1e-3);
+        }
+    }
+
+    #[bench]
+    fn bench_pyr_down(b: &mut Bencher) {
+        let image = gray_bench_image(500, 500);
+        b.iter(|| {
+            let down = pyr_down(&image);
+            black_box(down);
+        });
+    }
+
+    #[bench]
+    fn bench_pyr_up(b: &mut Bencher) {
+        let image = gray_bench_image(250, 250);
+        b.iter(|| {
+            let up = pyr_up(&image);
+            black_box(up);
+        });
+    }
+}
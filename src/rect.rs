@@ -1,5 +1,6 @@
 //! Basic manipulation of rectangles.
 
+use image::{GenericImageView, SubImage};
 use std::cmp;
 
 /// A rectangular region of non-zero width and height.
@@ -123,6 +124,54 @@ impl Rect {
     }
 }
 
+/// Returns a view of the region of `image` covered by `rect`, or `None` if `rect` does not
+/// lie entirely within the image.
+///
+/// Functions in this crate that accept any `GenericImageView` can be applied to the view to
+/// process a region of interest without copying it. Functions that require an `Image` can be
+/// applied to a copy of the region, created using `to_image`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::Luma;
+/// use imageproc::rect::{view, Rect};
+/// use imageproc::region_labelling::{connected_components, Connectivity};
+///
+/// let image = gray_image!(
+///     1, 1, 0, 0;
+///     0, 0, 0, 1;
+///     0, 1, 0, 1);
+///
+/// let roi = view(&image, Rect::at(1, 1).of_size(3, 2)).unwrap();
+/// let labels = gray_image!(type: u32,
+///     0, 0, 1;
+///     2, 0, 1);
+/// assert_pixels_eq!(connected_components(&roi, Connectivity::Eight, Luma([0])), labels);
+///
+/// assert!(view(&image, Rect::at(2, 0).of_size(3, 1)).is_none());
+/// # }
+/// ```
+pub fn view<I: GenericImageView>(image: &I, rect: Rect) -> Option<SubImage<&I>> {
+    let in_bounds = rect.left >= 0
+        && rect.top >= 0
+        && rect.left as u64 + rect.width as u64 <= image.width() as u64
+        && rect.top as u64 + rect.height as u64 <= image.height() as u64;
+    if !in_bounds {
+        return None;
+    }
+    Some(SubImage::new(
+        image,
+        rect.left as u32,
+        rect.top as u32,
+        rect.width,
+        rect.height,
+    ))
+}
+
 impl Region<i32> for Rect {
     fn contains(&self, x: i32, y: i32) -> bool {
         self.left <= x && x <= self.right() && self.top <= y && y <= self.bottom()
@@ -163,9 +212,144 @@ impl RectPosition {
     }
 }
 
+/// A rectangle with floating point center and size, rotated about its center.
+///
+/// The rectangle covers the points whose offset from `center`, after rotating by `-angle`,
+/// lies within `width / 2` horizontally and `height / 2` vertically. `angle` is measured in
+/// radians, clockwise in image coordinates (i.e. with the y axis pointing down).
+///
+/// # Examples
+/// ```
+/// use imageproc::rect::{Region, RotatedRect};
+/// use std::f32::consts::PI;
+///
+/// // A 6x2 rectangle centred at (10, 10), rotated by 90 degrees.
+/// let rect = RotatedRect::new((10.0, 10.0), 6.0, 2.0, PI / 2.0);
+///
+/// assert!(rect.contains(10.0, 12.5));
+/// assert!(!rect.contains(12.5, 10.0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RotatedRect {
+    center: (f32, f32),
+    width: f32,
+    height: f32,
+    angle: f32,
+}
+
+impl RotatedRect {
+    /// Constructs a rectangle with the given center, size and rotation in radians.
+    /// Width and height are required to be strictly positive.
+    pub fn new(center: (f32, f32), width: f32, height: f32, angle: f32) -> RotatedRect {
+        assert!(width > 0.0, "width must be strictly positive");
+        assert!(height > 0.0, "height must be strictly positive");
+        RotatedRect {
+            center,
+            width,
+            height,
+            angle,
+        }
+    }
+
+    /// Center of rect.
+    pub fn center(&self) -> (f32, f32) {
+        self.center
+    }
+
+    /// Width of rect, measured along its rotated x axis.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Height of rect, measured along its rotated y axis.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Rotation of rect about its center, in radians.
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    /// The corners of rect, in the order top-left, top-right, bottom-right, bottom-left
+    /// before rotation.
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        self.corners_with_extents(self.width / 2.0, self.height / 2.0)
+    }
+
+    /// Returns the four points at the given offsets from the center along the rotated axes.
+    pub(crate) fn corners_with_extents(
+        &self,
+        half_width: f32,
+        half_height: f32,
+    ) -> [(f32, f32); 4] {
+        let (sin, cos) = self.angle.sin_cos();
+        let (cx, cy) = self.center;
+        let corner = |dx: f32, dy: f32| (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos);
+        [
+            corner(-half_width, -half_height),
+            corner(half_width, -half_height),
+            corner(half_width, half_height),
+            corner(-half_width, half_height),
+        ]
+    }
+
+    /// Smallest axis-aligned rectangle containing all points of this rect,
+    /// rounded outwards to integer coordinates.
+    pub fn bounding_rect(&self) -> Rect {
+        let corners = self.corners();
+        let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min);
+        let max_x = corners
+            .iter()
+            .map(|c| c.0)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min);
+        let max_y = corners
+            .iter()
+            .map(|c| c.1)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let (left, top) = (min_x.floor() as i32, min_y.floor() as i32);
+        let (right, bottom) = (max_x.ceil() as i32, max_y.ceil() as i32);
+        Rect::at(left, top).of_size((right - left + 1) as u32, (bottom - top + 1) as u32)
+    }
+}
+
+impl Region<f32> for RotatedRect {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        let (sin, cos) = self.angle.sin_cos();
+        let (dx, dy) = (x - self.center.0, y - self.center.1);
+        let (u, v) = (dx * cos + dy * sin, -dx * sin + dy * cos);
+        u.abs() <= self.width / 2.0 && v.abs() <= self.height / 2.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Rect, Region};
+    use super::{view, Rect, Region, RotatedRect};
+    use image::{GenericImageView, GrayImage, Luma};
+
+    #[test]
+    fn test_view() {
+        let image = GrayImage::from_fn(5, 4, |x, y| Luma([(10 * y + x) as u8]));
+
+        let roi = view(&image, Rect::at(1, 2).of_size(4, 2)).unwrap();
+        assert_eq!(roi.dimensions(), (4, 2));
+        assert_eq!(roi.get_pixel(0, 0), Luma([21]));
+        assert_eq!(roi.get_pixel(3, 1), Luma([34]));
+
+        let whole = view(&image, Rect::at(0, 0).of_size(5, 4)).unwrap();
+        assert_eq!(whole.to_image(), image);
+    }
+
+    #[test]
+    fn test_view_rejects_rects_outside_image() {
+        let image = GrayImage::new(5, 4);
+        assert!(view(&image, Rect::at(-1, 0).of_size(2, 2)).is_none());
+        assert!(view(&image, Rect::at(0, -1).of_size(2, 2)).is_none());
+        assert!(view(&image, Rect::at(4, 0).of_size(2, 2)).is_none());
+        assert!(view(&image, Rect::at(0, 3).of_size(2, 2)).is_none());
+        assert!(view(&image, Rect::at(0, 0).of_size(6, 1)).is_none());
+    }
 
     #[test]
     #[should_panic]
@@ -188,4 +372,28 @@ mod tests {
         assert!(r.contains(5f32, 5f32));
         assert!(!r.contains(10.1f32, 10f32));
     }
+
+    #[test]
+    fn test_rotated_rect_corners() {
+        let r = RotatedRect::new((5.0, 5.0), 4.0, 2.0, 0.0);
+        assert_eq!(
+            r.corners(),
+            [(3.0, 4.0), (7.0, 4.0), (7.0, 6.0), (3.0, 6.0)]
+        );
+        assert_eq!(r.bounding_rect(), Rect::at(3, 4).of_size(5, 3));
+    }
+
+    #[test]
+    fn test_rotated_rect_contains() {
+        let r = RotatedRect::new((0.0, 0.0), 4.0, 2.0, std::f32::consts::PI / 4.0);
+        assert!(r.contains(0.0, 0.0));
+        assert!(r.contains(1.0, 1.0));
+        assert!(!r.contains(1.0, -1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rotated_rect_rejects_empty_rectangle() {
+        RotatedRect::new((0.0, 0.0), 0.0, 1.0, 0.0);
+    }
 }
@@ -3,6 +3,7 @@
 use image::{GenericImage, GenericImageView, ImageBuffer, Luma};
 
 use crate::definitions::Image;
+use crate::error::{check_dimensions, Error};
 use crate::union_find::DisjointSetForest;
 use std::cmp;
 
@@ -124,20 +125,69 @@ pub fn connected_components<I>(
     background: I::Pixel,
 ) -> Image<Luma<u32>>
 where
-    I: GenericImage,
+    I: GenericImageView,
     I::Pixel: Eq,
 {
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(width, height);
+    connected_components_into(image, conn, background, &mut out);
+    out
+}
+
+/// Labels the connected foreground components of an image, writing the labels to `out`,
+/// which must have the same dimensions as `image`. Any existing contents of `out` are
+/// overwritten.
+///
+/// The output image is not allocated, but working storage proportional to the number of
+/// pixels in the image is. See the [`connected_components`](fn.connected_components.html)
+/// documentation for more information.
+///
+/// # Panics
+/// Panics if `out` does not have the same dimensions as `image`, or if the image contains
+/// 2<sup>32</sup> or more pixels.
+pub fn connected_components_into<I>(
+    image: &I,
+    conn: Connectivity,
+    background: I::Pixel,
+    out: &mut Image<Luma<u32>>,
+) where
+    I: GenericImageView,
+    I::Pixel: Eq,
+{
+    try_connected_components_into(image, conn, background, out).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Labels the connected foreground components of an image, writing the labels to `out`.
+/// Returns an error if `out` does not have the same dimensions as `image`.
+///
+/// See the [`connected_components_into`](fn.connected_components_into.html) documentation
+/// for more information.
+///
+/// # Panics
+/// Panics if the image contains 2<sup>32</sup> or more pixels.
+pub fn try_connected_components_into<I>(
+    image: &I,
+    conn: Connectivity,
+    background: I::Pixel,
+    out: &mut Image<Luma<u32>>,
+) -> Result<(), Error>
+where
+    I: GenericImageView,
+    I::Pixel: Eq,
+{
+    check_dimensions(out.dimensions(), image.dimensions())?;
     let (width, height) = image.dimensions();
     let image_size = width as usize * height as usize;
     if image_size >= 2usize.saturating_pow(32) {
         panic!("Images with 2^32 or more pixels are not supported");
     }
 
-    let mut out = ImageBuffer::new(width, height);
+    // Background pixels are skipped below, so must be cleared here.
+    out.iter_mut().for_each(|p| *p = 0);
 
     // TODO: add macro to abandon early if either dimension is zero
     if width == 0 || height == 0 {
-        return out;
+        return Ok(());
     }
 
     let mut forest = DisjointSetForest::new(image_size);
@@ -238,22 +288,75 @@ where
             }
         }
     }
-
-    out
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     extern crate wasm_bindgen_test;
 
-    use super::connected_components;
     use super::Connectivity::{Eight, Four};
+    use super::{connected_components, connected_components_into, try_connected_components_into};
     use crate::definitions::{HasBlack, HasWhite};
+    use crate::error::Error;
     use ::test;
     use image::{GrayImage, ImageBuffer, Luma};
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::*;
 
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_connected_components_of_view_match_copy() {
+        use crate::rect::{view, Rect};
+
+        let image = GrayImage::from_fn(12, 10, |x, y| Luma([((x * 3 + y * 5) % 7 < 3) as u8]));
+        let roi = view(&image, Rect::at(3, 2).of_size(7, 6)).unwrap();
+        assert_pixels_eq!(
+            connected_components(&roi, Eight, Luma::black()),
+            connected_components(&roi.to_image(), Eight, Luma::black())
+        );
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_connected_components_into_overwrites_output() {
+        let image = gray_image!(
+            1, 0, 1, 1;
+            0, 1, 1, 0;
+            0, 0, 0, 0;
+            0, 0, 0, 1);
+
+        let mut out = ImageBuffer::from_pixel(4, 4, Luma([9u32]));
+        for &conn in &[Four, Eight] {
+            connected_components_into(&image, conn, Luma::black(), &mut out);
+            assert_pixels_eq!(out, connected_components(&image, conn, Luma::black()));
+        }
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_try_connected_components_into_checks_dimensions() {
+        let image = gray_image!(
+            1, 0, 1;
+            0, 0, 1);
+
+        let mut out = ImageBuffer::new(3, 2);
+        assert_eq!(
+            try_connected_components_into(&image, Four, Luma::black(), &mut out),
+            Ok(())
+        );
+        assert_pixels_eq!(out, connected_components(&image, Four, Luma::black()));
+
+        let mut out = ImageBuffer::new(2, 3);
+        assert_eq!(
+            try_connected_components_into(&image, Four, Luma::black(), &mut out),
+            Err(Error::DimensionMismatch {
+                expected: (3, 2),
+                actual: (2, 3)
+            })
+        );
+    }
+
     #[cfg_attr(not(target_arch = "wasm32"), test)]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn test_connected_components_eight_white_background() {
@@ -3,15 +3,57 @@
 //!
 //! [seam carving]: https://en.wikipedia.org/wiki/Seam_carving
 
-use crate::definitions::{HasBlack, Image};
+use crate::definitions::{HasBlack, Image, MaybeSync};
+use crate::geometric_transformations::transpose;
 use crate::gradients::sobel_gradient_map;
 use crate::map::{map_colors, WithChannel};
 use image::{GrayImage, Luma, Pixel, Rgb};
-use std::cmp::min;
 
 /// An image seam connecting the bottom of an image to its top (in that order).
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct VerticalSeam(Vec<u32>);
 
+impl VerticalSeam {
+    /// Creates a seam from the x coordinate of the seam in each row, starting from the
+    /// bottom row of the image.
+    pub fn new(xs: Vec<u32>) -> VerticalSeam {
+        VerticalSeam(xs)
+    }
+
+    /// The x coordinate of the seam in each row, starting from the bottom row of the image.
+    pub fn xs(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+/// An image seam connecting the right of an image to its left (in that order).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HorizontalSeam(Vec<u32>);
+
+impl HorizontalSeam {
+    /// Creates a seam from the y coordinate of the seam in each column, starting from the
+    /// rightmost column of the image.
+    pub fn new(ys: Vec<u32>) -> HorizontalSeam {
+        HorizontalSeam(ys)
+    }
+
+    /// The y coordinate of the seam in each column, starting from the rightmost column
+    /// of the image.
+    pub fn ys(&self) -> &[u32] {
+        &self.0
+    }
+
+    // The vertical seam in the transpose of an image corresponding to this seam.
+    fn transposed(&self) -> VerticalSeam {
+        VerticalSeam(self.0.clone())
+    }
+}
+
+// Energy added to protected pixels and subtracted from pixels to be removed. This is
+// large enough that the presence or absence of a single such pixel in a seam outweighs
+// any difference in gradients.
+const MASK_ENERGY: i64 = 1 << 40;
+
 /// Reduces the width of an image using seam carving.
 ///
 /// Warning: this is very slow! It implements the algorithm from
@@ -22,7 +64,28 @@ pub struct VerticalSeam(Vec<u32>);
 pub fn shrink_width<P>(image: &Image<P>, target_width: u32) -> Image<P>
 // TODO: this is pretty silly! We should just be able to express that we want a pixel which is a slice of integral values
 where
-    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + 'static,
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    assert!(
+        target_width <= image.width(),
+        "target_width must be <= input image width"
+    );
+
+    shrink_width_with_seams(image, target_width).0
+}
+
+/// Reduces the width of an image using seam carving, and returns the removed seams
+/// in the order they were removed.
+///
+/// The seams can be drawn on the original image using
+/// [`draw_vertical_seams`](fn.draw_vertical_seams.html).
+pub fn shrink_width_with_seams<P>(
+    image: &Image<P>,
+    target_width: u32,
+) -> (Image<P>, Vec<VerticalSeam>)
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
     <P as WithChannel<u16>>::Pixel: HasBlack,
 {
     assert!(
@@ -32,20 +95,195 @@ where
 
     let iterations = image.width() - target_width;
     let mut result = image.clone();
+    let mut seams = Vec::with_capacity(iterations as usize);
 
     for _ in 0..iterations {
         let seam = find_vertical_seam(&result);
         result = remove_vertical_seam(&result, &seam);
+        seams.push(seam);
+    }
+
+    (result, seams)
+}
+
+/// Reduces the width of an image using seam carving, avoiding pixels in `protect` and
+/// preferentially removing pixels in `remove`.
+///
+/// Each mask must have the same dimensions as `image`, and a pixel is in a mask if its
+/// value in that mask is non-zero. Seams only pass through protected pixels if there is
+/// no alternative.
+pub fn shrink_width_masked<P>(
+    image: &Image<P>,
+    target_width: u32,
+    protect: Option<&GrayImage>,
+    remove: Option<&GrayImage>,
+) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    assert!(
+        target_width <= image.width(),
+        "target_width must be <= input image width"
+    );
+
+    let mut result = image.clone();
+    let mut protect = protect.cloned();
+    let mut remove = remove.cloned();
+
+    for _ in target_width..image.width() {
+        let seam = find_vertical_seam_masked(&result, protect.as_ref(), remove.as_ref());
+        result = remove_vertical_seam(&result, &seam);
+        protect = protect.map(|m| remove_vertical_seam(&m, &seam));
+        remove = remove.map(|m| remove_vertical_seam(&m, &seam));
+    }
+
+    result
+}
+
+/// Removes an object from an image by repeatedly removing vertical seams passing through
+/// the pixels in `remove`, until none remain. Pixels in `protect` are avoided.
+///
+/// Each mask must have the same dimensions as `image`, and a pixel is in a mask if its
+/// value in that mask is non-zero. The output is narrower than `image` by the number of
+/// seams removed.
+pub fn remove_object<P>(
+    image: &Image<P>,
+    remove: &GrayImage,
+    protect: Option<&GrayImage>,
+) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    let mut result = image.clone();
+    let mut remove = remove.clone();
+    let mut protect = protect.cloned();
+
+    while remove.iter().any(|&p| p > 0) && result.width() >= 2 {
+        let seam = find_vertical_seam_masked(&result, protect.as_ref(), Some(&remove));
+        result = remove_vertical_seam(&result, &seam);
+        remove = remove_vertical_seam(&remove, &seam);
+        protect = protect.map(|m| remove_vertical_seam(&m, &seam));
     }
 
     result
 }
 
+/// Reduces the height of an image using seam carving.
+pub fn shrink_height<P>(image: &Image<P>, target_height: u32) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    assert!(
+        target_height <= image.height(),
+        "target_height must be <= input image height"
+    );
+    transpose(&shrink_width(&transpose(image), target_height))
+}
+
+/// Increases the width of an image using seam carving.
+///
+/// The `target_width - image.width()` lowest energy seams are found by carving a copy of
+/// the image, and each is then duplicated in the original image, with inserted pixels set
+/// to the average of their left and right neighbours. This allows the width of an image
+/// to be increased by at most `image.width() - 1` in a single call.
+pub fn enlarge_width<P>(image: &Image<P>, target_width: u32) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    let (width, height) = image.dimensions();
+    assert!(
+        target_width >= width,
+        "target_width must be >= input image width"
+    );
+    assert!(
+        target_width - width < width,
+        "cannot enlarge width by more than image width - 1 in a single call"
+    );
+
+    let (_, seams) = shrink_width_with_seams(image, width - (target_width - width));
+    let duplicated = seam_positions_in_original(height, &seams);
+
+    let mut out = Image::new(target_width, height);
+    for y in 0..height {
+        let row = &duplicated[y as usize];
+        let mut out_x = 0;
+        for x in 0..width {
+            let p = *image.get_pixel(x, y);
+            out.put_pixel(out_x, y, p);
+            out_x += 1;
+            if row.contains(&x) {
+                let right = *image.get_pixel((x + 1).min(width - 1), y);
+                let average = p.map2(&right, |a, b| ((a as u16 + b as u16) / 2) as u8);
+                out.put_pixel(out_x, y, average);
+                out_x += 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Increases the height of an image using seam carving.
+///
+/// See [`enlarge_width`](fn.enlarge_width.html) for details.
+pub fn enlarge_height<P>(image: &Image<P>, target_height: u32) -> Image<P>
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    transpose(&enlarge_width(&transpose(image), target_height))
+}
+
+// Maps seams, each found in an image with the previous seams removed, to the x
+// coordinates of their pixels in the original image, for each row.
+fn seam_positions_in_original(height: u32, seams: &[VerticalSeam]) -> Vec<Vec<u32>> {
+    let mut offsets = vec![vec![]; height as usize];
+    for seam in seams {
+        for (y, x) in (0..height).rev().zip(&seam.0) {
+            let row = &mut offsets[y as usize];
+            // Removed pixels are visited in increasing order of original position,
+            // so each one at or before the current position shifts it right by one.
+            let mut sorted = row.clone();
+            sorted.sort_unstable();
+            let mut x_original = *x;
+            for o in sorted {
+                if o <= x_original {
+                    x_original += 1;
+                }
+            }
+            row.push(x_original);
+        }
+    }
+    offsets
+}
+
 /// Computes an 8-connected path from the bottom of the image to the top whose sum of
 /// gradient magnitudes is minimal.
 pub fn find_vertical_seam<P>(image: &Image<P>) -> VerticalSeam
 where
-    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + 'static,
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    find_vertical_seam_masked(image, None, None)
+}
+
+/// Computes an 8-connected path from the bottom of the image to the top whose sum of
+/// gradient magnitudes is minimal, avoiding pixels in `protect` and preferring pixels
+/// in `remove`.
+///
+/// Each mask must have the same dimensions as `image`, and a pixel is in a mask if its
+/// value in that mask is non-zero.
+pub fn find_vertical_seam_masked<P>(
+    image: &Image<P>,
+    protect: Option<&GrayImage>,
+    remove: Option<&GrayImage>,
+) -> VerticalSeam
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
     <P as WithChannel<u16>>::Pixel: HasBlack,
 {
     let (width, height) = image.dimensions();
@@ -54,77 +292,92 @@ where
         "Cannot find seams if image width is < 2"
     );
 
-    let mut gradients = sobel_gradient_map(&image, |p| {
+    let gradients = sobel_gradient_map(&image, |p| {
         let gradient_sum: u16 = p.channels().iter().sum();
         let gradient_mean: u16 = gradient_sum / P::CHANNEL_COUNT as u16;
         Luma([gradient_mean as u32])
     });
+    let mut energies: Vec<i64> = gradients.iter().map(|&g| g as i64).collect();
 
-    // Find the least energy path through the gradient image.
-    for y in 1..height {
-        for x in 0..width {
-            set_path_energy(&mut gradients, x, y);
+    for (mask, sign) in [(protect, 1), (remove, -1)].iter() {
+        if let Some(mask) = mask {
+            assert_eq!(
+                mask.dimensions(),
+                image.dimensions(),
+                "mask dimensions must match image dimensions"
+            );
+            for (e, &m) in energies.iter_mut().zip(mask.iter()) {
+                if m > 0 {
+                    *e += sign * MASK_ENERGY;
+                }
+            }
+        }
+    }
+
+    // Find the least energy path through the energy image.
+    let w = width as usize;
+    for y in 1..height as usize {
+        for x in 0..w {
+            set_path_energy(&mut energies, w, x, y);
         }
     }
 
     // Retrace our steps to find the vertical seam.
+    let last_row = &energies[(height as usize - 1) * w..];
     let mut min_x = 0;
-    let mut min_energy = gradients.get_pixel(0, height - 1)[0];
-
-    for x in 1..width {
-        let c = gradients.get_pixel(x, height - 1)[0];
-        if c < min_energy {
+    for x in 1..w {
+        if last_row[x] < last_row[min_x] {
             min_x = x;
-            min_energy = c;
         }
     }
 
     let mut seam = Vec::with_capacity(height as usize);
-
-    seam.push(min_x);
+    seam.push(min_x as u32);
 
     let mut last_x = min_x;
 
-    for y in (1..height).rev() {
-        let above = gradients.get_pixel(last_x, y - 1)[0];
-        if last_x > 0 {
-            let left = gradients.get_pixel(last_x - 1, y - 1)[0];
-            if left < above {
-                min_x = last_x - 1;
-                min_energy = left;
-            }
+    for y in (1..height as usize).rev() {
+        let row = &energies[(y - 1) * w..y * w];
+        let mut min_energy = row[last_x];
+        min_x = last_x;
+        if last_x > 0 && row[last_x - 1] < min_energy {
+            min_x = last_x - 1;
+            min_energy = row[last_x - 1];
         }
-        if last_x < width - 1 {
-            let right = gradients.get_pixel(last_x + 1, y - 1)[0];
-            if right < min_energy {
-                min_x = last_x + 1;
-                min_energy = right;
-            }
+        if last_x < w - 1 && row[last_x + 1] < min_energy {
+            min_x = last_x + 1;
         }
 
         last_x = min_x;
-        seam.push(min_x);
+        seam.push(min_x as u32);
     }
 
     VerticalSeam(seam)
 }
 
+/// Computes an 8-connected path from the right of the image to the left whose sum of
+/// gradient magnitudes is minimal.
+pub fn find_horizontal_seam<P>(image: &Image<P>) -> HorizontalSeam
+where
+    P: Pixel<Subpixel = u8> + WithChannel<u16> + WithChannel<i16> + MaybeSync + 'static,
+    <P as WithChannel<u16>>::Pixel: HasBlack,
+{
+    HorizontalSeam(find_vertical_seam(&transpose(image)).0)
+}
+
 /// Assumes that the previous rows have all been processed.
-fn set_path_energy(path_energies: &mut Image<Luma<u32>>, x: u32, y: u32) {
-    let above = path_energies.get_pixel(x, y - 1)[0];
-    let mut min_energy = above;
+fn set_path_energy(path_energies: &mut [i64], width: usize, x: usize, y: usize) {
+    let above = &path_energies[(y - 1) * width..y * width];
+    let mut min_energy = above[x];
 
     if x > 0 {
-        let above_left = path_energies.get_pixel(x - 1, y - 1)[0];
-        min_energy = min(above, above_left);
+        min_energy = min_energy.min(above[x - 1]);
     }
-    if x < path_energies.width() - 1 {
-        let above_right = path_energies.get_pixel(x + 1, y - 1)[0];
-        min_energy = min(min_energy, above_right);
+    if x < width - 1 {
+        min_energy = min_energy.min(above[x + 1]);
     }
 
-    let current = path_energies.get_pixel(x, y)[0];
-    path_energies.put_pixel(x, y, Luma([min_energy + current]));
+    path_energies[y * width + x] += min_energy;
 }
 
 /// Returns the result of removing `seam` from `image`.
@@ -157,36 +410,149 @@ where
     out
 }
 
+/// Returns the result of removing `seam` from `image`.
+pub fn remove_horizontal_seam<P>(image: &Image<P>, seam: &HorizontalSeam) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    assert!(
+        seam.0.len() as u32 == image.width(),
+        "seam length does not match image width"
+    );
+    transpose(&remove_vertical_seam(&transpose(image), &seam.transposed()))
+}
+
 /// Draws a series of `seams` on `image` in red. Assumes that the provided seams were
 /// removed in the given order from the input image.
 pub fn draw_vertical_seams(image: &GrayImage, seams: &[VerticalSeam]) -> Image<Rgb<u8>> {
-    let height = image.height();
-
-    let mut offsets = vec![vec![]; height as usize];
     let mut out = map_colors(image, |p| p.to_rgb());
+    let positions = seam_positions_in_original(image.height(), seams);
 
-    for seam in seams {
-        for (y, x) in (0..height).rev().zip(&seam.0) {
-            let mut x_original = *x;
-            for o in &offsets[y as usize] {
-                if *o < *x {
-                    x_original += 1;
-                }
-            }
-            out.put_pixel(x_original, y, Rgb([255, 0, 0]));
-            offsets[y as usize].push(x_original);
+    for (y, row) in positions.iter().enumerate() {
+        for x in row {
+            out.put_pixel(*x, y as u32, Rgb([255, 0, 0]));
         }
     }
 
     out
 }
 
+/// Draws a series of `seams` on `image` in red. Assumes that the provided seams were
+/// removed in the given order from the input image.
+pub fn draw_horizontal_seams(image: &GrayImage, seams: &[HorizontalSeam]) -> Image<Rgb<u8>> {
+    let seams: Vec<VerticalSeam> = seams.iter().map(|s| s.transposed()).collect();
+    transpose(&draw_vertical_seams(&transpose(image), &seams))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::utils::gray_bench_image;
     use test::{black_box, Bencher};
 
+    // An image with a single vertical edge, between columns 2 and 3.
+    fn step() -> GrayImage {
+        GrayImage::from_fn(7, 5, |x, _| Luma([if x < 3 { 10 } else { 200 }]))
+    }
+
+    fn has_edge(row: &[u8]) -> bool {
+        row.contains(&10) && row.contains(&200)
+    }
+
+    #[test]
+    fn test_find_vertical_seam_avoids_edges() {
+        let seam = find_vertical_seam(&step());
+        assert_eq!(seam.xs().len(), 5);
+        assert!(seam.xs().iter().all(|&x| x != 2 && x != 3));
+    }
+
+    #[test]
+    fn test_find_vertical_seam_masked() {
+        let image = GrayImage::from_pixel(5, 4, Luma([10]));
+
+        let protect = GrayImage::from_fn(5, 4, |x, _| Luma([if x < 4 { 1 } else { 0 }]));
+        let seam = find_vertical_seam_masked(&image, Some(&protect), None);
+        assert_eq!(seam.xs(), &[4, 4, 4, 4]);
+
+        let remove = GrayImage::from_fn(5, 4, |x, _| Luma([if x == 2 { 1 } else { 0 }]));
+        let seam = find_vertical_seam_masked(&image, None, Some(&remove));
+        assert_eq!(seam.xs(), &[2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_shrink_width_with_seams() {
+        let image = step();
+        let (shrunk, seams) = shrink_width_with_seams(&image, 3);
+        assert_eq!(shrunk.dimensions(), (3, 5));
+        assert_eq!(seams.len(), 4);
+        // The edge is preserved
+        for y in 0..5 {
+            let row: Vec<u8> = (0..3).map(|x| shrunk.get_pixel(x, y)[0]).collect();
+            assert!(has_edge(&row));
+        }
+    }
+
+    #[test]
+    fn test_shrink_height() {
+        let image = transpose(&step());
+        let shrunk = shrink_height(&image, 3);
+        assert_eq!(shrunk.dimensions(), (5, 3));
+        for x in 0..5 {
+            let col: Vec<u8> = (0..3).map(|y| shrunk.get_pixel(x, y)[0]).collect();
+            assert!(has_edge(&col));
+        }
+    }
+
+    #[test]
+    fn test_find_and_remove_horizontal_seam() {
+        let image = gray_image!(
+            1, 2, 1;
+            1, 2, 1;
+            9, 9, 9;
+            9, 9, 9);
+        let seam = find_horizontal_seam(&image);
+        assert_eq!(seam.ys(), &[3, 3, 3]);
+
+        let expected = gray_image!(
+            1, 2, 1;
+            1, 2, 1;
+            9, 9, 9);
+        assert_pixels_eq!(remove_horizontal_seam(&image, &seam), expected);
+    }
+
+    #[test]
+    fn test_enlarge_width() {
+        let image = step();
+        let enlarged = enlarge_width(&image, 10);
+        assert_eq!(enlarged.dimensions(), (10, 5));
+        // Low energy seams are duplicated, so the edge remains sharp
+        for y in 0..5 {
+            let row: Vec<u8> = (0..10).map(|x| enlarged.get_pixel(x, y)[0]).collect();
+            assert!(row.iter().all(|&p| p == 10 || p == 200));
+            assert!(has_edge(&row));
+        }
+        assert_eq!(enlarge_height(&transpose(&image), 10).dimensions(), (5, 10));
+    }
+
+    #[test]
+    fn test_remove_object() {
+        let image = GrayImage::from_fn(6, 4, |x, y| Luma([(10 * x + y) as u8]));
+        let remove = GrayImage::from_fn(6, 4, |x, _| Luma([if x == 2 || x == 3 { 1 } else { 0 }]));
+
+        let result = remove_object(&image, &remove, None);
+        assert_eq!(result.dimensions(), (4, 4));
+        assert!(result.iter().all(|&p| p / 10 != 2 && p / 10 != 3));
+    }
+
+    #[test]
+    fn test_seam_positions_in_original() {
+        let seams = vec![VerticalSeam::new(vec![1, 1]), VerticalSeam::new(vec![1, 0])];
+        assert_eq!(
+            seam_positions_in_original(2, &seams),
+            vec![vec![1, 0], vec![1, 2]]
+        );
+    }
+
     macro_rules! bench_shrink_width {
         ($name:ident, side: $s:expr, shrink_by: $m:expr) => {
             #[bench]
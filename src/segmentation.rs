@@ -0,0 +1,2522 @@
+//! Functions for partitioning an image into regions.
+
+use crate::definitions::Image;
+use crate::distance_transform::Norm;
+use crate::gradients::sobel_gradients;
+use crate::morphology::{close_mut, open_mut};
+use crate::pixelops::abs_diff;
+use crate::point::Point;
+use crate::rect::{Rect, Region};
+use crate::region_labelling::{connected_components, Connectivity};
+use crate::union_find::DisjointSetForest;
+use conv::ValueInto;
+use image::{GrayImage, Luma, Pixel, Primitive, Rgb, RgbImage};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Marker-based watershed segmentation.
+///
+/// Treats `image` as a topographic surface, and floods it starting from the labelled
+/// regions in `markers`. Pixels with non-zero values in `markers` are seeds, labelled with
+/// their marker value. Unlabelled pixels are visited in order of increasing intensity,
+/// each taking the label of its already labelled neighbours. Pixels which are reached from
+/// two or more different labels form the watershed boundaries between regions, and are
+/// labelled 0 in the output. Flooding does not continue past boundary pixels, so boundaries
+/// are a single pixel thick. Any pixels not reachable from a marker are also labelled 0.
+///
+/// `image` is typically a gradient magnitude image, so that boundaries follow edges, or
+/// the negated distance transform of a binary image, so that touching objects are split
+/// along their narrowest points.
+///
+/// # Panics
+///
+/// If `image` and `markers` have different dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::region_labelling::Connectivity;
+/// use imageproc::segmentation::watershed;
+///
+/// // Two basins separated by a ridge
+/// let image = gray_image!(
+///     0, 1, 9, 1, 0;
+///     0, 1, 9, 1, 0;
+///     0, 1, 9, 1, 0);
+///
+/// let markers = gray_image!(type: u32,
+///     1, 0, 0, 0, 2;
+///     0, 0, 0, 0, 0;
+///     0, 0, 0, 0, 0);
+///
+/// let expected = gray_image!(type: u32,
+///     1, 1, 0, 2, 2;
+///     1, 1, 0, 2, 2;
+///     1, 1, 0, 2, 2);
+///
+/// assert_pixels_eq!(watershed(&image, &markers, Connectivity::Four), expected);
+/// # }
+/// ```
+pub fn watershed<T>(
+    image: &Image<Luma<T>>,
+    markers: &Image<Luma<u32>>,
+    connectivity: Connectivity,
+) -> Image<Luma<u32>>
+where
+    T: Primitive + Ord + 'static,
+{
+    assert_eq!(
+        image.dimensions(),
+        markers.dimensions(),
+        "image and markers must have the same dimensions"
+    );
+
+    let (width, height) = image.dimensions();
+    let mut labels = markers.clone();
+    let mut queued = vec![false; (width * height) as usize];
+
+    // Entries are ordered by intensity and then by insertion order, so that pixels of
+    // equal intensity are flooded breadth-first.
+    let mut queue = BinaryHeap::new();
+    let mut count = 0u64;
+
+    for (x, y, label) in markers.enumerate_pixels() {
+        if label[0] == 0 {
+            continue;
+        }
+        queued[(y * width + x) as usize] = true;
+        for (nx, ny) in neighbors(x, y, width, height, connectivity) {
+            let index = (ny * width + nx) as usize;
+            if markers.get_pixel(nx, ny)[0] == 0 && !queued[index] {
+                queued[index] = true;
+                queue.push(Reverse((image.get_pixel(nx, ny)[0], count, nx, ny)));
+                count += 1;
+            }
+        }
+    }
+
+    while let Some(Reverse((_, _, x, y))) = queue.pop() {
+        let mut label = 0;
+        let mut boundary = false;
+        for (nx, ny) in neighbors(x, y, width, height, connectivity) {
+            let neighbor = labels.get_pixel(nx, ny)[0];
+            if neighbor == 0 {
+                continue;
+            }
+            if label == 0 {
+                label = neighbor;
+            } else if neighbor != label {
+                boundary = true;
+            }
+        }
+
+        if boundary || label == 0 {
+            continue;
+        }
+        labels.put_pixel(x, y, Luma([label]));
+
+        for (nx, ny) in neighbors(x, y, width, height, connectivity) {
+            let index = (ny * width + nx) as usize;
+            if !queued[index] {
+                queued[index] = true;
+                queue.push(Reverse((image.get_pixel(nx, ny)[0], count, nx, ny)));
+                count += 1;
+            }
+        }
+    }
+
+    labels
+}
+
+/// Seeded region growing.
+///
+/// Grows a region from each of the given `seeds`, with the region grown from `seeds[i]`
+/// labelled `i + 1` in the output. Unlabelled pixels adjacent to a region are added to it
+/// if the Euclidean distance between their value and the mean value of the region's pixels
+/// is at most `threshold`. Candidate pixels are added in order of increasing distance,
+/// so pixels which could join more than one region join the region they are most similar
+/// to. Pixels which are not added to any region are labelled 0.
+///
+/// # Panics
+///
+/// If any seed lies outside the image.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::region_labelling::Connectivity;
+/// use imageproc::segmentation::region_growing;
+///
+/// let image = gray_image!(
+///     10, 12, 90, 91;
+///     11, 13, 92, 40;
+///     50, 12, 90, 90);
+///
+/// let expected = gray_image!(type: u32,
+///     1, 1, 2, 2;
+///     1, 1, 2, 0;
+///     0, 1, 2, 2);
+///
+/// let labels = region_growing(&image, &[(0, 0), (3, 0)], 5.0, Connectivity::Four);
+/// assert_pixels_eq!(labels, expected);
+/// # }
+/// ```
+pub fn region_growing<P>(
+    image: &Image<P>,
+    seeds: &[(u32, u32)],
+    threshold: f32,
+    connectivity: Connectivity,
+) -> Image<Luma<u32>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32>,
+{
+    let (width, height) = image.dimensions();
+    let channels = P::CHANNEL_COUNT as usize;
+    let value = |x: u32, y: u32| -> Vec<f32> {
+        image
+            .get_pixel(x, y)
+            .channels()
+            .iter()
+            .map(|c| (*c).value_into().unwrap())
+            .collect()
+    };
+
+    let mut labels: Image<Luma<u32>> = Image::new(width, height);
+    // Sum of pixel values and number of pixels in each region
+    let mut sums = vec![vec![0f32; channels]; seeds.len()];
+    let mut counts = vec![0usize; seeds.len()];
+
+    // Entries are ordered by distance and then by insertion order. Distances are
+    // non-negative, so ordering their bit representations orders the distances.
+    let mut queue = BinaryHeap::new();
+    let mut count = 0u64;
+
+    for (i, &(x, y)) in seeds.iter().enumerate() {
+        assert!(
+            x < width && y < height,
+            "seed ({}, {}) is outside the image",
+            x,
+            y
+        );
+        queue.push(Reverse((0u32, count, x, y, i)));
+        count += 1;
+    }
+
+    while let Some(Reverse((_, _, x, y, region))) = queue.pop() {
+        if labels.get_pixel(x, y)[0] != 0 {
+            continue;
+        }
+        let v = value(x, y);
+        if counts[region] > 0 && distance_to_mean(&v, &sums[region], counts[region]) > threshold {
+            // The region mean has moved away from this pixel since it was queued
+            continue;
+        }
+
+        labels.put_pixel(x, y, Luma([region as u32 + 1]));
+        for (s, c) in sums[region].iter_mut().zip(&v) {
+            *s += c;
+        }
+        counts[region] += 1;
+
+        for (nx, ny) in neighbors(x, y, width, height, connectivity) {
+            if labels.get_pixel(nx, ny)[0] != 0 {
+                continue;
+            }
+            let d = distance_to_mean(&value(nx, ny), &sums[region], counts[region]);
+            if d <= threshold {
+                queue.push(Reverse((d.to_bits(), count, nx, ny, region)));
+                count += 1;
+            }
+        }
+    }
+
+    labels
+}
+
+fn distance_to_mean(value: &[f32], sum: &[f32], count: usize) -> f32 {
+    value
+        .iter()
+        .zip(sum)
+        .map(|(v, s)| (v - s / count as f32).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Graph-based image segmentation, as described in [Efficient Graph-Based Image
+/// Segmentation] by Felzenszwalb and Huttenlocher.
+///
+/// Each pixel is a node in a graph, connected to its 8 neighbours by edges weighted by the
+/// Euclidean distance between their pixel values. Regions are merged greedily in order of
+/// increasing edge weight, with two regions merged only if the weight of the edge between
+/// them is no greater than the internal difference of either region (the largest edge
+/// weight in its minimum spanning tree) plus `k` divided by its size. Larger values of `k`
+/// therefore produce larger regions. After this, any region smaller than `min_size`
+/// pixels is merged into the neighbouring region it has the lowest weight edge to.
+///
+/// Images are typically smoothed before segmenting, for example by applying a Gaussian
+/// blur with standard deviation 0.8.
+///
+/// Returns an image where each pixel is labelled by the region it belongs to. Labels are
+/// consecutive integers starting at 0, in the order that regions are first encountered in
+/// a raster scan.
+///
+/// [Efficient Graph-Based Image Segmentation]: http://people.cs.uchicago.edu/~pff/papers/seg-ijcv.pdf
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::segmentation::felzenszwalb;
+///
+/// let image = gray_image!(
+///     10, 12, 11, 200, 201;
+///     11, 10, 12, 202, 200;
+///     12, 11, 10, 200, 203);
+///
+/// let expected = gray_image!(type: u32,
+///     0, 0, 0, 1, 1;
+///     0, 0, 0, 1, 1;
+///     0, 0, 0, 1, 1);
+///
+/// assert_pixels_eq!(felzenszwalb(&image, 10.0, 1), expected);
+/// # }
+/// ```
+pub fn felzenszwalb<P>(image: &Image<P>, k: f32, min_size: u32) -> Image<Luma<u32>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f32>,
+{
+    let (width, height) = image.dimensions();
+    let channels = P::CHANNEL_COUNT as usize;
+    let values: Vec<f32> = image
+        .as_raw()
+        .iter()
+        .map(|v| (*v).value_into().unwrap())
+        .collect();
+    let index = |x: u32, y: u32| (y * width + x) as usize;
+    let distance = |a: usize, b: usize| {
+        let (a, b) = (
+            &values[a * channels..][..channels],
+            &values[b * channels..][..channels],
+        );
+        a.iter()
+            .zip(b)
+            .map(|(p, q)| (p - q) * (p - q))
+            .sum::<f32>()
+            .sqrt()
+    };
+
+    // Each pixel is connected to its right, lower left, lower and lower right neighbours
+    let mut edges = Vec::with_capacity(4 * (width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let a = index(x, y);
+            if x + 1 < width {
+                edges.push((a, index(x + 1, y)));
+            }
+            if y + 1 < height {
+                if x > 0 {
+                    edges.push((a, index(x - 1, y + 1)));
+                }
+                edges.push((a, index(x, y + 1)));
+                if x + 1 < width {
+                    edges.push((a, index(x + 1, y + 1)));
+                }
+            }
+        }
+    }
+    let mut edges: Vec<(usize, usize, f32)> = edges
+        .into_iter()
+        .map(|(a, b)| (a, b, distance(a, b)))
+        .collect();
+    edges.sort_by(|p, q| p.2.partial_cmp(&q.2).unwrap());
+
+    let count = (width * height) as usize;
+    let mut forest = DisjointSetForest::new(count);
+    // The internal difference of each region plus k / size, indexed by region root
+    let mut thresholds = vec![k; count];
+
+    for &(a, b, w) in &edges {
+        let (ra, rb) = (forest.root(a), forest.root(b));
+        if ra != rb && w <= thresholds[ra] && w <= thresholds[rb] {
+            forest.union(ra, rb);
+            let root = forest.root(ra);
+            thresholds[root] = w + k / forest.tree_size(root) as f32;
+        }
+    }
+
+    for &(a, b, _) in &edges {
+        let (ra, rb) = (forest.root(a), forest.root(b));
+        if ra != rb
+            && (forest.tree_size(ra) < min_size as usize
+                || forest.tree_size(rb) < min_size as usize)
+        {
+            forest.union(ra, rb);
+        }
+    }
+
+    let mut region_labels = vec![u32::MAX; count];
+    let mut next_label = 0;
+    let mut labels = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let root = forest.root(index(x, y));
+            if region_labels[root] == u32::MAX {
+                region_labels[root] = next_label;
+                next_label += 1;
+            }
+            labels.put_pixel(x, y, Luma([region_labels[root]]));
+        }
+    }
+    labels
+}
+
+/// Mean shift filtering in the joint spatial and color domain.
+///
+/// Starting from the position and color of each pixel, repeatedly moves to the mean position
+/// and color of the pixels within `spatial_radius` of the current position (in each
+/// coordinate) whose colors are within Euclidean distance `color_radius` of the current color,
+/// until the shift is negligible or `max_iterations` is reached. Each output pixel takes the
+/// color of the mode its input pixel converges to.
+///
+/// This flattens regions of similar color while preserving edges between dissimilar regions,
+/// producing a cartoon-like result. Larger values of `color_radius` merge more dissimilar
+/// colors, and larger values of `spatial_radius` smooth over larger areas at the cost of
+/// running time, which is proportional to the square of `spatial_radius`.
+pub fn mean_shift_filter(
+    image: &RgbImage,
+    spatial_radius: u32,
+    color_radius: f32,
+    max_iterations: u32,
+) -> RgbImage {
+    let modes = mean_shift_modes(image, spatial_radius, color_radius, max_iterations);
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let mode = modes[(y * image.width() + x) as usize];
+        Rgb([
+            mode[0].round() as u8,
+            mode[1].round() as u8,
+            mode[2].round() as u8,
+        ])
+    })
+}
+
+/// Segments an image by grouping pixels whose [mean shift](fn.mean_shift_filter.html)
+/// modes are similar.
+///
+/// Pixels are first filtered using [`mean_shift_filter`](fn.mean_shift_filter.html), and then
+/// 4-connected neighbouring pixels whose modes have colors within Euclidean distance
+/// `color_radius / 2` are merged into the same segment. Finally each segment smaller than
+/// `min_size` pixels is merged into the adjacent segment with the most similar mean color.
+///
+/// Returns an image where each pixel is labelled by the segment it belongs to. Labels are
+/// consecutive integers starting at 0, in the order that segments are first encountered in
+/// a raster scan.
+pub fn mean_shift_segmentation(
+    image: &RgbImage,
+    spatial_radius: u32,
+    color_radius: f32,
+    min_size: u32,
+) -> Image<Luma<u32>> {
+    const MAX_ITERATIONS: u32 = 5;
+
+    let (width, height) = image.dimensions();
+    let count = (width * height) as usize;
+    let modes = mean_shift_modes(image, spatial_radius, color_radius, MAX_ITERATIONS);
+
+    let mut forest = DisjointSetForest::new(count);
+    let merge_distance = (color_radius / 2.0).powi(2);
+    for y in 0..height {
+        for x in 0..width {
+            let a = (y * width + x) as usize;
+            if x + 1 < width && squared_distance(&modes[a], &modes[a + 1]) <= merge_distance {
+                forest.union(a, a + 1);
+            }
+            let below = a + width as usize;
+            if y + 1 < height && squared_distance(&modes[a], &modes[below]) <= merge_distance {
+                forest.union(a, below);
+            }
+        }
+    }
+
+    // Merge small segments into their most similar neighbour, smallest first, until
+    // no small segments remain
+    loop {
+        let mut sums = vec![[0f32; 3]; count];
+        for (i, mode) in modes.iter().enumerate() {
+            let root = forest.root(i);
+            for c in 0..3 {
+                sums[root][c] += mode[c];
+            }
+        }
+        let mut best: Vec<Option<(f32, usize)>> = vec![None; count];
+        for y in 0..height {
+            for x in 0..width {
+                let a = (y * width + x) as usize;
+                for (nx, ny) in neighbors(x, y, width, height, Connectivity::Four) {
+                    let (ra, rb) = (forest.root(a), forest.root((ny * width + nx) as usize));
+                    if ra == rb || forest.tree_size(ra) >= min_size as usize {
+                        continue;
+                    }
+                    let mean = |r: usize, size: usize| {
+                        let s = sums[r];
+                        let n = size as f32;
+                        [s[0] / n, s[1] / n, s[2] / n]
+                    };
+                    let d = squared_distance(
+                        &mean(ra, forest.tree_size(ra)),
+                        &mean(rb, forest.tree_size(rb)),
+                    );
+                    let closer = match best[ra] {
+                        Some((best_distance, _)) => d < best_distance,
+                        None => true,
+                    };
+                    if closer {
+                        best[ra] = Some((d, rb));
+                    }
+                }
+            }
+        }
+
+        let mut small: Vec<(usize, usize)> = best
+            .iter()
+            .enumerate()
+            .filter_map(|(r, b)| b.map(|(_, target)| (r, target)))
+            .collect();
+        if small.is_empty() {
+            break;
+        }
+        small.sort_by_key(|&(r, _)| forest.tree_size(r));
+        for (r, target) in small {
+            if forest.tree_size(r) < min_size as usize {
+                forest.union(r, target);
+            }
+        }
+    }
+
+    let mut region_labels = vec![u32::MAX; count];
+    let mut next_label = 0;
+    Image::from_fn(width, height, |x, y| {
+        let root = forest.root((y * width + x) as usize);
+        if region_labels[root] == u32::MAX {
+            region_labels[root] = next_label;
+            next_label += 1;
+        }
+        Luma([region_labels[root]])
+    })
+}
+
+// Returns the color of the mode that each pixel converges to under mean shift.
+fn mean_shift_modes(
+    image: &RgbImage,
+    spatial_radius: u32,
+    color_radius: f32,
+    max_iterations: u32,
+) -> Vec<[f32; 3]> {
+    // Stop once the squared shift in the joint domain is below this value
+    const CONVERGENCE: f32 = 0.01;
+
+    let (width, height) = image.dimensions();
+    let colors: Vec<[f32; 3]> = image
+        .pixels()
+        .map(|p| [p[0] as f32, p[1] as f32, p[2] as f32])
+        .collect();
+    let radius = spatial_radius as i64;
+    let color_radius_squared = color_radius * color_radius;
+
+    let mut modes = Vec::with_capacity(colors.len());
+    for y in 0..height {
+        for x in 0..width {
+            let (mut px, mut py) = (x as f32, y as f32);
+            let mut color = colors[(y * width + x) as usize];
+
+            for _ in 0..max_iterations {
+                let (cx, cy) = (px.round() as i64, py.round() as i64);
+                let x0 = (cx - radius).max(0) as u32;
+                let x1 = (cx + radius).min(width as i64 - 1) as u32;
+                let y0 = (cy - radius).max(0) as u32;
+                let y1 = (cy + radius).min(height as i64 - 1) as u32;
+
+                let mut sum = [0f32; 5];
+                let mut n = 0u32;
+                for wy in y0..=y1 {
+                    for wx in x0..=x1 {
+                        let c = &colors[(wy * width + wx) as usize];
+                        if squared_distance(c, &color) <= color_radius_squared {
+                            sum[0] += wx as f32;
+                            sum[1] += wy as f32;
+                            sum[2] += c[0];
+                            sum[3] += c[1];
+                            sum[4] += c[2];
+                            n += 1;
+                        }
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+
+                let n = n as f32;
+                let (nx, ny) = (sum[0] / n, sum[1] / n);
+                let new_color = [sum[2] / n, sum[3] / n, sum[4] / n];
+                let shift =
+                    (nx - px).powi(2) + (ny - py).powi(2) + squared_distance(&new_color, &color);
+                px = nx;
+                py = ny;
+                color = new_color;
+                if shift < CONVERGENCE {
+                    break;
+                }
+            }
+            modes.push(color);
+        }
+    }
+    modes
+}
+
+/// The centre of a superpixel computed by [`slic`](fn.slic.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SuperpixelCenter {
+    /// Mean x-coordinate of the pixels in the superpixel.
+    pub x: f32,
+    /// Mean y-coordinate of the pixels in the superpixel.
+    pub y: f32,
+    /// Mean color of the pixels in the superpixel, in the CIELAB color space
+    /// as `[L*, a*, b*]`.
+    pub color: [f32; 3],
+}
+
+/// The output of [`slic`](fn.slic.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Superpixels {
+    /// The superpixel each pixel belongs to. Labels are indices into `centers`.
+    pub labels: Image<Luma<u32>>,
+    /// The centre of each superpixel.
+    pub centers: Vec<SuperpixelCenter>,
+}
+
+/// Partitions an image into approximately `k` compact regions of similar color using
+/// [SLIC] (simple linear iterative clustering).
+///
+/// Pixels are clustered by k-means in the five dimensional space of CIELAB color and
+/// position, with the search for the nearest cluster centre to each pixel limited to a
+/// neighbourhood of twice the initial spacing `S` between centres. The distance between a
+/// pixel and a centre is `sqrt(d_lab^2 + (d_xy * compactness / S)^2)`, so larger values of
+/// `compactness` produce more regular superpixels and smaller values produce superpixels which
+/// adhere more closely to image boundaries. Values between 1 and 40 are typical.
+///
+/// After `iterations` rounds of clustering, connectivity is enforced by merging each
+/// superpixel fragment smaller than a quarter of the expected superpixel size into an
+/// adjacent superpixel, and giving every other fragment its own label. Every superpixel in
+/// the output is therefore 4-connected, and the number of superpixels may differ
+/// slightly from `k`.
+///
+/// [SLIC]: https://www.epfl.ch/labs/ivrl/research/slic-superpixels/
+///
+/// # Panics
+///
+/// If `k` is zero.
+pub fn slic(image: &RgbImage, k: u32, compactness: f32, iterations: u32) -> Superpixels {
+    assert!(k > 0, "k must be non-zero");
+
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return Superpixels {
+            labels: Image::new(width, height),
+            centers: vec![],
+        };
+    }
+
+    let lab: Vec<[f32; 3]> = image.pixels().map(|p| rgb_to_lab(p.0)).collect();
+    let index = |x: u32, y: u32| (y * width + x) as usize;
+
+    // Initialize centres on a regular grid, moved to the lowest gradient position in
+    // their 3x3 neighbourhood to avoid placing them on edges.
+    let step = ((width * height) as f32 / k as f32).sqrt();
+    let columns = ((width as f32 / step).round() as u32).max(1);
+    let rows = ((height as f32 / step).round() as u32).max(1);
+    let gradient = |x: u32, y: u32| {
+        if x == 0 || y == 0 || x + 1 >= width || y + 1 >= height {
+            return f32::INFINITY;
+        }
+        squared_distance(&lab[index(x - 1, y)], &lab[index(x + 1, y)])
+            + squared_distance(&lab[index(x, y - 1)], &lab[index(x, y + 1)])
+    };
+
+    let mut centers = Vec::with_capacity((rows * columns) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            let cx = ((column as f32 + 0.5) * width as f32 / columns as f32) as u32;
+            let cy = ((row as f32 + 0.5) * height as f32 / rows as f32) as u32;
+            let mut best = (cx, cy);
+            let mut best_gradient = gradient(cx, cy);
+            for (nx, ny) in neighbors(cx, cy, width, height, Connectivity::Eight) {
+                let g = gradient(nx, ny);
+                if g < best_gradient {
+                    best = (nx, ny);
+                    best_gradient = g;
+                }
+            }
+            centers.push(SuperpixelCenter {
+                x: best.0 as f32,
+                y: best.1 as f32,
+                color: lab[index(best.0, best.1)],
+            });
+        }
+    }
+
+    let mut labels = vec![0u32; (width * height) as usize];
+    let mut distances = vec![f32::INFINITY; (width * height) as usize];
+    let spatial_weight = (compactness / step).powi(2);
+    let radius = step.ceil() as i64;
+
+    for _ in 0..iterations {
+        for d in distances.iter_mut() {
+            *d = f32::INFINITY;
+        }
+
+        for (i, c) in centers.iter().enumerate() {
+            let x0 = (c.x.round() as i64 - radius).max(0) as u32;
+            let x1 = (c.x.round() as i64 + radius).min(width as i64 - 1) as u32;
+            let y0 = (c.y.round() as i64 - radius).max(0) as u32;
+            let y1 = (c.y.round() as i64 + radius).min(height as i64 - 1) as u32;
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let (dx, dy) = (x as f32 - c.x, y as f32 - c.y);
+                    let d = squared_distance(&lab[index(x, y)], &c.color)
+                        + (dx * dx + dy * dy) * spatial_weight;
+                    if d < distances[index(x, y)] {
+                        distances[index(x, y)] = d;
+                        labels[index(x, y)] = i as u32;
+                    }
+                }
+            }
+        }
+
+        let previous = centers.clone();
+        centers = superpixel_centers(&labels, &lab, width, centers.len());
+        // Clusters which lost all of their pixels keep their previous position
+        for (c, p) in centers.iter_mut().zip(&previous) {
+            if c.x.is_nan() {
+                *c = *p;
+            }
+        }
+    }
+
+    // Pixels outside every search window are only possible with no iterations
+    if iterations == 0 {
+        for y in 0..height {
+            for x in 0..width {
+                let nearest = centers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| (i, (x as f32 - c.x).powi(2) + (y as f32 - c.y).powi(2)))
+                    .fold((0, f32::INFINITY), |a, b| if b.1 < a.1 { b } else { a });
+                labels[index(x, y)] = nearest.0 as u32;
+            }
+        }
+    }
+
+    let min_size = ((width * height) as f32 / (4.0 * centers.len() as f32)) as usize;
+    let (labels, count) = enforce_connectivity(&labels, width, height, min_size);
+    let centers = superpixel_centers(&labels, &lab, width, count);
+
+    Superpixels {
+        labels: Image::from_raw(width, height, labels).unwrap(),
+        centers,
+    }
+}
+
+// Relabels the 4-connected components of labels so that each has a distinct label,
+// except for components smaller than min_size, which are merged into an adjacent
+// component. Returns the new labels and the number of distinct labels.
+fn enforce_connectivity(
+    labels: &[u32],
+    width: u32,
+    height: u32,
+    min_size: usize,
+) -> (Vec<u32>, usize) {
+    const UNSET: u32 = u32::MAX;
+    let mut output = vec![UNSET; labels.len()];
+    let mut count = 0u32;
+    let mut component = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for start in 0..labels.len() {
+        if output[start] != UNSET {
+            continue;
+        }
+        let (sx, sy) = (start as u32 % width, start as u32 / width);
+
+        // A previously labelled neighbour, to merge into if this component is too small
+        let adjacent = neighbors(sx, sy, width, height, Connectivity::Four)
+            .map(|(x, y)| output[(y * width + x) as usize])
+            .find(|&l| l != UNSET);
+
+        component.clear();
+        output[start] = count;
+        queue.push_back(start);
+        while let Some(i) = queue.pop_front() {
+            component.push(i);
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            for (nx, ny) in neighbors(x, y, width, height, Connectivity::Four) {
+                let n = (ny * width + nx) as usize;
+                if output[n] == UNSET && labels[n] == labels[start] {
+                    output[n] = count;
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        match adjacent {
+            Some(label) if component.len() < min_size => {
+                for &i in &component {
+                    output[i] = label;
+                }
+            }
+            _ => count += 1,
+        }
+    }
+
+    (output, count as usize)
+}
+
+// Computes the mean position and color of each label. Centres of empty labels are NaN.
+fn superpixel_centers(
+    labels: &[u32],
+    lab: &[[f32; 3]],
+    width: u32,
+    count: usize,
+) -> Vec<SuperpixelCenter> {
+    let mut sums = vec![[0f64; 6]; count];
+    for (i, (&label, color)) in labels.iter().zip(lab).enumerate() {
+        let sum = &mut sums[label as usize];
+        sum[0] += (i as u32 % width) as f64;
+        sum[1] += (i as u32 / width) as f64;
+        for c in 0..3 {
+            sum[2 + c] += color[c] as f64;
+        }
+        sum[5] += 1.0;
+    }
+    sums.iter()
+        .map(|s| {
+            let mean = |v: f64| (v / s[5]) as f32;
+            SuperpixelCenter {
+                x: mean(s[0]),
+                y: mean(s[1]),
+                color: [mean(s[2]), mean(s[3]), mean(s[4])],
+            }
+        })
+        .collect()
+}
+
+/// Draws the boundaries between the regions of a label image, such as the output of
+/// [`slic`](fn.slic.html), on a new copy of an image.
+///
+/// A pixel is on a boundary if its label differs from the label of the pixel to its
+/// right or the pixel below it.
+///
+/// # Panics
+///
+/// If `image` and `labels` have different dimensions.
+pub fn draw_label_boundaries<P>(image: &Image<P>, labels: &Image<Luma<u32>>, color: P) -> Image<P>
+where
+    P: Pixel + 'static,
+{
+    let mut out = image.clone();
+    draw_label_boundaries_mut(&mut out, labels, color);
+    out
+}
+
+/// Draws the boundaries between the regions of a label image, such as the output of
+/// [`slic`](fn.slic.html), on an image in place.
+///
+/// See [`draw_label_boundaries`](fn.draw_label_boundaries.html) for details.
+pub fn draw_label_boundaries_mut<P>(image: &mut Image<P>, labels: &Image<Luma<u32>>, color: P)
+where
+    P: Pixel + 'static,
+{
+    assert_eq!(
+        image.dimensions(),
+        labels.dimensions(),
+        "image and labels must have the same dimensions"
+    );
+    let (width, height) = labels.dimensions();
+    for y in 0..height {
+        for x in 0..width {
+            let label = labels.get_pixel(x, y)[0];
+            let right = x + 1 < width && labels.get_pixel(x + 1, y)[0] != label;
+            let below = y + 1 < height && labels.get_pixel(x, y + 1)[0] != label;
+            if right || below {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// The output of [`kmeans_colors`](fn.kmeans_colors.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorClusters<P: Pixel> {
+    /// The centre of each cluster, rounded to the nearest representable value.
+    pub palette: Vec<P>,
+    /// The cluster each pixel belongs to. Labels are indices into `palette`.
+    pub labels: Image<Luma<u32>>,
+}
+
+impl<P: Pixel + 'static> ColorClusters<P> {
+    /// Returns an image where each pixel is replaced by the color of its cluster.
+    pub fn quantized(&self) -> Image<P> {
+        Image::from_fn(self.labels.width(), self.labels.height(), |x, y| {
+            self.palette[self.labels.get_pixel(x, y)[0] as usize]
+        })
+    }
+}
+
+/// Clusters the colors of an image into at most `k` clusters using k-means.
+///
+/// Initial cluster centres are chosen using k-means++ seeding with a random number generator
+/// seeded with `seed`, so the output is deterministic for a given seed. Clustering stops when
+/// no pixel changes cluster, or after `max_iterations` iterations. In either case each pixel
+/// is labelled with the cluster whose centre is nearest to it.
+///
+/// Returns the palette of cluster colors and the cluster of each pixel. Fewer than `k`
+/// clusters are returned if the image contains fewer than `k` distinct colors.
+/// [`ColorClusters::quantized`](struct.ColorClusters.html#method.quantized) can be used to
+/// produce a color-quantized image.
+///
+/// # Panics
+///
+/// If `k` is zero.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::{Rgb, RgbImage};
+/// use imageproc::segmentation::kmeans_colors;
+///
+/// let image = RgbImage::from_fn(4, 2, |x, y| {
+///     if x < 2 { Rgb([250, 10 + y as u8, 10]) } else { Rgb([10, 10, 240 + x as u8]) }
+/// });
+///
+/// let clusters = kmeans_colors(&image, 2, 10, 1);
+/// let mut palette = clusters.palette.clone();
+/// palette.sort_by_key(|p| p[2]);
+/// assert_eq!(palette, vec![Rgb([250, 11, 10]), Rgb([10, 10, 243])]);
+/// # }
+/// ```
+pub fn kmeans_colors<P>(
+    image: &Image<P>,
+    k: u32,
+    max_iterations: u32,
+    seed: u64,
+) -> ColorClusters<P>
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    assert!(k > 0, "k must be non-zero");
+
+    let (centers, assignments) = kmeans(
+        image.as_raw(),
+        P::CHANNEL_COUNT as usize,
+        k as usize,
+        max_iterations,
+        seed,
+    );
+
+    let palette = centers
+        .iter()
+        .map(|c| {
+            let values: Vec<u8> = c.iter().map(|v| v.round() as u8).collect();
+            *P::from_slice(&values)
+        })
+        .collect();
+
+    ColorClusters {
+        palette,
+        labels: Image::from_raw(image.width(), image.height(), assignments).unwrap(),
+    }
+}
+
+// Clusters samples, given as consecutive groups of dims values, into at most k clusters
+// using k-means with k-means++ seeding. Returns the cluster centres and the index of the
+// centre nearest to each sample.
+//
+// Each iteration moves every centre to the mean of its cluster and then reassigns samples,
+// so the returned assignments are always consistent with the returned centres, even if
+// clustering stops after max_iterations without converging.
+fn kmeans<T>(
+    samples: &[T],
+    dims: usize,
+    k: usize,
+    max_iterations: u32,
+    seed: u64,
+) -> (Vec<Vec<f64>>, Vec<u32>)
+where
+    T: Copy + Into<f64>,
+{
+    let samples: Vec<&[T]> = samples.chunks(dims).collect();
+    let distance = |a: &[T], b: &[f64]| -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(p, q)| ((*p).into() - q).powi(2))
+            .sum()
+    };
+    let to_center = |s: &[T]| -> Vec<f64> { s.iter().map(|c| (*c).into()).collect() };
+
+    // k-means++ seeding: each new centre is a sample chosen with probability
+    // proportional to its squared distance from the nearest existing centre
+    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    let mut centers: Vec<Vec<f64>> = Vec::with_capacity(k);
+    if !samples.is_empty() {
+        let first = rng.gen_range(0, samples.len());
+        centers.push(to_center(samples[first]));
+    }
+    let mut nearest: Vec<f64> = samples
+        .iter()
+        .map(|s| centers.first().map_or(0.0, |c| distance(s, c)))
+        .collect();
+    while centers.len() < k {
+        let total: f64 = nearest.iter().sum();
+        if total <= 0.0 {
+            break;
+        }
+        let mut target = rng.gen::<f64>() * total;
+        let mut chosen = samples.len() - 1;
+        for (i, d) in nearest.iter().enumerate() {
+            target -= *d;
+            if target < 0.0 && *d > 0.0 {
+                chosen = i;
+                break;
+            }
+        }
+        let center = to_center(samples[chosen]);
+        for (n, s) in nearest.iter_mut().zip(&samples) {
+            *n = n.min(distance(s, &center));
+        }
+        centers.push(center);
+    }
+
+    let nearest_center = |s: &[T], centers: &[Vec<f64>]| -> u32 {
+        let mut best = (0, f64::INFINITY);
+        for (i, c) in centers.iter().enumerate() {
+            let d = distance(s, c);
+            if d < best.1 {
+                best = (i as u32, d);
+            }
+        }
+        best.0
+    };
+
+    let mut assignments: Vec<u32> = samples
+        .iter()
+        .map(|s| nearest_center(s, &centers))
+        .collect();
+    for _ in 0..max_iterations {
+        let mut sums = vec![vec![0f64; dims + 1]; centers.len()];
+        for (s, &a) in samples.iter().zip(&assignments) {
+            let sum = &mut sums[a as usize];
+            for (t, v) in sum.iter_mut().zip(s.iter()) {
+                *t += (*v).into();
+            }
+            sum[dims] += 1.0;
+        }
+        for (c, sum) in centers.iter_mut().zip(&sums) {
+            if sum[dims] > 0.0 {
+                for (v, t) in c.iter_mut().zip(sum) {
+                    *v = t / sum[dims];
+                }
+            }
+        }
+
+        let mut changed = false;
+        for (a, s) in assignments.iter_mut().zip(&samples) {
+            let nearest = nearest_center(s, &centers);
+            if *a != nearest {
+                *a = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    (centers, assignments)
+}
+
+/// Segments the foreground of an image from a rectangle containing it using
+/// [`grab_cut`](fn.grab_cut.html).
+///
+/// All pixels outside `rect` are treated as definite background, and all pixels inside it
+/// are unknown.
+///
+/// # Panics
+///
+/// If `rect` does not lie within the image, or covers the whole image.
+pub fn grab_cut_rect(image: &RgbImage, rect: Rect, iterations: u32) -> GrayImage {
+    let (width, height) = image.dimensions();
+    assert!(
+        rect.left() >= 0
+            && rect.top() >= 0
+            && rect.right() < width as i32
+            && rect.bottom() < height as i32,
+        "rect must lie within the image"
+    );
+    let trimap = GrayImage::from_fn(width, height, |x, y| {
+        if rect.contains(x as i32, y as i32) {
+            Luma([128u8])
+        } else {
+            Luma([TRIMAP_BACKGROUND])
+        }
+    });
+    grab_cut(image, &trimap, iterations)
+}
+
+/// The value of pixels in a trimap which are known to belong to the background.
+/// See [`grab_cut`](fn.grab_cut.html).
+pub const TRIMAP_BACKGROUND: u8 = 0;
+
+/// The value of pixels in a trimap which are known to belong to the foreground.
+/// See [`grab_cut`](fn.grab_cut.html).
+pub const TRIMAP_FOREGROUND: u8 = 255;
+
+/// Binary foreground segmentation using [GrabCut].
+///
+/// Pixels with value [`TRIMAP_BACKGROUND`](constant.TRIMAP_BACKGROUND.html) in `trimap` are
+/// known to belong to the background, pixels with value
+/// [`TRIMAP_FOREGROUND`](constant.TRIMAP_FOREGROUND.html) are known to belong to the
+/// foreground, and all other pixels are unknown. Unknown pixels are initially treated as
+/// foreground.
+///
+/// Each iteration fits Gaussian mixture models with five components to the colors of
+/// the current foreground and background pixels, and then relabels the unknown pixels by
+/// finding a minimum cut of a graph combining the likelihood of each pixel's color under
+/// each model with a smoothness term penalising differently labelled neighbours of similar
+/// color. The returned image is [`TRIMAP_FOREGROUND`](constant.TRIMAP_FOREGROUND.html) for
+/// foreground pixels and [`TRIMAP_BACKGROUND`](constant.TRIMAP_BACKGROUND.html) for
+/// background pixels. A few iterations are usually sufficient.
+///
+/// [GrabCut]: https://en.wikipedia.org/wiki/GrabCut
+///
+/// # Panics
+///
+/// If `image` and `trimap` have different dimensions, or if `trimap` contains no background
+/// pixels or no non-background pixels.
+pub fn grab_cut(image: &RgbImage, trimap: &GrayImage, iterations: u32) -> GrayImage {
+    assert_eq!(
+        image.dimensions(),
+        trimap.dimensions(),
+        "image and trimap must have the same dimensions"
+    );
+
+    // Weight of the smoothness term, as in the GrabCut paper
+    const GAMMA: f64 = 50.0;
+    const COMPONENTS: usize = 5;
+
+    let (width, height) = image.dimensions();
+    let count = (width * height) as usize;
+    let colors: Vec<[f64; 3]> = image
+        .pixels()
+        .map(|p| [p[0] as f64, p[1] as f64, p[2] as f64])
+        .collect();
+    let hard: Vec<u8> = trimap.iter().cloned().collect();
+    let mut foreground: Vec<bool> = hard.iter().map(|&t| t != TRIMAP_BACKGROUND).collect();
+    assert!(
+        foreground.iter().any(|&f| f),
+        "trimap must contain non-background pixels"
+    );
+    assert!(
+        foreground.iter().any(|&f| !f),
+        "trimap must contain background pixels"
+    );
+
+    // Edges to the right, lower left, lower and lower right neighbours of each pixel,
+    // weighted by the contrast between their colors
+    let mut links = Vec::with_capacity(4 * count);
+    for y in 0..height {
+        for x in 0..width {
+            let a = (y * width + x) as usize;
+            let mut link = |nx: u32, ny: u32, distance: f64| {
+                let b = (ny * width + nx) as usize;
+                links.push((a, b, distance, squared_distance_f64(&colors[a], &colors[b])));
+            };
+            if x + 1 < width {
+                link(x + 1, y, 1.0);
+            }
+            if y + 1 < height {
+                if x > 0 {
+                    link(x - 1, y + 1, 2f64.sqrt());
+                }
+                link(x, y + 1, 1.0);
+                if x + 1 < width {
+                    link(x + 1, y + 1, 2f64.sqrt());
+                }
+            }
+        }
+    }
+    let mean_contrast = links.iter().map(|l| l.3).sum::<f64>() / links.len().max(1) as f64;
+    let beta = if mean_contrast > 0.0 {
+        1.0 / (2.0 * mean_contrast)
+    } else {
+        0.0
+    };
+    let smoothness: Vec<(usize, usize, f64)> = links
+        .iter()
+        .map(|&(a, b, distance, contrast)| (a, b, GAMMA / distance * (-beta * contrast).exp()))
+        .collect();
+
+    // Larger than the total cost of any cut through the smoothness edges of a single pixel
+    let infinity = 1.0 + 8.0 * GAMMA;
+
+    for _ in 0..iterations {
+        let samples = |label: bool| -> Vec<[f64; 3]> {
+            colors
+                .iter()
+                .zip(&foreground)
+                .filter(|(_, &f)| f == label)
+                .map(|(c, _)| *c)
+                .collect()
+        };
+        let foreground_samples = samples(true);
+        let background_samples = samples(false);
+        if foreground_samples.is_empty() || background_samples.is_empty() {
+            break;
+        }
+        let foreground_model = GaussianMixture::fit(&foreground_samples, COMPONENTS);
+        let background_model = GaussianMixture::fit(&background_samples, COMPONENTS);
+
+        // The source side of the cut is the foreground
+        let (source, sink) = (count, count + 1);
+        let mut network = FlowNetwork::new(count + 2);
+        for (i, color) in colors.iter().enumerate() {
+            let (to_source, to_sink) = match hard[i] {
+                TRIMAP_FOREGROUND => (infinity, 0.0),
+                TRIMAP_BACKGROUND => (0.0, infinity),
+                _ => (
+                    background_model.negative_log_likelihood(color),
+                    foreground_model.negative_log_likelihood(color),
+                ),
+            };
+            network.add_edge(source, i, to_source, 0.0);
+            network.add_edge(i, sink, to_sink, 0.0);
+        }
+        for &(a, b, weight) in &smoothness {
+            network.add_edge(a, b, weight, weight);
+        }
+
+        network.max_flow(source, sink);
+        let source_side = network.source_side(source);
+
+        let mut changed = false;
+        for i in 0..count {
+            if foreground[i] != source_side[i] {
+                foreground[i] = source_side[i];
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    GrayImage::from_fn(width, height, |x, y| {
+        if foreground[(y * width + x) as usize] {
+            Luma([TRIMAP_FOREGROUND])
+        } else {
+            Luma([TRIMAP_BACKGROUND])
+        }
+    })
+}
+
+// A Gaussian mixture model of RGB colors.
+struct GaussianMixture {
+    components: Vec<Gaussian>,
+}
+
+struct Gaussian {
+    weight: f64,
+    mean: [f64; 3],
+    inverse_covariance: [[f64; 3]; 3],
+    // Logarithm of the normalization constant of the Gaussian density
+    log_normalization: f64,
+}
+
+impl GaussianMixture {
+    // Fits a mixture with at most the given number of components, by clustering the
+    // samples using k-means and fitting a Gaussian to each cluster.
+    fn fit(samples: &[[f64; 3]], components: usize) -> GaussianMixture {
+        let flat: Vec<f64> = samples.iter().flatten().cloned().collect();
+        let (_, assignments) = kmeans(&flat, 3, components, 10, 0);
+        let k = assignments.iter().max().map_or(0, |m| *m as usize + 1);
+
+        let mut clusters = vec![vec![]; k];
+        for (s, &a) in samples.iter().zip(&assignments) {
+            clusters[a as usize].push(*s);
+        }
+
+        let components = clusters
+            .iter()
+            .filter(|c| !c.is_empty())
+            .map(|cluster| {
+                let n = cluster.len() as f64;
+                let mut mean = [0.0; 3];
+                for s in cluster {
+                    for c in 0..3 {
+                        mean[c] += s[c] / n;
+                    }
+                }
+                // Regularize the covariance so that it is invertible
+                let mut covariance = [[0.0; 3]; 3];
+                for s in cluster {
+                    for r in 0..3 {
+                        for c in 0..3 {
+                            covariance[r][c] += (s[r] - mean[r]) * (s[c] - mean[c]) / n;
+                        }
+                    }
+                }
+                for (r, row) in covariance.iter_mut().enumerate() {
+                    row[r] += 0.01;
+                }
+
+                let (inverse_covariance, determinant) = invert_3x3(&covariance);
+                Gaussian {
+                    weight: n / samples.len() as f64,
+                    mean,
+                    inverse_covariance,
+                    log_normalization: -0.5
+                        * (3.0 * (2.0 * std::f64::consts::PI).ln() + determinant.ln()),
+                }
+            })
+            .collect();
+
+        GaussianMixture { components }
+    }
+
+    fn negative_log_likelihood(&self, color: &[f64; 3]) -> f64 {
+        let likelihood: f64 = self
+            .components
+            .iter()
+            .map(|g| {
+                let d = [
+                    color[0] - g.mean[0],
+                    color[1] - g.mean[1],
+                    color[2] - g.mean[2],
+                ];
+                let mut mahalanobis = 0.0;
+                for r in 0..3 {
+                    for c in 0..3 {
+                        mahalanobis += d[r] * g.inverse_covariance[r][c] * d[c];
+                    }
+                }
+                g.weight * (g.log_normalization - 0.5 * mahalanobis).exp()
+            })
+            .sum();
+        -likelihood.max(1e-300).ln()
+    }
+}
+
+// Returns the inverse and determinant of a symmetric positive definite 3x3 matrix.
+fn invert_3x3(m: &[[f64; 3]; 3]) -> ([[f64; 3]; 3], f64) {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let adjugate = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    let determinant =
+        m[0][0] * adjugate[0][0] + m[0][1] * adjugate[1][0] + m[0][2] * adjugate[2][0];
+    let mut inverse = [[0.0; 3]; 3];
+    for r in 0..3 {
+        for c in 0..3 {
+            inverse[r][c] = adjugate[r][c] / determinant;
+        }
+    }
+    (inverse, determinant)
+}
+
+fn squared_distance_f64(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+// A flow network supporting maximum flow computation using Dinic's algorithm.
+struct FlowNetwork {
+    // Index of the first edge leaving each node
+    first: Vec<Option<usize>>,
+    // For each edge, the next edge leaving the same node, its target node and its
+    // residual capacity. Edges are stored in pairs, so that edge i ^ 1 is the reverse of
+    // edge i.
+    next: Vec<Option<usize>>,
+    target: Vec<usize>,
+    capacity: Vec<f64>,
+}
+
+impl FlowNetwork {
+    fn new(nodes: usize) -> FlowNetwork {
+        FlowNetwork {
+            first: vec![None; nodes],
+            next: vec![],
+            target: vec![],
+            capacity: vec![],
+        }
+    }
+
+    // Adds an edge from u to v with the given capacity, and an edge from v to u with
+    // the given reverse capacity.
+    fn add_edge(&mut self, u: usize, v: usize, capacity: f64, reverse_capacity: f64) {
+        for &(from, to, c) in &[(u, v, capacity), (v, u, reverse_capacity)] {
+            self.next.push(self.first[from]);
+            self.first[from] = Some(self.target.len());
+            self.target.push(to);
+            self.capacity.push(c);
+        }
+    }
+
+    fn max_flow(&mut self, source: usize, sink: usize) -> f64 {
+        const EPSILON: f64 = 1e-9;
+        let nodes = self.first.len();
+        let mut total = 0.0;
+        let mut levels = vec![usize::MAX; nodes];
+        let mut queue = VecDeque::new();
+
+        loop {
+            // Compute distances from the source in the residual network
+            for l in levels.iter_mut() {
+                *l = usize::MAX;
+            }
+            levels[source] = 0;
+            queue.push_back(source);
+            while let Some(u) = queue.pop_front() {
+                let mut edge = self.first[u];
+                while let Some(e) = edge {
+                    let v = self.target[e];
+                    if self.capacity[e] > EPSILON && levels[v] == usize::MAX {
+                        levels[v] = levels[u] + 1;
+                        queue.push_back(v);
+                    }
+                    edge = self.next[e];
+                }
+            }
+            if levels[sink] == usize::MAX {
+                return total;
+            }
+
+            // Find a blocking flow using augmenting paths along which levels increase
+            let mut current = self.first.clone();
+            let mut path: Vec<usize> = vec![];
+            let mut u = source;
+            loop {
+                if u == sink {
+                    let flow = path
+                        .iter()
+                        .map(|&e| self.capacity[e])
+                        .fold(f64::INFINITY, f64::min);
+                    for &e in &path {
+                        self.capacity[e] -= flow;
+                        self.capacity[e ^ 1] += flow;
+                    }
+                    total += flow;
+                    path.clear();
+                    u = source;
+                    continue;
+                }
+
+                while let Some(e) = current[u] {
+                    let v = self.target[e];
+                    if self.capacity[e] > EPSILON && levels[v] == levels[u] + 1 {
+                        break;
+                    }
+                    current[u] = self.next[e];
+                }
+
+                match current[u] {
+                    Some(e) => {
+                        path.push(e);
+                        u = self.target[e];
+                    }
+                    None => {
+                        // Dead end, so retreat along the path
+                        match path.pop() {
+                            Some(e) => {
+                                levels[u] = usize::MAX;
+                                u = self.target[e ^ 1];
+                                current[u] = self.next[e];
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns whether each node is reachable from the source in the residual network.
+    fn source_side(&self, source: usize) -> Vec<bool> {
+        const EPSILON: f64 = 1e-9;
+        let mut reachable = vec![false; self.first.len()];
+        reachable[source] = true;
+        let mut queue = VecDeque::new();
+        queue.push_back(source);
+        while let Some(u) = queue.pop_front() {
+            let mut edge = self.first[u];
+            while let Some(e) = edge {
+                let v = self.target[e];
+                if self.capacity[e] > EPSILON && !reachable[v] {
+                    reachable[v] = true;
+                    queue.push_back(v);
+                }
+                edge = self.next[e];
+            }
+        }
+        reachable
+    }
+}
+
+/// Parameters for [`active_contour`](fn.active_contour.html).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ActiveContourOptions {
+    /// Weight of the continuity energy, which favours evenly spaced points.
+    pub alpha: f32,
+    /// Weight of the curvature energy, which favours smooth contours.
+    pub beta: f32,
+    /// Weight of the image energy, which attracts points towards strong edges.
+    pub gamma: f32,
+    /// Each point moves to the best position within this many pixels of its current
+    /// position in each direction at each iteration.
+    pub search_radius: u32,
+    /// Maximum number of iterations.
+    pub max_iterations: u32,
+}
+
+impl ActiveContourOptions {
+    /// User-provided options.
+    pub fn new(
+        alpha: f32,
+        beta: f32,
+        gamma: f32,
+        search_radius: u32,
+        max_iterations: u32,
+    ) -> ActiveContourOptions {
+        ActiveContourOptions {
+            alpha,
+            beta,
+            gamma,
+            search_radius,
+            max_iterations,
+        }
+    }
+}
+
+/// Evolves a closed contour towards edges in an image, using the greedy active contour
+/// (snake) algorithm of Williams and Shah.
+///
+/// At each iteration every point of the contour moves to the position in its search window
+/// which minimises a weighted sum of three energies, each normalized to `[0, 1]` within the
+/// search window:
+///
+/// * continuity, the difference between the distance to the previous point and the mean
+///   distance between consecutive points, weighted by `alpha`
+/// * curvature, the squared norm of the second difference of the contour at the point,
+///   weighted by `beta`
+/// * image energy, the negated [Sobel gradient](../gradients/fn.sobel_gradients.html)
+///   magnitude, weighted by `gamma`
+///
+/// Iteration stops when no point moves, or after `options.max_iterations` iterations. Points
+/// are kept within the image bounds. Noisy images should be smoothed first so that the
+/// contour is not attracted to spurious edges.
+pub fn active_contour(
+    image: &GrayImage,
+    initial: &[Point<i32>],
+    options: ActiveContourOptions,
+) -> Vec<Point<i32>> {
+    let mut points = initial.to_vec();
+    let n = points.len();
+    if n < 3 || image.width() == 0 || image.height() == 0 {
+        return points;
+    }
+
+    let gradients = sobel_gradients(image);
+    let (width, height) = (image.width() as i32, image.height() as i32);
+    let radius = options.search_radius as i32;
+    let side = (2 * radius + 1) as usize;
+
+    let mut continuity = vec![0f32; side * side];
+    let mut curvature = vec![0f32; side * side];
+    let mut edge = vec![0f32; side * side];
+
+    for _ in 0..options.max_iterations {
+        let mean_spacing = (0..n)
+            .map(|i| distance(points[i], points[(i + 1) % n]))
+            .sum::<f32>()
+            / n as f32;
+
+        let mut moved = 0;
+        for i in 0..n {
+            let previous = points[(i + n - 1) % n];
+            let next = points[(i + 1) % n];
+            let current = points[i];
+
+            let mut candidates = Vec::with_capacity(side * side);
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let p = Point::new(current.x + dx, current.y + dy);
+                    if p.x < 0 || p.y < 0 || p.x >= width || p.y >= height {
+                        continue;
+                    }
+                    let k = candidates.len();
+                    continuity[k] = (mean_spacing - distance(previous, p)).abs();
+                    let (cx, cy) = (previous.x - 2 * p.x + next.x, previous.y - 2 * p.y + next.y);
+                    curvature[k] = (cx * cx + cy * cy) as f32;
+                    edge[k] = gradients.get_pixel(p.x as u32, p.y as u32)[0] as f32;
+                    candidates.push(p);
+                }
+            }
+            let k = candidates.len();
+
+            // Normalize each energy to [0, 1] within the search window
+            let normalize = |values: &mut [f32], invert: bool| {
+                let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+                let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+                let range = max - min;
+                for v in values.iter_mut() {
+                    *v = if range > 0.0 {
+                        if invert {
+                            (max - *v) / range
+                        } else {
+                            (*v - min) / range
+                        }
+                    } else {
+                        0.0
+                    };
+                }
+            };
+            normalize(&mut continuity[..k], false);
+            normalize(&mut curvature[..k], false);
+            normalize(&mut edge[..k], true);
+
+            let mut best = current;
+            let mut best_energy = f32::INFINITY;
+            for (j, p) in candidates.iter().enumerate() {
+                let energy = options.alpha * continuity[j]
+                    + options.beta * curvature[j]
+                    + options.gamma * edge[j];
+                // Prefer staying put when energies are equal
+                if energy < best_energy || (energy == best_energy && *p == current) {
+                    best = *p;
+                    best_energy = energy;
+                }
+            }
+
+            if best != current {
+                points[i] = best;
+                moved += 1;
+            }
+        }
+
+        if moved == 0 {
+            break;
+        }
+    }
+
+    points
+}
+
+fn distance(a: Point<i32>, b: Point<i32>) -> f32 {
+    (((a.x - b.x).pow(2) + (a.y - b.y).pow(2)) as f32).sqrt()
+}
+
+/// Parameters for [`chan_vese`](fn.chan_vese.html).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ChanVeseOptions {
+    /// Weight of the length of the boundary between the two regions. Larger values
+    /// produce smoother boundaries.
+    pub mu: f32,
+    /// Weight of the variance of the intensities inside the segmented region.
+    pub lambda1: f32,
+    /// Weight of the variance of the intensities outside the segmented region.
+    pub lambda2: f32,
+    /// Step size used when evolving the level set.
+    pub time_step: f32,
+    /// Maximum number of iterations.
+    pub max_iterations: u32,
+    /// Iteration stops when the root mean square change in the level set between
+    /// consecutive iterations falls below this value.
+    pub tolerance: f32,
+}
+
+impl ChanVeseOptions {
+    /// User-provided options.
+    pub fn new(
+        mu: f32,
+        lambda1: f32,
+        lambda2: f32,
+        time_step: f32,
+        max_iterations: u32,
+        tolerance: f32,
+    ) -> ChanVeseOptions {
+        ChanVeseOptions {
+            mu,
+            lambda1,
+            lambda2,
+            time_step,
+            max_iterations,
+            tolerance,
+        }
+    }
+}
+
+/// Segments an image into two regions using the Chan–Vese level set method.
+///
+/// This finds a partition of the image which minimises the weighted sum of the length of
+/// the boundary between the two regions (weighted by `options.mu`) and the squared
+/// differences of the intensities in each region from that region's mean (weighted by
+/// `options.lambda1` and `options.lambda2`). It does not rely on image gradients, so can
+/// segment objects whose boundaries are not defined by strong edges.
+///
+/// The level set is initialised to a checkerboard pattern, and evolved using the
+/// semi-implicit scheme described in [Chan–Vese Segmentation] by Pascal Getreuer. Intensities
+/// are scaled to `[0, 1]`, so the default parameters suggested there (`mu = 0.25`,
+/// `lambda1 = lambda2 = 1`, `time_step = 0.5`, `tolerance = 1e-3`) are appropriate for any
+/// image.
+///
+/// Returns a mask whose value is 255 for pixels in the region where the level set is
+/// positive and 0 elsewhere. Which of the two regions is the foreground depends on the
+/// image, so callers may need to invert the result. A constant image forms a single region,
+/// and gives a mask that is 0 everywhere.
+///
+/// [Chan–Vese Segmentation]: https://doi.org/10.5201/ipol.2012.g-cv
+pub fn chan_vese(image: &GrayImage, options: ChanVeseOptions) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let (w, h) = (width as usize, height as usize);
+    if w == 0 || h == 0 {
+        return GrayImage::new(width, height);
+    }
+
+    let min = image.iter().cloned().min().unwrap() as f32;
+    let max = image.iter().cloned().max().unwrap() as f32;
+    if max == min {
+        // Any boundary would only add to the energy of a constant image
+        return GrayImage::new(width, height);
+    }
+    let values: Vec<f32> = image
+        .iter()
+        .map(|&p| (p as f32 - min) / (max - min))
+        .collect();
+
+    let mut phi: Vec<f32> = (0..h)
+        .flat_map(|y| {
+            (0..w).map(move |x| {
+                let (x, y) = (x as f32, y as f32);
+                (std::f32::consts::PI / 5.0 * x).sin() * (std::f32::consts::PI / 5.0 * y).sin()
+            })
+        })
+        .collect();
+    let mut next = vec![0f32; w * h];
+
+    const ETA: f32 = 1e-8;
+    for _ in 0..options.max_iterations {
+        // Mean intensities inside and outside the region where phi is positive
+        let (mut sum_inside, mut count_inside, mut sum_outside) = (0f32, 0usize, 0f32);
+        for (&v, &p) in values.iter().zip(phi.iter()) {
+            if p > 0.0 {
+                sum_inside += v;
+                count_inside += 1;
+            } else {
+                sum_outside += v;
+            }
+        }
+        let count_outside = w * h - count_inside;
+        let c1 = if count_inside > 0 {
+            sum_inside / count_inside as f32
+        } else {
+            0.0
+        };
+        let c2 = if count_outside > 0 {
+            sum_outside / count_outside as f32
+        } else {
+            0.0
+        };
+
+        // Values outside the image are replicated from the nearest edge pixel
+        let at = |x: usize, y: usize, dx: i32, dy: i32| {
+            let nx = (x as i32 + dx).clamp(0, w as i32 - 1) as usize;
+            let ny = (y as i32 + dy).clamp(0, h as i32 - 1) as usize;
+            phi[ny * w + nx]
+        };
+
+        let mut squared_change = 0f32;
+        for y in 0..h {
+            for x in 0..w {
+                let p = phi[y * w + x];
+                let (left, right) = (at(x, y, -1, 0), at(x, y, 1, 0));
+                let (up, down) = (at(x, y, 0, -1), at(x, y, 0, 1));
+                let dx0 = (right - left) / 2.0;
+                let dy0 = (down - up) / 2.0;
+
+                let c_right = 1.0 / (ETA + (right - p).powi(2) + dy0 * dy0).sqrt();
+                let c_left = 1.0 / (ETA + (p - left).powi(2) + dy0 * dy0).sqrt();
+                let c_down = 1.0 / (ETA + dx0 * dx0 + (down - p).powi(2)).sqrt();
+                let c_up = 1.0 / (ETA + dx0 * dx0 + (p - up).powi(2)).sqrt();
+
+                let curvature = right * c_right + left * c_left + down * c_down + up * c_up;
+                let v = values[y * w + x];
+                let fit = -options.lambda1 * (v - c1).powi(2) + options.lambda2 * (v - c2).powi(2);
+
+                // Regularised Dirac delta, scaled by the time step
+                let delta = options.time_step / (1.0 + p * p);
+                let updated = (p + delta * (options.mu * curvature + fit))
+                    / (1.0 + options.mu * delta * (c_right + c_left + c_down + c_up));
+
+                squared_change += (updated - p).powi(2);
+                next[y * w + x] = updated;
+            }
+        }
+        std::mem::swap(&mut phi, &mut next);
+
+        if (squared_change / (w * h) as f32).sqrt() < options.tolerance {
+            break;
+        }
+    }
+
+    GrayImage::from_fn(width, height, |x, y| {
+        Luma([if phi[(y * width + x) as usize] > 0.0 {
+            255
+        } else {
+            0
+        }])
+    })
+}
+
+// Converts an sRGB color to CIELAB, using the D65 white point.
+fn rgb_to_lab(rgb: [u8; 3]) -> [f32; 3] {
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(rgb[0]), linear(rgb[1]), linear(rgb[2]));
+
+    let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.072175 * b;
+    let z = (0.0193339 * r + 0.119192 * g + 0.9503041 * b) / 1.08883;
+
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn squared_distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+// The in-bounds neighbors of (x, y) under the given connectivity.
+fn neighbors(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    connectivity: Connectivity,
+) -> impl Iterator<Item = (u32, u32)> {
+    const FOUR: [(i32, i32); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+    const EIGHT: [(i32, i32); 8] = [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+    ];
+    let offsets: &'static [(i32, i32)] = match connectivity {
+        Connectivity::Four => &FOUR,
+        Connectivity::Eight => &EIGHT,
+    };
+    offsets.iter().filter_map(move |&(dx, dy)| {
+        let nx = x as i32 + dx;
+        let ny = y as i32 + dy;
+        if nx >= 0 && ny >= 0 && (nx as u32) < width && (ny as u32) < height {
+            Some((nx as u32, ny as u32))
+        } else {
+            None
+        }
+    })
+}
+
+/// Detects moving regions between two frames of a video from a static camera.
+///
+/// Returns a binary image which is 255 at pixels judged to be in motion and 0 elsewhere.
+/// This is computed by:
+///
+/// 1. taking the [absolute difference](../pixelops/fn.abs_diff.html) of the two frames,
+/// 2. [thresholding](../contrast/fn.threshold.html) this, so that pixels whose intensity
+///    changed by more than `threshold` are foreground,
+/// 3. [opening](../morphology/fn.open.html) and then [closing](../morphology/fn.close.html)
+///    the result with a 3x3 square, to remove isolated noisy pixels and fill small gaps, and
+/// 4. removing any 8-connected foreground components with fewer than `min_blob_area` pixels.
+///
+/// Frames are typically blurred before calling this function, to reduce sensor noise.
+///
+/// # Panics
+/// If `previous` and `current` do not have the same dimensions.
+///
+/// # Examples
+/// ```
+/// use image::{GrayImage, Luma};
+/// use imageproc::segmentation::motion_mask;
+///
+/// let previous = GrayImage::from_pixel(20, 20, Luma([50]));
+/// let mut current = previous.clone();
+///
+/// // A small object moves into view, and there is noise at a single pixel.
+/// for y in 5..10 {
+///     for x in 5..10 {
+///         current.put_pixel(x, y, Luma([200]));
+///     }
+/// }
+/// current.put_pixel(15, 15, Luma([255]));
+///
+/// let mask = motion_mask(&previous, &current, 30, 10);
+/// assert_eq!(mask.get_pixel(7, 7)[0], 255);
+/// assert_eq!(mask.get_pixel(15, 15)[0], 0);
+/// assert_eq!(mask.pixels().filter(|p| p[0] == 255).count(), 25);
+/// ```
+pub fn motion_mask(
+    previous: &GrayImage,
+    current: &GrayImage,
+    threshold: u8,
+    min_blob_area: u32,
+) -> GrayImage {
+    let difference = abs_diff(previous, current);
+    let mut mask = crate::contrast::threshold(&difference, threshold);
+    open_mut(&mut mask, Norm::LInf, 1);
+    close_mut(&mut mask, Norm::LInf, 1);
+
+    if min_blob_area > 1 {
+        let labels = connected_components(&mask, Connectivity::Eight, Luma([0u8]));
+        let mut areas = vec![];
+        for label in labels.iter() {
+            let label = *label as usize;
+            if label >= areas.len() {
+                areas.resize(label + 1, 0u32);
+            }
+            areas[label] += 1;
+        }
+        for (p, label) in mask.iter_mut().zip(labels.iter()) {
+            if *label != 0 && areas[*label as usize] < min_blob_area {
+                *p = 0;
+            }
+        }
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance_transform::euclidean_squared_distance_transform;
+    use crate::region_labelling::connected_components;
+    use crate::utils::{gray_bench_image, rgb_bench_image};
+    use image::GrayImage;
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn test_motion_mask_removes_small_blobs() {
+        let previous = GrayImage::new(16, 8);
+        let mut current = previous.clone();
+        // A 3x3 blob, the smallest square to survive the opening, and a 4x4 blob
+        for y in 2..5 {
+            for x in 2..5 {
+                current.put_pixel(x, y, Luma([100]));
+            }
+        }
+        for y in 2..6 {
+            for x in 9..13 {
+                current.put_pixel(x, y, Luma([100]));
+            }
+        }
+
+        let mask = motion_mask(&previous, &current, 50, 1);
+        assert_eq!(mask.get_pixel(3, 3)[0], 255);
+        assert_eq!(mask.get_pixel(10, 3)[0], 255);
+        assert_eq!(mask.iter().filter(|p| **p == 255).count(), 25);
+
+        let mask = motion_mask(&previous, &current, 50, 10);
+        assert_eq!(mask.get_pixel(3, 3)[0], 0);
+        assert_eq!(mask.iter().filter(|p| **p == 255).count(), 16);
+
+        let mask = motion_mask(&previous, &current, 100, 1);
+        assert!(mask.iter().all(|p| *p == 0));
+    }
+
+    #[test]
+    fn test_watershed_splits_touching_discs() {
+        // Two overlapping discs, centred at (8, 10) and (20, 10)
+        let in_disc = |x: u32, y: u32, cx: i32, cy: i32| {
+            let (dx, dy) = (x as i32 - cx, y as i32 - cy);
+            dx * dx + dy * dy <= 49
+        };
+        let foreground = GrayImage::from_fn(29, 21, |x, y| {
+            Luma([(in_disc(x, y, 8, 10) || in_disc(x, y, 20, 10)) as u8 * 255])
+        });
+
+        // Flood the negated distance from the background, starting from the disc centres
+        let background = GrayImage::from_fn(29, 21, |x, y| {
+            Luma([(foreground.get_pixel(x, y)[0] == 0) as u8])
+        });
+        let distances = euclidean_squared_distance_transform(&background);
+        let surface = Image::from_fn(29, 21, |x, y| {
+            Luma([1000 - distances.get_pixel(x, y)[0].sqrt().round() as u16])
+        });
+        let mut markers = Image::new(29, 21);
+        markers.put_pixel(8, 10, Luma([1u32]));
+        markers.put_pixel(20, 10, Luma([2u32]));
+
+        let labels = watershed(&surface, &markers, Connectivity::Four);
+
+        for (x, y, l) in labels.enumerate_pixels() {
+            if foreground.get_pixel(x, y)[0] == 0 {
+                continue;
+            }
+            match l[0] {
+                1 => assert!(x < 15, "({}, {}) labelled 1", x, y),
+                2 => assert!(x > 13, "({}, {}) labelled 2", x, y),
+                _ => assert!((13..=15).contains(&x), "({}, {}) labelled 0", x, y),
+            }
+        }
+        assert_eq!(labels.get_pixel(4, 10)[0], 1);
+        assert_eq!(labels.get_pixel(24, 10)[0], 2);
+    }
+
+    #[test]
+    fn test_watershed_leaves_unreachable_pixels_unlabelled() {
+        let image = gray_image!(
+            0, 0, 0;
+            0, 0, 0);
+        let markers = gray_image!(type: u32,
+            0, 0, 0;
+            0, 0, 0);
+        assert_pixels_eq!(watershed(&image, &markers, Connectivity::Eight), markers);
+    }
+
+    #[test]
+    fn test_watershed_preserves_markers() {
+        let image = gray_image!(
+            5, 5, 5, 5;
+            5, 5, 5, 5);
+        let markers = gray_image!(type: u32,
+            3, 0, 0, 0;
+            0, 0, 0, 3);
+        let expected = gray_image!(type: u32,
+            3, 3, 3, 3;
+            3, 3, 3, 3);
+        assert_pixels_eq!(watershed(&image, &markers, Connectivity::Four), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_watershed_rejects_mismatched_markers() {
+        let _ = watershed(
+            &GrayImage::new(3, 3),
+            &Image::<Luma<u32>>::new(3, 2),
+            Connectivity::Four,
+        );
+    }
+
+    #[test]
+    fn test_region_growing_assigns_contested_pixels_to_most_similar_region() {
+        let image = gray_image!(10, 30, 50);
+        let labels = region_growing(&image, &[(0, 0), (2, 0)], 25.0, Connectivity::Four);
+        // The middle pixel is within the threshold of both seeds, and is equally similar
+        // to both, so joins the region of the first seed
+        assert_pixels_eq!(labels, gray_image!(type: u32, 1, 1, 2));
+
+        let image = gray_image!(10, 32, 50);
+        let labels = region_growing(&image, &[(0, 0), (2, 0)], 25.0, Connectivity::Four);
+        assert_pixels_eq!(labels, gray_image!(type: u32, 1, 2, 2));
+    }
+
+    #[test]
+    fn test_region_growing_connectivity() {
+        let image = gray_image!(
+            0, 9;
+            9, 0);
+        let four = region_growing(&image, &[(0, 0)], 1.0, Connectivity::Four);
+        assert_pixels_eq!(four, gray_image!(type: u32, 1, 0; 0, 0));
+        let eight = region_growing(&image, &[(0, 0)], 1.0, Connectivity::Eight);
+        assert_pixels_eq!(eight, gray_image!(type: u32, 1, 0; 0, 1));
+    }
+
+    #[test]
+    fn test_region_growing_uses_region_mean() {
+        // A gradual ramp: each step is within the threshold of its neighbour, but the
+        // far end is not within the threshold of the region mean
+        let image = gray_image!(0, 3, 6, 9, 12, 15);
+        let labels = region_growing(&image, &[(0, 0)], 5.0, Connectivity::Four);
+        assert_pixels_eq!(labels, gray_image!(type: u32, 1, 1, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_region_growing_color() {
+        let image = RgbImage::from_fn(4, 1, |x, _| {
+            if x < 2 {
+                Rgb([200, 10, 10])
+            } else {
+                Rgb([200, 10, 60])
+            }
+        });
+        let labels = region_growing(&image, &[(0, 0)], 10.0, Connectivity::Four);
+        assert_pixels_eq!(labels, gray_image!(type: u32, 1, 1, 0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_region_growing_rejects_seed_outside_image() {
+        let _ = region_growing(&GrayImage::new(3, 3), &[(3, 0)], 1.0, Connectivity::Four);
+    }
+
+    #[test]
+    fn test_felzenszwalb_separates_color_regions() {
+        let image = RgbImage::from_fn(20, 12, |x, y| {
+            let noise = ((x * 7 + y * 13) % 5) as u8;
+            if x + y < 14 {
+                Rgb([200 + noise, 40, 40 - noise])
+            } else {
+                Rgb([40, 40 + noise, 200 - noise])
+            }
+        });
+        let labels = felzenszwalb(&image, 100.0, 10);
+        for (x, y, l) in labels.enumerate_pixels() {
+            assert_eq!(l[0], (x + y >= 14) as u32, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_felzenszwalb_merges_small_regions() {
+        // A single distinct pixel in a uniform image
+        let mut image = GrayImage::from_pixel(6, 6, Luma([50u8]));
+        image.put_pixel(2, 3, Luma([250u8]));
+
+        let labels = felzenszwalb(&image, 1.0, 1);
+        assert_eq!(labels.iter().max(), Some(&1));
+
+        let labels = felzenszwalb(&image, 1.0, 2);
+        assert!(labels.iter().all(|l| *l == 0));
+    }
+
+    #[test]
+    fn test_felzenszwalb_larger_k_gives_fewer_regions() {
+        let image = gray_bench_image(40, 40);
+        let count = |k| felzenszwalb(&image, k, 1).iter().max().unwrap() + 1;
+        assert!(count(500.0) < count(5.0));
+    }
+
+    // A red disc on a noisy two-tone green and blue background
+    fn grab_cut_scene() -> (RgbImage, GrayImage) {
+        let inside = |x: u32, y: u32| {
+            let (dx, dy) = (x as i32 - 20, y as i32 - 16);
+            dx * dx + dy * dy <= 81
+        };
+        let image = RgbImage::from_fn(40, 32, |x, y| {
+            let noise = ((x * 13 + y * 29) % 17) as u8;
+            if inside(x, y) {
+                Rgb([190 + noise, 30 + noise, 40])
+            } else if (x + y) % 7 < 3 {
+                Rgb([30, 160 + noise, 50])
+            } else {
+                Rgb([40, 60, 170 + noise])
+            }
+        });
+        let expected = GrayImage::from_fn(40, 32, |x, y| Luma([inside(x, y) as u8 * 255]));
+        (image, expected)
+    }
+
+    #[test]
+    fn test_grab_cut_rect() {
+        let (image, expected) = grab_cut_scene();
+        let mask = grab_cut_rect(&image, Rect::at(6, 4).of_size(28, 24), 5);
+        assert_pixels_eq!(mask, expected);
+    }
+
+    #[test]
+    fn test_grab_cut_trimap_respects_hard_labels() {
+        let (image, expected) = grab_cut_scene();
+        let mut trimap = GrayImage::from_pixel(40, 32, Luma([128u8]));
+        for x in 0..40 {
+            trimap.put_pixel(x, 0, Luma([TRIMAP_BACKGROUND]));
+        }
+        // A known foreground pixel, and a known background pixel inside the disc
+        trimap.put_pixel(20, 16, Luma([TRIMAP_FOREGROUND]));
+        trimap.put_pixel(22, 16, Luma([TRIMAP_BACKGROUND]));
+
+        let mask = grab_cut(&image, &trimap, 5);
+        assert_eq!(mask.get_pixel(20, 16)[0], TRIMAP_FOREGROUND);
+        assert_eq!(mask.get_pixel(22, 16)[0], TRIMAP_BACKGROUND);
+        let agreement = mask
+            .iter()
+            .zip(expected.iter())
+            .filter(|(m, e)| m == e)
+            .count();
+        assert!(agreement >= 40 * 32 - 4, "agreement {}", agreement);
+    }
+
+    #[test]
+    fn test_max_flow() {
+        // The example network from the Wikipedia page on the Ford-Fulkerson algorithm
+        let mut network = FlowNetwork::new(4);
+        network.add_edge(0, 1, 1000.0, 0.0);
+        network.add_edge(0, 2, 1000.0, 0.0);
+        network.add_edge(1, 2, 1.0, 0.0);
+        network.add_edge(1, 3, 1000.0, 0.0);
+        network.add_edge(2, 3, 1000.0, 0.0);
+        assert_eq!(network.max_flow(0, 3), 2000.0);
+
+        let mut network = FlowNetwork::new(4);
+        network.add_edge(0, 1, 3.0, 0.0);
+        network.add_edge(0, 2, 2.0, 0.0);
+        network.add_edge(1, 2, 5.0, 0.0);
+        network.add_edge(1, 3, 1.0, 0.0);
+        network.add_edge(2, 3, 3.0, 0.0);
+        assert_eq!(network.max_flow(0, 3), 4.0);
+        assert_eq!(network.source_side(0), vec![true, true, true, false]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grab_cut_rejects_trimap_without_background() {
+        let image = RgbImage::new(4, 4);
+        let _ = grab_cut(&image, &GrayImage::from_pixel(4, 4, Luma([128u8])), 1);
+    }
+
+    // Two noisy flat regions separated by a vertical edge
+    fn two_region_image() -> RgbImage {
+        RgbImage::from_fn(24, 16, |x, y| {
+            let noise = ((x * 7 + y * 13) % 9) as u8;
+            if x < 12 {
+                Rgb([60 + noise, 90, 120 - noise])
+            } else {
+                Rgb([200 - noise, 180, 40 + noise])
+            }
+        })
+    }
+
+    #[test]
+    fn test_mean_shift_filter_smooths_regions_and_preserves_edges() {
+        let image = two_region_image();
+        let filtered = mean_shift_filter(&image, 4, 20.0, 10);
+
+        let spread = |x0: u32, x1: u32| {
+            let values: Vec<u8> = (0..16)
+                .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+                .map(|(x, y)| filtered.get_pixel(x, y)[0])
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        assert!(spread(0, 12) <= 2, "left spread {}", spread(0, 12));
+        assert!(spread(12, 24) <= 2, "right spread {}", spread(12, 24));
+
+        assert!(filtered.get_pixel(11, 8)[0] < 80);
+        assert!(filtered.get_pixel(12, 8)[0] > 180);
+    }
+
+    #[test]
+    fn test_mean_shift_segmentation() {
+        let image = two_region_image();
+        let labels = mean_shift_segmentation(&image, 4, 20.0, 10);
+        for (x, y, l) in labels.enumerate_pixels() {
+            assert_eq!(l[0], (x >= 12) as u32, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_mean_shift_segmentation_merges_small_segments() {
+        let mut image = RgbImage::from_pixel(10, 10, Rgb([50, 50, 50]));
+        image.put_pixel(4, 4, Rgb([250, 250, 250]));
+        image.put_pixel(7, 2, Rgb([150, 150, 150]));
+
+        let labels = mean_shift_segmentation(&image, 2, 10.0, 1);
+        assert_eq!(labels.iter().max(), Some(&2));
+
+        let labels = mean_shift_segmentation(&image, 2, 10.0, 2);
+        assert!(labels.iter().all(|l| *l == 0));
+    }
+
+    #[test]
+    fn test_kmeans_colors_quantizes_to_cluster_means() {
+        let image = gray_image!(
+            10, 12, 200, 202;
+            11, 13, 201, 203);
+        let clusters = kmeans_colors(&image, 2, 10, 3);
+
+        let mut palette: Vec<u8> = clusters.palette.iter().map(|p| p[0]).collect();
+        palette.sort_unstable();
+        assert_eq!(palette, vec![12, 202]);
+
+        let dark = clusters.labels.get_pixel(0, 0)[0];
+        let expected = gray_image!(type: u32,
+            dark, dark, 1 - dark, 1 - dark;
+            dark, dark, 1 - dark, 1 - dark);
+        assert_pixels_eq!(clusters.labels, expected);
+
+        let quantized = gray_image!(
+            12, 12, 202, 202;
+            12, 12, 202, 202);
+        assert_pixels_eq!(clusters.quantized(), quantized);
+    }
+
+    #[test]
+    fn test_kmeans_colors_with_fewer_colors_than_clusters() {
+        let image = RgbImage::from_fn(5, 5, |x, _| {
+            if x < 2 {
+                Rgb([1, 2, 3])
+            } else {
+                Rgb([4, 5, 6])
+            }
+        });
+        let clusters = kmeans_colors(&image, 8, 10, 0);
+        assert_eq!(clusters.palette.len(), 2);
+        assert_pixels_eq!(clusters.quantized(), image);
+    }
+
+    #[test]
+    fn test_kmeans_colors_labels_match_palette_without_convergence() {
+        let image = GrayImage::from_fn(256, 4, |x, y| Luma([(x as u8).wrapping_mul(y as u8 + 1)]));
+        for max_iterations in 0..3 {
+            let clusters = kmeans_colors(&image, 5, max_iterations, 7);
+            for (x, y, p) in image.enumerate_pixels() {
+                let distance = |c: &Luma<u8>| (p[0] as i32 - c[0] as i32).abs();
+                let label = clusters.labels.get_pixel(x, y)[0] as usize;
+                let nearest = clusters.palette.iter().map(distance).min().unwrap();
+                // Allow for rounding of the palette colors
+                assert!(distance(&clusters.palette[label]) <= nearest + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kmeans_colors_is_deterministic_for_seed() {
+        let image = rgb_bench_image(30, 30);
+        let a = kmeans_colors(&image, 6, 20, 42);
+        let b = kmeans_colors(&image, 6, 20, 42);
+        assert_eq!(a, b);
+        assert_eq!(a.palette.len(), 6);
+    }
+
+    #[test]
+    fn test_active_contour_shrinks_onto_square() {
+        // A bright 20x20 square in a 60x60 image, and an initial contour around it
+        let image = GrayImage::from_fn(60, 60, |x, y| {
+            Luma([if (20..40).contains(&x) && (20..40).contains(&y) {
+                200
+            } else {
+                20
+            }])
+        });
+        let mut initial = vec![];
+        for i in 0..10 {
+            initial.push(Point::new(10 + 4 * i, 10));
+        }
+        for i in 0..10 {
+            initial.push(Point::new(50, 10 + 4 * i));
+        }
+        for i in 0..10 {
+            initial.push(Point::new(50 - 4 * i, 50));
+        }
+        for i in 0..10 {
+            initial.push(Point::new(10, 50 - 4 * i));
+        }
+
+        let options = ActiveContourOptions::new(1.0, 1.0, 1.2, 2, 200);
+        let contour = active_contour(&image, &initial, options);
+
+        assert_eq!(contour.len(), initial.len());
+        for p in &contour {
+            // Each point lies within a pixel or two of the edge of the square
+            let dx = (p.x - 19).abs().min((p.x - 40).abs());
+            let dy = (p.y - 19).abs().min((p.y - 40).abs());
+            let on_vertical_edge = dx <= 2 && p.y >= 17 && p.y <= 42;
+            let on_horizontal_edge = dy <= 2 && p.x >= 17 && p.x <= 42;
+            assert!(on_vertical_edge || on_horizontal_edge, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_active_contour_is_unchanged_with_no_iterations() {
+        let image = gray_bench_image(20, 20);
+        let initial = vec![Point::new(2, 2), Point::new(15, 3), Point::new(8, 16)];
+        let options = ActiveContourOptions::new(1.0, 1.0, 1.0, 1, 0);
+        assert_eq!(active_contour(&image, &initial, options), initial);
+    }
+
+    #[test]
+    fn test_rgb_to_lab() {
+        let lab = rgb_to_lab([255, 255, 255]);
+        assert!((lab[0] - 100.0).abs() < 0.01);
+        assert!(lab[1].abs() < 0.01 && lab[2].abs() < 0.01);
+
+        let lab = rgb_to_lab([255, 0, 0]);
+        assert!((lab[0] - 53.24).abs() < 0.05);
+        assert!((lab[1] - 80.09).abs() < 0.05);
+        assert!((lab[2] - 67.20).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_slic_follows_color_boundary() {
+        // Left third red, remainder blue
+        let image = RgbImage::from_fn(30, 20, |x, _| {
+            if x < 10 {
+                Rgb([200, 30, 30])
+            } else {
+                Rgb([30, 30, 200])
+            }
+        });
+
+        let superpixels = slic(&image, 6, 10.0, 10);
+        let labels = &superpixels.labels;
+
+        // No superpixel straddles the boundary
+        for (x, y, l) in labels.enumerate_pixels() {
+            let c = superpixels.centers[l[0] as usize];
+            assert_eq!(c.x < 10.0, x < 10, "pixel ({}, {}), centre {:?}", x, y, c);
+        }
+        assert!(superpixels.centers.len() >= 4 && superpixels.centers.len() <= 8);
+    }
+
+    #[test]
+    fn test_slic_superpixels_are_connected() {
+        let image = RgbImage::from_fn(40, 30, |x, y| {
+            Rgb([((x * 37 + y * 11) % 256) as u8, ((x * y) % 256) as u8, 90])
+        });
+        let superpixels = slic(&image, 12, 5.0, 5);
+        let components =
+            connected_components(&superpixels.labels, Connectivity::Four, Luma([u32::MAX]));
+        let count = components.iter().max().unwrap();
+        assert_eq!(*count as usize, superpixels.centers.len());
+    }
+
+    #[test]
+    fn test_draw_label_boundaries() {
+        let labels = gray_image!(type: u32,
+            0, 0, 1;
+            0, 0, 1;
+            2, 2, 2);
+        let image = GrayImage::new(3, 3);
+        let expected = gray_image!(
+            0, 9, 0;
+            9, 9, 9;
+            0, 0, 0);
+        assert_pixels_eq!(
+            draw_label_boundaries(&image, &labels, Luma([9u8])),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_chan_vese_low_contrast_square() {
+        // A square which is only slightly brighter than the noisy background
+        let mut rng = StdRng::seed_from_u64(1);
+        let image = GrayImage::from_fn(40, 40, |x, y| {
+            let inside = (12..28).contains(&x) && (12..28).contains(&y);
+            let base: i32 = if inside { 130 } else { 120 };
+            Luma([(base + rng.gen_range(-8, 9)) as u8])
+        });
+
+        let options = ChanVeseOptions::new(0.25, 1.0, 1.0, 0.5, 200, 1e-3);
+        let mask = chan_vese(&image, options);
+
+        let foreground = *mask.get_pixel(20, 20);
+        let mut misclassified = 0;
+        for (x, y, p) in mask.enumerate_pixels() {
+            let inside = (12..28).contains(&x) && (12..28).contains(&y);
+            if (*p == foreground) != inside {
+                misclassified += 1;
+            }
+        }
+        assert!(misclassified < 40, "{} pixels misclassified", misclassified);
+    }
+
+    #[test]
+    fn test_chan_vese_constant_image() {
+        let image = GrayImage::from_pixel(10, 10, Luma([50u8]));
+        let options = ChanVeseOptions::new(0.25, 1.0, 1.0, 0.5, 50, 1e-3);
+        let mask = chan_vese(&image, options);
+        assert_pixels_eq!(mask, GrayImage::new(10, 10));
+    }
+
+    #[bench]
+    fn bench_slic(b: &mut Bencher) {
+        let image = rgb_bench_image(200, 200);
+        b.iter(|| {
+            let superpixels = slic(&image, 100, 10.0, 10);
+            black_box(superpixels);
+        });
+    }
+
+    #[bench]
+    fn bench_felzenszwalb(b: &mut Bencher) {
+        let image = rgb_bench_image(200, 200);
+        b.iter(|| {
+            let labels = felzenszwalb(&image, 300.0, 20);
+            black_box(labels);
+        });
+    }
+
+    #[bench]
+    fn bench_grab_cut_rect(b: &mut Bencher) {
+        let image = rgb_bench_image(100, 100);
+        b.iter(|| {
+            let mask = grab_cut_rect(&image, Rect::at(20, 20).of_size(60, 60), 2);
+            black_box(mask);
+        });
+    }
+
+    #[bench]
+    fn bench_mean_shift_filter(b: &mut Bencher) {
+        let image = rgb_bench_image(100, 100);
+        b.iter(|| {
+            let filtered = mean_shift_filter(&image, 5, 20.0, 5);
+            black_box(filtered);
+        });
+    }
+
+    #[bench]
+    fn bench_kmeans_colors(b: &mut Bencher) {
+        let image = rgb_bench_image(200, 200);
+        b.iter(|| {
+            let clusters = kmeans_colors(&image, 8, 10, 1);
+            black_box(clusters);
+        });
+    }
+}
@@ -3,8 +3,9 @@
 use crate::definitions::Image;
 use crate::math::cast;
 use conv::ValueInto;
-use image::{GenericImageView, GrayImage, Pixel, Primitive};
+use image::{GenericImageView, GrayImage, Luma, Pixel, Primitive};
 use num::Bounded;
+use std::any::TypeId;
 
 /// A set of per-channel histograms from an image with 8 bits per channel.
 pub struct ChannelHistogram {
@@ -101,6 +102,909 @@ pub fn percentile(image: &GrayImage, p: u8) -> u8 {
     unreachable!();
 }
 
+/// Returns the requested percentiles of the intensities of the pixels in a grayscale image,
+/// or `None` if no pixels are included.
+///
+/// As for [`percentile`](fn.percentile.html), the `p`th percentile is the least value `x`
+/// such that at least `p`% of the included pixels have intensity less than or equal to `x`.
+/// If `mask` is provided then only pixels at which the mask is non-zero are included.
+///
+/// Percentiles are computed from a histogram of the included intensities. When all
+/// intensities are integers spanning at most 65536 values, as is always the case for 8 and
+/// 16 bit images, there is one bin per value and the results are exact. Otherwise the range
+/// of the intensities is divided into 65536 equal bins and each result is the greatest
+/// intensity in the bin containing the true percentile, so may exceed it by up to a bin width.
+///
+/// # Panics
+/// If any requested percentile is not in the range `[0, 100]`, or if `mask` is provided and
+/// does not have the same dimensions as `image`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::percentiles;
+///
+/// let image = gray_image!(type: u16,
+///     100, 200, 300, 400;
+///     500, 600, 700, 800);
+///
+/// assert_eq!(percentiles(&image, &[0.0, 50.0, 100.0], None), Some(vec![100, 400, 800]));
+///
+/// let mask = gray_image!(
+///     0, 0, 0, 0;
+///     1, 1, 1, 1);
+///
+/// assert_eq!(percentiles(&image, &[0.0, 50.0], Some(&mask)), Some(vec![500, 600]));
+/// # }
+/// ```
+pub fn percentiles<T>(
+    image: &Image<Luma<T>>,
+    ps: &[f32],
+    mask: Option<&GrayImage>,
+) -> Option<Vec<T>>
+where
+    T: Primitive + 'static,
+{
+    assert!(
+        ps.iter().all(|p| (0.0..=100.0).contains(p)),
+        "requested percentiles must be between 0 and 100"
+    );
+    match mask {
+        Some(mask) => {
+            assert_dimensions_match!(image, mask);
+            let values = image
+                .iter()
+                .zip(mask.iter())
+                .filter(|(_, m)| **m != 0)
+                .map(|(v, _)| *v);
+            value_percentiles(values, ps)
+        }
+        None => value_percentiles(image.iter().cloned(), ps),
+    }
+}
+
+// Computes the requested percentiles of a sequence of values, as described in the
+// documentation for percentiles.
+fn value_percentiles<T, I>(values: I, ps: &[f32]) -> Option<Vec<T>>
+where
+    T: Primitive + 'static,
+    I: Iterator<Item = T> + Clone,
+{
+    const MAX_BINS: usize = 65536;
+
+    // 8 and 16 bit values are binned directly, with one bin per possible value
+    let direct_bins = if TypeId::of::<T>() == TypeId::of::<u8>() {
+        Some(256)
+    } else if TypeId::of::<T>() == TypeId::of::<u16>() {
+        Some(MAX_BINS)
+    } else {
+        None
+    };
+    if let Some(bins) = direct_bins {
+        let mut counts = vec![0u64; bins];
+        for v in values {
+            counts[v.to_usize().unwrap()] += 1;
+        }
+        let bins = percentile_bins(&mut counts, ps)?;
+        return Some(bins.into_iter().map(|b| T::from(b).unwrap()).collect());
+    }
+
+    let (mut min, mut max, mut integral) = (f64::INFINITY, f64::NEG_INFINITY, true);
+    for v in values.clone() {
+        let v = v.to_f64().unwrap();
+        min = min.min(v);
+        max = max.max(v);
+        integral &= v.fract() == 0.0;
+    }
+    if min > max {
+        return None;
+    }
+    let exact = min == max || (max - min < MAX_BINS as f64 && integral);
+    let (bins, bin_width) = if exact {
+        ((max - min) as usize + 1, 1.0)
+    } else {
+        (MAX_BINS, (max - min) / MAX_BINS as f64)
+    };
+
+    let mut counts = vec![0u64; bins];
+    let mut bin_max = vec![f64::NEG_INFINITY; if exact { 0 } else { bins }];
+    for v in values {
+        let v = v.to_f64().unwrap();
+        let bin = (((v - min) / bin_width) as usize).min(bins - 1);
+        counts[bin] += 1;
+        if !exact {
+            bin_max[bin] = bin_max[bin].max(v);
+        }
+    }
+
+    let bins = percentile_bins(&mut counts, ps)?;
+    let result = bins
+        .into_iter()
+        .map(|bin| {
+            let value = if exact {
+                min + bin as f64
+            } else {
+                bin_max[bin]
+            };
+            T::from(value).unwrap()
+        })
+        .collect();
+    Some(result)
+}
+
+// Replaces counts by their cumulative sums and returns the index of the bin containing each
+// requested percentile, or None if all counts are zero.
+fn percentile_bins(counts: &mut [u64], ps: &[f32]) -> Option<Vec<usize>> {
+    for i in 1..counts.len() {
+        counts[i] += counts[i - 1];
+    }
+    let total = *counts.last()?;
+    if total == 0 {
+        return None;
+    }
+    let bins = ps
+        .iter()
+        .map(|&p| {
+            let required = p as f64 / 100.0 * total as f64;
+            counts
+                .iter()
+                .position(|&c| c > 0 && c as f64 >= required)
+                .unwrap()
+        })
+        .collect();
+    Some(bins)
+}
+
+/// Summary statistics of the values of a single image channel.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ChannelStatistics {
+    /// The number of values.
+    pub count: u64,
+    /// The least value.
+    pub min: f64,
+    /// The greatest value.
+    pub max: f64,
+    /// The mean value.
+    pub mean: f64,
+    /// The population standard deviation of the values.
+    pub std_dev: f64,
+    /// The median value, i.e. the 50th percentile as defined by
+    /// [`percentiles`](fn.percentiles.html). This is only computed by
+    /// [`statistics_with_median`](fn.statistics_with_median.html), and is `None` otherwise.
+    pub median: Option<f64>,
+}
+
+/// Computes the minimum, maximum, mean and standard deviation of each channel of an image
+/// in a single pass. Returns `None` if no pixels are included.
+///
+/// If `mask` is provided then only pixels at which the mask is non-zero are included.
+/// The `median` field of each result is `None`. Medians require a second pass over the
+/// image, so are only computed by [`statistics_with_median`](fn.statistics_with_median.html).
+///
+/// # Panics
+/// If `mask` is provided and does not have the same dimensions as `image`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::statistics;
+///
+/// let image = rgb_image!(
+///     [1, 10, 0], [3, 20, 0];
+///     [5, 30, 0], [7, 40, 0]);
+///
+/// let stats = statistics(&image, None).unwrap();
+/// assert_eq!(stats[0].mean, 4.0);
+/// assert_eq!(stats[1].min, 10.0);
+/// assert_eq!(stats[1].max, 40.0);
+/// assert_eq!(stats[2].std_dev, 0.0);
+///
+/// let mask = gray_image!(
+///     0, 1;
+///     0, 1);
+///
+/// let stats = statistics(&image, Some(&mask)).unwrap();
+/// assert_eq!(stats[0].count, 2);
+/// assert_eq!(stats[0].mean, 5.0);
+/// assert_eq!(stats[0].std_dev, 2.0);
+/// # }
+/// ```
+pub fn statistics<P>(image: &Image<P>, mask: Option<&GrayImage>) -> Option<Vec<ChannelStatistics>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64>,
+{
+    if let Some(mask) = mask {
+        assert_dimensions_match!(image, mask);
+    }
+
+    let channels = P::CHANNEL_COUNT as usize;
+    let mut stats = vec![
+        ChannelStatistics {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            std_dev: 0.0,
+            median: None,
+        };
+        channels
+    ];
+
+    for (x, y, p) in image.enumerate_pixels() {
+        if let Some(mask) = mask {
+            if mask.get_pixel(x, y)[0] == 0 {
+                continue;
+            }
+        }
+        // Welford's algorithm, accumulating the sum of squared deviations in std_dev
+        for (s, c) in stats.iter_mut().zip(p.channels()) {
+            let v: f64 = cast(*c);
+            s.count += 1;
+            s.min = s.min.min(v);
+            s.max = s.max.max(v);
+            let delta = v - s.mean;
+            s.mean += delta / s.count as f64;
+            s.std_dev += delta * (v - s.mean);
+        }
+    }
+
+    if stats[0].count == 0 {
+        return None;
+    }
+    for s in &mut stats {
+        s.std_dev = (s.std_dev / s.count as f64).sqrt();
+    }
+    Some(stats)
+}
+
+/// Computes the minimum, maximum, mean, standard deviation and median of each channel of an
+/// image. Returns `None` if no pixels are included.
+///
+/// The median is computed from a histogram of each channel as described in the
+/// [`percentiles`](fn.percentiles.html) documentation, so is exact for images with 8 or 16 bits
+/// per channel. See the [`statistics`](fn.statistics.html) documentation for more information.
+///
+/// # Panics
+/// If `mask` is provided and does not have the same dimensions as `image`.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::statistics_with_median;
+///
+/// let image = gray_image!(
+///     1, 2, 200;
+///     3, 4, 250);
+///
+/// let stats = statistics_with_median(&image, None).unwrap();
+/// assert_eq!(stats[0].median, Some(3.0));
+/// # }
+/// ```
+pub fn statistics_with_median<P>(
+    image: &Image<P>,
+    mask: Option<&GrayImage>,
+) -> Option<Vec<ChannelStatistics>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: ValueInto<f64> + 'static,
+{
+    let mut stats = statistics(image, mask)?;
+    let channels = P::CHANNEL_COUNT as usize;
+    for (c, s) in stats.iter_mut().enumerate() {
+        let values = image.chunks(channels).map(move |p| p[c]);
+        let median = match mask {
+            Some(mask) => value_percentiles(
+                values
+                    .zip(mask.iter())
+                    .filter(|(_, m)| **m != 0)
+                    .map(|(v, _)| v),
+                &[50.0],
+            ),
+            None => value_percentiles(values, &[50.0]),
+        };
+        s.median = median.map(|m| cast(m[0]));
+    }
+    Some(stats)
+}
+
+/// A histogram of the intensities of one channel of an image with 8 bits per channel.
+///
+/// Each of the `bins()` bins covers an equal-sized (up to rounding) range of consecutive
+/// intensities, with bin `i` covering the intensities `v` such that
+/// `v * bins / 256 == i`. Unlike [`histogram`](fn.histogram.html), this supports bin counts
+/// other than 256 and provides cumulative, percentile and median queries.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::Histogram;
+///
+/// let image = gray_image!(
+///     1, 2, 3,  4,  5;
+///     6, 7, 8, 9, 200);
+///
+/// let hist = Histogram::from_channel(&image, 0, 4);
+/// assert_eq!(hist.counts(), &[9, 0, 0, 1]);
+/// assert_eq!(hist.bin_range(3), (192, 255));
+///
+/// let hist = Histogram::from_channel(&image, 0, 256);
+/// assert_eq!(hist.median(), Some(5));
+/// assert_eq!(hist.percentile(90.0), Some(9));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Vec<u32>,
+}
+
+impl Histogram {
+    /// Creates a histogram from the count of each bin.
+    ///
+    /// # Panics
+    /// If `counts` is empty or has more than 256 entries.
+    pub fn from_counts(counts: Vec<u32>) -> Histogram {
+        assert!(
+            !counts.is_empty() && counts.len() <= 256,
+            "a histogram must have between 1 and 256 bins"
+        );
+        Histogram { counts }
+    }
+
+    /// Computes a histogram with the given number of bins of a single channel of an image.
+    ///
+    /// # Panics
+    /// If `channel` is not less than the number of channels of `P`, or if `bins` is 0
+    /// or greater than 256.
+    pub fn from_channel<P>(image: &Image<P>, channel: usize, bins: usize) -> Histogram
+    where
+        P: Pixel<Subpixel = u8> + 'static,
+    {
+        assert!(
+            channel < P::CHANNEL_COUNT as usize,
+            "channel index out of bounds"
+        );
+        let mut hist = Histogram::from_counts(vec![0; bins]);
+        for p in image.pixels() {
+            let bin = hist.bin(p.channels()[channel]);
+            hist.counts[bin] += 1;
+        }
+        hist
+    }
+
+    /// Computes a histogram with the given number of bins for each channel of an image.
+    ///
+    /// # Panics
+    /// If `bins` is 0 or greater than 256.
+    pub fn per_channel<P>(image: &Image<P>, bins: usize) -> Vec<Histogram>
+    where
+        P: Pixel<Subpixel = u8> + 'static,
+    {
+        let mut hists = vec![Histogram::from_counts(vec![0; bins]); P::CHANNEL_COUNT as usize];
+        for p in image.pixels() {
+            for (hist, c) in hists.iter_mut().zip(p.channels()) {
+                let bin = hist.bin(*c);
+                hist.counts[bin] += 1;
+            }
+        }
+        hists
+    }
+
+    /// The number of bins in this histogram.
+    pub fn bins(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// The count of each bin.
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// The total count over all bins.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|&c| c as u64).sum()
+    }
+
+    /// The index of the bin containing `intensity`.
+    pub fn bin(&self, intensity: u8) -> usize {
+        intensity as usize * self.bins() / 256
+    }
+
+    /// The least and greatest intensities contained in the given bin.
+    ///
+    /// # Panics
+    /// If `bin >= self.bins()`.
+    pub fn bin_range(&self, bin: usize) -> (u8, u8) {
+        assert!(bin < self.bins(), "bin index out of bounds");
+        let n = self.bins();
+        // The least v such that v * n / 256 >= bin
+        let start = |b: usize| if b == 0 { 0 } else { (256 * b - 1) / n + 1 };
+        (start(bin) as u8, (start(bin + 1) - 1) as u8)
+    }
+
+    /// The cumulative counts of this histogram, i.e. the entry at index `i` is
+    /// the sum of the counts of bins `0..=i`.
+    pub fn cumulative(&self) -> Vec<u64> {
+        self.counts
+            .iter()
+            .scan(0u64, |sum, &c| {
+                *sum += c as u64;
+                Some(*sum)
+            })
+            .collect()
+    }
+
+    /// Returns the `p`th percentile of the counted intensities, or `None` if the
+    /// histogram is empty.
+    ///
+    /// This is the greatest intensity in the first bin `b` such that at least one and
+    /// at least `p`% of the counted values lie in bins `0..=b`. In particular, for a histogram
+    /// with 256 bins this is the least intensity `x` such that at least `p`% of the counted
+    /// intensities are less than or equal to `x`, and the 0th percentile is the minimum
+    /// counted intensity.
+    ///
+    /// # Panics
+    /// If `p` is not in the range `[0, 100]`.
+    pub fn percentile(&self, p: f32) -> Option<u8> {
+        assert!((0.0..=100.0).contains(&p), "p must be between 0 and 100");
+        let total = self.total();
+        let required = p as f64 / 100.0 * total as f64;
+        self.cumulative()
+            .iter()
+            .position(|&c| c > 0 && c as f64 >= required)
+            .map(|bin| self.bin_range(bin).1)
+    }
+
+    /// Returns the median of the counted intensities, or `None` if the histogram is empty.
+    ///
+    /// This is equivalent to `self.percentile(50.0)`.
+    pub fn median(&self) -> Option<u8> {
+        self.percentile(50.0)
+    }
+
+    /// Returns the mean of the counted intensities, or `None` if the histogram is empty.
+    ///
+    /// Each value is treated as lying at the midpoint of its bin.
+    pub fn mean(&self) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let sum: f64 = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(bin, &c)| {
+                let (lo, hi) = self.bin_range(bin);
+                c as f64 * (lo as f64 + hi as f64) / 2.0
+            })
+            .sum();
+        Some(sum / total as f64)
+    }
+
+    /// Returns the Shannon entropy in bits of the distribution of counted values over bins,
+    /// or 0 if the histogram is empty.
+    pub fn entropy(&self) -> f64 {
+        entropy_of_counts(&self.counts)
+    }
+}
+
+// The Shannon entropy in bits of the distribution with the given counts.
+fn entropy_of_counts(counts: &[u32]) -> f64 {
+    let total: u64 = counts.iter().map(|&c| c as u64).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Method used by [`compare_histograms`](fn.compare_histograms.html) to measure the
+/// difference or similarity between two histograms.
+///
+/// All methods first normalize each histogram so that its counts sum to 1, so histograms
+/// of images of different sizes can be compared.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HistogramComparison {
+    /// The symmetric chi-square distance `sum((p - q)^2 / (p + q))`, with bins that are empty
+    /// in both histograms ignored. This is 0 for identical histograms and 2 for histograms
+    /// with no overlap.
+    ///
+    /// Smaller values are more similar.
+    ChiSquare,
+    /// The histogram intersection `sum(min(p, q))`. This is 1 for identical histograms and
+    /// 0 for histograms with no overlap.
+    ///
+    /// Higher values are more similar.
+    Intersection,
+    /// The Bhattacharyya (or Hellinger) distance `sqrt(1 - sum(sqrt(p * q)))`, as computed by
+    /// OpenCV's `HISTCMP_BHATTACHARYYA`. This is 0 for identical histograms and 1 for
+    /// histograms with no overlap.
+    ///
+    /// Smaller values are more similar.
+    Bhattacharyya,
+    /// The one-dimensional earth mover's distance, i.e. the minimum total distance in bins
+    /// that mass must be moved to transform one histogram into the other. This is computed
+    /// as the sum of the absolute differences of the cumulative histograms. Unlike the other
+    /// methods, this takes into account how far apart differing bins are.
+    ///
+    /// Smaller values are more similar.
+    EarthMovers,
+}
+
+/// Compares two histograms using the given method.
+///
+/// # Panics
+/// If the histograms have different numbers of bins, or if either histogram is empty.
+///
+/// # Examples
+/// ```
+/// use imageproc::stats::{compare_histograms, Histogram, HistogramComparison};
+///
+/// let a = Histogram::from_counts(vec![4, 0, 0, 0]);
+/// let b = Histogram::from_counts(vec![0, 2, 0, 0]);
+/// let c = Histogram::from_counts(vec![0, 0, 0, 8]);
+///
+/// // b and c are both disjoint from a
+/// assert_eq!(compare_histograms(&a, &b, HistogramComparison::Intersection), 0.0);
+/// assert_eq!(compare_histograms(&a, &c, HistogramComparison::Intersection), 0.0);
+/// assert_eq!(compare_histograms(&a, &c, HistogramComparison::Bhattacharyya), 1.0);
+///
+/// // but b is closer to a than c is
+/// assert_eq!(compare_histograms(&a, &b, HistogramComparison::EarthMovers), 1.0);
+/// assert_eq!(compare_histograms(&a, &c, HistogramComparison::EarthMovers), 3.0);
+/// ```
+pub fn compare_histograms(a: &Histogram, b: &Histogram, method: HistogramComparison) -> f64 {
+    assert_eq!(
+        a.bins(),
+        b.bins(),
+        "histograms must have the same number of bins"
+    );
+    let (total_a, total_b) = (a.total(), b.total());
+    assert!(total_a > 0 && total_b > 0, "histograms must be non-empty");
+
+    let p = a.counts.iter().map(|&c| c as f64 / total_a as f64);
+    let q = b.counts.iter().map(|&c| c as f64 / total_b as f64);
+    let pairs = p.zip(q);
+
+    match method {
+        HistogramComparison::ChiSquare => pairs
+            .filter(|(p, q)| p + q > 0.0)
+            .map(|(p, q)| (p - q) * (p - q) / (p + q))
+            .sum(),
+        HistogramComparison::Intersection => pairs.map(|(p, q)| p.min(q)).sum(),
+        HistogramComparison::Bhattacharyya => {
+            let coefficient: f64 = pairs.map(|(p, q)| (p * q).sqrt()).sum();
+            (1.0 - coefficient).max(0.0).sqrt()
+        }
+        HistogramComparison::EarthMovers => {
+            let mut difference = 0.0;
+            let mut distance = 0.0;
+            for (p, q) in pairs {
+                difference += p - q;
+                distance += f64::abs(difference);
+            }
+            distance
+        }
+    }
+}
+
+/// A joint histogram of the intensities of corresponding pixels in two grayscale images.
+///
+/// The intensities of each image are binned as in [`Histogram`](struct.Histogram.html).
+/// The count for bins `(i, j)` is the number of positions at which the first image has
+/// an intensity in bin `i` and the second image has an intensity in bin `j`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JointHistogram {
+    bins: usize,
+    counts: Vec<u32>,
+}
+
+impl JointHistogram {
+    /// The number of bins used for the intensities of each image.
+    pub fn bins(&self) -> usize {
+        self.bins
+    }
+
+    /// The number of positions at which the first image has an intensity in bin `a`
+    /// and the second image has an intensity in bin `b`.
+    ///
+    /// # Panics
+    /// If `a` or `b` is not less than `self.bins()`.
+    pub fn count(&self, a: usize, b: usize) -> u32 {
+        assert!(a < self.bins && b < self.bins, "bin index out of bounds");
+        self.counts[a * self.bins + b]
+    }
+
+    /// All counts, in row-major order. That is, the count for bins `(a, b)` is at
+    /// index `a * self.bins() + b`.
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// The total count over all bins, i.e. the number of pixels in each image.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|&c| c as u64).sum()
+    }
+
+    /// The histogram of the first image, obtained by summing over the bins of the second.
+    pub fn marginal_a(&self) -> Histogram {
+        Histogram::from_counts(
+            self.counts
+                .chunks(self.bins)
+                .map(|row| row.iter().sum())
+                .collect(),
+        )
+    }
+
+    /// The histogram of the second image, obtained by summing over the bins of the first.
+    pub fn marginal_b(&self) -> Histogram {
+        let mut counts = vec![0; self.bins];
+        for row in self.counts.chunks(self.bins) {
+            for (c, r) in counts.iter_mut().zip(row) {
+                *c += r;
+            }
+        }
+        Histogram::from_counts(counts)
+    }
+
+    /// The Shannon entropy in bits of the joint distribution of bin pairs.
+    pub fn entropy(&self) -> f64 {
+        entropy_of_counts(&self.counts)
+    }
+
+    /// The mutual information in bits of the binned intensities of the two images,
+    /// i.e. `H(A) + H(B) - H(A, B)` where `H(A)` and `H(B)` are the entropies of the marginal
+    /// histograms and `H(A, B)` is the joint entropy.
+    ///
+    /// This measures how much knowing the intensity of a pixel in one image tells us about
+    /// the intensity of the corresponding pixel in the other, and is maximised when one image
+    /// is a function of the other. It does not assume any particular relationship between the
+    /// intensities, so is suitable for aligning images from different modalities.
+    pub fn mutual_information(&self) -> f64 {
+        let value = self.marginal_a().entropy() + self.marginal_b().entropy() - self.entropy();
+        value.max(0.0)
+    }
+
+    /// The normalized mutual information `(H(A) + H(B)) / H(A, B)` of Studholme et al.
+    ///
+    /// This is between 1, for independent intensities, and 2, when each image is a function
+    /// of the other, and is less sensitive than
+    /// [`mutual_information`](#method.mutual_information) to the amount of overlap between
+    /// registered images. If both images are constant then this is defined to be 2.
+    pub fn normalized_mutual_information(&self) -> f64 {
+        let joint = self.entropy();
+        if joint == 0.0 {
+            return 2.0;
+        }
+        (self.marginal_a().entropy() + self.marginal_b().entropy()) / joint
+    }
+}
+
+/// Computes the joint histogram of the intensities of corresponding pixels in two images,
+/// using `bins` bins for the intensities of each image.
+///
+/// Joint histograms are the basis of mutual information based registration,
+/// and of co-occurrence analysis between channels or frames. To compare channels of a
+/// color image, first extract them using e.g. [`map::red_channel`](../map/fn.red_channel.html).
+///
+/// # Panics
+/// If the images do not have the same dimensions, or if `bins` is 0 or greater than 256.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::joint_histogram;
+///
+/// let a = gray_image!(
+///     0,   0, 255;
+///     0, 255, 255);
+///
+/// let b = gray_image!(
+///     10,  10, 250;
+///     250, 10, 250);
+///
+/// let joint = joint_histogram(&a, &b, 2);
+/// assert_eq!(joint.counts(), &[2, 1, 1, 2]);
+/// assert_eq!(joint.count(0, 1), 1);
+/// # }
+/// ```
+pub fn joint_histogram(a: &GrayImage, b: &GrayImage, bins: usize) -> JointHistogram {
+    assert_dimensions_match!(a, b);
+    let binning = Histogram::from_counts(vec![0; bins]);
+    let mut counts = vec![0u32; bins * bins];
+    for (p, q) in a.iter().zip(b.iter()) {
+        counts[binning.bin(*p) * bins + binning.bin(*q)] += 1;
+    }
+    JointHistogram { bins, counts }
+}
+
+/// Computes the Shannon entropy in bits of the intensity histogram of a grayscale image.
+///
+/// This is between 0, for an image with a single intensity, and 8, for an image in which
+/// all intensities occur equally often.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::entropy;
+///
+/// let image = gray_image!(
+///     0, 0, 0, 0;
+///     0, 0, 0, 0);
+/// assert_eq!(entropy(&image), 0.0);
+///
+/// let image = gray_image!(
+///     0, 0, 0, 0;
+///     1, 1, 2, 3);
+/// assert_eq!(entropy(&image), 1.75);
+/// # }
+/// ```
+pub fn entropy(image: &GrayImage) -> f64 {
+    Histogram::from_channel(image, 0, 256).entropy()
+}
+
+/// Computes the Shannon entropy in bits of the intensity histogram of the
+/// `(2 * radius + 1) x (2 * radius + 1)` window centred on each pixel.
+///
+/// Windows are truncated at the image boundaries. Local entropy is high in textured
+/// or detailed regions and low in flat ones, so it is useful as a texture feature and
+/// as a focus measure.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::local_entropy;
+///
+/// let image = gray_image!(
+///     0, 0, 0, 0, 7;
+///     0, 0, 0, 0, 7;
+///     0, 0, 0, 0, 7);
+///
+/// let entropy = local_entropy(&image, 1);
+/// assert!(entropy.get_pixel(1, 1)[0].abs() < 1e-6);
+/// // The window centred at (3, 1) contains six zeros and three sevens
+/// assert!((entropy.get_pixel(3, 1)[0] - 0.9183).abs() < 1e-4);
+/// # }
+/// ```
+pub fn local_entropy(image: &GrayImage, radius: u32) -> Image<Luma<f32>> {
+    let (width, height) = image.dimensions();
+    let mut out = Image::new(width, height);
+    if width == 0 || height == 0 {
+        return out;
+    }
+
+    // The entropy of a window with n pixels in which intensity i occurs c_i times is
+    // log2(n) - sum(c_i * log2(c_i)) / n, so we only need to track sum(c_i * log2(c_i))
+    // as pixels enter and leave the window.
+    let c_log_c = |c: u32| {
+        if c == 0 {
+            0.0
+        } else {
+            c as f64 * (c as f64).log2()
+        }
+    };
+
+    for y in 0..height {
+        let top = y.saturating_sub(radius);
+        let bottom = (y + radius).min(height - 1);
+
+        let mut counts = [0u32; 256];
+        let mut n = 0u32;
+        let mut sum = 0.0;
+        let mut update = |x: u32, add: bool| {
+            for wy in top..=bottom {
+                let c = &mut counts[image.get_pixel(x, wy)[0] as usize];
+                sum -= c_log_c(*c);
+                if add {
+                    *c += 1;
+                    n += 1;
+                } else {
+                    *c -= 1;
+                    n -= 1;
+                }
+                sum += c_log_c(*c);
+            }
+            (n as f64).log2() - sum / n as f64
+        };
+
+        let mut value = 0.0;
+        for x in 0..radius.min(width) {
+            value = update(x, true);
+        }
+        for x in 0..width {
+            if x + radius < width {
+                value = update(x + radius, true);
+            }
+            if x > radius {
+                value = update(x - radius - 1, false);
+            }
+            out.put_pixel(x, y, Luma([value.max(0.0) as f32]));
+        }
+    }
+    out
+}
+
+/// Computes the mutual information in bits of the intensities of two grayscale images,
+/// using `bins` bins for the intensities of each image.
+///
+/// See [`JointHistogram::mutual_information`](struct.JointHistogram.html#method.mutual_information).
+///
+/// # Panics
+/// If the images do not have the same dimensions, or if `bins` is 0 or greater than 256.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::{mutual_information, normalized_mutual_information};
+///
+/// let a = gray_image!(
+///     0,   0, 255, 255;
+///     0,   0, 255, 255);
+///
+/// // b is an intensity-inverted copy of a, so determines it completely
+/// let b = gray_image!(
+///     200, 200, 10, 10;
+///     200, 200, 10, 10);
+///
+/// assert_eq!(mutual_information(&a, &b, 16), 1.0);
+/// assert_eq!(normalized_mutual_information(&a, &b, 16), 2.0);
+///
+/// // c tells us nothing about a
+/// let c = gray_image!(
+///     0, 255, 0, 255;
+///     0, 255, 0, 255);
+///
+/// assert_eq!(mutual_information(&a, &c, 16), 0.0);
+/// assert_eq!(normalized_mutual_information(&a, &c, 16), 1.0);
+/// # }
+/// ```
+pub fn mutual_information(a: &GrayImage, b: &GrayImage, bins: usize) -> f64 {
+    joint_histogram(a, b, bins).mutual_information()
+}
+
+/// Computes the normalized mutual information of the intensities of two grayscale images,
+/// using `bins` bins for the intensities of each image.
+///
+/// See [`JointHistogram::normalized_mutual_information`](struct.JointHistogram.html#method.normalized_mutual_information).
+///
+/// # Panics
+/// If the images do not have the same dimensions, or if `bins` is 0 or greater than 256.
+pub fn normalized_mutual_information(a: &GrayImage, b: &GrayImage, bins: usize) -> f64 {
+    joint_histogram(a, b, bins).normalized_mutual_information()
+}
+
 /// Returns the square root of the mean of the squares of differences
 /// between all subpixels in left and right. All channels are considered
 /// equally. If you do not want this (e.g. if using RGBA) then change
@@ -119,6 +1023,11 @@ where
 /// aproximation. All channels are considered equally. If you do not want this
 /// (e.g. if using RGBA) then change image formats first.
 /// See also [peak signal-to-noise ratio (wikipedia)](https://en.wikipedia.org/wiki/Peak_signal-to-noise_ratio).
+///
+/// The peak signal value is the maximum value of the subpixel type, e.g. 255 for `u8`
+/// and 65535 for `u16`. For images whose intensities do not span the full range of their
+/// subpixel type, such as floating point images or 12-bit images stored as `u16`, use
+/// [`peak_signal_to_noise_ratio_with_peak`](fn.peak_signal_to_noise_ratio_with_peak.html).
 pub fn peak_signal_to_noise_ratio<I, J, P>(original: &I, noisy: &J) -> f64
 where
     I: GenericImageView<Pixel = P>,
@@ -127,29 +1036,333 @@ where
     P::Subpixel: ValueInto<f64> + Primitive,
 {
     let max: f64 = cast(<P::Subpixel as Bounded>::max_value());
+    peak_signal_to_noise_ratio_with_peak(original, noisy, max)
+}
+
+/// Returns the peak signal to noise ratio for a clean image and its noisy
+/// aproximation, where `peak` is the maximum possible intensity. All channels are
+/// considered equally.
+///
+/// Returns positive infinity if the images are identical.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::{ImageBuffer, Luma};
+/// use imageproc::stats::peak_signal_to_noise_ratio_with_peak;
+///
+/// // Intensities in [0, 1]
+/// let original = ImageBuffer::from_pixel(2, 2, Luma([0.5f32]));
+/// let noisy = ImageBuffer::from_fn(2, 2, |x, _| Luma([0.5f32 + 0.1 * x as f32]));
+///
+/// // The mean squared error is 0.005
+/// let psnr = peak_signal_to_noise_ratio_with_peak(&original, &noisy, 1.0);
+/// assert!((psnr - 23.0103).abs() < 1e-3);
+/// # }
+/// ```
+pub fn peak_signal_to_noise_ratio_with_peak<I, J, P>(original: &I, noisy: &J, peak: f64) -> f64
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+    P::Subpixel: ValueInto<f64>,
+{
     let mse = mean_squared_error(original, noisy);
-    20f64 * max.log(10f64) - 10f64 * mse.log(10f64)
+    20f64 * peak.log(10f64) - 10f64 * mse.log(10f64)
+}
+
+/// Returns the mean of the squares of differences between all subpixels in left and right.
+/// All channels are considered equally. Use
+/// [`mean_squared_error_per_channel`](fn.mean_squared_error_per_channel.html) to compute
+/// errors for each channel separately.
+pub fn mean_squared_error<I, J, P>(left: &I, right: &J) -> f64
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+    P::Subpixel: ValueInto<f64>,
+{
+    mean_of_channel_errors(left, right, |d| d * d)
+}
+
+/// Returns the mean of the absolute differences between all subpixels in left and right.
+/// All channels are considered equally. Use
+/// [`mean_absolute_error_per_channel`](fn.mean_absolute_error_per_channel.html) to compute
+/// errors for each channel separately.
+pub fn mean_absolute_error<I, J, P>(left: &I, right: &J) -> f64
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+    P::Subpixel: ValueInto<f64>,
+{
+    mean_of_channel_errors(left, right, f64::abs)
+}
+
+/// Returns the mean of the squares of differences between corresponding subpixels in left
+/// and right, for each channel.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::stats::{mean_squared_error, mean_squared_error_per_channel};
+///
+/// let left = rgb_image!([10, 20, 30], [10, 20, 30]);
+/// let right = rgb_image!([12, 20, 30], [14, 20, 33]);
+///
+/// assert_eq!(mean_squared_error_per_channel(&left, &right), vec![10.0, 0.0, 4.5]);
+/// assert_eq!(mean_squared_error(&left, &right), 14.5 / 3.0);
+/// # }
+/// ```
+pub fn mean_squared_error_per_channel<I, J, P>(left: &I, right: &J) -> Vec<f64>
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+    P::Subpixel: ValueInto<f64>,
+{
+    channel_errors(left, right, |d| d * d)
+}
+
+/// Returns the mean of the absolute differences between corresponding subpixels in left
+/// and right, for each channel.
+pub fn mean_absolute_error_per_channel<I, J, P>(left: &I, right: &J) -> Vec<f64>
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+    P::Subpixel: ValueInto<f64>,
+{
+    channel_errors(left, right, f64::abs)
+}
+
+fn mean_of_channel_errors<I, J, P, F>(left: &I, right: &J, error: F) -> f64
+where
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
+    P: Pixel,
+    P::Subpixel: ValueInto<f64>,
+    F: Fn(f64) -> f64,
+{
+    let errors = channel_errors(left, right, error);
+    errors.iter().sum::<f64>() / errors.len() as f64
 }
 
-fn mean_squared_error<I, J, P>(left: &I, right: &J) -> f64
+// Returns the mean of error(left - right) over all pixels, for each channel.
+fn channel_errors<I, J, P, F>(left: &I, right: &J, error: F) -> Vec<f64>
 where
     I: GenericImageView<Pixel = P>,
     J: GenericImageView<Pixel = P>,
     P: Pixel,
     P::Subpixel: ValueInto<f64>,
+    F: Fn(f64) -> f64,
 {
     assert_dimensions_match!(left, right);
-    let mut sum_squared_diffs = 0f64;
+    let mut sums = vec![0f64; P::CHANNEL_COUNT as usize];
     for (p, q) in left.pixels().zip(right.pixels()) {
-        for (c, d) in p.2.channels().iter().zip(q.2.channels().iter()) {
+        for ((s, c), d) in sums
+            .iter_mut()
+            .zip(p.2.channels().iter())
+            .zip(q.2.channels().iter())
+        {
             let fc: f64 = cast(*c);
             let fd: f64 = cast(*d);
-            let diff = fc - fd;
-            sum_squared_diffs += diff * diff;
+            *s += error(fc - fd);
+        }
+    }
+    let count = left.width() as f64 * left.height() as f64;
+    sums.iter().map(|s| s / count).collect()
+}
+
+// The constants used to stabilise the SSIM luminance and contrast terms, as fractions of
+// the maximum intensity.
+const SSIM_K1: f64 = 0.01;
+const SSIM_K2: f64 = 0.03;
+
+/// Computes the [structural similarity index] (SSIM) of two grayscale images.
+///
+/// Returns the mean SSIM over the image and the per-pixel SSIM map, in that order. SSIM is 1
+/// for identical images and decreases as the images become less similar, taking into account
+/// differences in local luminance, contrast and structure.
+///
+/// Local statistics are computed over the 7x7 square window centred on each pixel, truncated
+/// at the image boundaries, using integral images. This matches the default used by
+/// scikit-image, rather than the Gaussian window of the original paper.
+///
+/// [structural similarity index]: https://en.wikipedia.org/wiki/Structural_similarity
+///
+/// # Panics
+///
+/// If the images do not have the same dimensions.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # extern crate imageproc;
+/// # fn main() {
+/// use image::{GrayImage, Luma};
+/// use imageproc::stats::ssim;
+///
+/// let image = GrayImage::from_fn(20, 20, |x, y| Luma([(x * 10 + y) as u8]));
+/// let darker = GrayImage::from_fn(20, 20, |x, y| Luma([(x * 10 + y) as u8 / 2]));
+///
+/// let (score, map) = ssim(&image, &image);
+/// assert_eq!(score, 1.0);
+/// assert_eq!(map.dimensions(), (20, 20));
+///
+/// let (score, _) = ssim(&image, &darker);
+/// assert!(score < 0.9);
+/// # }
+/// ```
+pub fn ssim(a: &GrayImage, b: &GrayImage) -> (f64, Image<Luma<f32>>) {
+    assert_dimensions_match!(a, b);
+    let (width, height) = a.dimensions();
+    let a: Vec<f64> = a.iter().map(|&p| p as f64).collect();
+    let b: Vec<f64> = b.iter().map(|&p| p as f64).collect();
+
+    let (luminance, contrast_structure) = ssim_components(&a, &b, width, height);
+    let values: Vec<f64> = luminance
+        .iter()
+        .zip(&contrast_structure)
+        .map(|(l, cs)| l * cs)
+        .collect();
+
+    let map = Image::from_fn(width, height, |x, y| {
+        Luma([values[(y * width + x) as usize] as f32])
+    });
+    (mean(&values), map)
+}
+
+/// Computes the [multi-scale structural similarity index] (MS-SSIM) of two grayscale images.
+///
+/// The images are repeatedly downsampled by averaging 2x2 blocks. The contrast and structure
+/// terms of [`ssim`](fn.ssim.html) are computed at every scale and the luminance term only at
+/// the coarsest, and these are combined using the weights from the original paper. Up to five
+/// scales are used. Smaller images use fewer scales, stopping before either dimension becomes
+/// less than 7, and the weights of the scales used are normalized to sum to 1.
+///
+/// [multi-scale structural similarity index]: https://doi.org/10.1109/ACSSC.2003.1292216
+///
+/// # Panics
+///
+/// If the images do not have the same dimensions.
+pub fn ms_ssim(a: &GrayImage, b: &GrayImage) -> f64 {
+    assert_dimensions_match!(a, b);
+    const WEIGHTS: [f64; 5] = [0.0448, 0.2856, 0.3001, 0.2363, 0.1333];
+
+    let (mut width, mut height) = a.dimensions();
+    let mut a: Vec<f64> = a.iter().map(|&p| p as f64).collect();
+    let mut b: Vec<f64> = b.iter().map(|&p| p as f64).collect();
+
+    let mut scales = 1;
+    let (mut w, mut h) = (width, height);
+    while scales < WEIGHTS.len() && w / 2 >= 7 && h / 2 >= 7 {
+        scales += 1;
+        w /= 2;
+        h /= 2;
+    }
+    let total_weight: f64 = WEIGHTS[..scales].iter().sum();
+
+    let mut result = 1.0;
+    for (scale, weight) in WEIGHTS[..scales].iter().enumerate() {
+        let (luminance, contrast_structure) = ssim_components(&a, &b, width, height);
+        // Negative values are clamped to 0 so that fractional powers are defined
+        let value = if scale + 1 == scales {
+            let values: Vec<f64> = luminance
+                .iter()
+                .zip(&contrast_structure)
+                .map(|(l, cs)| l * cs)
+                .collect();
+            mean(&values)
+        } else {
+            mean(&contrast_structure)
+        };
+        result *= value.max(0.0).powf(weight / total_weight);
+
+        if scale + 1 < scales {
+            a = downsample(&a, width, height);
+            b = downsample(&b, width, height);
+            width /= 2;
+            height /= 2;
+        }
+    }
+    result
+}
+
+// Computes the luminance and contrast-structure terms of SSIM at each pixel of two images
+// with the given dimensions, stored in row-major order.
+fn ssim_components(a: &[f64], b: &[f64], width: u32, height: u32) -> (Vec<f64>, Vec<f64>) {
+    const RADIUS: usize = 3;
+    let c1 = (SSIM_K1 * 255.0).powi(2);
+    let c2 = (SSIM_K2 * 255.0).powi(2);
+    let (w, h) = (width as usize, height as usize);
+
+    let a_sums = box_sums(a.iter().cloned(), w, h);
+    let b_sums = box_sums(b.iter().cloned(), w, h);
+    let aa_sums = box_sums(a.iter().map(|v| v * v), w, h);
+    let bb_sums = box_sums(b.iter().map(|v| v * v), w, h);
+    let ab_sums = box_sums(a.iter().zip(b).map(|(u, v)| u * v), w, h);
+
+    let mut luminance = Vec::with_capacity(w * h);
+    let mut contrast_structure = Vec::with_capacity(w * h);
+    for y in 0..h {
+        let (top, bottom) = (y.saturating_sub(RADIUS), (y + RADIUS + 1).min(h));
+        for x in 0..w {
+            let (left, right) = (x.saturating_sub(RADIUS), (x + RADIUS + 1).min(w));
+            let n = ((bottom - top) * (right - left)) as f64;
+            let window_sum = |sums: &[f64]| {
+                let at = |x: usize, y: usize| sums[y * (w + 1) + x];
+                (at(right, bottom) + at(left, top) - at(left, bottom) - at(right, top)) / n
+            };
+            let (mean_a, mean_b) = (window_sum(&a_sums), window_sum(&b_sums));
+            let var_a = window_sum(&aa_sums) - mean_a * mean_a;
+            let var_b = window_sum(&bb_sums) - mean_b * mean_b;
+            let covariance = window_sum(&ab_sums) - mean_a * mean_b;
+
+            luminance.push((2.0 * mean_a * mean_b + c1) / (mean_a * mean_a + mean_b * mean_b + c1));
+            contrast_structure.push((2.0 * covariance + c2) / (var_a + var_b + c2));
+        }
+    }
+    (luminance, contrast_structure)
+}
+
+// The integral image of the given values, with width + 1 columns and height + 1 rows.
+fn box_sums<I: Iterator<Item = f64>>(values: I, width: usize, height: usize) -> Vec<f64> {
+    let mut sums = vec![0.0; (width + 1) * (height + 1)];
+    let mut values = values;
+    for y in 0..height {
+        let mut row_sum = 0.0;
+        for x in 0..width {
+            row_sum += values.next().unwrap();
+            sums[(y + 1) * (width + 1) + x + 1] = sums[y * (width + 1) + x + 1] + row_sum;
+        }
+    }
+    sums
+}
+
+// Halves the dimensions of an image by averaging 2x2 blocks.
+fn downsample(values: &[f64], width: u32, height: u32) -> Vec<f64> {
+    let (w, h) = (width as usize, height as usize);
+    let mut out = Vec::with_capacity((w / 2) * (h / 2));
+    for y in 0..h / 2 {
+        for x in 0..w / 2 {
+            let at = |dx: usize, dy: usize| values[(2 * y + dy) * w + 2 * x + dx];
+            out.push((at(0, 0) + at(1, 0) + at(0, 1) + at(1, 1)) / 4.0);
         }
     }
-    let count = (left.width() * left.height() * P::CHANNEL_COUNT as u32) as f64;
-    sum_squared_diffs / count
+    out
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 1.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
 }
 
 #[cfg(test)]
@@ -233,6 +1446,306 @@ mod tests {
         assert!(b.iter().skip(4).all(|x| *x == 0));
     }
 
+    #[test]
+    fn test_histogram_type_matches_histogram() {
+        let image = rgb_image!([1u8, 10u8, 1u8], [2u8, 20u8, 2u8], [3u8, 30u8, 3u8]);
+        let expected = histogram(&image);
+        let hists = Histogram::per_channel(&image, 256);
+        for c in 0..3 {
+            assert_eq!(hists[c].counts(), &expected.channels[c][..]);
+            assert_eq!(hists[c], Histogram::from_channel(&image, c, 256));
+        }
+        assert_eq!(
+            hists[1].cumulative()[..],
+            cumulative_histogram(&image).channels[1]
+                .iter()
+                .map(|&c| c as u64)
+                .collect::<Vec<_>>()[..]
+        );
+    }
+
+    #[test]
+    fn test_histogram_bin_ranges() {
+        for &bins in &[1, 3, 7, 64, 100, 256] {
+            let hist = Histogram::from_counts(vec![0; bins]);
+            let mut next = 0;
+            for bin in 0..bins {
+                let (lo, hi) = hist.bin_range(bin);
+                assert_eq!(lo as usize, next);
+                assert!(hi >= lo);
+                assert_eq!(hist.bin(lo), bin);
+                assert_eq!(hist.bin(hi), bin);
+                next = hi as usize + 1;
+            }
+            assert_eq!(next, 256);
+        }
+    }
+
+    #[test]
+    fn test_histogram_percentile_matches_percentile() {
+        let image = gray_image!(
+            1, 2, 3, 4, 5;
+            6, 7, 8, 9, 10);
+        let hist = Histogram::from_channel(&image, 0, 256);
+        for p in 1..=100 {
+            assert_eq!(hist.percentile(p as f32), Some(percentile(&image, p)));
+        }
+        assert_eq!(hist.percentile(0.0), Some(1));
+        assert_eq!(hist.median(), Some(5));
+    }
+
+    #[test]
+    fn test_histogram_empty() {
+        let hist = Histogram::from_counts(vec![0; 16]);
+        assert_eq!(hist.total(), 0);
+        assert_eq!(hist.median(), None);
+        assert_eq!(hist.mean(), None);
+    }
+
+    #[test]
+    fn test_histogram_mean() {
+        let image = gray_image!(0, 10, 20);
+        let hist = Histogram::from_channel(&image, 0, 256);
+        assert_eq!(hist.mean(), Some(10.0));
+    }
+
+    #[test]
+    fn test_joint_histogram_of_image_with_itself_is_diagonal() {
+        let image = GrayImage::from_fn(16, 16, |x, y| Luma([(x * 16 + y) as u8]));
+        let joint = joint_histogram(&image, &image, 8);
+        for a in 0..8 {
+            for b in 0..8 {
+                let expected = if a == b { 32 } else { 0 };
+                assert_eq!(joint.count(a, b), expected);
+            }
+        }
+        assert_eq!(joint.total(), 256);
+    }
+
+    #[test]
+    fn test_joint_histogram_marginals() {
+        let a = GrayImage::from_fn(10, 10, |x, y| Luma([(x * 25) as u8 ^ (y * 7) as u8]));
+        let b = GrayImage::from_fn(10, 10, |x, y| Luma([(y * 25) as u8 + x as u8]));
+        let joint = joint_histogram(&a, &b, 16);
+        assert_eq!(joint.marginal_a(), Histogram::from_channel(&a, 0, 16));
+        assert_eq!(joint.marginal_b(), Histogram::from_channel(&b, 0, 16));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_joint_histogram_rejects_mismatched_dimensions() {
+        let _ = joint_histogram(&GrayImage::new(2, 3), &GrayImage::new(3, 2), 4);
+    }
+
+    #[test]
+    fn test_mutual_information_of_image_with_itself_is_its_entropy() {
+        let image = GrayImage::from_fn(16, 9, |x, y| Luma([((x * 7 + y * 13) % 64) as u8]));
+        let joint = joint_histogram(&image, &image, 256);
+        assert_approx_eq!(joint.mutual_information(), entropy(&image), 1e-9);
+        assert_approx_eq!(joint.normalized_mutual_information(), 2.0, 1e-9);
+    }
+
+    #[test]
+    fn test_mutual_information_is_symmetric_and_bounded() {
+        let a = GrayImage::from_fn(16, 9, |x, y| Luma([((x * 7 + y * 13) % 64) as u8]));
+        let b = GrayImage::from_fn(16, 9, |x, y| Luma([((x * 5 + y * y) % 97) as u8]));
+        let mi = mutual_information(&a, &b, 32);
+        assert_approx_eq!(mi, mutual_information(&b, &a, 32), 1e-9);
+        assert!(mi >= 0.0 && mi <= entropy(&a).min(entropy(&b)));
+
+        let nmi = normalized_mutual_information(&a, &b, 32);
+        assert!((1.0..=2.0).contains(&nmi));
+    }
+
+    #[test]
+    fn test_percentiles_match_percentile() {
+        let image = GrayImage::from_fn(17, 13, |x, y| Luma([((x * 31 + y * 17) % 251) as u8]));
+        let ps: Vec<u8> = (0..=100).collect();
+        let expected: Vec<u8> = ps.iter().map(|&p| percentile(&image, p)).collect();
+        let ps: Vec<f32> = ps.iter().map(|&p| p as f32).collect();
+        assert_eq!(percentiles(&image, &ps, None), Some(expected));
+    }
+
+    #[test]
+    fn test_percentiles_of_u16_image_spanning_full_range() {
+        let image = gray_image!(type: u16,
+            0, 1, 40000;
+            65535, 65535, 7);
+        assert_eq!(
+            percentiles(&image, &[0.0, 50.0, 60.0, 100.0], None),
+            Some(vec![0, 7, 40000, 65535])
+        );
+    }
+
+    #[test]
+    fn test_percentiles_of_float_image() {
+        let image = Image::from_fn(100, 1, |x, _| Luma([x as f32 / 10.0 + 0.05]));
+        let result = percentiles(&image, &[0.0, 25.0, 100.0], None).unwrap();
+        assert_eq!(result[0], 0.05);
+        assert_eq!(result[1], 2.45);
+        assert_eq!(result[2], 9.95);
+    }
+
+    #[test]
+    fn test_percentiles_with_empty_mask() {
+        let image = gray_image!(1, 2; 3, 4);
+        assert_eq!(
+            percentiles(&image, &[50.0], Some(&GrayImage::new(2, 2))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_statistics_gray() {
+        let image = gray_image!(type: f32,
+            1.0, 2.0, 3.0;
+            4.0, 5.0, 6.0);
+
+        let stats = statistics(&image, None).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].count, 6);
+        assert_eq!(stats[0].min, 1.0);
+        assert_eq!(stats[0].max, 6.0);
+        assert_approx_eq!(stats[0].mean, 3.5, 1e-12);
+        assert_approx_eq!(stats[0].std_dev, (35.0f64 / 12.0).sqrt(), 1e-12);
+    }
+
+    #[test]
+    fn test_statistics_with_median() {
+        let image = rgb_image!(
+            [1, 10, 0], [3, 20, 0];
+            [5, 30, 0], [7, 40, 0]);
+        let mask = gray_image!(
+            1, 1;
+            1, 0);
+
+        assert_eq!(statistics(&image, None).unwrap()[0].median, None);
+
+        let stats = statistics_with_median(&image, None).unwrap();
+        let medians: Vec<Option<f64>> = stats.iter().map(|s| s.median).collect();
+        assert_eq!(medians, vec![Some(3.0), Some(20.0), Some(0.0)]);
+
+        let stats = statistics_with_median(&image, Some(&mask)).unwrap();
+        assert_eq!(stats[0].count, 3);
+        assert_eq!(stats[1].median, Some(20.0));
+        assert_eq!(
+            stats[1].mean,
+            statistics(&image, Some(&mask)).unwrap()[1].mean
+        );
+
+        let image = gray_image!(type: f32, 0.5, 1.5, 2.5);
+        assert_eq!(
+            statistics_with_median(&image, None).unwrap()[0].median,
+            Some(1.5)
+        );
+    }
+
+    #[test]
+    fn test_statistics_with_empty_mask() {
+        let image = gray_image!(1, 2; 3, 4);
+        assert_eq!(statistics(&image, Some(&GrayImage::new(2, 2))), None);
+        assert_eq!(statistics(&GrayImage::new(0, 0), None), None);
+        assert_eq!(
+            statistics_with_median(&image, Some(&GrayImage::new(2, 2))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_compare_identical_histograms() {
+        let a = Histogram::from_counts(vec![1, 5, 0, 2]);
+        let b = Histogram::from_counts(vec![2, 10, 0, 4]);
+        assert_eq!(
+            compare_histograms(&a, &b, HistogramComparison::ChiSquare),
+            0.0
+        );
+        assert_approx_eq!(
+            compare_histograms(&a, &b, HistogramComparison::Intersection),
+            1.0,
+            1e-12
+        );
+        assert_approx_eq!(
+            compare_histograms(&a, &b, HistogramComparison::Bhattacharyya),
+            0.0,
+            1e-6
+        );
+        assert_eq!(
+            compare_histograms(&a, &b, HistogramComparison::EarthMovers),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_compare_histograms() {
+        let a = Histogram::from_counts(vec![2, 2, 0, 0]);
+        let b = Histogram::from_counts(vec![0, 2, 2, 0]);
+        // p = [0.5, 0.5, 0, 0], q = [0, 0.5, 0.5, 0]
+        assert_approx_eq!(
+            compare_histograms(&a, &b, HistogramComparison::ChiSquare),
+            1.0,
+            1e-12
+        );
+        assert_approx_eq!(
+            compare_histograms(&a, &b, HistogramComparison::Intersection),
+            0.5,
+            1e-12
+        );
+        assert_approx_eq!(
+            compare_histograms(&a, &b, HistogramComparison::Bhattacharyya),
+            0.5f64.sqrt(),
+            1e-12
+        );
+        assert_approx_eq!(
+            compare_histograms(&a, &b, HistogramComparison::EarthMovers),
+            1.0,
+            1e-12
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_compare_histograms_rejects_mismatched_bins() {
+        let a = Histogram::from_counts(vec![1, 2]);
+        let b = Histogram::from_counts(vec![1, 2, 3]);
+        let _ = compare_histograms(&a, &b, HistogramComparison::Intersection);
+    }
+
+    #[test]
+    fn test_entropy_of_uniform_distribution() {
+        let image = GrayImage::from_fn(16, 16, |x, y| Luma([(y * 16 + x) as u8]));
+        assert_approx_eq!(entropy(&image), 8.0, 1e-12);
+        assert_eq!(Histogram::from_counts(vec![0; 4]).entropy(), 0.0);
+    }
+
+    #[test]
+    fn test_local_entropy_matches_window_histograms() {
+        let image = GrayImage::from_fn(9, 7, |x, y| Luma([((x * x + 3 * y) % 5) as u8]));
+        for &radius in &[0, 1, 2, 10] {
+            let local = local_entropy(&image, radius);
+            for y in 0..image.height() {
+                for x in 0..image.width() {
+                    let mut counts = vec![0; 256];
+                    for wy in y.saturating_sub(radius)..=(y + radius).min(image.height() - 1) {
+                        for wx in x.saturating_sub(radius)..=(x + radius).min(image.width() - 1) {
+                            counts[image.get_pixel(wx, wy)[0] as usize] += 1;
+                        }
+                    }
+                    let expected = Histogram::from_counts(counts).entropy();
+                    assert_approx_eq!(local.get_pixel(x, y)[0] as f64, expected, 1e-5);
+                }
+            }
+        }
+    }
+
+    #[bench]
+    fn bench_local_entropy(b: &mut Bencher) {
+        let image = crate::utils::gray_bench_image(200, 200);
+        b.iter(|| {
+            let entropy = local_entropy(&image, 4);
+            test::black_box(entropy);
+        });
+    }
+
     #[test]
     fn test_root_mean_squared_error_grayscale() {
         let left = gray_image!(
@@ -273,6 +1786,104 @@ mod tests {
         RgbImage::from_fn(width, height, |x, y| Rgb([(x + y) as u8, x as u8, y as u8]))
     }
 
+    // Computes SSIM at (x, y) directly from the pixels in its window.
+    fn ssim_reference(a: &GrayImage, b: &GrayImage, x: u32, y: u32) -> f64 {
+        let (width, height) = a.dimensions();
+        let mut pairs = vec![];
+        for wy in y.saturating_sub(3)..(y + 4).min(height) {
+            for wx in x.saturating_sub(3)..(x + 4).min(width) {
+                pairs.push((a.get_pixel(wx, wy)[0] as f64, b.get_pixel(wx, wy)[0] as f64));
+            }
+        }
+        let n = pairs.len() as f64;
+        let mean_a = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_b = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+        let var_a = pairs.iter().map(|p| (p.0 - mean_a).powi(2)).sum::<f64>() / n;
+        let var_b = pairs.iter().map(|p| (p.1 - mean_b).powi(2)).sum::<f64>() / n;
+        let cov = pairs
+            .iter()
+            .map(|p| (p.0 - mean_a) * (p.1 - mean_b))
+            .sum::<f64>()
+            / n;
+        let (c1, c2) = (6.5025, 58.5225);
+        ((2.0 * mean_a * mean_b + c1) * (2.0 * cov + c2))
+            / ((mean_a * mean_a + mean_b * mean_b + c1) * (var_a + var_b + c2))
+    }
+
+    #[test]
+    fn test_ssim_matches_reference() {
+        let a = GrayImage::from_fn(15, 11, |x, y| Luma([((x * 37 + y * 91) % 256) as u8]));
+        let b = GrayImage::from_fn(15, 11, |x, y| Luma([((x * 41 + y * 13) % 256) as u8]));
+
+        let (score, map) = ssim(&a, &b);
+        let mut total = 0.0;
+        for (x, y, p) in map.enumerate_pixels() {
+            let expected = ssim_reference(&a, &b, x, y);
+            assert!((p[0] as f64 - expected).abs() < 1e-5);
+            total += expected;
+        }
+        assert!((score - total / 165.0).abs() < 1e-9);
+        assert_eq!(ssim(&b, &a).0, score);
+    }
+
+    #[test]
+    fn test_ms_ssim() {
+        let image = GrayImage::from_fn(128, 96, |x, y| {
+            Luma([((x as f32 / 5.0).sin() * 60.0 + (y as f32 / 7.0).cos() * 60.0 + 128.0) as u8])
+        });
+        assert!((ms_ssim(&image, &image) - 1.0).abs() < 1e-12);
+
+        let slightly_noisy = crate::noise::gaussian_noise(&image, 0.0, 5.0, 1);
+        let very_noisy = crate::noise::gaussian_noise(&image, 0.0, 40.0, 1);
+        let slight = ms_ssim(&image, &slightly_noisy);
+        let very = ms_ssim(&image, &very_noisy);
+        assert!(slight < 1.0 && very < slight, "{} {}", slight, very);
+
+        // Images too small to downsample use a single scale
+        let small = GrayImage::from_fn(10, 10, |x, y| Luma([(x * y) as u8]));
+        let other = GrayImage::from_fn(10, 10, |x, y| Luma([(x + y) as u8]));
+        assert!((ms_ssim(&small, &other) - ssim(&small, &other).0.max(0.0)).abs() < 1e-12);
+    }
+
+    #[bench]
+    fn bench_ssim(b: &mut Bencher) {
+        let left = left_image_gray(100, 100);
+        let right = right_image_gray(100, 100);
+        b.iter(|| {
+            let score = ssim(&left, &right);
+            test::black_box(score);
+        });
+    }
+
+    #[test]
+    fn test_mean_absolute_error() {
+        let left = gray_image!(1, 2, 3; 4, 5, 6);
+        let right = gray_image!(2, 2, 0; 4, 9, 6);
+        assert_eq!(mean_absolute_error(&left, &right), 8.0 / 6.0);
+        assert_eq!(
+            mean_absolute_error_per_channel(&left, &right),
+            vec![8.0 / 6.0]
+        );
+        assert_eq!(mean_squared_error(&left, &right), 26.0 / 6.0);
+    }
+
+    #[test]
+    fn test_peak_signal_to_noise_ratio_bit_depths() {
+        let original = gray_image!(type: u16, 100, 200; 300, 400);
+        let noisy = gray_image!(type: u16, 101, 199; 301, 399);
+        let psnr = peak_signal_to_noise_ratio(&original, &noisy);
+        assert!((psnr - 20.0 * 65535f64.log10()).abs() < 1e-9);
+
+        // A 12-bit image stored as u16
+        let psnr = peak_signal_to_noise_ratio_with_peak(&original, &noisy, 4095.0);
+        assert!((psnr - 20.0 * 4095f64.log10()).abs() < 1e-9);
+
+        assert_eq!(
+            peak_signal_to_noise_ratio(&original, &original),
+            f64::INFINITY
+        );
+    }
+
     #[bench]
     fn bench_root_mean_squared_error_rgb(b: &mut Bencher) {
         let left = left_image_rgb(50, 50);
@@ -1,26 +1,61 @@
 //! Functions for performing template matching.
-use crate::definitions::Image;
-use crate::integral_image::{integral_squared_image, sum_image_pixels};
+use crate::definitions::{Image, Position, Score};
+use crate::distance_transform::euclidean_squared_distance_transform;
+use crate::error::{check_dimensions, Error};
+use crate::gpu;
+use crate::integral_image::{integral_image, integral_squared_image, sum_image_pixels};
 use crate::rect::Rect;
+use crate::suppress::local_maxima;
 use image::Primitive;
 use image::{GrayImage, Luma};
+use rustfft::num_complex::Complex;
+use rustfft::{FftDirection, FftPlanner};
 
 /// Method used to compute the matching score between a template and an image region.
+///
+/// Each method computes the same scores as the corresponding method of OpenCV's
+/// `matchTemplate`, so results can be compared directly.
+///
+/// New methods may be added in future releases, so matches on this type must include a
+/// wildcard arm.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum MatchTemplateMethod {
     /// Sum of the squares of the difference between image and template pixel
-    /// intensities.
+    /// intensities. Equivalent to OpenCV's `TM_SQDIFF`.
     ///
     /// Smaller values are better.
     SumOfSquaredErrors,
-    /// Divides the sum computed using `SumOfSquaredErrors` by a normalization term.
+    /// Divides the sum computed using `SumOfSquaredErrors` by the square root of the
+    /// product of the sums of squares of the image region and template pixel intensities.
+    /// Equivalent to OpenCV's `TM_SQDIFF_NORMED`.
     SumOfSquaredErrorsNormalized,
-    /// Cross Correlation
+    /// Sum of the products of image and template pixel intensities. Equivalent to OpenCV's
+    /// `TM_CCORR`.
     ///
     /// Higher values are better.
     CrossCorrelation,
-    /// Divides the sum computed using `CrossCorrelation` by a normalization term.
+    /// Divides the sum computed using `CrossCorrelation` by the square root of the
+    /// product of the sums of squares of the image region and template pixel intensities.
+    /// Equivalent to OpenCV's `TM_CCORR_NORMED`.
     CrossCorrelationNormalized,
+    /// Cross-correlation of the mean-subtracted image region and template. Equivalent to
+    /// OpenCV's `TM_CCOEFF`.
+    ///
+    /// Higher values are better. Scores are unaffected by changing the brightness of
+    /// either the image or the template.
+    CorrelationCoefficient,
+    /// Normalized cross-correlation of the mean-subtracted image region and template, i.e.
+    /// the Pearson correlation coefficient of their pixel intensities. Equivalent to OpenCV's
+    /// `TM_CCOEFF_NORMED`.
+    ///
+    /// Scores lie in `[-1, 1]`, and higher values are better. Scores are unaffected by
+    /// changing the brightness or contrast of either the image or the template, which makes
+    /// this the most robust method under varying lighting. The score is 0 where either the
+    /// template or the image region is constant.
+    ///
+    /// The sums and sums of squares of image regions are computed using integral images.
+    CorrelationCoefficientNormalized,
 }
 
 /// Slides a `template` over an `image` and scores the match at each point using
@@ -29,6 +64,14 @@ pub enum MatchTemplateMethod {
 /// The returned image has dimensions `image.width() - template.width() + 1` by
 /// `image.height() - template.height() + 1`.
 ///
+/// For large templates the correlation between the image and template is computed using the
+/// fast Fourier transform, and sums over image regions are computed using integral images.
+/// This is selected automatically when it is expected to be faster than computing the scores
+/// directly, which is typically the case for templates larger than around 16 by 16 pixels.
+/// If the `gpu` feature is enabled and a GPU is available then the correlation is instead
+/// computed on the GPU for templates with at least 49 pixels, except for the sum of squared
+/// errors methods, whose scores need more precision than the GPU's `f32` arithmetic provides.
+///
 /// # Panics
 ///
 /// If either dimension of `template` is not strictly less than the corresponding dimension
@@ -37,20 +80,47 @@ pub fn match_template(
     image: &GrayImage,
     template: &GrayImage,
     method: MatchTemplateMethod,
+) -> Image<Luma<f32>> {
+    try_match_template(image, template, method).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Slides a `template` over an `image` and scores the match at each point using
+/// the requested `method`, returning an error if `template` is larger than `image`.
+///
+/// See the [`match_template`](fn.match_template.html) documentation for more information.
+pub fn try_match_template(
+    image: &GrayImage,
+    template: &GrayImage,
+    method: MatchTemplateMethod,
+) -> Result<Image<Luma<f32>>, Error> {
+    check_template_fits(image, template)?;
+    Ok(match_template_unchecked(image, template, method))
+}
+
+fn match_template_unchecked(
+    image: &GrayImage,
+    template: &GrayImage,
+    method: MatchTemplateMethod,
 ) -> Image<Luma<f32>> {
     use image::GenericImageView;
 
     let (image_width, image_height) = image.dimensions();
     let (template_width, template_height) = template.dimensions();
 
-    assert!(
-        image_width >= template_width,
-        "image width must be greater than or equal to template width"
-    );
-    assert!(
-        image_height >= template_height,
-        "image height must be greater than or equal to template height"
-    );
+    if use_fft(image.dimensions(), template.dimensions()) || use_gpu(method, template.dimensions())
+    {
+        return match_template_by_correlation(image, template, method);
+    }
+
+    match method {
+        MatchTemplateMethod::CorrelationCoefficient => {
+            return match_template_correlation_coefficient(image, template, false)
+        }
+        MatchTemplateMethod::CorrelationCoefficientNormalized => {
+            return match_template_correlation_coefficient(image, template, true)
+        }
+        _ => {}
+    }
 
     let should_normalize = matches! { method,
     MatchTemplateMethod::SumOfSquaredErrorsNormalized
@@ -89,6 +159,9 @@ pub fn match_template(
                         CrossCorrelation | CrossCorrelationNormalized => {
                             image_value * template_value
                         }
+                        CorrelationCoefficient | CorrelationCoefficientNormalized => {
+                            unreachable!()
+                        }
                     };
                 }
             }
@@ -108,6 +181,423 @@ pub fn match_template(
     result
 }
 
+fn match_template_correlation_coefficient(
+    image: &GrayImage,
+    template: &GrayImage,
+    normalize: bool,
+) -> Image<Luma<f32>> {
+    let (template_width, template_height) = template.dimensions();
+    let n = (template_width * template_height) as f64;
+
+    // Correlating with the mean-subtracted template is equivalent to correlating the
+    // mean-subtracted image region with the mean-subtracted template.
+    let template_mean = template.iter().map(|p| *p as f64).sum::<f64>() / n;
+    let centred: Vec<f32> = template
+        .iter()
+        .map(|p| (*p as f64 - template_mean) as f32)
+        .collect();
+    let template_variance: f64 = centred.iter().map(|t| (*t as f64).powi(2)).sum();
+
+    let integral = integral_image::<_, u64>(image);
+    let squared_integral = integral_squared_image::<_, u64>(image);
+    let data = image.as_raw();
+
+    let mut result = Image::new(
+        image.width() - template_width + 1,
+        image.height() - template_height + 1,
+    );
+
+    for y in 0..result.height() {
+        for x in 0..result.width() {
+            let (right, bottom) = (x + template_width - 1, y + template_height - 1);
+            let norm = if normalize {
+                let sum = sum_image_pixels(&integral, x, y, right, bottom)[0] as f64;
+                let sum_squares =
+                    sum_image_pixels(&squared_integral, x, y, right, bottom)[0] as f64;
+                let region_variance = sum_squares - sum * sum / n;
+                (region_variance * template_variance).sqrt()
+            } else {
+                1.0
+            };
+            if norm <= 0.0 {
+                continue;
+            }
+
+            let mut correlation = 0f32;
+            for dy in 0..template_height {
+                let start = ((y + dy) * image.width() + x) as usize;
+                let template_row = &centred[(dy * template_width) as usize..];
+                for (i, t) in data[start..start + template_width as usize]
+                    .iter()
+                    .zip(template_row)
+                {
+                    correlation += *i as f32 * t;
+                }
+            }
+
+            let score = if normalize {
+                (correlation as f64 / norm).clamp(-1.0, 1.0) as f32
+            } else {
+                correlation
+            };
+            result.put_pixel(x, y, Luma([score]));
+        }
+    }
+
+    result
+}
+
+// Returns an error if either dimension of template is greater than the corresponding
+// dimension of image.
+fn check_template_fits(image: &GrayImage, template: &GrayImage) -> Result<(), Error> {
+    let (image_width, image_height) = image.dimensions();
+    let (template_width, template_height) = template.dimensions();
+    if template_width > image_width || template_height > image_height {
+        return Err(Error::KernelTooLarge {
+            kernel: template.dimensions(),
+            image: image.dimensions(),
+        });
+    }
+    Ok(())
+}
+
+/// Slides a `template` over an `image` and scores the match at each point using
+/// the requested `method`, considering only the template pixels for which `mask` is non-zero.
+///
+/// This allows matching non-rectangular objects, by ignoring background pixels inside the
+/// bounding box of the template. Each score is computed exactly as for
+/// [`match_template`](fn.match_template.html), but with all sums taken over the masked
+/// template pixels and the corresponding image pixels only. In particular, the means used by
+/// `CorrelationCoefficient` and `CorrelationCoefficientNormalized` are the means of the
+/// masked pixels.
+///
+/// The returned image has dimensions `image.width() - template.width() + 1` by
+/// `image.height() - template.height() + 1`.
+///
+/// # Panics
+///
+/// If either dimension of `template` is not strictly less than the corresponding dimension
+/// of `image`, or if `mask` and `template` have different dimensions.
+pub fn match_template_with_mask(
+    image: &GrayImage,
+    template: &GrayImage,
+    mask: &GrayImage,
+    method: MatchTemplateMethod,
+) -> Image<Luma<f32>> {
+    try_match_template_with_mask(image, template, mask, method).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Slides a `template` over an `image` and scores the match at each point using
+/// the requested `method`, considering only the template pixels for which `mask` is non-zero.
+/// Returns an error if `template` is larger than `image`, or if `mask` and `template` have
+/// different dimensions.
+///
+/// See the [`match_template_with_mask`](fn.match_template_with_mask.html) documentation for
+/// more information.
+pub fn try_match_template_with_mask(
+    image: &GrayImage,
+    template: &GrayImage,
+    mask: &GrayImage,
+    method: MatchTemplateMethod,
+) -> Result<Image<Luma<f32>>, Error> {
+    check_template_fits(image, template)?;
+    check_dimensions(mask.dimensions(), template.dimensions())?;
+    Ok(match_template_with_mask_unchecked(
+        image, template, mask, method,
+    ))
+}
+
+fn match_template_with_mask_unchecked(
+    image: &GrayImage,
+    template: &GrayImage,
+    mask: &GrayImage,
+    method: MatchTemplateMethod,
+) -> Image<Luma<f32>> {
+    use MatchTemplateMethod::*;
+
+    let (image_width, image_height) = image.dimensions();
+    let (template_width, template_height) = template.dimensions();
+
+    // Offsets into the image and values of the masked template pixels
+    let masked: Vec<(usize, f64)> = template
+        .enumerate_pixels()
+        .filter(|(x, y, _)| mask.get_pixel(*x, *y)[0] != 0)
+        .map(|(x, y, p)| ((y * image_width + x) as usize, p[0] as f64))
+        .collect();
+    let n = masked.len() as f64;
+
+    let centre = matches!(
+        method,
+        CorrelationCoefficient | CorrelationCoefficientNormalized
+    );
+    let template_mean = if centre && n > 0.0 {
+        masked.iter().map(|(_, t)| t).sum::<f64>() / n
+    } else {
+        0.0
+    };
+    let template_squared_sum: f64 = masked
+        .iter()
+        .map(|(_, t)| (t - template_mean).powi(2))
+        .sum();
+
+    let data = image.as_raw();
+    let width = image_width - template_width + 1;
+    let height = image_height - template_height + 1;
+
+    Image::from_fn(width, height, |x, y| {
+        let start = (y * image_width + x) as usize;
+
+        let mut sum = 0f64;
+        let mut squared_sum = 0f64;
+        let mut correlation = 0f64;
+        let mut squared_error = 0f64;
+        for (offset, t) in &masked {
+            let i = data[start + offset] as f64;
+            sum += i;
+            squared_sum += i * i;
+            correlation += i * (t - template_mean);
+            squared_error += (i - t) * (i - t);
+        }
+
+        let normalize = |score: f64, norm: f64| if norm > 0.0 { score / norm } else { score };
+        let score = match method {
+            SumOfSquaredErrors => squared_error,
+            SumOfSquaredErrorsNormalized => {
+                normalize(squared_error, (squared_sum * template_squared_sum).sqrt())
+            }
+            CrossCorrelation | CorrelationCoefficient => correlation,
+            CrossCorrelationNormalized => {
+                normalize(correlation, (squared_sum * template_squared_sum).sqrt())
+            }
+            CorrelationCoefficientNormalized => {
+                let region_variance = if n > 0.0 {
+                    squared_sum - sum * sum / n
+                } else {
+                    0.0
+                };
+                let norm = (region_variance * template_squared_sum).sqrt();
+                if norm > 0.0 {
+                    (correlation / norm).clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                }
+            }
+        };
+        Luma([score as f32])
+    })
+}
+
+// Returns true if computing template match scores via the FFT is expected to be faster
+// than computing them directly.
+fn use_fft(image_dimensions: (u32, u32), template_dimensions: (u32, u32)) -> bool {
+    let (image_width, image_height) = image_dimensions;
+    let (template_width, template_height) = template_dimensions;
+    let output_area =
+        (image_width - template_width + 1) as f64 * (image_height - template_height + 1) as f64;
+    let direct_cost = output_area * template_width as f64 * template_height as f64;
+    // Three 2d FFTs, each of which costs roughly area * log2(area) complex multiply-adds
+    let image_area = image_width as f64 * image_height as f64;
+    let fft_cost = 12.0 * image_area * image_area.log2().max(1.0);
+    direct_cost > fft_cost
+}
+
+// Returns true if the cross-correlation between the image and a template of the given size
+// should be computed on the GPU. The GPU computes in f32, which is not precise enough for the
+// sum of squared errors methods: they subtract the correlation from sums of squares of similar
+// magnitude, so an exact match would no longer score close to zero.
+fn use_gpu(method: MatchTemplateMethod, template_dimensions: (u32, u32)) -> bool {
+    use MatchTemplateMethod::*;
+
+    let (template_width, template_height) = template_dimensions;
+    !matches!(method, SumOfSquaredErrors | SumOfSquaredErrorsNormalized)
+        && template_width as usize * template_height as usize >= gpu::MIN_KERNEL_LEN
+        && gpu::is_available()
+}
+
+// Computes match scores from the cross-correlation of the image and template, using integral
+// images to compute sums over image regions.
+fn match_template_by_correlation(
+    image: &GrayImage,
+    template: &GrayImage,
+    method: MatchTemplateMethod,
+) -> Image<Luma<f32>> {
+    use MatchTemplateMethod::*;
+
+    let (template_width, template_height) = template.dimensions();
+    let n = (template_width * template_height) as f64;
+
+    let centre = matches!(
+        method,
+        CorrelationCoefficient | CorrelationCoefficientNormalized
+    );
+    let template_mean = if centre {
+        template.iter().map(|p| *p as f64).sum::<f64>() / n
+    } else {
+        0.0
+    };
+    let template_values: Vec<f64> = template.iter().map(|p| *p as f64 - template_mean).collect();
+    let template_squared_sum: f64 = template_values.iter().map(|t| t * t).sum();
+
+    let correlation = if use_gpu(method, template.dimensions()) {
+        cross_correlation_gpu(image, &template_values, template_width)
+    } else {
+        None
+    };
+    let correlation = correlation
+        .unwrap_or_else(|| cross_correlation_fft(image, &template_values, template_width));
+    let integral = if method == CorrelationCoefficientNormalized {
+        Some(integral_image::<_, u64>(image))
+    } else {
+        None
+    };
+    let squared_integral = integral_squared_image::<_, u64>(image);
+
+    let width = image.width() - template_width + 1;
+    let height = image.height() - template_height + 1;
+    Image::from_fn(width, height, |x, y| {
+        let (right, bottom) = (x + template_width - 1, y + template_height - 1);
+        let c = correlation[(y * image.width() + x) as usize];
+        let image_squared_sum = sum_image_pixels(&squared_integral, x, y, right, bottom)[0] as f64;
+        let normalize = |score: f64, norm: f64| if norm > 0.0 { score / norm } else { score };
+
+        let score = match method {
+            SumOfSquaredErrors => (image_squared_sum - 2.0 * c + template_squared_sum).max(0.0),
+            SumOfSquaredErrorsNormalized => normalize(
+                (image_squared_sum - 2.0 * c + template_squared_sum).max(0.0),
+                (image_squared_sum * template_squared_sum).sqrt(),
+            ),
+            CrossCorrelation | CorrelationCoefficient => c,
+            CrossCorrelationNormalized => {
+                normalize(c, (image_squared_sum * template_squared_sum).sqrt())
+            }
+            CorrelationCoefficientNormalized => {
+                let sum =
+                    sum_image_pixels(integral.as_ref().unwrap(), x, y, right, bottom)[0] as f64;
+                let region_variance = image_squared_sum - sum * sum / n;
+                let norm = (region_variance * template_squared_sum).sqrt();
+                if norm > 0.0 {
+                    (c / norm).clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                }
+            }
+        };
+        Luma([score as f32])
+    })
+}
+
+// Returns the cross-correlation of image with a template with the given row-major values,
+// using the FFT. The entry at index y * image.width() + x is the sum over all template
+// positions (dx, dy) of image(x + dx, y + dy) * template(dx, dy). Only entries for which the
+// template lies entirely within the image are meaningful.
+fn cross_correlation_fft(image: &GrayImage, template: &[f64], template_width: u32) -> Vec<f64> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let template_width = template_width as usize;
+    let zero = Complex::new(0.0, 0.0);
+
+    let mut image_spectrum: Vec<Complex<f64>> =
+        image.iter().map(|p| Complex::new(*p as f64, 0.0)).collect();
+    let mut template_spectrum = vec![zero; width * height];
+    for (dy, row) in template.chunks(template_width).enumerate() {
+        for (dx, t) in row.iter().enumerate() {
+            template_spectrum[dy * width + dx] = Complex::new(*t, 0.0);
+        }
+    }
+
+    let mut planner = FftPlanner::new();
+    fft_2d(
+        &mut planner,
+        &mut image_spectrum,
+        width,
+        height,
+        FftDirection::Forward,
+    );
+    fft_2d(
+        &mut planner,
+        &mut template_spectrum,
+        width,
+        height,
+        FftDirection::Forward,
+    );
+
+    // Correlation is multiplication by the complex conjugate in the frequency domain.
+    // As the template is zero-padded to the size of the image, the circular correlation
+    // computed here is equal to the linear correlation wherever the template fits.
+    for (i, t) in image_spectrum.iter_mut().zip(&template_spectrum) {
+        *i *= t.conj();
+    }
+    fft_2d(
+        &mut planner,
+        &mut image_spectrum,
+        width,
+        height,
+        FftDirection::Inverse,
+    );
+
+    let scale = 1.0 / (width * height) as f64;
+    image_spectrum.iter().map(|c| c.re * scale).collect()
+}
+
+// Returns the cross-correlation of image with a template with the given row-major values,
+// computed on the GPU, in the same layout as cross_correlation_fft. Returns None if the gpu
+// feature is not enabled, no GPU is available or the template is too small for the GPU to
+// be worth using.
+fn cross_correlation_gpu(
+    image: &GrayImage,
+    template: &[f64],
+    template_width: u32,
+) -> Option<Vec<f64>> {
+    let (width, height) = image.dimensions();
+    let template_height = (template.len() / template_width as usize) as u32;
+    let pixels: Vec<f32> = image.iter().map(|p| *p as f32).collect();
+    let template: Vec<f32> = template.iter().map(|t| *t as f32).collect();
+    let sums = gpu::template_correlation_f32(
+        &pixels,
+        width,
+        height,
+        &template,
+        template_width,
+        template_height,
+    )?;
+
+    let mut correlation = vec![0.0; width as usize * height as usize];
+    let sums_width = (width - template_width + 1) as usize;
+    for (row, sums) in correlation
+        .chunks_mut(width as usize)
+        .zip(sums.chunks(sums_width))
+    {
+        for (c, sum) in row.iter_mut().zip(sums) {
+            *c = *sum as f64;
+        }
+    }
+    Some(correlation)
+}
+
+// Applies an unnormalized 2d FFT in place to row-major data with the given dimensions.
+fn fft_2d(
+    planner: &mut FftPlanner<f64>,
+    data: &mut [Complex<f64>],
+    width: usize,
+    height: usize,
+    direction: FftDirection,
+) {
+    // Transforms all rows, as process operates on consecutive chunks of the FFT length
+    planner.plan_fft(width, direction).process(data);
+
+    let mut transposed = vec![Complex::new(0.0, 0.0); width * height];
+    for y in 0..height {
+        for x in 0..width {
+            transposed[x * height + y] = data[y * width + x];
+        }
+    }
+    planner.plan_fft(height, direction).process(&mut transposed);
+    for y in 0..height {
+        for x in 0..width {
+            data[y * width + x] = transposed[x * height + y];
+        }
+    }
+}
+
 fn sum_squares(template: &GrayImage) -> f32 {
     template.iter().map(|p| *p as f32 * *p as f32).sum()
 }
@@ -129,6 +619,263 @@ fn normalization_term(
     (image_sum * template_squared_sum).sqrt()
 }
 
+/// Scores the match between a template edge map and an image edge map at each translation
+/// of the template using chamfer matching.
+///
+/// Pixels with non-zero intensity in `image_edges` and `template_edges` are edge pixels,
+/// as in the output of [`canny`](../edges/fn.canny.html). The score at each position is the
+/// mean Euclidean distance from the edge pixels of the template, when placed with its top
+/// left corner at that position, to their nearest edge pixels in the image. Distances are
+/// found using the image's [distance transform](../distance_transform/index.html), so
+/// edge pixels do not need to line up exactly. This makes chamfer matching robust to small
+/// deformations and to differences in the intensities either side of each edge.
+///
+/// Smaller values are better. If the template has no edge pixels then all scores are zero,
+/// and if the image has no edge pixels then all scores are infinite.
+///
+/// The returned image has dimensions `image_edges.width() - template_edges.width() + 1` by
+/// `image_edges.height() - template_edges.height() + 1`.
+///
+/// # Panics
+///
+/// If either dimension of `template_edges` is greater than the corresponding dimension
+/// of `image_edges`.
+pub fn match_template_chamfer(
+    image_edges: &GrayImage,
+    template_edges: &GrayImage,
+) -> Image<Luma<f32>> {
+    try_match_template_chamfer(image_edges, template_edges).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Scores the match between a template edge map and an image edge map at each translation
+/// of the template using chamfer matching, returning an error if `template_edges` is larger
+/// than `image_edges`.
+///
+/// See the [`match_template_chamfer`](fn.match_template_chamfer.html) documentation for more
+/// information.
+pub fn try_match_template_chamfer(
+    image_edges: &GrayImage,
+    template_edges: &GrayImage,
+) -> Result<Image<Luma<f32>>, Error> {
+    check_template_fits(image_edges, template_edges)?;
+    Ok(match_template_chamfer_unchecked(
+        image_edges,
+        template_edges,
+    ))
+}
+
+fn match_template_chamfer_unchecked(
+    image_edges: &GrayImage,
+    template_edges: &GrayImage,
+) -> Image<Luma<f32>> {
+    let (image_width, image_height) = image_edges.dimensions();
+    let (template_width, template_height) = template_edges.dimensions();
+
+    let distances: Vec<f32> = euclidean_squared_distance_transform(image_edges)
+        .iter()
+        .map(|d| d.sqrt() as f32)
+        .collect();
+
+    let offsets: Vec<usize> = template_edges
+        .enumerate_pixels()
+        .filter(|(_, _, p)| p[0] != 0)
+        .map(|(x, y, _)| (y * image_width + x) as usize)
+        .collect();
+
+    Image::from_fn(
+        image_width - template_width + 1,
+        image_height - template_height + 1,
+        |x, y| {
+            if offsets.is_empty() {
+                return Luma([0.0]);
+            }
+            let start = (y * image_width + x) as usize;
+            let sum: f32 = offsets.iter().map(|o| distances[start + o]).sum();
+            Luma([sum / offsets.len() as f32])
+        },
+    )
+}
+
+/// A candidate position of a template found by chamfer matching.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChamferMatch {
+    /// x-coordinate of the top left corner of the template.
+    pub x: u32,
+    /// y-coordinate of the top left corner of the template.
+    pub y: u32,
+    /// The mean distance from template edge pixels to the nearest image edge pixels.
+    /// Smaller values are better.
+    pub score: f32,
+}
+
+// Wraps a match so that better matches have higher scores, for use with local_maxima.
+#[derive(Copy, Clone)]
+struct NegatedScore(ChamferMatch);
+
+impl Position for NegatedScore {
+    fn x(&self) -> u32 {
+        self.0.x
+    }
+    fn y(&self) -> u32 {
+        self.0.y
+    }
+}
+
+impl Score for NegatedScore {
+    fn score(&self) -> f32 {
+        -self.0.score
+    }
+}
+
+/// Finds the positions at which a template edge map best matches an image edge map,
+/// using [`match_template_chamfer`](fn.match_template_chamfer.html).
+///
+/// Returns all positions whose score is at most `max_score` and is the smallest score in the
+/// `(2 * radius + 1)` square block centred on them, in order of increasing score. Ties are
+/// resolved lexicographically.
+///
+/// # Panics
+///
+/// If either dimension of `template_edges` is greater than the corresponding dimension
+/// of `image_edges`.
+pub fn find_chamfer_matches(
+    image_edges: &GrayImage,
+    template_edges: &GrayImage,
+    max_score: f32,
+    radius: u32,
+) -> Vec<ChamferMatch> {
+    let scores = match_template_chamfer(image_edges, template_edges);
+    let candidates: Vec<NegatedScore> = scores
+        .enumerate_pixels()
+        .filter(|(_, _, p)| p[0] <= max_score)
+        .map(|(x, y, p)| NegatedScore(ChamferMatch { x, y, score: p[0] }))
+        .collect();
+
+    let mut matches: Vec<ChamferMatch> = local_maxima(&candidates, radius)
+        .into_iter()
+        .map(|m| m.0)
+        .collect();
+    matches.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap());
+    matches
+}
+
+/// A local maximum of a score map, located to sub-pixel accuracy.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Peak {
+    /// The refined x-coordinate of the peak.
+    pub x: f32,
+    /// The refined y-coordinate of the peak.
+    pub y: f32,
+    /// The interpolated score at the refined position.
+    pub score: f32,
+}
+
+// A pixel of a score map, for use with local_maxima.
+#[derive(Copy, Clone)]
+struct ScoredPixel {
+    x: u32,
+    y: u32,
+    score: f32,
+}
+
+impl Position for ScoredPixel {
+    fn x(&self) -> u32 {
+        self.x
+    }
+    fn y(&self) -> u32 {
+        self.y
+    }
+}
+
+impl Score for ScoredPixel {
+    fn score(&self) -> f32 {
+        self.score
+    }
+}
+
+/// Finds the local maxima of a score map, such as the output of
+/// [`match_template`](fn.match_template.html), and refines their positions to sub-pixel
+/// accuracy.
+///
+/// A pixel is a peak if its score is at least `threshold` and is the largest score in the
+/// `(2 * radius + 1)` square block centred on it, with ties resolved lexicographically. The
+/// position of each peak is refined by fitting a parabola through the peak and its horizontal
+/// neighbours, and another through the peak and its vertical neighbours, and taking the
+/// positions of their maxima. Offsets are limited to half a pixel in each direction, and no
+/// refinement is applied along an axis for peaks on the border of the score map.
+///
+/// Peaks are returned in order of decreasing score. To find minima of a score map for which
+/// smaller values are better, such as the output of `match_template` using
+/// `SumOfSquaredErrors`, negate the score map first.
+///
+/// # Examples
+/// ```
+/// # extern crate image;
+/// # #[macro_use]
+/// # extern crate imageproc;
+/// # fn main() {
+/// use imageproc::template_matching::find_peaks_subpixel;
+///
+/// // A peak lying a quarter of a pixel to the right of (1, 1)
+/// let scores = gray_image!(type: f32,
+///     0.0, 0.0, 0.0;
+///     0.0, 0.75, 0.5;
+///     0.0, 0.0, 0.0);
+///
+/// let peaks = find_peaks_subpixel(&scores, 0.7, 1);
+/// assert_eq!(peaks.len(), 1);
+/// assert_eq!((peaks[0].x, peaks[0].y), (1.25, 1.0));
+/// # }
+/// ```
+pub fn find_peaks_subpixel(scores: &Image<Luma<f32>>, threshold: f32, radius: u32) -> Vec<Peak> {
+    let candidates: Vec<ScoredPixel> = scores
+        .enumerate_pixels()
+        .filter(|(_, _, p)| p[0] >= threshold)
+        .map(|(x, y, p)| ScoredPixel { x, y, score: p[0] })
+        .collect();
+
+    let (width, height) = scores.dimensions();
+    let score = |x: u32, y: u32| scores.get_pixel(x, y)[0];
+
+    let mut peaks: Vec<Peak> = local_maxima(&candidates, radius)
+        .into_iter()
+        .map(|p| {
+            let (x, y, c) = (p.x, p.y, p.score);
+            let (dx, x_gain) = if x > 0 && x + 1 < width {
+                quadratic_peak_offset(score(x - 1, y), c, score(x + 1, y))
+            } else {
+                (0.0, 0.0)
+            };
+            let (dy, y_gain) = if y > 0 && y + 1 < height {
+                quadratic_peak_offset(score(x, y - 1), c, score(x, y + 1))
+            } else {
+                (0.0, 0.0)
+            };
+            Peak {
+                x: x as f32 + dx,
+                y: y as f32 + dy,
+                score: c + x_gain + y_gain,
+            }
+        })
+        .collect();
+
+    peaks.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    peaks
+}
+
+// Returns the offset of the maximum of the parabola through (-1, left), (0, centre) and
+// (1, right), clamped to [-0.5, 0.5], and the increase in value at the offset relative
+// to centre.
+fn quadratic_peak_offset(left: f32, centre: f32, right: f32) -> (f32, f32) {
+    let curvature = left - 2.0 * centre + right;
+    if curvature >= 0.0 {
+        return (0.0, 0.0);
+    }
+    let offset = (0.5 * (left - right) / curvature).clamp(-0.5, 0.5);
+    let gain = 0.25 * (right - left) * offset;
+    (offset, gain)
+}
+
 /// The largest and smallest values in an image,
 /// together with their locations.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -145,14 +892,28 @@ pub struct Extremes<T> {
 
 /// Finds the largest and smallest values in an image and their locations.
 /// If there are multiple such values then the lexicographically smallest is returned.
+///
+/// # Panics
+///
+/// If `image` is empty.
 pub fn find_extremes<T>(image: &Image<Luma<T>>) -> Extremes<T>
 where
     T: Primitive + 'static,
 {
-    assert!(
-        image.width() > 0 && image.height() > 0,
-        "image must be non-empty"
-    );
+    try_find_extremes(image).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Finds the largest and smallest values in an image and their locations, returning
+/// an error if `image` is empty.
+///
+/// See the [`find_extremes`](fn.find_extremes.html) documentation for more information.
+pub fn try_find_extremes<T>(image: &Image<Luma<T>>) -> Result<Extremes<T>, Error>
+where
+    T: Primitive + 'static,
+{
+    if image.width() == 0 || image.height() == 0 {
+        return Err(Error::EmptyImage);
+    }
 
     let mut min_value = image.get_pixel(0, 0)[0];
     let mut max_value = image.get_pixel(0, 0)[0];
@@ -171,19 +932,19 @@ where
         }
     }
 
-    Extremes {
+    Ok(Extremes {
         max_value,
         min_value,
         max_value_location,
         min_value_location,
-    }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::utils::gray_bench_image;
-    use image::GrayImage;
+    use image::{GrayImage, Luma};
     use test::{black_box, Bencher};
 
     #[test]
@@ -324,6 +1085,414 @@ mod tests {
         assert_pixels_eq!(actual, expected);
     }
 
+    #[test]
+    fn match_template_correlation_coefficient() {
+        let image = gray_image!(
+            1, 4, 2;
+            2, 1, 3;
+            3, 3, 4
+        );
+        let template = gray_image!(
+            1, 2;
+            3, 4
+        );
+
+        let actual = match_template(
+            &image,
+            &template,
+            MatchTemplateMethod::CorrelationCoefficient,
+        );
+
+        // Correlation of each region with the centred template [-1.5, -0.5, 0.5, 1.5]
+        let expected = gray_image!(type: f32,
+            -1.0, -2.0;
+            2.5, 4.5
+        );
+
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn match_template_correlation_coefficient_normalized() {
+        let image = gray_image!(
+            1, 4, 2;
+            2, 1, 3;
+            3, 3, 4
+        );
+        let template = gray_image!(
+            1, 2;
+            3, 4
+        );
+
+        let actual = match_template(
+            &image,
+            &template,
+            MatchTemplateMethod::CorrelationCoefficientNormalized,
+        );
+
+        // Pearson correlation coefficients of each region with [1, 2, 3, 4]
+        let expected = gray_image!(type: f32,
+            -0.18257419, -0.4;
+            0.6741999, 0.9233805
+        );
+
+        assert_pixels_eq_within!(actual, expected, 1e-5);
+    }
+
+    #[test]
+    fn match_template_correlation_coefficient_normalized_is_lighting_invariant() {
+        let image = GrayImage::from_fn(30, 20, |x, y| {
+            Luma([((7 * x * x + 13 * y + 3 * x * y) % 251) as u8])
+        });
+        let template = GrayImage::from_fn(8, 6, |x, y| {
+            // A darker, lower contrast copy of part of the image
+            let p = image.get_pixel(x + 12, y + 7)[0];
+            Luma([10 + p / 3])
+        });
+
+        let scores = match_template(
+            &image,
+            &template,
+            MatchTemplateMethod::CorrelationCoefficientNormalized,
+        );
+        let extremes = find_extremes(&scores);
+        assert_eq!(extremes.max_value_location, (12, 7));
+        assert!(extremes.max_value > 0.99);
+    }
+
+    #[test]
+    fn match_template_correlation_coefficient_normalized_handles_constant_regions() {
+        let image = GrayImage::from_pixel(4, 4, Luma([7u8]));
+        let template = gray_image!(
+            1, 2;
+            3, 4
+        );
+        let actual = match_template(
+            &image,
+            &template,
+            MatchTemplateMethod::CorrelationCoefficientNormalized,
+        );
+        assert_pixels_eq!(actual, Image::from_pixel(3, 3, Luma([0f32])));
+    }
+
+    #[test]
+    fn match_template_fft_matches_direct_computation() {
+        let image = GrayImage::from_fn(23, 17, |x, y| {
+            Luma([((7 * x * x + 13 * y + 3 * x * y) % 251) as u8])
+        });
+        let template = GrayImage::from_fn(6, 5, |x, y| Luma([((x * 31 + y * 17) % 200) as u8]));
+        assert!(!use_fft(image.dimensions(), template.dimensions()));
+
+        use MatchTemplateMethod::*;
+        for &method in &[
+            SumOfSquaredErrors,
+            SumOfSquaredErrorsNormalized,
+            CrossCorrelation,
+            CrossCorrelationNormalized,
+            CorrelationCoefficient,
+            CorrelationCoefficientNormalized,
+        ] {
+            let direct = match_template(&image, &template, method);
+            let fft = match_template_by_correlation(&image, &template, method);
+            assert_eq!(direct.dimensions(), fft.dimensions());
+            for (d, f) in direct.iter().zip(fft.iter()) {
+                let tolerance = 1e-4 * d.abs().max(1.0);
+                assert!((d - f).abs() < tolerance, "{:?}: {} != {}", method, d, f);
+            }
+        }
+    }
+
+    #[test]
+    fn match_template_large_template_uses_fft() {
+        let image = GrayImage::from_fn(100, 90, |x, y| {
+            Luma([((7 * x * x + 13 * y + 3 * x * y) % 251) as u8])
+        });
+        let template = GrayImage::from_fn(40, 40, |x, y| *image.get_pixel(x + 31, y + 22));
+        assert!(use_fft(image.dimensions(), template.dimensions()));
+
+        let scores = match_template(
+            &image,
+            &template,
+            MatchTemplateMethod::CorrelationCoefficientNormalized,
+        );
+        let extremes = find_extremes(&scores);
+        assert_eq!(extremes.max_value_location, (31, 22));
+        assert!((extremes.max_value - 1.0).abs() < 1e-4);
+
+        let scores = match_template(&image, &template, MatchTemplateMethod::SumOfSquaredErrors);
+        assert_eq!(find_extremes(&scores).min_value_location, (31, 22));
+    }
+
+    #[test]
+    fn match_template_with_mask_full_mask_matches_unmasked() {
+        let image = GrayImage::from_fn(13, 11, |x, y| {
+            Luma([((7 * x * x + 13 * y + 3 * x * y) % 251) as u8])
+        });
+        let template = GrayImage::from_fn(4, 3, |x, y| Luma([((x * 31 + y * 17) % 200) as u8]));
+        let mask = GrayImage::from_pixel(4, 3, Luma([1u8]));
+
+        use MatchTemplateMethod::*;
+        for &method in &[
+            SumOfSquaredErrors,
+            SumOfSquaredErrorsNormalized,
+            CrossCorrelation,
+            CrossCorrelationNormalized,
+            CorrelationCoefficient,
+            CorrelationCoefficientNormalized,
+        ] {
+            let unmasked = match_template(&image, &template, method);
+            let masked = match_template_with_mask(&image, &template, &mask, method);
+            for (u, m) in unmasked.iter().zip(masked.iter()) {
+                let tolerance = 1e-4 * u.abs().max(1.0);
+                assert!((u - m).abs() < tolerance, "{:?}: {} != {}", method, u, m);
+            }
+        }
+    }
+
+    #[test]
+    fn match_template_with_mask_sum_of_squared_errors() {
+        let image = gray_image!(
+            1, 4, 2;
+            2, 1, 3;
+            3, 3, 4
+        );
+        let template = gray_image!(
+            1, 2;
+            3, 4
+        );
+        let mask = gray_image!(
+            1, 0;
+            0, 1
+        );
+
+        let actual = match_template_with_mask(
+            &image,
+            &template,
+            &mask,
+            MatchTemplateMethod::SumOfSquaredErrors,
+        );
+        let expected = gray_image!(type: f32,
+            9.0, 10.0;
+            2.0, 0.0
+        );
+
+        assert_pixels_eq!(actual, expected);
+    }
+
+    #[test]
+    fn match_template_with_mask_ignores_background() {
+        // A bright cross on a background that differs between template and image
+        let cross = |x: u32, y: u32| x == 2 || y == 2;
+        let template = GrayImage::from_fn(5, 5, |x, y| Luma([if cross(x, y) { 200 } else { 0 }]));
+        let mask = GrayImage::from_fn(5, 5, |x, y| Luma([if cross(x, y) { 255 } else { 0 }]));
+        let mut image = GrayImage::from_fn(20, 20, |x, y| Luma([((x * 13 + y * 7) % 50) as u8]));
+        for y in 0..5 {
+            for x in 0..5 {
+                if cross(x, y) {
+                    image.put_pixel(x + 9, y + 4, Luma([200]));
+                }
+            }
+        }
+
+        let scores = match_template_with_mask(
+            &image,
+            &template,
+            &mask,
+            MatchTemplateMethod::SumOfSquaredErrors,
+        );
+        let extremes = find_extremes(&scores);
+        assert_eq!(extremes.min_value_location, (9, 4));
+        assert_eq!(extremes.min_value, 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn match_template_with_mask_panics_if_mask_size_differs_from_template() {
+        let _ = match_template_with_mask(
+            &GrayImage::new(5, 5),
+            &GrayImage::new(2, 2),
+            &GrayImage::new(3, 2),
+            MatchTemplateMethod::SumOfSquaredErrors,
+        );
+    }
+
+    #[test]
+    fn test_try_match_template() {
+        let image = gray_bench_image(8, 6);
+        let template = GrayImage::from_fn(3, 2, |x, y| *image.get_pixel(x + 2, y + 1));
+        let method = MatchTemplateMethod::SumOfSquaredErrors;
+        assert_eq!(
+            try_match_template(&image, &template, method),
+            Ok(match_template(&image, &template, method))
+        );
+        assert_eq!(
+            try_match_template(&template, &image, method),
+            Err(Error::KernelTooLarge {
+                kernel: (8, 6),
+                image: (3, 2)
+            })
+        );
+        assert_eq!(
+            try_match_template(&GrayImage::new(5, 5), &GrayImage::new(4, 6), method),
+            Err(Error::KernelTooLarge {
+                kernel: (4, 6),
+                image: (5, 5)
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_match_template_with_mask() {
+        let image = gray_bench_image(8, 6);
+        let template = GrayImage::new(2, 2);
+        let method = MatchTemplateMethod::CrossCorrelation;
+        assert!(try_match_template_with_mask(&image, &template, &template, method).is_ok());
+        assert_eq!(
+            try_match_template_with_mask(&image, &template, &GrayImage::new(3, 2), method),
+            Err(Error::DimensionMismatch {
+                expected: (2, 2),
+                actual: (3, 2)
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_match_template_chamfer() {
+        let image = square_outline(10, 8, 2, 1, 5);
+        let template = square_outline(5, 5, 0, 0, 5);
+        assert_eq!(
+            try_match_template_chamfer(&image, &template),
+            Ok(match_template_chamfer(&image, &template))
+        );
+        assert_eq!(
+            try_match_template_chamfer(&template, &image),
+            Err(Error::KernelTooLarge {
+                kernel: (10, 8),
+                image: (5, 5)
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_find_extremes() {
+        let image = gray_image!(type: u16, 3, 9; 1, 4);
+        assert_eq!(try_find_extremes(&image), Ok(find_extremes(&image)));
+        assert_eq!(
+            try_find_extremes(&Image::<Luma<u16>>::new(0, 3)),
+            Err(Error::EmptyImage)
+        );
+    }
+
+    fn square_outline(width: u32, height: u32, left: u32, top: u32, size: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| {
+            let inside = x >= left && x < left + size && y >= top && y < top + size;
+            let border = x == left || x == left + size - 1 || y == top || y == top + size - 1;
+            Luma([if inside && border { 255 } else { 0 }])
+        })
+    }
+
+    #[test]
+    fn match_template_chamfer_scores_mean_distance() {
+        let image = gray_image!(
+            0, 0, 0, 0;
+            0, 1, 0, 0;
+            0, 0, 0, 0
+        );
+        let template = gray_image!(
+            1, 0;
+            0, 1
+        );
+
+        // Mean distance from the template pixels to the image edge at (1, 1)
+        let sqrt2 = 2f32.sqrt();
+        let expected = gray_image!(type: f32,
+            sqrt2 / 2.0, 1.0, (sqrt2 + 2.0) / 2.0;
+            1.0, sqrt2 / 2.0, (1.0 + 5f32.sqrt()) / 2.0
+        );
+
+        let actual = match_template_chamfer(&image, &template);
+        assert_pixels_eq_within!(actual, expected, 1e-5);
+    }
+
+    #[test]
+    fn sum_of_squared_errors_is_not_computed_on_the_gpu() {
+        use MatchTemplateMethod::*;
+        for method in [SumOfSquaredErrors, SumOfSquaredErrorsNormalized] {
+            assert!(!use_gpu(method, (64, 64)));
+        }
+        assert!(!use_gpu(CrossCorrelation, (2, 2)));
+    }
+
+    #[test]
+    fn match_template_chamfer_handles_template_without_edges() {
+        let scores = match_template_chamfer(&GrayImage::new(4, 4), &GrayImage::new(2, 2));
+        assert_pixels_eq!(scores, Image::from_pixel(3, 3, Luma([0f32])));
+    }
+
+    #[test]
+    fn find_chamfer_matches_tolerates_deformation() {
+        // The image contains a slightly smaller copy of the template's square outline,
+        // and a distant unrelated edge
+        let template = square_outline(12, 12, 0, 0, 12);
+        let mut image = square_outline(40, 30, 21, 9, 11);
+        for y in 2..8 {
+            image.put_pixel(3, y, Luma([255]));
+        }
+
+        let matches = find_chamfer_matches(&image, &template, 1.0, 3);
+        assert_eq!(matches.len(), 1);
+        let best = matches[0];
+        assert!(best.x >= 20 && best.x <= 21, "x = {}", best.x);
+        assert!(best.y >= 8 && best.y <= 9, "y = {}", best.y);
+        assert!(best.score < 1.0);
+    }
+
+    #[test]
+    fn find_peaks_subpixel_recovers_parabola_maximum() {
+        // Samples of a paraboloid with maximum 10 at (3.3, 2.8)
+        let scores = Image::from_fn(7, 6, |x, y| {
+            let (dx, dy) = (x as f32 - 3.3, y as f32 - 2.8);
+            Luma([10.0 - dx * dx - 2.0 * dy * dy])
+        });
+
+        let peaks = find_peaks_subpixel(&scores, 0.0, 2);
+        assert_eq!(peaks.len(), 1);
+        assert!((peaks[0].x - 3.3).abs() < 1e-4);
+        assert!((peaks[0].y - 2.8).abs() < 1e-4);
+        assert!((peaks[0].score - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn find_peaks_subpixel_applies_threshold_and_sorts_by_score() {
+        let scores = gray_image!(type: f32,
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0;
+            0.0, 5.0, 0.0, 0.0, 9.0, 0.0;
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0;
+            0.0, 0.0, 2.0, 0.0, 0.0, 0.0
+        );
+
+        let peaks = find_peaks_subpixel(&scores, 3.0, 1);
+        let positions: Vec<(f32, f32, f32)> = peaks.iter().map(|p| (p.x, p.y, p.score)).collect();
+        assert_eq!(positions, vec![(4.0, 1.0, 9.0), (1.0, 1.0, 5.0)]);
+    }
+
+    #[test]
+    fn find_peaks_subpixel_does_not_refine_border_peaks() {
+        let scores = gray_image!(type: f32,
+            9.0, 4.0, 0.0;
+            8.0, 0.0, 0.0
+        );
+        let peaks = find_peaks_subpixel(&scores, 1.0, 1);
+        assert_eq!(
+            peaks,
+            vec![Peak {
+                x: 0.0,
+                y: 0.0,
+                score: 9.0
+            }]
+        );
+    }
+
     macro_rules! bench_match_template {
         ($name:ident, image_size: $s:expr, template_size: $t:expr, method: $m:expr) => {
             #[bench]
@@ -375,6 +1544,18 @@ mod tests {
         template_size: 16,
         method: MatchTemplateMethod::SumOfSquaredErrorsNormalized);
 
+    bench_match_template!(
+        bench_match_template_s100_t16_ccoeff_norm,
+        image_size: 100,
+        template_size: 16,
+        method: MatchTemplateMethod::CorrelationCoefficientNormalized);
+
+    bench_match_template!(
+        bench_match_template_s200_t64_sse,
+        image_size: 200,
+        template_size: 64,
+        method: MatchTemplateMethod::SumOfSquaredErrors);
+
     #[test]
     fn test_find_extremes() {
         let image = gray_image!(
@@ -0,0 +1,694 @@
+//! Functions for processing large images in overlapping tiles.
+//!
+//! Many operations only need a small neighbourhood of each input pixel to compute the
+//! corresponding output pixel. Such an operation can be applied to an image one tile at
+//! a time, by reading each tile together with a border of surrounding pixels, its *halo*,
+//! and discarding the halo from the result. The memory required is then bounded by the
+//! tile size rather than the image size, e.g. when the input is read from disk one region
+//! at a time using [`process_tiles`](fn.process_tiles.html).
+//!
+//! Halos are clipped at the image boundary, so operations that pad by continuity (as the
+//! filters in this crate do) produce exactly the same results as when applied to the
+//! whole image, provided that the halo is at least the radius of the operation's kernel.
+//!
+//! This module provides operations for several of the filters in this crate, e.g.
+//! [`GaussianBlur`](struct.GaussianBlur.html), whose halos are chosen to match their
+//! kernels. Other functions can be used via [`TileFn`](struct.TileFn.html).
+
+use crate::definitions::{Clamp, HasBlack, HasWhite, Image, MaybeSend, MaybeSync};
+use crate::distance_transform::Norm;
+use crate::filter::{box_filter, filter3x3, gaussian_blur_f32, median_filter};
+use crate::map::{ChannelMap, WithChannel};
+use crate::morphology::{close, dilate, erode, open};
+use crate::rect::Rect;
+use conv::ValueInto;
+use image::{Luma, Pixel, Primitive};
+use num::Num;
+use std::marker::PhantomData;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// An operation that can be applied to an image one tile at a time.
+pub trait TileOperation<P: Pixel> {
+    /// The type of the output pixels.
+    type Output: Pixel;
+
+    /// The number of pixels beyond each edge of an output region that must be read
+    /// to compute the output in that region, e.g. the radius of a filter kernel.
+    fn halo(&self) -> u32;
+
+    /// Applies the operation to a tile. The output must have the same dimensions as `tile`.
+    fn apply(&self, tile: &Image<P>) -> Image<Self::Output>;
+}
+
+/// A [`TileOperation`](trait.TileOperation.html) defined by a function and its halo.
+///
+/// # Examples
+/// ```
+/// use imageproc::filter::{gaussian_blur_f32, sharpen3x3};
+/// use imageproc::tiling::{process_tiled, TileFn};
+/// use imageproc::utils::gray_bench_image;
+///
+/// let image = gray_bench_image(100, 80);
+/// let sigma = 1.5f32;
+///
+/// // gaussian_blur_f32 uses a kernel of radius ceil(2 * sigma), and sharpen3x3 of radius 1
+/// let halo = (2.0 * sigma).ceil() as u32 + 1;
+/// let op = TileFn::new(halo, |tile| sharpen3x3(&gaussian_blur_f32(tile, sigma)));
+///
+/// assert_eq!(
+///     process_tiled(&image, 32, 32, &op),
+///     sharpen3x3(&gaussian_blur_f32(&image, sigma))
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TileFn<F> {
+    halo: u32,
+    f: F,
+}
+
+impl<F> TileFn<F> {
+    /// Creates an operation which applies `f` to tiles with the given halo.
+    pub fn new<P, Q>(halo: u32, f: F) -> TileFn<F>
+    where
+        P: Pixel,
+        Q: Pixel,
+        F: Fn(&Image<P>) -> Image<Q>,
+    {
+        TileFn { halo, f }
+    }
+}
+
+impl<P, Q, F> TileOperation<P> for TileFn<F>
+where
+    P: Pixel,
+    Q: Pixel,
+    F: Fn(&Image<P>) -> Image<Q>,
+{
+    type Output = Q;
+
+    fn halo(&self) -> u32 {
+        self.halo
+    }
+
+    fn apply(&self, tile: &Image<P>) -> Image<Q> {
+        (self.f)(tile)
+    }
+}
+
+/// Applies [`gaussian_blur_f32`](../filter/fn.gaussian_blur_f32.html) to each tile.
+///
+/// # Examples
+/// ```
+/// use imageproc::filter::gaussian_blur_f32;
+/// use imageproc::tiling::{process_tiled, GaussianBlur};
+/// use imageproc::utils::gray_bench_image;
+///
+/// let image = gray_bench_image(100, 80);
+///
+/// assert_eq!(
+///     process_tiled(&image, 32, 32, &GaussianBlur::new(1.5)),
+///     gaussian_blur_f32(&image, 1.5)
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaussianBlur {
+    sigma: f32,
+}
+
+impl GaussianBlur {
+    /// Creates an operation which blurs tiles with the given standard deviation.
+    pub fn new(sigma: f32) -> GaussianBlur {
+        GaussianBlur { sigma }
+    }
+}
+
+impl<P> TileOperation<P> for GaussianBlur
+where
+    P: Pixel + MaybeSync + 'static,
+    P::Subpixel: ValueInto<f32> + Clamp<f32> + MaybeSend + MaybeSync,
+{
+    type Output = P;
+
+    fn halo(&self) -> u32 {
+        (2.0 * self.sigma).ceil() as u32
+    }
+
+    fn apply(&self, tile: &Image<P>) -> Image<P> {
+        gaussian_blur_f32(tile, self.sigma)
+    }
+}
+
+/// Applies [`box_filter`](../filter/fn.box_filter.html) to each tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxFilter {
+    x_radius: u32,
+    y_radius: u32,
+}
+
+impl BoxFilter {
+    /// Creates an operation which filters tiles with a box of the given radii.
+    pub fn new(x_radius: u32, y_radius: u32) -> BoxFilter {
+        BoxFilter { x_radius, y_radius }
+    }
+}
+
+impl<T> TileOperation<Luma<T>> for BoxFilter
+where
+    T: Primitive + ValueInto<f64> + Clamp<f64> + 'static,
+{
+    type Output = Luma<T>;
+
+    fn halo(&self) -> u32 {
+        self.x_radius.max(self.y_radius)
+    }
+
+    fn apply(&self, tile: &Image<Luma<T>>) -> Image<Luma<T>> {
+        box_filter(tile, self.x_radius, self.y_radius)
+    }
+}
+
+/// Applies [`median_filter`](../filter/fn.median_filter.html) to each tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MedianFilter {
+    x_radius: u32,
+    y_radius: u32,
+}
+
+impl MedianFilter {
+    /// Creates an operation which filters tiles with a window of the given radii.
+    pub fn new(x_radius: u32, y_radius: u32) -> MedianFilter {
+        MedianFilter { x_radius, y_radius }
+    }
+}
+
+impl<P> TileOperation<P> for MedianFilter
+where
+    P: Pixel<Subpixel = u8> + 'static,
+{
+    type Output = P;
+
+    fn halo(&self) -> u32 {
+        self.x_radius.max(self.y_radius)
+    }
+
+    fn apply(&self, tile: &Image<P>) -> Image<P> {
+        median_filter(tile, self.x_radius, self.y_radius)
+    }
+}
+
+/// Applies [`filter3x3`](../filter/fn.filter3x3.html) to each tile, producing an output
+/// with channel type `S`.
+///
+/// # Examples
+/// ```
+/// use image::Luma;
+/// use imageproc::definitions::Image;
+/// use imageproc::filter::filter3x3;
+/// use imageproc::gradients::HORIZONTAL_SOBEL;
+/// use imageproc::tiling::{process_tiled, Filter3x3};
+/// use imageproc::utils::gray_bench_image;
+///
+/// let image = gray_bench_image(100, 80);
+/// let sobel = Filter3x3::<i32, i16>::new(HORIZONTAL_SOBEL);
+///
+/// let expected: Image<Luma<i16>> = filter3x3(&image, &HORIZONTAL_SOBEL);
+/// assert_eq!(process_tiled(&image, 32, 32, &sobel), expected);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter3x3<K, S> {
+    kernel: [K; 9],
+    output: PhantomData<fn() -> S>,
+}
+
+impl<K, S> Filter3x3<K, S> {
+    /// Creates an operation which correlates tiles with the given row-major kernel.
+    pub fn new(kernel: [K; 9]) -> Filter3x3<K, S> {
+        Filter3x3 {
+            kernel,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<P, K, S> TileOperation<P> for Filter3x3<K, S>
+where
+    P::Subpixel: ValueInto<K> + MaybeSync,
+    S: Clamp<K> + Primitive + MaybeSend + 'static,
+    P: WithChannel<S> + MaybeSync + 'static,
+    K: Num + Copy + MaybeSync,
+{
+    type Output = ChannelMap<P, S>;
+
+    fn halo(&self) -> u32 {
+        1
+    }
+
+    fn apply(&self, tile: &Image<P>) -> Image<ChannelMap<P, S>> {
+        filter3x3(tile, &self.kernel)
+    }
+}
+
+/// A morphological operator from the [`morphology`](../morphology/index.html) module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MorphologyOp {
+    /// [`dilate`](../morphology/fn.dilate.html)
+    Dilate,
+    /// [`erode`](../morphology/fn.erode.html)
+    Erode,
+    /// [`open`](../morphology/fn.open.html)
+    Open,
+    /// [`close`](../morphology/fn.close.html)
+    Close,
+}
+
+/// Applies a morphological operator to each tile.
+///
+/// # Examples
+/// ```
+/// use imageproc::distance_transform::Norm;
+/// use imageproc::morphology::open;
+/// use imageproc::tiling::{process_tiled, Morphology, MorphologyOp};
+/// use imageproc::utils::gray_bench_image;
+///
+/// let image = gray_bench_image(100, 80);
+/// let opening = Morphology::new(MorphologyOp::Open, Norm::L1, 3);
+///
+/// assert_eq!(process_tiled(&image, 32, 32, &opening), open(&image, Norm::L1, 3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Morphology {
+    op: MorphologyOp,
+    norm: Norm,
+    k: u8,
+}
+
+impl Morphology {
+    /// Creates an operation which applies `op` to tiles with the given norm and distance.
+    pub fn new(op: MorphologyOp, norm: Norm, k: u8) -> Morphology {
+        Morphology { op, norm, k }
+    }
+}
+
+impl<T> TileOperation<Luma<T>> for Morphology
+where
+    T: Primitive + 'static,
+    Luma<T>: HasBlack + HasWhite,
+{
+    type Output = Luma<T>;
+
+    fn halo(&self) -> u32 {
+        // Opening and closing apply two operators in sequence
+        match self.op {
+            MorphologyOp::Dilate | MorphologyOp::Erode => self.k as u32,
+            MorphologyOp::Open | MorphologyOp::Close => 2 * self.k as u32,
+        }
+    }
+
+    fn apply(&self, tile: &Image<Luma<T>>) -> Image<Luma<T>> {
+        match self.op {
+            MorphologyOp::Dilate => dilate(tile, self.norm, self.k),
+            MorphologyOp::Erode => erode(tile, self.norm, self.k),
+            MorphologyOp::Open => open(tile, self.norm, self.k),
+            MorphologyOp::Close => close(tile, self.norm, self.k),
+        }
+    }
+}
+
+/// A tile of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    /// The region of the output computed from this tile.
+    pub region: Rect,
+    /// The region of the input that must be read to compute the output for `region`,
+    /// i.e. `region` expanded by the halo and clipped to the image bounds.
+    pub padded: Rect,
+}
+
+/// Splits an image of the given size into tiles of at most `tile_width` by `tile_height`
+/// pixels, in row-major order. The `region`s of the tiles are disjoint and cover the image.
+///
+/// # Panics
+/// If `tile_width` or `tile_height` is zero.
+pub fn tiles(
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    halo: u32,
+) -> impl Iterator<Item = Tile> {
+    assert!(
+        tile_width > 0 && tile_height > 0,
+        "tile_width and tile_height must be non-zero"
+    );
+    let (width, height) = (width as u64, height as u64);
+    let (tile_width, tile_height, halo) = (tile_width as u64, tile_height as u64, halo as u64);
+
+    (0..height)
+        .step_by(tile_height as usize)
+        .flat_map(move |top| {
+            (0..width).step_by(tile_width as usize).map(move |left| {
+                let right = (left + tile_width).min(width);
+                let bottom = (top + tile_height).min(height);
+                let padded_left = left.saturating_sub(halo);
+                let padded_top = top.saturating_sub(halo);
+                let padded_right = (right + halo).min(width);
+                let padded_bottom = (bottom + halo).min(height);
+                Tile {
+                    region: Rect::at(left as i32, top as i32)
+                        .of_size((right - left) as u32, (bottom - top) as u32),
+                    padded: Rect::at(padded_left as i32, padded_top as i32).of_size(
+                        (padded_right - padded_left) as u32,
+                        (padded_bottom - padded_top) as u32,
+                    ),
+                }
+            })
+        })
+}
+
+/// Applies an operation to an image of the given size one tile at a time, without
+/// requiring the whole image to be in memory.
+///
+/// For each tile, `read` is called with the region of the input image to read, which
+/// includes the operation's halo, and must return the pixels in that region. `write` is then
+/// called with the region of the output that has been computed and the output pixels for
+/// that region.
+///
+/// # Panics
+/// If `tile_width` or `tile_height` is zero, or if `read` or the operation return an image
+/// of the wrong size.
+///
+/// # Examples
+/// ```
+/// use image::{GenericImage, GenericImageView, GrayImage};
+/// use imageproc::filter::median_filter;
+/// use imageproc::tiling::{process_tiles, TileFn};
+/// use imageproc::utils::gray_bench_image;
+///
+/// // In practice the input might be read from, and the output written to, a tiled file format.
+/// let input = gray_bench_image(300, 200);
+/// let mut output = GrayImage::new(300, 200);
+///
+/// let median = TileFn::new(2, |tile| median_filter(tile, 2, 2));
+/// process_tiles(
+///     300,
+///     200,
+///     64,
+///     64,
+///     &median,
+///     |r| {
+///         let (x, y) = (r.left() as u32, r.top() as u32);
+///         input.view(x, y, r.width(), r.height()).to_image()
+///     },
+///     |r, tile| {
+///         let (x, y) = (r.left() as u32, r.top() as u32);
+///         output.copy_from(tile, x, y).unwrap();
+///     },
+/// );
+///
+/// assert_eq!(output, median_filter(&input, 2, 2));
+/// ```
+pub fn process_tiles<P, O, R, W>(
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    op: &O,
+    mut read: R,
+    mut write: W,
+) where
+    P: Pixel + 'static,
+    O: TileOperation<P>,
+    O::Output: 'static,
+    R: FnMut(Rect) -> Image<P>,
+    W: FnMut(Rect, &Image<O::Output>),
+{
+    for tile in tiles(width, height, tile_width, tile_height, op.halo()) {
+        let input = read(tile.padded);
+        assert_eq!(
+            input.dimensions(),
+            (tile.padded.width(), tile.padded.height()),
+            "read returned an image of the wrong size"
+        );
+        let output = apply_to_tile(op, &input, &tile);
+        write(tile.region, &output);
+    }
+}
+
+/// Applies an operation to an image one tile at a time, and stitches the results
+/// together into an image of the same size as the input.
+///
+/// The result is the same as applying the operation to the whole image, provided its
+/// halo is large enough. Tiles are processed in parallel if the `rayon` feature is enabled.
+///
+/// See the [`TileFn`](struct.TileFn.html) documentation for an example.
+///
+/// # Panics
+/// If `tile_width` or `tile_height` is zero, or if the operation returns an image of the
+/// wrong size.
+pub fn process_tiled<P, O>(
+    image: &Image<P>,
+    tile_width: u32,
+    tile_height: u32,
+    op: &O,
+) -> Image<O::Output>
+where
+    P: Pixel + MaybeSync + 'static,
+    P::Subpixel: MaybeSync,
+    O: TileOperation<P> + MaybeSync,
+    O::Output: MaybeSend + 'static,
+    <O::Output as Pixel>::Subpixel: MaybeSend,
+{
+    let (width, height) = image.dimensions();
+    let tiles: Vec<Tile> = tiles(width, height, tile_width, tile_height, op.halo()).collect();
+
+    let process = |tile: &Tile| {
+        let input = crop(image, tile.padded);
+        (tile.region, apply_to_tile(op, &input, tile))
+    };
+
+    #[cfg(feature = "rayon")]
+    let outputs: Vec<_> = tiles.par_iter().map(process).collect();
+    #[cfg(not(feature = "rayon"))]
+    let outputs: Vec<_> = tiles.iter().map(process).collect();
+
+    let mut out = Image::new(width, height);
+    for (region, output) in &outputs {
+        paste(&mut out, output, *region);
+    }
+    out
+}
+
+// Applies op to the padded input for a tile, and returns the output for tile.region.
+fn apply_to_tile<P, O>(op: &O, input: &Image<P>, tile: &Tile) -> Image<O::Output>
+where
+    P: Pixel + 'static,
+    O: TileOperation<P>,
+    O::Output: 'static,
+{
+    let output = op.apply(input);
+    assert_eq!(
+        output.dimensions(),
+        input.dimensions(),
+        "operation returned an image of the wrong size"
+    );
+    let r = tile.region;
+    let left = r.left() - tile.padded.left();
+    let top = r.top() - tile.padded.top();
+    crop(&output, Rect::at(left, top).of_size(r.width(), r.height()))
+}
+
+// Copies the pixels in rect, which must lie within image.
+fn crop<P: Pixel + 'static>(image: &Image<P>, rect: Rect) -> Image<P> {
+    let channels = P::CHANNEL_COUNT as usize;
+    let pitch = image.width() as usize * channels;
+    let len = rect.width() as usize * channels;
+    let left = rect.left() as usize * channels;
+    let mut data = Vec::with_capacity(len * rect.height() as usize);
+    for y in rect.top()..=rect.bottom() {
+        let start = y as usize * pitch + left;
+        data.extend_from_slice(&image.as_raw()[start..start + len]);
+    }
+    Image::from_raw(rect.width(), rect.height(), data).unwrap()
+}
+
+// Copies tile into image, with its top left corner at the top left of rect.
+fn paste<P: Pixel + 'static>(image: &mut Image<P>, tile: &Image<P>, rect: Rect) {
+    let channels = P::CHANNEL_COUNT as usize;
+    let pitch = image.width() as usize * channels;
+    let len = rect.width() as usize * channels;
+    let left = rect.left() as usize * channels;
+    for (row, y) in tile.chunks(len).zip(rect.top()..=rect.bottom()) {
+        let start = y as usize * pitch + left;
+        (**image)[start..start + len].copy_from_slice(row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{gray_bench_image, rgb_bench_image};
+    use image::{GrayImage, Luma, Rgb};
+    use test::{black_box, Bencher};
+
+    #[test]
+    fn test_tiles_cover_image() {
+        let (width, height) = (23, 17);
+        let mut counts = vec![0; (width * height) as usize];
+        for tile in tiles(width, height, 5, 4, 2) {
+            let (r, p) = (tile.region, tile.padded);
+            assert!(r.width() <= 5 && r.height() <= 4);
+            assert_eq!(p.intersect(r), Some(r));
+            assert_eq!(p.left(), (r.left() - 2).max(0));
+            assert_eq!(p.bottom(), (r.bottom() + 2).min(height as i32 - 1));
+            for y in r.top()..=r.bottom() {
+                for x in r.left()..=r.right() {
+                    counts[(y * width as i32 + x) as usize] += 1;
+                }
+            }
+        }
+        assert!(counts.iter().all(|c| *c == 1));
+    }
+
+    #[test]
+    fn test_tiles_of_empty_image() {
+        assert_eq!(tiles(0, 10, 4, 4, 1).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tiles_rejects_zero_tile_size() {
+        let _ = tiles(10, 10, 0, 4, 1);
+    }
+
+    #[test]
+    fn test_process_tiled_matches_whole_image() {
+        let image = gray_bench_image(37, 29);
+
+        let sharpen = TileFn::new(1, |t: &GrayImage| -> Image<Luma<i16>> {
+            filter3x3(t, &[0i32, -1, 0, -1, 5, -1, 0, -1, 0])
+        });
+        assert_eq!(process_tiled(&image, 8, 5, &sharpen), sharpen.apply(&image));
+
+        let median = TileFn::new(3, |t| median_filter(t, 3, 2));
+        assert_eq!(
+            process_tiled(&image, 10, 10, &median),
+            median_filter(&image, 3, 2)
+        );
+
+        let dilation = TileFn::new(2, |t| dilate(t, Norm::LInf, 2));
+        assert_eq!(
+            process_tiled(&image, 7, 9, &dilation),
+            dilate(&image, Norm::LInf, 2)
+        );
+
+        let rgb = rgb_bench_image(21, 13);
+        let blur = TileFn::new(4, |t| gaussian_blur_f32(t, 2.0));
+        assert_eq!(
+            process_tiled(&rgb, 6, 6, &blur),
+            gaussian_blur_f32(&rgb, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_filter_operations_match_whole_image() {
+        let image = gray_bench_image(41, 33);
+        let rgb = rgb_bench_image(27, 19);
+
+        let blur = GaussianBlur::new(1.7);
+        assert_eq!(TileOperation::<Rgb<u8>>::halo(&blur), 4);
+        assert_eq!(
+            process_tiled(&rgb, 7, 6, &blur),
+            gaussian_blur_f32(&rgb, 1.7)
+        );
+
+        let boxes = BoxFilter::new(2, 5);
+        assert_eq!(TileOperation::<Luma<u8>>::halo(&boxes), 5);
+        assert_eq!(
+            process_tiled(&image, 9, 8, &boxes),
+            box_filter(&image, 2, 5)
+        );
+
+        let median = MedianFilter::new(3, 1);
+        assert_eq!(TileOperation::<Luma<u8>>::halo(&median), 3);
+        assert_eq!(
+            process_tiled(&rgb, 5, 5, &median),
+            median_filter(&rgb, 3, 1)
+        );
+
+        let laplacian = Filter3x3::<i32, i16>::new([0, 1, 0, 1, -4, 1, 0, 1, 0]);
+        let expected: Image<Luma<i16>> = filter3x3(&image, &[0i32, 1, 0, 1, -4, 1, 0, 1, 0]);
+        assert_eq!(process_tiled(&image, 6, 11, &laplacian), expected);
+    }
+
+    #[test]
+    fn test_morphology_operations_match_whole_image() {
+        let image = crate::map::map_colors(&gray_bench_image(41, 33), |p| {
+            Luma([if p[0] > 200 { 255u8 } else { 0 }])
+        });
+        for &norm in &[Norm::L1, Norm::LInf, Norm::Chamfer34] {
+            for &op in &[
+                MorphologyOp::Dilate,
+                MorphologyOp::Erode,
+                MorphologyOp::Open,
+                MorphologyOp::Close,
+            ] {
+                let morph = Morphology::new(op, norm, 2);
+                assert_eq!(
+                    process_tiled(&image, 8, 7, &morph),
+                    morph.apply(&image),
+                    "{:?} {:?}",
+                    op,
+                    norm
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_process_tiles_reads_padded_regions() {
+        let image = gray_bench_image(20, 12);
+        let mut output = GrayImage::new(20, 12);
+        let mut reads = Vec::new();
+
+        let invert = TileFn::new(1, |t: &GrayImage| {
+            GrayImage::from_fn(t.width(), t.height(), |x, y| {
+                Luma([255 - t.get_pixel(x, y)[0]])
+            })
+        });
+        process_tiles(
+            20,
+            12,
+            10,
+            6,
+            &invert,
+            |r| {
+                reads.push(r);
+                crop(&image, r)
+            },
+            |r, t| paste(&mut output, t, r),
+        );
+
+        assert_eq!(
+            reads,
+            vec![
+                Rect::at(0, 0).of_size(11, 7),
+                Rect::at(9, 0).of_size(11, 7),
+                Rect::at(0, 5).of_size(11, 7),
+                Rect::at(9, 5).of_size(11, 7),
+            ]
+        );
+        assert_eq!(output, invert.apply(&image));
+    }
+
+    #[bench]
+    fn bench_process_tiled_gaussian_blur(b: &mut Bencher) {
+        let image = gray_bench_image(500, 500);
+        let blur = TileFn::new(6, |t| gaussian_blur_f32(t, 3.0));
+        b.iter(|| {
+            let blurred = process_tiled(&image, 128, 128, &blur);
+            black_box(blurred);
+        });
+    }
+}
@@ -50,6 +50,13 @@ impl DisjointSetForest {
         j
     }
 
+    /// Returns the number of elements in the tree containing i.
+    /// Needs mutable reference to self for path compression.
+    pub fn tree_size(&mut self, i: usize) -> usize {
+        let root = self.root(i);
+        self.tree_size[root]
+    }
+
     /// Returns true if i and j are in the same tree.
     /// Need mutable reference to self for path compression.
     pub fn find(&mut self, i: usize, j: usize) -> bool {
@@ -130,6 +137,16 @@ mod tests {
         assert_eq!(forest.trees(), vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
     }
 
+    #[test]
+    fn test_tree_size() {
+        let mut forest = DisjointSetForest::new(5);
+        forest.union(0, 3);
+        forest.union(3, 4);
+        assert_eq!(forest.tree_size(4), 3);
+        assert_eq!(forest.tree_size(0), 3);
+        assert_eq!(forest.tree_size(1), 1);
+    }
+
     #[test]
     fn test_union_find_sequence() {
         let mut forest = DisjointSetForest::new(6);
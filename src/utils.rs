@@ -1,8 +1,6 @@
 //! Utils for testing and debugging.
 
-use image::{
-    open, DynamicImage, GenericImage, GenericImageView, GrayImage, Luma, Pixel, Rgb, RgbImage,
-};
+use image::{open, DynamicImage, GenericImageView, GrayImage, Luma, Pixel, Rgb, RgbImage};
 
 use itertools::Itertools;
 use std::cmp::{max, min};
@@ -325,8 +323,8 @@ pub fn pixel_diff_summary<I, J, P>(actual: &I, expected: &J) -> Option<String>
 where
     P: Pixel + PartialEq,
     P::Subpixel: fmt::Debug,
-    I: GenericImage<Pixel = P>,
-    J: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
 {
     significant_pixel_diff_summary(actual, expected, |p, q| p != q)
 }
@@ -342,8 +340,8 @@ pub fn significant_pixel_diff_summary<I, J, F, P>(
 where
     P: Pixel,
     P::Subpixel: fmt::Debug,
-    I: GenericImage<Pixel = P>,
-    J: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
     F: Fn((u32, u32, I::Pixel), (u32, u32, J::Pixel)) -> bool,
 {
     if actual.dimensions() != expected.dimensions() {
@@ -436,8 +434,8 @@ macro_rules! assert_dimensions_match {
 pub fn pixel_diffs<I, J, F, P>(actual: &I, expected: &J, is_diff: F) -> Vec<Diff<I::Pixel>>
 where
     P: Pixel,
-    I: GenericImage<Pixel = P>,
-    J: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
     F: Fn((u32, u32, I::Pixel), (u32, u32, J::Pixel)) -> bool,
 {
     if is_empty(actual) || is_empty(expected) {
@@ -462,7 +460,7 @@ where
         .collect::<Vec<_>>()
 }
 
-fn is_empty<I: GenericImage>(image: &I) -> bool {
+fn is_empty<I: GenericImageView>(image: &I) -> bool {
     image.width() == 0 || image.height() == 0
 }
 
@@ -483,8 +481,8 @@ pub fn describe_pixel_diffs<I, J, P>(actual: &I, expected: &J, diffs: &[Diff<P>]
 where
     P: Pixel,
     P::Subpixel: fmt::Debug,
-    I: GenericImage<Pixel = P>,
-    J: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
+    J: GenericImageView<Pixel = P>,
 {
     let mut err = "pixels do not match.\n".to_owned();
 
@@ -566,7 +564,7 @@ fn render_image_region<I, P, C>(
 where
     P: Pixel,
     P::Subpixel: fmt::Debug,
-    I: GenericImage<Pixel = P>,
+    I: GenericImageView<Pixel = P>,
     C: Fn(u32, u32) -> Color,
 {
     let mut rendered = String::new();